@@ -1,11 +1,1588 @@
 //! clap [Args](clap::Args) for Dev testnet configuration
 
-use clap::Args;
+use clap::{builder::RangedU64ValueParser, Args};
+use humantime::parse_duration;
+use reth_clayer::{
+    BlockMetadata, Compression, PbftConfig, CLAYER_PROTOCOL_VERSION,
+    DEFAULT_MAX_CONCURRENT_REQUESTS, DEFAULT_SEEN_CACHE_SIZE, DEFAULT_SEEN_CACHE_TTL,
+    DEFAULT_TOKEN_REFRESH_INTERVAL, MAX_TICKER_JITTER,
+};
+use reth_primitives::{Address, NodeRecord};
+use reth_provider::providers::DEFAULT_DB_WRITE_RETRIES;
+use reth_tracing::{tracing::level_filters::LevelFilter, TargetLevel};
+use std::{path::PathBuf, time::Duration};
+use url::Url;
+
+/// Default size in megabytes after which the consensus message log is rotated.
+const DEFAULT_MESSAGE_LOG_MAX_SIZE_MB: u64 = 100;
+
+/// Target of the logs of the consensus engine.
+pub const CONSENSUS_LOG_TARGET: &str = "consensus::cl";
+
 /// Parameters for Dev testnet configuration
-#[derive(Debug, Args, PartialEq, Default, Clone, Copy)]
+#[derive(Debug, Args, PartialEq, Default, Clone)]
 #[clap(next_help_heading = "Clayer")]
 pub struct ClayerArgs {
     /// This is a temporary parameter used to configure the role of the consensus node. it will be deleted later
     #[arg(long = "clayer.mine", value_name = "CAN_MINE", default_value_t = false)]
     pub mine: bool,
+
+    /// Record every inbound and outbound consensus message to `<DATADIR>/clayer/messages.log`
+    #[arg(long = "clayer.message-log", default_value_t = false)]
+    pub message_log: bool,
+
+    /// Size in megabytes after which the consensus message log is rotated [default: 100]
+    #[arg(long = "clayer.message-log-max-size", value_name = "MB", requires = "message_log")]
+    pub message_log_max_size: Option<u64>,
+
+    /// Number of committed blocks the pipeline may lag behind before the primary slows down its
+    /// proposals, 0 disables the backpressure [default: 64]
+    #[arg(long = "clayer.max-pipeline-lag", value_name = "BLOCKS")]
+    pub max_pipeline_lag: Option<u64>,
+
+    /// Number of publish ticks queued while the node is busy before the oldest are dropped as
+    /// stale [default: 16]
+    #[arg(long = "clayer.max-queued-ticks", value_name = "TICKS")]
+    pub max_queued_ticks: Option<usize>,
+
+    /// Maximum number of faulty validators to tolerate, a quorum is `2f + 1` out of at least
+    /// `3f + 1` validators [default: (n - 1) / 3]
+    #[arg(
+        long = "clayer.faults",
+        value_name = "F",
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..)
+    )]
+    pub faults: Option<u64>,
+
+    /// How long consensus Commit messages are batched before they are processed, a batch that
+    /// completes a quorum is processed immediately. 0s disables batching [default: 50ms]
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.commit-aggregation-window 100ms
+    #[arg(
+        long = "clayer.commit-aggregation-window",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub commit_aggregation_window: Option<Duration>,
+
+    /// Shift every attempt to publish a block randomly by up to this percentage of the publishing
+    /// interval, so validators don't all try at the same time. The attempts keep the average
+    /// interval, at most 50 [default: 0]
+    #[arg(
+        long = "clayer.publishing-jitter",
+        value_name = "PERCENT",
+        value_parser = RangedU64ValueParser::<u8>::new().range(0..=MAX_TICKER_JITTER as u64)
+    )]
+    pub publishing_jitter: Option<u8>,
+
+    /// Do not propose blocks without transactions, the proposal is retried until the execution
+    /// layer built a payload with transactions
+    #[arg(long = "clayer.skip-empty-blocks", default_value_t = false)]
+    pub skip_empty_blocks: bool,
+
+    /// Do not ask the execution layer to build a payload while the transaction pool is empty,
+    /// only keep its head current with plain forkchoice updates. Requires
+    /// `--clayer.skip-empty-blocks`
+    #[arg(
+        long = "clayer.skip-idle-payload-builds",
+        default_value_t = false,
+        requires = "skip_empty_blocks"
+    )]
+    pub skip_idle_payload_builds: bool,
+
+    /// Follow consensus and track the committed chain without signing messages or proposing
+    /// blocks
+    #[arg(long = "clayer.observer", default_value_t = false)]
+    pub observer: bool,
+
+    /// How far the timestamp of a proposed block may be ahead of the local clock before the
+    /// block is rejected. 0s disables the check [default: 15s]
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.max-timestamp-skew 30s
+    #[arg(
+        long = "clayer.max-timestamp-skew",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub max_timestamp_skew: Option<Duration>,
+
+    /// How long a JWT token for the engine API is reused before a new one is minted, capped at
+    /// 50s to stay within the 60s the execution layer accepts. 0s mints a token for every
+    /// request [default: 30s]
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.jwt-refresh-interval 10s
+    #[arg(
+        long = "clayer.jwt-refresh-interval",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub jwt_refresh_interval: Option<Duration>,
+
+    /// Talk HTTP/2 to the engine API, multiplexing concurrent calls over a single connection.
+    /// The execution layer must accept HTTP/2 without an upgrade from HTTP/1.1
+    #[arg(long = "clayer.engine-http2", default_value_t = false)]
+    pub engine_http2: bool,
+
+    /// Call the engine API of this node directly instead of over the auth server, without the
+    /// network and the JWT. The auth server keeps serving other consensus clients
+    #[arg(long = "clayer.in-process-engine", default_value_t = false)]
+    pub in_process_engine: bool,
+
+    /// Maximum number of engine API requests in flight at the same time, further requests are
+    /// queued [default: 32]
+    #[arg(
+        long = "clayer.engine-max-concurrent-requests",
+        value_name = "REQUESTS",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub engine_max_concurrent_requests: Option<usize>,
+
+    /// Keep running if the execution layer fails the startup self-test, by default the node
+    /// shuts down
+    #[arg(long = "clayer.ignore-failed-self-test", default_value_t = false)]
+    pub ignore_failed_self_test: bool,
+
+    /// Number of retries of a consensus database write that failed with a transient conflict,
+    /// e.g. a busy database, 0 fails on the first conflict [default: 3]
+    #[arg(long = "clayer.db-write-retries", value_name = "RETRIES")]
+    pub db_write_retries: Option<u32>,
+
+    /// Number of most recent blocks whose committed execution payloads are kept for replay and
+    /// audit, 0 disables storing them [default: 10000]
+    #[arg(long = "clayer.payload-retention", value_name = "BLOCKS")]
+    pub payload_retention: Option<u64>,
+
+    /// How often a call to the execution layer is retried with exponential backoff while it
+    /// reports the block as not ready yet, 0 disables retrying [default: 3]
+    #[arg(long = "clayer.not-ready-retries", value_name = "RETRIES")]
+    pub not_ready_retries: Option<u32>,
+
+    /// How often the head of the execution layer is compared with the last committed block. A
+    /// diverged execution layer is realigned, the node shuts down if the divergence persists. 0s
+    /// disables the check [default: 30s]
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.head-check-interval 1m
+    #[arg(
+        long = "clayer.head-check-interval",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub head_check_interval: Option<Duration>,
+
+    /// Age after which the primary requests a new payload id from the execution layer instead of
+    /// fetching a payload the execution layer may have discarded. 0s always uses the cached
+    /// payload id [default: 12s]
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.payload-id-max-age 6s
+    #[arg(
+        long = "clayer.payload-id-max-age",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub payload_id_max_age: Option<Duration>,
+
+    /// Debug check: fetch the head of the execution layer after every commit and log an error if
+    /// it is not the committed block
+    #[arg(long = "clayer.check-head-after-commit", default_value_t = false)]
+    pub check_head_after_commit: bool,
+
+    /// Only consider a block committed once the execution layer reports it as its finalized
+    /// block, polled with the not-ready retries
+    #[arg(long = "clayer.require-el-finalized", default_value_t = false)]
+    pub require_el_finalized: bool,
+
+    /// Rotate the primary round-robin every this many committed blocks, independent of view
+    /// changes caused by faults. 0 only changes the primary on faults [default: 20]
+    #[arg(long = "clayer.primary-rotation-blocks", value_name = "BLOCKS")]
+    pub primary_rotation_blocks: Option<u64>,
+
+    /// Sign consensus messages through the remote signer at this URL instead of with the node
+    /// key, e.g. a service in front of an HSM. The signer must hold the node key
+    #[arg(long = "clayer.remote-signer", value_name = "URL")]
+    pub remote_signer: Option<Url>,
+
+    /// Only propose blocks while enough validators are connected to reach quorum
+    #[arg(long = "clayer.require-connected-quorum", default_value_t = false)]
+    pub require_connected_quorum: bool,
+
+    /// Record the capabilities and client version of the execution layer in the data directory
+    /// and warn on startup if they changed since the last run
+    #[arg(long = "clayer.track-el-identity", default_value_t = false)]
+    pub track_el_identity: bool,
+
+    /// Execute every payload with the local state before it is voted on or committed and reject
+    /// it if its state root differs from the computed one
+    #[arg(long = "clayer.verify-state-root", default_value_t = false)]
+    pub verify_state_root: bool,
+
+    /// Allow committing a validated block without quorum through the `clayer_forceCommit` RPC.
+    /// Only meant to recover a stuck network, e.g. a testnet
+    #[arg(long = "clayer.allow-force-commit", default_value_t = false)]
+    pub allow_force_commit: bool,
+
+    /// Trust the transaction senders of blocks committed by the consensus layer: they are not
+    /// recovered again for blocks the execution layer already knows and their signatures are not
+    /// checked for malleability. Only safe because every imported block was committed by a quorum
+    /// of validators, rejected with `--dev`
+    #[arg(long = "clayer.trust-committed-senders", default_value_t = false)]
+    pub trust_committed_senders: bool,
+
+    /// Drop consensus peers that don't send a hello or a consensus message of this network
+    /// within this time after connecting. Unbounded if not set
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.handshake-timeout 30s
+    #[arg(
+        long = "clayer.handshake-timeout",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub handshake_timeout: Option<Duration>,
+
+    /// How often the consensus peers are pinged to detect peers that stopped responding while
+    /// still connected. A dead primary triggers a view change right away. Disabled if not set
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.heartbeat-interval 1s
+    #[arg(
+        long = "clayer.heartbeat-interval",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub heartbeat_interval: Option<Duration>,
+
+    /// How long a consensus peer may not be heard from before it is marked dead
+    /// [default: 3 heartbeat intervals]
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.heartbeat-timeout 5s
+    #[arg(
+        long = "clayer.heartbeat-timeout",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        requires = "heartbeat_interval",
+        verbatim_doc_comment
+    )]
+    pub heartbeat_timeout: Option<Duration>,
+
+    /// How long to wait before reconnecting to a disconnected validator. Validators are needed
+    /// for quorum, so unlike other peers they are retried at this interval without a limit on
+    /// the attempts [default: 1s]
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.validator-reconnect-interval 500ms
+    #[arg(
+        long = "clayer.validator-reconnect-interval",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub validator_reconnect_interval: Option<Duration>,
+
+    /// Comma separated enode URLs of the bootstrap peers of the consensus overlay. They are
+    /// dialed at launch and reconnected like validators, independently of the eth bootnodes and
+    /// trusted peers
+    #[arg(long = "clayer.bootstrap-peers", value_delimiter = ',')]
+    pub bootstrap_peers: Vec<NodeRecord>,
+
+    /// Change the validator set at the end of every epoch by the `ValidatorAdded` and
+    /// `ValidatorRemoved` events of this contract, instead of querying the election contract
+    /// after every block
+    #[arg(long = "clayer.validator-contract", value_name = "ADDRESS")]
+    pub validator_contract: Option<Address>,
+
+    /// Number of committed blocks of an epoch. The validator set changes and the epoch boundary
+    /// events are emitted at the multiples of it [default: 100]
+    #[arg(
+        long = "clayer.epoch-length",
+        value_name = "BLOCKS",
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..)
+    )]
+    pub epoch_length: Option<u64>,
+
+    /// Compress the consensus messages to validators that support it, either `snappy` or
+    /// `zstd`. Messages to other peers are sent uncompressed
+    #[arg(long = "clayer.compression", value_name = "ALGORITHM")]
+    pub compression: Option<Compression>,
+
+    /// Version of the consensus messages this node sends. Keep it at 1 while validators of older
+    /// releases remain in the network, they can't decode the relay fields added in version 2.
+    /// Messages of both versions are accepted [default: 2]
+    #[arg(
+        long = "clayer.protocol-version",
+        value_name = "VERSION",
+        value_parser = RangedU64ValueParser::<u8>::new().range(1..=CLAYER_PROTOCOL_VERSION as u64)
+    )]
+    pub protocol_version: Option<u8>,
+
+    /// Drop the copies of consensus messages relayed by other peers, remembering up to this many
+    /// received messages. Once full, the oldest messages are forgotten first [default: 10000]
+    #[arg(
+        long = "clayer.seen-cache-size",
+        value_name = "MESSAGES",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub seen_cache_size: Option<usize>,
+
+    /// How long a received consensus message is remembered to drop the copies relayed by other
+    /// peers [default: 10s]
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.seen-cache-ttl 30s
+    #[arg(
+        long = "clayer.seen-cache-ttl",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub seen_cache_ttl: Option<Duration>,
+
+    /// Stop proposing and only follow the network after this many consecutive proposals of this
+    /// node failed, e.g. because the execution layer failed to build the block or the view changed
+    /// before quorum. Disabled if not set
+    #[arg(
+        long = "clayer.safe-mode-failures",
+        value_name = "FAILURES",
+        value_parser = RangedU64ValueParser::<u32>::new().range(1..)
+    )]
+    pub safe_mode_failures: Option<u32>,
+
+    /// How long safe mode lasts before proposing resumes automatically. 0s only leaves safe mode
+    /// through `clayer_exitSafeMode` [default: 10m]
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.safe-mode-duration 30m
+    #[arg(
+        long = "clayer.safe-mode-duration",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub safe_mode_duration: Option<Duration>,
+
+    /// Log a warning and count a breach for every block whose commit latency, from its
+    /// PrePrepare to its commit, exceeds this duration. Disabled if not set
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.commit-latency-sla 3s
+    #[arg(
+        long = "clayer.commit-latency-sla",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub commit_latency_sla: Option<Duration>,
+
+    /// Refuse to commit a block that would unwind more than this many blocks of the execution
+    /// layer, a safety measure against deep reorgs. Disabled if not set
+    #[arg(long = "clayer.max-reorg-depth", value_name = "BLOCKS")]
+    pub max_reorg_depth: Option<u64>,
+
+    /// How long the consensus engine may take to save its state on shutdown before it is
+    /// abandoned and the node exits anyway [default: 10s]
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.shutdown-timeout 30s
+    #[arg(
+        long = "clayer.shutdown-timeout",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub shutdown_timeout: Option<Duration>,
+
+    /// Weight, in prepare votes, added to the block of the primary of the current view when it
+    /// competes with late blocks of earlier views for the same height. Disabled if not set
+    #[arg(long = "clayer.proposer-boost", value_name = "VOTES")]
+    pub proposer_boost: Option<u64>,
+
+    /// Maximum size in bytes of the encoded payload of a proposed block. Larger payloads built by
+    /// the execution layer are not proposed. Unbounded if not set
+    #[arg(long = "clayer.max-block-bytes", value_name = "BYTES")]
+    pub max_block_bytes: Option<usize>,
+
+    /// Add the per peer consensus message counts to the metrics at most once per interval instead
+    /// of on every message, reducing contention under heavy consensus load. The metrics lag
+    /// behind by up to the interval. Disabled if not set
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.metrics-flush-interval 1s
+    #[arg(
+        long = "clayer.metrics-flush-interval",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub metrics_flush_interval: Option<Duration>,
+
+    /// How long the node waits for the consensus layer to be ready before it reports being started
+    /// anyway. Consensus is ready once the execution layer passed the startup self-test and
+    /// enough validators are connected to reach quorum. Waits indefinitely if not set
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.readiness-timeout 5m
+    #[arg(
+        long = "clayer.readiness-timeout",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub readiness_timeout: Option<Duration>,
+
+    /// Record a randao mix for every committed block, the hash of the mix of its parent and its
+    /// block hash, queryable through `clayer_randaoMix` as a source of verifiable randomness
+    #[arg(long = "clayer.randao", default_value_t = false)]
+    pub randao: bool,
+
+    /// Directory of the consensus database, the consensus numbers, seals, committed payloads and
+    /// view changes. Defaults to the database of the execution layer in the data dir
+    #[arg(long = "clayer.state-dir", value_name = "PATH")]
+    pub state_dir: Option<PathBuf>,
+
+    /// Stop proposing once a consensus message signed by the key of this node is received that
+    /// it did not send, i.e. another node runs with the same validator key. The alert is logged
+    /// either way
+    #[arg(long = "clayer.halt-on-duplicate-identity", default_value_t = false)]
+    pub halt_on_duplicate_identity: bool,
+
+    /// Force a view change once a view lasted this long, so a primary does not hold its role
+    /// indefinitely in a quiet network. Disabled if not set
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --clayer.max-view-lifetime 1h
+    #[arg(
+        long = "clayer.max-view-lifetime",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub max_view_lifetime: Option<Duration>,
+
+    /// Reject proposed blocks whose gas limit is out of the bounds of EIP-1559, moving by 1/1024
+    /// or more of the gas limit of their parent
+    #[arg(long = "clayer.check-gas-limit", default_value_t = false)]
+    pub check_gas_limit: bool,
+
+    /// Sign the version and the commit of this node and attach them to the blocks it proposes,
+    /// so validators can tell which software proposed a block. Validators running a version
+    /// without block metadata can't decode such proposals
+    #[arg(long = "clayer.block-metadata", default_value_t = false)]
+    pub block_metadata: bool,
+
+    /// Number of the most recent forkchoice updates sent to the execution layer kept for
+    /// `clayer_forkchoiceHistory`, 0 keeps none [default: 64]
+    #[arg(long = "clayer.forkchoice-history-size", value_name = "UPDATES")]
+    pub forkchoice_history_size: Option<usize>,
+
+    /// Level of the consensus logs, independent of the verbosity of all other logs. At runtime
+    /// `SIGUSR1` raises and `SIGUSR2` lowers it by one level
+    #[arg(long = "clayer.log-level", value_name = "LEVEL")]
+    pub log_level: Option<LevelFilter>,
+}
+
+impl ClayerArgs {
+    /// Returns the size in bytes after which the consensus message log is rotated.
+    pub fn message_log_max_size(&self) -> u64 {
+        self.message_log_max_size.unwrap_or(DEFAULT_MESSAGE_LOG_MAX_SIZE_MB) * 1024 * 1024
+    }
+
+    /// Returns how long a JWT token for the engine API is reused.
+    pub fn jwt_refresh_interval(&self) -> Duration {
+        self.jwt_refresh_interval.unwrap_or(DEFAULT_TOKEN_REFRESH_INTERVAL)
+    }
+
+    /// Returns how many engine API requests are sent at the same time.
+    pub fn engine_max_concurrent_requests(&self) -> usize {
+        self.engine_max_concurrent_requests.unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS)
+    }
+
+    /// Returns how often a consensus database write is retried after a transient conflict.
+    pub fn db_write_retries(&self) -> u32 {
+        self.db_write_retries.unwrap_or(DEFAULT_DB_WRITE_RETRIES)
+    }
+
+    /// Returns how long a consensus peer may be silent before it is marked dead, if the heartbeat
+    /// is enabled.
+    pub fn heartbeat_timeout(&self) -> Option<Duration> {
+        let interval = self.heartbeat_interval?;
+        Some(self.heartbeat_timeout.unwrap_or(interval * 3))
+    }
+
+    /// Returns the version of the consensus messages this node sends.
+    pub fn protocol_version(&self) -> u8 {
+        self.protocol_version.unwrap_or(CLAYER_PROTOCOL_VERSION)
+    }
+
+    /// Returns how many received consensus messages are remembered to drop their copies.
+    pub fn seen_cache_size(&self) -> usize {
+        self.seen_cache_size.unwrap_or(DEFAULT_SEEN_CACHE_SIZE)
+    }
+
+    /// Returns how long a received consensus message is remembered to drop its copies.
+    pub fn seen_cache_ttl(&self) -> Duration {
+        self.seen_cache_ttl.unwrap_or(DEFAULT_SEEN_CACHE_TTL)
+    }
+
+    /// Returns the level of the consensus logs if it is set independently of the verbosity.
+    pub fn consensus_log_level(&self) -> Option<TargetLevel> {
+        self.log_level.map(|level| TargetLevel::new(CONSENSUS_LOG_TARGET, level))
+    }
+
+    /// Returns the PBFT configuration with the overrides of these arguments applied.
+    pub fn pbft_config(&self) -> PbftConfig {
+        let mut config = PbftConfig::default();
+        if let Some(max_pipeline_lag) = self.max_pipeline_lag {
+            config.max_pipeline_lag = max_pipeline_lag;
+        }
+        if let Some(max_queued_ticks) = self.max_queued_ticks {
+            config.max_queued_ticks = max_queued_ticks;
+        }
+        config.faults = self.faults;
+        if let Some(window) = self.commit_aggregation_window {
+            config.commit_aggregation_window = window;
+        }
+        if let Some(publishing_jitter) = self.publishing_jitter {
+            config.block_publishing_jitter = publishing_jitter;
+        }
+        config.cancel_empty_payloads = self.skip_empty_blocks;
+        config.observer = self.observer;
+        if let Some(max_timestamp_skew) = self.max_timestamp_skew {
+            config.max_timestamp_skew = max_timestamp_skew;
+        }
+        config.abort_on_failed_self_test = !self.ignore_failed_self_test;
+        if let Some(payload_retention) = self.payload_retention {
+            config.payload_retention = payload_retention;
+        }
+        if let Some(not_ready_retries) = self.not_ready_retries {
+            config.not_ready_retries = not_ready_retries;
+        }
+        if let Some(head_check_interval) = self.head_check_interval {
+            config.head_check_interval = head_check_interval;
+        }
+        if let Some(payload_id_max_age) = self.payload_id_max_age {
+            config.payload_id_max_age = payload_id_max_age;
+        }
+        config.check_head_after_commit = self.check_head_after_commit;
+        config.require_el_finalized = self.require_el_finalized;
+        if let Some(primary_rotation_blocks) = self.primary_rotation_blocks {
+            config.forced_view_change_interval = primary_rotation_blocks;
+        }
+        config.require_connected_quorum = self.require_connected_quorum;
+        config.allow_force_commit = self.allow_force_commit;
+        config.validator_contract = self.validator_contract;
+        if let Some(epoch_length) = self.epoch_length {
+            config.epoch_length = epoch_length;
+        }
+        if let Some(safe_mode_failures) = self.safe_mode_failures {
+            config.safe_mode_failures = safe_mode_failures;
+        }
+        if let Some(safe_mode_duration) = self.safe_mode_duration {
+            config.safe_mode_duration = safe_mode_duration;
+        }
+        config.commit_latency_sla = self.commit_latency_sla;
+        config.max_reorg_depth = self.max_reorg_depth;
+        if let Some(shutdown_timeout) = self.shutdown_timeout {
+            config.shutdown_timeout = shutdown_timeout;
+        }
+        if let Some(proposer_boost) = self.proposer_boost {
+            config.proposer_boost = proposer_boost;
+        }
+        config.max_block_bytes = self.max_block_bytes;
+        config.randao = self.randao;
+        config.halt_on_duplicate_identity = self.halt_on_duplicate_identity;
+        config.max_view_lifetime = self.max_view_lifetime;
+        config.check_gas_limit = self.check_gas_limit;
+        if self.block_metadata {
+            config.block_metadata = Some(BlockMetadata {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                build: env!("VERGEN_GIT_SHA").to_string(),
+            });
+        }
+        if let Some(forkchoice_history_size) = self.forkchoice_history_size {
+            config.forkchoice_history_size = forkchoice_history_size;
+        }
+        config
+    }
+
+    /// Returns the resolved clayer settings as a TOML table, keyed by the argument names without
+    /// the `clayer.` prefix.
+    pub fn to_toml(&self) -> toml::Table {
+        let config = self.pbft_config();
+        let mut table = toml::Table::new();
+        table.insert("mine".to_string(), self.mine.into());
+        table.insert("message-log".to_string(), self.message_log.into());
+        table.insert(
+            "message-log-max-size".to_string(),
+            (self.message_log_max_size() as i64).into(),
+        );
+        table.insert("max-pipeline-lag".to_string(), (config.max_pipeline_lag as i64).into());
+        table.insert("max-queued-ticks".to_string(), (config.max_queued_ticks as i64).into());
+        if let Some(faults) = config.faults {
+            table.insert("faults".to_string(), (faults as i64).into());
+        }
+        table.insert(
+            "commit-aggregation-window".to_string(),
+            humantime::format_duration(config.commit_aggregation_window).to_string().into(),
+        );
+        table.insert(
+            "publishing-jitter".to_string(),
+            (config.block_publishing_jitter as i64).into(),
+        );
+        table.insert("skip-empty-blocks".to_string(), self.skip_empty_blocks.into());
+        table.insert("skip-idle-payload-builds".to_string(), self.skip_idle_payload_builds.into());
+        table.insert("observer".to_string(), self.observer.into());
+        table.insert(
+            "max-timestamp-skew".to_string(),
+            humantime::format_duration(config.max_timestamp_skew).to_string().into(),
+        );
+        table.insert(
+            "jwt-refresh-interval".to_string(),
+            humantime::format_duration(self.jwt_refresh_interval()).to_string().into(),
+        );
+        table.insert("engine-http2".to_string(), self.engine_http2.into());
+        table.insert("in-process-engine".to_string(), self.in_process_engine.into());
+        table.insert(
+            "engine-max-concurrent-requests".to_string(),
+            (self.engine_max_concurrent_requests() as i64).into(),
+        );
+        table.insert("ignore-failed-self-test".to_string(), self.ignore_failed_self_test.into());
+        table.insert("db-write-retries".to_string(), (self.db_write_retries() as i64).into());
+        table.insert("payload-retention".to_string(), (config.payload_retention as i64).into());
+        table.insert("not-ready-retries".to_string(), (config.not_ready_retries as i64).into());
+        table.insert(
+            "head-check-interval".to_string(),
+            humantime::format_duration(config.head_check_interval).to_string().into(),
+        );
+        table.insert(
+            "payload-id-max-age".to_string(),
+            humantime::format_duration(config.payload_id_max_age).to_string().into(),
+        );
+        table.insert("check-head-after-commit".to_string(), self.check_head_after_commit.into());
+        table.insert("require-el-finalized".to_string(), self.require_el_finalized.into());
+        table.insert(
+            "primary-rotation-blocks".to_string(),
+            (config.forced_view_change_interval as i64).into(),
+        );
+        if let Some(remote_signer) = &self.remote_signer {
+            table.insert("remote-signer".to_string(), remote_signer.to_string().into());
+        }
+        table.insert("require-connected-quorum".to_string(), self.require_connected_quorum.into());
+        table.insert("track-el-identity".to_string(), self.track_el_identity.into());
+        table.insert("verify-state-root".to_string(), self.verify_state_root.into());
+        table.insert("allow-force-commit".to_string(), self.allow_force_commit.into());
+        table.insert("trust-committed-senders".to_string(), self.trust_committed_senders.into());
+        if let Some(handshake_timeout) = self.handshake_timeout {
+            table.insert(
+                "handshake-timeout".to_string(),
+                humantime::format_duration(handshake_timeout).to_string().into(),
+            );
+        }
+        if let Some((interval, timeout)) = self.heartbeat_interval.zip(self.heartbeat_timeout()) {
+            table.insert(
+                "heartbeat-interval".to_string(),
+                humantime::format_duration(interval).to_string().into(),
+            );
+            table.insert(
+                "heartbeat-timeout".to_string(),
+                humantime::format_duration(timeout).to_string().into(),
+            );
+        }
+        if let Some(validator_contract) = self.validator_contract {
+            table.insert("validator-contract".to_string(), validator_contract.to_string().into());
+        }
+        table.insert("epoch-length".to_string(), (config.epoch_length as i64).into());
+        if let Some(compression) = self.compression {
+            table.insert("compression".to_string(), compression.to_string().into());
+        }
+        table.insert("protocol-version".to_string(), (self.protocol_version() as i64).into());
+        table.insert("seen-cache-size".to_string(), (self.seen_cache_size() as i64).into());
+        table.insert(
+            "seen-cache-ttl".to_string(),
+            humantime::format_duration(self.seen_cache_ttl()).to_string().into(),
+        );
+        if let Some(safe_mode_failures) = self.safe_mode_failures {
+            table.insert("safe-mode-failures".to_string(), (safe_mode_failures as i64).into());
+        }
+        table.insert(
+            "safe-mode-duration".to_string(),
+            humantime::format_duration(config.safe_mode_duration).to_string().into(),
+        );
+        if let Some(sla) = self.commit_latency_sla {
+            table.insert(
+                "commit-latency-sla".to_string(),
+                humantime::format_duration(sla).to_string().into(),
+            );
+        }
+        table.insert(
+            "shutdown-timeout".to_string(),
+            humantime::format_duration(config.shutdown_timeout).to_string().into(),
+        );
+        if let Some(max_reorg_depth) = self.max_reorg_depth {
+            table.insert("max-reorg-depth".to_string(), (max_reorg_depth as i64).into());
+        }
+        if let Some(proposer_boost) = self.proposer_boost {
+            table.insert("proposer-boost".to_string(), (proposer_boost as i64).into());
+        }
+        if let Some(max_block_bytes) = self.max_block_bytes {
+            table.insert("max-block-bytes".to_string(), (max_block_bytes as i64).into());
+        }
+        if let Some(interval) = self.metrics_flush_interval {
+            table.insert(
+                "metrics-flush-interval".to_string(),
+                humantime::format_duration(interval).to_string().into(),
+            );
+        }
+        if let Some(timeout) = self.readiness_timeout {
+            table.insert(
+                "readiness-timeout".to_string(),
+                humantime::format_duration(timeout).to_string().into(),
+            );
+        }
+        table.insert("randao".to_string(), self.randao.into());
+        if let Some(state_dir) = &self.state_dir {
+            table.insert("state-dir".to_string(), state_dir.display().to_string().into());
+        }
+        table.insert(
+            "halt-on-duplicate-identity".to_string(),
+            self.halt_on_duplicate_identity.into(),
+        );
+        if let Some(lifetime) = self.max_view_lifetime {
+            table.insert(
+                "max-view-lifetime".to_string(),
+                humantime::format_duration(lifetime).to_string().into(),
+            );
+        }
+        table.insert("check-gas-limit".to_string(), self.check_gas_limit.into());
+        table.insert("block-metadata".to_string(), self.block_metadata.into());
+        table.insert(
+            "forkchoice-history-size".to_string(),
+            (config.forkchoice_history_size as i64).into(),
+        );
+        if let Some(interval) = self.validator_reconnect_interval {
+            table.insert(
+                "validator-reconnect-interval".to_string(),
+                humantime::format_duration(interval).to_string().into(),
+            );
+        }
+        if !self.bootstrap_peers.is_empty() {
+            let peers = self.bootstrap_peers.iter().map(ToString::to_string).collect::<Vec<_>>();
+            table.insert("bootstrap-peers".to_string(), peers.into());
+        }
+        if let Some(log_level) = self.log_level {
+            table.insert("log-level".to_string(), log_level.to_string().to_lowercase().into());
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Args, Parser};
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn clayer_args_sanity_check() {
+        let default_args = ClayerArgs::default();
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, default_args);
+    }
+
+    #[test]
+    fn parse_message_log() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.message-log"]).args;
+        assert!(args.message_log);
+        assert_eq!(args.message_log_max_size(), DEFAULT_MESSAGE_LOG_MAX_SIZE_MB * 1024 * 1024);
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.message-log",
+            "--clayer.message-log-max-size",
+            "10",
+        ])
+        .args;
+        assert_eq!(args.message_log_max_size(), 10 * 1024 * 1024);
+
+        assert!(CommandParser::<ClayerArgs>::try_parse_from([
+            "reth",
+            "--clayer.message-log-max-size",
+            "10"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_max_pipeline_lag() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().max_pipeline_lag, reth_clayer::DEFAULT_MAX_PIPELINE_LAG);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.max-pipeline-lag", "0"])
+                .args;
+        assert_eq!(args.pbft_config().max_pipeline_lag, 0);
+    }
+
+    #[test]
+    fn parse_faults() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().faults, None);
+
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.faults", "2"]).args;
+        assert_eq!(args.pbft_config().faults, Some(2));
+
+        assert!(
+            CommandParser::<ClayerArgs>::try_parse_from(["reth", "--clayer.faults", "0"]).is_err()
+        );
+    }
+
+    #[test]
+    fn parse_commit_aggregation_window() {
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.commit-aggregation-window",
+            "100ms",
+        ])
+        .args;
+        assert_eq!(args.pbft_config().commit_aggregation_window, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn parse_skip_empty_blocks() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.pbft_config().cancel_empty_payloads);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.skip-empty-blocks"]).args;
+        assert!(args.pbft_config().cancel_empty_payloads);
+    }
+
+    #[test]
+    fn parse_observer() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.pbft_config().observer);
+
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.observer"]).args;
+        assert!(args.pbft_config().observer);
+    }
+
+    #[test]
+    fn parse_max_timestamp_skew() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().max_timestamp_skew, Duration::from_secs(15));
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.max-timestamp-skew", "0s"])
+                .args;
+        assert_eq!(args.pbft_config().max_timestamp_skew, Duration::ZERO);
+    }
+
+    #[test]
+    fn parse_jwt_refresh_interval() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.jwt_refresh_interval(), DEFAULT_TOKEN_REFRESH_INTERVAL);
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.jwt-refresh-interval",
+            "5s",
+        ])
+        .args;
+        assert_eq!(args.jwt_refresh_interval(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn parse_engine_http2() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.engine_http2);
+
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.engine-http2"]).args;
+        assert!(args.engine_http2);
+        assert_eq!(args.to_toml()["engine-http2"], true.into());
+    }
+
+    #[test]
+    fn parse_ignore_failed_self_test() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(args.pbft_config().abort_on_failed_self_test);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.ignore-failed-self-test"])
+                .args;
+        assert!(!args.pbft_config().abort_on_failed_self_test);
+    }
+
+    #[test]
+    fn parse_payload_retention() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().payload_retention, 10_000);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.payload-retention", "0"])
+                .args;
+        assert_eq!(args.pbft_config().payload_retention, 0);
+    }
+
+    #[test]
+    fn parse_not_ready_retries() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().not_ready_retries, 3);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.not-ready-retries", "0"])
+                .args;
+        assert_eq!(args.pbft_config().not_ready_retries, 0);
+    }
+
+    #[test]
+    fn parse_head_check_interval() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().head_check_interval, Duration::from_secs(30));
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.head-check-interval", "0s"])
+                .args;
+        assert_eq!(args.pbft_config().head_check_interval, Duration::ZERO);
+    }
+
+    #[test]
+    fn parse_primary_rotation_blocks() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().forced_view_change_interval, 20);
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.primary-rotation-blocks",
+            "5",
+        ])
+        .args;
+        assert_eq!(args.pbft_config().forced_view_change_interval, 5);
+    }
+
+    #[test]
+    fn parse_remote_signer() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(args.remote_signer.is_none());
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.remote-signer",
+            "http://127.0.0.1:9000",
+        ])
+        .args;
+        assert_eq!(args.remote_signer, Some(Url::parse("http://127.0.0.1:9000").unwrap()));
+        assert_eq!(args.to_toml()["remote-signer"].as_str(), Some("http://127.0.0.1:9000/"));
+
+        assert!(CommandParser::<ClayerArgs>::try_parse_from([
+            "reth",
+            "--clayer.remote-signer",
+            "not a url"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_require_connected_quorum() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.pbft_config().require_connected_quorum);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.require-connected-quorum"])
+                .args;
+        assert!(args.pbft_config().require_connected_quorum);
+    }
+
+    #[test]
+    fn parse_track_el_identity() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.track_el_identity);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.track-el-identity"]).args;
+        assert!(args.track_el_identity);
+        assert_eq!(args.to_toml()["track-el-identity"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn parse_allow_force_commit() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.pbft_config().allow_force_commit);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.allow-force-commit"]).args;
+        assert!(args.pbft_config().allow_force_commit);
+    }
+
+    #[test]
+    fn parse_payload_id_max_age() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().payload_id_max_age, Duration::from_secs(12));
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.payload-id-max-age", "0s"])
+                .args;
+        assert_eq!(args.pbft_config().payload_id_max_age, Duration::ZERO);
+    }
+
+    #[test]
+    fn parse_handshake_timeout() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.handshake_timeout, None);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.handshake-timeout", "30s"])
+                .args;
+        assert_eq!(args.handshake_timeout, Some(Duration::from_secs(30)));
+        assert_eq!(args.to_toml()["handshake-timeout"].as_str(), Some("30s"));
+    }
+
+    #[test]
+    fn parse_validator_reconnect_interval() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.validator_reconnect_interval, None);
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.validator-reconnect-interval",
+            "500ms",
+        ])
+        .args;
+        assert_eq!(args.validator_reconnect_interval, Some(Duration::from_millis(500)));
+        assert_eq!(args.to_toml()["validator-reconnect-interval"].as_str(), Some("500ms"));
+    }
+
+    #[test]
+    fn parse_validator_contract() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().validator_contract, None);
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.validator-contract",
+            "0x0000000000000000000000000000000000001001",
+        ])
+        .args;
+        let contract: Address = "0x0000000000000000000000000000000000001001".parse().unwrap();
+        assert_eq!(args.pbft_config().validator_contract, Some(contract));
+        assert_eq!(
+            args.to_toml()["validator-contract"].as_str(),
+            Some(contract.to_string().as_str())
+        );
+    }
+
+    #[test]
+    fn parse_epoch_length() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().epoch_length, 100);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.epoch-length", "32"]).args;
+        assert_eq!(args.pbft_config().epoch_length, 32);
+        assert_eq!(args.to_toml()["epoch-length"].as_integer(), Some(32));
+
+        assert!(CommandParser::<ClayerArgs>::try_parse_from([
+            "reth",
+            "--clayer.epoch-length",
+            "0"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_compression() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.compression, None);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.compression", "zstd"]).args;
+        assert_eq!(args.compression, Some(Compression::Zstd));
+        assert_eq!(args.to_toml()["compression"].as_str(), Some("zstd"));
+
+        assert!(CommandParser::<ClayerArgs>::try_parse_from([
+            "reth",
+            "--clayer.compression",
+            "gzip"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_safe_mode() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().safe_mode_failures, 0);
+        assert_eq!(args.pbft_config().safe_mode_duration, Duration::from_secs(600));
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.safe-mode-failures",
+            "5",
+            "--clayer.safe-mode-duration",
+            "0s",
+        ])
+        .args;
+        assert_eq!(args.pbft_config().safe_mode_failures, 5);
+        assert_eq!(args.pbft_config().safe_mode_duration, Duration::ZERO);
+        assert_eq!(args.to_toml()["safe-mode-failures"].as_integer(), Some(5));
+        assert_eq!(args.to_toml()["safe-mode-duration"].as_str(), Some("0s"));
+
+        assert!(CommandParser::<ClayerArgs>::try_parse_from([
+            "reth",
+            "--clayer.safe-mode-failures",
+            "0"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_verify_state_root() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.verify_state_root);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.verify-state-root"]).args;
+        assert!(args.verify_state_root);
+        assert_eq!(args.to_toml()["verify-state-root"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn parse_log_level() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(args.consensus_log_level().is_none());
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.log-level", "debug"]).args;
+        let level = args.consensus_log_level().unwrap();
+        assert_eq!(level.target(), CONSENSUS_LOG_TARGET);
+        assert_eq!(level.level(), LevelFilter::DEBUG);
+        assert_eq!(args.to_toml()["log-level"].as_str(), Some("debug"));
+
+        assert!(CommandParser::<ClayerArgs>::try_parse_from([
+            "reth",
+            "--clayer.log-level",
+            "loud"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_engine_max_concurrent_requests() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.engine_max_concurrent_requests(), DEFAULT_MAX_CONCURRENT_REQUESTS);
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.engine-max-concurrent-requests",
+            "4",
+        ])
+        .args;
+        assert_eq!(args.engine_max_concurrent_requests(), 4);
+        assert_eq!(args.to_toml()["engine-max-concurrent-requests"].as_integer(), Some(4));
+
+        assert!(CommandParser::<ClayerArgs>::try_parse_from([
+            "reth",
+            "--clayer.engine-max-concurrent-requests",
+            "0"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_check_head_after_commit() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.pbft_config().check_head_after_commit);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.check-head-after-commit"])
+                .args;
+        assert!(args.pbft_config().check_head_after_commit);
+        assert_eq!(args.to_toml()["check-head-after-commit"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn parse_publishing_jitter() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().block_publishing_jitter, 0);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.publishing-jitter", "10"])
+                .args;
+        assert_eq!(args.pbft_config().block_publishing_jitter, 10);
+        assert_eq!(args.to_toml()["publishing-jitter"].as_integer(), Some(10));
+
+        assert!(CommandParser::<ClayerArgs>::try_parse_from([
+            "reth",
+            "--clayer.publishing-jitter",
+            "51"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_seen_cache() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.seen_cache_size(), DEFAULT_SEEN_CACHE_SIZE);
+        assert_eq!(args.seen_cache_ttl(), DEFAULT_SEEN_CACHE_TTL);
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.seen-cache-size",
+            "100",
+            "--clayer.seen-cache-ttl",
+            "30s",
+        ])
+        .args;
+        assert_eq!(args.seen_cache_size(), 100);
+        assert_eq!(args.seen_cache_ttl(), Duration::from_secs(30));
+        assert_eq!(args.to_toml()["seen-cache-size"].as_integer(), Some(100));
+        assert_eq!(args.to_toml()["seen-cache-ttl"].as_str(), Some("30s"));
+
+        assert!(CommandParser::<ClayerArgs>::try_parse_from([
+            "reth",
+            "--clayer.seen-cache-size",
+            "0"
+        ])
+        .is_err());
+
+        // the seen-cache is always enabled, its TTL can be set alone
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.seen-cache-ttl", "30s"])
+                .args;
+        assert_eq!(args.seen_cache_size(), DEFAULT_SEEN_CACHE_SIZE);
+        assert_eq!(args.seen_cache_ttl(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn parse_protocol_version() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.protocol_version(), CLAYER_PROTOCOL_VERSION);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.protocol-version", "1"])
+                .args;
+        assert_eq!(args.protocol_version(), 1);
+        assert_eq!(args.to_toml()["protocol-version"].as_integer(), Some(1));
+
+        for version in ["0", "3"] {
+            assert!(CommandParser::<ClayerArgs>::try_parse_from([
+                "reth",
+                "--clayer.protocol-version",
+                version
+            ])
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn parse_require_el_finalized() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.pbft_config().require_el_finalized);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.require-el-finalized"]).args;
+        assert!(args.pbft_config().require_el_finalized);
+        assert_eq!(args.to_toml()["require-el-finalized"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn parse_commit_latency_sla() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().commit_latency_sla, None);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.commit-latency-sla", "3s"])
+                .args;
+        assert_eq!(args.pbft_config().commit_latency_sla, Some(Duration::from_secs(3)));
+        assert_eq!(args.to_toml()["commit-latency-sla"].as_str(), Some("3s"));
+    }
+
+    #[test]
+    fn parse_in_process_engine() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.in_process_engine);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.in-process-engine"]).args;
+        assert!(args.in_process_engine);
+        assert_eq!(args.to_toml()["in-process-engine"], true.into());
+    }
+
+    #[test]
+    fn parse_db_write_retries() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.db_write_retries(), DEFAULT_DB_WRITE_RETRIES);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.db-write-retries", "0"])
+                .args;
+        assert_eq!(args.db_write_retries(), 0);
+        assert_eq!(args.to_toml()["db-write-retries"].as_integer(), Some(0));
+    }
+
+    #[test]
+    fn parse_bootstrap_peers() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(args.bootstrap_peers.is_empty());
+        assert!(args.to_toml().get("bootstrap-peers").is_none());
+
+        let enode = "enode://d860a01f9722d78051619d1e2351aba3f43f943f6f00718d1b9baa4101932a1f5011f16bb2b1bb35db20d6fe28fa0bf09636d26a87d31de9ec6203eeedb1f666@18.138.108.67:30303";
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.bootstrap-peers", enode])
+                .args;
+        assert_eq!(args.bootstrap_peers, vec![enode.parse::<NodeRecord>().unwrap()]);
+        assert_eq!(args.to_toml()["bootstrap-peers"][0].as_str(), Some(enode));
+    }
+
+    #[test]
+    fn parse_heartbeat() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.heartbeat_interval, None);
+        assert_eq!(args.heartbeat_timeout(), None);
+        assert!(args.to_toml().get("heartbeat-interval").is_none());
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.heartbeat-interval", "1s"])
+                .args;
+        assert_eq!(args.heartbeat_timeout(), Some(Duration::from_secs(3)));
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.heartbeat-interval",
+            "1s",
+            "--clayer.heartbeat-timeout",
+            "5s",
+        ])
+        .args;
+        assert_eq!(args.heartbeat_interval, Some(Duration::from_secs(1)));
+        assert_eq!(args.heartbeat_timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(args.to_toml()["heartbeat-interval"].as_str(), Some("1s"));
+        assert_eq!(args.to_toml()["heartbeat-timeout"].as_str(), Some("5s"));
+
+        assert!(CommandParser::<ClayerArgs>::try_parse_from([
+            "reth",
+            "--clayer.heartbeat-timeout",
+            "5s"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_max_reorg_depth() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().max_reorg_depth, None);
+        assert!(args.to_toml().get("max-reorg-depth").is_none());
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.max-reorg-depth", "64"])
+                .args;
+        assert_eq!(args.pbft_config().max_reorg_depth, Some(64));
+        assert_eq!(args.to_toml()["max-reorg-depth"].as_integer(), Some(64));
+    }
+
+    #[test]
+    fn parse_max_queued_ticks() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().max_queued_ticks, reth_clayer::DEFAULT_MAX_QUEUED_TICKS);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.max-queued-ticks", "4"])
+                .args;
+        assert_eq!(args.pbft_config().max_queued_ticks, 4);
+        assert_eq!(args.to_toml()["max-queued-ticks"].as_integer(), Some(4));
+    }
+
+    #[test]
+    fn parse_shutdown_timeout() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().shutdown_timeout, PbftConfig::default().shutdown_timeout);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.shutdown-timeout", "30s"])
+                .args;
+        assert_eq!(args.pbft_config().shutdown_timeout, Duration::from_secs(30));
+        assert_eq!(args.to_toml()["shutdown-timeout"].as_str(), Some("30s"));
+    }
+
+    #[test]
+    fn parse_proposer_boost() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().proposer_boost, 0);
+        assert!(args.to_toml().get("proposer-boost").is_none());
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.proposer-boost", "2"]).args;
+        assert_eq!(args.pbft_config().proposer_boost, 2);
+        assert_eq!(args.to_toml()["proposer-boost"].as_integer(), Some(2));
+    }
+
+    #[test]
+    fn parse_max_block_bytes() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().max_block_bytes, None);
+        assert!(args.to_toml().get("max-block-bytes").is_none());
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.max-block-bytes",
+            "1048576",
+        ])
+        .args;
+        assert_eq!(args.pbft_config().max_block_bytes, Some(1048576));
+        assert_eq!(args.to_toml()["max-block-bytes"].as_integer(), Some(1048576));
+    }
+
+    #[test]
+    fn parse_metrics_flush_interval() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.metrics_flush_interval, None);
+        assert!(args.to_toml().get("metrics-flush-interval").is_none());
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.metrics-flush-interval",
+            "500ms",
+        ])
+        .args;
+        assert_eq!(args.metrics_flush_interval, Some(Duration::from_millis(500)));
+        assert_eq!(args.to_toml()["metrics-flush-interval"].as_str(), Some("500ms"));
+    }
+
+    #[test]
+    fn parse_skip_idle_payload_builds() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.skip_idle_payload_builds);
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.skip-empty-blocks",
+            "--clayer.skip-idle-payload-builds",
+        ])
+        .args;
+        assert!(args.skip_idle_payload_builds);
+        assert_eq!(args.to_toml()["skip-idle-payload-builds"].as_bool(), Some(true));
+
+        assert!(CommandParser::<ClayerArgs>::try_parse_from([
+            "reth",
+            "--clayer.skip-idle-payload-builds"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_readiness_timeout() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.readiness_timeout, None);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.readiness-timeout", "5m"])
+                .args;
+        assert_eq!(args.readiness_timeout, Some(Duration::from_secs(300)));
+        assert_eq!(args.to_toml()["readiness-timeout"].as_str(), Some("5m"));
+    }
+
+    #[test]
+    fn parse_randao() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.pbft_config().randao);
+
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.randao"]).args;
+        assert!(args.pbft_config().randao);
+        assert_eq!(args.to_toml()["randao"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn parse_state_dir() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(args.state_dir.is_none());
+        assert!(!args.to_toml().contains_key("state-dir"));
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.state-dir", "/data/clayer"])
+                .args;
+        assert_eq!(args.state_dir, Some(PathBuf::from("/data/clayer")));
+        assert_eq!(args.to_toml()["state-dir"].as_str(), Some("/data/clayer"));
+    }
+
+    #[test]
+    fn parse_halt_on_duplicate_identity() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.pbft_config().halt_on_duplicate_identity);
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.halt-on-duplicate-identity",
+        ])
+        .args;
+        assert!(args.pbft_config().halt_on_duplicate_identity);
+        assert_eq!(args.to_toml()["halt-on-duplicate-identity"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn parse_max_view_lifetime() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().max_view_lifetime, None);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.max-view-lifetime", "1h"])
+                .args;
+        assert_eq!(args.pbft_config().max_view_lifetime, Some(Duration::from_secs(3600)));
+        assert_eq!(args.to_toml()["max-view-lifetime"].as_str(), Some("1h"));
+    }
+
+    #[test]
+    fn parse_check_gas_limit() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert!(!args.pbft_config().check_gas_limit);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.check-gas-limit"]).args;
+        assert!(args.pbft_config().check_gas_limit);
+        assert_eq!(args.to_toml()["check-gas-limit"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn parse_block_metadata() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.pbft_config().block_metadata, None);
+
+        let args =
+            CommandParser::<ClayerArgs>::parse_from(["reth", "--clayer.block-metadata"]).args;
+        let metadata = args.pbft_config().block_metadata.unwrap();
+        assert_eq!(metadata.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(args.to_toml()["block-metadata"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn parse_forkchoice_history_size() {
+        let args = CommandParser::<ClayerArgs>::parse_from(["reth"]).args;
+        assert_eq!(
+            args.pbft_config().forkchoice_history_size,
+            reth_clayer::DEFAULT_FORKCHOICE_HISTORY_SIZE
+        );
+
+        let args = CommandParser::<ClayerArgs>::parse_from([
+            "reth",
+            "--clayer.forkchoice-history-size",
+            "8",
+        ])
+        .args;
+        assert_eq!(args.pbft_config().forkchoice_history_size, 8);
+        assert_eq!(args.to_toml()["forkchoice-history-size"].as_integer(), Some(8));
+    }
 }
@@ -1,7 +1,13 @@
 //! clap [Args](clap::Args) for database configuration
 
-use clap::Args;
+use clap::{builder::RangedU64ValueParser, Args};
+use humantime::parse_duration;
+use reth_db::DatabaseGeometry;
 use reth_interfaces::db::LogLevel;
+use std::time::Duration;
+
+const MEGABYTE: usize = 1024 * 1024;
+const GIGABYTE: usize = MEGABYTE * 1024;
 
 /// Parameters for database configuration
 #[derive(Debug, Args, PartialEq, Default, Clone, Copy)]
@@ -10,6 +16,82 @@ pub struct DatabaseArgs {
     /// Database logging level. Levels higher than "notice" require a debug build.
     #[arg(long = "db.log-level", value_enum)]
     pub log_level: Option<LogLevel>,
+
+    /// Warn when fewer pages than this are free in the database.
+    #[arg(long = "db.min-free-pages", value_name = "PAGES")]
+    pub min_free_pages: Option<u64>,
+
+    /// Warn when the database tables take up at least this many gigabytes.
+    #[arg(long = "db.size-alert-threshold", value_name = "GB")]
+    pub size_alert_threshold: Option<u64>,
+
+    /// How often the database is checked against the alert thresholds [default: 60s]
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --db.monitor-interval 5m
+    #[arg(
+        long = "db.monitor-interval",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub monitor_interval: Option<Duration>,
+
+    /// Maximum size of the database in gigabytes, writes fail once it is reached [default: 4096]
+    #[arg(
+        long = "db.max-size",
+        value_name = "GB",
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..)
+    )]
+    pub max_size: Option<u64>,
+
+    /// Step in megabytes the database file grows by, at most the maximum size [default: 4096]
+    #[arg(
+        long = "db.growth-step",
+        value_name = "MB",
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..)
+    )]
+    pub growth_step: Option<u64>,
+}
+
+impl DatabaseArgs {
+    /// Returns the size alert threshold in bytes.
+    pub fn size_alert_threshold(&self) -> Option<u64> {
+        self.size_alert_threshold.map(|gb| gb * 1024 * 1024 * 1024)
+    }
+
+    /// Returns the size limits the database is opened with.
+    ///
+    /// Fails if a size doesn't fit in the address space or the growth step exceeds the maximum
+    /// size.
+    pub fn geometry(&self) -> eyre::Result<DatabaseGeometry> {
+        let default = DatabaseGeometry::default();
+        let geometry = DatabaseGeometry {
+            max_size: match self.max_size {
+                Some(gb) => to_bytes(gb, GIGABYTE)
+                    .ok_or_else(|| eyre::eyre!("database maximum size of {gb} GB is too large"))?,
+                None => default.max_size,
+            },
+            growth_step: match self.growth_step {
+                Some(mb) => to_bytes(mb, MEGABYTE)
+                    .ok_or_else(|| eyre::eyre!("database growth step of {mb} MB is too large"))?,
+                None => default.growth_step,
+            },
+        };
+        if geometry.growth_step > geometry.max_size {
+            eyre::bail!(
+                "database growth step of {} MB exceeds the maximum size of {} GB",
+                geometry.growth_step / MEGABYTE,
+                geometry.max_size / GIGABYTE
+            )
+        }
+        Ok(geometry)
+    }
+}
+
+/// Converts `size` in units of `unit` bytes to bytes, `None` on overflow.
+fn to_bytes(size: u64, unit: usize) -> Option<usize> {
+    usize::try_from(size).ok()?.checked_mul(unit)
 }
 
 #[cfg(test)]
@@ -30,4 +112,61 @@ mod tests {
         let args = CommandParser::<DatabaseArgs>::parse_from(["reth"]).args;
         assert_eq!(args, default_args);
     }
+
+    #[test]
+    fn test_parse_database_alert_args() {
+        let args = CommandParser::<DatabaseArgs>::parse_from([
+            "reth",
+            "--db.min-free-pages",
+            "1000",
+            "--db.size-alert-threshold",
+            "2",
+            "--db.monitor-interval",
+            "5m",
+        ])
+        .args;
+        assert_eq!(args.min_free_pages, Some(1000));
+        assert_eq!(args.size_alert_threshold(), Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(args.monitor_interval, Some(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn test_parse_database_geometry_args() {
+        let args = CommandParser::<DatabaseArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.geometry().unwrap(), DatabaseGeometry::default());
+
+        let args = CommandParser::<DatabaseArgs>::parse_from([
+            "reth",
+            "--db.max-size",
+            "8192",
+            "--db.growth-step",
+            "512",
+        ])
+        .args;
+        assert_eq!(
+            args.geometry().unwrap(),
+            DatabaseGeometry { max_size: 8192 * GIGABYTE, growth_step: 512 * MEGABYTE }
+        );
+
+        // the database can't grow by more than its maximum size
+        let args = CommandParser::<DatabaseArgs>::parse_from([
+            "reth",
+            "--db.max-size",
+            "1",
+            "--db.growth-step",
+            "2048",
+        ])
+        .args;
+        assert!(args.geometry().is_err());
+        assert!(
+            CommandParser::<DatabaseArgs>::try_parse_from(["reth", "--db.max-size", "0"]).is_err()
+        );
+
+        // sizes that overflow the address space are rejected instead of wrapping
+        let max = u64::MAX.to_string();
+        for arg in ["--db.max-size", "--db.growth-step"] {
+            let args = CommandParser::<DatabaseArgs>::parse_from(["reth", arg, &max]).args;
+            assert!(args.geometry().is_err());
+        }
+    }
 }
@@ -23,6 +23,13 @@ pub struct DebugArgs {
     #[arg(long = "debug.tip", help_heading = "Debug", conflicts_with = "continuous")]
     pub tip: Option<B256>,
 
+    /// Number of times downloading the `--debug.tip` header from the network is retried before
+    /// giving up.
+    ///
+    /// Retries forever if not set.
+    #[arg(long = "debug.tip-fetch-retries", help_heading = "Debug", value_name = "RETRIES")]
+    pub tip_fetch_retries: Option<usize>,
+
     /// Runs the sync only up to the specified block.
     #[arg(long = "debug.max-block", help_heading = "Debug")]
     pub max_block: Option<u64>,
@@ -77,4 +84,14 @@ mod tests {
         let args = CommandParser::<DebugArgs>::parse_from(["reth"]).args;
         assert_eq!(args, default_args);
     }
+
+    #[test]
+    fn parse_tip_fetch_retries() {
+        let args = CommandParser::<DebugArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.tip_fetch_retries, None);
+
+        let args =
+            CommandParser::<DebugArgs>::parse_from(["reth", "--debug.tip-fetch-retries", "3"]).args;
+        assert_eq!(args.tip_fetch_retries, Some(3));
+    }
 }
@@ -0,0 +1,88 @@
+//! clap [Args](clap::Args) for downloader configuration
+
+use clap::{builder::RangedU64ValueParser, Args};
+
+/// Upper bound of `--downloader.body-concurrency`.
+const MAX_BODY_CONCURRENCY: u64 = 1_000;
+
+/// Parameters for configuring the block downloaders
+#[derive(Debug, Args, PartialEq, Default, Clone, Copy)]
+#[clap(next_help_heading = "Downloader")]
+pub struct DownloaderArgs {
+    /// The maximum number of headers requested from a peer in a single `GetBlockHeaders`
+    /// request, must be at least 1.
+    ///
+    /// Overrides `downloader_request_limit` of the `[stages.headers]` config section.
+    #[arg(
+        long = "downloader.header-batch-size",
+        value_name = "COUNT",
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..)
+    )]
+    pub header_batch_size: Option<u64>,
+
+    /// The maximum number of concurrent `GetBlockBodies` requests, between 1 and 1000.
+    ///
+    /// Overrides `downloader_max_concurrent_requests` of the `[stages.bodies]` config section.
+    /// `downloader_min_concurrent_requests` is lowered to it if it is larger.
+    #[arg(
+        long = "downloader.body-concurrency",
+        value_name = "COUNT",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..=MAX_BODY_CONCURRENCY)
+    )]
+    pub body_concurrency: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_downloader_args() {
+        let default_args = DownloaderArgs::default();
+        let args = CommandParser::<DownloaderArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, default_args);
+
+        let args = CommandParser::<DownloaderArgs>::parse_from([
+            "reth",
+            "--downloader.header-batch-size",
+            "256",
+        ])
+        .args;
+        assert_eq!(args, DownloaderArgs { header_batch_size: Some(256), body_concurrency: None });
+
+        assert!(CommandParser::<DownloaderArgs>::try_parse_from([
+            "reth",
+            "--downloader.header-batch-size",
+            "0"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_body_concurrency() {
+        let args = CommandParser::<DownloaderArgs>::parse_from([
+            "reth",
+            "--downloader.body-concurrency",
+            "8",
+        ])
+        .args;
+        assert_eq!(args.body_concurrency, Some(8));
+
+        for invalid in ["0", "1001"] {
+            assert!(CommandParser::<DownloaderArgs>::try_parse_from([
+                "reth",
+                "--downloader.body-concurrency",
+                invalid
+            ])
+            .is_err());
+        }
+    }
+}
@@ -20,6 +20,10 @@ pub use debug_args::DebugArgs;
 mod database_args;
 pub use database_args::DatabaseArgs;
 
+/// DownloaderArgs struct for configuring the block downloaders
+mod downloader_args;
+pub use downloader_args::DownloaderArgs;
+
 mod secret_key;
 pub use secret_key::{get_secret_key, SecretKeyError};
 
@@ -47,6 +51,10 @@ pub use dev_args::DevArgs;
 mod pruning_args;
 pub use pruning_args::PruningArgs;
 
+/// SnapshotArgs for configuring the snapshotter
+mod snapshot_args;
+pub use snapshot_args::SnapshotArgs;
+
 mod clayer_arg;
 pub use clayer_arg::ClayerArgs;
 
@@ -74,6 +74,13 @@ pub struct NetworkArgs {
     /// Maximum number of inbound requests. default: 30
     #[arg(long)]
     pub max_inbound_peers: Option<usize>,
+
+    /// Topic consensus messages are tagged with, messages tagged with another topic are dropped.
+    ///
+    /// Defaults to the chain id, so that clayer networks sharing peers do not see each other's
+    /// consensus messages.
+    #[arg(long = "network.consensus-topic", value_name = "TOPIC")]
+    pub consensus_topic: Option<u64>,
 }
 
 impl NetworkArgs {
@@ -123,6 +130,11 @@ impl NetworkArgs {
 
         Some(peers_file)
     }
+
+    /// Returns the topic of the consensus messages, the configured one or the id of the chain.
+    pub fn consensus_topic(&self, chain_spec: &ChainSpec) -> u64 {
+        self.consensus_topic.unwrap_or_else(|| chain_spec.chain.id())
+    }
 }
 
 impl Default for NetworkArgs {
@@ -141,6 +153,7 @@ impl Default for NetworkArgs {
             port: DEFAULT_DISCOVERY_PORT,
             max_outbound_peers: None,
             max_inbound_peers: None,
+            consensus_topic: None,
         }
     }
 }
@@ -257,6 +270,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_consensus_topic() {
+        let chain_spec = reth_primitives::MAINNET.clone();
+        let args = CommandParser::<NetworkArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.consensus_topic(&chain_spec), chain_spec.chain.id());
+
+        let args =
+            CommandParser::<NetworkArgs>::parse_from(["reth", "--network.consensus-topic", "7"])
+                .args;
+        assert_eq!(args.consensus_topic(&chain_spec), 7);
+    }
+
     #[test]
     fn network_args_default_sanity_test() {
         let default_args = NetworkArgs::default();
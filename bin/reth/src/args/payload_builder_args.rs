@@ -6,6 +6,7 @@ use clap::{
     builder::{RangedU64ValueParser, TypedValueParser},
     Arg, Args, Command,
 };
+use reth_basic_payload_builder::{GasTarget, TxOrdering};
 use reth_primitives::constants::{
     ETHEREUM_BLOCK_GAS_LIMIT, MAXIMUM_EXTRA_DATA_SIZE, SLOT_DURATION,
 };
@@ -35,6 +36,19 @@ pub struct PayloadBuilderArgs {
     #[arg(long = "builder.max-tasks", default_value = "3", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
     pub max_payload_tasks: usize,
 
+    /// The order of the transactions in blocks built by this node: pool, fifo or fee-priority.
+    ///
+    /// Blocks built by an external execution layer are ordered by it.
+    #[arg(long = "builder.tx-ordering", default_value_t = TxOrdering::Pool, value_name = "ORDERING")]
+    pub tx_ordering: TxOrdering,
+
+    /// Stop adding transactions to blocks built by this node once their gas used reaches this
+    /// percentage of the gas limit.
+    ///
+    /// By default blocks are filled up to the gas limit.
+    #[arg(long = "builder.gas-target", value_name = "PERCENT", value_parser = RangedU64ValueParser::<u8>::new().range(1..=100))]
+    pub gas_target: Option<u8>,
+
     /// By default the pending block equals the latest block
     /// to save resources and not leak txs from the tx-pool,
     /// this flag enables computing of the pending block
@@ -56,6 +70,8 @@ impl Default for PayloadBuilderArgs {
             interval: Duration::from_secs(1),
             deadline: SLOT_DURATION,
             max_payload_tasks: 3,
+            tx_ordering: TxOrdering::Pool,
+            gas_target: None,
             #[cfg(feature = "optimism")]
             compute_pending_block: false,
         }
@@ -83,6 +99,14 @@ impl PayloadBuilderConfig for PayloadBuilderArgs {
         self.max_payload_tasks
     }
 
+    fn tx_ordering(&self) -> TxOrdering {
+        self.tx_ordering
+    }
+
+    fn gas_target(&self) -> Option<GasTarget> {
+        self.gas_target.map(GasTarget::new)
+    }
+
     #[cfg(feature = "optimism")]
     fn compute_pending_block(&self) -> bool {
         self.compute_pending_block
@@ -169,6 +193,42 @@ mod tests {
         assert!(args.is_err());
     }
 
+    #[test]
+    fn test_args_with_tx_ordering() {
+        let args = CommandParser::<PayloadBuilderArgs>::parse_from([
+            "reth",
+            "--builder.tx-ordering",
+            "fee-priority",
+        ])
+        .args;
+        assert_eq!(args.tx_ordering, TxOrdering::FeePriority);
+
+        assert!(CommandParser::<PayloadBuilderArgs>::try_parse_from([
+            "reth",
+            "--builder.tx-ordering",
+            "lifo"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_args_with_gas_target() {
+        let args =
+            CommandParser::<PayloadBuilderArgs>::parse_from(["reth", "--builder.gas-target", "50"])
+                .args;
+        assert_eq!(args.gas_target, Some(50));
+        assert_eq!(args.gas_target(), Some(GasTarget::new(50)));
+
+        for invalid in ["0", "101"] {
+            assert!(CommandParser::<PayloadBuilderArgs>::try_parse_from([
+                "reth",
+                "--builder.gas-target",
+                invalid
+            ])
+            .is_err());
+        }
+    }
+
     #[test]
     fn payload_builder_args_default_sanity_check() {
         let default_args = PayloadBuilderArgs::default();
@@ -1,11 +1,13 @@
 //! Pruning and full node arguments
 
 use clap::Args;
+use humantime::parse_duration;
 use reth_config::config::PruneConfig;
 use reth_primitives::{
     ChainSpec, PruneMode, PruneModes, ReceiptsLogPruneConfig, MINIMUM_PRUNING_DISTANCE,
 };
-use std::sync::Arc;
+use reth_prune::DEFAULT_STALL_WINDOW;
+use std::{sync::Arc, time::Duration};
 
 /// Parameters for pruning and full node
 #[derive(Debug, Args, PartialEq, Default)]
@@ -15,6 +17,13 @@ pub struct PruningArgs {
     /// This flag takes priority over pruning configuration in reth.toml.
     #[arg(long, default_value_t = false)]
     pub full: bool,
+
+    /// Period without pruning progress after which the pruner is reported as stalled.
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --prune.stall-window 10m
+    #[arg(long = "prune.stall-window", value_parser = parse_duration, verbatim_doc_comment)]
+    pub stall_window: Option<Duration>,
 }
 
 impl PruningArgs {
@@ -46,6 +55,11 @@ impl PruningArgs {
             None
         })
     }
+
+    /// Returns the period without pruning progress after which the pruner is considered stalled.
+    pub fn stall_window(&self) -> Duration {
+        self.stall_window.unwrap_or(DEFAULT_STALL_WINDOW)
+    }
 }
 
 #[cfg(test)]
@@ -66,4 +80,14 @@ mod tests {
         let args = CommandParser::<PruningArgs>::parse_from(["reth"]).args;
         assert_eq!(args, default_args);
     }
+
+    #[test]
+    fn parse_stall_window() {
+        let args = CommandParser::<PruningArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.stall_window(), DEFAULT_STALL_WINDOW);
+
+        let args =
+            CommandParser::<PruningArgs>::parse_from(["reth", "--prune.stall-window", "2m"]).args;
+        assert_eq!(args.stall_window(), Duration::from_secs(120));
+    }
 }
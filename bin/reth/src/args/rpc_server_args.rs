@@ -17,6 +17,8 @@ use clap::{
     Arg, Args, Command,
 };
 use futures::TryFutureExt;
+use jsonrpsee::Methods;
+use reth_clayer::{ClayerAdminApiServer, ClayerApiServer, ClayerRpc, InProcessEngine};
 use reth_network_api::{NetworkInfo, Peers};
 use reth_provider::{
     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader,
@@ -30,7 +32,7 @@ use reth_rpc_builder::{
     auth::{AuthServerConfig, AuthServerHandle},
     constants,
     error::RpcError,
-    EthConfig, IpcServerBuilder, RethRpcModule, RpcModuleBuilder, RpcModuleConfig,
+    EthConfig, IpcServerBuilder, RethRpcModule, RpcMethodFilter, RpcModuleBuilder, RpcModuleConfig,
     RpcModuleSelection, RpcServerConfig, RpcServerHandle, ServerBuilder, TransportRpcModuleConfig,
 };
 use reth_rpc_engine_api::{EngineApi, EngineApiServer};
@@ -131,6 +133,20 @@ pub struct RpcServerArgs {
     #[arg(long = "rpc.jwtsecret", value_name = "HEX", global = true, required = false)]
     pub rpc_jwtsecret: Option<JwtSecret>,
 
+    /// Comma separated JSON-RPC methods the HTTP, WS and IPC servers exclusively serve, e.g.
+    /// `eth_blockNumber,eth_call`.
+    ///
+    /// All other methods of the enabled modules fail with `method not found`.
+    #[arg(long = "rpc.allow-methods", value_name = "METHODS", value_delimiter = ',')]
+    pub rpc_allow_methods: Option<Vec<String>>,
+
+    /// Comma separated JSON-RPC methods the HTTP, WS and IPC servers never serve, e.g.
+    /// `admin_addPeer,debug_traceTransaction`.
+    ///
+    /// Takes precedence over `--rpc.allow-methods`, denied methods fail with `method not found`.
+    #[arg(long = "rpc.deny-methods", value_name = "METHODS", value_delimiter = ',')]
+    pub rpc_deny_methods: Vec<String>,
+
     /// Set the maximum RPC request payload size for both HTTP and WS in megabytes.
     #[arg(long, default_value_t = RPC_DEFAULT_MAX_REQUEST_SIZE_MB.into())]
     pub rpc_max_request_size: MaxU32,
@@ -143,8 +159,10 @@ pub struct RpcServerArgs {
     #[arg(long, default_value_t = RPC_DEFAULT_MAX_SUBS_PER_CONN.into())]
     pub rpc_max_subscriptions_per_connection: MaxU32,
 
-    /// Maximum number of RPC server connections.
-    #[arg(long, value_name = "COUNT", default_value_t = RPC_DEFAULT_MAX_CONNECTIONS.into())]
+    /// Maximum number of concurrent RPC server connections for both HTTP and WS, and for IPC.
+    ///
+    /// Connections past the limit are rejected with `429 Too Many Requests`.
+    #[arg(long, visible_alias = "rpc.max-connections", value_name = "COUNT", default_value_t = RPC_DEFAULT_MAX_CONNECTIONS.into())]
     pub rpc_max_connections: MaxU32,
 
     /// Maximum number of concurrent tracing requests.
@@ -184,12 +202,18 @@ impl RpcServerArgs {
     /// Returns the handles for the launched regular RPC server(s) (if any) and the server handle
     /// for the auth server that handles the `engine_` API that's accessed by the consensus
     /// layer.
+    ///
+    /// The read-only methods of `clayer_rpc` are merged into the transports that list the `clayer`
+    /// module, its admin methods into IPC and the auth server only. The methods of the auth server
+    /// are installed into `in_process_engine`, if set.
     #[allow(clippy::too_many_arguments)]
     pub async fn start_servers<Reth, Engine, Conf>(
         &self,
         components: &Reth,
         engine_api: Engine,
         jwt_secret: JwtSecret,
+        clayer_rpc: Option<ClayerRpc>,
+        in_process_engine: Option<InProcessEngine>,
         conf: &mut Conf,
     ) -> eyre::Result<RethRpcServerHandles>
     where
@@ -202,17 +226,37 @@ impl RpcServerArgs {
         let module_config = self.transport_rpc_module_config();
         debug!(target: "reth::cli", http=?module_config.http(), ws=?module_config.ws(), "Using RPC module config");
 
-        let (mut modules, auth_module, mut registry) = RpcModuleBuilder::default()
+        let (mut modules, mut auth_module, mut registry) = RpcModuleBuilder::default()
             .with_provider(components.provider())
             .with_pool(components.pool())
             .with_network(components.network())
             .with_events(components.events())
             .with_executor(components.task_executor())
-            .build_with_auth_server(module_config, engine_api);
+            .build_with_auth_server(module_config.clone(), engine_api);
 
         let rpc_components = RethRpcComponents { registry: &mut registry, modules: &mut modules };
         // apply configured customization
         conf.extend_rpc_modules(self, components, rpc_components)?;
+        if let Some(rpc) = clayer_rpc {
+            let methods: Methods = ClayerApiServer::into_rpc(rpc.clone()).into();
+            if serves_clayer(module_config.http()) {
+                modules.merge_http(methods.clone())?;
+            }
+            if serves_clayer(module_config.ws()) {
+                modules.merge_ws(methods.clone())?;
+            }
+            if serves_clayer(module_config.ipc()) {
+                modules.merge_ipc(methods)?;
+            }
+            // anyone reaching the HTTP or WS server could stall consensus with these
+            let admin_methods: Methods = ClayerAdminApiServer::into_rpc(rpc).into();
+            modules.merge_ipc(admin_methods.clone())?;
+            auth_module.module_mut().merge(admin_methods)?;
+        }
+
+        if let Some(engine) = in_process_engine {
+            engine.install(auth_module.module_mut().clone());
+        }
 
         let server_config = self.rpc_server_config();
         let launch_rpc = modules.clone().start_server(server_config).map_ok(|handle| {
@@ -404,8 +448,19 @@ impl RethRpcConfig for RpcServerArgs {
             .max_connections(self.rpc_max_connections.get())
     }
 
+    fn rpc_method_filter(&self) -> RpcMethodFilter {
+        let mut filter = RpcMethodFilter::default()
+            .deny(self.rpc_deny_methods.iter().map(|method| method.trim()));
+        if let Some(allowed) = &self.rpc_allow_methods {
+            filter = filter.allow(allowed.iter().map(|method| method.trim()));
+        }
+        filter
+    }
+
     fn rpc_server_config(&self) -> RpcServerConfig {
-        let mut config = RpcServerConfig::default().with_jwt_secret(self.rpc_secret_key());
+        let mut config = RpcServerConfig::default()
+            .with_jwt_secret(self.rpc_secret_key())
+            .with_method_filter(self.rpc_method_filter());
 
         if self.http {
             let socket_address = SocketAddr::new(self.http_addr, self.http_port);
@@ -450,6 +505,13 @@ impl RethRpcConfig for RpcServerArgs {
     }
 }
 
+/// Returns whether `selection` lists the `clayer` module.
+fn serves_clayer(selection: Option<&RpcModuleSelection>) -> bool {
+    selection.map_or(false, |selection| {
+        selection.iter_selection().any(|module| module == RethRpcModule::Clayer)
+    })
+}
+
 impl Default for RpcServerArgs {
     fn default() -> Self {
         Self {
@@ -638,6 +700,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rpc_method_filter() {
+        let args = CommandParser::<RpcServerArgs>::parse_from(["reth"]).args;
+        assert!(args.rpc_method_filter().is_empty());
+
+        let args = CommandParser::<RpcServerArgs>::parse_from([
+            "reth",
+            "--rpc.allow-methods",
+            "eth_blockNumber, eth_call,admin_addPeer",
+            "--rpc.deny-methods",
+            "admin_addPeer",
+        ])
+        .args;
+        let filter = args.rpc_method_filter();
+        assert!(filter.is_allowed("eth_blockNumber"));
+        assert!(filter.is_allowed("eth_call"));
+        assert!(!filter.is_allowed("admin_addPeer"));
+        assert!(!filter.is_allowed("eth_getBalance"));
+    }
+
     #[test]
     fn test_rpc_server_config() {
         let args = CommandParser::<RpcServerArgs>::parse_from([
@@ -699,6 +781,39 @@ mod tests {
         assert_eq!(config.max_logs_per_response, Some(200));
     }
 
+    #[test]
+    fn test_rpc_max_connections() {
+        let args = CommandParser::<RpcServerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.rpc_max_connections.get(), RPC_DEFAULT_MAX_CONNECTIONS);
+
+        let args =
+            CommandParser::<RpcServerArgs>::parse_from(["reth", "--rpc.max-connections", "2"]).args;
+        assert_eq!(args.rpc_max_connections.get(), 2);
+    }
+
+    #[test]
+    fn test_clayer_module_opt_in() {
+        let args = CommandParser::<RpcServerArgs>::parse_from(["reth", "--http", "--ws"]).args;
+        let config = args.transport_rpc_module_config();
+        assert!(!serves_clayer(config.http()));
+        assert!(!serves_clayer(config.ws()));
+        assert!(serves_clayer(config.ipc()));
+
+        let args = CommandParser::<RpcServerArgs>::parse_from([
+            "reth",
+            "--http",
+            "--http.api",
+            "eth,clayer",
+            "--ws",
+            "--ipcdisable",
+        ])
+        .args;
+        let config = args.transport_rpc_module_config();
+        assert!(serves_clayer(config.http()));
+        assert!(!serves_clayer(config.ws()));
+        assert!(!serves_clayer(config.ipc()));
+    }
+
     #[test]
     fn rpc_server_args_default_sanity_test() {
         let default_args = RpcServerArgs::default();
@@ -0,0 +1,53 @@
+//! clap [Args](clap::Args) for snapshot configuration
+
+use clap::{builder::RangedU64ValueParser, Args};
+
+/// Parameters for configuring the snapshotter
+#[derive(Debug, Args, PartialEq, Default, Clone, Copy)]
+#[clap(next_help_heading = "Snapshot")]
+pub struct SnapshotArgs {
+    /// The number of blocks per snapshot.
+    ///
+    /// Overrides the snapshot block interval of the chain spec, must be at least 1.
+    #[arg(
+        long = "snapshot.block-interval",
+        value_name = "BLOCKS",
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..)
+    )]
+    pub block_interval: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_snapshot_args() {
+        let default_args = SnapshotArgs::default();
+        let args = CommandParser::<SnapshotArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, default_args);
+
+        let args = CommandParser::<SnapshotArgs>::parse_from([
+            "reth",
+            "--snapshot.block-interval",
+            "1000",
+        ])
+        .args;
+        assert_eq!(args, SnapshotArgs { block_interval: Some(1000) });
+
+        assert!(CommandParser::<SnapshotArgs>::try_parse_from([
+            "reth",
+            "--snapshot.block-interval",
+            "0"
+        ])
+        .is_err());
+    }
+}
@@ -3,9 +3,10 @@
 use crate::cli::config::RethTransactionPoolConfig;
 use clap::Args;
 use reth_transaction_pool::{
-    LocalTransactionConfig, PoolConfig, PriceBumpConfig, SubPoolLimit, DEFAULT_PRICE_BUMP,
-    REPLACE_BLOB_PRICE_BUMP, TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER,
-    TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT, TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
+    blobstore::InMemoryBlobStoreConfig, LocalTransactionConfig, PoolConfig, PriceBumpConfig,
+    SubPoolLimit, DEFAULT_ADDITIONAL_VALIDATION_TASKS, DEFAULT_PRICE_BUMP, REPLACE_BLOB_PRICE_BUMP,
+    TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER, TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT,
+    TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
 };
 
 /// Parameters for debugging purposes
@@ -47,6 +48,27 @@ pub struct TxPoolArgs {
     /// Flag to disable local transaction exemptions.
     #[arg(long = "txpool.nolocals")]
     pub no_locals: bool,
+
+    /// Max size of the in-memory blob store in megabytes.
+    ///
+    /// Once exceeded, the oldest blobs are moved to the on-disk blob store in the datadir.
+    /// Unbounded if not set.
+    #[arg(long = "txpool.max-blob-store-size", value_name = "MB")]
+    pub max_blob_store_size: Option<usize>,
+
+    /// Number of tasks validating incoming transactions in parallel, in addition to the main
+    /// validation task.
+    #[arg(
+        long = "txpool.validation-tasks",
+        value_name = "COUNT",
+        default_value_t = DEFAULT_ADDITIONAL_VALIDATION_TASKS
+    )]
+    pub validation_tasks: usize,
+
+    /// Persist the pending and queued transactions of the pool on shutdown and re-inject them on
+    /// the next startup, after validating them against the current state.
+    #[arg(long = "txpool.persist", default_value_t = false)]
+    pub persist: bool,
 }
 
 impl Default for TxPoolArgs {
@@ -62,6 +84,20 @@ impl Default for TxPoolArgs {
             price_bump: DEFAULT_PRICE_BUMP,
             blob_transaction_price_bump: REPLACE_BLOB_PRICE_BUMP,
             no_locals: false,
+            max_blob_store_size: None,
+            validation_tasks: DEFAULT_ADDITIONAL_VALIDATION_TASKS,
+            persist: false,
+        }
+    }
+}
+
+impl TxPoolArgs {
+    /// Returns the limits of the in-memory blob store.
+    pub fn blob_store_config(&self) -> InMemoryBlobStoreConfig {
+        let config = InMemoryBlobStoreConfig::default();
+        match self.max_blob_store_size {
+            Some(max_size) => config.with_max_size(max_size * 1024 * 1024),
+            None => config,
         }
     }
 }
@@ -114,4 +150,36 @@ mod tests {
         let args = CommandParser::<TxPoolArgs>::parse_from(["reth"]).args;
         assert_eq!(args, default_args);
     }
+
+    #[test]
+    fn parse_max_blob_store_size() {
+        let args = CommandParser::<TxPoolArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.blob_store_config(), InMemoryBlobStoreConfig::default());
+
+        let args =
+            CommandParser::<TxPoolArgs>::parse_from(["reth", "--txpool.max-blob-store-size", "64"])
+                .args;
+        assert_eq!(args.max_blob_store_size, Some(64));
+        assert_eq!(args.blob_store_config().max_size, Some(64 * 1024 * 1024));
+    }
+
+    #[test]
+    fn parse_validation_tasks() {
+        let args = CommandParser::<TxPoolArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.validation_tasks, DEFAULT_ADDITIONAL_VALIDATION_TASKS);
+
+        let args =
+            CommandParser::<TxPoolArgs>::parse_from(["reth", "--txpool.validation-tasks", "4"])
+                .args;
+        assert_eq!(args.validation_tasks, 4);
+    }
+
+    #[test]
+    fn parse_persist() {
+        let args = CommandParser::<TxPoolArgs>::parse_from(["reth"]).args;
+        assert!(!args.persist);
+
+        let args = CommandParser::<TxPoolArgs>::parse_from(["reth", "--txpool.persist"]).args;
+        assert!(args.persist);
+    }
 }
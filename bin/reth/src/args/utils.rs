@@ -1,5 +1,6 @@
 //! Clap parser utilities
 
+use crate::prometheus_exporter::MetricsEndpoint;
 use reth_primitives::{fs, AllGenesisFormats, BlockHashOrNumber, ChainSpec, B256};
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs},
@@ -158,6 +159,20 @@ pub fn parse_socket_address(value: &str) -> eyre::Result<SocketAddr, SocketAddre
         .ok_or_else(|| SocketAddressParsingError::Parse(value.to_string()))
 }
 
+/// Parse a [MetricsEndpoint] from a `str`.
+///
+/// Values of the format `unix:<path>` are the path of a Unix socket, everything else is parsed
+/// with [parse_socket_address].
+pub fn parse_metrics_endpoint(
+    value: &str,
+) -> eyre::Result<MetricsEndpoint, SocketAddressParsingError> {
+    match value.strip_prefix("unix:") {
+        Some("") => Err(SocketAddressParsingError::Empty),
+        Some(path) => Ok(MetricsEndpoint::Unix(PathBuf::from(path))),
+        None => parse_socket_address(value).map(MetricsEndpoint::Tcp),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -289,4 +304,17 @@ mod tests {
             assert_eq!(socket_addr.port(), port);
         }
     }
+
+    #[test]
+    fn parse_metrics_endpoints() {
+        assert_eq!(
+            parse_metrics_endpoint("unix:/run/reth/metrics.sock").unwrap(),
+            MetricsEndpoint::Unix(PathBuf::from("/run/reth/metrics.sock"))
+        );
+        assert_eq!(
+            parse_metrics_endpoint(":9001").unwrap(),
+            MetricsEndpoint::Tcp(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9001))
+        );
+        assert!(matches!(parse_metrics_endpoint("unix:"), Err(SocketAddressParsingError::Empty)));
+    }
 }
@@ -0,0 +1,49 @@
+//! `reth clayer` command.
+use clap::{Parser, Subcommand};
+
+use crate::runner::CliContext;
+
+mod replay;
+mod replay_messages;
+mod state;
+mod verify_chain;
+
+/// `reth clayer` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+/// `reth clayer` subcommands
+#[derive(Subcommand, Debug)]
+pub enum Subcommands {
+    /// Replay the committed chain against an execution layer.
+    Replay(replay::Command),
+    /// Audit the committed chain against an execution layer.
+    VerifyChain(verify_chain::Command),
+    /// Replay a consensus message log through a fresh consensus engine.
+    ReplayMessages(replay_messages::Command),
+    /// Export the consensus state for a migration to another node.
+    ExportState(state::ExportCommand),
+    /// Import the consensus state exported on another node.
+    ///
+    /// The PBFT view, sequence number and phase are not part of the state. The imported node
+    /// restarts at the block after its committed head, in the view of that block's seal. This is
+    /// safe because the other validators ignore its messages for an older view, it only votes
+    /// again once it caught up to the current view through their view changes.
+    ImportState(state::ImportCommand),
+}
+
+impl Command {
+    /// Execute `clayer` command
+    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
+        match self.command {
+            Subcommands::Replay(command) => command.execute(ctx).await,
+            Subcommands::VerifyChain(command) => command.execute(ctx).await,
+            Subcommands::ReplayMessages(command) => command.execute(ctx).await,
+            Subcommands::ExportState(command) => command.execute(ctx).await,
+            Subcommands::ImportState(command) => command.execute(ctx).await,
+        }
+    }
+}
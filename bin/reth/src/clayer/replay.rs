@@ -0,0 +1,125 @@
+use crate::{
+    args::utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
+    dirs::{DataDirPath, MaybePlatformPath},
+    runner::CliContext,
+};
+use clap::Parser;
+use reth_clayer::{create_sync_api_with_addr, ChainReplayer};
+use reth_db::open_db_read_only;
+use reth_primitives::{BlockNumber, ChainSpec};
+use reth_provider::{BlockNumReader, ProviderFactory};
+use reth_rpc::JwtSecret;
+use reth_rpc_builder::constants;
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+};
+use tracing::*;
+
+/// `reth clayer replay` command
+///
+/// Reads the committed chain from the local database and drives the execution layer listening
+/// on `--authrpc.addr`/`--authrpc.port` to the same tip via `engine_newPayloadV2` and
+/// `engine_forkchoiceUpdatedV2`.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    /// Auth server address of the execution layer to replay against
+    #[arg(long = "authrpc.addr", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    auth_addr: IpAddr,
+
+    /// Auth server port of the execution layer to replay against
+    #[arg(long = "authrpc.port", default_value_t = constants::DEFAULT_AUTH_PORT)]
+    auth_port: u16,
+
+    /// Path to the JWT secret of the execution layer's auth server
+    #[arg(long = "authrpc.jwtsecret", value_name = "PATH")]
+    auth_jwtsecret: PathBuf,
+
+    /// Directory of the consensus database if the node runs with `--clayer.state-dir`
+    #[arg(long = "clayer.state-dir", value_name = "PATH")]
+    state_dir: Option<PathBuf>,
+
+    /// The first block to replay
+    #[arg(long, value_name = "BLOCK", default_value_t = 1)]
+    from: BlockNumber,
+
+    /// The last block to replay. Defaults to the best block of the local database.
+    #[arg(long, value_name = "BLOCK")]
+    to: Option<BlockNumber>,
+}
+
+impl Command {
+    /// Execute `clayer replay` command
+    pub async fn execute(self, _ctx: CliContext) -> eyre::Result<()> {
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db = Arc::new(open_db_read_only(&data_dir.db_path(), None)?);
+        let factory = ProviderFactory::new(db, self.chain.clone());
+
+        let to = match self.to {
+            Some(to) => to,
+            None => factory.best_block_number()?,
+        };
+        if self.from > to {
+            eyre::bail!("Nothing to replay: --from {} is past the last block {}", self.from, to);
+        }
+
+        let jwt_secret = JwtSecret::from_file(&self.auth_jwtsecret)?;
+        let api = create_sync_api_with_addr(
+            SocketAddr::new(self.auth_addr, self.auth_port),
+            jwt_secret.as_bytes(),
+        );
+        // the seals are stored apart from the blocks if the node runs with a state directory
+        let seals = match &self.state_dir {
+            Some(state_dir) => ProviderFactory::new(
+                Arc::new(open_db_read_only(state_dir, None)?),
+                self.chain.clone(),
+            ),
+            None => factory.clone(),
+        };
+        let replayer = ChainReplayer::new(factory, seals, Arc::new(api));
+
+        info!(target: "reth::cli", from = self.from, to, "Replaying committed chain");
+        let from = self.from;
+        // the engine API client is blocking
+        let tip = tokio::task::spawn_blocking(move || {
+            replayer.replay(from, to, |progress| {
+                info!(target: "reth::cli", number = progress.number, hash = ?progress.hash, target = progress.target, "Replayed block");
+            })
+        })
+        .await??;
+
+        match tip {
+            Some(tip) => {
+                info!(target: "reth::cli", number = tip.number, hash = ?tip.hash, "Execution layer reached the committed tip")
+            }
+            None => {
+                warn!(target: "reth::cli", from, "Block {} is not committed, nothing replayed", from)
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    args::{
+        get_secret_key,
+        utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
+    },
+    dirs::{DataDirPath, MaybePlatformPath},
+    runner::CliContext,
+};
+use clap::Parser;
+use reth_blockchain_tree::noop::NoopBlockchainTree;
+use reth_clayer::{
+    create_sync_api_with_addr, read_message_log, LocalSigner, MessageDirection, MessageReplayer,
+    PbftConfig,
+};
+use reth_db::{init_db, open_db_read_only};
+use reth_primitives::{BlockNumber, ChainSpec, PeerId};
+use reth_provider::{
+    providers::BlockchainProvider, ConsensusNumberReader, ConsensusNumberWriter, ConsensusProvider,
+    HeaderProvider, ProviderFactory,
+};
+use reth_rpc::JwtSecret;
+use reth_rpc_builder::constants;
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+};
+use tracing::*;
+
+/// `reth clayer replay-messages` command
+///
+/// Feeds the consensus messages recorded with `--clayer.message-log` to a consensus engine with a
+/// fresh state, starting at a committed block of the local database, to reproduce a consensus bug
+/// deterministically. The engine signs its own messages with the node key again and drives the
+/// execution layer listening on `--authrpc.addr`/`--authrpc.port`. The consensus state of the
+/// local database is not modified.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    /// Directory of the consensus database if the node runs with `--clayer.state-dir`
+    #[arg(long = "clayer.state-dir", value_name = "PATH")]
+    state_dir: Option<PathBuf>,
+
+    /// Secret key of the node that recorded the log. Defaults to the key in the data dir.
+    #[arg(long, value_name = "PATH")]
+    p2p_secret_key: Option<PathBuf>,
+
+    /// The validators, defaults to the ones of the voting contract at the `--from` block
+    #[arg(long, value_delimiter = ',')]
+    validators: Vec<PeerId>,
+
+    /// Auth server address of the execution layer to replay against
+    #[arg(long = "authrpc.addr", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    auth_addr: IpAddr,
+
+    /// Auth server port of the execution layer to replay against
+    #[arg(long = "authrpc.port", default_value_t = constants::DEFAULT_AUTH_PORT)]
+    auth_port: u16,
+
+    /// Path to the JWT secret of the execution layer's auth server
+    #[arg(long = "authrpc.jwtsecret", value_name = "PATH")]
+    auth_jwtsecret: PathBuf,
+
+    /// The committed block to start the replay at. Defaults to the parent of the first block a
+    /// received message of the log is about.
+    #[arg(long, value_name = "BLOCK")]
+    from: Option<BlockNumber>,
+
+    /// The message log to replay
+    #[arg(value_name = "LOG")]
+    log: PathBuf,
+}
+
+impl Command {
+    /// Execute `clayer replay-messages` command
+    pub async fn execute(self, _ctx: CliContext) -> eyre::Result<()> {
+        let records = read_message_log(&self.log)?;
+        let from = match self.from {
+            Some(from) => from,
+            None => records
+                .iter()
+                .find(|record| record.direction == MessageDirection::Inbound)
+                .map(|record| record.seq_num.saturating_sub(1))
+                .ok_or_else(|| eyre::eyre!("{:?} has no received messages", self.log))?,
+        };
+
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db = Arc::new(open_db_read_only(&data_dir.db_path(), None)?);
+        let factory = ProviderFactory::new(db, self.chain.clone());
+        let head =
+            factory.sealed_header(from)?.ok_or_else(|| eyre::eyre!("Block {} not found", from))?;
+
+        // the engine writes to its consensus database, only the seal of the first block is copied
+        // from the one of the node
+        let state_db_path = self.state_dir.unwrap_or_else(|| data_dir.db_path());
+        let state_db = Arc::new(open_db_read_only(&state_db_path, None)?);
+        let node_storages =
+            ConsensusProvider::new(ProviderFactory::new(state_db, self.chain.clone()))?;
+        let replay_dir = tempfile::tempdir()?;
+        let replay_db = Arc::new(init_db(replay_dir.path(), None)?);
+        let storages = ConsensusProvider::new(ProviderFactory::new(replay_db, self.chain.clone()))?;
+        if let Some(seal) = node_storages.consensus_content(head.hash)? {
+            storages.save_consensus_content(head.hash, seal)?;
+        }
+
+        let secret_key_path = self.p2p_secret_key.unwrap_or_else(|| data_dir.p2p_secret_path());
+        let signer = Arc::new(LocalSigner::new(get_secret_key(&secret_key_path)?));
+        let config = PbftConfig { members: self.validators, ..Default::default() };
+        let jwt_secret = JwtSecret::from_file(&self.auth_jwtsecret)?;
+        let api = create_sync_api_with_addr(
+            SocketAddr::new(self.auth_addr, self.auth_port),
+            jwt_secret.as_bytes(),
+        );
+        let client = BlockchainProvider::new(factory, NoopBlockchainTree::default())?;
+
+        info!(target: "reth::cli", log = ?self.log, records = records.len(), from, "Replaying consensus messages");
+        // the engine API client is blocking
+        let (summary, state) = tokio::task::spawn_blocking(move || {
+            let mut replayer = MessageReplayer::new(
+                signer,
+                &config,
+                &head,
+                Arc::new(api),
+                Arc::new(storages),
+                client,
+            )?;
+            let summary = replayer.replay(records);
+            eyre::Ok((summary, replayer.state().to_string()))
+        })
+        .await??;
+
+        info!(target: "reth::cli", replayed = summary.replayed, rejected = summary.rejected, skipped = summary.skipped, %state, "Replayed consensus messages");
+        Ok(())
+    }
+}
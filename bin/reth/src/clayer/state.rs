@@ -0,0 +1,198 @@
+use crate::{
+    args::utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
+    dirs::{DataDirPath, MaybePlatformPath},
+    runner::CliContext,
+};
+use clap::Parser;
+use reth_db::{init_db, models::consensus::ConsensusBytes, open_db_read_only};
+use reth_primitives::{fs, BlockNumber, Bytes, ChainSpec, B256};
+use reth_provider::{ConsensusProvider, ConsensusState, ProviderFactory};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+use tracing::*;
+
+/// The consensus state as written by `reth clayer export-state`.
+#[derive(Debug, Serialize, Deserialize)]
+struct StateFile {
+    /// The chain the state was exported from
+    chain_id: u64,
+    numbers: Vec<(B256, BlockNumber)>,
+    contents: Vec<(B256, Bytes)>,
+    committed_payloads: Vec<(BlockNumber, Bytes)>,
+    view_changes: Vec<(u64, Bytes)>,
+    /// Missing in files exported before randao mixes were recorded
+    #[serde(default)]
+    randao_mixes: Vec<(BlockNumber, B256)>,
+}
+
+impl StateFile {
+    fn new(chain_id: u64, state: ConsensusState) -> Self {
+        let bytes = |content: ConsensusBytes| Bytes::from(content.content);
+        Self {
+            chain_id,
+            numbers: state.numbers,
+            contents: state.contents.into_iter().map(|(hash, c)| (hash, bytes(c))).collect(),
+            committed_payloads: state
+                .committed_payloads
+                .into_iter()
+                .map(|(number, c)| (number, bytes(c)))
+                .collect(),
+            view_changes: state
+                .view_changes
+                .into_iter()
+                .map(|(view, c)| (view, bytes(c)))
+                .collect(),
+            randao_mixes: state.randao_mixes,
+        }
+    }
+
+    fn into_state(self) -> ConsensusState {
+        let content = |bytes: Bytes| ConsensusBytes { content: bytes.to_vec() };
+        ConsensusState {
+            numbers: self.numbers,
+            contents: self.contents.into_iter().map(|(hash, b)| (hash, content(b))).collect(),
+            committed_payloads: self
+                .committed_payloads
+                .into_iter()
+                .map(|(number, b)| (number, content(b)))
+                .collect(),
+            view_changes: self
+                .view_changes
+                .into_iter()
+                .map(|(view, b)| (view, content(b)))
+                .collect(),
+            randao_mixes: self.randao_mixes,
+        }
+    }
+}
+
+/// `reth clayer export-state` command
+///
+/// Writes the consensus tables of the local database, the consensus numbers, seals, committed
+/// payloads and view changes, to a JSON file to migrate a validator to another node with
+/// `reth clayer import-state`. The node key is not exported, it has to be moved separately.
+#[derive(Debug, Parser)]
+pub struct ExportCommand {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    /// Directory of the consensus database if the node runs with `--clayer.state-dir`
+    #[arg(long = "clayer.state-dir", value_name = "PATH")]
+    state_dir: Option<PathBuf>,
+
+    /// The file to write the consensus state to
+    #[arg(value_name = "FILE")]
+    path: PathBuf,
+}
+
+impl ExportCommand {
+    /// Execute `clayer export-state` command
+    pub async fn execute(self, _ctx: CliContext) -> eyre::Result<()> {
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db_path = self.state_dir.unwrap_or_else(|| data_dir.db_path());
+        let db = Arc::new(open_db_read_only(&db_path, None)?);
+        let provider = ConsensusProvider::new(ProviderFactory::new(db, self.chain.clone()))?;
+
+        let state = provider.export_state()?;
+        let file = StateFile::new(self.chain.chain.id(), state);
+        fs::write(&self.path, serde_json::to_vec(&file)?)?;
+
+        info!(target: "reth::cli", path = ?self.path, blocks = file.numbers.len(), payloads = file.committed_payloads.len(), view_changes = file.view_changes.len(), "Exported consensus state");
+        Ok(())
+    }
+}
+
+/// `reth clayer import-state` command
+///
+/// Writes the consensus state exported with `reth clayer export-state` to the local database.
+/// Refuses to overwrite an existing consensus state unless `--force` is set. The node must not be
+/// running.
+///
+/// The PBFT view, sequence number and phase are not exported, they are only kept in memory. The
+/// imported node restarts at the block after its committed head, in the view of that block's
+/// seal. This is safe because the other validators ignore its messages for an older view, it only
+/// votes again once it caught up to the current view through their view changes.
+#[derive(Debug, Parser)]
+pub struct ImportCommand {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    /// Directory of the consensus database if the node runs with `--clayer.state-dir`
+    #[arg(long = "clayer.state-dir", value_name = "PATH")]
+    state_dir: Option<PathBuf>,
+
+    /// Merge into the consensus state of the local database instead of refusing to import
+    #[arg(long)]
+    force: bool,
+
+    /// The file to read the consensus state from
+    #[arg(value_name = "FILE")]
+    path: PathBuf,
+}
+
+impl ImportCommand {
+    /// Execute `clayer import-state` command
+    pub async fn execute(self, _ctx: CliContext) -> eyre::Result<()> {
+        let file: StateFile = serde_json::from_str(&fs::read_to_string(&self.path)?)?;
+        if file.chain_id != self.chain.chain.id() {
+            eyre::bail!(
+                "Consensus state was exported from chain {}, not {}",
+                file.chain_id,
+                self.chain.chain.id()
+            );
+        }
+
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db_path = self.state_dir.unwrap_or_else(|| data_dir.db_path());
+        fs::create_dir_all(&db_path)?;
+        let db = Arc::new(init_db(db_path, None)?);
+        let provider = ConsensusProvider::new(ProviderFactory::new(db, self.chain.clone()))?;
+
+        if !self.force && !provider.export_state()?.is_empty() {
+            eyre::bail!("The database already has a consensus state, use --force to merge into it");
+        }
+        let state = file.into_state();
+        provider.import_state(&state)?;
+
+        info!(target: "reth::cli", path = ?self.path, blocks = state.numbers.len(), payloads = state.committed_payloads.len(), view_changes = state.view_changes.len(), "Imported consensus state");
+        Ok(())
+    }
+}
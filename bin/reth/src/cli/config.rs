@@ -1,6 +1,7 @@
 //! Config traits for various node components.
 
 use alloy_rlp::Encodable;
+use reth_basic_payload_builder::{GasTarget, TxOrdering};
 use reth_network::protocol::IntoRlpxSubProtocol;
 use reth_primitives::{Bytes, BytesMut};
 use reth_rpc::{
@@ -8,8 +9,8 @@ use reth_rpc::{
     JwtError, JwtSecret,
 };
 use reth_rpc_builder::{
-    auth::AuthServerConfig, error::RpcError, EthConfig, IpcServerBuilder, RpcServerConfig,
-    ServerBuilder, TransportRpcModuleConfig,
+    auth::AuthServerConfig, error::RpcError, EthConfig, IpcServerBuilder, RpcMethodFilter,
+    RpcServerConfig, ServerBuilder, TransportRpcModuleConfig,
 };
 use reth_transaction_pool::PoolConfig;
 use std::{borrow::Cow, path::PathBuf, time::Duration};
@@ -52,6 +53,9 @@ pub trait RethRpcConfig {
     /// Returns the default ipc server builder
     fn ipc_server_builder(&self) -> IpcServerBuilder;
 
+    /// Returns the filter of the individual methods served by all transports.
+    fn rpc_method_filter(&self) -> RpcMethodFilter;
+
     /// Creates the [RpcServerConfig] from cli args.
     fn rpc_server_config(&self) -> RpcServerConfig;
 
@@ -106,6 +110,12 @@ pub trait PayloadBuilderConfig {
     /// Maximum number of tasks to spawn for building a payload.
     fn max_payload_tasks(&self) -> usize;
 
+    /// The order of the transactions in built payloads.
+    fn tx_ordering(&self) -> TxOrdering;
+
+    /// The gas built payloads are filled up to, `None` fills them up to the gas limit.
+    fn gas_target(&self) -> Option<GasTarget>;
+
     /// Returns whether or not to construct the pending block.
     #[cfg(feature = "optimism")]
     fn compute_pending_block(&self) -> bool;
@@ -144,10 +144,11 @@ pub trait RethNodeCommandConfig: fmt::Debug {
         let payload_job_config =
             payload_job_config.compute_pending_block(conf.compute_pending_block());
 
-        // The default payload builder is implemented on the unit type.
+        // The default payload builder orders and fills payloads as configured.
         #[cfg(not(feature = "optimism"))]
-        #[allow(clippy::let_unit_value)]
-        let payload_builder = reth_basic_payload_builder::EthereumPayloadBuilder::default();
+        let payload_builder = reth_basic_payload_builder::EthereumPayloadBuilder::default()
+            .with_ordering(conf.tx_ordering())
+            .with_gas_target(conf.gas_target());
 
         // Optimism's payload builder is implemented on the OptimismPayloadBuilder type.
         #[cfg(feature = "optimism")]
@@ -1,7 +1,7 @@
 //! CLI definition and entrypoint to executable
 use crate::{
     args::utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
-    chain,
+    chain, clayer,
     cli::ext::RethCliExt,
     db, debug_cmd,
     dirs::{LogsDir, PlatformPath},
@@ -15,7 +15,7 @@ use reth_primitives::ChainSpec;
 use reth_tracing::{
     tracing::{metadata::LevelFilter, Level, Subscriber},
     tracing_subscriber::{filter::Directive, registry::LookupSpan, EnvFilter},
-    BoxedLayer, FileWorkerGuard,
+    BoxedLayer, FileWorkerGuard, TargetLevel, TargetLevelFilter,
 };
 use std::{fmt, fmt::Display, sync::Arc};
 
@@ -81,6 +81,11 @@ impl<Ext: RethCliExt> Cli<Ext> {
         self.logs.log_file_directory =
             self.logs.log_file_directory.join(self.chain.chain.to_string());
 
+        // the node adjusts the level of the consensus logs at runtime
+        if let Commands::Node(command) = &mut self.command {
+            command.consensus_log_level = command.clayer.consensus_log_level();
+        }
+
         let _guard = self.init_tracing()?;
 
         let runner = CliRunner;
@@ -95,6 +100,7 @@ impl<Ext: RethCliExt> Cli<Ext> {
             Commands::Config(command) => runner.run_until_ctrl_c(command.execute()),
             Commands::Debug(command) => runner.run_command_until_exit(|ctx| command.execute(ctx)),
             Commands::Recover(command) => runner.run_command_until_exit(|ctx| command.execute(ctx)),
+            Commands::Clayer(command) => runner.run_command_until_exit(|ctx| command.execute(ctx)),
         }
     }
 
@@ -103,10 +109,17 @@ impl<Ext: RethCliExt> Cli<Ext> {
     /// If file logging is enabled, this function returns a guard that must be kept alive to ensure
     /// that all logs are flushed to disk.
     pub fn init_tracing(&self) -> eyre::Result<Option<FileWorkerGuard>> {
-        let mut layers =
-            vec![reth_tracing::stdout(self.verbosity.directive(), &self.logs.color.to_string())];
+        let consensus_log_level = match &self.command {
+            Commands::Node(command) => command.consensus_log_level.clone(),
+            _ => None,
+        };
+        let mut layers = vec![reth_tracing::stdout_with_target_level(
+            self.verbosity.directive(),
+            &self.logs.color.to_string(),
+            consensus_log_level.clone(),
+        )];
 
-        let (additional_layers, guard) = self.logs.layers()?;
+        let (additional_layers, guard) = self.logs.layers(consensus_log_level)?;
         layers.extend(additional_layers);
 
         reth_tracing::init(layers);
@@ -162,6 +175,9 @@ pub enum Commands<Ext: RethCliExt = ()> {
     /// Scripts for node recovery
     #[command(name = "recover")]
     Recover(recover::Command),
+    /// Consensus layer utilities
+    #[command(name = "clayer")]
+    Clayer(clayer::Command),
 }
 
 impl<Ext: RethCliExt> Commands<Ext> {
@@ -225,7 +241,12 @@ const MB_TO_BYTES: u64 = 1024 * 1024;
 
 impl Logs {
     /// Builds tracing layers from the current log options.
-    pub fn layers<S>(&self) -> eyre::Result<(Vec<BoxedLayer<S>>, Option<FileWorkerGuard>)>
+    ///
+    /// The events of the target of `target_level` are filtered by its level in every layer.
+    pub fn layers<S>(
+        &self,
+        target_level: Option<TargetLevel>,
+    ) -> eyre::Result<(Vec<BoxedLayer<S>>, Option<FileWorkerGuard>)>
     where
         S: Subscriber,
         for<'a> S: LookupSpan<'a>,
@@ -247,7 +268,10 @@ impl Logs {
 
         // Create and add the journald layer if enabled
         if self.journald {
-            let journald_filter = create_env_filter(&self.journald_filter)?;
+            let journald_filter = TargetLevelFilter::new(
+                create_env_filter(&self.journald_filter)?,
+                target_level.clone(),
+            );
             layers.push(
                 reth_tracing::journald(journald_filter).expect("Could not connect to journald"),
             );
@@ -255,7 +279,8 @@ impl Logs {
 
         // Create and add the file logging layer if enabled
         let file_guard = if self.log_file_max_files > 0 {
-            let file_filter = create_env_filter(&self.log_file_filter)?;
+            let file_filter =
+                TargetLevelFilter::new(create_env_filter(&self.log_file_filter)?, target_level);
             let (layer, guard) = reth_tracing::file(
                 file_filter,
                 &self.log_file_directory,
@@ -17,10 +17,11 @@ use clap::Parser;
 use reth_db::{
     cursor::DbCursorRO, database::Database, open_db_read_only, table::Table, transaction::DbTx,
     AccountChangeSet, AccountHistory, AccountsTrie, BlockBodyIndices, BlockOmmers,
-    BlockWithdrawals, Bytecodes, CanonicalHeaders, ConsensusContent, ConsensusNumber, DatabaseEnv,
-    HashedAccount, HashedStorage, HeaderNumbers, HeaderTD, Headers, PlainAccountState,
-    PlainStorageState, PruneCheckpoints, Receipts, StorageChangeSet, StorageHistory, StoragesTrie,
-    SyncStage, SyncStageProgress, Tables, TransactionBlock, Transactions, TxHashNumber, TxSenders,
+    BlockWithdrawals, Bytecodes, CanonicalHeaders, CommittedPayloads, ConsensusContent,
+    ConsensusNumber, DatabaseEnv, HashedAccount, HashedStorage, HeaderNumbers, HeaderTD, Headers,
+    PlainAccountState, PlainStorageState, PruneCheckpoints, RandaoMixes, Receipts,
+    StorageChangeSet, StorageHistory, StoragesTrie, SyncStage, SyncStageProgress, Tables,
+    TransactionBlock, Transactions, TxHashNumber, TxSenders, ViewChanges,
 };
 use tracing::info;
 
@@ -146,6 +147,15 @@ impl Command {
                 Tables::ConsensusContent => {
                     find_diffs::<ConsensusContent>(primary_tx, secondary_tx, output_dir)?
                 }
+                Tables::CommittedPayloads => {
+                    find_diffs::<CommittedPayloads>(primary_tx, secondary_tx, output_dir)?
+                }
+                Tables::ViewChanges => {
+                    find_diffs::<ViewChanges>(primary_tx, secondary_tx, output_dir)?
+                }
+                Tables::RandaoMixes => {
+                    find_diffs::<RandaoMixes>(primary_tx, secondary_tx, output_dir)?
+                }
             };
         }
 
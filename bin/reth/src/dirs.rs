@@ -293,6 +293,27 @@ impl<D> ChainPath<D> {
         self.0.join("blobstore").into()
     }
 
+    /// Returns the path to the file the transaction pool is persisted to across restarts.
+    ///
+    /// `<DIR>/<CHAIN_ID>/txpool-transactions.rlp`
+    pub fn txpool_transactions_path(&self) -> PathBuf {
+        self.0.join("txpool-transactions.rlp").into()
+    }
+
+    /// Returns the path to the log file where consensus messages are recorded.
+    ///
+    /// `<DIR>/<CHAIN_ID>/clayer/messages.log`
+    pub fn clayer_message_log_path(&self) -> PathBuf {
+        self.0.join("clayer").join("messages.log").into()
+    }
+
+    /// Returns the path to the file where the identity of the execution layer is recorded.
+    ///
+    /// `<DIR>/<CHAIN_ID>/clayer/el-identity.json`
+    pub fn clayer_el_identity_path(&self) -> PathBuf {
+        self.0.join("clayer").join("el-identity.json").into()
+    }
+
     /// Returns the path to the config file for this chain.
     ///
     /// `<DIR>/<CHAIN_ID>/reth.toml`
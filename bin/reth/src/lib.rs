@@ -30,6 +30,7 @@
 
 pub mod args;
 pub mod chain;
+pub mod clayer;
 pub mod cli;
 pub mod config;
 pub mod db;
@@ -0,0 +1,202 @@
+//! Alerts about database growth.
+
+use reth_db::database_metrics::DatabaseMetrics;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Default interval of checking the database metrics against the thresholds.
+pub const DEFAULT_DB_MONITOR_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A threshold crossed by the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbGrowthAlert {
+    /// The number of free pages dropped below the threshold.
+    LowFreePages {
+        /// Pages on the freelist.
+        free_pages: u64,
+        /// The configured minimum.
+        threshold: u64,
+    },
+    /// The size of all tables reached the threshold.
+    SizeLimit {
+        /// Size of all tables in bytes.
+        size: u64,
+        /// The configured limit in bytes.
+        threshold: u64,
+    },
+}
+
+impl DbGrowthAlert {
+    /// Returns the label of the alert in the `db.growth_alerts` counter.
+    fn kind(&self) -> &'static str {
+        match self {
+            DbGrowthAlert::LowFreePages { .. } => "low_free_pages",
+            DbGrowthAlert::SizeLimit { .. } => "size_limit",
+        }
+    }
+}
+
+/// Periodically checks the [DatabaseMetrics] of a database and warns once a threshold is crossed.
+///
+/// An alert fires once when its threshold is crossed and again only after the database recovered
+/// in between.
+#[derive(Debug)]
+pub struct DbGrowthMonitor<DB> {
+    db: DB,
+    interval: Duration,
+    min_free_pages: Option<u64>,
+    max_size: Option<u64>,
+    low_free_pages: bool,
+    size_exceeded: bool,
+}
+
+impl<DB: DatabaseMetrics> DbGrowthMonitor<DB> {
+    /// Creates a monitor without thresholds, checking the database every `interval`.
+    pub fn new(db: DB, interval: Duration) -> Self {
+        Self {
+            db,
+            interval,
+            min_free_pages: None,
+            max_size: None,
+            low_free_pages: false,
+            size_exceeded: false,
+        }
+    }
+
+    /// Alerts when fewer than `min_free_pages` pages are on the freelist.
+    pub fn with_min_free_pages(mut self, min_free_pages: Option<u64>) -> Self {
+        self.min_free_pages = min_free_pages;
+        self
+    }
+
+    /// Alerts when the tables take up at least `max_size` bytes.
+    pub fn with_max_size(mut self, max_size: Option<u64>) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Returns `true` if any threshold is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.min_free_pages.is_some() || self.max_size.is_some()
+    }
+
+    /// Reads the current metrics and returns the alerts whose threshold was crossed since the
+    /// last check.
+    pub fn check(&mut self) -> Vec<DbGrowthAlert> {
+        let mut free_pages = None;
+        let mut size = 0;
+        for (name, value, _) in self.db.gauge_metrics() {
+            match name {
+                "db.freelist" => free_pages = Some(value as u64),
+                "db.table_size" => size += value as u64,
+                _ => {}
+            }
+        }
+
+        let mut alerts = Vec::new();
+        if let (Some(threshold), Some(free_pages)) = (self.min_free_pages, free_pages) {
+            let low = free_pages < threshold;
+            if low && !self.low_free_pages {
+                alerts.push(DbGrowthAlert::LowFreePages { free_pages, threshold });
+            }
+            self.low_free_pages = low;
+        }
+        if let Some(threshold) = self.max_size {
+            let exceeded = size >= threshold;
+            if exceeded && !self.size_exceeded {
+                alerts.push(DbGrowthAlert::SizeLimit { size, threshold });
+            }
+            self.size_exceeded = exceeded;
+        }
+        alerts
+    }
+
+    /// Checks the database every interval until the node shuts down.
+    pub async fn run(mut self) {
+        info!(target: "reth::cli", interval = ?self.interval, min_free_pages = ?self.min_free_pages, max_size = ?self.max_size, "Monitoring database growth");
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            for alert in self.check() {
+                metrics::increment_counter!("db.growth_alerts", "kind" => alert.kind());
+                match alert {
+                    DbGrowthAlert::LowFreePages { free_pages, threshold } => {
+                        warn!(target: "reth::cli", free_pages, threshold, "Database free pages dropped below the threshold")
+                    }
+                    DbGrowthAlert::SizeLimit { size, threshold } => {
+                        warn!(target: "reth::cli", size, threshold, "Database size reached the threshold")
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics::Label;
+    use std::cell::Cell;
+
+    /// Reports the freelist and a single table of the given size
+    #[derive(Default)]
+    struct MockMetrics(Cell<(u64, u64)>);
+
+    impl MockMetrics {
+        fn set(&self, free_pages: u64, size: u64) {
+            self.0.set((free_pages, size));
+        }
+    }
+
+    impl DatabaseMetrics for &MockMetrics {
+        fn gauge_metrics(&self) -> Vec<(&'static str, f64, Vec<Label>)> {
+            let (free_pages, size) = self.0.get();
+            vec![
+                ("db.table_size", size as f64, vec![Label::new("table", "Headers")]),
+                ("db.freelist", free_pages as f64, vec![]),
+            ]
+        }
+    }
+
+    #[test]
+    fn alerts_when_thresholds_are_crossed() {
+        let db = MockMetrics::default();
+        let mut monitor = DbGrowthMonitor::new(&db, DEFAULT_DB_MONITOR_INTERVAL)
+            .with_min_free_pages(Some(100))
+            .with_max_size(Some(1_000));
+        assert!(monitor.is_enabled());
+
+        db.set(500, 10);
+        assert!(monitor.check().is_empty());
+
+        db.set(50, 1_000);
+        assert_eq!(
+            monitor.check(),
+            vec![
+                DbGrowthAlert::LowFreePages { free_pages: 50, threshold: 100 },
+                DbGrowthAlert::SizeLimit { size: 1_000, threshold: 1_000 },
+            ]
+        );
+        // still below the thresholds, no repeated alert
+        db.set(40, 1_100);
+        assert!(monitor.check().is_empty());
+
+        // recovered and crossed again
+        db.set(200, 1_100);
+        assert!(monitor.check().is_empty());
+        db.set(10, 1_100);
+        assert_eq!(
+            monitor.check(),
+            vec![DbGrowthAlert::LowFreePages { free_pages: 10, threshold: 100 }]
+        );
+    }
+
+    #[test]
+    fn disabled_without_thresholds() {
+        let db = MockMetrics::default();
+        let mut monitor = DbGrowthMonitor::new(&db, DEFAULT_DB_MONITOR_INTERVAL);
+        assert!(!monitor.is_enabled());
+        db.set(0, u64::MAX / 2);
+        assert!(monitor.check().is_empty());
+    }
+}
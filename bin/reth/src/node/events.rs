@@ -203,6 +203,9 @@ impl<DB> NodeState<DB> {
             PrunerEvent::Finished { tip_block_number, elapsed, stats } => {
                 info!(tip_block_number, ?elapsed, ?stats, "Pruner finished");
             }
+            PrunerEvent::Stalled { since_progress } => {
+                warn!(?since_progress, "Pruner made no progress for a while, it may be stuck on a segment");
+            }
         }
     }
 }
@@ -0,0 +1,31 @@
+//! Adjusts the level of the consensus logs of a running node.
+
+use reth_tracing::TargetLevel;
+use tracing::{info, warn};
+
+/// Raises the level of the logs of `level` by one on `SIGUSR1` and lowers it by one on `SIGUSR2`.
+#[cfg(unix)]
+pub async fn adjust_on_signal(level: TargetLevel) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let (mut raise, mut lower) =
+        match (signal(SignalKind::user_defined1()), signal(SignalKind::user_defined2())) {
+            (Ok(raise), Ok(lower)) => (raise, lower),
+            (Err(err), _) | (_, Err(err)) => {
+                warn!(target: "reth::cli", %err, "Failed to listen for log level signals");
+                return
+            }
+        };
+    loop {
+        let new_level = tokio::select! {
+            Some(()) = raise.recv() => level.raise(),
+            Some(()) = lower.recv() => level.lower(),
+            else => return,
+        };
+        info!(target: "reth::cli", log_target = level.target(), level = %new_level, "Changed log level");
+    }
+}
+
+/// Signals are not supported, the level stays at the configured one.
+#[cfg(not(unix))]
+pub async fn adjust_on_signal(_level: TargetLevel) {}
@@ -4,9 +4,9 @@
 use crate::{
     args::{
         get_secret_key,
-        utils::{chain_help, genesis_value_parser, parse_socket_address, SUPPORTED_CHAINS},
-        ClayerArgs, DatabaseArgs, DebugArgs, DevArgs, NetworkArgs, PayloadBuilderArgs, PruningArgs,
-        RpcServerArgs, TxPoolArgs,
+        utils::{chain_help, genesis_value_parser, parse_metrics_endpoint, SUPPORTED_CHAINS},
+        ClayerArgs, DatabaseArgs, DebugArgs, DevArgs, DownloaderArgs, NetworkArgs,
+        PayloadBuilderArgs, PruningArgs, RpcServerArgs, SnapshotArgs, TxPoolArgs,
     },
     cli::{
         components::RethNodeComponentsImpl,
@@ -15,8 +15,11 @@ use crate::{
     },
     dirs::{ChainPath, DataDirPath, MaybePlatformPath},
     init::init_genesis,
-    node::cl_events::ConsensusLayerHealthEvents,
-    prometheus_exporter,
+    node::{
+        cl_events::ConsensusLayerHealthEvents,
+        db_monitor::{DbGrowthMonitor, DEFAULT_DB_MONITOR_INTERVAL},
+    },
+    prometheus_exporter::{self, MetricsEndpoint},
     runner::CliContext,
     utils::get_single_header,
     version::SHORT_VERSION,
@@ -28,22 +31,29 @@ use metrics_exporter_prometheus::PrometheusHandle;
 use reth_auto_seal_consensus::{AutoSealBuilder, AutoSealConsensus, MiningMode};
 use reth_beacon_consensus::{
     hooks::{EngineHooks, PruneHook},
-    BeaconConsensus, BeaconConsensusEngine, MIN_BLOCKS_FOR_PIPELINE_RUN,
+    BeaconConsensus, BeaconConsensusEngine, BeaconConsensusEngineHandle, BeaconEngineMessage,
+    MIN_BLOCKS_FOR_PIPELINE_RUN,
 };
 use reth_blockchain_tree::{
     config::BlockchainTreeConfig, externals::TreeExternals, BlockchainTree, ShareableBlockchainTree,
 };
-use reth_clayer::{AuthHttpConfig, ClayerConsensusMessagingAgent, ConsensusBuilder};
+use reth_clayer::{
+    AuthHttpConfig, ClTask, ClayerConsensusMessagingAgent, ClayerRpc, ConsensusBuilder,
+    ConsensusMessageLog, ConsensusReadiness, InProcessEngine, LocalStateRootVerifier, RemoteSigner,
+};
 use reth_config::{
-    config::{PruneConfig, StageConfig},
+    config::{BodiesConfig, HeadersConfig, PruneConfig, StageConfig},
     Config,
 };
-use reth_db::{database::Database, database_metrics::DatabaseMetrics, init_db};
+use reth_db::{
+    database::Database, database_metrics::DatabaseMetrics, init_db_with_geometry, DatabaseEnv,
+};
 use reth_downloaders::{
     bodies::bodies::BodiesDownloaderBuilder,
     headers::reverse_headers::ReverseHeadersDownloaderBuilder,
 };
 use reth_interfaces::{
+    blockchain_tree::BlockchainTreeEngine,
     clayer::ClayerConsensusMessageAgentTrait,
     consensus::Consensus,
     p2p::{
@@ -51,10 +61,12 @@ use reth_interfaces::{
         either::EitherDownloader,
         headers::{client::HeadersClient, downloader::HeaderDownloader},
     },
-    RethResult,
+    sync::NetworkSyncUpdater,
+    RethError, RethResult,
 };
 use reth_network::{NetworkBuilder, NetworkConfig, NetworkEvents, NetworkHandle, NetworkManager};
 use reth_network_api::{NetworkInfo, PeersInfo};
+use reth_payload_builder::PayloadBuilderHandle;
 use reth_primitives::{
     constants::eip4844::{LoadKzgSettingsError, MAINNET_KZG_TRUSTED_SETUP},
     fs,
@@ -63,11 +75,11 @@ use reth_primitives::{
     BlockHashOrNumber, BlockNumber, ChainSpec, DisplayHardforks, Head, SealedHeader, B256,
 };
 use reth_provider::{
-    providers::BlockchainProvider, providers::ConsensusProvider, BlockHashReader, BlockReader,
-    CanonStateSubscriptions, HeaderProvider, HeaderSyncMode, ProviderFactory,
-    StageCheckpointReader,
+    providers::BlockchainProvider, providers::ConsensusProvider, BlockHashReader, BlockIdReader,
+    BlockReader, CanonChainTracker, CanonStateSubscriptions, ChainSpecProvider, HeaderProvider,
+    HeaderSyncMode, ProviderFactory, StageCheckpointReader,
 };
-use reth_prune::PrunerBuilder;
+use reth_prune::{PrunerBuilder, PrunerStallMonitor};
 use reth_revm::EvmProcessorFactory;
 use reth_revm_inspectors::stack::Hook;
 use reth_rpc_engine_api::EngineApi;
@@ -79,21 +91,30 @@ use reth_stages::{
         TotalDifficultyStage, TransactionLookupStage,
     },
 };
-use reth_tasks::TaskExecutor;
+use reth_tasks::{TaskExecutor, TaskSpawner};
+use reth_tracing::TargetLevel;
 use reth_transaction_pool::{
-    blobstore::InMemoryBlobStore, TransactionPool, TransactionValidationTaskExecutor,
+    blobstore::{DiskFileBlobStore, InMemoryBlobStore},
+    TransactionPool, TransactionValidationTaskExecutor,
 };
 use secp256k1::SecretKey;
 use std::{
+    future::Future,
     net::{SocketAddr, SocketAddrV4},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
+};
+use tokio::sync::{
+    mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    oneshot, watch,
 };
-use tokio::sync::{mpsc::unbounded_channel, oneshot, watch};
 use tracing::*;
 
 pub mod cl_events;
+pub mod db_monitor;
 pub mod events;
+pub mod log_level;
 
 /// Start the node
 #[derive(Debug, Parser)]
@@ -112,6 +133,13 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     #[arg(long, value_name = "FILE", verbatim_doc_comment)]
     pub config: Option<PathBuf>,
 
+    /// Write the effective configuration to the given file and exit.
+    ///
+    /// The file contains the configuration file merged with the command line arguments, and the
+    /// resolved clayer settings in a `[clayer]` table. It can be used as a configuration file.
+    #[arg(long, value_name = "FILE")]
+    pub dump_config: Option<PathBuf>,
+
     /// The chain this node is running.
     ///
     /// Possible values are either a built-in chain or the path to a chain specification file.
@@ -128,9 +156,10 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
 
     /// Enable Prometheus metrics.
     ///
-    /// The metrics will be served at the given interface and port.
-    #[arg(long, value_name = "SOCKET", value_parser = parse_socket_address, help_heading = "Metrics")]
-    pub metrics: Option<SocketAddr>,
+    /// The metrics will be served at the given interface and port, or at the Unix socket
+    /// `unix:<path>`.
+    #[arg(long, value_name = "SOCKET", value_parser = parse_metrics_endpoint, help_heading = "Metrics")]
+    pub metrics: Option<MetricsEndpoint>,
 
     /// Add a new instance of a node.
     ///
@@ -145,13 +174,21 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     /// - AUTH_PORT: default + `instance` * 100 - 100
     /// - HTTP_RPC_PORT: default - `instance` + 1
     /// - WS_RPC_PORT: default + `instance` * 2 - 2
-    #[arg(long, value_name = "INSTANCE", global = true, default_value_t = 1, value_parser = value_parser!(u16).range(..=200))]
+    ///
+    /// Cannot be combined with an explicit `--port`, `--http.port`, `--ws.port` or
+    /// `--authrpc.port`, since the instance offset would silently override them.
+    #[arg(long, value_name = "INSTANCE", global = true, default_value_t = 1, value_parser = value_parser!(u16).range(..=200), conflicts_with_all = ["port", "http_port", "ws_port", "auth_port"])]
     pub instance: u16,
 
     /// Overrides the KZG trusted setup by reading from the supplied file.
     #[arg(long, value_name = "PATH")]
     pub trusted_setup_file: Option<PathBuf>,
 
+    /// Number of blocks the sync target may be ahead of the local tip before the consensus
+    /// engine runs the pipeline instead of syncing block by block [default: 32]
+    #[arg(long, value_name = "BLOCKS")]
+    pub pipeline_run_threshold: Option<u64>,
+
     /// All networking related arguments
     #[clap(flatten)]
     pub network: NetworkArgs,
@@ -184,6 +221,14 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     #[clap(flatten)]
     pub pruning: PruningArgs,
 
+    /// All downloader related arguments with --downloader prefix
+    #[clap(flatten)]
+    pub downloader: DownloaderArgs,
+
+    /// All snapshot related arguments with --snapshot prefix
+    #[clap(flatten)]
+    pub snapshot: SnapshotArgs,
+
     /// All clayer related arguments
     #[clap(flatten)]
     pub clayer: ClayerArgs,
@@ -197,6 +242,11 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     #[clap(flatten)]
     #[clap(next_help_heading = "Extension")]
     pub ext: Ext::Node,
+
+    /// Level of the consensus logs shared with the log layers, set from
+    /// [ClayerArgs::log_level] when tracing is initialized
+    #[clap(skip)]
+    pub consensus_log_level: Option<TargetLevel>,
 }
 
 impl<Ext: RethCliExt> NodeCommand<Ext> {
@@ -205,10 +255,12 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         let Self {
             datadir,
             config,
+            dump_config,
             chain,
             metrics,
             trusted_setup_file,
             instance,
+            pipeline_run_threshold,
             network,
             rpc,
             txpool,
@@ -217,18 +269,23 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             db,
             dev,
             pruning,
+            downloader,
+            snapshot,
             clayer,
             #[cfg(feature = "optimism")]
             rollup,
+            consensus_log_level,
             ..
         } = self;
         NodeCommand {
             datadir,
             config,
+            dump_config,
             chain,
             metrics,
             instance,
             trusted_setup_file,
+            pipeline_run_threshold,
             network,
             rpc,
             txpool,
@@ -237,10 +294,13 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             db,
             dev,
             pruning,
+            downloader,
+            snapshot,
             clayer,
             #[cfg(feature = "optimism")]
             rollup,
             ext,
+            consensus_log_level,
         }
     }
 
@@ -255,13 +315,21 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         // get config
         let config = self.load_config()?;
 
+        if let Some(path) = &self.dump_config {
+            self.write_config(&config, path)?;
+            info!(target: "reth::cli", ?path, "Wrote effective configuration");
+            return Ok(())
+        }
+
         let prometheus_handle = self.install_prometheus_recorder()?;
 
         let data_dir = self.data_dir();
         let db_path = data_dir.db_path();
 
         info!(target: "reth::cli", path = ?db_path, "Opening database");
-        let db = Arc::new(init_db(&db_path, self.db.log_level)?.with_metrics());
+        let db = Arc::new(
+            init_db_with_geometry(&db_path, self.db.log_level, self.db.geometry()?)?.with_metrics(),
+        );
         info!(target: "reth::cli", "Database opened");
 
         let mut provider_factory = ProviderFactory::new(Arc::clone(&db), Arc::clone(&self.chain));
@@ -270,7 +338,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         let snapshotter = reth_snapshot::Snapshotter::new(
             provider_factory.clone(),
             data_dir.snapshots_path(),
-            self.chain.snapshot_block_interval,
+            self.snapshot_block_interval(),
         )?;
 
         provider_factory = provider_factory
@@ -278,6 +346,20 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
 
         self.start_metrics_endpoint(prometheus_handle, Arc::clone(&db)).await?;
 
+        let db_monitor = DbGrowthMonitor::new(
+            Arc::clone(&db),
+            self.db.monitor_interval.unwrap_or(DEFAULT_DB_MONITOR_INTERVAL),
+        )
+        .with_min_free_pages(self.db.min_free_pages)
+        .with_max_size(self.db.size_alert_threshold());
+        if db_monitor.is_enabled() {
+            ctx.task_executor.spawn(Box::pin(db_monitor.run()));
+        }
+
+        if let Some(level) = self.consensus_log_level.clone() {
+            ctx.task_executor.spawn(Box::pin(log_level::adjust_on_signal(level)));
+        }
+
         debug!(target: "reth::cli", chain=%self.chain.chain, genesis=?self.chain.genesis_hash(), "Initializing genesis");
 
         let genesis_hash = init_genesis(Arc::clone(&db), self.chain.clone())?;
@@ -291,8 +373,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         let sync_metrics_listener = reth_stages::MetricsListener::new(sync_metrics_rx);
         ctx.task_executor.spawn_critical("stages metrics listener task", sync_metrics_listener);
 
-        let prune_config =
-            self.pruning.prune_config(Arc::clone(&self.chain))?.or(config.prune.clone());
+        let prune_config = config.prune.clone();
 
         // configure blockchain tree
         let tree_externals = TreeExternals::new(
@@ -300,7 +381,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             Arc::clone(&consensus),
             EvmProcessorFactory::new(self.chain.clone()),
         );
-        let tree_config = BlockchainTreeConfig::default();
+        let tree_config = self.tree_config()?;
         let tree = BlockchainTree::new(
             tree_externals,
             tree_config,
@@ -318,11 +399,16 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         // setup the blockchain provider
         let blockchain_db =
             BlockchainProvider::new(provider_factory.clone(), blockchain_tree.clone())?;
-        let blob_store = InMemoryBlobStore::default();
+        let blob_store = if self.txpool.max_blob_store_size.is_some() {
+            let overflow = DiskFileBlobStore::open(data_dir.blobstore_path(), Default::default())?;
+            InMemoryBlobStore::with_overflow(self.txpool.blob_store_config(), overflow)
+        } else {
+            InMemoryBlobStore::default()
+        };
         let validator = TransactionValidationTaskExecutor::eth_builder(Arc::clone(&self.chain))
             .with_head_timestamp(head.timestamp)
             .kzg_settings(self.kzg_settings()?)
-            .with_additional_tasks(1)
+            .with_additional_tasks(self.txpool.validation_tasks)
             .build_with_tasks(blockchain_db.clone(), ctx.task_executor.clone(), blob_store.clone());
 
         let transaction_pool =
@@ -347,6 +433,28 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             debug!(target: "reth::cli", "Spawned txpool maintenance task");
         }
 
+        if self.txpool.persist {
+            let path = data_dir.txpool_transactions_path();
+            match reth_transaction_pool::maintain::load_persisted_transactions(
+                &transaction_pool,
+                &path,
+            )
+            .await
+            {
+                Ok(count) => info!(target: "reth::cli", count, "Restored persisted transactions"),
+                Err(err) => {
+                    warn!(target: "reth::cli", %err, "Failed to restore persisted transactions")
+                }
+            }
+            let pool = transaction_pool.clone();
+            ctx.task_executor.spawn_critical_with_graceful_shutdown_signal(
+                "txpool persistence task",
+                |shutdown| {
+                    reth_transaction_pool::maintain::persist_transactions_task(shutdown, pool, path)
+                },
+            );
+        }
+
         info!(target: "reth::cli", "Connecting to P2P network");
         let network_secret_path =
             self.network.p2p_secret_key.clone().unwrap_or_else(|| data_dir.p2p_secret_path());
@@ -377,7 +485,39 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         self.ext.configure_network(network_builder.network_mut(), &components)?;
 
         // launch network
-        let clayer_consensus_messaging_agent = ClayerConsensusMessagingAgent::new();
+        let mut clayer_consensus_messaging_agent = ClayerConsensusMessagingAgent::new()
+            .with_topic(self.network.consensus_topic(&self.chain))
+            .with_protocol_version(self.clayer.protocol_version())
+            .with_message_ttl(self.clayer.pbft_config().message_ttl)
+            .with_seen_cache(self.clayer.seen_cache_size(), self.clayer.seen_cache_ttl())
+            .with_peers_handle(network_builder.handle().peers_handle().clone());
+        if let Some(handshake_timeout) = self.clayer.handshake_timeout {
+            clayer_consensus_messaging_agent =
+                clayer_consensus_messaging_agent.with_handshake_timeout(handshake_timeout);
+        }
+        if let Some((interval, timeout)) =
+            self.clayer.heartbeat_interval.zip(self.clayer.heartbeat_timeout())
+        {
+            clayer_consensus_messaging_agent =
+                clayer_consensus_messaging_agent.with_heartbeat(interval, timeout);
+        }
+        if let Some(compression) = self.clayer.compression {
+            clayer_consensus_messaging_agent =
+                clayer_consensus_messaging_agent.with_compression(compression);
+        }
+        if let Some(interval) = self.clayer.metrics_flush_interval {
+            clayer_consensus_messaging_agent =
+                clayer_consensus_messaging_agent.with_metrics_flush_interval(interval);
+        }
+        if self.clayer.message_log {
+            let message_log = ConsensusMessageLog::open(
+                data_dir.clayer_message_log_path(),
+                self.clayer.message_log_max_size(),
+            )?;
+            info!(target: "reth::cli", path = ?message_log.path(), "Recording consensus messages");
+            clayer_consensus_messaging_agent =
+                clayer_consensus_messaging_agent.with_message_log(message_log);
+        }
         let network = self.start_network(
             network_builder,
             &ctx.task_executor,
@@ -404,6 +544,13 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             None
         };
 
+        // the `clayer` rpc namespace, only available when the consensus layer is running
+        let mut clayer_rpc = None;
+        // awaited before the node reports being started, only set when the consensus layer runs
+        let mut consensus_readiness = None;
+        // the auth server methods the consensus layer calls directly, if enabled
+        let mut in_process_engine = None;
+
         // Configure the pipeline
         let (mut pipeline, client) = if self.dev.dev {
             info!(target: "reth::cli", "Starting Reth in dev mode");
@@ -465,13 +612,29 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
 
             // ===============================================================================
             // extract the jwt secret from the args if possible
-            let consensus_db = ConsensusProvider::new(provider_factory.clone())?;
+            let consensus_db = self.consensus_provider(&provider_factory)?;
 
             let default_jwt_path = data_dir.jwt_path();
             let jwt_secret = self.rpc.auth_jwt_secret(default_jwt_path)?;
-            let auth_config =
-                AuthHttpConfig { port: self.rpc.auth_port, auth: jwt_secret.as_bytes().to_vec() };
-            let mut task = ConsensusBuilder::new(
+            let mut auth_config = AuthHttpConfig {
+                port: self.rpc.auth_port,
+                auth: jwt_secret.as_bytes().to_vec(),
+                token_refresh_interval: self.clayer.jwt_refresh_interval(),
+                http2: self.clayer.engine_http2,
+                max_concurrent_requests: self.clayer.engine_max_concurrent_requests(),
+                in_process: None,
+            };
+            if self.clayer.in_process_engine {
+                info!(target: "reth::cli", "Calling the engine API in-process");
+                let engine = InProcessEngine::new();
+                auth_config.in_process = Some(engine.clone());
+                in_process_engine = Some(engine);
+            }
+            let mut pbft_config = self.clayer.pbft_config();
+            if self.clayer.track_el_identity {
+                pbft_config.el_identity_path = Some(data_dir.clayer_el_identity_path());
+            }
+            let mut builder = ConsensusBuilder::new(
                 secret_key,
                 Arc::clone(&self.chain),
                 blockchain_db.clone(),
@@ -480,10 +643,35 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                 consensus_db,
                 auth_config,
             )
-            .build();
+            .with_pbft_config(pbft_config);
+            if let Some(url) = self.clayer.remote_signer.clone() {
+                info!(target: "reth::cli", %url, "Signing consensus messages with a remote signer");
+                let public_key = secret_key.public_key(secp256k1::SECP256K1);
+                builder = builder.with_signer(Arc::new(RemoteSigner::new(url, public_key)));
+            }
+            if self.clayer.verify_state_root {
+                info!(target: "reth::cli", "Verifying the state root of every payload locally");
+                builder = builder.with_state_root_verifier(Arc::new(LocalStateRootVerifier::new(
+                    blockchain_db.clone(),
+                    EvmProcessorFactory::new(self.chain.clone()),
+                )));
+            }
+            if self.clayer.skip_idle_payload_builds {
+                builder = builder.with_pending_work(Arc::new(transaction_pool.clone()));
+            }
+            let mut task = builder.build();
             let pipeline_events = pipeline.events();
             task.set_pipeline_events(pipeline_events);
-            ctx.task_executor.spawn(Box::pin(task));
+            clayer_rpc = Some(ClayerRpc::new(
+                task.pending_proposals(),
+                task.forkchoice_history(),
+                task.handle(),
+            ));
+            consensus_readiness = Some(task.readiness());
+            ctx.task_executor
+                .spawn_critical_with_graceful_shutdown_signal("clayer consensus", |shutdown| {
+                    run_consensus_until_shutdown(shutdown, task)
+                });
             // ===============================================================================
 
             (pipeline, EitherDownloader::Right(network_client))
@@ -512,7 +700,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                 .prune_delete_limit(self.chain.prune_delete_limit)
                 .build(provider_factory, snapshotter.highest_snapshot_receiver());
 
-            let events = pruner.events();
+            let events = PrunerStallMonitor::new(pruner.events(), self.pruning.stall_window());
             hooks.add(PruneHook::new(pruner, Box::new(ctx.task_executor.clone())));
 
             info!(target: "reth::cli", ?prune_config, "Pruner initialized");
@@ -522,17 +710,15 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         };
 
         // Configure the consensus engine
-        let (beacon_consensus_engine, beacon_engine_handle) = BeaconConsensusEngine::with_channel(
+        let (beacon_consensus_engine, beacon_engine_handle) = self.build_consensus_engine(
             client,
             pipeline,
             blockchain_db.clone(),
             Box::new(ctx.task_executor.clone()),
             Box::new(network.clone()),
             max_block,
-            self.debug.continuous,
             payload_builder.clone(),
             initial_target,
-            MIN_BLOCKS_FOR_PIPELINE_RUN,
             consensus_engine_tx,
             consensus_engine_rx,
             hooks,
@@ -573,10 +759,30 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
 
         // adjust rpc port numbers based on instance number
         self.adjust_instance_ports();
+        if self.instance > 1 {
+            info!(
+                target: "reth::cli",
+                instance = self.instance,
+                port = self.network.port + self.instance - 1,
+                http_port = self.rpc.http_port,
+                ws_port = self.rpc.ws_port,
+                auth_port = self.rpc.auth_port,
+                "Adjusted ports for instance"
+            );
+        }
 
         // Start RPC servers
-        let _rpc_server_handles =
-            self.rpc.start_servers(&components, engine_api, jwt_secret, &mut self.ext).await?;
+        let _rpc_server_handles = self
+            .rpc
+            .start_servers(
+                &components,
+                engine_api,
+                jwt_secret,
+                clayer_rpc,
+                in_process_engine,
+                &mut self.ext,
+            )
+            .await?;
 
         // Run consensus engine to completion
         let (tx, rx) = oneshot::channel();
@@ -586,7 +792,11 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             let _ = tx.send(res);
         });
 
-        self.ext.on_node_started(&components)?;
+        let readiness_timeout = self.clayer.readiness_timeout;
+        on_consensus_ready(consensus_readiness, readiness_timeout, || {
+            self.ext.on_node_started(&components)
+        })
+        .await?;
 
         // If `enable_genesis_walkback` is set to true, the rollup client will need to
         // perform the derivation pipeline from genesis, validating the data dir.
@@ -652,11 +862,13 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         Client: HeadersClient + BodiesClient + Clone + 'static,
     {
         // building network downloaders using the fetch client
-        let header_downloader = ReverseHeadersDownloaderBuilder::new(config.headers)
+        let header_downloader = self
+            .header_downloader_builder(config.headers)
             .build(client.clone(), Arc::clone(&consensus))
             .into_task_with(task_executor);
 
-        let body_downloader = BodiesDownloaderBuilder::new(config.bodies)
+        let body_downloader = self
+            .body_downloader_builder(config.bodies)
             .build(client, Arc::clone(&consensus), provider_factory.clone())
             .into_task_with(task_executor);
 
@@ -677,6 +889,100 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         Ok(pipeline)
     }
 
+    /// Returns the [ReverseHeadersDownloaderBuilder] configured from the given [HeadersConfig]
+    /// and any downloader overrides passed on the command line.
+    fn header_downloader_builder(&self, config: HeadersConfig) -> ReverseHeadersDownloaderBuilder {
+        let mut builder = ReverseHeadersDownloaderBuilder::new(config);
+        if let Some(batch_size) = self.downloader.header_batch_size {
+            builder = builder.request_limit(batch_size);
+        }
+        builder
+    }
+
+    /// Returns the [BodiesDownloaderBuilder] configured from the given [BodiesConfig] and any
+    /// downloader overrides passed on the command line.
+    fn body_downloader_builder(&self, config: BodiesConfig) -> BodiesDownloaderBuilder {
+        let builder = BodiesDownloaderBuilder::new(config);
+        if let Some(max) = self.downloader.body_concurrency {
+            let min = config.downloader_min_concurrent_requests.min(max);
+            return builder.with_concurrent_requests_range(min..=max)
+        }
+        builder
+    }
+
+    /// Returns the number of blocks per snapshot, preferring the command line override over the
+    /// chain spec.
+    fn snapshot_block_interval(&self) -> u64 {
+        self.snapshot.block_interval.unwrap_or(self.chain.snapshot_block_interval)
+    }
+
+    /// Returns the gap to the sync target above which the consensus engine runs the pipeline,
+    /// preferring the command line override over [MIN_BLOCKS_FOR_PIPELINE_RUN].
+    fn pipeline_run_threshold(&self) -> u64 {
+        self.pipeline_run_threshold.unwrap_or(MIN_BLOCKS_FOR_PIPELINE_RUN)
+    }
+
+    /// Creates the consensus engine, running the pipeline once the sync target is more than
+    /// [Self::pipeline_run_threshold] blocks ahead of the local tip.
+    #[allow(clippy::too_many_arguments)]
+    fn build_consensus_engine<DB, BT, Client>(
+        &self,
+        client: Client,
+        pipeline: Pipeline<DB>,
+        blockchain: BT,
+        task_spawner: Box<dyn TaskSpawner>,
+        sync_state_updater: Box<dyn NetworkSyncUpdater>,
+        max_block: Option<BlockNumber>,
+        payload_builder: PayloadBuilderHandle,
+        target: Option<B256>,
+        to_engine: UnboundedSender<BeaconEngineMessage>,
+        rx: UnboundedReceiver<BeaconEngineMessage>,
+        hooks: EngineHooks,
+    ) -> RethResult<(BeaconConsensusEngine<DB, BT, Client>, BeaconConsensusEngineHandle)>
+    where
+        DB: Database + Unpin + 'static,
+        BT: BlockchainTreeEngine
+            + BlockReader
+            + BlockIdReader
+            + CanonChainTracker
+            + StageCheckpointReader
+            + ChainSpecProvider
+            + 'static,
+        Client: HeadersClient + BodiesClient + Clone + Unpin + 'static,
+    {
+        BeaconConsensusEngine::with_channel(
+            client,
+            pipeline,
+            blockchain,
+            task_spawner,
+            sync_state_updater,
+            max_block,
+            self.debug.continuous,
+            payload_builder,
+            target,
+            self.pipeline_run_threshold(),
+            to_engine,
+            rx,
+            hooks,
+        )
+    }
+
+    /// Returns the configuration of the blockchain tree.
+    ///
+    /// Fails if the senders of committed blocks should be trusted in dev mode, where no consensus
+    /// layer commits the blocks.
+    fn tree_config(&self) -> eyre::Result<BlockchainTreeConfig> {
+        let config = BlockchainTreeConfig::default();
+        if !self.clayer.trust_committed_senders {
+            return Ok(config)
+        }
+        if self.dev.dev {
+            eyre::bail!("--clayer.trust-committed-senders requires the consensus layer, not --dev")
+        }
+        warn!(target: "reth::cli", "Trusting the transaction senders of committed blocks, blocks are only safe to import if committed by a quorum of validators");
+        Ok(config.with_trusted_senders(true))
+    }
+
     /// Returns the chain specific path to the data dir.
     fn data_dir(&self) -> ChainPath<DataDirPath> {
         self.datadir.unwrap_or_chain_default(self.chain.chain)
@@ -697,6 +1003,9 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
 
         // Update the config with the command line arguments
         config.peers.connect_trusted_nodes_only = self.network.trusted_only;
+        if let Some(interval) = self.clayer.validator_reconnect_interval {
+            config.peers.validator_reconnect_interval = interval;
+        }
 
         if !self.network.trusted_peers.is_empty() {
             info!(target: "reth::cli", "Adding trusted nodes");
@@ -705,9 +1014,30 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             });
         }
 
+        if !self.clayer.bootstrap_peers.is_empty() {
+            info!(target: "reth::cli", "Adding consensus bootstrap peers");
+            let peers = self.clayer.bootstrap_peers.iter().cloned();
+            config.peers.consensus_bootstrap_nodes.extend(peers);
+        }
+
+        if let Some(prune_config) = self.pruning.prune_config(Arc::clone(&self.chain))? {
+            config.prune = Some(prune_config);
+        }
+
         Ok(config)
     }
 
+    /// Writes the effective configuration to `path`, extended by the resolved clayer settings.
+    fn write_config(&self, config: &Config, path: &Path) -> eyre::Result<()> {
+        let mut table: toml::Table = toml::Value::try_from(config)?.try_into()?;
+        table.insert("clayer".to_string(), toml::Value::Table(self.clayer.to_toml()));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, toml::to_string_pretty(&table)?)?;
+        Ok(())
+    }
+
     /// Loads the trusted setup params from a given file path or falls back to
     /// `MAINNET_KZG_TRUSTED_SETUP`.
     fn kzg_settings(&self) -> eyre::Result<Arc<KzgSettings>> {
@@ -732,10 +1062,10 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
     where
         Metrics: DatabaseMetrics + 'static + Send + Sync,
     {
-        if let Some(listen_addr) = self.metrics {
-            info!(target: "reth::cli", addr = %listen_addr, "Starting metrics endpoint");
+        if let Some(endpoint) = self.metrics.clone() {
+            info!(target: "reth::cli", addr = %endpoint, "Starting metrics endpoint");
             prometheus_exporter::serve(
-                listen_addr,
+                endpoint,
                 prometheus_handle,
                 db,
                 metrics_process::Collector::default(),
@@ -781,6 +1111,27 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         handle
     }
 
+    /// Returns the storage of the consensus layer.
+    ///
+    /// This is a separate database in `--clayer.state-dir` if set and the database of the
+    /// execution layer otherwise.
+    fn consensus_provider(
+        &self,
+        provider_factory: &ProviderFactory<Arc<DatabaseEnv>>,
+    ) -> eyre::Result<ConsensusProvider<Arc<DatabaseEnv>>> {
+        let provider = match &self.clayer.state_dir {
+            Some(state_dir) => {
+                info!(target: "reth::cli", path = ?state_dir, "Opening consensus database");
+                // the same size limits as the database of the execution layer
+                let db = init_db_with_geometry(state_dir, self.db.log_level, self.db.geometry()?)?;
+                let db = Arc::new(db.with_metrics());
+                ConsensusProvider::new(ProviderFactory::new(db, Arc::clone(&self.chain)))?
+            }
+            None => ConsensusProvider::new(provider_factory.clone())?,
+        };
+        Ok(provider.with_write_retries(self.clayer.db_write_retries()))
+    }
+
     /// Fetches the head block from the database.
     ///
     /// If the database is empty, returns the genesis block.
@@ -813,7 +1164,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
     /// Attempt to look up the block number for the tip hash in the database.
     /// If it doesn't exist, download the header and return the block number.
     ///
-    /// NOTE: The download is attempted with infinite retries.
+    /// NOTE: The download is retried `--debug.tip-fetch-retries` times, infinitely if not set.
     async fn lookup_or_fetch_tip<DB, Client>(
         &self,
         provider_factory: ProviderFactory<DB>,
@@ -829,7 +1180,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
 
     /// Attempt to look up the block with the given number and return the header.
     ///
-    /// NOTE: The download is attempted with infinite retries.
+    /// NOTE: The download is retried `--debug.tip-fetch-retries` times, infinitely if not set.
     async fn fetch_tip<DB, Client>(
         &self,
         factory: ProviderFactory<DB>,
@@ -851,13 +1202,23 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         }
 
         info!(target: "reth::cli", ?tip, "Fetching tip block from the network.");
+        let mut retries = 0;
         loop {
             match get_single_header(&client, tip).await {
                 Ok(tip_header) => {
                     info!(target: "reth::cli", ?tip, "Successfully fetched tip");
                     return Ok(tip_header);
                 }
+                Err(error) if self.debug.tip_fetch_retries.map_or(false, |max| retries >= max) => {
+                    error!(target: "reth::cli", %error, "Failed to fetch the tip");
+                    return Err(RethError::Custom(format!(
+                        "failed to fetch the tip {tip:?} after {} attempts, check the network \
+                         connectivity and that the tip hash is correct",
+                        retries + 1
+                    )))
+                }
                 Err(error) => {
+                    retries += 1;
                     error!(target: "reth::cli", %error, "Failed to fetch the tip. Retrying...");
                 }
             }
@@ -982,6 +1343,9 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                             .max(config.storage_hashing.clean_threshold),
                         prune_modes.clone(),
                     )
+                    .with_parallel_receipt_verification(
+                        config.execution.parallel_receipt_verification,
+                    )
                     .with_metrics_tx(metrics_tx),
                 )
                 .set(AccountHashingStage::new(
@@ -1022,6 +1386,27 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
     }
 }
 
+/// Runs `on_started` once the consensus layer is ready, or after `timeout` with a warning if it is
+/// set. Runs it right away without a consensus layer.
+async fn on_consensus_ready(
+    readiness: Option<ConsensusReadiness>,
+    timeout: Option<Duration>,
+    on_started: impl FnOnce() -> eyre::Result<()>,
+) -> eyre::Result<()> {
+    if let Some(readiness) = readiness {
+        info!(target: "reth::cli", "Waiting for the consensus layer to be ready");
+        match timeout {
+            Some(timeout) => {
+                if tokio::time::timeout(timeout, readiness.ready()).await.is_err() {
+                    warn!(target: "reth::cli", ?timeout, state = ?readiness.state(), "Consensus layer is not ready, reporting the node as started anyway");
+                }
+            }
+            None => readiness.ready().await,
+        }
+    }
+    on_started()
+}
+
 /// Drives the [NetworkManager] future until a [Shutdown](reth_tasks::shutdown::Shutdown) signal is
 /// received. If configured, this writes known peers to `persistent_peers_file` afterwards.
 async fn run_network_until_shutdown<C>(
@@ -1060,6 +1445,29 @@ async fn run_network_until_shutdown<C>(
     drop(graceful_guard)
 }
 
+/// Drives the [ClTask] future until a [Shutdown](reth_tasks::shutdown::Shutdown) signal is
+/// received, then waits on a blocking thread until the consensus engine saved its state.
+async fn run_consensus_until_shutdown<Client, CDB>(
+    shutdown: reth_tasks::shutdown::GracefulShutdown,
+    mut task: ClTask<Client, CDB>,
+) where
+    ClTask<Client, CDB>: Future<Output = ()> + Unpin + Send + 'static,
+{
+    pin_mut!(shutdown);
+
+    let mut graceful_guard = None;
+    tokio::select! {
+        _ = &mut task => {},
+        guard = shutdown => {
+            graceful_guard = Some(guard);
+        },
+    }
+
+    let _ = tokio::task::spawn_blocking(move || task.shutdown()).await;
+
+    drop(graceful_guard)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1070,6 +1478,57 @@ mod tests {
         path::Path,
     };
 
+    #[test]
+    fn dump_config_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let datadir = dir.path().to_str().unwrap();
+        let path = dir.path().join("dump").join("reth.toml");
+        let cmd = NodeCommand::<()>::try_parse_from([
+            "reth",
+            "--datadir",
+            datadir,
+            "--dump-config",
+            path.to_str().unwrap(),
+            "--full",
+            "--clayer.faults",
+            "2",
+        ])
+        .unwrap();
+
+        let config = cmd.load_config().unwrap();
+        assert!(config.prune.is_some());
+        cmd.write_config(&config, cmd.dump_config.as_ref().unwrap()).unwrap();
+
+        let dumped = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(toml::from_str::<Config>(&dumped).unwrap(), config);
+
+        let table = toml::from_str::<toml::Table>(&dumped).unwrap();
+        let clayer = table["clayer"].as_table().unwrap();
+        assert_eq!(clayer["faults"].as_integer(), Some(2));
+        assert_eq!(clayer["mine"].as_bool(), Some(false));
+        assert_eq!(clayer["commit-aggregation-window"].as_str(), Some("50ms"));
+    }
+
+    #[test]
+    fn load_config_adds_bootstrap_peers() {
+        let dir = tempfile::tempdir().unwrap();
+        let enode = "enode://d860a01f9722d78051619d1e2351aba3f43f943f6f00718d1b9baa4101932a1f5011f16bb2b1bb35db20d6fe28fa0bf09636d26a87d31de9ec6203eeedb1f666@18.138.108.67:30303";
+        let cmd = NodeCommand::<()>::try_parse_from([
+            "reth",
+            "--datadir",
+            dir.path().to_str().unwrap(),
+            "--clayer.bootstrap-peers",
+            enode,
+        ])
+        .unwrap();
+
+        let peer = enode.parse::<reth_primitives::NodeRecord>().unwrap();
+        let config = cmd.load_config().unwrap();
+        assert!(config.peers.consensus_bootstrap_nodes.contains(&peer));
+        // the arguments are still usable after loading the config
+        assert_eq!(cmd.clayer.bootstrap_peers, vec![peer]);
+    }
+
     #[test]
     fn parse_help_node_command() {
         let err = NodeCommand::<()>::try_parse_from(["reth", "--help"]).unwrap_err();
@@ -1123,14 +1582,23 @@ mod tests {
     #[test]
     fn parse_metrics_port() {
         let cmd = NodeCommand::<()>::try_parse_from(["reth", "--metrics", "9001"]).unwrap();
-        assert_eq!(cmd.metrics, Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9001)));
+        assert_eq!(
+            cmd.metrics,
+            Some(MetricsEndpoint::Tcp(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9001)))
+        );
 
         let cmd = NodeCommand::<()>::try_parse_from(["reth", "--metrics", ":9001"]).unwrap();
-        assert_eq!(cmd.metrics, Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9001)));
+        assert_eq!(
+            cmd.metrics,
+            Some(MetricsEndpoint::Tcp(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9001)))
+        );
 
         let cmd =
             NodeCommand::<()>::try_parse_from(["reth", "--metrics", "localhost:9001"]).unwrap();
-        assert_eq!(cmd.metrics, Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9001)));
+        assert_eq!(
+            cmd.metrics,
+            Some(MetricsEndpoint::Tcp(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9001)))
+        );
     }
 
     #[test]
@@ -1217,4 +1685,210 @@ mod tests {
         // check network listening port number
         assert_eq!(cmd.network.port, 30305);
     }
+
+    #[test]
+    fn parse_instance_conflicts_with_explicit_ports() {
+        for port in ["--port", "--http.port", "--ws.port", "--authrpc.port"] {
+            let err =
+                NodeCommand::<()>::try_parse_from(["reth", "--instance", "2", port, "30303"])
+                    .unwrap_err();
+            assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict, "{port}");
+        }
+
+        // explicit ports are fine without an instance
+        let cmd = NodeCommand::<()>::try_parse_from(["reth", "--port", "30303"]).unwrap();
+        assert_eq!(cmd.network.port, 30303);
+        assert_eq!(cmd.instance, 1);
+    }
+
+    #[test]
+    fn parse_header_batch_size() {
+        let config = HeadersConfig::default();
+
+        let cmd = NodeCommand::<()>::parse_from(["reth"]);
+        assert_eq!(cmd.downloader.header_batch_size, None);
+        assert_eq!(
+            cmd.header_downloader_builder(config),
+            ReverseHeadersDownloaderBuilder::new(config)
+        );
+
+        let cmd = NodeCommand::<()>::parse_from(["reth", "--downloader.header-batch-size", "128"]);
+        assert_eq!(cmd.downloader.header_batch_size, Some(128));
+        assert_eq!(
+            cmd.header_downloader_builder(config),
+            ReverseHeadersDownloaderBuilder::new(config).request_limit(128)
+        );
+    }
+
+    #[test]
+    fn parse_body_concurrency() {
+        let config = BodiesConfig::default();
+
+        let cmd = NodeCommand::<()>::parse_from(["reth"]);
+        assert_eq!(
+            cmd.body_downloader_builder(config).concurrent_requests_range,
+            config.downloader_min_concurrent_requests..=config.downloader_max_concurrent_requests
+        );
+
+        let cmd = NodeCommand::<()>::parse_from(["reth", "--downloader.body-concurrency", "20"]);
+        assert_eq!(cmd.body_downloader_builder(config).concurrent_requests_range, 5..=20);
+
+        // the minimum never exceeds the maximum
+        let cmd = NodeCommand::<()>::parse_from(["reth", "--downloader.body-concurrency", "2"]);
+        assert_eq!(cmd.body_downloader_builder(config).concurrent_requests_range, 2..=2);
+    }
+
+    #[test]
+    fn parse_snapshot_block_interval() {
+        let cmd = NodeCommand::<()>::parse_from(["reth"]);
+        assert_eq!(cmd.snapshot_block_interval(), cmd.chain.snapshot_block_interval);
+
+        let cmd = NodeCommand::<()>::parse_from(["reth", "--snapshot.block-interval", "1234"]);
+        assert_ne!(cmd.chain.snapshot_block_interval, 1234);
+        assert_eq!(cmd.snapshot_block_interval(), 1234);
+    }
+
+    #[test]
+    fn parse_pipeline_run_threshold() {
+        use reth_blockchain_tree::noop::NoopBlockchainTree;
+        use reth_db::test_utils::create_test_rw_db;
+        use reth_interfaces::{sync::NoopSyncStateUpdater, test_utils::NoopFullBlockClient};
+        use reth_tasks::TokioTaskExecutor;
+
+        let cmd = NodeCommand::<()>::parse_from(["reth"]);
+        assert_eq!(cmd.pipeline_run_threshold(), MIN_BLOCKS_FOR_PIPELINE_RUN);
+
+        let cmd = NodeCommand::<()>::parse_from(["reth", "--pipeline-run-threshold", "1024"]);
+        assert_eq!(cmd.pipeline_run_threshold, Some(1024));
+        assert_eq!(cmd.pipeline_run_threshold(), 1024);
+
+        // the threshold is handed to the consensus engine
+        let factory = ProviderFactory::new(create_test_rw_db(), cmd.chain.clone());
+        let blockchain =
+            BlockchainProvider::new(factory.clone(), NoopBlockchainTree::default()).unwrap();
+        let (to_engine, rx) = unbounded_channel();
+        let (engine, _) = cmd
+            .build_consensus_engine(
+                NoopFullBlockClient::default(),
+                Pipeline::builder().build(factory),
+                blockchain,
+                Box::<TokioTaskExecutor>::default(),
+                Box::<NoopSyncStateUpdater>::default(),
+                None,
+                PayloadBuilderHandle::new(unbounded_channel().0),
+                None,
+                to_engine,
+                rx,
+                EngineHooks::new(),
+            )
+            .unwrap();
+        assert_eq!(engine.pipeline_run_threshold(), 1024);
+    }
+
+    #[test]
+    fn parse_trust_committed_senders() {
+        let cmd = NodeCommand::<()>::parse_from(["reth"]);
+        assert!(!cmd.tree_config().unwrap().trusted_senders());
+
+        let cmd = NodeCommand::<()>::parse_from(["reth", "--clayer.trust-committed-senders"]);
+        assert!(cmd.tree_config().unwrap().trusted_senders());
+
+        // without a consensus layer nothing vouches for the blocks
+        let cmd =
+            NodeCommand::<()>::parse_from(["reth", "--dev", "--clayer.trust-committed-senders"]);
+        assert!(cmd.tree_config().is_err());
+    }
+
+    #[tokio::test]
+    async fn fetch_tip_gives_up_after_retries() {
+        use reth_db::test_utils::create_test_rw_db;
+        use reth_interfaces::{p2p::error::RequestError, test_utils::TestHeadersClient};
+
+        let factory = ProviderFactory::new(create_test_rw_db(), reth_primitives::MAINNET.clone());
+        let client = TestHeadersClient::default();
+        client.set_error(RequestError::Timeout).await;
+        let tip = B256::with_last_byte(1);
+
+        let cmd = NodeCommand::<()>::parse_from(["reth", "--debug.tip-fetch-retries", "2"]);
+        let err = cmd.lookup_or_fetch_tip(factory, &client, tip).await.unwrap_err();
+
+        assert!(err.to_string().contains("after 3 attempts"), "{err}");
+        assert_eq!(client.request_attempts(), 3);
+    }
+
+    #[test]
+    fn consensus_tables_in_state_dir() {
+        use reth_db::init_db;
+        use reth_provider::{ConsensusNumberReader, ConsensusNumberWriter};
+
+        let datadir = tempfile::tempdir().unwrap();
+        let state_dir = datadir.path().join("clayer");
+        let db = Arc::new(init_db(datadir.path().join("db"), None).unwrap());
+        let factory = ProviderFactory::new(db, reth_primitives::MAINNET.clone());
+
+        let cmd = NodeCommand::<()>::parse_from([
+            "reth",
+            "--clayer.state-dir",
+            state_dir.to_str().unwrap(),
+        ]);
+        let consensus_db = cmd.consensus_provider(&factory).unwrap();
+        let hash = B256::with_last_byte(1);
+        consensus_db.save_consensus_number(hash, 1).unwrap();
+
+        assert!(state_dir.join("mdbx.dat").exists());
+        assert_eq!(consensus_db.consensus_number(hash).unwrap(), Some(1));
+        // the database of the execution layer is untouched
+        assert_eq!(factory.consensus_number(hash).unwrap(), None);
+
+        // the consensus database is opened with the `--db.*` size limits
+        let cmd = NodeCommand::<()>::parse_from([
+            "reth",
+            "--clayer.state-dir",
+            datadir.path().join("invalid").to_str().unwrap(),
+            "--db.max-size",
+            "1",
+            "--db.growth-step",
+            "2048",
+        ]);
+        assert!(cmd.consensus_provider(&factory).is_err());
+        assert!(!datadir.path().join("invalid").exists());
+    }
+
+    #[tokio::test]
+    async fn node_started_waits_for_consensus_readiness() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let readiness = ConsensusReadiness::default();
+        let started = Arc::new(AtomicBool::new(false));
+        let waiting = tokio::spawn({
+            let (readiness, started) = (readiness.clone(), started.clone());
+            on_consensus_ready(Some(readiness), None, move || {
+                started.store(true, Ordering::SeqCst);
+                Ok(())
+            })
+        });
+
+        // the execution layer answered, but no validators are connected yet
+        readiness.set_engine_api_ready();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!started.load(Ordering::SeqCst));
+
+        readiness.set_peered();
+        tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap().unwrap().unwrap();
+        assert!(started.load(Ordering::SeqCst));
+
+        // gives up waiting after the timeout
+        let started = AtomicBool::new(false);
+        on_consensus_ready(
+            Some(ConsensusReadiness::default()),
+            Some(Duration::from_millis(10)),
+            || {
+                started.store(true, Ordering::SeqCst);
+                Ok(())
+            },
+        )
+        .await
+        .unwrap();
+        assert!(started.load(Ordering::SeqCst));
+    }
 }
@@ -9,9 +9,33 @@ use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use metrics_util::layers::{PrefixLayer, Stack};
 use reth_db::database_metrics::DatabaseMetrics;
 use reth_metrics::metrics::Unit;
-use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+use std::{convert::Infallible, fmt, net::SocketAddr, path::PathBuf, sync::Arc};
 use tracing::error;
 
+/// Where the Prometheus metrics are served
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetricsEndpoint {
+    /// HTTP at the given interface and port
+    Tcp(SocketAddr),
+    /// HTTP over the Unix domain socket at the given path
+    Unix(PathBuf),
+}
+
+impl From<SocketAddr> for MetricsEndpoint {
+    fn from(addr: SocketAddr) -> Self {
+        Self::Tcp(addr)
+    }
+}
+
+impl fmt::Display for MetricsEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => addr.fmt(f),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
 pub(crate) trait Hook: Fn() + Send + Sync {}
 impl<T: Fn() + Send + Sync> Hook for T {}
 
@@ -34,14 +58,14 @@ pub(crate) fn install_recorder() -> eyre::Result<PrometheusHandle> {
 /// The hooks are called every time the metrics are requested at the given endpoint, and can be used
 /// to record values for pull-style metrics, i.e. metrics that are not automatically updated.
 pub(crate) async fn serve_with_hooks<F: Hook + 'static>(
-    listen_addr: SocketAddr,
+    endpoint: MetricsEndpoint,
     handle: PrometheusHandle,
     hooks: impl IntoIterator<Item = F>,
 ) -> eyre::Result<()> {
     let hooks: Vec<_> = hooks.into_iter().collect();
 
     // Start endpoint
-    start_endpoint(listen_addr, handle, Arc::new(move || hooks.iter().for_each(|hook| hook())))
+    start_endpoint(endpoint, handle, Arc::new(move || hooks.iter().for_each(|hook| hook())))
         .await
         .wrap_err("Could not start Prometheus endpoint")?;
 
@@ -50,7 +74,7 @@ pub(crate) async fn serve_with_hooks<F: Hook + 'static>(
 
 /// Starts an endpoint at the given address to serve Prometheus metrics.
 async fn start_endpoint<F: Hook + 'static>(
-    listen_addr: SocketAddr,
+    endpoint: MetricsEndpoint,
     handle: PrometheusHandle,
     hook: Arc<F>,
 ) -> eyre::Result<()> {
@@ -65,17 +89,70 @@ async fn start_endpoint<F: Hook + 'static>(
             }))
         }
     });
-    let server =
-        Server::try_bind(&listen_addr).wrap_err("Could not bind to address")?.serve(make_svc);
 
-    tokio::spawn(async move { server.await.expect("Metrics endpoint crashed") });
+    match endpoint {
+        MetricsEndpoint::Tcp(listen_addr) => {
+            let server = Server::try_bind(&listen_addr)
+                .wrap_err("Could not bind to address")?
+                .serve(make_svc);
+            tokio::spawn(async move { server.await.expect("Metrics endpoint crashed") });
+        }
+        #[cfg(unix)]
+        MetricsEndpoint::Unix(path) => {
+            let server = Server::builder(unix::UnixAccept::bind(&path)?).serve(make_svc);
+            tokio::spawn(async move { server.await.expect("Metrics endpoint crashed") });
+        }
+        #[cfg(not(unix))]
+        MetricsEndpoint::Unix(_) => {
+            eyre::bail!("Serving metrics over a Unix socket is not supported on this platform")
+        }
+    }
 
     Ok(())
 }
 
+#[cfg(unix)]
+mod unix {
+    use eyre::WrapErr;
+    use hyper::server::accept::Accept;
+    use std::{
+        fs,
+        os::unix::fs::FileTypeExt,
+        path::Path,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use tokio::net::{UnixListener, UnixStream};
+
+    /// Accepts the connections of a Unix socket for [hyper::Server]
+    pub(super) struct UnixAccept(UnixListener);
+
+    impl UnixAccept {
+        /// Binds the socket at `path`, replacing the socket left behind by a previous run.
+        pub(super) fn bind(path: &Path) -> eyre::Result<Self> {
+            if fs::symlink_metadata(path).map_or(false, |meta| meta.file_type().is_socket()) {
+                fs::remove_file(path).wrap_err("Could not remove stale socket")?;
+            }
+            Ok(Self(UnixListener::bind(path).wrap_err("Could not bind to socket")?))
+        }
+    }
+
+    impl Accept for UnixAccept {
+        type Conn = UnixStream;
+        type Error = std::io::Error;
+
+        fn poll_accept(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+            self.0.poll_accept(cx).map(|res| Some(res.map(|(stream, _)| stream)))
+        }
+    }
+}
+
 /// Serves Prometheus metrics over HTTP with database and process metrics.
 pub(crate) async fn serve<Metrics>(
-    listen_addr: SocketAddr,
+    endpoint: MetricsEndpoint,
     handle: PrometheusHandle,
     db: Metrics,
     process: metrics_process::Collector,
@@ -93,7 +170,7 @@ where
         Box::new(collect_memory_stats),
         Box::new(collect_io_stats),
     ];
-    serve_with_hooks(listen_addr, handle, hooks).await?;
+    serve_with_hooks(endpoint, handle, hooks).await?;
 
     // We describe the metrics after the recorder is installed, otherwise this information is not
     // registered
@@ -238,3 +315,40 @@ fn collect_io_stats() {}
 
 #[cfg(not(target_os = "linux"))]
 fn describe_io_stats() {}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use metrics::{Key, Recorder};
+    use std::{
+        io::{Read, Write},
+        os::unix::net::UnixStream,
+    };
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serves_metrics_over_unix_socket() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        recorder.register_counter(&Key::from_name("scrapes")).increment(1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metrics.sock");
+        let hooks: Vec<Box<dyn Hook<Output = ()>>> = vec![];
+        serve_with_hooks(MetricsEndpoint::Unix(path.clone()), handle.clone(), hooks).await.unwrap();
+
+        let response = tokio::task::spawn_blocking(move || {
+            let mut stream = UnixStream::connect(path).unwrap();
+            stream.write_all(b"GET / HTTP/1.0\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        })
+        .await
+        .unwrap();
+
+        assert!(response.starts_with("HTTP/1.0 200 OK"), "{response}");
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        assert_eq!(body, handle.render());
+        assert!(body.contains("scrapes 1"), "{body}");
+    }
+}
@@ -84,10 +84,10 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
         prune_modes: Option<PruneModes>,
     ) -> RethResult<Self> {
         let max_reorg_depth = config.max_reorg_depth() as usize;
-        // The size of the broadcast is twice the maximum reorg depth, because at maximum reorg
-        // depth at least N blocks must be sent at once.
+        // Bounded, subscribers that fall behind by more than the capacity miss the oldest
+        // notifications
         let (canon_state_notification_sender, _receiver) =
-            tokio::sync::broadcast::channel(max_reorg_depth * 2);
+            tokio::sync::broadcast::channel(config.canon_state_notification_capacity());
 
         let last_canonical_hashes =
             externals.fetch_latest_canonical_hashes(config.num_of_canonical_hashes() as usize)?;
@@ -676,12 +676,26 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
     /// # Note
     ///
     /// This recovers transaction signers (unlike [`BlockchainTree::insert_block`]).
+    ///
+    /// If the senders are trusted, see [`BlockchainTreeConfig::with_trusted_senders`], known blocks
+    /// are not recovered again and the signatures are not checked for a low `s` value.
     pub fn insert_block_without_senders(
         &mut self,
         block: SealedBlock,
+        block_validation_kind: BlockValidationKind,
     ) -> Result<InsertPayloadOk, InsertBlockError> {
-        match block.try_seal_with_senders() {
-            Ok(block) => self.insert_block(block, BlockValidationKind::Exhaustive),
+        let block = if self.config.trusted_senders() {
+            match self.is_block_known(block.num_hash()) {
+                Ok(Some(status)) => return Ok(InsertPayloadOk::AlreadySeen(status)),
+                Err(err) => return Err(InsertBlockError::new(block, err)),
+                _ => {}
+            }
+            block.try_seal_with_senders_unchecked()
+        } else {
+            block.try_seal_with_senders()
+        };
+        match block {
+            Ok(block) => self.insert_block(block, block_validation_kind),
             Err(block) => Err(InsertBlockError::sender_recovery_error(block)),
         }
     }
@@ -1135,6 +1149,9 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
 
         // send notification about new canonical chain.
         let _ = self.canon_state_notification_sender.send(chain_notification);
+        self.metrics
+            .canon_state_notifications_queued
+            .set(self.canon_state_notification_sender.len() as f64);
 
         debug!(
             target: "blockchain_tree",
@@ -1281,7 +1298,7 @@ mod tests {
     use assert_matches::assert_matches;
     use linked_hash_set::LinkedHashSet;
     use reth_db::{tables, test_utils::TempDatabase, transaction::DbTxMut, DatabaseEnv};
-    use reth_interfaces::test_utils::TestConsensus;
+    use reth_interfaces::{blockchain_tree::BlockchainTreeEngine, test_utils::TestConsensus};
     use reth_primitives::{
         constants::{EIP1559_INITIAL_BASE_FEE, EMPTY_ROOT_HASH, ETHEREUM_BLOCK_GAS_LIMIT},
         keccak256,
@@ -1293,6 +1310,7 @@ mod tests {
         B256, MAINNET,
     };
     use reth_provider::{
+        providers::BlockchainProvider,
         test_utils::{
             blocks::BlockChainTestData, create_test_provider_factory_with_chain_spec,
             TestExecutorFactory,
@@ -1980,4 +1998,83 @@ mod tests {
             .with_buffered_blocks(BTreeMap::from([]))
             .assert(&tree);
     }
+
+    #[test]
+    fn trusted_senders_skip_recovery_of_known_blocks() {
+        let data = BlockChainTestData::default_with_numbers(11, 12);
+        let (block1, exec1) = data.blocks[0].clone();
+        let externals = setup_externals(vec![exec1]);
+        setup_genesis(&externals.provider_factory, data.genesis);
+
+        let config = BlockchainTreeConfig::new(1, 2, 3, 2).with_trusted_senders(true);
+        let mut tree = BlockchainTree::new(externals, config, None).expect("failed to create tree");
+        tree.make_canonical(&B256::ZERO).unwrap();
+        tree.finalize_block(10);
+
+        assert_eq!(
+            tree.insert_block_without_senders(
+                block1.block.clone(),
+                BlockValidationKind::Exhaustive
+            )
+            .unwrap(),
+            InsertPayloadOk::Inserted(BlockStatus::Valid)
+        );
+
+        // same header, but a transaction whose sender can't be recovered
+        let mut unrecoverable = block1.block.clone();
+        unrecoverable.body.push(TransactionSigned::default());
+        assert_eq!(
+            tree.insert_block_without_senders(
+                unrecoverable.clone(),
+                BlockValidationKind::Exhaustive
+            )
+            .unwrap(),
+            InsertPayloadOk::AlreadySeen(BlockStatus::Valid)
+        );
+
+        tree.config = tree.config.with_trusted_senders(false);
+        assert_matches!(
+            tree.insert_block_without_senders(unrecoverable, BlockValidationKind::Exhaustive)
+                .unwrap_err()
+                .kind(),
+            InsertBlockErrorKind::SenderRecovery
+        );
+    }
+
+    #[test]
+    fn trusted_senders_through_blockchain_provider() {
+        let data = BlockChainTestData::default_with_numbers(11, 12);
+        let (block1, exec1) = data.blocks[0].clone();
+        let externals = setup_externals(vec![exec1]);
+        let provider_factory = externals.provider_factory.clone();
+        setup_genesis(&provider_factory, data.genesis);
+
+        let config = BlockchainTreeConfig::new(1, 2, 3, 2).with_trusted_senders(true);
+        let mut tree = BlockchainTree::new(externals, config, None).expect("failed to create tree");
+        tree.make_canonical(&B256::ZERO).unwrap();
+        tree.finalize_block(10);
+
+        // the beacon engine inserts blocks through the provider
+        let provider = BlockchainProvider::with_latest(
+            provider_factory,
+            ShareableBlockchainTree::new(tree),
+            block1.block.header.clone(),
+        );
+        assert_eq!(
+            provider
+                .insert_block_without_senders(block1.block.clone(), BlockValidationKind::Exhaustive)
+                .unwrap(),
+            InsertPayloadOk::Inserted(BlockStatus::Valid)
+        );
+
+        // the senders of the known block are not recovered again
+        let mut unrecoverable = block1.block.clone();
+        unrecoverable.body.push(TransactionSigned::default());
+        assert_eq!(
+            provider
+                .insert_block_without_senders(unrecoverable, BlockValidationKind::Exhaustive)
+                .unwrap(),
+            InsertPayloadOk::AlreadySeen(BlockStatus::Valid)
+        );
+    }
 }
@@ -19,6 +19,12 @@ pub struct BlockchainTreeConfig {
     /// be 256. It covers both number of blocks required for reorg, and number of blocks
     /// required for `BLOCKHASH` EVM opcode.
     num_of_additional_canonical_block_hashes: u64,
+    /// Number of canonical state notifications buffered for subscribers, defaults to twice the
+    /// `max_reorg_depth`.
+    canon_state_notification_capacity: Option<usize>,
+    /// Whether the transaction senders of blocks inserted without senders are trusted, see
+    /// [BlockchainTreeConfig::with_trusted_senders].
+    trusted_senders: bool,
 }
 
 impl Default for BlockchainTreeConfig {
@@ -33,6 +39,8 @@ impl Default for BlockchainTreeConfig {
             num_of_additional_canonical_block_hashes: 256,
             // max unconnected blocks.
             max_unconnected_blocks: 200,
+            canon_state_notification_capacity: None,
+            trusted_senders: false,
         }
     }
 }
@@ -53,9 +61,43 @@ impl BlockchainTreeConfig {
             max_reorg_depth,
             num_of_additional_canonical_block_hashes,
             max_unconnected_blocks,
+            canon_state_notification_capacity: None,
+            trusted_senders: false,
         }
     }
 
+    /// Sets the number of canonical state notifications buffered for subscribers.
+    ///
+    /// Once the buffer is full, a subscriber that has not received the oldest notification yet
+    /// lags behind: the oldest notification is dropped for it and it continues with the oldest
+    /// one still buffered. The tree never waits for slow subscribers.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0.
+    pub fn with_canon_state_notification_capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "canonical state notification capacity must be positive");
+        self.canon_state_notification_capacity = Some(capacity);
+        self
+    }
+
+    /// Sets whether the transaction senders of blocks inserted without senders are trusted.
+    ///
+    /// Trusted senders are recovered without checking that the signatures have a low `s` value and
+    /// are not recovered at all for blocks the tree already knows.
+    ///
+    /// WARNING: Only safe if every block handed to the tree is known to be valid, e.g. committed by
+    /// a permissioned consensus layer. Blocks with malleable signatures are accepted otherwise.
+    pub fn with_trusted_senders(mut self, trusted: bool) -> Self {
+        self.trusted_senders = trusted;
+        self
+    }
+
+    /// Return whether the transaction senders of blocks inserted without senders are trusted.
+    pub fn trusted_senders(&self) -> bool {
+        self.trusted_senders
+    }
+
     /// Return the maximum reorg depth.
     pub fn max_reorg_depth(&self) -> u64 {
         self.max_reorg_depth
@@ -88,4 +130,12 @@ impl BlockchainTreeConfig {
     pub fn max_unconnected_blocks(&self) -> usize {
         self.max_unconnected_blocks
     }
+
+    /// Return the number of canonical state notifications buffered for subscribers.
+    ///
+    /// Defaults to twice the maximum reorg depth, because at maximum reorg depth at least N blocks
+    /// must be sent at once.
+    pub fn canon_state_notification_capacity(&self) -> usize {
+        self.canon_state_notification_capacity.unwrap_or(self.max_reorg_depth as usize * 2)
+    }
 }
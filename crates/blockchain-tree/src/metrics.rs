@@ -19,6 +19,8 @@ pub struct TreeMetrics {
     pub latest_reorg_depth: Gauge,
     /// Longest sidechain height
     pub longest_sidechain_height: Gauge,
+    /// Number of canonical state notifications the slowest subscriber has not received yet
+    pub canon_state_notifications_queued: Gauge,
 }
 
 /// Metrics for the blockchain tree block buffer
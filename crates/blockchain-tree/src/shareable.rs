@@ -45,6 +45,18 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         tree.buffer_block(block)
     }
 
+    fn insert_block_without_senders(
+        &self,
+        block: SealedBlock,
+        validation_kind: BlockValidationKind,
+    ) -> Result<InsertPayloadOk, InsertBlockError> {
+        trace!(target: "blockchain_tree", hash=?block.hash, number=block.number, parent_hash=?block.parent_hash, "Inserting block without senders");
+        let mut tree = self.tree.write();
+        let res = tree.insert_block_without_senders(block, validation_kind);
+        tree.update_chains_metrics();
+        res
+    }
+
     fn insert_block(
         &self,
         block: SealedBlockWithSenders,
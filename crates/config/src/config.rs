@@ -184,6 +184,8 @@ pub struct ExecutionConfig {
     pub max_changes: Option<u64>,
     /// The maximum gas to process before the execution stage commits.
     pub max_cumulative_gas: Option<u64>,
+    /// Whether to verify the receipts of a batch in parallel once it's executed.
+    pub parallel_receipt_verification: bool,
 }
 
 impl Default for ExecutionConfig {
@@ -193,6 +195,7 @@ impl Default for ExecutionConfig {
             max_changes: Some(5_000_000),
             // 50k full blocks of 30M gas
             max_cumulative_gas: Some(30_000_000 * 50_000),
+            parallel_receipt_verification: false,
         }
     }
 }
@@ -374,6 +374,12 @@ where
         self.handle.clone()
     }
 
+    /// Returns the gap to the sync target above which missing blocks are downloaded and executed
+    /// with the pipeline instead of the blockchain tree.
+    pub fn pipeline_run_threshold(&self) -> u64 {
+        self.pipeline_run_threshold
+    }
+
     /// Returns true if the distance from the local tip to the block is greater than the configured
     /// threshold.
     ///
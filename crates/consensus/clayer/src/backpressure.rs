@@ -0,0 +1,246 @@
+//! Backpressure between the pipeline and the consensus engine.
+//!
+//! While the pipeline is syncing, blocks committed by consensus are not executed locally until the
+//! pipeline reaches them. If the pipeline falls too far behind, the primary slows down its
+//! proposals so the node does not keep committing blocks it can not execute.
+//!
+//! Publish ticks that pile up while the node is busy are dropped as stale, see [TickQueue].
+
+use reth_metrics::{
+    metrics::{Counter, Gauge},
+    Metrics,
+};
+use reth_primitives::{stage::StageId, BlockNumber};
+use reth_stages::PipelineEvent;
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+use tracing::warn;
+
+/// Default number of committed blocks the pipeline may lag behind before proposals slow down.
+pub const DEFAULT_MAX_PIPELINE_LAG: u64 = 64;
+
+/// Default number of publish ticks queued before the oldest ones are dropped as stale.
+pub const DEFAULT_MAX_QUEUED_TICKS: usize = 16;
+
+/// Upper bound of the factor the proposal interval is multiplied with.
+const MAX_SLOWDOWN_FACTOR: u32 = 8;
+
+/// Number of consecutive ticks that dropped stale ticks after which the node is considered unable
+/// to keep up.
+const PERSISTENT_DROP_TICKS: u32 = 3;
+
+#[derive(Debug, Default)]
+struct PipelineProgressInner {
+    /// Whether the pipeline is currently running
+    syncing: bool,
+    /// The last block fully processed by the pipeline
+    height: BlockNumber,
+}
+
+/// Progress of the pipeline, updated from [PipelineEvent]s and shared with the consensus engine.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineProgress {
+    inner: Arc<parking_lot::RwLock<PipelineProgressInner>>,
+}
+
+impl PipelineProgress {
+    /// Updates the progress with an event emitted by the pipeline.
+    pub fn on_pipeline_event(&self, event: &PipelineEvent) {
+        let mut inner = self.inner.write();
+        match event {
+            PipelineEvent::Run { .. } => inner.syncing = true,
+            PipelineEvent::Ran { stage_id: StageId::Finish, result, .. } => {
+                inner.height = result.checkpoint.block_number;
+                if result.done {
+                    inner.syncing = false;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns how many blocks the pipeline is behind the `committed` block.
+    ///
+    /// An idle pipeline never lags, blocks are then executed as they are committed.
+    pub fn lag(&self, committed: BlockNumber) -> u64 {
+        let inner = self.inner.read();
+        if inner.syncing {
+            committed.saturating_sub(inner.height)
+        } else {
+            0
+        }
+    }
+}
+
+/// Returns the minimum interval between two proposals.
+///
+/// Once the pipeline lags more than `max_lag` blocks behind the `committed` block, `base` is
+/// multiplied by one more for every `max_lag` blocks of lag, up to [MAX_SLOWDOWN_FACTOR]. A
+/// `max_lag` of 0 disables backpressure.
+pub fn proposal_interval(
+    base: Duration,
+    progress: &PipelineProgress,
+    committed: BlockNumber,
+    max_lag: u64,
+) -> Duration {
+    if max_lag == 0 {
+        return base
+    }
+    let lag = progress.lag(committed);
+    if lag <= max_lag {
+        return base
+    }
+    let factor = (lag / max_lag + 1).min(MAX_SLOWDOWN_FACTOR as u64) as u32;
+    base * factor
+}
+
+/// Metrics of the publish tick queue
+#[derive(Metrics)]
+#[metrics(scope = "consensus.clayer.publish_ticks")]
+struct TickQueueMetrics {
+    /// Number of queued publish ticks
+    queued: Gauge,
+    /// Number of stale publish ticks dropped because the queue was full
+    dropped: Counter,
+}
+
+/// Publish ticks waiting to be handled, bounded in size.
+///
+/// Once the queue is full the oldest ticks are dropped, they are stale by the time the node gets
+/// to them. Drops on several consecutive ticks mean the node can't keep up and are logged.
+#[derive(Debug)]
+pub(crate) struct TickQueue {
+    ticks: VecDeque<u64>,
+    max_len: usize,
+    dropped: u64,
+    /// Number of consecutive pushes that dropped ticks
+    consecutive_drops: u32,
+    metrics: TickQueueMetrics,
+}
+
+impl TickQueue {
+    /// Creates a queue holding up to `max_len` ticks.
+    pub(crate) fn new(max_len: usize) -> Self {
+        Self {
+            ticks: VecDeque::new(),
+            max_len: max_len.max(1),
+            dropped: 0,
+            consecutive_drops: 0,
+            metrics: TickQueueMetrics::default(),
+        }
+    }
+
+    /// Queues `tick`, dropping the oldest ticks beyond the maximum length.
+    pub(crate) fn push(&mut self, tick: u64) {
+        self.ticks.push_back(tick);
+        let mut dropped = 0;
+        while self.ticks.len() > self.max_len {
+            self.ticks.pop_front();
+            dropped += 1;
+        }
+        self.metrics.queued.set(self.ticks.len() as f64);
+        if dropped == 0 {
+            self.consecutive_drops = 0;
+            return
+        }
+
+        self.dropped += dropped;
+        self.metrics.dropped.increment(dropped);
+        self.consecutive_drops += 1;
+        if self.consecutive_drops >= PERSISTENT_DROP_TICKS {
+            warn!(target: "consensus::cl", dropped = self.dropped, max_queued = self.max_len, consecutive = self.consecutive_drops, "Dropping stale publish ticks, the node can't keep up");
+        }
+    }
+
+    /// Removes the oldest tick.
+    pub(crate) fn pop_front(&mut self) -> Option<u64> {
+        let tick = self.ticks.pop_front();
+        self.metrics.queued.set(self.ticks.len() as f64);
+        tick
+    }
+
+    /// Returns whether no tick is queued.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.ticks.is_empty()
+    }
+
+    /// Returns the number of ticks dropped because the queue was full.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::stage::StageCheckpoint;
+    use reth_stages::{ExecOutput, PipelineStagesProgress};
+
+    fn run(stage_id: StageId) -> PipelineEvent {
+        PipelineEvent::Run {
+            pipeline_stages_progress: PipelineStagesProgress { current: 1, total: 2 },
+            stage_id,
+            checkpoint: None,
+            target: None,
+        }
+    }
+
+    fn ran_finish(block_number: BlockNumber, done: bool) -> PipelineEvent {
+        PipelineEvent::Ran {
+            pipeline_stages_progress: PipelineStagesProgress { current: 2, total: 2 },
+            stage_id: StageId::Finish,
+            result: ExecOutput { checkpoint: StageCheckpoint::new(block_number), done },
+        }
+    }
+
+    #[test]
+    fn pipeline_lag_slows_proposals() {
+        let base = Duration::from_secs(5);
+        let progress = PipelineProgress::default();
+        let interval = |committed| proposal_interval(base, &progress, committed, 10);
+
+        // idle pipeline, no backpressure
+        assert_eq!(interval(100), base);
+
+        progress.on_pipeline_event(&run(StageId::Headers));
+        progress.on_pipeline_event(&ran_finish(90, false));
+        assert_eq!(progress.lag(100), 10);
+        assert_eq!(interval(100), base);
+
+        // consensus keeps committing while the pipeline lags
+        assert_eq!(interval(101), base * 2);
+        assert_eq!(interval(115), base * 3);
+        assert_eq!(interval(10_000), base * MAX_SLOWDOWN_FACTOR);
+
+        // disabled
+        assert_eq!(proposal_interval(base, &progress, 10_000, 0), base);
+
+        // the pipeline caught up
+        progress.on_pipeline_event(&run(StageId::Finish));
+        progress.on_pipeline_event(&ran_finish(125, true));
+        assert_eq!(progress.lag(125), 0);
+        assert_eq!(interval(126), base);
+    }
+
+    #[test]
+    fn drops_stale_ticks_past_capacity() {
+        let mut queue = TickQueue::new(3);
+        for tick in 0..3 {
+            queue.push(tick);
+        }
+        assert_eq!(queue.dropped(), 0);
+
+        // nothing is handled while the ticks keep coming
+        for tick in 3..8 {
+            queue.push(tick);
+        }
+        assert_eq!(queue.dropped(), 5);
+        assert_eq!(queue.consecutive_drops, 5);
+        assert_eq!(queue.pop_front(), Some(5));
+
+        // a tick that fits resets the streak
+        queue.push(8);
+        assert_eq!(queue.consecutive_drops, 0);
+        assert_eq!(queue.dropped(), 5);
+        assert_eq!(queue.ticks, [6, 7, 8]);
+    }
+}
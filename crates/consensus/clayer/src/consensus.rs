@@ -1,4 +1,17 @@
+mod block_metadata;
+pub use block_metadata::{BlockMetadata, SignedBlockMetadata};
+mod command;
+pub use command::*;
+mod compression;
+pub use compression::Compression;
+use compression::{
+    decode_frame, engine_capabilities_frame, hello_frame, ping_frame, pong_frame, Frame,
+};
 mod config;
+mod event_queue;
+use event_queue::EventQueue;
+mod heartbeat;
+use heartbeat::Heartbeat;
 // use alloy_primitives::{keccak256, B64};
 pub use config::*;
 mod logs;
@@ -6,41 +19,86 @@ pub use logs::*;
 mod message;
 use lru_cache::LruCache;
 pub use message::*;
+mod message_log;
+pub use message_log::*;
+mod message_replay;
+pub use message_replay::*;
+mod metrics;
+use metrics::{CommitMetrics, PeerMessageStats, PhaseTimings};
+pub use metrics::{PeerMessageCounts, PhaseSampleCounts};
 mod pbft_error;
 pub use pbft_error::*;
+mod seen_cache;
+use seen_cache::{message_id, SeenCache};
+pub use seen_cache::{DEFAULT_SEEN_CACHE_SIZE, DEFAULT_SEEN_CACHE_TTL};
+mod signer;
+pub use signer::*;
 mod state;
 use reth_db::models::consensus::ConsensusBytes;
-use reth_provider::{BlockReaderIdExt, ConsensusNumberReader, ConsensusNumberWriter};
+use reth_provider::{
+    BlockHashReader, BlockReaderIdExt, ConsensusNumberReader, ConsensusNumberWriter,
+};
 use reth_rpc_types::{engine::PayloadId, ExecutionPayloadV1, ExecutionPayloadV2, PeerId};
 pub use state::*;
 mod validators;
+pub use validators::{ValidatorSetChange, VALIDATOR_ADDED_EVENT, VALIDATOR_REMOVED_EVENT};
+mod view_changes;
+pub use view_changes::{ViewChangeReason, ViewChangeRecord};
 
 use alloy_rlp::{Decodable, Encodable};
 use itertools::Itertools;
 use reth_ecies::util::id2pk;
 use reth_eth_wire::{
-    ClayerBlock, ClayerConsensusMessage, ClayerConsensusMessageHeader, ClayerExecutionPayload,
-    ClayerSignature, PbftMessage, PbftMessageInfo, PbftMessageType, PbftNewValidator, PbftNewView,
-    PbftSeal, PbftSignedVote,
+    ClayerBlock, ClayerConsensusMessage, ClayerConsensusMessageHeader, ClayerConsensusMessageV1,
+    ClayerExecutionPayload, ClayerSignature, PbftMessage, PbftMessageInfo, PbftMessageType,
+    PbftNewValidator, PbftNewView, PbftSeal, PbftSignedVote, CLAYER_PROTOCOL_VERSION,
 };
 use reth_interfaces::clayer::{ClayerConsensusEvent, ClayerConsensusMessageAgentTrait};
+use reth_network::peers::PeersHandle;
 use reth_primitives::{
-    keccak256, public_key_to_address, sign_message, BlockId, SealedHeader, B256, B64,
+    constants::EIP1559_DEFAULT_ELASTICITY_MULTIPLIER, keccak256, public_key_to_address, Address,
+    BlockId, SealedHeader, Signature, B256, B64,
 };
 use std::{
-    collections::{HashSet, VecDeque},
-    sync::Arc,
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    future::Future,
+    ops::{Range, RangeInclusive},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::{
+    mpsc::{self, Receiver, Sender},
+    oneshot,
 };
-use tokio::sync::mpsc::{self, Receiver, Sender};
 use tracing::*;
 
 use crate::{
-    engine_api::{ApiService, ExecutionPayloadWrapperV2},
-    timing::{retry_until_ok, Timeout},
+    backpressure::{proposal_interval, PipelineProgress},
+    engine_api::{ApiService, ApiServiceError, ExecutionPayloadWrapperV2},
+    engine_pbft::order_competing_blocks,
+    epoch::EpochTracker,
+    timing::{retry_until_ok, Timeout, ViewLifetime},
 };
 
 pub const ELECT_VOTING_ADDRESS: &str = "0x0000000000000000000000000000000000001000";
 
+/// How long [`ClayerConsensusMessagingAgent::broadcast_consensus_with_result`] waits for the
+/// network by default
+pub const DEFAULT_BROADCAST_RESULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Number of messages this node signed that are remembered to recognize the copies relayed back
+/// by its peers
+const OWN_MESSAGES_CACHE_SIZE: usize = 1024;
+
+/// Lowest gas limit a block may have
+const MINIMUM_GAS_LIMIT: u64 = 5000;
+
+/// A block gas limit must differ by less than `1 / GAS_LIMIT_BOUND_DIVISOR` from the parent's
+const GAS_LIMIT_BOUND_DIVISOR: u64 = 1024;
+
 pub struct ClayerConsensusMessagingAgent {
     pub inner: Arc<parking_lot::RwLock<ClayerConsensusMessagingAgentInner>>,
 }
@@ -57,6 +115,145 @@ impl ClayerConsensusMessagingAgent {
             inner: Arc::new(parking_lot::RwLock::new(ClayerConsensusMessagingAgentInner::new())),
         }
     }
+
+    /// Records every inbound and outbound consensus message to the given log.
+    pub fn with_message_log(self, message_log: ConsensusMessageLog) -> Self {
+        self.inner.write().message_log = Some(parking_lot::Mutex::new(message_log));
+        self
+    }
+
+    /// Only accepts messages tagged with `topic` and tags all messages of this node with it.
+    pub fn with_topic(self, topic: u64) -> Self {
+        self.inner.write().topic = topic;
+        self
+    }
+
+    /// Drops newly connected peers that don't introduce themselves as peers of this network within
+    /// `timeout`.
+    ///
+    /// Every connecting peer is sent a hello with the protocol version and topic of this node, a
+    /// peer completes the handshake with a hello of the same topic or a consensus message of this
+    /// network.
+    ///
+    /// A dropped peer is removed from the consensus peers and reported to the engine as
+    /// disconnected, it is only considered again once it reconnects.
+    pub fn with_handshake_timeout(self, timeout: Duration) -> Self {
+        self.inner.write().handshake_timeout = Some(timeout);
+        self
+    }
+
+    /// Compresses the messages to peers that support `compression`.
+    ///
+    /// Every connecting peer is sent the compressions this node can decode, messages to a peer
+    /// are only compressed once it advertised `compression` in return.
+    pub fn with_compression(self, compression: Compression) -> Self {
+        self.inner.write().compression = Some(compression);
+        self
+    }
+
+    /// Remembers up to `max_size` received messages for `ttl` to drop the copies relayed by other
+    /// peers. Once full, the oldest messages are forgotten first.
+    ///
+    /// Defaults to [DEFAULT_SEEN_CACHE_SIZE] messages for [DEFAULT_SEEN_CACHE_TTL].
+    pub fn with_seen_cache(self, max_size: usize, ttl: Duration) -> Self {
+        self.inner.write().seen = SeenCache::new(max_size, ttl);
+        self
+    }
+
+    /// Relays received messages for at most `message_ttl` hops, higher TTLs of received messages
+    /// are lowered to it.
+    pub fn with_message_ttl(self, message_ttl: u8) -> Self {
+        self.inner.write().message_ttl = message_ttl;
+        self
+    }
+
+    /// Sends consensus messages of protocol `version`, defaults to [CLAYER_PROTOCOL_VERSION].
+    ///
+    /// Version 1 messages can be decoded by validators of older releases but carry no TTL or
+    /// topic, they are not relayed.
+    pub fn with_protocol_version(self, version: u8) -> Self {
+        self.inner.write().protocol_version = version;
+        self
+    }
+
+    /// Pings the consensus peers every `interval` and marks the peers dead that were not heard
+    /// from within `timeout`.
+    ///
+    /// A dead peer stays connected but is removed from the consensus peers and reported to the
+    /// engine, it is a consensus peer again once it is heard from.
+    pub fn with_heartbeat(self, interval: Duration, timeout: Duration) -> Self {
+        self.inner.write().heartbeat = Some(Heartbeat::new(interval, timeout));
+        self
+    }
+
+    /// Adds the per peer message counts to the metrics at most once per `interval` instead of on
+    /// every message.
+    pub fn with_metrics_flush_interval(self, interval: Duration) -> Self {
+        self.inner.write().peer_stats.set_flush_interval(interval);
+        self
+    }
+
+    /// Waits up to `timeout` for the network to dispatch a broadcast with a result.
+    pub fn with_broadcast_result_timeout(self, timeout: Duration) -> Self {
+        self.inner.write().broadcast_result_timeout = timeout;
+        self
+    }
+
+    /// Reports the validators to the network, which reconnects them with its validator policy.
+    pub fn with_peers_handle(self, peers: PeersHandle) -> Self {
+        self.inner.write().peers = Some(peers);
+        self
+    }
+
+    /// Returns the topic consensus messages are tagged with.
+    pub fn topic(&self) -> u64 {
+        self.inner.read().topic
+    }
+
+    /// Sets the validators whose messages are tracked per peer, messages of all other peers are
+    /// tracked together.
+    pub fn set_validators(&self, validators: &[PeerId]) {
+        let mut inner = self.inner.write();
+        inner.peer_stats.set_validators(validators);
+        if let Some(peers) = &inner.peers {
+            peers.set_validators(validators.iter().copied().collect());
+        }
+    }
+
+    /// Advertises the engine API methods of the local execution layer to all consensus peers,
+    /// peers that connect later are sent them on connect.
+    ///
+    /// A peer whose execution layer supports different payload versions is logged with a warning,
+    /// it may propose blocks this node can't import or the other way around.
+    pub fn set_engine_capabilities(&self, capabilities: BTreeSet<String>) {
+        self.inner.write().set_engine_capabilities(capabilities);
+    }
+
+    /// Records a message from `peer_id` that the engine rejected.
+    pub fn record_rejected(&self, peer_id: PeerId) {
+        self.inner.write().peer_stats.record_rejected(peer_id);
+    }
+
+    /// Returns the message counts of `peer_id`, or the shared counts of all non-validators.
+    pub fn peer_message_counts(&self, peer_id: PeerId) -> PeerMessageCounts {
+        self.inner.read().peer_stats.counts(peer_id)
+    }
+
+    /// Broadcasts `data` to `peers`, or to all peers if empty, and resolves to the number of
+    /// peers the network sent it to.
+    ///
+    /// Resolves to `None` if the network did not dispatch the message within the broadcast result
+    /// timeout.
+    pub fn broadcast_consensus_with_result(
+        &self,
+        peers: Vec<PeerId>,
+        data: reth_primitives::Bytes,
+    ) -> impl Future<Output = Option<usize>> {
+        let mut inner = self.inner.write();
+        let timeout = inner.broadcast_result_timeout;
+        let result = inner.broadcast_consensus_with_result(peers, data);
+        async move { tokio::time::timeout(timeout, result).await.ok()?.ok() }
+    }
 }
 
 impl ClayerConsensusMessageAgentTrait for ClayerConsensusMessagingAgent {
@@ -87,6 +284,11 @@ impl ClayerConsensusMessageAgentTrait for ClayerConsensusMessagingAgent {
     fn broadcast_consensus(&self, peers: Vec<PeerId>, data: reth_primitives::Bytes) {
         self.inner.read().broadcast_consensus(peers, data);
     }
+
+    fn on_consensus_dispatched(&self, peers: usize) {
+        self.inner.write().on_consensus_dispatched(peers);
+    }
+
     /// get all peers
     fn get_peers(&self) -> Vec<PeerId> {
         self.inner.read().get_peers()
@@ -94,17 +296,82 @@ impl ClayerConsensusMessageAgentTrait for ClayerConsensusMessagingAgent {
 }
 
 pub struct ClayerConsensusMessagingAgentInner {
-    queued: VecDeque<ClayerConsensusEvent>,
+    queued: EventQueue,
     // cache_tx: crossbeam_channel::Sender<ClayerConsensusEvent>,
     // cache_re: crossbeam_channel::Receiver<ClayerConsensusEvent>,
     sender: Option<Sender<(Vec<PeerId>, reth_primitives::Bytes)>>,
     active_peers: HashSet<PeerId>,
+    message_log: Option<parking_lot::Mutex<ConsensusMessageLog>>,
+    peer_stats: PeerMessageStats,
+    /// Messages tagged with another topic belong to a different network
+    topic: u64,
+    /// How long a new peer has to complete the handshake, unbounded if unset
+    handshake_timeout: Option<Duration>,
+    /// Connected peers that did not send a message of this network yet, with their connect time
+    pending_handshakes: HashMap<PeerId, Instant>,
+    /// The peer set of the network, told about validator changes
+    peers: Option<PeersHandle>,
+    /// Compression of the messages to peers that support it, disabled if unset
+    compression: Option<Compression>,
+    /// Connected peers that advertised support for the configured compression
+    compressing_peers: HashSet<PeerId>,
+    /// The messages received recently, copies of them are dropped
+    seen: SeenCache,
+    /// Most hops a received message is relayed for
+    message_ttl: u8,
+    /// Version of the consensus messages sent by this node
+    protocol_version: u8,
+    /// Detects connected peers that stopped responding, disabled if unset
+    heartbeat: Option<Heartbeat>,
+    /// The engine API methods of the local execution layer, unset until it was queried
+    engine_capabilities: Option<BTreeSet<String>>,
+    /// The engine API methods the connected peers advertised
+    peer_engine_capabilities: HashMap<PeerId, BTreeSet<String>>,
+    /// Messages handed to the network since the listener was installed
+    queued_messages: AtomicU64,
+    /// Messages the network dispatched since the listener was installed
+    dispatched_messages: u64,
+    /// Broadcasts waiting for the network to dispatch their messages
+    pending_dispatches: Vec<PendingDispatch>,
+    /// How long to wait for the network to dispatch a broadcast with a result
+    broadcast_result_timeout: Duration,
+}
+
+/// A broadcast waiting for the network to dispatch its messages
+struct PendingDispatch {
+    /// The sequence numbers of the messages the broadcast was handed to the network as
+    messages: Range<u64>,
+    /// The number of peers the dispatched messages were sent to so far
+    peers: usize,
+    result: oneshot::Sender<usize>,
 }
 
 impl ClayerConsensusMessagingAgentInner {
     pub fn new() -> Self {
         // let (tx, re) = crossbeam_channel::unbounded::<ClayerConsensusEvent>();
-        Self { queued: VecDeque::new(), sender: None, active_peers: HashSet::new() }
+        Self {
+            queued: EventQueue::default(),
+            sender: None,
+            active_peers: HashSet::new(),
+            message_log: None,
+            peer_stats: PeerMessageStats::default(),
+            topic: 0,
+            handshake_timeout: None,
+            pending_handshakes: HashMap::new(),
+            peers: None,
+            compression: None,
+            compressing_peers: HashSet::new(),
+            seen: SeenCache::new(DEFAULT_SEEN_CACHE_SIZE, DEFAULT_SEEN_CACHE_TTL),
+            message_ttl: PbftConfig::default().message_ttl,
+            protocol_version: CLAYER_PROTOCOL_VERSION,
+            heartbeat: None,
+            engine_capabilities: None,
+            peer_engine_capabilities: HashMap::new(),
+            queued_messages: AtomicU64::new(0),
+            dispatched_messages: 0,
+            pending_dispatches: Vec::new(),
+            broadcast_result_timeout: DEFAULT_BROADCAST_RESULT_TIMEOUT,
+        }
     }
 }
 
@@ -112,10 +379,66 @@ impl ClayerConsensusMessagingAgentInner {
     fn pending_consensus_listener(&mut self) -> Receiver<(Vec<PeerId>, reth_primitives::Bytes)> {
         let (sender, rx) = mpsc::channel(1024);
         self.sender = Some(sender);
+        // the messages of the previous listener are never dispatched
+        *self.queued_messages.get_mut() = 0;
+        self.dispatched_messages = 0;
+        self.pending_dispatches.clear();
         rx
     }
 
     fn push_received_cache(&mut self, peer_id: PeerId, data: reth_primitives::Bytes) {
+        let frame = match decode_frame(&data) {
+            Ok(frame) => frame,
+            Err(err) => {
+                debug!(target:"consensus::cl", ?peer_id, %err, "Dropping unreadable consensus frame");
+                self.peer_stats.record_dropped(peer_id);
+                return
+            }
+        };
+        self.on_heartbeat_seen(peer_id);
+        let data = match frame {
+            Frame::Message(data) => data,
+            Frame::Capabilities(supported) => {
+                if self
+                    .compression
+                    .is_some_and(|compression| compression.is_supported_by(supported))
+                {
+                    self.compressing_peers.insert(peer_id);
+                }
+                return
+            }
+            // pings are answered even with the heartbeat disabled, the peer may have it enabled
+            Frame::Ping => return self.send(vec![peer_id], pong_frame()),
+            Frame::Pong => return,
+            Frame::EngineCapabilities(capabilities) => {
+                self.peer_engine_capabilities.insert(peer_id, capabilities);
+                return self.check_engine_capabilities(peer_id)
+            }
+            Frame::Hello { version, topic } => {
+                if topic == self.topic {
+                    self.pending_handshakes.remove(&peer_id);
+                } else {
+                    debug!(target:"consensus::cl", ?peer_id, version, topic, "Consensus peer introduced itself with a foreign topic");
+                }
+                return
+            }
+        };
+        if let Some(message_log) = &self.message_log {
+            message_log.lock().record_inbound(peer_id, &data);
+        }
+        let data = self.upgrade_message(data);
+        self.peer_stats.record_received(peer_id);
+        if !self.relay(peer_id, &data) {
+            self.peer_stats.record_dropped(peer_id);
+            return
+        }
+        // peers of older releases don't send a hello, a message tagged with the topic of this
+        // network completes their handshake
+        if self.pending_handshakes.contains_key(&peer_id) &&
+            ClayerConsensusMessage::decode(&mut data.as_ref()).is_ok()
+        {
+            self.pending_handshakes.remove(&peer_id);
+        }
         self.queued.push_back(ClayerConsensusEvent::PeerMessage(peer_id, data));
         // let _ = self.cache_tx.send(ClayerConsensusEvent::PeerMessage(peer_id, data));
     }
@@ -126,24 +449,191 @@ impl ClayerConsensusMessagingAgentInner {
         // let _ = self.cache_tx.send(ClayerConsensusEvent::PeerNetWork(peer_id, connect));
         if connect {
             self.active_peers.insert(peer_id);
+            if let Some(heartbeat) = &mut self.heartbeat {
+                heartbeat.on_connected(peer_id, Instant::now());
+            }
+            if self.handshake_timeout.is_some() {
+                self.pending_handshakes.insert(peer_id, Instant::now());
+            }
+            // sent even with the handshake timeout disabled, the peer may have it enabled
+            self.send(vec![peer_id], hello_frame(self.protocol_version, self.topic));
+            if self.compression.is_some() {
+                self.send(vec![peer_id], Compression::capabilities_frame());
+            }
+            if let Some(capabilities) = &self.engine_capabilities {
+                self.send(vec![peer_id], engine_capabilities_frame(capabilities));
+            }
         } else {
             self.active_peers.remove(&peer_id);
+            self.pending_handshakes.remove(&peer_id);
+            self.compressing_peers.remove(&peer_id);
+            self.peer_engine_capabilities.remove(&peer_id);
+            if let Some(heartbeat) = &mut self.heartbeat {
+                heartbeat.on_disconnected(&peer_id);
+            }
         }
     }
 
     /// pop network event(PeerConnected, PeerDisconnected)
     fn pop_event(&mut self) -> Option<ClayerConsensusEvent> {
+        self.expire_handshakes();
+        self.check_heartbeats();
+        // the counts of idle peers reach the metrics without waiting for their next message
+        self.peer_stats.flush_if_due(Instant::now());
         self.queued.pop_front()
     }
 
+    /// Pings the consensus peers when due and marks the peers dead that were not heard from in
+    /// time
+    fn check_heartbeats(&mut self) {
+        let Some(heartbeat) = &mut self.heartbeat else { return };
+        let now = Instant::now();
+        let dead = heartbeat.expire(now);
+        let ping = heartbeat.ping_due(now);
+        for peer_id in dead {
+            warn!(target:"consensus::cl", ?peer_id, "Consensus peer stopped responding, marking it dead");
+            self.active_peers.remove(&peer_id);
+            self.queued.push_back(ClayerConsensusEvent::PeerDead(peer_id));
+        }
+        // an empty peer list would broadcast to everyone
+        if ping && !self.active_peers.is_empty() {
+            self.send(self.get_peers(), ping_frame());
+        }
+    }
+
+    /// Records a sign of life of `peer_id`, a dead peer is a consensus peer again
+    fn on_heartbeat_seen(&mut self, peer_id: PeerId) {
+        if self
+            .heartbeat
+            .as_mut()
+            .is_some_and(|heartbeat| heartbeat.on_seen(peer_id, Instant::now()))
+        {
+            info!(target:"consensus::cl", ?peer_id, "Dead consensus peer is responding again");
+            self.active_peers.insert(peer_id);
+            self.queued.push_back(ClayerConsensusEvent::PeerNetWork(peer_id, true));
+        }
+    }
+
+    fn set_engine_capabilities(&mut self, capabilities: BTreeSet<String>) {
+        // an empty peer list would broadcast to everyone
+        if !self.active_peers.is_empty() {
+            self.send(self.get_peers(), engine_capabilities_frame(&capabilities));
+        }
+        self.engine_capabilities = Some(capabilities);
+        let peers: Vec<PeerId> = self.peer_engine_capabilities.keys().copied().collect();
+        for peer_id in peers {
+            self.check_engine_capabilities(peer_id);
+        }
+    }
+
+    /// Warns if the execution layer of `peer_id` supports other payload versions than the local
+    /// one
+    fn check_engine_capabilities(&self, peer_id: PeerId) {
+        let (Some(local), Some(remote)) =
+            (&self.engine_capabilities, self.peer_engine_capabilities.get(&peer_id))
+        else {
+            return
+        };
+        let local_only: Vec<&String> =
+            local.difference(remote).filter(|method| is_payload_method(method)).collect();
+        let remote_only: Vec<&String> =
+            remote.difference(local).filter(|method| is_payload_method(method)).collect();
+        if !local_only.is_empty() || !remote_only.is_empty() {
+            warn!(target:"consensus::cl", ?peer_id, missing = ?local_only, extra = ?remote_only, "Consensus peer runs an execution layer with different engine API payload versions");
+        }
+    }
+
+    /// Drops the peers that did not complete the handshake in time
+    fn expire_handshakes(&mut self) {
+        let Some(timeout) = self.handshake_timeout else { return };
+        let expired: Vec<PeerId> = self
+            .pending_handshakes
+            .iter()
+            .filter(|(_, connected_at)| connected_at.elapsed() > timeout)
+            .map(|(peer_id, _)| *peer_id)
+            .collect();
+        for peer_id in expired {
+            warn!(target:"consensus::cl", ?peer_id, ?timeout, "Dropping consensus peer that did not complete the handshake");
+            self.pending_handshakes.remove(&peer_id);
+            self.active_peers.remove(&peer_id);
+            if let Some(heartbeat) = &mut self.heartbeat {
+                heartbeat.on_disconnected(&peer_id);
+            }
+            self.queued.push_back(ClayerConsensusEvent::PeerNetWork(peer_id, false));
+        }
+    }
+
     fn push_block_event(&mut self, event: ClayerConsensusEvent) {
-        self.queued.push_front(event);
+        self.queued.push_block_event(event);
     }
 
     fn broadcast_consensus(&self, peers: Vec<PeerId>, data: reth_primitives::Bytes) {
+        if let Some(message_log) = &self.message_log {
+            message_log.lock().record_outbound(&data);
+        }
+        let data = self.downgrade_message(data);
+
+        let Some(compression) = self.compression else { return self.send(peers, data) };
+        let targets = if peers.is_empty() { self.get_peers() } else { peers.clone() };
+        let (compressed, plain): (Vec<PeerId>, Vec<PeerId>) =
+            targets.into_iter().partition(|peer| self.compressing_peers.contains(peer));
+        if compressed.is_empty() {
+            return self.send(peers, data)
+        }
+        self.send(compressed, compression.encode(&data));
+        // an empty peer list would broadcast to everyone
+        if !plain.is_empty() {
+            self.send(plain, data);
+        }
+    }
+
+    /// Broadcasts `data` and returns a receiver for the number of peers the network sent it to.
+    fn broadcast_consensus_with_result(
+        &mut self,
+        peers: Vec<PeerId>,
+        data: reth_primitives::Bytes,
+    ) -> oneshot::Receiver<usize> {
+        // forget the broadcasts nobody waits for anymore
+        self.pending_dispatches.retain(|pending| !pending.result.is_closed());
+
+        let (result, rx) = oneshot::channel();
+        // no other message is sent while `self` is borrowed mutably
+        let first = *self.queued_messages.get_mut();
+        self.broadcast_consensus(peers, data);
+        let messages = first..*self.queued_messages.get_mut();
+        if messages.is_empty() {
+            let _ = result.send(0);
+        } else {
+            self.pending_dispatches.push(PendingDispatch { messages, peers: 0, result });
+        }
+        rx
+    }
+
+    /// Records that the network sent the oldest message it did not dispatch yet to `peers` peers.
+    fn on_consensus_dispatched(&mut self, peers: usize) {
+        let message = self.dispatched_messages;
+        self.dispatched_messages += 1;
+        for pending in &mut self.pending_dispatches {
+            if pending.messages.contains(&message) {
+                pending.peers += peers;
+            }
+        }
+        let (dispatched, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending_dispatches)
+            .into_iter()
+            .partition(|pending| pending.messages.end <= self.dispatched_messages);
+        self.pending_dispatches = pending;
+        for dispatch in dispatched {
+            let _ = dispatch.result.send(dispatch.peers);
+        }
+    }
+
+    /// Hands `data` to the network to send it to `peers`, or to all peers if empty.
+    fn send(&self, peers: Vec<PeerId>, data: reth_primitives::Bytes) {
         if let Some(sender) = &self.sender {
             match sender.try_send((peers, data)) {
-                Ok(()) => {}
+                Ok(()) => {
+                    self.queued_messages.fetch_add(1, Ordering::Relaxed);
+                }
                 Err(err) => {
                     error!(target:"consensus::cl","broadcast_consensus error {:?}",err);
                 }
@@ -154,6 +644,86 @@ impl ClayerConsensusMessagingAgentInner {
     fn get_peers(&self) -> Vec<PeerId> {
         self.active_peers.iter().cloned().collect()
     }
+
+    /// Converts a received version 1 message into a message of the topic of this network.
+    ///
+    /// Version 1 peers don't relay, so the message is given a TTL of 1 and only delivered to this
+    /// node.
+    fn upgrade_message(&self, data: reth_primitives::Bytes) -> reth_primitives::Bytes {
+        if ClayerConsensusMessage::decode(&mut data.as_ref()).is_ok() {
+            return data
+        }
+        let Ok(message) = ClayerConsensusMessageV1::decode(&mut data.as_ref()) else { return data };
+        let message = ClayerConsensusMessage {
+            header_bytes: message.header_bytes,
+            header_signature: message.header_signature,
+            message_bytes: message.message_bytes,
+            ttl: 1,
+            topic: self.topic,
+        };
+        let mut out = vec![];
+        message.encode(&mut out);
+        out.into()
+    }
+
+    /// Encodes a consensus message as version 1 if this node sends version 1 messages.
+    fn downgrade_message(&self, data: reth_primitives::Bytes) -> reth_primitives::Bytes {
+        if self.protocol_version >= CLAYER_PROTOCOL_VERSION {
+            return data
+        }
+        let Ok(message) = ClayerConsensusMessage::decode(&mut data.as_ref()) else { return data };
+        let mut out = vec![];
+        ClayerConsensusMessageV1::from(message).encode(&mut out);
+        out.into()
+    }
+
+    /// Relays a message received from `peer_id` to all other peers with a decremented TTL.
+    ///
+    /// The TTL of the message is lowered to the TTL of this node first, so a peer can't make its
+    /// messages travel further than the network is configured for.
+    ///
+    /// Returns `false` if the message belongs to a different topic, its TTL already expired or it
+    /// was already received and it should be dropped.
+    fn relay(&mut self, peer_id: PeerId, data: &reth_primitives::Bytes) -> bool {
+        let Ok(mut message) = ClayerConsensusMessage::decode(&mut data.as_ref()) else {
+            // undecodable messages are reported by the engine
+            return true
+        };
+        if message.topic != self.topic {
+            debug!(target:"consensus::cl", ?peer_id, topic = message.topic, "Dropping foreign message");
+            return false
+        }
+        if message.ttl == 0 {
+            debug!(target:"consensus::cl", ?peer_id, "Dropping consensus message with expired TTL");
+            return false
+        }
+        if !self.seen.insert(message_id(&message)) {
+            trace!(target:"consensus::cl", ?peer_id, "Dropping already seen consensus message");
+            return false
+        }
+
+        message.ttl = message.ttl.min(self.message_ttl).saturating_sub(1);
+        // version 1 peers would receive the relayed copies as new messages
+        if message.ttl > 0 && self.protocol_version >= CLAYER_PROTOCOL_VERSION {
+            let peers: Vec<PeerId> =
+                self.active_peers.iter().filter(|peer| **peer != peer_id).cloned().collect();
+            // an empty peer list would broadcast to everyone
+            if !peers.is_empty() {
+                let mut out = vec![];
+                message.encode(&mut out);
+                self.broadcast_consensus(peers, out.into());
+            }
+        }
+        true
+    }
+}
+
+/// Returns whether the engine API `method` is a versioned payload method, validators have to agree
+/// on those to import each other's blocks.
+fn is_payload_method(method: &str) -> bool {
+    ["engine_newPayloadV", "engine_getPayloadV", "engine_forkchoiceUpdatedV"]
+        .iter()
+        .any(|prefix| method.starts_with(prefix))
 }
 
 pub struct ClayerConsensusEngine<Client, CDB> {
@@ -164,6 +734,76 @@ pub struct ClayerConsensusEngine<Client, CDB> {
     db: Arc<CDB>,
     client: Client,
     announce_block: LruCache<B256, u64>,
+    /// TTL of the consensus messages this node broadcasts
+    message_ttl: u8,
+    /// Progress of the local pipeline, used to slow down proposals while it lags behind
+    pipeline_progress: PipelineProgress,
+    /// How many committed blocks the pipeline may lag behind before proposals slow down
+    max_pipeline_lag: u64,
+    /// Set when the node stepped down as primary, no blocks are proposed until another node
+    /// became primary
+    stepping_down: bool,
+    /// Follow consensus without signing any message, see [PbftConfig::observer]
+    observer: bool,
+    /// How far a proposed block's timestamp may be ahead of the local clock
+    max_timestamp_skew: Duration,
+    /// Set while consensus is paused, no blocks are proposed or committed until it is resumed
+    paused: bool,
+    /// Number of most recent committed payloads kept in the database
+    payload_retention: u64,
+    /// Committed payloads that could not be saved yet, retried on the next commit and on shutdown
+    unsaved_payloads: VecDeque<ExecutionPayloadWrapperV2>,
+    /// Number of consecutive head checks that found the EL diverged from the committed head
+    head_divergences: u32,
+    /// Number of consecutive divergent head checks after which the engine aborts
+    max_head_divergences: u32,
+    /// Sequence number and arrival time of the last accepted PrePrepare, the start of the commit
+    /// latency in the commit summary
+    pre_prepare_received: Option<(u64, Instant)>,
+    /// Withhold proposals while too few validators are connected to reach quorum
+    require_connected_quorum: bool,
+    /// Whether blocks may be committed without quorum, see [Self::force_commit]
+    allow_force_commit: bool,
+    /// Gap between the committed height and the execution layer at the last commit
+    el_commit_gap: Option<i64>,
+    commit_metrics: CommitMetrics,
+    phase_timings: PhaseTimings,
+    /// Contract whose events change the validator set, see [PbftConfig::validator_contract]
+    validator_contract: Option<Address>,
+    /// Emits the epoch boundaries, the validator set changes at the end of an epoch
+    epochs: EpochTracker,
+    /// Consecutive failed proposals that trip safe mode, see [PbftConfig::safe_mode_failures]
+    safe_mode_failures: u32,
+    /// How long safe mode lasts, see [PbftConfig::safe_mode_duration]
+    safe_mode_duration: Duration,
+    /// Number of consecutive proposals of this node that failed
+    proposal_failures: u32,
+    /// When the node entered safe mode, unset while it proposes normally
+    safe_mode_since: Option<Instant>,
+    /// Commit latency above which a block breaches the SLA, see [PbftConfig::commit_latency_sla]
+    commit_latency_sla: Option<Duration>,
+    /// Number of committed blocks that breached the commit latency SLA
+    commit_sla_breaches: u64,
+    /// Weight added to the block of the timely primary, see [PbftConfig::proposer_boost]
+    proposer_boost: u64,
+    /// Whether a randao mix is recorded for every committed block, see [PbftConfig::randao]
+    randao: bool,
+    /// Hashes of the headers of the most recent messages this node signed
+    own_messages: LruCache<B256, ()>,
+    /// Set once a message signed by the key of this node was received that it did not send
+    duplicate_identity: bool,
+    /// Stop proposing once a duplicate identity is detected, see
+    /// [PbftConfig::halt_on_duplicate_identity]
+    halt_on_duplicate_identity: bool,
+    /// How long the current view lasts, see [PbftConfig::max_view_lifetime]
+    view_lifetime: Option<ViewLifetime>,
+    /// Whether the gas limit of proposed blocks is checked, see [PbftConfig::check_gas_limit]
+    check_gas_limit: bool,
+    /// The block the London hardfork activated at, see [PbftConfig::london_block]
+    london_block: Option<u64>,
+    /// Metadata signed and attached to the blocks this node proposes, see
+    /// [PbftConfig::block_metadata]
+    block_metadata: Option<BlockMetadata>,
 }
 
 impl<Client, CDB> ClayerConsensusEngine<Client, CDB>
@@ -184,14 +824,81 @@ where
             db,
             client,
             announce_block: LruCache::new(10),
+            message_ttl: PbftConfig::default().message_ttl,
+            pipeline_progress: PipelineProgress::default(),
+            max_pipeline_lag: PbftConfig::default().max_pipeline_lag,
+            stepping_down: false,
+            observer: false,
+            max_timestamp_skew: Duration::ZERO,
+            paused: false,
+            payload_retention: PbftConfig::default().payload_retention,
+            unsaved_payloads: VecDeque::new(),
+            head_divergences: 0,
+            max_head_divergences: PbftConfig::default().max_head_divergences,
+            pre_prepare_received: None,
+            require_connected_quorum: false,
+            allow_force_commit: false,
+            el_commit_gap: None,
+            commit_metrics: CommitMetrics::default(),
+            phase_timings: PhaseTimings::default(),
+            validator_contract: None,
+            epochs: EpochTracker::new(PbftConfig::default().epoch_length),
+            safe_mode_failures: 0,
+            safe_mode_duration: PbftConfig::default().safe_mode_duration,
+            proposal_failures: 0,
+            safe_mode_since: None,
+            commit_latency_sla: None,
+            commit_sla_breaches: 0,
+            proposer_boost: 0,
+            randao: false,
+            own_messages: LruCache::new(OWN_MESSAGES_CACHE_SIZE),
+            duplicate_identity: false,
+            halt_on_duplicate_identity: false,
+            view_lifetime: None,
+            check_gas_limit: false,
+            london_block: None,
+            block_metadata: None,
         }
     }
 
+    /// Sets the pipeline progress the proposal cadence is adjusted to.
+    pub fn with_pipeline_progress(mut self, pipeline_progress: PipelineProgress) -> Self {
+        self.pipeline_progress = pipeline_progress;
+        self
+    }
+
+    /// Sets the tracker the epoch boundaries of the committed blocks are emitted to.
+    pub fn with_epoch_tracker(mut self, epochs: EpochTracker) -> Self {
+        self.epochs = epochs;
+        self
+    }
+
     pub fn initialize(&mut self, block: ClayerBlock, config: &PbftConfig, state: &mut PbftState) {
         // Add chain head to log and update state
         self.msg_log.resize_log(&config);
+        self.message_ttl = config.message_ttl;
+        self.max_pipeline_lag = config.max_pipeline_lag;
+        self.observer = config.observer;
+        self.max_timestamp_skew = config.max_timestamp_skew;
+        self.payload_retention = config.payload_retention;
+        self.max_head_divergences = config.max_head_divergences;
+        self.require_connected_quorum = config.require_connected_quorum;
+        self.allow_force_commit = config.allow_force_commit;
+        self.validator_contract = config.validator_contract;
+        self.epochs.set_epoch_length(config.epoch_length);
+        self.safe_mode_failures = config.safe_mode_failures;
+        self.safe_mode_duration = config.safe_mode_duration;
+        self.commit_latency_sla = config.commit_latency_sla;
+        self.proposer_boost = config.proposer_boost;
+        self.randao = config.randao;
+        self.halt_on_duplicate_identity = config.halt_on_duplicate_identity;
+        self.view_lifetime = config.max_view_lifetime.map(ViewLifetime::new);
+        self.check_gas_limit = config.check_gas_limit;
+        self.london_block = config.london_block;
+        self.block_metadata = config.block_metadata.clone();
         self.msg_log.add_validated_block(block.clone());
         state.chain_head = block.block_id();
+        self.agent.set_validators(state.validators.member_ids());
 
         info!(target: "consensus::cl","Initialized with block number {}, state {}", block.block_num(),state);
 
@@ -219,12 +926,17 @@ where
 
         // Primary initializes a block
         if state.is_primary() {
-            self.service.initialize_block(None).unwrap_or_else(|err| {
-                error!("Couldn't initialize block on startup due to error: {}", err)
-            });
+            self.service.retry_not_ready(|service| service.initialize_block(None)).unwrap_or_else(
+                |err| error!("Couldn't initialize block on startup due to error: {}", err),
+            );
         }
     }
 
+    /// Returns the agent exchanging consensus messages with the network
+    pub(crate) fn agent(&self) -> &ClayerConsensusMessagingAgent {
+        &self.agent
+    }
+
     fn service_mut(&mut self) -> &mut ApiService {
         &mut self.service
     }
@@ -258,8 +970,8 @@ where
 
         let msg_type = PbftMessageType::from(msg.info().ptype);
 
-        //  if the node is a member of the PBFT network or not；
-        if !state.is_validator() {
+        //  if the node is a member of the PBFT network or not；observers follow all phases
+        if !state.is_validator() && !self.observer {
             match msg_type {
                 PbftMessageType::AnnounceBlock => {
                     self.handle_announceblock_response(&msg, state)?
@@ -361,7 +1073,7 @@ where
             .collect::<Vec<_>>();
 
         if !mismatched_blocks.is_empty() {
-            self.start_view_change(state, state.view + 1)?;
+            self.start_view_change(state, state.view + 1, ViewChangeReason::FaultyPrimary)?;
             return Err(PbftError::FaultyPrimary(format!(
                 "When checking PrePrepare with block {:?}, found PrePrepare(s) with same view and \
                  seq num but mismatched block(s): {:?}",
@@ -371,7 +1083,11 @@ where
         }
 
         // Add message to the log
+        let seq_num = msg.info().seq_num;
         self.msg_log.add_message(msg.clone());
+        if self.pre_prepare_received.map_or(true, |(received, _)| received != seq_num) {
+            self.pre_prepare_received = Some((seq_num, Instant::now()));
+        }
 
         // If the node is in the PrePreparing phase, this message is for the current sequence
         // number, and the node already has this block: switch to Preparing
@@ -403,7 +1119,7 @@ where
 
         // The primary is not allowed to send a Prepare; its PrePrepare counts as its "vote"
         if info.signer_id == state.get_primary_id() {
-            self.start_view_change(state, state.view + 1)?;
+            self.start_view_change(state, state.view + 1, ViewChangeReason::FaultyPrimary)?;
             return Err(PbftError::FaultyPrimary(format!(
                 "Received Prepare from primary at view {}, seq_num {}",
                 state.view, state.seq_num
@@ -434,6 +1150,7 @@ where
                 > 2 * state.f;
             if has_matching_pre_prepare && has_required_prepares {
                 state.switch_phase(PbftPhase::Committing)?;
+                self.phase_timings.on_phase(&state.phase);
                 info!(target: "consensus::cl","Broadcasting Commit");
                 self.broadcast_pbft_message(
                     state.view,
@@ -472,61 +1189,147 @@ where
 
         self.msg_log.add_message(msg);
 
+        // While paused, the commits are only logged; the block is committed on resume
+        if self.paused {
+            debug!(target: "consensus::cl","{}: Consensus is paused; not committing", state);
+            return Ok(());
+        }
+
         // If this message is for the current sequence number and the node is in the Committing
         // phase, check if the node is ready to commit the block
         if info.seq_num == state.seq_num && state.phase == PbftPhase::Committing {
-            // The node is ready to commit the block (i.e. the predicate `committable` is true)
-            // when its log has 2f + 1 Commit messages from different nodes that match the
-            // PrePrepare message received earlier (same view, sequence number, and block)
-            let has_matching_pre_prepare =
-                self.msg_log.has_pre_prepare(info.seq_num, info.view, block_id);
-            let has_required_commits = self
-                .msg_log
-                // Only get Commits with matching seq_num, view, and block_id
-                .get_messages_of_type_seq_view_block(
-                    PbftMessageType::Commit,
-                    info.seq_num,
-                    info.view,
-                    block_id,
-                )
-                // Check if there are at least 2f + 1 Commits
-                .len() as u64
-                > 2 * state.f;
+            self.try_committing(info.view, block_id, state)?;
+        }
+
+        Ok(())
+    }
+
+    /// Commit the block of the current sequence number if the log has the required 2f + 1
+    /// `Commit` messages for it at the given view
+    fn try_committing(
+        &mut self,
+        view: u64,
+        block_id: B256,
+        state: &mut PbftState,
+    ) -> Result<(), PbftError> {
+        // The node is ready to commit the block (i.e. the predicate `committable` is true)
+        // when its log has 2f + 1 Commit messages from different nodes that match the
+        // PrePrepare message received earlier (same view, sequence number, and block)
+        let has_matching_pre_prepare = self.msg_log.has_pre_prepare(state.seq_num, view, block_id);
+        let has_required_commits = self
+            .msg_log
+            // Only get Commits with matching seq_num, view, and block_id
+            .get_messages_of_type_seq_view_block(
+                PbftMessageType::Commit,
+                state.seq_num,
+                view,
+                block_id,
+            )
+            // Check if there are at least 2f + 1 Commits
+            .len() as u64
+            > 2 * state.f;
 
-            if has_matching_pre_prepare && has_required_commits {
-                let payload = self.service.commit_block(block_id.clone()).map_err(|err| {
+        if has_matching_pre_prepare && has_required_commits {
+            let payload = self
+                .service
+                .retry_not_ready(|service| service.commit_block(block_id))
+                .map_err(|err| {
                     PbftError::ServiceError(
                         format!("Failed to commit block {:?}", hex::encode(&block_id)),
                         err.to_string(),
                     )
                 })?;
-                self.agent.push_block_event(ClayerConsensusEvent::BlockCommit((
-                    block_id,
-                    payload.execution_payload.payload_inner.timestamp,
-                    true,
-                )));
+            self.log_commit_summary(
+                state.seq_num,
+                view,
+                block_id,
+                Some(payload.execution_payload.payload_inner.transactions.len()),
+                false,
+                state,
+            );
+            self.unsaved_payloads.push_back(payload.clone());
+            self.flush_committed_payloads().unwrap_or_else(|err| {
+                error!(target: "consensus::cl","Couldn't save committed payload due to error: {}", err)
+            });
+            self.agent.push_block_event(ClayerConsensusEvent::BlockCommit((
+                block_id,
+                payload.execution_payload.payload_inner.timestamp,
+                true,
+            )));
 
-                state.switch_phase(PbftPhase::Finishing(false))?;
-                // Stop the commit timeout, since the network has agreed to commit the block
-                state.commit_timeout.stop();
+            state.switch_phase(PbftPhase::Finishing(false))?;
+            self.phase_timings.on_phase(&state.phase);
+            // Stop the commit timeout, since the network has agreed to commit the block
+            state.commit_timeout.stop();
 
-                // state.last_block_timestamp = payload.execution_payload.payload_inner.timestamp;
-                // self.on_block_commit(block_id, state)?;
+            // state.last_block_timestamp = payload.execution_payload.payload_inner.timestamp;
+            // self.on_block_commit(block_id, state)?;
 
-                //broadcast new block hash
-                self.broadcast_pbft_message(
-                    state.view,
-                    state.seq_num,
-                    PbftMessageType::AnnounceBlock,
-                    block_id,
-                    state,
-                )?;
-            }
+            //broadcast new block hash
+            self.broadcast_pbft_message(
+                state.view,
+                state.seq_num,
+                PbftMessageType::AnnounceBlock,
+                block_id,
+                state,
+            )?;
         }
 
         Ok(())
     }
 
+    /// Emits a single structured log line summarizing the consensus decision for a committed
+    /// block.
+    ///
+    /// The latency is measured from the PrePrepare of the block, it is omitted if the node never
+    /// received one, e.g. because it caught up using a seal. A latency above the
+    /// [PbftConfig::commit_latency_sla] is reported as a breach.
+    fn log_commit_summary(
+        &mut self,
+        seq_num: u64,
+        view: u64,
+        block_id: B256,
+        tx_count: Option<usize>,
+        catch_up: bool,
+        state: &PbftState,
+    ) {
+        let votes = |ptype| {
+            self.msg_log.get_messages_of_type_seq_view_block(ptype, seq_num, view, block_id).len()
+        };
+        let commit_latency = self
+            .pre_prepare_received
+            .filter(|(received, _)| *received == seq_num)
+            .map(|(_, at)| at.elapsed());
+        let commit_latency_ms = commit_latency.map(|latency| latency.as_millis() as u64);
+        info!(
+            target: "consensus::cl::commit",
+            height = seq_num,
+            hash = %block_id,
+            view,
+            prepares = votes(PbftMessageType::Prepare),
+            commits = votes(PbftMessageType::Commit),
+            proposer = %state.get_primary_id_at_view(view),
+            tx_count,
+            commit_latency_ms,
+            catch_up,
+            "Committed block"
+        );
+
+        if let (Some(latency), Some(sla)) = (commit_latency, self.commit_latency_sla) {
+            if latency > sla {
+                self.commit_sla_breaches += 1;
+                self.commit_metrics.commit_sla_breaches.increment(1);
+                warn!(target: "consensus::cl", height = seq_num, hash = %block_id, ?latency, ?sla, "Commit latency exceeded the SLA");
+            }
+        }
+    }
+
+    /// Returns the number of committed blocks whose commit latency exceeded the
+    /// [PbftConfig::commit_latency_sla]
+    pub fn commit_sla_breaches(&self) -> u64 {
+        self.commit_sla_breaches
+    }
+
     /// Handle a `ViewChange` message
     ///
     /// When a `ViewChange` is received, check that it isn't outdated and add it to the log. If the
@@ -571,7 +1374,7 @@ where
         if is_later_view && start_view_change {
             info!(target: "consensus::cl","{}: Received f + 1 ViewChange messages; starting early view change", state);
             // Can exit early since the node will self-send another ViewChange message here
-            return self.start_view_change(state, msg_view);
+            return self.start_view_change(state, msg_view, ViewChangeReason::PeerViewChanges);
         }
 
         let messages =
@@ -643,8 +1446,16 @@ where
             }
         }
 
-        // If this node was the primary before, cancel any block that may have been initialized
+        // If this node was the primary before, cancel any block that may have been initialized;
+        // only a block that was already proposed for the current sequence number failed
         if state.is_primary() {
+            let proposed = matches!(state.phase, PbftPhase::Preparing | PbftPhase::Committing);
+            if proposed && !self.stepping_down {
+                self.on_proposal_failed(
+                    "the view changed before the proposal was committed",
+                    state,
+                );
+            }
             self.service.cancel_block().unwrap_or_else(|err| {
                 info!(target: "consensus::cl","Failed to cancel block when becoming secondary: {:?}", err);
             });
@@ -666,12 +1477,14 @@ where
 
         // Initialize a new block if this node is the new primary
         if state.is_primary() {
-            self.service.initialize_block(None).map_err(|err| {
-                PbftError::ServiceError(
-                    "Couldn't initialize block after view change".into(),
-                    err.to_string(),
-                )
-            })?;
+            self.service.retry_not_ready(|service| service.initialize_block(None)).map_err(
+                |err| {
+                    PbftError::ServiceError(
+                        "Couldn't initialize block after view change".into(),
+                        err.to_string(),
+                    )
+                },
+            )?;
         }
 
         Ok(())
@@ -924,16 +1737,80 @@ where
             )));
         }
 
-        // Add the currently unvalidated block to the log
-        self.msg_log.add_unvalidated_block(block.clone());
+        // Reject blocks from the future, the proposer's clock is off or it tries to move the chain
+        // time ahead. The genesis block is never proposed, so its timestamp is never checked
+        let now = chrono::prelude::Local::now().timestamp() as u64;
+        if let Err(err) = check_timestamp_skew(block.block.timestamp, now, self.max_timestamp_skew)
+        {
+            self.agent.record_rejected(block.info.signer_id);
+            self.service.fail_block(block.block_id()).unwrap_or_else(
+                |err| error!(target: "consensus::cl","Couldn't fail block due to error: {:?}", err),
+            );
+            return Err(PbftError::InvalidMessage(format!(
+                "Received block {:?} / {:?}: {}",
+                block.block_num(),
+                hex::encode(&block.block_id()),
+                err
+            )));
+        }
 
-        if block.payload_id == B64::ZERO {
-            return Err(PbftError::InternalError(format!("BlockNew payload_id is zero")));
+        // Reject gas limits the execution layer would refuse, before the block is voted on. The
+        // genesis block is never proposed, so every proposed block has a parent gas limit
+        if self.check_gas_limit {
+            let london_transition = self.london_block == Some(block.block_num());
+            if let Err(err) = check_gas_limit(
+                block.block.gas_limit,
+                previous_block.block.gas_limit,
+                london_transition,
+            ) {
+                self.agent.record_rejected(block.info.signer_id);
+                self.service.fail_block(block.block_id()).unwrap_or_else(
+                    |err| error!(target: "consensus::cl","Couldn't fail block due to error: {:?}", err),
+                );
+                return Err(PbftError::InvalidMessage(format!(
+                    "Received block {:?} / {:?}: {}",
+                    block.block_num(),
+                    hex::encode(&block.block_id()),
+                    err
+                )));
+            }
         }
-        // Have the validator check the block
-        self.service
-            .check_blocks(
-                PayloadId::from(block.payload_id),
+
+        // Reject metadata the proposer did not sign for this block
+        if let Some(metadata) = &block.metadata {
+            let signed = SignedBlockMetadata::decode_bytes(metadata).and_then(|signed| {
+                signed.verify(block.block_id(), block.info.signer_id)?;
+                Ok(signed)
+            });
+            match signed {
+                Ok(signed) => {
+                    debug!(target: "consensus::cl", proposer = ?block.info.signer_id, version = signed.metadata.version, build = signed.metadata.build, "Received block {} with metadata", block.block_num())
+                }
+                Err(err) => {
+                    self.agent.record_rejected(block.info.signer_id);
+                    self.service.fail_block(block.block_id()).unwrap_or_else(
+                        |err| error!(target: "consensus::cl","Couldn't fail block due to error: {:?}", err),
+                    );
+                    return Err(PbftError::InvalidMessage(format!(
+                        "Received block {:?} / {:?} with invalid metadata: {}",
+                        block.block_num(),
+                        hex::encode(&block.block_id()),
+                        err
+                    )));
+                }
+            }
+        }
+
+        // Add the currently unvalidated block to the log
+        self.msg_log.add_unvalidated_block(block.clone());
+
+        if block.payload_id == B64::ZERO {
+            return Err(PbftError::InternalError(format!("BlockNew payload_id is zero")));
+        }
+        // Have the validator check the block
+        self.service
+            .check_blocks(
+                PayloadId::from(block.payload_id),
                 execution_payload_to_payload(&block.block),
                 state.is_primary(),
             )
@@ -1091,16 +1968,19 @@ where
             latest_header.header.number
         } else {
             // Commit the block, stop the idle timeout, and skip straight to Finishing
-            let payload = self.service.commit_block(seal.block_id.clone()).map_err(|err| {
-                PbftError::ServiceError(
-                    format!(
-                        "Failed to commit block with catch-up {:?} / {:?}",
-                        state.seq_num,
-                        hex::encode(&seal.block_id)
-                    ),
-                    err.to_string(),
-                )
-            })?;
+            let payload = self
+                .service
+                .retry_not_ready(|service| service.commit_block(seal.block_id))
+                .map_err(|err| {
+                    PbftError::ServiceError(
+                        format!(
+                            "Failed to commit block with catch-up {:?} / {:?}",
+                            state.seq_num,
+                            hex::encode(&seal.block_id)
+                        ),
+                        err.to_string(),
+                    )
+                })?;
             payload.execution_payload.payload_inner.timestamp
         };
 
@@ -1127,6 +2007,12 @@ where
         //     )
         // })?;
 
+        let tx_count = self
+            .msg_log
+            .get_block_with_id(seal.block_id)
+            .map(|block| block.block.transactions.len());
+        self.log_commit_summary(seal.info.seq_num, view, seal.block_id, tx_count, true, state);
+
         self.agent.push_block_event(ClayerConsensusEvent::BlockCommit((
             seal.block_id.clone(),
             timestamp,
@@ -1179,6 +2065,8 @@ where
             });
         }
 
+        self.record_el_commit_gap(state.seq_num);
+
         // Increment sequence number and update state
         state.seq_num += 1;
         state.mode = PbftMode::Normal;
@@ -1194,6 +2082,10 @@ where
             self.save_seal(&seal)?;
         }
 
+        if self.randao {
+            self.save_randao_mix(state.seq_num - 1, block_id)?;
+        }
+
         // If node(s) are waiting for a seal to commit the last block, send it now
         let requesters = self
             .msg_log
@@ -1208,12 +2100,21 @@ where
             });
         }
 
+        if let Some(boundary) = self.epochs.on_commit(state.seq_num - 1, block_id) {
+            info!(target: "consensus::cl", epoch = boundary.epoch, number = boundary.block_number, "Reached the end of an epoch");
+        }
+
+        if state.is_primary() {
+            self.proposal_failures = 0;
+        }
+
         // Update membership if necessary
         self.update_membership(block_id.clone(), state.seq_num - 1, state);
 
         // Increment the view if a view change must be forced for fairness view
         if state.at_forced_view_change() {
             state.view += 1;
+            info!(target: "consensus::cl", view = state.view, primary = %state.get_primary_id(), "Rotating primary");
         }
 
         // Tell the log to garbage collect if it needs to
@@ -1224,13 +2125,14 @@ where
         // commit block (state.seq_num - 1)
         // current public block (state.seq_num)
         // grandchildren (state.seq_num + 1)
-        let grandchildren = self
+        let mut grandchildren = self
             .msg_log
             .get_blocks_with_num(state.seq_num + 1)
             .iter()
             .cloned()
             .cloned()
             .collect::<Vec<_>>();
+        order_competing_blocks(&mut grandchildren, |block| self.block_weight(block, state));
         for block in grandchildren {
             if self.try_handling_block(block, state).is_ok() {
                 return Ok(());
@@ -1256,32 +2158,85 @@ where
         state.idle_timeout.start();
 
         // If we already have a block at this sequence number with a valid PrePrepare for it, start
-        // Preparing (there may be multiple blocks, but only one will have a valid PrePrepare)
-        let block_ids = self
+        // Preparing (there may be multiple blocks, they are tried in tie-break order)
+        let mut blocks = self
             .msg_log
             .get_blocks_with_num(state.seq_num)
             .iter()
-            .map(|block| block.block_id())
+            .cloned()
+            .cloned()
             .collect::<Vec<_>>();
-        for id in block_ids {
-            self.try_preparing(id, state)?;
+        order_competing_blocks(&mut blocks, |block| self.block_weight(block, state));
+        for block in blocks {
+            self.try_preparing(block.block_id(), state)?;
         }
 
         // Initialize a new block if this node is the primary and it is not in the process of
         // catching up
         if state.is_primary() {
             info!(target: "consensus::cl","{}: Initializing block on top of {}", state, hex::encode(&block_id));
-            self.service.initialize_block(Some(block_id)).map_err(|err| {
-                PbftError::ServiceError(
-                    "Couldn't initialize block after commit".into(),
-                    err.to_string(),
-                )
-            })?;
+            self.service
+                .retry_not_ready(|service| service.initialize_block(Some(block_id)))
+                .map_err(|err| {
+                    PbftError::ServiceError(
+                        "Couldn't initialize block after commit".into(),
+                        err.to_string(),
+                    )
+                })?;
         }
         info!(target: "consensus::cl","==================================on_block_commit over {}======================================",state.seq_num-1);
         Ok(())
     }
 
+    /// Returns the weight of a block competing with other blocks for the same height: the number of
+    /// prepare votes for it plus the proposer boost if it was proposed by the primary of the
+    /// current view. Blocks of primaries of earlier views arrived late, e.g. while a view change
+    /// resolved, the boost keeps them from splitting the votes of the validators.
+    fn block_weight(&self, block: &ClayerBlock, state: &PbftState) -> u64 {
+        let votes = self
+            .msg_log
+            .get_messages_of_type_seq(PbftMessageType::Prepare, block.block_num())
+            .iter()
+            .filter(|msg| msg.get_block_id() == block.block_id())
+            .count() as u64;
+        let timely = block.info.view == state.view &&
+            block.info.signer_id == state.get_primary_id_at_view(state.view);
+        if timely {
+            votes + self.proposer_boost
+        } else {
+            votes
+        }
+    }
+
+    /// Records how far the latest block of the execution layer lags behind the committed `height`
+    fn record_el_commit_gap(&mut self, height: u64) {
+        match self.service.latest_block_number() {
+            Ok(Some(el_height)) => {
+                let gap = height as i64 - el_height as i64;
+                if gap > 0 {
+                    debug!(target: "consensus::cl", height, el_height, "Execution layer lags behind the committed blocks");
+                }
+                self.commit_metrics.el_commit_gap.set(gap as f64);
+                self.el_commit_gap = Some(gap);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                warn!(target: "consensus::cl", ?err, "Couldn't get the latest block of the execution layer");
+            }
+        }
+    }
+
+    /// Returns the committed height minus the latest height of the execution layer, measured at
+    /// the last commit
+    pub fn el_commit_gap(&self) -> Option<i64> {
+        self.el_commit_gap
+    }
+
+    /// Returns how many durations of the preparing and committing phases were recorded
+    pub fn phase_samples(&self) -> PhaseSampleCounts {
+        self.phase_timings.counts()
+    }
+
     /// Check the on-chain list of members; if it has changed, update members list and return true.
     ///
     /// # Panics
@@ -1294,26 +2249,29 @@ where
 
         // let on_chain_members = state.validators.member_ids().clone();
 
-        let on_chain_members =
-            retry_until_ok(state.exponential_retry_base, state.exponential_retry_max, || {
-                self.service.query_validators(ELECT_VOTING_ADDRESS.to_string(), block_number)
-            });
+        let on_chain_members: Vec<PeerId> = if let Some(contract) = self.validator_contract {
+            // the validator set only changes at the end of an epoch
+            if !self.epochs.is_boundary(block_number) {
+                return
+            }
+            self.members_from_events(contract, block_number, state)
+        } else {
+            let on_chain_members =
+                retry_until_ok(state.exponential_retry_base, state.exponential_retry_max, || {
+                    self.service.query_validators(ELECT_VOTING_ADDRESS.to_string(), block_number)
+                });
 
-        let on_chain_members: Vec<PeerId> =
             assemble_peer_id(on_chain_members).unwrap_or_else(|err| {
                 error!(target: "consensus::cl","Failed to parse members on-chain: {}", err);
                 state.validators.member_ids().clone()
-            });
+            })
+        };
 
         let (add_or_sub, peerid) = state.validators.compare(&on_chain_members);
         if !state.validators.is_same(&on_chain_members) {
             info!(target: "consensus::cl","Updating membership: {:?}", on_chain_members);
-            state.validators.update(&on_chain_members);
-            let f = (state.validators.len() - 1) / 3;
-            if f == 0 {
-                panic!("This network no longer contains enough nodes to be fault tolerant");
-            }
-            state.f = f as u64;
+            state.update_members(&on_chain_members);
+            self.agent.set_validators(state.validators.member_ids());
         }
 
         // broadcast to new validator
@@ -1338,6 +2296,50 @@ where
         }
     }
 
+    /// Returns the validators after the changes `contract` emitted in the epoch ending with
+    /// `block_number`.
+    ///
+    /// The receipts are read from the local database, reading is retried until the pipeline
+    /// imported the blocks of the epoch.
+    fn members_from_events(
+        &self,
+        contract: Address,
+        block_number: u64,
+        state: &PbftState,
+    ) -> Vec<PeerId> {
+        let first = block_number.saturating_sub(self.epochs.epoch_length() - 1).max(1);
+        let mut changes = Vec::new();
+        for number in first..=block_number {
+            let receipts = retry_until_ok(
+                state.exponential_retry_base,
+                state.exponential_retry_max,
+                || match self.client.receipts_by_block(number.into()) {
+                    Ok(Some(receipts)) => Ok(receipts),
+                    Ok(None) => {
+                        debug!(target: "consensus::cl", number, "Waiting for the receipts of the epoch");
+                        Err(())
+                    }
+                    Err(err) => {
+                        warn!(target: "consensus::cl", number, %err, "Failed to read the receipts of the epoch");
+                        Err(())
+                    }
+                },
+            );
+            changes.extend(
+                receipts
+                    .iter()
+                    .filter(|receipt| receipt.success)
+                    .flat_map(|receipt| &receipt.logs)
+                    .filter(|log| log.address == contract)
+                    .filter_map(ValidatorSetChange::from_log),
+            );
+        }
+        if !changes.is_empty() {
+            info!(target: "consensus::cl", epoch_end = block_number, ?changes, "Validator set changes of the epoch");
+        }
+        state.validators.apply(changes)
+    }
+
     /// When the node has a block and a corresponding PrePrepare for its current sequence number,
     /// and it is in the PrePreparing phase, it can enter the Preparing phase and broadcast its
     /// Prepare
@@ -1351,6 +2353,7 @@ where
                 && block.block_num() == state.seq_num
             {
                 state.switch_phase(PbftPhase::Preparing)?;
+                self.phase_timings.on_phase(&state.phase);
 
                 // Stop idle timeout, since a new block and valid PrePrepare were received in time
                 state.idle_timeout.stop();
@@ -1396,6 +2399,26 @@ where
         self.broadcast_bootstrap_commit(peer_id, state)
     }
 
+    /// Handle a peer that stopped answering the heartbeat while still connected
+    ///
+    /// A dead primary can't make progress, so a validator starts a view change right away instead
+    /// of waiting for the idle or commit timeout to expire.
+    pub fn on_peer_dead(
+        &mut self,
+        peer_id: PeerId,
+        state: &mut PbftState,
+    ) -> Result<(), PbftError> {
+        if peer_id != state.get_primary_id()
+            || !state.validators.contains(&state.id)
+            || state.mode != PbftMode::Normal
+        {
+            return Ok(());
+        }
+
+        warn!(target: "consensus::cl","{}: Primary {:?} stopped responding, starting view change", state, peer_id);
+        self.start_view_change(state, state.view + 1, ViewChangeReason::UnresponsivePrimary)
+    }
+
     /// When the whole network is starting "fresh" from a non-genesis block, none of the nodes will
     /// have the `Commit` messages necessary to build the consensus seal for the last committed
     /// block (the chain head). To bootstrap the network in this scenario, all nodes will send a
@@ -1411,6 +2434,10 @@ where
         // of the chain head's predecessor is used. For block 1 this is view 0; otherwise, it's the
         // view of the block's consensus seal
         info!(target: "consensus::cl","broadcast_bootstrap_commit: state {}", state);
+        // observers never sign messages
+        if self.observer {
+            return Ok(());
+        }
         let view = if state.seq_num == 1 {
             0
         } else {
@@ -1459,16 +2486,14 @@ where
         let header_bytes = reth_primitives::Bytes::copy_from_slice(header_out.as_slice());
 
         //sign header
-        let signature_hash = keccak256(&header_bytes);
-        let signature =
-            sign_message(B256::from_slice(&state.kp.secret_bytes()[..]), signature_hash).map_err(
-                |err| PbftError::SigningError(format!("signing header error: {}", err.to_string())),
-            )?;
+        let signature = self.sign_header(&header_bytes, state)?;
 
         let clayer_msg = ClayerConsensusMessage {
             header_bytes,
             header_signature: ClayerSignature(signature),
             message_bytes,
+            ttl: self.message_ttl,
+            topic: self.agent.topic(),
         };
         let mut msg_out = vec![];
         clayer_msg.encode(&mut msg_out);
@@ -1857,8 +2882,293 @@ where
 
     // ---------- Methods called in the main engine loop to periodically check and update state ----------
 
+    /// Handle a command sent to the engine through a [ConsensusHandle]
+    pub fn on_command(&mut self, command: ConsensusCommand, state: &mut PbftState) {
+        match command {
+            ConsensusCommand::StepDown(reply) => {
+                let _ = reply.send(self.step_down(state));
+            }
+            ConsensusCommand::Pause(reply) => {
+                let _ = reply.send(self.pause(state));
+            }
+            ConsensusCommand::Resume(reply) => {
+                let _ = reply.send(self.resume(state));
+            }
+            ConsensusCommand::ForceCommit(block_id, reply) => {
+                let _ = reply.send(self.force_commit(block_id, state));
+            }
+            ConsensusCommand::ExitSafeMode(reply) => {
+                let _ = reply.send(self.exit_safe_mode(state));
+            }
+            ConsensusCommand::ViewChanges(views, reply) => {
+                let _ = reply.send(self.load_view_changes(views));
+            }
+            ConsensusCommand::RandaoMix(number, reply) => {
+                let _ = reply.send(self.load_randao_mix(number));
+            }
+        }
+    }
+
+    /// Commit the block `block_id` of the current sequence number without waiting for the network
+    /// to agree on it. This is a last resort to unstick a network that can't reach quorum and is
+    /// only allowed with [PbftConfig::allow_force_commit].
+    ///
+    /// The block must have been validated by the execution layer. No seal is created for it, so
+    /// other nodes can't catch up to it and have to force commit the same block.
+    pub fn force_commit(&mut self, block_id: B256, state: &mut PbftState) -> Result<(), PbftError> {
+        if !self.allow_force_commit {
+            return Err(PbftError::InternalError(
+                "force commit is disabled, start the node with --clayer.allow-force-commit"
+                    .to_string(),
+            ));
+        }
+        if matches!(state.phase, PbftPhase::Finishing(_)) {
+            return Err(PbftError::InternalError(format!(
+                "block {} is already being committed",
+                state.seq_num
+            )));
+        }
+        if !self
+            .msg_log
+            .get_blocks_with_num(state.seq_num)
+            .iter()
+            .any(|block| block.block_id() == block_id)
+        {
+            return Err(PbftError::InternalError(format!(
+                "block {} is not a validated block at height {}",
+                block_id, state.seq_num
+            )));
+        }
+
+        warn!(target: "consensus::cl", %block_id, height = state.seq_num, view = state.view, "FORCE COMMITTING block without quorum");
+        let payload = self
+            .service
+            .retry_not_ready(|service| service.commit_block(block_id))
+            .map_err(|err| {
+                PbftError::ServiceError(
+                    format!("Failed to force commit block {:?}", hex::encode(block_id)),
+                    err.to_string(),
+                )
+            })?;
+        self.log_commit_summary(
+            state.seq_num,
+            state.view,
+            block_id,
+            Some(payload.execution_payload.payload_inner.transactions.len()),
+            false,
+            state,
+        );
+        self.unsaved_payloads.push_back(payload.clone());
+        self.flush_committed_payloads().unwrap_or_else(|err| {
+            error!(target: "consensus::cl","Couldn't save committed payload due to error: {}", err)
+        });
+        // not committing through consensus, there are no votes to build a seal from
+        self.agent.push_block_event(ClayerConsensusEvent::BlockCommit((
+            block_id,
+            payload.execution_payload.payload_inner.timestamp,
+            false,
+        )));
+
+        state.phase = PbftPhase::Finishing(false);
+        state.idle_timeout.stop();
+        state.commit_timeout.stop();
+        warn!(target: "consensus::cl", %block_id, height = state.seq_num, "Force committed block");
+        Ok(())
+    }
+
+    /// Stop proposing and committing blocks. Inbound messages are still handled, so the node
+    /// stays in sync with the network.
+    ///
+    /// Returns `false` if consensus is already paused.
+    pub fn pause(&mut self, state: &mut PbftState) -> bool {
+        if self.paused {
+            return false;
+        }
+        info!(target: "consensus::cl","{}: Pausing consensus", state);
+        self.paused = true;
+        state.idle_timeout.stop();
+        state.commit_timeout.stop();
+        true
+    }
+
+    /// Resume consensus after [Self::pause], committing the current block if the network agreed
+    /// on it in the meantime.
+    ///
+    /// Returns `false` if consensus is not paused.
+    pub fn resume(&mut self, state: &mut PbftState) -> Result<bool, PbftError> {
+        if !self.paused {
+            return Ok(false);
+        }
+        info!(target: "consensus::cl","{}: Resuming consensus", state);
+        self.paused = false;
+        match state.phase {
+            PbftPhase::PrePreparing => state.idle_timeout.start(),
+            PbftPhase::Preparing | PbftPhase::Committing => state.commit_timeout.start(),
+            PbftPhase::Finishing(_) => {}
+        }
+
+        if state.phase == PbftPhase::Committing {
+            let block_ids: Vec<B256> = self
+                .msg_log
+                .get_messages_of_type_seq_view(
+                    PbftMessageType::PrePrepare,
+                    state.seq_num,
+                    state.view,
+                )
+                .into_iter()
+                .map(|msg| msg.get_block_id())
+                .collect();
+            for block_id in block_ids {
+                if state.phase != PbftPhase::Committing {
+                    break;
+                }
+                self.try_committing(state.view, block_id, state)?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns whether consensus is paused, see [Self::pause]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Start a view change if this node is the primary, so it can be taken down without
+    /// disrupting consensus. No blocks are proposed until another node became primary.
+    ///
+    /// Returns whether a view change was started.
+    pub fn step_down(&mut self, state: &mut PbftState) -> Result<bool, PbftError> {
+        if !state.is_primary() || matches!(state.mode, PbftMode::ViewChanging(_)) {
+            return Ok(false);
+        }
+        info!(target: "consensus::cl","{}: Stepping down as primary", state);
+        self.stepping_down = true;
+        self.start_view_change(state, state.view + 1, ViewChangeReason::StepDown)?;
+        Ok(true)
+    }
+
+    /// Records a failed proposal of this node, entering safe mode after
+    /// [PbftConfig::safe_mode_failures] consecutive failures.
+    fn on_proposal_failed(&mut self, reason: &str, state: &PbftState) {
+        self.commit_metrics.proposal_failures.increment(1);
+        self.proposal_failures += 1;
+        warn!(target: "consensus::cl", failures = self.proposal_failures, reason, "{}: Proposal failed", state);
+        if self.safe_mode_failures == 0 ||
+            self.proposal_failures < self.safe_mode_failures ||
+            self.safe_mode_since.is_some()
+        {
+            return;
+        }
+        error!(target: "consensus::cl", failures = self.proposal_failures, duration = ?self.safe_mode_duration, "{}: Entering safe mode, not proposing blocks until it is left", state);
+        self.safe_mode_since = Some(Instant::now());
+        self.commit_metrics.safe_mode.set(1.0);
+    }
+
+    /// Returns whether the node is in safe mode and does not propose blocks.
+    pub fn is_in_safe_mode(&self) -> bool {
+        self.safe_mode_since.is_some()
+    }
+
+    /// Leaves safe mode once [PbftConfig::safe_mode_duration] elapsed.
+    fn check_safe_mode_expired(&mut self, state: &PbftState) {
+        if let Some(since) = self.safe_mode_since {
+            if !self.safe_mode_duration.is_zero() && since.elapsed() >= self.safe_mode_duration {
+                info!(target: "consensus::cl", "{}: Safe mode expired", state);
+                self.exit_safe_mode(state);
+            }
+        }
+    }
+
+    /// Leave safe mode and propose blocks again.
+    ///
+    /// Returns `false` if the node is not in safe mode.
+    pub fn exit_safe_mode(&mut self, state: &PbftState) -> bool {
+        if self.safe_mode_since.take().is_none() {
+            return false;
+        }
+        info!(target: "consensus::cl", "{}: Leaving safe mode", state);
+        self.proposal_failures = 0;
+        self.commit_metrics.safe_mode.set(0.0);
+        true
+    }
+
+    /// Signs the header of a message of this node and remembers it, so the copies relayed back by
+    /// peers are not mistaken for messages of another node with the same key.
+    fn sign_header(
+        &mut self,
+        header_bytes: &[u8],
+        state: &PbftState,
+    ) -> Result<Signature, PbftError> {
+        let signature_hash = keccak256(header_bytes);
+        let signature = state.signer.sign(signature_hash)?;
+        self.own_messages.insert(signature_hash, ());
+        Ok(signature)
+    }
+
+    /// Checks whether `message` is signed by the key of this node without having been sent by it,
+    /// which means another node runs with the same validator key and both equivocate.
+    ///
+    /// Raises an alert and, with [PbftConfig::halt_on_duplicate_identity], stops proposing until
+    /// the node is restarted.
+    pub fn check_duplicate_identity(
+        &mut self,
+        message: &ClayerConsensusMessage,
+        state: &PbftState,
+    ) -> bool {
+        let Ok(header) = ClayerConsensusMessageHeader::decode(&mut message.header_bytes.as_ref())
+        else {
+            return false;
+        };
+        if header.signer_id != state.id {
+            return false;
+        }
+        let signature_hash = keccak256(&message.header_bytes);
+        if self.own_messages.contains_key(&signature_hash) {
+            return false;
+        }
+        // a forged signature does not prove that the key is in use elsewhere
+        let expected = public_key_to_address(state.signer.public_key());
+        if message.header_signature.0.recover_signer(signature_hash) != Some(expected) {
+            return false;
+        }
+
+        self.commit_metrics.duplicate_identity.increment(1);
+        error!(target: "consensus::cl", ptype = header.message_type, halt = self.halt_on_duplicate_identity, "{}: Received a message signed by the key of this node that it did not send, another node runs with the same validator key", state);
+        self.duplicate_identity = true;
+        true
+    }
+
+    /// Returns whether another node was seen running with the validator key of this node.
+    pub fn duplicate_identity_detected(&self) -> bool {
+        self.duplicate_identity
+    }
+
     /// At a regular interval, try to finalize a block when the primary is ready
     pub fn try_publish(&mut self, state: &mut PbftState) -> Result<(), PbftError> {
+        if self.observer || self.paused {
+            return Ok(());
+        }
+
+        // In safe mode or with a duplicate identity, hand the primary role to another node instead
+        // of proposing
+        self.check_safe_mode_expired(state);
+        if self.is_in_safe_mode() || (self.halt_on_duplicate_identity && self.duplicate_identity) {
+            if !state.is_primary() {
+                self.stepping_down = false;
+            } else if !self.stepping_down {
+                self.step_down(state)?;
+            }
+            return Ok(());
+        }
+
+        // After stepping down, wait for the view change to move the primary elsewhere
+        if self.stepping_down {
+            if state.is_primary() {
+                return Ok(());
+            }
+            self.stepping_down = false;
+        }
+
         // Only the primary takes care of this, and we try publishing a block
         // on every engine loop, even if it's not yet ready. This isn't an error,
         // so just return Ok(()).
@@ -1868,15 +3178,40 @@ where
 
         let now = chrono::prelude::Local::now().timestamp() as u64;
         let interval = now - state.last_block_timestamp;
-        if state.seq_num > 1 && state.block_publishing_min_interval.as_secs() > interval {
+        // slow down while the pipeline lags behind the committed blocks
+        let min_interval = proposal_interval(
+            state.block_publishing_min_interval,
+            &self.pipeline_progress,
+            state.seq_num.saturating_sub(1),
+            self.max_pipeline_lag,
+        );
+        if state.seq_num > 1 && min_interval.as_secs() > interval {
+            if min_interval > state.block_publishing_min_interval {
+                trace!(target: "consensus::cl", ?min_interval, "Pipeline is lagging, delaying proposal");
+            }
             return Ok(());
         }
 
+        // a proposal can't reach quorum without 2f other validators, don't waste the round
+        if self.require_connected_quorum {
+            let connected = self.connected_validators(state);
+            if connected < 2 * state.f {
+                debug!(target: "consensus::cl", connected, required = 2 * state.f, "Too few validators connected to reach quorum, not proposing");
+                return Ok(());
+            }
+        }
+
         info!(target: "consensus::cl","===================================try_publish============================================");
         info!(target: "consensus::cl","{}: Try publish proposal", state);
 
-        match self.service.summarize_block(state.seq_num, state.validators.accounts()) {
+        let (seq_num, accounts) = (state.seq_num, state.validators.accounts());
+        match self
+            .service
+            .retry_not_ready(|service| service.summarize_block(seq_num, accounts.clone()))
+        {
             Ok(_) => {}
+            // nothing to propose while idle, checked again on the next tick
+            Err(ApiServiceError::EmptyPayload) => return Ok(()),
             Err(err) => {
                 info!("Couldn't summarize, so not finalizing: {}", err);
                 return Ok(());
@@ -1910,15 +3245,48 @@ where
 
                 Ok(())
             }
+            Err(ApiServiceError::EmptyPayload) => {
+                debug!(target: "consensus::cl", "{}: Not publishing an empty block", state);
+                Ok(())
+            }
             Err(err) => {
+                self.on_proposal_failed("the execution layer failed to build the block", state);
                 Err(PbftError::ServiceError("Couldn't finalize block".into(), err.to_string()))
             }
         }
     }
 
+    /// Returns whether enough other validators are connected for a proposal to reach quorum.
+    pub fn has_connected_quorum(&self, state: &PbftState) -> bool {
+        self.connected_validators(state) >= 2 * state.f
+    }
+
+    /// Returns the number of other validators this node is connected to.
+    fn connected_validators(&self, state: &PbftState) -> u64 {
+        self.agent
+            .get_peers()
+            .iter()
+            .filter(|peer| **peer != state.id && state.validators.contains(peer))
+            .count() as u64
+    }
+
     /// Check to see if the idle timeout has expired
     pub fn check_idle_timeout_expired(&mut self, state: &mut PbftState) -> bool {
-        state.idle_timeout.check_expired()
+        // no view change is started for a primary that was paused on purpose
+        !self.paused && state.idle_timeout.check_expired()
+    }
+
+    /// Check whether the current view lasted longer than [PbftConfig::max_view_lifetime], the
+    /// primary is then rotated even if it keeps producing blocks
+    pub fn check_view_lifetime_expired(&mut self, state: &PbftState) -> bool {
+        self.check_view_lifetime_expired_at(state, Instant::now())
+    }
+
+    fn check_view_lifetime_expired_at(&mut self, state: &PbftState, now: Instant) -> bool {
+        let Some(lifetime) = self.view_lifetime.as_mut() else { return false };
+        // the view is tracked even while paused, so the lifetime restarts with every new view
+        let expired = lifetime.check_expired_at(state.view, now);
+        !self.paused && state.mode == PbftMode::Normal && expired
     }
 
     /// Start the idle timeout
@@ -1928,7 +3296,7 @@ where
 
     /// Check to see if the commit timeout has expired
     pub fn check_commit_timeout_expired(&mut self, state: &mut PbftState) -> bool {
-        state.commit_timeout.check_expired()
+        !self.paused && state.commit_timeout.check_expired()
     }
 
     /// Start the commit timeout
@@ -1941,6 +3309,32 @@ where
         state.view_change_timeout.check_expired()
     }
 
+    /// Check that the head of the execution layer is the last committed block, and issue a
+    /// forkchoice update to realign it otherwise
+    ///
+    /// Returns [PbftError::HeadDiverged] if the heads still diverge after more than
+    /// `max_head_divergences` consecutive checks, the engine has to stop then
+    pub fn reconcile_head(&mut self) -> Result<(), PbftError> {
+        let el_head = self.service.reconcile_head().map_err(|err| {
+            PbftError::ServiceError("Couldn't check execution layer head".into(), err.to_string())
+        })?;
+        let Some(el_head) = el_head else {
+            self.head_divergences = 0;
+            return Ok(());
+        };
+
+        self.head_divergences += 1;
+        warn!(target: "consensus::cl", ?el_head, divergences = self.head_divergences, "Execution layer head diverged from the committed head; realigning");
+        if self.head_divergences > self.max_head_divergences {
+            error!(target: "consensus::cl", ?el_head, divergences = self.head_divergences, "Execution layer head keeps diverging from the committed head");
+            return Err(PbftError::HeadDiverged(format!(
+                "execution layer head {} diverged from the committed head on {} consecutive checks",
+                el_head, self.head_divergences
+            )));
+        }
+        Ok(())
+    }
+
     // ---------- Methods for communication between nodes ----------
 
     /// Construct a PbftMessage message and broadcast it to all peers (including self)
@@ -1980,6 +3374,11 @@ where
         to_all: bool,
         to_self: bool,
     ) -> Result<(), PbftError> {
+        // observers never sign messages, not even their own votes
+        if self.observer {
+            return Ok(());
+        }
+
         // Broadcast to peers
         let message_bytes = msg.get_message_bytes();
 
@@ -1994,16 +3393,14 @@ where
         let header_bytes = reth_primitives::Bytes::copy_from_slice(header_out.as_slice());
 
         //sign header
-        let signature_hash = keccak256(&header_bytes);
-        let signature =
-            sign_message(B256::from_slice(&state.kp.secret_bytes()[..]), signature_hash).map_err(
-                |err| PbftError::SigningError(format!("signing header error: {}", err.to_string())),
-            )?;
+        let signature = self.sign_header(&header_bytes, state)?;
 
         let clayer_msg = ClayerConsensusMessage {
             header_bytes,
             header_signature: ClayerSignature(signature),
             message_bytes,
+            ttl: self.message_ttl,
+            topic: self.agent.topic(),
         };
         let mut msg_out = vec![];
         clayer_msg.encode(&mut msg_out);
@@ -2040,8 +3437,18 @@ where
             signer_id: state.id.clone(),
         };
 
-        let msg: ClayerBlock =
-            ClayerBlock { info, block: payload, seal_bytes, payload_id: payload_id.id() };
+        let mut msg: ClayerBlock = ClayerBlock {
+            info,
+            block: payload,
+            seal_bytes,
+            payload_id: payload_id.id(),
+            metadata: None,
+        };
+        if let Some(metadata) = self.block_metadata.clone() {
+            let signed =
+                SignedBlockMetadata::sign(metadata, msg.block_id(), state.signer.as_ref())?;
+            msg.metadata = Some(signed.to_bytes());
+        }
 
         trace!(target: "consensus::cl","{}: Created BlockNew message: {:?}", state, msg);
 
@@ -2056,6 +3463,11 @@ where
         state: &PbftState,
         recipient: &PeerId,
     ) -> Result<(), PbftError> {
+        // observers never sign messages
+        if self.observer {
+            return Ok(());
+        }
+
         let seal = self.build_seal(state).map_err(|err| {
             PbftError::InternalError(format!("Failed to build requested seal due to: {}", err))
         })?;
@@ -2075,16 +3487,14 @@ where
         let header_bytes = reth_primitives::Bytes::copy_from_slice(header_out.as_slice());
 
         //sign header
-        let signature_hash = keccak256(&header_bytes);
-        let signature =
-            sign_message(B256::from_slice(&state.kp.secret_bytes()[..]), signature_hash).map_err(
-                |err| PbftError::SigningError(format!("signing header error: {}", err.to_string())),
-            )?;
+        let signature = self.sign_header(&header_bytes, state)?;
 
         let clayer_msg = ClayerConsensusMessage {
             header_bytes,
             header_signature: ClayerSignature(signature),
             message_bytes,
+            ttl: self.message_ttl,
+            topic: self.agent.topic(),
         };
         let mut msg_out = vec![];
         clayer_msg.encode(&mut msg_out);
@@ -2105,6 +3515,11 @@ where
         recipient: &PeerId,
         seal: &PbftSeal,
     ) -> Result<(), PbftError> {
+        // observers never sign messages
+        if self.observer {
+            return Ok(());
+        }
+
         let mut msg_out = vec![];
         seal.encode(&mut msg_out);
         let message_bytes = reth_primitives::Bytes::copy_from_slice(msg_out.as_slice());
@@ -2121,16 +3536,14 @@ where
         let header_bytes = reth_primitives::Bytes::copy_from_slice(header_out.as_slice());
 
         //sign header
-        let signature_hash = keccak256(&header_bytes);
-        let signature =
-            sign_message(B256::from_slice(&state.kp.secret_bytes()[..]), signature_hash).map_err(
-                |err| PbftError::SigningError(format!("signing header error: {}", err.to_string())),
-            )?;
+        let signature = self.sign_header(&header_bytes, state)?;
 
         let clayer_msg = ClayerConsensusMessage {
             header_bytes,
             header_signature: ClayerSignature(signature),
             message_bytes,
+            ttl: self.message_ttl,
+            topic: self.agent.topic(),
         };
         let mut msg_out = vec![];
         clayer_msg.encode(&mut msg_out);
@@ -2153,6 +3566,193 @@ where
         Ok(())
     }
 
+    /// Store the committed payload and prune the payloads that are older than the retention
+    /// depth
+    fn save_committed_payload(&self, payload: &ExecutionPayloadWrapperV2) -> Result<(), PbftError> {
+        if self.payload_retention == 0 {
+            return Ok(());
+        }
+        let number = payload.execution_payload.payload_inner.block_number;
+        let content = serde_json::to_vec(payload).map_err(|err| {
+            PbftError::SerializationError(
+                "Error serializing committed payload".into(),
+                err.to_string(),
+            )
+        })?;
+        self.db.save_committed_payload(number, ConsensusBytes { content }).map_err(|err| {
+            PbftError::InternalError(format!("Failed to save committed payload due to: {}", err))
+        })?;
+
+        let oldest = (number + 1).saturating_sub(self.payload_retention);
+        if oldest > 0 {
+            self.db.prune_committed_payloads(oldest).map_err(|err| {
+                PbftError::InternalError(format!(
+                    "Failed to prune committed payloads due to: {}",
+                    err
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Saves the committed payloads that were not saved yet, oldest first. Payloads that fail to
+    /// save are kept for the next attempt.
+    fn flush_committed_payloads(&mut self) -> Result<(), PbftError> {
+        while let Some(payload) = self.unsaved_payloads.front() {
+            self.save_committed_payload(payload)?;
+            self.unsaved_payloads.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Writes the state that is only kept in memory to the database before the engine stops.
+    ///
+    /// Proposals that were not committed yet are discarded, they are proposed again once the
+    /// node restarted.
+    ///
+    /// The PBFT state (view, sequence number and phase) is not persisted. A restarted node
+    /// starts at the block after its committed head, in the view of that block's seal, and
+    /// catches up to the current view through the view changes and seals of the other
+    /// validators.
+    pub fn shutdown(&mut self) -> Result<(), PbftError> {
+        let proposals = self.service.pending_proposals();
+        if !proposals.is_empty() {
+            info!(target: "consensus::cl", count = proposals.len(), "Discarding uncommitted proposals on shutdown");
+        }
+        self.flush_committed_payloads()
+    }
+
+    /// Returns the payload committed at the given block number, if it is within the retention
+    /// depth
+    pub fn load_committed_payload(
+        &self,
+        number: u64,
+    ) -> Result<Option<ExecutionPayloadWrapperV2>, PbftError> {
+        let result = self.db.committed_payload(number).map_err(|err| {
+            PbftError::InternalError(format!("Failed to load committed payload due to: {}", err))
+        })?;
+        result
+            .map(|content| {
+                serde_json::from_slice(&content.content).map_err(|err| {
+                    PbftError::SerializationError(
+                        "Error parsing committed payload".into(),
+                        err.to_string(),
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    /// Records the randao mix of the block `block_id` committed at `number`, which extends the mix
+    /// of its parent. The chain starts from the zero hash at the first block committed since
+    /// [PbftConfig::randao] was enabled.
+    fn save_randao_mix(&self, number: u64, block_id: B256) -> Result<(), PbftError> {
+        let parent_mix = match number.checked_sub(1) {
+            Some(parent) => self.backfill_randao_mix(parent)?,
+            None => B256::ZERO,
+        };
+        self.db.save_randao_mix(number, next_randao_mix(parent_mix, block_id)).map_err(|err| {
+            PbftError::InternalError(format!("Failed to save randao mix due to: {}", err))
+        })?;
+        Ok(())
+    }
+
+    /// Returns the randao mix of the block at `number`, the zero hash if no mix was recorded up to
+    /// it.
+    ///
+    /// Mixes missing after the first recorded one, e.g. while [PbftConfig::randao] was disabled,
+    /// are recomputed from the hashes of the canonical blocks and saved. Fails if one of these
+    /// blocks is unknown, the chain of mixes would diverge from the other validators otherwise.
+    fn backfill_randao_mix(&self, number: u64) -> Result<B256, PbftError> {
+        if let Some(mix) = self.load_randao_mix(number)? {
+            return Ok(mix)
+        }
+        let first = self.db.first_randao_mix_number().map_err(|err| {
+            PbftError::InternalError(format!("Failed to load randao mix due to: {}", err))
+        })?;
+        let Some(first) = first.filter(|first| *first < number) else { return Ok(B256::ZERO) };
+
+        // the mix of `first` is recorded, so there is a last recorded mix before `number`
+        let mut last = number - 1;
+        let mut mix = loop {
+            if let Some(mix) = self.load_randao_mix(last)? {
+                break mix
+            }
+            if last == first {
+                return Err(PbftError::InternalError(format!(
+                    "Randao mix of block {} is missing",
+                    first
+                )))
+            }
+            last -= 1;
+        };
+
+        for missing in last + 1..=number {
+            let block_hash = self
+                .client
+                .block_hash(missing)
+                .map_err(|err| {
+                    PbftError::InternalError(format!("Failed to load block hash due to: {}", err))
+                })?
+                .ok_or_else(|| {
+                    PbftError::InternalError(format!(
+                        "Randao mix of block {} is missing and the block is unknown",
+                        missing
+                    ))
+                })?;
+            mix = next_randao_mix(mix, block_hash);
+            self.db.save_randao_mix(missing, mix).map_err(|err| {
+                PbftError::InternalError(format!("Failed to save randao mix due to: {}", err))
+            })?;
+        }
+        warn!(target: "consensus::cl", from = last + 1, to = number, "Backfilled missing randao mixes");
+        Ok(mix)
+    }
+
+    /// Returns the randao mix after the block at `number` was committed, `None` if no mix was
+    /// recorded for it
+    pub fn load_randao_mix(&self, number: u64) -> Result<Option<B256>, PbftError> {
+        self.db.randao_mix(number).map_err(|err| {
+            PbftError::InternalError(format!("Failed to load randao mix due to: {}", err))
+        })
+    }
+
+    /// Stores the record of a view change started by this node. Failures are only logged, they
+    /// must not prevent the view change.
+    fn save_view_change(&self, record: &ViewChangeRecord) {
+        let content = match serde_json::to_vec(record) {
+            Ok(content) => content,
+            Err(err) => {
+                error!(target: "consensus::cl", ?err, "Failed to serialize view change record");
+                return;
+            }
+        };
+        if let Err(err) = self.db.save_view_change(record.new_view, ConsensusBytes { content }) {
+            error!(target: "consensus::cl", ?err, view = record.new_view, "Failed to save view change record");
+        }
+    }
+
+    /// Returns the recorded view changes of this node to the views in `views`, ordered by view
+    pub fn load_view_changes(
+        &self,
+        views: RangeInclusive<u64>,
+    ) -> Result<Vec<ViewChangeRecord>, PbftError> {
+        let records = self.db.view_changes(views).map_err(|err| {
+            PbftError::InternalError(format!("Failed to load view changes due to: {}", err))
+        })?;
+        records
+            .iter()
+            .map(|record| {
+                serde_json::from_slice(&record.content).map_err(|err| {
+                    PbftError::SerializationError(
+                        "Error parsing view change record".into(),
+                        err.to_string(),
+                    )
+                })
+            })
+            .collect()
+    }
+
     pub fn load_seal(&self, block_id: B256) -> Result<Option<PbftSeal>, PbftError> {
         let result = self.db.consensus_content(block_id).map_err(|err| {
             PbftError::InternalError(format!("Failed to load seal due to: {}", err))
@@ -2176,11 +3776,22 @@ where
     /// Start a view change when this node suspects that the primary is faulty
     ///
     /// Update state to reflect that the node is now in the process of this view change, start the
-    /// view change timeout, and broadcast a view change message
+    /// view change timeout, record the view change and broadcast a view change message
     ///
     /// # Panics
     /// + If the view change timeout overflows
-    pub fn start_view_change(&mut self, state: &mut PbftState, view: u64) -> Result<(), PbftError> {
+    pub fn start_view_change(
+        &mut self,
+        state: &mut PbftState,
+        view: u64,
+        reason: ViewChangeReason,
+    ) -> Result<(), PbftError> {
+        // Observers follow the view changes of the validators by their NewView messages
+        if self.observer {
+            warn!(target: "consensus::cl","{}: Observer would have started change to view {}", state, view);
+            return Ok(());
+        }
+
         // Do not send messages again if we are already in the midst of this or a later view change
         if match state.mode {
             PbftMode::ViewChanging(v) => view <= v,
@@ -2189,8 +3800,9 @@ where
             return Ok(());
         }
 
-        info!(target: "consensus::cl","{}: Starting change to view {}", state, view);
+        info!(target: "consensus::cl","{}: Starting change to view {} ({:?})", state, view, reason);
 
+        self.save_view_change(&ViewChangeRecord::new(state.view, view, reason, state.seq_num));
         state.mode = PbftMode::ViewChanging(view);
 
         // Stop the idle and commit timeouts because they are not needed until after the view
@@ -2237,7 +3849,9 @@ where
     }
 }
 
-fn execution_payload_from_payload(payload: &ExecutionPayloadWrapperV2) -> ClayerExecutionPayload {
+pub(crate) fn execution_payload_from_payload(
+    payload: &ExecutionPayloadWrapperV2,
+) -> ClayerExecutionPayload {
     let p = &payload.execution_payload.payload_inner;
     let withdrawals = payload
         .execution_payload
@@ -2306,6 +3920,11 @@ fn execution_payload_to_payload(payload: &ClayerExecutionPayload) -> ExecutionPa
 }
 
 /// for initialize, broadcast_bootstrap_commit
+/// Returns the randao mix after committing the block `block_hash` on top of `mix`, the hash of both
+pub fn next_randao_mix(mix: B256, block_hash: B256) -> B256 {
+    keccak256([mix.as_slice(), block_hash.as_slice()].concat())
+}
+
 pub fn clayer_block_from_header(header: &SealedHeader) -> ClayerBlock {
     let block = ClayerExecutionPayload {
         parent_hash: header.parent_hash,
@@ -2341,7 +3960,47 @@ pub fn clayer_block_from_header(header: &SealedHeader) -> ClayerBlock {
         block,
         seal_bytes: reth_primitives::Bytes::default(),
         payload_id: B64::ZERO,
+        metadata: None,
+    }
+}
+
+/// Checks that a block `timestamp` is at most `max_skew` ahead of `now`, a zero `max_skew`
+/// accepts every timestamp.
+fn check_timestamp_skew(timestamp: u64, now: u64, max_skew: Duration) -> Result<(), String> {
+    if max_skew.is_zero() || timestamp <= now.saturating_add(max_skew.as_secs()) {
+        return Ok(());
+    }
+    Err(format!(
+        "timestamp {} is {}s ahead of the local clock, at most {}s are allowed",
+        timestamp,
+        timestamp - now,
+        max_skew.as_secs()
+    ))
+}
+
+/// Checks that a block `gas_limit` differs by less than 1/1024 from `parent_gas_limit` and is at
+/// least [MINIMUM_GAS_LIMIT]. On the block activating London the parent gas limit is doubled first,
+/// the gas target of EIP-1559 starts at the gas limit before the fork.
+fn check_gas_limit(
+    gas_limit: u64,
+    parent_gas_limit: u64,
+    london_transition: bool,
+) -> Result<(), String> {
+    let parent_gas_limit = if london_transition {
+        parent_gas_limit * EIP1559_DEFAULT_ELASTICITY_MULTIPLIER
+    } else {
+        parent_gas_limit
+    };
+    if gas_limit < MINIMUM_GAS_LIMIT {
+        return Err(format!("gas limit {} is below the minimum {}", gas_limit, MINIMUM_GAS_LIMIT))
+    }
+    if gas_limit.abs_diff(parent_gas_limit) >= parent_gas_limit / GAS_LIMIT_BOUND_DIVISOR {
+        return Err(format!(
+            "gas limit {} is out of bounds of the parent gas limit {}",
+            gas_limit, parent_gas_limit
+        ))
     }
+    Ok(())
 }
 
 /// for initialize, broadcast_bootstrap_commit
@@ -2379,7 +4038,7 @@ pub fn clayer_block_from_seal(header: &SealedHeader, seal: PbftSeal) -> ClayerBl
     seal.encode(&mut msg_out);
     let message_bytes = reth_primitives::Bytes::copy_from_slice(msg_out.as_slice());
 
-    ClayerBlock { info, block, seal_bytes: message_bytes, payload_id: B64::ZERO }
+    ClayerBlock { info, block, seal_bytes: message_bytes, payload_id: B64::ZERO, metadata: None }
 }
 
 pub fn assemble_peer_id(datas: Vec<Vec<u8>>) -> Result<Vec<PeerId>, PbftError> {
@@ -2414,7 +4073,1677 @@ pub fn assemble_peer_id(datas: Vec<Vec<u8>>) -> Result<Vec<PeerId>, PbftError> {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::{
+        engine_api::{
+            http_blocking::HttpJsonRpcSync, json_structures::BlockTag, ClRpcError, ExecutionApi,
+            ExecutionBlock,
+        },
+        engine_pbft::parse_consensus_message,
+    };
     use alloy_primitives::B256;
+    use reth_ecies::util::pk2id;
+    use reth_interfaces::{
+        db::DatabaseError,
+        provider::{ProviderError, ProviderResult},
+    };
+    use reth_primitives::{Block, Header};
+    use reth_provider::{
+        providers::ConsensusProvider,
+        test_utils::{create_test_provider_factory, MockEthProvider},
+    };
+    use reth_rpc_types::engine::{
+        ExecutionPayloadInputV2, ForkchoiceState, ForkchoiceUpdated, PayloadAttributes,
+        PayloadStatus, PayloadStatusEnum,
+    };
+    use secp256k1::{KeyPair, SecretKey, SECP256K1};
+
+    /// Encodes an envelope with the given TTL, the payload is irrelevant for relaying
+    fn envelope(ttl: u8) -> reth_primitives::Bytes {
+        let message = ClayerConsensusMessage { ttl, ..Default::default() };
+        let mut out = vec![];
+        message.encode(&mut out);
+        out.into()
+    }
+
+    #[test]
+    fn relay_stops_at_ttl() {
+        let config = PbftConfig { message_ttl: 3, ..Default::default() };
+        // a line of nodes, each connected to its neighbours: 0 - 1 - 2 - 3 - 4
+        let ids: Vec<PeerId> = (0..5).map(|_| PeerId::random()).collect();
+        let mut hops = Vec::new();
+        for i in 1..ids.len() {
+            let agent = ClayerConsensusMessagingAgent::new().with_message_ttl(config.message_ttl);
+            let mut rx = agent.pending_consensus_listener();
+            agent.push_network_event(ids[i - 1], true);
+            if let Some(next) = ids.get(i + 1) {
+                agent.push_network_event(*next, true);
+            }
+            // drain the connection events and the hellos
+            while agent.pop_event().is_some() {}
+            while rx.try_recv().is_ok() {}
+            hops.push((agent, rx));
+        }
+
+        // node 0 broadcasts, every node forwards whatever it relays to the next node
+        let mut from = ids[0];
+        let mut data = envelope(config.message_ttl);
+        let mut delivered = 0;
+        for (i, (agent, rx)) in hops.iter_mut().enumerate() {
+            agent.push_received_cache(from, data.clone());
+            if agent.pop_event().is_some() {
+                delivered += 1;
+            }
+            match rx.try_recv() {
+                Ok((peers, relayed)) => {
+                    assert_eq!(peers, vec![ids[i + 2]]);
+                    from = ids[i + 1];
+                    data = relayed;
+                }
+                Err(_) => break,
+            }
+        }
+        assert_eq!(delivered, config.message_ttl as usize);
+
+        // a message whose TTL is already exhausted is dropped
+        let (agent, rx) = &mut hops[0];
+        agent.push_received_cache(ids[0], envelope(0));
+        assert!(agent.pop_event().is_none());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn caps_ttl_of_received_messages() {
+        let (a, b) = (PeerId::random(), PeerId::random());
+        let agent = ClayerConsensusMessagingAgent::new().with_message_ttl(2);
+        let mut rx = agent.pending_consensus_listener();
+        agent.push_network_event(a, true);
+        agent.push_network_event(b, true);
+        while agent.pop_event().is_some() {}
+        while rx.try_recv().is_ok() {}
+
+        // the message is relayed as if it was sent with the TTL of this node
+        agent.push_received_cache(a, envelope(u8::MAX));
+        assert!(agent.pop_event().is_some());
+        let (peers, relayed) = rx.try_recv().unwrap();
+        assert_eq!(peers, vec![b]);
+        assert_eq!(ClayerConsensusMessage::decode(&mut relayed.as_ref()).unwrap().ttl, 1);
+
+        // the seen-cache is enabled by default, the copy relayed back is dropped
+        agent.push_received_cache(b, relayed);
+        assert!(agent.pop_event().is_none());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn exchanges_version_1_messages() {
+        let (a, b) = (PeerId::random(), PeerId::random());
+        let legacy = ClayerConsensusMessagingAgent::new().with_topic(1).with_protocol_version(1);
+        let mut from_legacy = legacy.pending_consensus_listener();
+        let agent = ClayerConsensusMessagingAgent::new().with_topic(1).with_message_ttl(3);
+        let mut from_agent = agent.pending_consensus_listener();
+        agent.push_network_event(a, true);
+        agent.push_network_event(b, true);
+        while agent.pop_event().is_some() {}
+        while from_agent.try_recv().is_ok() {}
+
+        let message = ClayerConsensusMessage {
+            message_bytes: vec![1; 32].into(),
+            ttl: 3,
+            topic: 1,
+            ..Default::default()
+        };
+        let mut out = vec![];
+        message.encode(&mut out);
+        legacy.broadcast_consensus(vec![], out.into());
+        let (_, sent) = from_legacy.try_recv().unwrap();
+        let v1 = ClayerConsensusMessageV1::decode(&mut sent.as_ref()).unwrap();
+        assert_eq!(v1, ClayerConsensusMessageV1::from(message.clone()));
+        assert!(ClayerConsensusMessage::decode(&mut sent.as_ref()).is_err());
+
+        // a version 1 message is delivered with the local topic but not relayed
+        agent.push_received_cache(a, sent);
+        let Some(ClayerConsensusEvent::PeerMessage(peer, data)) = agent.pop_event() else {
+            panic!("expected the version 1 message to be delivered")
+        };
+        assert_eq!(peer, a);
+        assert_eq!(
+            ClayerConsensusMessage::decode(&mut data.as_ref()).unwrap(),
+            ClayerConsensusMessage { ttl: 1, ..message }
+        );
+        assert!(from_agent.try_recv().is_err());
+    }
+
+    #[test]
+    fn drops_messages_of_other_topics() {
+        let (a, b) = (PeerId::random(), PeerId::random());
+        let mainnet = ClayerConsensusMessagingAgent::new().with_topic(1);
+        let same = ClayerConsensusMessagingAgent::new().with_topic(1);
+        let other = ClayerConsensusMessagingAgent::new().with_topic(2);
+        let tagged = |agent: &ClayerConsensusMessagingAgent| {
+            let message =
+                ClayerConsensusMessage { ttl: 1, topic: agent.topic(), ..Default::default() };
+            let mut out = vec![];
+            message.encode(&mut out);
+            reth_primitives::Bytes::from(out)
+        };
+
+        same.push_received_cache(a, tagged(&mainnet));
+        assert!(
+            matches!(same.pop_event(), Some(ClayerConsensusEvent::PeerMessage(peer, _)) if peer == a)
+        );
+
+        other.push_received_cache(a, tagged(&mainnet));
+        assert!(other.pop_event().is_none());
+        assert_eq!(other.peer_message_counts(a).dropped, 1);
+
+        mainnet.push_received_cache(b, tagged(&other));
+        assert!(mainnet.pop_event().is_none());
+        other.push_received_cache(b, tagged(&other));
+        assert!(other.pop_event().is_some());
+    }
+
+    #[test]
+    fn drops_relayed_copies() {
+        let (a, b) = (PeerId::random(), PeerId::random());
+        let agent =
+            ClayerConsensusMessagingAgent::new().with_seen_cache(2, Duration::from_secs(60));
+        let message = |seq_num: u8, ttl: u8| {
+            let message = ClayerConsensusMessage {
+                message_bytes: vec![seq_num].into(),
+                ttl,
+                ..Default::default()
+            };
+            let mut out = vec![];
+            message.encode(&mut out);
+            reth_primitives::Bytes::from(out)
+        };
+
+        agent.push_received_cache(a, message(1, 3));
+        assert!(agent.pop_event().is_some());
+        // the same message relayed by another peer, with a lower TTL
+        agent.push_received_cache(b, message(1, 2));
+        assert!(agent.pop_event().is_none());
+        assert_eq!(agent.peer_message_counts(b).dropped, 1);
+
+        // the first message is evicted once two newer ones were received
+        agent.push_received_cache(a, message(2, 3));
+        agent.push_received_cache(a, message(3, 3));
+        agent.push_received_cache(b, message(1, 2));
+        assert_eq!(std::iter::from_fn(|| agent.pop_event()).count(), 3);
+        assert_eq!(agent.inner.read().seen.evictions(), 2);
+    }
+
+    #[test]
+    fn drops_peer_without_handshake() {
+        let (silent, talking) = (PeerId::random(), PeerId::random());
+        let agent = ClayerConsensusMessagingAgent::new()
+            .with_topic(1)
+            .with_handshake_timeout(Duration::from_millis(20));
+        agent.push_network_event(silent, true);
+        agent.push_network_event(talking, true);
+        let message = ClayerConsensusMessage { ttl: 1, topic: 1, ..Default::default() };
+        let mut out = vec![];
+        message.encode(&mut out);
+        agent.push_received_cache(talking, out.into());
+        while agent.pop_event().is_some() {}
+
+        // both peers are connected until the timeout expires
+        let mut peers = agent.get_peers();
+        peers.sort();
+        let mut expected = vec![silent, talking];
+        expected.sort();
+        assert_eq!(peers, expected);
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(matches!(
+            agent.pop_event(),
+            Some(ClayerConsensusEvent::PeerNetWork(peer, false)) if peer == silent
+        ));
+        assert!(agent.pop_event().is_none());
+        assert_eq!(agent.get_peers(), vec![talking]);
+    }
+
+    #[test]
+    fn quiet_peer_completes_handshake_with_hello() {
+        let (quiet, foreign) = (PeerId::random(), PeerId::random());
+        let agent = ClayerConsensusMessagingAgent::new()
+            .with_topic(1)
+            .with_handshake_timeout(Duration::from_millis(20));
+        let mut sent = agent.pending_consensus_listener();
+        agent.push_network_event(quiet, true);
+        agent.push_network_event(foreign, true);
+
+        // every connecting peer is greeted
+        let hello = hello_frame(CLAYER_PROTOCOL_VERSION, 1);
+        assert_eq!(sent.try_recv().unwrap(), (vec![quiet], hello.clone()));
+        assert_eq!(sent.try_recv().unwrap(), (vec![foreign], hello.clone()));
+
+        // only the hello of this network completes the handshake, neither is a consensus message
+        agent.push_received_cache(quiet, hello);
+        agent.push_received_cache(foreign, hello_frame(CLAYER_PROTOCOL_VERSION, 2));
+        while agent.pop_event().is_some() {}
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(matches!(
+            agent.pop_event(),
+            Some(ClayerConsensusEvent::PeerNetWork(peer, false)) if peer == foreign
+        ));
+        assert!(agent.pop_event().is_none());
+        assert_eq!(agent.get_peers(), vec![quiet]);
+    }
+
+    #[test]
+    fn marks_unresponsive_peer_dead() {
+        let (silent, responsive) = (PeerId::random(), PeerId::random());
+        let timeout = Duration::from_millis(50);
+        let agent =
+            ClayerConsensusMessagingAgent::new().with_heartbeat(Duration::from_millis(10), timeout);
+        let mut sent = agent.pending_consensus_listener();
+        let connected_at = Instant::now();
+        agent.push_network_event(silent, true);
+        agent.push_network_event(responsive, true);
+        while sent.try_recv().is_ok() {}
+
+        // only the responsive peer answers the pings
+        let mut dead_after = None;
+        while dead_after.is_none() && connected_at.elapsed() < timeout * 4 {
+            while let Some(event) = agent.pop_event() {
+                if matches!(event, ClayerConsensusEvent::PeerDead(peer) if peer == silent) {
+                    dead_after = Some(connected_at.elapsed());
+                }
+            }
+            while let Ok((peers, frame)) = sent.try_recv() {
+                assert_eq!(frame, ping_frame());
+                if peers.contains(&responsive) {
+                    agent.push_received_cache(responsive, pong_frame());
+                }
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let dead_after = dead_after.expect("silent peer was not marked dead");
+        assert!(dead_after >= timeout && dead_after < timeout * 2, "{dead_after:?}");
+        assert_eq!(agent.get_peers(), vec![responsive]);
+
+        // a dead peer that is heard from again is a consensus peer again
+        agent.push_received_cache(silent, pong_frame());
+        assert!(matches!(
+            agent.pop_event(),
+            Some(ClayerConsensusEvent::PeerNetWork(peer, true)) if peer == silent
+        ));
+        assert_eq!(agent.get_peers().len(), 2);
+    }
+
+    #[test]
+    fn compresses_messages_to_negotiated_peers() {
+        let (id_a, id_b, legacy) = (PeerId::random(), PeerId::random(), PeerId::random());
+        let a =
+            ClayerConsensusMessagingAgent::new().with_topic(1).with_compression(Compression::Zstd);
+        let b =
+            ClayerConsensusMessagingAgent::new().with_topic(1).with_compression(Compression::Zstd);
+        let mut from_a = a.pending_consensus_listener();
+        let mut from_b = b.pending_consensus_listener();
+
+        // both advertise their compressions on connect, the legacy peer does not answer
+        a.push_network_event(id_b, true);
+        a.push_network_event(legacy, true);
+        b.push_network_event(id_a, true);
+        while let Ok((peers, frame)) = from_a.try_recv() {
+            if peers == vec![id_b] {
+                b.push_received_cache(id_a, frame);
+            }
+        }
+        while let Ok((_, frame)) = from_b.try_recv() {
+            a.push_received_cache(id_b, frame);
+        }
+        while a.pop_event().is_some() {}
+        while b.pop_event().is_some() {}
+
+        let message = ClayerConsensusMessage {
+            message_bytes: vec![7; 64 * 1024].into(),
+            ttl: 1,
+            topic: 1,
+            ..Default::default()
+        };
+        let mut out = vec![];
+        message.encode(&mut out);
+        let encoded = reth_primitives::Bytes::from(out);
+        a.broadcast_consensus(vec![], encoded.clone());
+
+        let (peers, compressed) = from_a.try_recv().unwrap();
+        assert_eq!(peers, vec![id_b]);
+        assert!(compressed.len() < encoded.len());
+        assert_eq!(from_a.try_recv().unwrap(), (vec![legacy], encoded.clone()));
+
+        b.push_received_cache(id_a, compressed);
+        assert!(matches!(
+            b.pop_event(),
+            Some(ClayerConsensusEvent::PeerMessage(peer, data)) if peer == id_a && data == encoded
+        ));
+    }
+
+    /// Consensus storage without any seals
+    struct NoSeals;
+
+    impl ConsensusNumberReader for NoSeals {
+        fn last_consensus_number(&self) -> ProviderResult<u64> {
+            Ok(0)
+        }
+
+        fn consensus_number(&self, _hash: B256) -> ProviderResult<Option<u64>> {
+            Ok(None)
+        }
+
+        fn consensus_content(&self, _hash: B256) -> ProviderResult<Option<ConsensusBytes>> {
+            Ok(None)
+        }
+
+        fn committed_payload(&self, _number: u64) -> ProviderResult<Option<ConsensusBytes>> {
+            Ok(None)
+        }
+
+        fn view_changes(&self, _views: RangeInclusive<u64>) -> ProviderResult<Vec<ConsensusBytes>> {
+            Ok(vec![])
+        }
+
+        fn randao_mix(&self, _number: u64) -> ProviderResult<Option<B256>> {
+            Ok(None)
+        }
+
+        fn first_randao_mix_number(&self) -> ProviderResult<Option<u64>> {
+            Ok(None)
+        }
+    }
+
+    impl ConsensusNumberWriter for NoSeals {
+        fn save_consensus_number(&self, _hash: B256, _num: u64) -> ProviderResult<bool> {
+            Ok(true)
+        }
+
+        fn save_consensus_content(&self, _hash: B256, _ct: ConsensusBytes) -> ProviderResult<bool> {
+            Ok(true)
+        }
+
+        fn save_committed_payload(
+            &self,
+            _number: u64,
+            _payload: ConsensusBytes,
+        ) -> ProviderResult<bool> {
+            Ok(true)
+        }
+
+        fn prune_committed_payloads(&self, _number: u64) -> ProviderResult<usize> {
+            Ok(0)
+        }
+
+        fn save_view_change(&self, _view: u64, _record: ConsensusBytes) -> ProviderResult<bool> {
+            Ok(true)
+        }
+
+        fn save_randao_mix(&self, _number: u64, _mix: B256) -> ProviderResult<bool> {
+            Ok(true)
+        }
+    }
+
+    /// Returns the state of a node that is the primary of view 0 among four validators
+    fn primary_state() -> PbftState {
+        let secret = SecretKey::new(&mut rand::thread_rng());
+        let id = pk2id(&KeyPair::from_secret_key(SECP256K1, &secret).public_key());
+        let mut members = vec![id];
+        members.extend((0..3).map(|_| PeerId::random()));
+        let config = PbftConfig { members, ..Default::default() };
+        PbftState::new(secret, 1, 0, &config).unwrap()
+    }
+
+    fn engine(
+        agent: ClayerConsensusMessagingAgent,
+    ) -> ClayerConsensusEngine<MockEthProvider, NoSeals> {
+        let service = ApiService::new(Arc::new(HttpJsonRpcSync::default()));
+        ClayerConsensusEngine::new(agent, service, Arc::new(NoSeals), MockEthProvider::default())
+    }
+
+    #[test]
+    fn dead_primary_starts_view_change() {
+        let mut engine = engine(ClayerConsensusMessagingAgent::new());
+        let mut state = primary_state();
+        state.view = 1;
+        let primary = state.get_primary_id();
+        let backup = (0..4)
+            .map(|index| state.validators.index(index))
+            .find(|peer| *peer != primary && *peer != state.id)
+            .unwrap();
+
+        // a dead backup does not block progress
+        engine.on_peer_dead(backup, &mut state).unwrap();
+        assert_eq!(state.mode, PbftMode::Normal);
+
+        engine.on_peer_dead(primary, &mut state).unwrap();
+        assert_eq!(state.mode, PbftMode::ViewChanging(2));
+    }
+
+    #[test]
+    fn step_down_starts_view_change() {
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut broadcasts = agent.pending_consensus_listener();
+        let mut engine = engine(agent);
+        let mut state = primary_state();
+        assert!(state.is_primary());
+
+        let (tx, mut reply) = tokio::sync::oneshot::channel();
+        engine.on_command(ConsensusCommand::StepDown(tx), &mut state);
+        assert!(reply.try_recv().unwrap().unwrap());
+        assert_eq!(state.mode, PbftMode::ViewChanging(1));
+
+        let (_, bytes) = broadcasts.try_recv().unwrap();
+        let msg = ParsedMessage::from_peer_message(parse_consensus_message(&bytes).unwrap(), &[])
+            .unwrap();
+        assert_eq!(msg.info().ptype, PbftMessageType::ViewChange as u8);
+        assert_eq!(msg.info().view, 1);
+
+        // the view change is already in progress
+        assert!(!engine.step_down(&mut state).unwrap());
+        // no proposals while this node is still the primary
+        engine.try_publish(&mut state).unwrap();
+        assert!(broadcasts.try_recv().is_err());
+    }
+
+    #[test]
+    fn step_down_ignored_if_not_primary() {
+        let mut engine = engine(ClayerConsensusMessagingAgent::new());
+        let mut state = primary_state();
+        state.view = 1;
+        assert!(!state.is_primary());
+
+        assert!(!engine.step_down(&mut state).unwrap());
+        assert_eq!(state.mode, PbftMode::Normal);
+    }
+
+    #[test]
+    fn forces_view_change_after_max_view_lifetime() {
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut broadcasts = agent.pending_consensus_listener();
+        let mut engine = engine(agent);
+        engine.view_lifetime = Some(ViewLifetime::new(Duration::from_secs(60)));
+        let mut state = primary_state();
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert!(!engine.check_view_lifetime_expired_at(&state, at(0)));
+        assert!(!engine.check_view_lifetime_expired_at(&state, at(59)));
+        assert!(engine.check_view_lifetime_expired_at(&state, at(61)));
+
+        // the task rotates the primary
+        engine
+            .start_view_change(&mut state, state.view + 1, ViewChangeReason::MaxViewLifetime)
+            .unwrap();
+        assert_eq!(state.mode, PbftMode::ViewChanging(1));
+        let (_, bytes) = broadcasts.try_recv().unwrap();
+        let msg = ParsedMessage::from_peer_message(parse_consensus_message(&bytes).unwrap(), &[])
+            .unwrap();
+        assert_eq!(msg.info().ptype, PbftMessageType::ViewChange as u8);
+        assert!(!engine.check_view_lifetime_expired_at(&state, at(62)));
+
+        // the lifetime restarts with the new view
+        state.view = 1;
+        state.mode = PbftMode::Normal;
+        assert!(!engine.check_view_lifetime_expired_at(&state, at(63)));
+        assert!(!engine.check_view_lifetime_expired_at(&state, at(123)));
+        assert!(engine.check_view_lifetime_expired_at(&state, at(124)));
+    }
+
+    /// Returns a Prepare of `signer_id` for `block_id` signed with `signer`
+    fn signed_prepare(
+        signer: &dyn ConsensusSigner,
+        signer_id: PeerId,
+        block_id: B256,
+    ) -> ClayerConsensusMessage {
+        let info = PbftMessageInfo {
+            ptype: PbftMessageType::Prepare as u8,
+            view: 0,
+            seq_num: 1,
+            signer_id,
+        };
+        let mut message_bytes = vec![];
+        PbftMessage { info, block_id }.encode(&mut message_bytes);
+        let header = ClayerConsensusMessageHeader {
+            message_type: PbftMessageType::Prepare as u8,
+            content_hash: keccak256(&message_bytes),
+            signer_id,
+        };
+        let mut header_bytes = vec![];
+        header.encode(&mut header_bytes);
+        let signature = signer.sign(keccak256(&header_bytes)).unwrap();
+        ClayerConsensusMessage {
+            header_bytes: header_bytes.into(),
+            header_signature: ClayerSignature(signature),
+            message_bytes: message_bytes.into(),
+            ttl: 1,
+            topic: Default::default(),
+        }
+    }
+
+    #[test]
+    fn detects_duplicate_identity() {
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut broadcasts = agent.pending_consensus_listener();
+        let mut engine = engine(agent);
+        engine.halt_on_duplicate_identity = true;
+        let mut state = primary_state();
+
+        // a message claiming the id of this node without its key is no evidence
+        let forger = LocalSigner::new(SecretKey::new(&mut rand::thread_rng()));
+        let forged = signed_prepare(&forger, state.id, B256::with_last_byte(1));
+        assert!(!engine.check_duplicate_identity(&forged, &state));
+        assert!(!engine.duplicate_identity_detected());
+
+        // another node signing with the key of this node
+        let foreign = signed_prepare(state.signer.as_ref(), state.id, B256::with_last_byte(1));
+        assert!(engine.check_duplicate_identity(&foreign, &state));
+        assert!(engine.duplicate_identity_detected());
+
+        // the primary hands its role to another node instead of proposing
+        engine.try_publish(&mut state).unwrap();
+        let (_, bytes) = broadcasts.try_recv().unwrap();
+        let own = ClayerConsensusMessage::decode(&mut bytes.as_ref()).unwrap();
+        let msg = ParsedMessage::from_peer_message(own.clone(), &[]).unwrap();
+        assert_eq!(msg.info().ptype, PbftMessageType::ViewChange as u8);
+
+        // the own messages relayed back by peers are not mistaken for those of another node
+        assert!(!engine.check_duplicate_identity(&own, &state));
+    }
+
+    /// Signs with a local key and counts the signed messages, like a remote signer would
+    #[derive(Debug)]
+    struct CountingSigner {
+        inner: LocalSigner,
+        signed: std::sync::atomic::AtomicUsize,
+    }
+
+    impl ConsensusSigner for CountingSigner {
+        fn public_key(&self) -> secp256k1::PublicKey {
+            self.inner.public_key()
+        }
+
+        fn sign(&self, hash: B256) -> Result<reth_primitives::Signature, PbftError> {
+            self.signed.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.inner.sign(hash)
+        }
+    }
+
+    #[test]
+    fn signs_messages_through_signer() {
+        let signer = Arc::new(CountingSigner {
+            inner: LocalSigner::new(SecretKey::new(&mut rand::thread_rng())),
+            signed: Default::default(),
+        });
+        let mut members = vec![signer.id()];
+        members.extend((0..3).map(|_| PeerId::random()));
+        let config = PbftConfig { members, ..Default::default() };
+        let mut state = PbftState::with_signer(signer.clone(), 1, 0, &config).unwrap();
+        assert_eq!(state.id, signer.id());
+
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut broadcasts = agent.pending_consensus_listener();
+        let mut engine = engine(agent);
+        assert!(engine.step_down(&mut state).unwrap());
+        assert_eq!(signer.signed.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let (_, bytes) = broadcasts.try_recv().unwrap();
+        let message = ClayerConsensusMessage::decode(&mut bytes.as_ref()).unwrap();
+        let recovered =
+            message.header_signature.0.recover_signer(keccak256(&message.header_bytes)).unwrap();
+        assert_eq!(recovered, public_key_to_address(signer.public_key()));
+    }
+
+    #[test]
+    fn timestamp_skew() {
+        let skew = Duration::from_secs(15);
+        assert!(check_timestamp_skew(1_000, 1_000, skew).is_ok());
+        assert!(check_timestamp_skew(1_015, 1_000, skew).is_ok());
+        assert!(check_timestamp_skew(1_016, 1_000, skew).is_err());
+        // blocks from the past are left to the execution layer
+        assert!(check_timestamp_skew(0, 1_000, skew).is_ok());
+        assert!(check_timestamp_skew(u64::MAX, 1_000, Duration::ZERO).is_ok());
+    }
+
+    #[test]
+    fn rejects_block_from_the_future() {
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut engine = engine(agent.clone());
+        engine.max_timestamp_skew = Duration::from_secs(15);
+        let mut state = primary_state();
+
+        let genesis = Header::default().seal_slow();
+        engine.msg_log.add_validated_block(clayer_block_from_header(&genesis));
+        let now = chrono::prelude::Local::now().timestamp() as u64;
+        let header = Header {
+            number: 1,
+            parent_hash: genesis.hash,
+            timestamp: now + 3600,
+            ..Default::default()
+        };
+        let mut block = clayer_block_from_header(&header.seal_slow());
+        block.info.signer_id = state.get_primary_id();
+        block.payload_id = B64::with_last_byte(1);
+        let block_id = block.block_id();
+
+        let err = engine.on_block_new(block, &mut state).unwrap_err();
+        assert!(matches!(err, PbftError::InvalidMessage(_)), "{err:?}");
+        assert!(engine.msg_log.get_unvalidated_block_with_id(&block_id).is_none());
+        assert_eq!(agent.peer_message_counts(state.get_primary_id()).rejected, 1);
+    }
+
+    #[test]
+    fn gas_limit_bounds() {
+        let parent = 30_000_000;
+        assert!(check_gas_limit(parent, parent, false).is_ok());
+        assert!(check_gas_limit(parent + parent / 1024 - 1, parent, false).is_ok());
+        assert!(check_gas_limit(parent + parent / 1024, parent, false).is_err());
+        assert!(check_gas_limit(parent - parent / 1024 + 1, parent, false).is_ok());
+        assert!(check_gas_limit(parent - parent / 1024, parent, false).is_err());
+        // the gas limit doubles on the block activating London
+        assert!(check_gas_limit(2 * parent, parent, false).is_err());
+        assert!(check_gas_limit(2 * parent, parent, true).is_ok());
+        assert!(check_gas_limit(MINIMUM_GAS_LIMIT - 1, MINIMUM_GAS_LIMIT, false).is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_bounds_gas_limit() {
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut engine = engine(agent.clone());
+        engine.check_gas_limit = true;
+        let mut state = primary_state();
+
+        let genesis = Header { gas_limit: 30_000_000, ..Default::default() }.seal_slow();
+        engine.msg_log.add_validated_block(clayer_block_from_header(&genesis));
+        let header = Header {
+            number: 1,
+            parent_hash: genesis.hash,
+            gas_limit: 2 * genesis.gas_limit,
+            ..Default::default()
+        };
+        let mut block = clayer_block_from_header(&header.seal_slow());
+        block.info.signer_id = state.get_primary_id();
+        block.payload_id = B64::with_last_byte(1);
+        let block_id = block.block_id();
+
+        let err = engine.on_block_new(block, &mut state).unwrap_err();
+        assert!(matches!(err, PbftError::InvalidMessage(_)), "{err:?}");
+        assert!(engine.msg_log.get_unvalidated_block_with_id(&block_id).is_none());
+        assert_eq!(agent.peer_message_counts(state.get_primary_id()).rejected, 1);
+    }
+
+    /// An execution layer that accepts every block and reports a fixed validator set
+    struct AcceptingEl(Vec<PeerId>);
+
+    impl ExecutionApi for AcceptingEl {
+        fn get_block_by_number(
+            &self,
+            _tag: BlockTag,
+        ) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            Ok(None)
+        }
+
+        fn get_block_by_hash(
+            &self,
+            _block_hash: B256,
+        ) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            Ok(None)
+        }
+
+        fn forkchoice_updated_v2(
+            &self,
+            forkchoice_state: ForkchoiceState,
+            _payload_attributes: Option<PayloadAttributes>,
+        ) -> Result<ForkchoiceUpdated, ClRpcError> {
+            Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid)
+                .with_latest_valid_hash(forkchoice_state.head_block_hash))
+        }
+
+        fn new_payload_v2(
+            &self,
+            payload: ExecutionPayloadInputV2,
+        ) -> Result<PayloadStatus, ClRpcError> {
+            let block_hash = payload.execution_payload.block_hash;
+            Ok(PayloadStatus::new(PayloadStatusEnum::Valid, Some(block_hash)))
+        }
+
+        fn get_payload_v2(
+            &self,
+            _payload_id: PayloadId,
+        ) -> Result<ExecutionPayloadWrapperV2, ClRpcError> {
+            Err(ClRpcError::RequestFailed("not building payloads".to_string()))
+        }
+
+        fn query_validators(
+            &self,
+            _contract_address: String,
+            _block_number: u64,
+        ) -> Result<Vec<Vec<u8>>, ClRpcError> {
+            // the contract returns every id in two halves
+            Ok(self.0.iter().flat_map(|id| [id[..32].to_vec(), id[32..].to_vec()]).collect())
+        }
+    }
+
+    /// A vote of `signer` received from the network
+    fn peer_vote(ptype: PbftMessageType, signer: PeerId, block_id: B256) -> ParsedMessage {
+        let info = PbftMessageInfo { ptype: ptype as u8, view: 0, seq_num: 1, signer_id: signer };
+        let mut msg = ParsedMessage::from_pbft_message(PbftMessage { info, block_id }).unwrap();
+        msg.from_self = false;
+        msg
+    }
+
+    #[test]
+    fn observer_tracks_commits_without_signing() {
+        let members: Vec<PeerId> = (0..4).map(|_| PeerId::random()).collect();
+        let config = PbftConfig { members: members.clone(), observer: true, ..Default::default() };
+        let mut state =
+            PbftState::new(SecretKey::new(&mut rand::thread_rng()), 0, 0, &config).unwrap();
+        assert!(!state.is_validator());
+
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut broadcasts = agent.pending_consensus_listener();
+        let service = ApiService::new(Arc::new(AcceptingEl(members.clone())));
+        let mut engine = ClayerConsensusEngine::new(
+            agent.clone(),
+            service,
+            Arc::new(NoSeals),
+            MockEthProvider::default(),
+        );
+
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+        engine.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+        let header = Header { number: 1, parent_hash: genesis.hash, ..Default::default() };
+        let mut block = clayer_block_from_header(&header.seal_slow());
+        block.info.signer_id = members[0];
+        block.payload_id = B64::with_last_byte(1);
+        let block_id = block.block_id();
+
+        // members[0] is the primary of view 0 and proposes block 1
+        let pre_prepare = peer_vote(PbftMessageType::PrePrepare, members[0], block_id);
+        engine.on_peer_message(members[0], pre_prepare, &mut state).unwrap();
+        engine.on_block_new(block, &mut state).unwrap();
+        assert_eq!(state.phase, PbftPhase::Preparing);
+
+        for &signer in &members[1..] {
+            let prepare = peer_vote(PbftMessageType::Prepare, signer, block_id);
+            engine.on_peer_message(signer, prepare, &mut state).unwrap();
+        }
+        assert_eq!(state.phase, PbftPhase::Committing);
+
+        for &signer in &members[..3] {
+            let commit = peer_vote(PbftMessageType::Commit, signer, block_id);
+            engine.on_peer_message(signer, commit, &mut state).unwrap();
+        }
+        assert_eq!(state.phase, PbftPhase::Finishing(false));
+        let Some(ClayerConsensusEvent::BlockCommit((committed, timestamp, true))) =
+            agent.pop_event()
+        else {
+            panic!("block was not committed")
+        };
+        assert_eq!(committed, block_id);
+
+        engine.on_block_commit(committed, timestamp, true, &mut state).unwrap();
+        assert_eq!(state.seq_num, 2);
+        assert_eq!(state.chain_head, block_id);
+
+        // neither a Prepare, a Commit nor an AnnounceBlock was sent
+        assert!(broadcasts.try_recv().is_err());
+    }
+
+    #[test]
+    fn rotates_primary_at_block_boundary() {
+        let members: Vec<PeerId> = (0..4).map(|_| PeerId::random()).collect();
+        let config = PbftConfig {
+            members: members.clone(),
+            observer: true,
+            forced_view_change_interval: 3,
+            ..Default::default()
+        };
+        let mut state =
+            PbftState::new(SecretKey::new(&mut rand::thread_rng()), 0, 0, &config).unwrap();
+        let service = ApiService::new(Arc::new(AcceptingEl(members.clone())));
+        let mut engine = ClayerConsensusEngine::new(
+            ClayerConsensusMessagingAgent::new(),
+            service,
+            Arc::new(NoSeals),
+            MockEthProvider::default(),
+        );
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+        engine.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+
+        let mut commit = |number: u8, state: &mut PbftState| {
+            engine
+                .on_block_commit(B256::with_last_byte(number), number as u64, false, state)
+                .unwrap();
+            state.get_primary_id()
+        };
+        assert_eq!(state.get_primary_id(), members[0]);
+        assert_eq!(commit(1, &mut state), members[0]);
+        // block 3 is proposed by the next primary
+        assert_eq!(commit(2, &mut state), members[1]);
+        assert_eq!(commit(3, &mut state), members[1]);
+
+        // a view change caused by a fault moves the primary, the rotation still happens at the
+        // next boundary
+        state.view += 1;
+        assert_eq!(commit(4, &mut state), members[2]);
+        assert_eq!(commit(5, &mut state), members[3]);
+        assert_eq!(state.view, 3);
+
+        // disabled
+        state.forced_view_change_interval = 0;
+        for number in 6..=9 {
+            assert_eq!(commit(number, &mut state), members[3]);
+        }
+    }
+
+    /// Collects everything the log subscriber writes
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<parking_lot::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn logs_commit_summary() {
+        let members: Vec<PeerId> = (0..4).map(|_| PeerId::random()).collect();
+        let config = PbftConfig { members: members.clone(), observer: true, ..Default::default() };
+        let mut state =
+            PbftState::new(SecretKey::new(&mut rand::thread_rng()), 0, 0, &config).unwrap();
+        let service = ApiService::new(Arc::new(AcceptingEl(members.clone())));
+        let mut engine = ClayerConsensusEngine::new(
+            ClayerConsensusMessagingAgent::new(),
+            service,
+            Arc::new(NoSeals),
+            MockEthProvider::default(),
+        );
+
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+        engine.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+        let header = Header { number: 1, parent_hash: genesis.hash, ..Default::default() };
+        let mut block = clayer_block_from_header(&header.seal_slow());
+        block.info.signer_id = members[0];
+        block.payload_id = B64::with_last_byte(1);
+        let block_id = block.block_id();
+
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = reth_tracing::tracing_subscriber::fmt()
+            .with_max_level(Level::INFO)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let pre_prepare = peer_vote(PbftMessageType::PrePrepare, members[0], block_id);
+            engine.on_peer_message(members[0], pre_prepare, &mut state).unwrap();
+            engine.on_block_new(block, &mut state).unwrap();
+            for &signer in &members[1..] {
+                let prepare = peer_vote(PbftMessageType::Prepare, signer, block_id);
+                engine.on_peer_message(signer, prepare, &mut state).unwrap();
+            }
+            for &signer in &members[..3] {
+                let commit = peer_vote(PbftMessageType::Commit, signer, block_id);
+                engine.on_peer_message(signer, commit, &mut state).unwrap();
+            }
+        });
+        assert_eq!(state.phase, PbftPhase::Finishing(false));
+
+        let logs = String::from_utf8(logs.0.lock().clone()).unwrap();
+        let summaries: Vec<_> =
+            logs.lines().filter(|line| line.contains("Committed block")).collect();
+        assert_eq!(summaries.len(), 1);
+        for field in [
+            "height=1".to_string(),
+            format!("hash={block_id}"),
+            "view=0".to_string(),
+            "prepares=3".to_string(),
+            "commits=3".to_string(),
+            format!("proposer={}", members[0]),
+            "tx_count=0".to_string(),
+            "commit_latency_ms=".to_string(),
+            "catch_up=false".to_string(),
+        ] {
+            assert!(summaries[0].contains(&field), "{field} missing in {}", summaries[0]);
+        }
+    }
+
+    /// An execution layer that builds block 1 on top of the genesis block `0`
+    struct ProposingEl(B256);
+
+    impl ExecutionApi for ProposingEl {
+        fn get_block_by_number(
+            &self,
+            _tag: BlockTag,
+        ) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            Ok(Some(ExecutionBlock {
+                block_hash: self.0,
+                block_number: 0,
+                parent_hash: B256::ZERO,
+                total_difficulty: reth_primitives::U256::ZERO,
+                timestamp: 0,
+            }))
+        }
+
+        fn get_block_by_hash(
+            &self,
+            _block_hash: B256,
+        ) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            Ok(None)
+        }
+
+        fn forkchoice_updated_v2(
+            &self,
+            _forkchoice_state: ForkchoiceState,
+            payload_attributes: Option<PayloadAttributes>,
+        ) -> Result<ForkchoiceUpdated, ClRpcError> {
+            let updated = ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid);
+            Ok(if payload_attributes.is_some() {
+                updated.with_payload_id(PayloadId::new([7; 8]))
+            } else {
+                updated
+            })
+        }
+
+        fn new_payload_v2(
+            &self,
+            payload: ExecutionPayloadInputV2,
+        ) -> Result<PayloadStatus, ClRpcError> {
+            let block_hash = payload.execution_payload.block_hash;
+            Ok(PayloadStatus::new(PayloadStatusEnum::Valid, Some(block_hash)))
+        }
+
+        fn get_payload_v2(
+            &self,
+            _payload_id: PayloadId,
+        ) -> Result<ExecutionPayloadWrapperV2, ClRpcError> {
+            let payload_inner = ExecutionPayloadV1 {
+                parent_hash: self.0,
+                fee_recipient: Default::default(),
+                state_root: B256::ZERO,
+                receipts_root: B256::ZERO,
+                logs_bloom: Default::default(),
+                prev_randao: B256::ZERO,
+                block_number: 1,
+                gas_limit: 30_000_000,
+                gas_used: 0,
+                timestamp: 1,
+                extra_data: Default::default(),
+                base_fee_per_gas: reth_primitives::U256::from(7),
+                block_hash: B256::with_last_byte(1),
+                transactions: vec![],
+            };
+            Ok(ExecutionPayloadWrapperV2 {
+                execution_payload: ExecutionPayloadV2 { payload_inner, withdrawals: vec![] },
+                block_value: reth_primitives::U256::ZERO,
+            })
+        }
+
+        fn query_validators(
+            &self,
+            _contract_address: String,
+            _block_number: u64,
+        ) -> Result<Vec<Vec<u8>>, ClRpcError> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn prunes_committed_payloads_past_retention() {
+        let db = ConsensusProvider::new(create_test_provider_factory()).unwrap();
+        let service = ApiService::new(Arc::new(HttpJsonRpcSync::default()));
+        let mut engine = ClayerConsensusEngine::new(
+            ClayerConsensusMessagingAgent::new(),
+            service,
+            Arc::new(db),
+            MockEthProvider::default(),
+        );
+        engine.payload_retention = 3;
+
+        for number in 1..=5 {
+            let mut payload =
+                ProposingEl(B256::ZERO).get_payload_v2(PayloadId::new([7; 8])).unwrap();
+            payload.execution_payload.payload_inner.block_number = number;
+            engine.save_committed_payload(&payload).unwrap();
+        }
+
+        for number in 1..=2 {
+            assert!(engine.load_committed_payload(number).unwrap().is_none());
+        }
+        for number in 3..=5 {
+            let payload = engine.load_committed_payload(number).unwrap().unwrap();
+            assert_eq!(payload.execution_payload.payload_inner.block_number, number);
+        }
+    }
+
+    #[test]
+    fn reports_commit_latency_sla_breach() {
+        let mut engine = engine(ClayerConsensusMessagingAgent::new());
+        engine.commit_latency_sla = Some(Duration::from_millis(50));
+        let state = primary_state();
+
+        engine.pre_prepare_received = Some((1, Instant::now()));
+        engine.log_commit_summary(1, 0, B256::with_last_byte(1), None, false, &state);
+        assert_eq!(engine.commit_sla_breaches(), 0);
+
+        // the block takes longer than the SLA to commit
+        engine.pre_prepare_received = Some((2, Instant::now()));
+        std::thread::sleep(Duration::from_millis(60));
+        engine.log_commit_summary(2, 0, B256::with_last_byte(2), None, false, &state);
+        assert_eq!(engine.commit_sla_breaches(), 1);
+
+        // blocks caught up without a PrePrepare have no latency
+        engine.log_commit_summary(3, 0, B256::with_last_byte(3), None, true, &state);
+        assert_eq!(engine.commit_sla_breaches(), 1);
+    }
+
+    #[test]
+    fn records_view_changes() {
+        let db = ConsensusProvider::new(create_test_provider_factory()).unwrap();
+        let service = ApiService::new(Arc::new(HttpJsonRpcSync::default()));
+        let mut engine = ClayerConsensusEngine::new(
+            ClayerConsensusMessagingAgent::new(),
+            service,
+            Arc::new(db),
+            MockEthProvider::default(),
+        );
+        let mut state = primary_state();
+
+        engine.start_view_change(&mut state, 1, ViewChangeReason::StepDown).unwrap();
+        engine.start_view_change(&mut state, 2, ViewChangeReason::ViewChangeTimeout).unwrap();
+        engine.start_view_change(&mut state, 4, ViewChangeReason::PeerViewChanges).unwrap();
+        // already changing to a later view, nothing is recorded
+        engine.start_view_change(&mut state, 3, ViewChangeReason::CommitTimeout).unwrap();
+
+        let (tx, mut reply) = tokio::sync::oneshot::channel();
+        engine.on_command(ConsensusCommand::ViewChanges(2..=4, tx), &mut state);
+        let records = reply.try_recv().unwrap().unwrap();
+        assert_eq!(
+            records.iter().map(|r| (r.new_view, r.reason)).collect::<Vec<_>>(),
+            [(2, ViewChangeReason::ViewChangeTimeout), (4, ViewChangeReason::PeerViewChanges)]
+        );
+        // the node stays on view 0 until a NewView is accepted
+        assert!(records.iter().all(|r| r.old_view == 0 && r.block_number == state.seq_num));
+
+        let all = engine.load_view_changes(0..=u64::MAX).unwrap();
+        assert_eq!(all.iter().map(|r| r.new_view).collect::<Vec<_>>(), [1, 2, 4]);
+        assert_eq!(all[0].reason, ViewChangeReason::StepDown);
+        assert!(engine.load_view_changes(5..=10).unwrap().is_empty());
+    }
+
+    /// Consensus storage keeping committed payloads in memory, saving fails while `failing` is set
+    #[derive(Default)]
+    struct FlakyPayloads {
+        payloads: parking_lot::Mutex<std::collections::BTreeMap<u64, ConsensusBytes>>,
+        failing: std::sync::atomic::AtomicBool,
+    }
+
+    impl ConsensusNumberReader for FlakyPayloads {
+        fn last_consensus_number(&self) -> ProviderResult<u64> {
+            Ok(0)
+        }
+
+        fn consensus_number(&self, _hash: B256) -> ProviderResult<Option<u64>> {
+            Ok(None)
+        }
+
+        fn consensus_content(&self, _hash: B256) -> ProviderResult<Option<ConsensusBytes>> {
+            Ok(None)
+        }
+
+        fn committed_payload(&self, number: u64) -> ProviderResult<Option<ConsensusBytes>> {
+            Ok(self.payloads.lock().get(&number).cloned())
+        }
+
+        fn view_changes(&self, _views: RangeInclusive<u64>) -> ProviderResult<Vec<ConsensusBytes>> {
+            Ok(vec![])
+        }
+
+        fn randao_mix(&self, _number: u64) -> ProviderResult<Option<B256>> {
+            Ok(None)
+        }
+
+        fn first_randao_mix_number(&self) -> ProviderResult<Option<u64>> {
+            Ok(None)
+        }
+    }
+
+    impl ConsensusNumberWriter for FlakyPayloads {
+        fn save_consensus_number(&self, _hash: B256, _num: u64) -> ProviderResult<bool> {
+            Ok(true)
+        }
+
+        fn save_consensus_content(&self, _hash: B256, _ct: ConsensusBytes) -> ProviderResult<bool> {
+            Ok(true)
+        }
+
+        fn save_committed_payload(
+            &self,
+            number: u64,
+            payload: ConsensusBytes,
+        ) -> ProviderResult<bool> {
+            if self.failing.load(std::sync::atomic::Ordering::Relaxed) {
+                return Err(ProviderError::Database(DatabaseError::Open(-1)))
+            }
+            self.payloads.lock().insert(number, payload);
+            Ok(true)
+        }
+
+        fn prune_committed_payloads(&self, _number: u64) -> ProviderResult<usize> {
+            Ok(0)
+        }
+
+        fn save_view_change(&self, _view: u64, _record: ConsensusBytes) -> ProviderResult<bool> {
+            Ok(true)
+        }
+
+        fn save_randao_mix(&self, _number: u64, _mix: B256) -> ProviderResult<bool> {
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn shutdown_saves_pending_payloads() {
+        let db = Arc::new(FlakyPayloads::default());
+        let service = ApiService::new(Arc::new(HttpJsonRpcSync::default()));
+        let mut engine = ClayerConsensusEngine::new(
+            ClayerConsensusMessagingAgent::new(),
+            service,
+            db.clone(),
+            MockEthProvider::default(),
+        );
+
+        // the database is unavailable while two blocks are committed
+        db.failing.store(true, std::sync::atomic::Ordering::Relaxed);
+        for number in 1..=2 {
+            let mut payload =
+                ProposingEl(B256::ZERO).get_payload_v2(PayloadId::new([7; 8])).unwrap();
+            payload.execution_payload.payload_inner.block_number = number;
+            engine.unsaved_payloads.push_back(payload);
+            assert!(engine.flush_committed_payloads().is_err());
+        }
+        assert!(db.payloads.lock().is_empty());
+
+        db.failing.store(false, std::sync::atomic::Ordering::Relaxed);
+        engine.shutdown().unwrap();
+        assert!(engine.unsaved_payloads.is_empty());
+        for number in 1..=2 {
+            let payload = engine.load_committed_payload(number).unwrap().unwrap();
+            assert_eq!(payload.execution_payload.payload_inner.block_number, number);
+        }
+    }
+
+    #[test]
+    fn realigns_diverged_execution_layer() {
+        let genesis = B256::with_last_byte(1);
+        let service = ApiService::new(Arc::new(ProposingEl(genesis)));
+        let mut engine = ClayerConsensusEngine::new(
+            ClayerConsensusMessagingAgent::new(),
+            service,
+            Arc::new(NoSeals),
+            MockEthProvider::default(),
+        );
+        engine.max_head_divergences = 2;
+
+        engine.service.sync_block(genesis).unwrap();
+        engine.reconcile_head().unwrap();
+        assert_eq!(engine.head_divergences, 0);
+
+        // the EL stays at genesis although block 2 was committed
+        engine.service.sync_block(B256::with_last_byte(2)).unwrap();
+        engine.reconcile_head().unwrap();
+        engine.reconcile_head().unwrap();
+        assert_eq!(engine.head_divergences, 2);
+
+        assert!(matches!(engine.reconcile_head(), Err(PbftError::HeadDiverged(_))));
+    }
+
+    #[test]
+    fn paused_primary_does_not_propose() {
+        let secret = SecretKey::new(&mut rand::thread_rng());
+        let id = pk2id(&KeyPair::from_secret_key(SECP256K1, &secret).public_key());
+        let mut members = vec![id];
+        members.extend((0..3).map(|_| PeerId::random()));
+        let config = PbftConfig { members, ..Default::default() };
+        let mut state = PbftState::new(secret, 0, 0, &config).unwrap();
+        assert!(state.is_primary());
+
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut broadcasts = agent.pending_consensus_listener();
+        let service = ApiService::new(Arc::new(ProposingEl(genesis.hash)));
+        let mut engine = ClayerConsensusEngine::new(
+            agent.clone(),
+            service,
+            Arc::new(NoSeals),
+            MockEthProvider::default(),
+        );
+        engine.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+
+        let (tx, mut reply) = tokio::sync::oneshot::channel();
+        engine.on_command(ConsensusCommand::Pause(tx), &mut state);
+        assert!(reply.try_recv().unwrap());
+        assert!(engine.is_paused());
+        // pausing twice is a no-op
+        assert!(!engine.pause(&mut state));
+
+        // the primary does not propose while paused, nor does it time out
+        engine.try_publish(&mut state).unwrap();
+        assert!(broadcasts.try_recv().is_err());
+        assert!(!engine.check_idle_timeout_expired(&mut state));
+
+        let (tx, mut reply) = tokio::sync::oneshot::channel();
+        engine.on_command(ConsensusCommand::Resume(tx), &mut state);
+        assert!(reply.try_recv().unwrap().unwrap());
+        assert!(!engine.is_paused());
+
+        engine.try_publish(&mut state).unwrap();
+        let (_, bytes) = broadcasts.try_recv().unwrap();
+        let msg = ParsedMessage::from_peer_message(parse_consensus_message(&bytes).unwrap(), &[])
+            .unwrap();
+        assert_eq!(msg.info().ptype, PbftMessageType::BlockNew as u8);
+        assert_eq!(msg.info().seq_num, 1);
+    }
+
+    #[test]
+    fn attaches_and_verifies_block_metadata() {
+        let secret = SecretKey::new(&mut rand::thread_rng());
+        let id = pk2id(&KeyPair::from_secret_key(SECP256K1, &secret).public_key());
+        let mut members = vec![id];
+        members.extend((0..3).map(|_| PeerId::random()));
+        let metadata = BlockMetadata { version: "0.1.0".into(), build: "defa64b2".into() };
+        let config =
+            PbftConfig { members, block_metadata: Some(metadata.clone()), ..Default::default() };
+        let mut state = PbftState::new(secret, 0, 0, &config).unwrap();
+
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut broadcasts = agent.pending_consensus_listener();
+        let service = ApiService::new(Arc::new(ProposingEl(genesis.hash)));
+        let mut proposer = ClayerConsensusEngine::new(
+            agent,
+            service,
+            Arc::new(NoSeals),
+            MockEthProvider::default(),
+        );
+        proposer.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+
+        proposer.try_publish(&mut state).unwrap();
+        let (_, bytes) = broadcasts.try_recv().unwrap();
+        let msg = ParsedMessage::from_peer_message(parse_consensus_message(&bytes).unwrap(), &[])
+            .unwrap();
+        let mut block = msg.get_block_new().clone();
+        let signed = SignedBlockMetadata::decode_bytes(block.metadata.as_ref().unwrap()).unwrap();
+        assert_eq!(signed.metadata, metadata);
+        signed.verify(block.block_id(), id).unwrap();
+        // the signature is bound to the block
+        assert!(signed.verify(B256::with_last_byte(2), id).is_err());
+
+        // validators reject the block once its metadata was tampered with
+        let mut tampered = signed;
+        tampered.metadata.version = "9.9.9".into();
+        block.metadata = Some(tampered.to_bytes());
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut validator = engine(agent.clone());
+        validator.msg_log.add_validated_block(clayer_block_from_header(&genesis));
+        let mut validator_state = primary_state();
+        let block_id = block.block_id();
+
+        let err = validator.on_block_new(block, &mut validator_state).unwrap_err();
+        assert!(matches!(err, PbftError::InvalidMessage(_)), "{err:?}");
+        assert!(validator.msg_log.get_unvalidated_block_with_id(&block_id).is_none());
+        assert_eq!(agent.peer_message_counts(id).rejected, 1);
+    }
+
+    #[test]
+    fn withholds_proposal_without_connected_quorum() {
+        let secret = SecretKey::new(&mut rand::thread_rng());
+        let id = pk2id(&KeyPair::from_secret_key(SECP256K1, &secret).public_key());
+        let mut members = vec![id];
+        members.extend((0..3).map(|_| PeerId::random()));
+        let config = PbftConfig {
+            members: members.clone(),
+            require_connected_quorum: true,
+            ..Default::default()
+        };
+        let mut state = PbftState::new(secret, 0, 0, &config).unwrap();
+        assert!(state.is_primary());
+
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut broadcasts = agent.pending_consensus_listener();
+        let service = ApiService::new(Arc::new(ProposingEl(genesis.hash)));
+        let mut engine = ClayerConsensusEngine::new(
+            agent.clone(),
+            service,
+            Arc::new(NoSeals),
+            MockEthProvider::default(),
+        );
+        engine.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+
+        // one of the two required validators and a peer outside the validator set
+        agent.push_network_event(members[1], true);
+        agent.push_network_event(PeerId::random(), true);
+        while broadcasts.try_recv().is_ok() {}
+        engine.try_publish(&mut state).unwrap();
+        assert!(broadcasts.try_recv().is_err());
+
+        agent.push_network_event(members[2], true);
+        while broadcasts.try_recv().is_ok() {}
+        engine.try_publish(&mut state).unwrap();
+        let (_, bytes) = broadcasts.try_recv().unwrap();
+        let msg = ParsedMessage::from_peer_message(parse_consensus_message(&bytes).unwrap(), &[])
+            .unwrap();
+        assert_eq!(msg.info().ptype, PbftMessageType::BlockNew as u8);
+    }
+
+    /// Starts building payloads but fails to return them, so every proposal fails
+    struct RejectingEl(ProposingEl);
+
+    impl ExecutionApi for RejectingEl {
+        fn get_block_by_number(&self, tag: BlockTag) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            self.0.get_block_by_number(tag)
+        }
+
+        fn get_block_by_hash(
+            &self,
+            block_hash: B256,
+        ) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            self.0.get_block_by_hash(block_hash)
+        }
+
+        fn forkchoice_updated_v2(
+            &self,
+            forkchoice_state: ForkchoiceState,
+            payload_attributes: Option<PayloadAttributes>,
+        ) -> Result<ForkchoiceUpdated, ClRpcError> {
+            self.0.forkchoice_updated_v2(forkchoice_state, payload_attributes)
+        }
+
+        fn new_payload_v2(
+            &self,
+            payload: ExecutionPayloadInputV2,
+        ) -> Result<PayloadStatus, ClRpcError> {
+            self.0.new_payload_v2(payload)
+        }
+
+        fn get_payload_v2(
+            &self,
+            _payload_id: PayloadId,
+        ) -> Result<ExecutionPayloadWrapperV2, ClRpcError> {
+            Err(ClRpcError::RequestFailed("failed to build payload".to_string()))
+        }
+
+        fn query_validators(
+            &self,
+            contract_address: String,
+            block_number: u64,
+        ) -> Result<Vec<Vec<u8>>, ClRpcError> {
+            self.0.query_validators(contract_address, block_number)
+        }
+    }
+
+    #[test]
+    fn enters_safe_mode_after_failed_proposals() {
+        let secret = SecretKey::new(&mut rand::thread_rng());
+        let id = pk2id(&KeyPair::from_secret_key(SECP256K1, &secret).public_key());
+        let mut members = vec![id];
+        members.extend((0..3).map(|_| PeerId::random()));
+        let config = PbftConfig {
+            members: members.clone(),
+            safe_mode_failures: 3,
+            safe_mode_duration: Duration::ZERO,
+            ..Default::default()
+        };
+        let mut state = PbftState::new(secret, 0, 0, &config).unwrap();
+        assert!(state.is_primary());
+
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut broadcasts = agent.pending_consensus_listener();
+        let service = ApiService::new(Arc::new(RejectingEl(ProposingEl(genesis.hash))));
+        let mut engine = ClayerConsensusEngine::new(
+            agent.clone(),
+            service,
+            Arc::new(NoSeals),
+            MockEthProvider::default(),
+        );
+        engine.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+
+        for _ in 0..3 {
+            assert!(!engine.is_in_safe_mode());
+            assert!(engine.try_publish(&mut state).is_err());
+        }
+        assert!(engine.is_in_safe_mode());
+        assert!(broadcasts.try_recv().is_err());
+
+        // no more proposals, the node hands the primary role to another node instead
+        engine.try_publish(&mut state).unwrap();
+        engine.try_publish(&mut state).unwrap();
+        let (_, bytes) = broadcasts.try_recv().unwrap();
+        let msg = ParsedMessage::from_peer_message(parse_consensus_message(&bytes).unwrap(), &[])
+            .unwrap();
+        assert_eq!(msg.info().ptype, PbftMessageType::ViewChange as u8);
+        assert!(broadcasts.try_recv().is_err());
+
+        // safe mode without a duration is only left manually
+        let (tx, mut reply) = tokio::sync::oneshot::channel();
+        engine.on_command(ConsensusCommand::ExitSafeMode(tx), &mut state);
+        assert!(reply.try_recv().unwrap());
+        assert!(!engine.is_in_safe_mode());
+        assert!(!engine.exit_safe_mode(&state));
+    }
+
+    #[test]
+    fn idle_view_change_is_not_a_failed_proposal() {
+        let secrets: Vec<_> = (0..4).map(|_| SecretKey::new(&mut rand::thread_rng())).collect();
+        let members: Vec<PeerId> = secrets
+            .iter()
+            .map(|secret| pk2id(&KeyPair::from_secret_key(SECP256K1, secret).public_key()))
+            .collect();
+        let config = PbftConfig { members: members.clone(), ..Default::default() };
+        let mut state = PbftState::new(secrets[0], 1, 0, &config).unwrap();
+        assert!(state.is_primary());
+
+        // the backups did not receive a block before their idle timeouts expired
+        let new_primary = state.get_primary_id_at_view(1);
+        let votes: Vec<ParsedMessage> = (1..4)
+            .filter(|index| members[*index] != new_primary)
+            .map(|index| {
+                let agent = ClayerConsensusMessagingAgent::new();
+                let mut broadcasts = agent.pending_consensus_listener();
+                let mut backup = PbftState::new(secrets[index], 1, 0, &config).unwrap();
+                engine(agent)
+                    .start_view_change(&mut backup, 1, ViewChangeReason::IdleTimeout)
+                    .unwrap();
+                let (_, bytes) = broadcasts.try_recv().unwrap();
+                ParsedMessage::from_peer_message(parse_consensus_message(&bytes).unwrap(), &[])
+                    .unwrap()
+            })
+            .collect();
+        let new_view = PbftNewView {
+            info: PbftMessageInfo {
+                ptype: PbftMessageType::NewView as u8,
+                view: 1,
+                seq_num: 0,
+                signer_id: new_primary,
+            },
+            view_changes:
+                ClayerConsensusEngine::<MockEthProvider, NoSeals>::signed_votes_from_messages(
+                    &votes.iter().collect::<Vec<_>>(),
+                ),
+        };
+
+        // the primary had nothing in flight, so the view change is no failed proposal
+        let mut engine = engine(ClayerConsensusMessagingAgent::new());
+        engine
+            .on_peer_message(
+                new_primary,
+                ParsedMessage::from_new_view_message(new_view),
+                &mut state,
+            )
+            .unwrap();
+        assert_eq!(state.view, 1);
+        assert_eq!(engine.proposal_failures, 0);
+    }
+
+    #[test]
+    fn force_commit_requires_flag() {
+        let secret = SecretKey::new(&mut rand::thread_rng());
+        let id = pk2id(&KeyPair::from_secret_key(SECP256K1, &secret).public_key());
+        let mut members: Vec<PeerId> = (0..4).map(|_| PeerId::random()).collect();
+        members[1] = id;
+        let config = PbftConfig { members: members.clone(), ..Default::default() };
+        let mut state = PbftState::new(secret, 0, 0, &config).unwrap();
+
+        let agent = ClayerConsensusMessagingAgent::new();
+        let service = ApiService::new(Arc::new(AcceptingEl(members.clone())));
+        let mut engine = ClayerConsensusEngine::new(
+            agent.clone(),
+            service,
+            Arc::new(NoSeals),
+            MockEthProvider::default(),
+        );
+
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+        engine.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+        let header = Header { number: 1, parent_hash: genesis.hash, ..Default::default() };
+        let mut block = clayer_block_from_header(&header.seal_slow());
+        block.info.signer_id = members[0];
+        block.payload_id = B64::with_last_byte(1);
+        let block_id = block.block_id();
+
+        // block 1 is validated, but the other validators never vote for it
+        let pre_prepare = peer_vote(PbftMessageType::PrePrepare, members[0], block_id);
+        engine.on_peer_message(members[0], pre_prepare, &mut state).unwrap();
+        engine.on_block_new(block, &mut state).unwrap();
+        assert_eq!(state.phase, PbftPhase::Preparing);
+
+        let (tx, mut reply) = tokio::sync::oneshot::channel();
+        engine.on_command(ConsensusCommand::ForceCommit(block_id, tx), &mut state);
+        assert!(matches!(reply.try_recv().unwrap(), Err(PbftError::InternalError(_))));
+        assert_eq!(state.phase, PbftPhase::Preparing);
+        assert!(agent.pop_event().is_none());
+
+        engine.allow_force_commit = true;
+        // only validated blocks of the current height can be force committed
+        assert!(engine.force_commit(B256::with_last_byte(9), &mut state).is_err());
+
+        let (tx, mut reply) = tokio::sync::oneshot::channel();
+        engine.on_command(ConsensusCommand::ForceCommit(block_id, tx), &mut state);
+        reply.try_recv().unwrap().unwrap();
+        assert_eq!(state.phase, PbftPhase::Finishing(false));
+        let Some(ClayerConsensusEvent::BlockCommit((committed, timestamp, false))) =
+            agent.pop_event()
+        else {
+            panic!("block was not committed")
+        };
+        assert_eq!(committed, block_id);
+
+        engine.on_block_commit(committed, timestamp, false, &mut state).unwrap();
+        assert_eq!(state.seq_num, 2);
+        assert_eq!(state.chain_head, block_id);
+    }
+
+    #[test]
+    fn tracks_gap_to_lagging_execution_layer() {
+        let secret = SecretKey::new(&mut rand::thread_rng());
+        let id = pk2id(&KeyPair::from_secret_key(SECP256K1, &secret).public_key());
+        let mut members: Vec<PeerId> = (0..4).map(|_| PeerId::random()).collect();
+        members[1] = id;
+        let config = PbftConfig { members, ..Default::default() };
+        let mut state = PbftState::new(secret, 0, 0, &config).unwrap();
+
+        // the execution layer is stuck at the genesis block
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+        let service = ApiService::new(Arc::new(ProposingEl(genesis.hash)));
+        let mut engine = ClayerConsensusEngine::new(
+            ClayerConsensusMessagingAgent::new(),
+            service,
+            Arc::new(NoSeals),
+            MockEthProvider::default(),
+        );
+        engine.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+        assert_eq!(engine.el_commit_gap(), None);
+
+        engine.on_block_commit(B256::with_last_byte(1), 1, false, &mut state).unwrap();
+        assert_eq!(engine.el_commit_gap(), Some(1));
+        engine.on_block_commit(B256::with_last_byte(2), 2, false, &mut state).unwrap();
+        assert_eq!(engine.el_commit_gap(), Some(2));
+    }
+
+    #[test]
+    fn records_randao_mix_of_committed_blocks() {
+        let members: Vec<PeerId> = (0..4).map(|_| PeerId::random()).collect();
+        let config = PbftConfig {
+            members: members.clone(),
+            observer: true,
+            randao: true,
+            ..Default::default()
+        };
+        let mut state =
+            PbftState::new(SecretKey::new(&mut rand::thread_rng()), 0, 0, &config).unwrap();
+        let db = Arc::new(ConsensusProvider::new(create_test_provider_factory()).unwrap());
+        let mut engine = ClayerConsensusEngine::new(
+            ClayerConsensusMessagingAgent::new(),
+            ApiService::new(Arc::new(AcceptingEl(members))),
+            db.clone(),
+            MockEthProvider::default(),
+        );
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+        engine.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+
+        // the hash chain of the committed block hashes, starting from the zero hash
+        let mut expected = B256::ZERO;
+        for number in 1..=4u8 {
+            let block_id = B256::with_last_byte(number);
+            engine.on_block_commit(block_id, number as u64, false, &mut state).unwrap();
+            expected = keccak256([expected.as_slice(), block_id.as_slice()].concat());
+            assert_eq!(engine.load_randao_mix(number as u64).unwrap(), Some(expected));
+        }
+        assert_eq!(db.randao_mix(4), Ok(Some(expected)));
+        assert_eq!(engine.load_randao_mix(5).unwrap(), None);
+    }
+
+    #[test]
+    fn backfills_missing_randao_mix() {
+        let client = MockEthProvider::default();
+        for number in 1..=2u8 {
+            let header = Header { number: number as u64, ..Default::default() };
+            client.add_block(B256::with_last_byte(number), Block { header, ..Default::default() });
+        }
+        let db = Arc::new(ConsensusProvider::new(create_test_provider_factory()).unwrap());
+        let engine = ClayerConsensusEngine::new(
+            ClayerConsensusMessagingAgent::new(),
+            ApiService::new(Arc::new(HttpJsonRpcSync::default())),
+            db.clone(),
+            client.clone(),
+        );
+
+        // the chain of mixes starts at the first recorded block
+        engine.save_randao_mix(1, B256::with_last_byte(1)).unwrap();
+        let first = next_randao_mix(B256::ZERO, B256::with_last_byte(1));
+        assert_eq!(db.first_randao_mix_number(), Ok(Some(1)));
+        assert_eq!(db.randao_mix(1), Ok(Some(first)));
+
+        // block 2 was committed without recording its mix
+        engine.save_randao_mix(3, B256::with_last_byte(3)).unwrap();
+        let second = next_randao_mix(first, B256::with_last_byte(2));
+        assert_eq!(db.randao_mix(2), Ok(Some(second)));
+        assert_eq!(db.randao_mix(3), Ok(Some(next_randao_mix(second, B256::with_last_byte(3)))));
+
+        // block 4 is unknown, the mix of block 5 can't be derived from it
+        assert!(engine.save_randao_mix(5, B256::with_last_byte(5)).is_err());
+        assert_eq!(db.randao_mix(4), Ok(None));
+        assert_eq!(db.randao_mix(5), Ok(None));
+    }
 
     #[test]
     fn test_bytes_default() {
@@ -2444,4 +5773,244 @@ mod tests {
         let b = B256::default();
         println!("{} {}", B256::default(), b.is_zero());
     }
+
+    #[test]
+    fn times_prepare_and_commit_phases() {
+        let members: Vec<PeerId> = (0..4).map(|_| PeerId::random()).collect();
+        let config = PbftConfig { members: members.clone(), observer: true, ..Default::default() };
+        let mut state =
+            PbftState::new(SecretKey::new(&mut rand::thread_rng()), 0, 0, &config).unwrap();
+        let agent = ClayerConsensusMessagingAgent::new();
+        let service = ApiService::new(Arc::new(AcceptingEl(members.clone())));
+        let mut engine = ClayerConsensusEngine::new(
+            agent,
+            service,
+            Arc::new(NoSeals),
+            MockEthProvider::default(),
+        );
+
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+        engine.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+        let header = Header { number: 1, parent_hash: genesis.hash, ..Default::default() };
+        let mut block = clayer_block_from_header(&header.seal_slow());
+        block.info.signer_id = members[0];
+        block.payload_id = B64::with_last_byte(1);
+        let block_id = block.block_id();
+
+        let pre_prepare = peer_vote(PbftMessageType::PrePrepare, members[0], block_id);
+        engine.on_peer_message(members[0], pre_prepare, &mut state).unwrap();
+        engine.on_block_new(block, &mut state).unwrap();
+        assert_eq!(engine.phase_samples(), PhaseSampleCounts::default());
+
+        for &signer in &members[1..] {
+            let prepare = peer_vote(PbftMessageType::Prepare, signer, block_id);
+            engine.on_peer_message(signer, prepare, &mut state).unwrap();
+        }
+        assert_eq!(state.phase, PbftPhase::Committing);
+        assert_eq!(engine.phase_samples(), PhaseSampleCounts { prepare: 1, commit: 0 });
+
+        for &signer in &members[..3] {
+            let commit = peer_vote(PbftMessageType::Commit, signer, block_id);
+            engine.on_peer_message(signer, commit, &mut state).unwrap();
+        }
+        assert_eq!(state.phase, PbftPhase::Finishing(false));
+        assert_eq!(engine.phase_samples(), PhaseSampleCounts { prepare: 1, commit: 1 });
+    }
+
+    #[test]
+    fn updates_validators_from_events_at_epoch_boundary() {
+        let members: Vec<PeerId> = (0..4).map(|_| PeerId::random()).collect();
+        let contract = Address::with_last_byte(0x42);
+        let config = PbftConfig {
+            members: members.clone(),
+            observer: true,
+            validator_contract: Some(contract),
+            epoch_length: 2,
+            ..Default::default()
+        };
+        let mut state =
+            PbftState::new(SecretKey::new(&mut rand::thread_rng()), 0, 0, &config).unwrap();
+
+        let joining = PeerId::random();
+        let log = |change: ValidatorSetChange, address: Address| reth_primitives::Log {
+            address,
+            topics: change.topics(),
+            data: Default::default(),
+        };
+        let receipt = |logs| reth_primitives::Receipt { success: true, logs, ..Default::default() };
+        let client = MockEthProvider::default();
+        let mut parent_hash = B256::ZERO;
+        for (number, receipts) in [
+            (1, vec![receipt(vec![log(ValidatorSetChange::Added(joining), contract)])]),
+            (
+                2,
+                vec![
+                    receipt(vec![log(ValidatorSetChange::Removed(members[3]), contract)]),
+                    // events of other contracts are ignored
+                    receipt(vec![log(
+                        ValidatorSetChange::Removed(members[2]),
+                        Address::with_last_byte(1),
+                    )]),
+                ],
+            ),
+        ] {
+            let header = Header { number, parent_hash, ..Default::default() };
+            let hash = header.hash_slow();
+            client.add_block(
+                hash,
+                reth_primitives::Block { header, body: vec![], ommers: vec![], withdrawals: None },
+            );
+            client.add_receipts(hash, receipts);
+            parent_hash = hash;
+        }
+
+        let agent = ClayerConsensusMessagingAgent::new();
+        let service = ApiService::new(Arc::new(AcceptingEl(members.clone())));
+        let mut engine = ClayerConsensusEngine::new(agent, service, Arc::new(NoSeals), client);
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+        engine.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+
+        // the validator set is kept within the epoch
+        engine.update_membership(B256::with_last_byte(1), 1, &mut state);
+        assert_eq!(state.validators.member_ids(), &members);
+
+        // and changes by all events of the epoch at its end
+        engine.update_membership(B256::with_last_byte(2), 2, &mut state);
+        assert_eq!(
+            state.validators.member_ids(),
+            &vec![members[0], members[1], members[2], joining]
+        );
+    }
+
+    #[test]
+    fn proposer_boost_favors_timely_block() {
+        let mut engine = engine(ClayerConsensusMessagingAgent::new());
+        let mut state = primary_state();
+        state.view = 1;
+        let proposal = |view, block_hash| {
+            let mut block = ClayerBlock::default();
+            block.info.view = view;
+            block.info.signer_id = state.get_primary_id_at_view(view);
+            block.block.block_number = 1;
+            block.block.block_hash = block_hash;
+            block
+        };
+        // the primary of the last view proposed late, one validator already voted for its block
+        let late = proposal(0, B256::with_last_byte(0x0a));
+        let timely = proposal(1, B256::with_last_byte(0xb0));
+        engine.msg_log.add_message(peer_vote(
+            PbftMessageType::Prepare,
+            state.validators.index(2),
+            late.block_id(),
+        ));
+
+        let mut blocks = vec![timely.clone(), late.clone()];
+        order_competing_blocks(&mut blocks, |block| engine.block_weight(block, &state));
+        assert_eq!(blocks[0].block_id(), late.block_id());
+
+        engine.proposer_boost = 2;
+        let mut blocks = vec![late.clone(), timely.clone()];
+        order_competing_blocks(&mut blocks, |block| engine.block_weight(block, &state));
+        assert_eq!(blocks[0].block_id(), timely.block_id());
+    }
+
+    #[test]
+    fn warns_about_peer_with_different_engine_capabilities() {
+        let capabilities = |methods: &[&str]| -> BTreeSet<String> {
+            methods.iter().map(|method| method.to_string()).collect()
+        };
+        let (id_a, id_b, id_c) = (PeerId::random(), PeerId::random(), PeerId::random());
+        let (a, b, c) = (
+            ClayerConsensusMessagingAgent::new(),
+            ClayerConsensusMessagingAgent::new(),
+            ClayerConsensusMessagingAgent::new(),
+        );
+        let mut from_a = a.pending_consensus_listener();
+        let mut from_b = b.pending_consensus_listener();
+        let mut from_c = c.pending_consensus_listener();
+
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = reth_tracing::tracing_subscriber::fmt()
+            .with_max_level(Level::WARN)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            // a advertises on connect, b and c once they queried their execution layers
+            a.set_engine_capabilities(capabilities(&[
+                "engine_getClientVersionV1",
+                "engine_newPayloadV2",
+            ]));
+            a.push_network_event(id_b, true);
+            b.push_network_event(id_a, true);
+            b.set_engine_capabilities(capabilities(&[
+                "engine_newPayloadV2",
+                "engine_newPayloadV3",
+            ]));
+            c.push_network_event(id_a, true);
+            c.set_engine_capabilities(capabilities(&["engine_newPayloadV2"]));
+            a.push_network_event(id_c, true);
+
+            while let Ok((peers, frame)) = from_a.try_recv() {
+                if peers == vec![id_b] {
+                    b.push_received_cache(id_a, frame);
+                } else if peers == vec![id_c] {
+                    c.push_received_cache(id_a, frame);
+                }
+            }
+            while let Ok((_, frame)) = from_b.try_recv() {
+                a.push_received_cache(id_b, frame);
+            }
+            while let Ok((_, frame)) = from_c.try_recv() {
+                a.push_received_cache(id_c, frame);
+            }
+        });
+        // capability frames are not consensus messages
+        while let Some(event) = a.pop_event() {
+            assert!(!matches!(event, ClayerConsensusEvent::PeerMessage(..)));
+        }
+
+        let logs = String::from_utf8(logs.0.lock().clone()).unwrap();
+        let warnings: Vec<_> = logs
+            .lines()
+            .filter(|line| line.contains("different engine API payload versions"))
+            .collect();
+        // a and b warn about each other, a and c only differ in a method that is not versioned
+        assert_eq!(warnings.len(), 2, "{logs}");
+        assert!(warnings.iter().any(|line| line.contains(&format!("{id_a:?}"))), "{logs}");
+        assert!(warnings.iter().any(|line| line.contains(&format!("{id_b:?}"))), "{logs}");
+        assert!(warnings.iter().all(|line| line.contains("engine_newPayloadV3")), "{logs}");
+    }
+
+    #[test]
+    fn broadcast_result_counts_dispatched_peers() {
+        let agent = ClayerConsensusMessagingAgent::new()
+            .with_broadcast_result_timeout(Duration::from_millis(50));
+        let mut sent = agent.pending_consensus_listener();
+        let connected = [PeerId::random(), PeerId::random(), PeerId::random()];
+        for peer in connected {
+            agent.push_network_event(peer, true);
+        }
+        // dispatches like the network to the connected peers that are addressed
+        let dispatch = |sent: &mut Receiver<(Vec<PeerId>, reth_primitives::Bytes)>| {
+            while let Ok((peers, _)) = sent.try_recv() {
+                let count = connected
+                    .iter()
+                    .filter(|peer| peers.is_empty() || peers.contains(peer))
+                    .count();
+                agent.on_consensus_dispatched(count);
+            }
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+        let everyone = agent.broadcast_consensus_with_result(vec![], envelope(1));
+        let one = agent.broadcast_consensus_with_result(vec![connected[0]], envelope(1));
+        dispatch(&mut sent);
+        assert_eq!(runtime.block_on(everyone), Some(connected.len()));
+        assert_eq!(runtime.block_on(one), Some(1));
+
+        // the network never picks this one up
+        let lost = agent.broadcast_consensus_with_result(vec![], envelope(1));
+        assert_eq!(runtime.block_on(lost), None);
+    }
 }
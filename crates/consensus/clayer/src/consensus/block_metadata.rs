@@ -0,0 +1,84 @@
+//! Metadata a proposer signs and attaches to its blocks for provenance.
+//!
+//! The execution layer caps the extra data of a block at 32 bytes, which leaves no room for a
+//! signature, so the signed metadata travels with the `BlockNew` message of the proposal.
+
+use super::{ConsensusSigner, PbftError};
+use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use reth_ecies::util::id2pk;
+use reth_eth_wire::ClayerSignature;
+use reth_primitives::{keccak256, public_key_to_address, Bytes, PeerId, B256};
+
+/// What a proposer tells about itself in its blocks
+#[derive(Debug, Clone, PartialEq, Eq, Default, RlpEncodable, RlpDecodable)]
+pub struct BlockMetadata {
+    /// Version of the node software
+    pub version: String,
+    /// Commit the node software was built from
+    pub build: String,
+}
+
+/// [BlockMetadata] signed by the proposer of a block
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct SignedBlockMetadata {
+    /// The metadata of the proposer
+    pub metadata: BlockMetadata,
+    /// Signature of the proposer over the block hash and the metadata
+    pub signature: ClayerSignature,
+}
+
+impl SignedBlockMetadata {
+    /// Signs `metadata` for the block `block_id` with `signer`.
+    pub fn sign(
+        metadata: BlockMetadata,
+        block_id: B256,
+        signer: &dyn ConsensusSigner,
+    ) -> Result<Self, PbftError> {
+        let signature = signer.sign(signature_hash(&metadata, block_id))?;
+        Ok(Self { metadata, signature: ClayerSignature(signature) })
+    }
+
+    /// Decodes the metadata attached to a block.
+    pub fn decode_bytes(bytes: &Bytes) -> Result<Self, PbftError> {
+        Self::decode(&mut bytes.as_ref()).map_err(|err| {
+            PbftError::SerializationError("Error parsing block metadata".into(), err.to_string())
+        })
+    }
+
+    /// Returns the encoded metadata to attach to a block.
+    pub fn to_bytes(&self) -> Bytes {
+        let mut out = vec![];
+        self.encode(&mut out);
+        out.into()
+    }
+
+    /// Checks that the metadata was signed by `signer_id` for the block `block_id`.
+    pub fn verify(&self, block_id: B256, signer_id: PeerId) -> Result<(), PbftError> {
+        let pk = id2pk(signer_id).map_err(|err| {
+            PbftError::SigningError(format!(
+                "Couldn't parse public key from signer ID ({:?}) due to error: {:?}",
+                signer_id, err
+            ))
+        })?;
+        let recovered =
+            self.signature.0.recover_signer(signature_hash(&self.metadata, block_id)).ok_or(
+                PbftError::SigningError("Couldn't recover signer of block metadata".into()),
+            )?;
+        let expected = public_key_to_address(pk);
+        if recovered != expected {
+            return Err(PbftError::SigningError(format!(
+                "Block metadata signature verification failed - Recovered: `{:?}`, Expected: `{:?}`",
+                recovered, expected
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The hash signed for `metadata`, bound to the block so the metadata can't be moved to another
+/// block.
+fn signature_hash(metadata: &BlockMetadata, block_id: B256) -> B256 {
+    let mut out = block_id.to_vec();
+    metadata.encode(&mut out);
+    keccak256(out)
+}
@@ -0,0 +1,128 @@
+//! Commands sent to the consensus engine from outside its thread.
+
+use super::{pbft_error::PbftError, view_changes::ViewChangeRecord};
+use reth_primitives::B256;
+use std::{
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::{mpsc, oneshot};
+
+/// A request to the consensus engine, answered once the engine handled it
+#[derive(Debug)]
+pub enum ConsensusCommand {
+    /// Stop being the primary by starting a view change. Replies whether a view change was
+    /// started, which is only the case if this node is the primary.
+    StepDown(oneshot::Sender<Result<bool, PbftError>>),
+    /// Stop proposing and committing blocks. Replies whether consensus was running before.
+    Pause(oneshot::Sender<bool>),
+    /// Continue after [ConsensusCommand::Pause]. Replies whether consensus was paused before.
+    Resume(oneshot::Sender<Result<bool, PbftError>>),
+    /// Commit the given validated block without quorum. Replies once the block was committed.
+    ForceCommit(B256, oneshot::Sender<Result<(), PbftError>>),
+    /// Resume proposing after repeated failures put the node in safe mode. Replies whether the
+    /// node was in safe mode.
+    ExitSafeMode(oneshot::Sender<bool>),
+    /// Read the recorded view changes to the given views. Replies with the records ordered by
+    /// view.
+    ViewChanges(RangeInclusive<u64>, oneshot::Sender<Result<Vec<ViewChangeRecord>, PbftError>>),
+    /// Read the randao mix after the block at the given number was committed. Replies with `None`
+    /// if no mix was recorded for it.
+    RandaoMix(u64, oneshot::Sender<Result<Option<B256>, PbftError>>),
+}
+
+/// A cloneable handle to send [ConsensusCommand]s to the consensus engine
+#[derive(Debug, Clone)]
+pub struct ConsensusHandle {
+    to_engine: mpsc::UnboundedSender<ConsensusCommand>,
+    /// Whether the engine was paused through this handle or one of its clones
+    paused: Arc<AtomicBool>,
+}
+
+impl ConsensusHandle {
+    /// Creates a new handle and the receiver the consensus engine reads commands from.
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<ConsensusCommand>) {
+        let (to_engine, rx) = mpsc::unbounded_channel();
+        (Self { to_engine, paused: Arc::default() }, rx)
+    }
+
+    /// Asks the engine to step down as primary.
+    ///
+    /// Resolves to `false` if this node is not the primary.
+    pub async fn step_down(&self) -> Result<bool, PbftError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(ConsensusCommand::StepDown(tx))?;
+        rx.await.map_err(|_| Self::engine_stopped())?
+    }
+
+    /// Asks the engine to pause consensus.
+    ///
+    /// Resolves to `false` if consensus is already paused.
+    pub async fn pause(&self) -> Result<bool, PbftError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(ConsensusCommand::Pause(tx))?;
+        let paused = rx.await.map_err(|_| Self::engine_stopped())?;
+        self.paused.store(true, Ordering::Relaxed);
+        Ok(paused)
+    }
+
+    /// Asks the engine to resume consensus.
+    ///
+    /// Resolves to `false` if consensus is not paused.
+    pub async fn resume(&self) -> Result<bool, PbftError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(ConsensusCommand::Resume(tx))?;
+        let resumed = rx.await.map_err(|_| Self::engine_stopped())??;
+        self.paused.store(false, Ordering::Relaxed);
+        Ok(resumed)
+    }
+
+    /// Asks the engine to commit the validated block `block_id` without quorum.
+    pub async fn force_commit(&self, block_id: B256) -> Result<(), PbftError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(ConsensusCommand::ForceCommit(block_id, tx))?;
+        rx.await.map_err(|_| Self::engine_stopped())?
+    }
+
+    /// Asks the engine to leave safe mode and propose blocks again.
+    ///
+    /// Resolves to `false` if the node is not in safe mode.
+    pub async fn exit_safe_mode(&self) -> Result<bool, PbftError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(ConsensusCommand::ExitSafeMode(tx))?;
+        rx.await.map_err(|_| Self::engine_stopped())
+    }
+
+    /// Asks the engine for the view changes it recorded to the views in `views`.
+    pub async fn view_changes(
+        &self,
+        views: RangeInclusive<u64>,
+    ) -> Result<Vec<ViewChangeRecord>, PbftError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(ConsensusCommand::ViewChanges(views, tx))?;
+        rx.await.map_err(|_| Self::engine_stopped())?
+    }
+
+    /// Asks the engine for the randao mix it recorded after committing the block at `number`.
+    pub async fn randao_mix(&self, number: u64) -> Result<Option<B256>, PbftError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(ConsensusCommand::RandaoMix(number, tx))?;
+        rx.await.map_err(|_| Self::engine_stopped())?
+    }
+
+    /// Returns whether consensus is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    fn send(&self, command: ConsensusCommand) -> Result<(), PbftError> {
+        self.to_engine.send(command).map_err(|_| Self::engine_stopped())
+    }
+
+    fn engine_stopped() -> PbftError {
+        PbftError::InternalError("consensus engine is not running".to_string())
+    }
+}
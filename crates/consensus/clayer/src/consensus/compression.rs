@@ -0,0 +1,277 @@
+//! Optional compression of the consensus messages sent between validators.
+//!
+//! Consensus messages are RLP lists, so their first byte is at least `0xc0`. Frames starting with
+//! a smaller byte carry a tag instead:
+//!
+//! + `0x01` followed by a bitmask of the [Compression]s the sender can decode, sent to every peer
+//!   on connect if compression is enabled
+//! + `0x02` followed by a snappy compressed message
+//! + `0x03` followed by a zstd compressed message
+//! + `0x04`, a heartbeat ping, answered with a pong by every node
+//! + `0x05`, a heartbeat pong
+//! + `0x06` followed by the RLP list of the engine API methods the execution layer of the sender
+//!   supports, sent to every peer on connect once the execution layer was queried
+//! + `0x07` followed by the protocol version and the big-endian `u64` topic of the sender, sent to
+//!   every peer on connect to complete the handshake
+//!
+//! A node only compresses the messages to peers that advertised the configured [Compression], all
+//! other peers receive plain messages.
+
+use alloy_rlp::{Decodable, Encodable};
+use reth_primitives::Bytes;
+use std::{collections::BTreeSet, fmt, str::FromStr};
+
+/// Tag of a frame advertising the supported compressions
+const CAPABILITIES_TAG: u8 = 0x01;
+/// Tag of a snappy compressed message
+const SNAPPY_TAG: u8 = 0x02;
+/// Tag of a zstd compressed message
+const ZSTD_TAG: u8 = 0x03;
+/// Tag of a heartbeat ping
+const PING_TAG: u8 = 0x04;
+/// Tag of a heartbeat pong
+const PONG_TAG: u8 = 0x05;
+/// Tag of a frame advertising the engine API methods of the execution layer
+const ENGINE_CAPABILITIES_TAG: u8 = 0x06;
+/// Tag of a frame introducing the sender
+const HELLO_TAG: u8 = 0x07;
+
+/// Largest size a compressed message may decompress to, the limit of an `eth` message
+const MAX_DECOMPRESSED_SIZE: usize = 10 * 1024 * 1024;
+
+/// Compression algorithm for the consensus messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Snappy, fast with a moderate ratio
+    Snappy,
+    /// Zstandard, a better ratio at a higher cost
+    Zstd,
+}
+
+impl Compression {
+    /// All compressions, every node can decode them
+    pub const ALL: [Compression; 2] = [Compression::Snappy, Compression::Zstd];
+
+    fn bit(self) -> u8 {
+        match self {
+            Compression::Snappy => 1 << 0,
+            Compression::Zstd => 1 << 1,
+        }
+    }
+
+    fn tag(self) -> u8 {
+        match self {
+            Compression::Snappy => SNAPPY_TAG,
+            Compression::Zstd => ZSTD_TAG,
+        }
+    }
+
+    /// Returns the frame advertising the compressions this node can decode.
+    pub fn capabilities_frame() -> Bytes {
+        let supported = Self::ALL.iter().fold(0, |mask, compression| mask | compression.bit());
+        Bytes::from(vec![CAPABILITIES_TAG, supported])
+    }
+
+    /// Returns whether the advertised `supported` bitmask includes this compression.
+    pub fn is_supported_by(self, supported: u8) -> bool {
+        supported & self.bit() != 0
+    }
+
+    /// Compresses the encoded consensus `message` into a frame.
+    ///
+    /// Returns the plain message if compressing it does not make it smaller.
+    pub fn encode(self, message: &Bytes) -> Bytes {
+        let compressed = match self {
+            Compression::Snappy => snap::raw::Encoder::new().compress_vec(message).ok(),
+            Compression::Zstd => zstd::bulk::compress(message, 0).ok(),
+        };
+        match compressed {
+            Some(compressed) if compressed.len() + 1 < message.len() => {
+                let mut frame = Vec::with_capacity(compressed.len() + 1);
+                frame.push(self.tag());
+                frame.extend_from_slice(&compressed);
+                frame.into()
+            }
+            _ => message.clone(),
+        }
+    }
+
+    fn decompress(self, data: &[u8]) -> Result<Vec<u8>, FrameError> {
+        match self {
+            Compression::Snappy => {
+                let len = snap::raw::decompress_len(data)
+                    .map_err(|err| FrameError::Decompress(err.to_string()))?;
+                if len > MAX_DECOMPRESSED_SIZE {
+                    return Err(FrameError::TooLarge(len))
+                }
+                snap::raw::Decoder::new()
+                    .decompress_vec(data)
+                    .map_err(|err| FrameError::Decompress(err.to_string()))
+            }
+            Compression::Zstd => zstd::bulk::decompress(data, MAX_DECOMPRESSED_SIZE)
+                .map_err(|err| FrameError::Decompress(err.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::Snappy => f.write_str("snappy"),
+            Compression::Zstd => f.write_str("zstd"),
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "snappy" => Ok(Compression::Snappy),
+            "zstd" => Ok(Compression::Zstd),
+            _ => Err(format!("unknown compression `{s}`, expected `snappy` or `zstd`")),
+        }
+    }
+}
+
+/// A frame received from a peer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Frame {
+    /// The bitmask of the compressions the peer can decode
+    Capabilities(u8),
+    /// An encoded consensus message, decompressed if it was compressed
+    Message(Bytes),
+    /// A heartbeat ping, to be answered with [pong_frame]
+    Ping,
+    /// A heartbeat pong
+    Pong,
+    /// The engine API methods the execution layer of the peer supports
+    EngineCapabilities(BTreeSet<String>),
+    /// The protocol version and the topic of the peer
+    Hello { version: u8, topic: u64 },
+}
+
+/// Returns the frame pinging a peer.
+pub(crate) fn ping_frame() -> Bytes {
+    Bytes::from(vec![PING_TAG])
+}
+
+/// Returns the frame answering a ping.
+pub(crate) fn pong_frame() -> Bytes {
+    Bytes::from(vec![PONG_TAG])
+}
+
+/// Returns the frame advertising the engine API methods of the local execution layer.
+pub(crate) fn engine_capabilities_frame(capabilities: &BTreeSet<String>) -> Bytes {
+    let mut out = vec![ENGINE_CAPABILITIES_TAG];
+    capabilities.iter().cloned().collect::<Vec<String>>().encode(&mut out);
+    out.into()
+}
+
+/// Returns the frame introducing this node with its protocol `version` and `topic`.
+pub(crate) fn hello_frame(version: u8, topic: u64) -> Bytes {
+    let mut out = vec![HELLO_TAG, version];
+    out.extend_from_slice(&topic.to_be_bytes());
+    out.into()
+}
+
+/// Errors of a frame that can't be read
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum FrameError {
+    /// The tag is not known
+    #[error("unknown frame tag {0:#04x}")]
+    UnknownTag(u8),
+    /// The capabilities frame has no bitmask
+    #[error("capabilities frame without capabilities")]
+    MissingCapabilities,
+    /// The engine capabilities frame is not an RLP list of strings
+    #[error("invalid engine capabilities: {0}")]
+    EngineCapabilities(alloy_rlp::Error),
+    /// The hello frame has no protocol version and topic
+    #[error("hello frame without protocol version and topic")]
+    InvalidHello,
+    /// The compressed message is invalid
+    #[error("failed to decompress message: {0}")]
+    Decompress(String),
+    /// The compressed message is larger than [MAX_DECOMPRESSED_SIZE]
+    #[error("compressed message of {0} bytes exceeds the size limit")]
+    TooLarge(usize),
+}
+
+/// Reads a frame received from a peer.
+pub(crate) fn decode_frame(data: &Bytes) -> Result<Frame, FrameError> {
+    let Some((&tag, rest)) = data.split_first() else { return Ok(Frame::Message(data.clone())) };
+    match tag {
+        CAPABILITIES_TAG => rest
+            .first()
+            .map(|supported| Frame::Capabilities(*supported))
+            .ok_or(FrameError::MissingCapabilities),
+        SNAPPY_TAG => Ok(Frame::Message(Compression::Snappy.decompress(rest)?.into())),
+        ZSTD_TAG => Ok(Frame::Message(Compression::Zstd.decompress(rest)?.into())),
+        PING_TAG => Ok(Frame::Ping),
+        PONG_TAG => Ok(Frame::Pong),
+        ENGINE_CAPABILITIES_TAG => Vec::<String>::decode(&mut &rest[..])
+            .map(|methods| Frame::EngineCapabilities(methods.into_iter().collect()))
+            .map_err(FrameError::EngineCapabilities),
+        HELLO_TAG => {
+            let (&version, topic) = rest.split_first().ok_or(FrameError::InvalidHello)?;
+            let topic = topic.try_into().map_err(|_| FrameError::InvalidHello)?;
+            Ok(Frame::Hello { version, topic: u64::from_be_bytes(topic) })
+        }
+        // RLP lists start at 0xc0
+        tag if tag >= 0xc0 => Ok(Frame::Message(data.clone())),
+        tag => Err(FrameError::UnknownTag(tag)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_eth_wire::ClayerConsensusMessage;
+
+    #[test]
+    fn round_trips_large_message() {
+        let message = ClayerConsensusMessage {
+            header_bytes: Bytes::from(vec![1; 128]),
+            message_bytes: Bytes::from(
+                (0..256 * 1024).map(|i| (i % 251) as u8).collect::<Vec<_>>(),
+            ),
+            ttl: 3,
+            topic: 1,
+            ..Default::default()
+        };
+        let mut out = vec![];
+        message.encode(&mut out);
+        let encoded = Bytes::from(out);
+
+        for compression in Compression::ALL {
+            let frame = compression.encode(&encoded);
+            assert_eq!(frame[0], compression.tag());
+            assert!(frame.len() < encoded.len(), "{compression} did not compress");
+
+            let Frame::Message(decoded) = decode_frame(&frame).unwrap() else {
+                panic!("expected a message frame")
+            };
+            assert_eq!(decoded, encoded);
+            assert_eq!(ClayerConsensusMessage::decode(&mut decoded.as_ref()).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn keeps_incompressible_message_plain() {
+        let message = ClayerConsensusMessage { ttl: 1, ..Default::default() };
+        let mut out = vec![];
+        message.encode(&mut out);
+        let encoded = Bytes::from(out);
+
+        assert_eq!(Compression::Zstd.encode(&encoded), encoded);
+        assert_eq!(decode_frame(&encoded).unwrap(), Frame::Message(encoded));
+        let Frame::Capabilities(supported) =
+            decode_frame(&Compression::capabilities_frame()).unwrap()
+        else {
+            panic!("expected a capabilities frame")
+        };
+        assert!(Compression::ALL.iter().all(|compression| compression.is_supported_by(supported)));
+    }
+}
@@ -1,4 +1,10 @@
+use super::BlockMetadata;
+use crate::{
+    backpressure::{DEFAULT_MAX_PIPELINE_LAG, DEFAULT_MAX_QUEUED_TICKS},
+    engine_api::forkchoice_history::DEFAULT_FORKCHOICE_HISTORY_SIZE,
+};
 use config::{Config, File};
+use reth_primitives::Address;
 use reth_rpc_types::PeerId;
 use serde::Deserialize;
 use std::{path::PathBuf, str::FromStr, time::Duration};
@@ -14,6 +20,10 @@ pub struct PbftConfig {
     /// How long to wait in between trying to publish blocks
     pub block_publishing_delay: Duration,
 
+    /// Random shift of every attempt to publish a block, in percent of `block_publishing_delay`,
+    /// so validators don't all try at the same time
+    pub block_publishing_jitter: u8,
+
     /// How long to wait for an update to arrive from the validator
     pub update_recv_timeout: Duration,
 
@@ -36,11 +46,157 @@ pub struct PbftConfig {
     /// different view change
     pub view_change_duration: Duration,
 
-    /// How many blocks to commit before forcing a view change for fairness
+    /// How many blocks to commit before forcing a view change for fairness, which rotates the
+    /// primary round-robin. The rotation happens at the same block numbers regardless of view
+    /// changes caused by faults. 0 disables the rotation, the primary then only changes on faults
     pub forced_view_change_interval: u64,
 
     /// How large the PbftLog is allowed to get before being pruned
     pub max_log_size: u64,
+
+    /// Number of hops a consensus message may travel. Peers relay received messages until the
+    /// TTL is exhausted, a TTL of 1 only delivers messages to directly connected peers
+    pub message_ttl: u8,
+
+    /// How many committed blocks the pipeline may lag behind before the primary slows down its
+    /// proposals, 0 disables the backpressure
+    pub max_pipeline_lag: u64,
+
+    /// How many publish ticks may be queued before the oldest are dropped as stale
+    pub max_queued_ticks: usize,
+
+    /// Maximum number of faulty nodes to tolerate, derived from the number of members as
+    /// `(n - 1) / 3` if unset
+    pub faults: Option<u64>,
+
+    /// How long Commit messages are batched before they are processed, a batch completing a
+    /// quorum is processed immediately
+    pub commit_aggregation_window: Duration,
+
+    /// Whether the primary skips proposing blocks without transactions. Backups still start a
+    /// view change once their idle timeout expires, which bounds the time without blocks
+    pub cancel_empty_payloads: bool,
+
+    /// Follow consensus and commit blocks without ever signing a message or proposing a block.
+    /// Faults detected by an observer are only logged
+    pub observer: bool,
+
+    /// How far the timestamp of a proposed block may be ahead of the local clock before the
+    /// block is rejected, zero disables the check
+    pub max_timestamp_skew: Duration,
+
+    /// Whether startup is aborted if the execution layer fails the startup self-test, otherwise
+    /// the failure is only logged
+    pub abort_on_failed_self_test: bool,
+
+    /// Number of most recent blocks whose committed execution payloads are kept in the database,
+    /// zero disables storing them
+    pub payload_retention: u64,
+
+    /// How often a call to the execution layer is retried while the block is not ready, e.g.
+    /// because the payload is still being built. Retries back off exponentially starting at
+    /// `exponential_retry_base`
+    pub not_ready_retries: u32,
+
+    /// How often the head of the execution layer is compared with the last committed block,
+    /// zero disables the check
+    pub head_check_interval: Duration,
+
+    /// Number of consecutive head checks that may find the execution layer diverged before the
+    /// consensus engine stops and shuts down the node. Every divergent check realigns the
+    /// execution layer
+    pub max_head_divergences: u32,
+
+    /// Fetch the head of the execution layer after every commit and log an error if it is not the
+    /// committed block, a debug check for commit bugs
+    pub check_head_after_commit: bool,
+
+    /// Only consider a block committed once the execution layer reports it as its finalized
+    /// block, so the finalized head of consensus never gets ahead of the execution layer
+    pub require_el_finalized: bool,
+
+    /// Age after which the primary requests a new payload id before fetching its payload, since
+    /// the execution layer discards payloads that are not fetched in time. Zero always uses the
+    /// payload id of the last forkchoice update
+    pub payload_id_max_age: Duration,
+
+    /// Only propose blocks while enough validators are connected to reach quorum, i.e. at least
+    /// `2f` other validators
+    pub require_connected_quorum: bool,
+
+    /// Allow committing a validated block without quorum through `clayer_forceCommit`, to
+    /// recover a network that is stuck
+    pub allow_force_commit: bool,
+
+    /// State file the capabilities and client version of the execution layer are recorded in on
+    /// startup, a change since the last run is logged as a warning
+    pub el_identity_path: Option<PathBuf>,
+
+    /// Contract whose [ValidatorSetChange](super::ValidatorSetChange) events change the validator
+    /// set at the end of every epoch. If unset the validator set is queried from the election
+    /// contract after every block
+    pub validator_contract: Option<Address>,
+
+    /// Number of blocks of an epoch, the epochs end at the multiples of it
+    pub epoch_length: u64,
+
+    /// Number of consecutive proposals of this node that may fail before it enters safe mode, in
+    /// which it stops proposing and only follows the network. Zero disables safe mode
+    pub safe_mode_failures: u32,
+
+    /// How long safe mode lasts before proposing is resumed automatically, zero only leaves safe
+    /// mode through `clayer_exitSafeMode`
+    pub safe_mode_duration: Duration,
+
+    /// Commit latency from the PrePrepare of a block to its commit above which a warning is
+    /// logged and the breach is counted, no SLA if unset
+    pub commit_latency_sla: Option<Duration>,
+
+    /// Maximum number of blocks of the execution layer a commit may unwind, deeper reorgs are
+    /// refused. Unbounded if unset
+    pub max_reorg_depth: Option<u64>,
+
+    /// How long the consensus engine may take to save its state on shutdown before it is
+    /// abandoned, so a hanging flush can not keep the process from exiting
+    pub shutdown_timeout: Duration,
+
+    /// Weight, in prepare votes, added to the block of the primary of the current view when it
+    /// competes with blocks proposed in earlier views, 0 disables the boost
+    pub proposer_boost: u64,
+
+    /// Maximum size in bytes of the encoded payload of a proposed block, larger payloads are not
+    /// proposed. Unbounded if unset
+    pub max_block_bytes: Option<usize>,
+
+    /// Record a randao mix for every committed block, the hash of the mix of its parent and its
+    /// block hash, as a source of verifiable randomness
+    pub randao: bool,
+
+    /// Stop proposing once a message signed by the key of this node is received that it did not
+    /// send, i.e. another node runs with the same validator key. The alert is raised either way
+    pub halt_on_duplicate_identity: bool,
+
+    /// Maximum time a view may last before a view change rotates the primary, even if blocks are
+    /// committed in it. Views last until the primary fails if unset
+    pub max_view_lifetime: Option<Duration>,
+
+    /// Reject proposed blocks whose gas limit moves by 1/1024 or more of the gas limit of their
+    /// parent, or is below the minimum gas limit
+    pub check_gas_limit: bool,
+
+    /// The block the London hardfork activated at, the gas limit of its parent is doubled by the
+    /// EIP-1559 elasticity multiplier. Set from the chain spec when consensus starts, `None` for a
+    /// chain without EIP-1559
+    pub london_block: Option<u64>,
+
+    /// Metadata, e.g. the version of the node, signed and attached to the blocks this node
+    /// proposes. Validators verify the metadata of every block that carries one. Nodes that don't
+    /// know block metadata can't decode the proposals carrying it
+    pub block_metadata: Option<BlockMetadata>,
+
+    /// Number of the most recent forkchoice updates sent to the execution layer that are kept for
+    /// debugging, 0 keeps none
+    pub forkchoice_history_size: usize,
 }
 
 impl Default for PbftConfig {
@@ -49,6 +205,7 @@ impl Default for PbftConfig {
             members: Vec::new(),
             block_publishing_min_interval: Duration::from_millis(5000),
             block_publishing_delay: Duration::from_millis(1000),
+            block_publishing_jitter: 0,
             update_recv_timeout: Duration::from_millis(10),
             exponential_retry_base: Duration::from_millis(100),
             exponential_retry_max: Duration::from_millis(60000),
@@ -57,6 +214,41 @@ impl Default for PbftConfig {
             view_change_duration: Duration::from_millis(5000),
             forced_view_change_interval: 20,
             max_log_size: 10000,
+            message_ttl: 1,
+            max_pipeline_lag: DEFAULT_MAX_PIPELINE_LAG,
+            max_queued_ticks: DEFAULT_MAX_QUEUED_TICKS,
+            faults: None,
+            commit_aggregation_window: Duration::from_millis(50),
+            cancel_empty_payloads: false,
+            observer: false,
+            max_timestamp_skew: Duration::from_secs(15),
+            abort_on_failed_self_test: true,
+            payload_retention: 10_000,
+            not_ready_retries: 3,
+            head_check_interval: Duration::from_secs(30),
+            max_head_divergences: 3,
+            check_head_after_commit: false,
+            require_el_finalized: false,
+            payload_id_max_age: Duration::from_secs(12),
+            require_connected_quorum: false,
+            allow_force_commit: false,
+            el_identity_path: None,
+            validator_contract: None,
+            epoch_length: 100,
+            safe_mode_failures: 0,
+            safe_mode_duration: Duration::from_secs(600),
+            commit_latency_sla: None,
+            max_reorg_depth: None,
+            shutdown_timeout: Duration::from_secs(10),
+            proposer_boost: 0,
+            max_block_bytes: None,
+            randao: false,
+            halt_on_duplicate_identity: false,
+            max_view_lifetime: None,
+            check_gas_limit: false,
+            london_block: None,
+            block_metadata: None,
+            forkchoice_history_size: DEFAULT_FORKCHOICE_HISTORY_SIZE,
         }
     }
 }
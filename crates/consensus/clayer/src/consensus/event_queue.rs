@@ -0,0 +1,125 @@
+//! Queue of the events waiting to be handled by the consensus engine.
+
+use alloy_rlp::Decodable;
+use reth_eth_wire::{ClayerConsensusMessage, ClayerConsensusMessageHeader, PbftMessageType};
+use reth_interfaces::clayer::ClayerConsensusEvent;
+use std::collections::VecDeque;
+
+/// Events waiting for the consensus engine, prioritized by how urgent they are.
+///
+/// Under load a backlog of prepares and pre-prepares would delay the commit messages that
+/// complete a block, so commit messages are dequeued ahead of all other peer messages. Events of
+/// the same priority keep their order.
+#[derive(Debug, Default)]
+pub(crate) struct EventQueue {
+    /// Block events of the execution layer, handled before any message
+    block_events: VecDeque<ClayerConsensusEvent>,
+    /// Commit messages
+    commits: VecDeque<ClayerConsensusEvent>,
+    /// All other messages and network events
+    others: VecDeque<ClayerConsensusEvent>,
+}
+
+impl EventQueue {
+    /// Queues a peer message or network event behind the events of the same priority.
+    pub(crate) fn push_back(&mut self, event: ClayerConsensusEvent) {
+        match &event {
+            ClayerConsensusEvent::PeerMessage(_, data) if is_commit(data) => {
+                self.commits.push_back(event)
+            }
+            _ => self.others.push_back(event),
+        }
+    }
+
+    /// Queues a block event ahead of all other events.
+    pub(crate) fn push_block_event(&mut self, event: ClayerConsensusEvent) {
+        self.block_events.push_front(event);
+    }
+
+    /// Returns the most urgent event.
+    pub(crate) fn pop_front(&mut self) -> Option<ClayerConsensusEvent> {
+        self.block_events
+            .pop_front()
+            .or_else(|| self.commits.pop_front())
+            .or_else(|| self.others.pop_front())
+    }
+}
+
+/// Returns `true` if `data` is an encoded commit message, unreadable messages are not.
+fn is_commit(data: &[u8]) -> bool {
+    let Ok(message) = ClayerConsensusMessage::decode(&mut &data[..]) else { return false };
+    ClayerConsensusMessageHeader::decode(&mut message.header_bytes.as_ref())
+        .is_ok_and(|header| header.message_type == PbftMessageType::Commit as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_rlp::Encodable;
+    use reth_primitives::{Bytes, B256};
+    use reth_rpc_types::PeerId;
+
+    fn message(message_type: PbftMessageType, seq_num: u8) -> ClayerConsensusEvent {
+        let header = ClayerConsensusMessageHeader {
+            message_type: message_type as u8,
+            content_hash: B256::with_last_byte(seq_num),
+            signer_id: PeerId::random(),
+        };
+        let mut header_bytes = vec![];
+        header.encode(&mut header_bytes);
+        let message =
+            ClayerConsensusMessage { header_bytes: header_bytes.into(), ..Default::default() };
+        let mut out = vec![];
+        message.encode(&mut out);
+        ClayerConsensusEvent::PeerMessage(header.signer_id, Bytes::from(out))
+    }
+
+    fn message_type(event: &ClayerConsensusEvent) -> PbftMessageType {
+        let ClayerConsensusEvent::PeerMessage(_, data) = event else {
+            panic!("expected a message")
+        };
+        let message = ClayerConsensusMessage::decode(&mut data.as_ref()).unwrap();
+        let header = ClayerConsensusMessageHeader::decode(&mut message.header_bytes.as_ref());
+        PbftMessageType::from(header.unwrap().message_type)
+    }
+
+    #[test]
+    fn dequeues_commits_first() {
+        let mut queue = EventQueue::default();
+        let mix = [
+            message(PbftMessageType::PrePrepare, 1),
+            message(PbftMessageType::Prepare, 1),
+            message(PbftMessageType::Prepare, 1),
+            message(PbftMessageType::Commit, 1),
+            message(PbftMessageType::PrePrepare, 2),
+            message(PbftMessageType::Prepare, 2),
+            message(PbftMessageType::Commit, 1),
+        ];
+        for event in mix {
+            queue.push_back(event);
+        }
+        queue.push_back(ClayerConsensusEvent::PeerNetWork(PeerId::random(), true));
+        queue.push_back(ClayerConsensusEvent::PeerMessage(PeerId::random(), Bytes::from(vec![1])));
+        queue.push_block_event(ClayerConsensusEvent::BlockValid(B256::with_last_byte(1)));
+
+        assert!(matches!(queue.pop_front(), Some(ClayerConsensusEvent::BlockValid(_))));
+        let types: Vec<_> =
+            std::iter::from_fn(|| queue.pop_front()).take(7).map(|e| message_type(&e)).collect();
+        assert_eq!(
+            types,
+            [
+                PbftMessageType::Commit,
+                PbftMessageType::Commit,
+                PbftMessageType::PrePrepare,
+                PbftMessageType::Prepare,
+                PbftMessageType::Prepare,
+                PbftMessageType::PrePrepare,
+                PbftMessageType::Prepare,
+            ]
+        );
+        // other events keep their order behind the messages queued before them
+        assert!(matches!(queue.pop_front(), Some(ClayerConsensusEvent::PeerNetWork(_, true))));
+        assert!(matches!(queue.pop_front(), Some(ClayerConsensusEvent::PeerMessage(_, _))));
+        assert!(queue.pop_front().is_none());
+    }
+}
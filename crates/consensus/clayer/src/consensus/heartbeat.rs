@@ -0,0 +1,77 @@
+//! Heartbeat detecting consensus peers that stopped responding while still connected.
+
+use reth_rpc_types::PeerId;
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+/// Pings the connected consensus peers and marks the peers dead that were not heard from within
+/// the timeout.
+///
+/// Every frame received from a peer counts as a sign of life, pings only keep otherwise idle
+/// peers from being marked dead. A dead peer that is heard from again is alive again.
+#[derive(Debug)]
+pub(crate) struct Heartbeat {
+    interval: Duration,
+    timeout: Duration,
+    /// When the connected peers were last pinged
+    last_ping: Option<Instant>,
+    /// The live peers with the last time they were heard from
+    last_seen: HashMap<PeerId, Instant>,
+    /// Connected peers that were marked dead
+    dead: HashSet<PeerId>,
+}
+
+impl Heartbeat {
+    /// Creates a heartbeat pinging every `interval` that marks peers dead after `timeout`.
+    pub(crate) fn new(interval: Duration, timeout: Duration) -> Self {
+        Self { interval, timeout, last_ping: None, last_seen: HashMap::new(), dead: HashSet::new() }
+    }
+
+    /// Starts tracking a newly connected peer.
+    pub(crate) fn on_connected(&mut self, peer_id: PeerId, now: Instant) {
+        self.dead.remove(&peer_id);
+        self.last_seen.insert(peer_id, now);
+    }
+
+    /// Stops tracking a disconnected peer.
+    pub(crate) fn on_disconnected(&mut self, peer_id: &PeerId) {
+        self.dead.remove(peer_id);
+        self.last_seen.remove(peer_id);
+    }
+
+    /// Records that `peer_id` was heard from. Returns `true` if the peer was dead.
+    pub(crate) fn on_seen(&mut self, peer_id: PeerId, now: Instant) -> bool {
+        let revived = self.dead.remove(&peer_id);
+        if revived || self.last_seen.contains_key(&peer_id) {
+            self.last_seen.insert(peer_id, now);
+        }
+        revived
+    }
+
+    /// Returns whether the peers are due to be pinged, and records the ping if they are.
+    pub(crate) fn ping_due(&mut self, now: Instant) -> bool {
+        if self.last_ping.is_some_and(|last_ping| now.duration_since(last_ping) < self.interval) {
+            return false
+        }
+        self.last_ping = Some(now);
+        true
+    }
+
+    /// Marks the peers dead that were not heard from within the timeout and returns them.
+    pub(crate) fn expire(&mut self, now: Instant) -> Vec<PeerId> {
+        let timeout = self.timeout;
+        let expired: Vec<PeerId> = self
+            .last_seen
+            .iter()
+            .filter(|(_, seen)| now.duration_since(**seen) > timeout)
+            .map(|(peer_id, _)| *peer_id)
+            .collect();
+        for peer_id in &expired {
+            self.last_seen.remove(peer_id);
+            self.dead.insert(*peer_id);
+        }
+        expired
+    }
+}
@@ -0,0 +1,276 @@
+//! Audit log of the consensus messages a node sends and receives.
+
+use super::message::ParsedMessage;
+use crate::engine_pbft::parse_consensus_message;
+use reth_eth_wire::PbftMessageType;
+use reth_rpc_types::PeerId;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use tracing::*;
+
+/// Number of rotated files kept next to the active log file.
+const MAX_ROTATED_FILES: usize = 5;
+
+/// Whether a consensus message was received from or sent to the network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageDirection {
+    /// Received from a peer
+    Inbound,
+    /// Broadcast to peers
+    Outbound,
+}
+
+/// A single line of the consensus message log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageLogRecord {
+    /// Unix timestamp in milliseconds
+    pub timestamp: i64,
+    /// Whether the message was received or sent
+    pub direction: MessageDirection,
+    /// The peer the message was received from, unset for outbound messages
+    pub peer: Option<PeerId>,
+    /// The node that signed the message
+    pub signer: PeerId,
+    /// The PBFT message type
+    #[serde(rename = "type")]
+    pub message_type: String,
+    /// The PBFT view of the message
+    pub view: u64,
+    /// The PBFT sequence number of the message
+    pub seq_num: u64,
+    /// The encoded message, missing in logs written before messages were recorded for replay
+    #[serde(default)]
+    pub message: reth_primitives::Bytes,
+}
+
+impl MessageLogRecord {
+    /// Builds a record from an encoded
+    /// [ClayerConsensusMessage](reth_eth_wire::ClayerConsensusMessage).
+    fn new(
+        direction: MessageDirection,
+        peer: Option<PeerId>,
+        bytes: &reth_primitives::Bytes,
+    ) -> Option<Self> {
+        let message = parse_consensus_message(bytes).ok()?;
+        let parsed = ParsedMessage::from_peer_message(message, &[]).ok()?;
+        let info = parsed.info();
+        Some(Self {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            direction,
+            peer,
+            signer: info.signer_id,
+            message_type: PbftMessageType::from(info.ptype).to_string(),
+            view: info.view,
+            seq_num: info.seq_num,
+            message: bytes.clone(),
+        })
+    }
+}
+
+/// Reads the records of a message log file, e.g. to replay them with
+/// [MessageReplayer](super::MessageReplayer).
+pub fn read_message_log(path: &Path) -> io::Result<Vec<MessageLogRecord>> {
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_str(line).map_err(io::Error::from))
+        .collect()
+}
+
+/// Appends every consensus message as a JSON line to a file, rotating it once it exceeds
+/// `max_size` bytes.
+///
+/// Rotated files are suffixed with `.1` (most recent) up to `.5`, older files are removed.
+#[derive(Debug)]
+pub struct ConsensusMessageLog {
+    path: PathBuf,
+    max_size: u64,
+    file: BufWriter<File>,
+    size: u64,
+}
+
+impl ConsensusMessageLog {
+    /// Opens the log file at `path`, creating it and its parent directories if needed.
+    pub fn open(path: impl Into<PathBuf>, max_size: u64) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, max_size, file: BufWriter::new(file), size })
+    }
+
+    /// Returns the path of the active log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records a message received from `peer`.
+    pub fn record_inbound(&mut self, peer: PeerId, bytes: &reth_primitives::Bytes) {
+        self.record(MessageDirection::Inbound, Some(peer), bytes)
+    }
+
+    /// Records a message broadcast by this node.
+    pub fn record_outbound(&mut self, bytes: &reth_primitives::Bytes) {
+        self.record(MessageDirection::Outbound, None, bytes)
+    }
+
+    fn record(
+        &mut self,
+        direction: MessageDirection,
+        peer: Option<PeerId>,
+        bytes: &reth_primitives::Bytes,
+    ) {
+        let Some(record) = MessageLogRecord::new(direction, peer, bytes) else {
+            debug!(target: "consensus::cl", ?direction, ?peer, "Skipping undecodable consensus message in message log");
+            return
+        };
+        if let Err(err) = self.write(&record) {
+            warn!(target: "consensus::cl", %err, path = ?self.path, "Failed to write consensus message log");
+        }
+    }
+
+    fn write(&mut self, record: &MessageLogRecord) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        self.file.write_all(&line)?;
+        self.file.flush()?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Moves the active file to `<path>.1`, shifting older rotations, and starts a new file.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        for index in (1..MAX_ROTATED_FILES).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(from, self.rotated_path(index + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.file = BufWriter::new(file);
+        self.size = 0;
+        Ok(())
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        path.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClayerConsensusMessagingAgent;
+    use alloy_rlp::Encodable;
+    use reth_eth_wire::{
+        ClayerConsensusMessage, ClayerConsensusMessageHeader, PbftMessage, PbftMessageInfo,
+    };
+    use reth_interfaces::clayer::ClayerConsensusMessageAgentTrait;
+    use reth_primitives::{keccak256, B256};
+
+    fn encoded_message(
+        ptype: PbftMessageType,
+        view: u64,
+        seq_num: u64,
+        signer: PeerId,
+    ) -> reth_primitives::Bytes {
+        let info = PbftMessageInfo { ptype: ptype as u8, view, seq_num, signer_id: signer };
+        let message_bytes =
+            ParsedMessage::from_pbft_message(PbftMessage { info, block_id: B256::ZERO })
+                .unwrap()
+                .get_message_bytes();
+        let header = ClayerConsensusMessageHeader {
+            message_type: ptype as u8,
+            content_hash: keccak256(&message_bytes),
+            signer_id: signer,
+        };
+        let mut header_bytes = vec![];
+        header.encode(&mut header_bytes);
+
+        let message = ClayerConsensusMessage {
+            header_bytes: header_bytes.into(),
+            message_bytes,
+            ..Default::default()
+        };
+        let mut out = vec![];
+        message.encode(&mut out);
+        out.into()
+    }
+
+    fn read_records(path: &Path) -> Vec<MessageLogRecord> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn records_inbound_and_outbound_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clayer").join("messages.log");
+        let log = ConsensusMessageLog::open(&path, 1024 * 1024).unwrap();
+        let agent = ClayerConsensusMessagingAgent::new().with_message_log(log);
+
+        let peer = PeerId::random();
+        let local = PeerId::random();
+        agent.push_received_cache(peer, encoded_message(PbftMessageType::PrePrepare, 1, 10, peer));
+        agent.push_received_cache(peer, encoded_message(PbftMessageType::Prepare, 1, 10, peer));
+        agent.broadcast_consensus(
+            vec![peer],
+            encoded_message(PbftMessageType::Commit, 1, 10, local),
+        );
+        // not a consensus message, skipped
+        agent.push_received_cache(peer, reth_primitives::Bytes::from_static(&[0xff]));
+
+        let records = read_records(&path);
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].direction, MessageDirection::Inbound);
+        assert_eq!(records[0].peer, Some(peer));
+        assert_eq!(records[0].message_type, "PrePrepare");
+        assert_eq!((records[0].view, records[0].seq_num), (1, 10));
+        assert_eq!(records[1].message_type, "Prepare");
+        assert_eq!(records[2].direction, MessageDirection::Outbound);
+        assert_eq!(records[2].peer, None);
+        assert_eq!(records[2].signer, local);
+        assert_eq!(records[2].message_type, "Commit");
+        assert_eq!(records[2].message, encoded_message(PbftMessageType::Commit, 1, 10, local));
+    }
+
+    #[test]
+    fn rotates_at_max_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("messages.log");
+        let peer = PeerId::random();
+        let message = encoded_message(PbftMessageType::Prepare, 0, 1, peer);
+
+        let mut log = ConsensusMessageLog::open(&path, 1).unwrap();
+        for _ in 0..MAX_ROTATED_FILES + 3 {
+            log.record_inbound(peer, &message);
+        }
+
+        // every record exceeds the limit, so each file holds a single record
+        assert_eq!(read_records(&path).len(), 1);
+        for index in 1..=MAX_ROTATED_FILES {
+            assert_eq!(read_records(&log.rotated_path(index)).len(), 1);
+        }
+        assert!(!log.rotated_path(MAX_ROTATED_FILES + 1).exists());
+    }
+}
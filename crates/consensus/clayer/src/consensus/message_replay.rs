@@ -0,0 +1,277 @@
+//! Replays a consensus message log through a consensus engine with a fresh state, to reproduce a
+//! bug deterministically.
+
+use super::{
+    assemble_peer_id, clayer_block_from_header, clayer_block_from_seal, ClayerConsensusEngine,
+    ClayerConsensusMessagingAgent, ConsensusSigner, MessageDirection, MessageLogRecord, PbftConfig,
+    PbftError, PbftState, ELECT_VOTING_ADDRESS,
+};
+use crate::{
+    engine_api::{ApiService, ExecutionApi},
+    engine_pbft::{
+        handle_consensus_event, parse_consensus_message, CommitAggregator, ConsensusEvent,
+        EquivocationDetector,
+    },
+};
+use reth_interfaces::clayer::{ClayerConsensusEvent, ClayerConsensusMessageAgentTrait};
+use reth_primitives::SealedHeader;
+use reth_provider::{BlockReaderIdExt, ConsensusNumberReader, ConsensusNumberWriter};
+use std::{sync::Arc, time::Duration};
+use tracing::*;
+
+/// Outcome of a replayed message log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MessageReplaySummary {
+    /// Number of received messages handed to the engine
+    pub replayed: usize,
+    /// Number of replayed messages the engine rejected
+    pub rejected: usize,
+    /// Number of records that were not replayed, the messages sent by the node and the records
+    /// written without the encoded message
+    pub skipped: usize,
+}
+
+/// Feeds the messages a node received, as recorded in its
+/// [ConsensusMessageLog](super::ConsensusMessageLog), to a consensus engine with a fresh
+/// [PbftState].
+///
+/// The messages the node sent are not replayed, the engine signs them again with the key of the
+/// node while it handles the received ones. Commits are not batched, so the outcome does not depend
+/// on timing.
+pub struct MessageReplayer<Client, CDB> {
+    engine: ClayerConsensusEngine<Client, CDB>,
+    state: PbftState,
+    commits: CommitAggregator,
+    equivocations: EquivocationDetector,
+}
+
+impl<Client, CDB> MessageReplayer<Client, CDB>
+where
+    CDB: ConsensusNumberReader + ConsensusNumberWriter + 'static,
+    Client: BlockReaderIdExt + 'static,
+{
+    /// Creates a replayer starting at the committed block `head`, signing with `signer`. The seal
+    /// of `head` is loaded from `storages`. The validators are the members of `config`, or the
+    /// ones of the voting contract at `head` if `config` has none, as on startup of the node.
+    pub fn new(
+        signer: Arc<dyn ConsensusSigner>,
+        config: &PbftConfig,
+        head: &SealedHeader,
+        api: Arc<dyn ExecutionApi>,
+        storages: Arc<CDB>,
+        client: Client,
+    ) -> Result<Self, PbftError> {
+        let mut config = config.clone();
+        if config.members.is_empty() {
+            let validators = api
+                .query_validators(ELECT_VOTING_ADDRESS.to_string(), head.number)
+                .map_err(|err| {
+                    PbftError::ServiceError("Failed to query validators".into(), err.to_string())
+                })?;
+            config.members = assemble_peer_id(validators)?;
+        }
+        let mut state = PbftState::with_signer(signer, head.number, head.timestamp, &config)?;
+        let agent = ClayerConsensusMessagingAgent::new();
+        let mut engine = ClayerConsensusEngine::new(agent, ApiService::new(api), storages, client);
+        let block = match engine.load_seal(head.hash)? {
+            Some(seal) if head.number != 0 => clayer_block_from_seal(head, seal),
+            _ => clayer_block_from_header(head),
+        };
+        engine.initialize(block, &config, &mut state);
+        Ok(Self {
+            engine,
+            state,
+            commits: CommitAggregator::new(Duration::ZERO),
+            equivocations: EquivocationDetector::default(),
+        })
+    }
+
+    /// Replays the received messages of `records` in order.
+    pub fn replay(
+        &mut self,
+        records: impl IntoIterator<Item = MessageLogRecord>,
+    ) -> MessageReplaySummary {
+        let mut summary = MessageReplaySummary::default();
+        for record in records {
+            let peer = match (record.direction, record.peer) {
+                (MessageDirection::Inbound, Some(peer)) if !record.message.is_empty() => peer,
+                _ => {
+                    summary.skipped += 1;
+                    continue
+                }
+            };
+            summary.replayed += 1;
+            let handled = parse_consensus_message(&record.message)
+                .and_then(|message| self.handle(ConsensusEvent::PeerMessage(peer, message)));
+            if let Err(err) = handled {
+                debug!(target: "consensus::cl", %err, ?peer, view = record.view, seq_num = record.seq_num, "Replayed message was rejected");
+                summary.rejected += 1;
+            }
+            self.handle_block_events();
+        }
+        summary
+    }
+
+    /// Handles the events the engine raised for itself, e.g. for a block the execution layer
+    /// validated.
+    fn handle_block_events(&mut self) {
+        while let Some(event) = self.engine.agent().pop_event() {
+            let event = match event {
+                ClayerConsensusEvent::BlockValid(block_id) => ConsensusEvent::BlockValid(block_id),
+                ClayerConsensusEvent::BlockInvalid(block_id) => {
+                    ConsensusEvent::BlockInvalid(block_id)
+                }
+                ClayerConsensusEvent::BlockCommit(commit) => ConsensusEvent::BlockCommit(commit),
+                // the replay has no network
+                ClayerConsensusEvent::PeerNetWork(..) |
+                ClayerConsensusEvent::PeerDead(_) |
+                ClayerConsensusEvent::PeerMessage(..) => continue,
+            };
+            if let Err(err) = self.handle(event) {
+                debug!(target: "consensus::cl", %err, "Replayed block event failed");
+            }
+        }
+    }
+
+    fn handle(&mut self, event: ConsensusEvent) -> Result<(), PbftError> {
+        handle_consensus_event(
+            &mut self.engine,
+            &mut self.commits,
+            &mut self.equivocations,
+            event,
+            &mut self.state,
+        )
+        .map(drop)
+    }
+
+    /// Returns the consensus state reached by the replay.
+    pub fn state(&self) -> &PbftState {
+        &self.state
+    }
+
+    /// Returns the engine the messages are replayed to.
+    pub fn engine(&self) -> &ClayerConsensusEngine<Client, CDB> {
+        &self.engine
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        consensus::{read_message_log, ConsensusMessageLog, LocalSigner, ParsedMessage, PbftMode},
+        engine_api::http_blocking::HttpJsonRpcSync,
+    };
+    use alloy_rlp::Encodable;
+    use reth_eth_wire::{
+        ClayerConsensusMessage, ClayerConsensusMessageHeader, ClayerSignature, PbftMessage,
+        PbftMessageInfo, PbftMessageType,
+    };
+    use reth_primitives::{keccak256, Header, B256};
+    use reth_provider::{
+        providers::ConsensusProvider,
+        test_utils::{create_test_provider_factory, MockEthProvider},
+    };
+    use secp256k1::SecretKey;
+
+    /// Returns an encoded ViewChange to `view` signed by `signer`
+    fn view_change(signer: &LocalSigner, view: u64, topic: u64) -> reth_primitives::Bytes {
+        let info = PbftMessageInfo {
+            ptype: PbftMessageType::ViewChange as u8,
+            view,
+            seq_num: 0,
+            signer_id: signer.id(),
+        };
+        let message_bytes =
+            ParsedMessage::from_pbft_message(PbftMessage { info, block_id: B256::ZERO })
+                .unwrap()
+                .get_message_bytes();
+        let header = ClayerConsensusMessageHeader {
+            message_type: PbftMessageType::ViewChange as u8,
+            content_hash: keccak256(&message_bytes),
+            signer_id: signer.id(),
+        };
+        let mut header_bytes = vec![];
+        header.encode(&mut header_bytes);
+        let signature = signer.sign(keccak256(&header_bytes)).unwrap();
+
+        let message = ClayerConsensusMessage {
+            header_bytes: header_bytes.into(),
+            header_signature: ClayerSignature(signature),
+            message_bytes,
+            ttl: 1,
+            topic,
+        };
+        let mut out = vec![];
+        message.encode(&mut out);
+        out.into()
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_run() {
+        let secrets: Vec<SecretKey> =
+            (0..4).map(|_| SecretKey::new(&mut rand::thread_rng())).collect();
+        let signer = |index: usize| LocalSigner::new(secrets[index]);
+        let config = PbftConfig {
+            members: (0..4).map(|index| signer(index).id()).collect(),
+            ..Default::default()
+        };
+        let genesis = Header { number: 0, ..Default::default() }.seal_slow();
+
+        // the original run, with the message log enabled
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("messages.log");
+        let log = ConsensusMessageLog::open(&path, 1024 * 1024).unwrap();
+        let agent = ClayerConsensusMessagingAgent::new().with_message_log(log);
+        let mut sent = agent.pending_consensus_listener();
+        let mut engine = ClayerConsensusEngine::new(
+            agent.clone(),
+            ApiService::new(Arc::new(HttpJsonRpcSync::default())),
+            Arc::new(ConsensusProvider::new(create_test_provider_factory()).unwrap()),
+            MockEthProvider::default(),
+        );
+        let mut state = PbftState::with_signer(Arc::new(signer(0)), 0, 0, &config).unwrap();
+        engine.initialize(clayer_block_from_header(&genesis), &config, &mut state);
+        let mut commits = CommitAggregator::new(Duration::ZERO);
+        let mut equivocations = EquivocationDetector::default();
+
+        // f + 1 validators change to a view this node is not the primary of
+        let view = (1..).find(|view| state.get_primary_id_at_view(*view) != state.id).unwrap();
+        for index in [1, 2] {
+            let message = view_change(&signer(index), view, agent.topic());
+            agent.push_received_cache(signer(index).id(), message);
+        }
+        while let Some(ClayerConsensusEvent::PeerMessage(peer, bytes)) = agent.pop_event() {
+            let message = parse_consensus_message(&bytes).unwrap();
+            let event = ConsensusEvent::PeerMessage(peer, message);
+            handle_consensus_event(
+                &mut engine,
+                &mut commits,
+                &mut equivocations,
+                event,
+                &mut state,
+            )
+            .unwrap();
+        }
+        assert_eq!(state.mode, PbftMode::ViewChanging(view));
+        let (_, own_view_change) = sent.try_recv().unwrap();
+
+        let mut replayer = MessageReplayer::new(
+            Arc::new(signer(0)),
+            &config,
+            &genesis,
+            Arc::new(HttpJsonRpcSync::default()),
+            Arc::new(ConsensusProvider::new(create_test_provider_factory()).unwrap()),
+            MockEthProvider::default(),
+        )
+        .unwrap();
+        let mut resent = replayer.engine().agent().pending_consensus_listener();
+        let summary = replayer.replay(read_message_log(&path).unwrap());
+
+        assert_eq!(summary, MessageReplaySummary { replayed: 2, rejected: 0, skipped: 1 });
+        assert_eq!(replayer.state().mode, state.mode);
+        assert_eq!(replayer.state().to_string(), state.to_string());
+        // the node signed the same ViewChange again
+        assert_eq!(resent.try_recv().unwrap().1, own_view_change);
+    }
+}
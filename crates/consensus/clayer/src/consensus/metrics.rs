@@ -0,0 +1,329 @@
+//! Metrics of the consensus engine, consensus messages are broken down by peer.
+
+use super::PbftPhase;
+use reth_metrics::{
+    metrics::{Counter, Gauge, Histogram},
+    Metrics,
+};
+use reth_rpc_types::PeerId;
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+/// Label shared by all peers that are not validators, so that arbitrary peers can not create an
+/// unbounded number of metric series.
+const NON_VALIDATOR_LABEL: &str = "non_validator";
+
+/// Consensus message metrics of a single peer
+#[derive(Metrics)]
+#[metrics(scope = "consensus.clayer.peer")]
+struct PeerMessageMetrics {
+    /// Number of consensus messages received from the peer
+    messages_received: Counter,
+    /// Number of consensus messages from the peer dropped before reaching the engine
+    messages_dropped: Counter,
+    /// Number of consensus messages from the peer rejected by the engine
+    messages_rejected: Counter,
+}
+
+/// Metrics of the committed blocks
+#[derive(Metrics)]
+#[metrics(scope = "consensus.clayer")]
+pub(crate) struct CommitMetrics {
+    /// Committed height minus the latest height of the execution layer, measured on every commit.
+    /// Stays positive while the execution layer fails to import the committed blocks
+    pub(crate) el_commit_gap: Gauge,
+    /// 1 while the node is in safe mode and does not propose blocks, 0 otherwise
+    pub(crate) safe_mode: Gauge,
+    /// Number of proposals of this node that failed
+    pub(crate) proposal_failures: Counter,
+    /// Number of blocks whose commit latency exceeded the SLA
+    pub(crate) commit_sla_breaches: Counter,
+    /// Number of received messages signed by the key of this node that it did not send
+    pub(crate) duplicate_identity: Counter,
+}
+
+/// Durations of the phases of a consensus round
+#[derive(Metrics)]
+#[metrics(scope = "consensus.clayer")]
+struct PhaseMetrics {
+    /// Seconds from entering the preparing phase on a PrePrepare until prepare quorum
+    prepare_phase_duration: Histogram,
+    /// Seconds from prepare quorum until commit quorum
+    commit_phase_duration: Histogram,
+}
+
+/// Number of phase durations recorded by the engine
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhaseSampleCounts {
+    /// Rounds that reached prepare quorum
+    pub prepare: u64,
+    /// Rounds that reached commit quorum
+    pub commit: u64,
+}
+
+/// Times the preparing and committing phases of the consensus rounds.
+///
+/// A round that is abandoned, e.g. by a view change, records nothing for its unfinished phase.
+#[derive(Debug, Default)]
+pub(crate) struct PhaseTimings {
+    metrics: PhaseMetrics,
+    /// The phase of the current round and when it was entered
+    current: Option<(PbftPhase, Instant)>,
+    counts: PhaseSampleCounts,
+}
+
+impl PhaseTimings {
+    /// Records the duration of the phase that ends by switching to `phase`.
+    pub(crate) fn on_phase(&mut self, phase: &PbftPhase) {
+        let now = Instant::now();
+        match (self.current.take(), phase) {
+            (Some((PbftPhase::Preparing, since)), PbftPhase::Committing) => {
+                self.metrics.prepare_phase_duration.record(now - since);
+                self.counts.prepare += 1;
+            }
+            (Some((PbftPhase::Committing, since)), PbftPhase::Finishing(_)) => {
+                self.metrics.commit_phase_duration.record(now - since);
+                self.counts.commit += 1;
+            }
+            _ => {}
+        }
+        self.current = Some((phase.clone(), now));
+    }
+
+    pub(crate) fn counts(&self) -> PhaseSampleCounts {
+        self.counts
+    }
+}
+
+/// Number of consensus messages received, dropped and rejected for a peer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PeerMessageCounts {
+    /// Messages received from the peer
+    pub received: u64,
+    /// Messages dropped before reaching the engine, e.g. because their TTL expired
+    pub dropped: u64,
+    /// Messages the engine rejected as invalid
+    pub rejected: u64,
+}
+
+/// The metrics and counts of a validator, or of all other peers
+#[derive(Debug)]
+struct PeerSeries {
+    metrics: PeerMessageMetrics,
+    counts: PeerMessageCounts,
+    /// The part of the counts already added to the metrics
+    exported: PeerMessageCounts,
+}
+
+impl PeerSeries {
+    /// Adds the counts recorded since the last export to the metrics and returns the number of
+    /// metric updates.
+    fn export(&mut self) -> u64 {
+        let mut updates = 0;
+        for (counter, count, exported) in [
+            (&self.metrics.messages_received, self.counts.received, &mut self.exported.received),
+            (&self.metrics.messages_dropped, self.counts.dropped, &mut self.exported.dropped),
+            (&self.metrics.messages_rejected, self.counts.rejected, &mut self.exported.rejected),
+        ] {
+            if count > *exported {
+                counter.increment(count - *exported);
+                *exported = count;
+                updates += 1;
+            }
+        }
+        updates
+    }
+}
+
+/// Tracks [PeerMessageCounts] per validator, all other peers are counted together.
+///
+/// The counts are always up to date. The metrics are updated on every message, or with a flush
+/// interval at most once per interval to save the atomic operations on the hot path.
+#[derive(Debug, Default)]
+pub(crate) struct PeerMessageStats {
+    validators: HashSet<PeerId>,
+    peers: HashMap<String, PeerSeries>,
+    /// How often the counts are added to the metrics, on every message if unset
+    flush_interval: Option<Duration>,
+    last_flush: Option<Instant>,
+    /// Number of metric updates so far
+    metric_updates: u64,
+}
+
+impl PeerMessageStats {
+    /// Updates the metrics at most once per `interval`.
+    pub(crate) fn set_flush_interval(&mut self, interval: Duration) {
+        self.flush();
+        self.flush_interval = Some(interval);
+        self.last_flush = Some(Instant::now());
+    }
+
+    /// Sets the validators that get their own metrics and drops the metrics of former validators.
+    pub(crate) fn set_validators(&mut self, validators: &[PeerId]) {
+        // the metrics of former validators are dropped with the counts not yet exported
+        self.flush();
+        self.validators = validators.iter().copied().collect();
+        let labels: HashSet<String> = self.validators.iter().map(ToString::to_string).collect();
+        self.peers.retain(|label, _| label == NON_VALIDATOR_LABEL || labels.contains(label));
+    }
+
+    pub(crate) fn record_received(&mut self, peer_id: PeerId) {
+        self.record(peer_id, |counts| counts.received += 1);
+    }
+
+    pub(crate) fn record_dropped(&mut self, peer_id: PeerId) {
+        self.record(peer_id, |counts| counts.dropped += 1);
+    }
+
+    pub(crate) fn record_rejected(&mut self, peer_id: PeerId) {
+        self.record(peer_id, |counts| counts.rejected += 1);
+    }
+
+    /// Adds the counts to the metrics if the flush interval passed since the last flush at `now`.
+    pub(crate) fn flush_if_due(&mut self, now: Instant) {
+        let Some(interval) = self.flush_interval else { return };
+        if self.last_flush.is_some_and(|last| now.saturating_duration_since(last) < interval) {
+            return
+        }
+        self.flush();
+        self.last_flush = Some(now);
+    }
+
+    /// Returns the counts recorded for `peer_id`, the shared counts if it is not a validator.
+    pub(crate) fn counts(&self, peer_id: PeerId) -> PeerMessageCounts {
+        self.peers.get(&self.label(peer_id)).map(|series| series.counts).unwrap_or_default()
+    }
+
+    /// Returns the counts of `peer_id` that were added to the metrics.
+    pub(crate) fn exported_counts(&self, peer_id: PeerId) -> PeerMessageCounts {
+        self.peers.get(&self.label(peer_id)).map(|series| series.exported).unwrap_or_default()
+    }
+
+    /// Returns the number of metric updates so far.
+    pub(crate) fn metric_updates(&self) -> u64 {
+        self.metric_updates
+    }
+
+    fn record(&mut self, peer_id: PeerId, count: impl FnOnce(&mut PeerMessageCounts)) {
+        let batched = self.flush_interval.is_some();
+        let series = self.peer_mut(peer_id);
+        count(&mut series.counts);
+        if batched {
+            self.flush_if_due(Instant::now());
+        } else {
+            let updates = series.export();
+            self.metric_updates += updates;
+        }
+    }
+
+    fn flush(&mut self) {
+        self.metric_updates += self.peers.values_mut().map(PeerSeries::export).sum::<u64>();
+    }
+
+    fn label(&self, peer_id: PeerId) -> String {
+        if self.validators.contains(&peer_id) {
+            peer_id.to_string()
+        } else {
+            NON_VALIDATOR_LABEL.to_string()
+        }
+    }
+
+    fn peer_mut(&mut self, peer_id: PeerId) -> &mut PeerSeries {
+        let label = self.label(peer_id);
+        self.peers.entry(label.clone()).or_insert_with(|| PeerSeries {
+            metrics: PeerMessageMetrics::new_with_labels(&[("peer", label)]),
+            counts: PeerMessageCounts::default(),
+            exported: PeerMessageCounts::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClayerConsensusMessagingAgent;
+    use alloy_rlp::Encodable;
+    use reth_eth_wire::ClayerConsensusMessage;
+    use reth_interfaces::clayer::ClayerConsensusMessageAgentTrait;
+
+    /// Encodes a distinct envelope for every `id`, copies of the same message are dropped
+    fn envelope(id: u8, ttl: u8) -> reth_primitives::Bytes {
+        let message =
+            ClayerConsensusMessage { message_bytes: vec![id].into(), ttl, ..Default::default() };
+        let mut out = vec![];
+        message.encode(&mut out);
+        out.into()
+    }
+
+    #[test]
+    fn counts_messages_per_peer() {
+        let agent = ClayerConsensusMessagingAgent::new();
+        let (a, b) = (PeerId::random(), PeerId::random());
+        agent.set_validators(&[a, b]);
+
+        agent.push_received_cache(a, envelope(1, 1));
+        agent.push_received_cache(a, envelope(2, 1));
+        agent.push_received_cache(b, envelope(3, 1));
+        // expired TTL
+        agent.push_received_cache(b, envelope(4, 0));
+        agent.record_rejected(b);
+
+        assert_eq!(
+            agent.peer_message_counts(a),
+            PeerMessageCounts { received: 2, dropped: 0, rejected: 0 }
+        );
+        assert_eq!(
+            agent.peer_message_counts(b),
+            PeerMessageCounts { received: 2, dropped: 1, rejected: 1 }
+        );
+
+        // non-validators share a single series
+        let (c, d) = (PeerId::random(), PeerId::random());
+        agent.push_received_cache(c, envelope(5, 1));
+        agent.push_received_cache(d, envelope(6, 1));
+        let shared = PeerMessageCounts { received: 2, dropped: 0, rejected: 0 };
+        assert_eq!(agent.peer_message_counts(c), shared);
+        assert_eq!(agent.peer_message_counts(d), shared);
+
+        // a former validator is counted as a non-validator
+        agent.set_validators(&[a]);
+        assert_eq!(agent.peer_message_counts(b), shared);
+    }
+
+    #[test]
+    fn batches_metric_updates() {
+        let (a, b) = (PeerId::random(), PeerId::random());
+        let interval = Duration::from_secs(60);
+        let mut immediate = PeerMessageStats::default();
+        let mut batched = PeerMessageStats::default();
+        batched.set_flush_interval(interval);
+        for stats in [&mut immediate, &mut batched] {
+            stats.set_validators(&[a, b]);
+            for i in 0..1000 {
+                stats.record_received(if i % 2 == 0 { a } else { b });
+            }
+            stats.record_dropped(a);
+            stats.record_rejected(b);
+        }
+
+        // every message updates the metrics right away
+        assert_eq!(immediate.metric_updates(), 1002);
+        assert_eq!(immediate.exported_counts(a), immediate.counts(a));
+
+        // the batched metrics lag behind until the flush interval passed, the counts don't
+        assert_eq!(batched.metric_updates(), 0);
+        assert_eq!(batched.exported_counts(a), PeerMessageCounts::default());
+        assert_eq!(batched.counts(a), immediate.counts(a));
+        batched.flush_if_due(Instant::now());
+        assert_eq!(batched.metric_updates(), 0);
+
+        batched.flush_if_due(Instant::now() + interval);
+        for peer in [a, b] {
+            assert_eq!(batched.exported_counts(peer), immediate.counts(peer));
+        }
+        // received and dropped of a, received and rejected of b
+        assert_eq!(batched.metric_updates(), 4);
+    }
+}
@@ -18,4 +18,7 @@ pub enum PbftError {
     InternalError(String),
     #[error("ServiceError {0} ,{1}")]
     ServiceError(String, String),
+    /// The head of the execution layer keeps diverging from the committed head, the engine stops
+    #[error("HeadDiverged {0}")]
+    HeadDiverged(String),
 }
@@ -0,0 +1,128 @@
+//! Cache of the recently received consensus messages, to drop the copies relayed by other peers.
+
+use alloy_rlp::Encodable;
+use reth_eth_wire::ClayerConsensusMessage;
+use reth_metrics::{
+    metrics::{Counter, Gauge},
+    Metrics,
+};
+use reth_primitives::{keccak256, B256};
+use std::{
+    collections::{HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+
+/// Default number of messages remembered by the seen-cache.
+pub const DEFAULT_SEEN_CACHE_SIZE: usize = 10_000;
+
+/// Default time after which a message is forgotten by the seen-cache.
+pub const DEFAULT_SEEN_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// Returns the id of `message` in the seen-cache, the same for all relayed copies of it.
+pub(crate) fn message_id(message: &ClayerConsensusMessage) -> B256 {
+    // the TTL is decremented on every hop
+    let message = ClayerConsensusMessage { ttl: 0, ..message.clone() };
+    let mut out = vec![];
+    message.encode(&mut out);
+    keccak256(out)
+}
+
+/// Metrics of the seen-cache
+#[derive(Metrics)]
+#[metrics(scope = "consensus.clayer.seen_cache")]
+struct SeenCacheMetrics {
+    /// Number of messages in the cache
+    entries: Gauge,
+    /// Number of messages evicted before their TTL expired because the cache was full
+    evictions: Counter,
+}
+
+/// Ids of the messages received within the TTL, bounded in size.
+///
+/// Once the cache is full the oldest message is evicted, so a flood of distinct messages can not
+/// make it grow without bounds. An evicted message is accepted again if it is received again.
+#[derive(Debug)]
+pub(crate) struct SeenCache {
+    ids: HashSet<B256>,
+    /// The ids with the time they were first seen, oldest first
+    order: VecDeque<(B256, Instant)>,
+    max_size: usize,
+    ttl: Duration,
+    evictions: u64,
+    metrics: SeenCacheMetrics,
+}
+
+impl SeenCache {
+    /// Creates a cache remembering up to `max_size` messages for `ttl` each.
+    pub(crate) fn new(max_size: usize, ttl: Duration) -> Self {
+        Self {
+            ids: HashSet::new(),
+            order: VecDeque::new(),
+            max_size: max_size.max(1),
+            ttl,
+            evictions: 0,
+            metrics: SeenCacheMetrics::default(),
+        }
+    }
+
+    /// Records the message `id`. Returns `false` if it was already seen within the TTL.
+    pub(crate) fn insert(&mut self, id: B256) -> bool {
+        self.insert_at(id, Instant::now())
+    }
+
+    fn insert_at(&mut self, id: B256, now: Instant) -> bool {
+        while self.order.front().is_some_and(|(_, seen)| now.duration_since(*seen) >= self.ttl) {
+            let (expired, _) = self.order.pop_front().expect("not empty");
+            self.ids.remove(&expired);
+        }
+        if !self.ids.insert(id) {
+            return false
+        }
+        if self.order.len() >= self.max_size {
+            let (evicted, _) = self.order.pop_front().expect("cache is full");
+            self.ids.remove(&evicted);
+            self.evictions += 1;
+            self.metrics.evictions.increment(1);
+        }
+        self.order.push_back((id, now));
+        self.metrics.entries.set(self.order.len() as f64);
+        true
+    }
+
+    /// Returns the number of messages in the cache.
+    pub(crate) fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Returns the number of messages evicted because the cache was full.
+    pub(crate) fn evictions(&self) -> u64 {
+        self.evictions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_oldest_past_capacity() {
+        let mut cache = SeenCache::new(3, Duration::from_secs(10));
+        let start = Instant::now();
+        for i in 0..5 {
+            assert!(cache.insert_at(B256::with_last_byte(i), start));
+        }
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.evictions(), 2);
+
+        // the newest messages are still known, the evicted ones are accepted again
+        assert!(!cache.insert_at(B256::with_last_byte(4), start));
+        assert!(cache.insert_at(B256::with_last_byte(0), start));
+        assert_eq!(cache.evictions(), 3);
+
+        // expired messages are dropped without counting as evictions
+        let later = start + Duration::from_secs(10);
+        assert!(cache.insert_at(B256::with_last_byte(4), later));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.evictions(), 3);
+    }
+}
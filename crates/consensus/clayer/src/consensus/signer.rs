@@ -0,0 +1,205 @@
+//! Signing of the consensus messages this node sends.
+
+use super::pbft_error::PbftError;
+use crate::engine_api::http_blocking::HttpJsonRpcSync;
+use reth_ecies::util::pk2id;
+use reth_network::config::SecretKey;
+use reth_primitives::{public_key_to_address, sign_message, Bytes, Signature, B256, U256};
+use reth_rpc_types::PeerId;
+use secp256k1::{PublicKey, SECP256K1};
+use serde_json::json;
+use std::{fmt, time::Duration};
+use url::Url;
+
+/// Default timeout of a signing request to a [RemoteSigner].
+pub const DEFAULT_REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Signs the headers of the consensus messages this node sends.
+///
+/// The public key of the signer is the identity of the node in consensus, its [PeerId] is the
+/// signer id of every message.
+pub trait ConsensusSigner: fmt::Debug + Send + Sync {
+    /// Returns the public key of the validator key.
+    fn public_key(&self) -> PublicKey;
+
+    /// Signs the `hash` of a message header with the validator key.
+    fn sign(&self, hash: B256) -> Result<Signature, PbftError>;
+
+    /// Returns the id of this node in consensus.
+    fn id(&self) -> PeerId {
+        pk2id(&self.public_key())
+    }
+}
+
+/// Signs with a validator key held in memory.
+#[derive(Clone)]
+pub struct LocalSigner {
+    secret: SecretKey,
+    public_key: PublicKey,
+}
+
+impl LocalSigner {
+    /// Creates a signer for the given validator key.
+    pub fn new(secret: SecretKey) -> Self {
+        Self { public_key: secret.public_key(SECP256K1), secret }
+    }
+}
+
+impl fmt::Debug for LocalSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalSigner").field("public_key", &self.public_key).finish_non_exhaustive()
+    }
+}
+
+impl ConsensusSigner for LocalSigner {
+    fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    fn sign(&self, hash: B256) -> Result<Signature, PbftError> {
+        sign_message(B256::from_slice(&self.secret.secret_bytes()[..]), hash)
+            .map_err(|err| PbftError::SigningError(format!("signing header error: {}", err)))
+    }
+}
+
+/// Signs through an external endpoint, e.g. a remote signer or a service in front of an HSM.
+///
+/// The endpoint is called with the JSON-RPC method `consensus_sign`, the params are the id of the
+/// validator and the hash to sign. It must return the 65 byte signature `r || s || v` as hex,
+/// `v` being either `0`/`1` or `27`/`28`. Signatures not recovering to the validator key are
+/// rejected.
+pub struct RemoteSigner {
+    api: HttpJsonRpcSync,
+    public_key: PublicKey,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    /// Creates a signer calling the endpoint at `url` to sign with the key of `public_key`.
+    pub fn new(url: Url, public_key: PublicKey) -> Self {
+        Self {
+            api: HttpJsonRpcSync::new(url, None).expect("creating a client does not fail"),
+            public_key,
+            timeout: DEFAULT_REMOTE_SIGNER_TIMEOUT,
+        }
+    }
+
+    /// Sets how long a signing request may take.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl fmt::Debug for RemoteSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteSigner")
+            .field("url", &self.api.url)
+            .field("public_key", &self.public_key)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl ConsensusSigner for RemoteSigner {
+    fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    fn sign(&self, hash: B256) -> Result<Signature, PbftError> {
+        let raw: Bytes = self
+            .api
+            .rpc_request("consensus_sign", json!([self.id(), hash]), self.timeout)
+            .map_err(|err| PbftError::SigningError(format!("remote signer error: {:?}", err)))?;
+        if raw.len() != 65 {
+            return Err(PbftError::SigningError(format!(
+                "remote signer returned a signature of {} bytes, expected 65",
+                raw.len()
+            )))
+        }
+        let signature = Signature {
+            r: U256::from_be_slice(&raw[..32]),
+            s: U256::from_be_slice(&raw[32..64]),
+            odd_y_parity: matches!(raw[64], 1 | 28),
+        };
+
+        let expected = public_key_to_address(self.public_key);
+        match signature.recover_signer(hash) {
+            Some(recovered) if recovered == expected => Ok(signature),
+            recovered => Err(PbftError::SigningError(format!(
+                "remote signer signed with the wrong key - Recovered: `{:?}`, Expected: `{:?}`",
+                recovered, expected
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_api::STATIC_ID;
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Request, Response, Server,
+    };
+    use std::{
+        convert::Infallible,
+        net::{Ipv4Addr, SocketAddr},
+    };
+
+    /// Spawns a remote signer signing every `consensus_sign` request with `secret`.
+    fn spawn_remote_signer(secret: SecretKey) -> Url {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let runtime =
+                tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            runtime.block_on(async move {
+                let make_service = make_service_fn(move |_| async move {
+                    Ok::<_, Infallible>(service_fn(move |request: Request<Body>| async move {
+                        let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                        let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                        assert_eq!(request["method"], "consensus_sign");
+                        let hash: B256 =
+                            serde_json::from_value(request["params"][1].clone()).unwrap();
+
+                        let signature = LocalSigner::new(secret).sign(hash).unwrap();
+                        let mut raw = Vec::with_capacity(65);
+                        raw.extend_from_slice(&signature.r.to_be_bytes::<32>());
+                        raw.extend_from_slice(&signature.s.to_be_bytes::<32>());
+                        raw.push(signature.odd_y_parity as u8 + 27);
+                        let result = Bytes::from(raw);
+                        let body = json!({ "jsonrpc": "2.0", "id": STATIC_ID, "result": result });
+                        Ok::<_, Infallible>(Response::new(Body::from(body.to_string())))
+                    }))
+                });
+                let server =
+                    Server::bind(&SocketAddr::from((Ipv4Addr::LOCALHOST, 0))).serve(make_service);
+                tx.send(server.local_addr()).unwrap();
+                server.await.unwrap();
+            });
+        });
+        Url::parse(&format!("http://{}/", rx.recv().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn signs_through_remote_endpoint() {
+        let secret = SecretKey::new(&mut rand::thread_rng());
+        let local = LocalSigner::new(secret);
+        let remote = RemoteSigner::new(spawn_remote_signer(secret), local.public_key());
+        assert_eq!(remote.id(), local.id());
+
+        let hash = B256::with_last_byte(1);
+        let signature = remote.sign(hash).unwrap();
+        assert_eq!(signature.recover_signer(hash), Some(public_key_to_address(local.public_key())));
+        assert_eq!(signature, local.sign(hash).unwrap());
+    }
+
+    #[test]
+    fn rejects_signature_of_other_key() {
+        let secret = SecretKey::new(&mut rand::thread_rng());
+        let other = LocalSigner::new(SecretKey::new(&mut rand::thread_rng()));
+        let remote = RemoteSigner::new(spawn_remote_signer(secret), other.public_key());
+
+        assert!(matches!(remote.sign(B256::with_last_byte(1)), Err(PbftError::SigningError(_))));
+    }
+}
@@ -1,12 +1,15 @@
-use super::{config::PbftConfig, pbft_error::PbftError, validators::Validators};
+use super::{
+    config::PbftConfig,
+    pbft_error::PbftError,
+    signer::{ConsensusSigner, LocalSigner},
+    validators::Validators,
+};
 use crate::timing::Timeout;
-use reth_ecies::util::pk2id;
 use reth_network::config::SecretKey;
 use reth_primitives::B256;
 use reth_rpc_types::PeerId;
-use secp256k1::{KeyPair, SECP256K1};
 use serde_derive::{Deserialize, Serialize};
-use std::{fmt, time::Duration};
+use std::{fmt, sync::Arc, time::Duration};
 use tracing::debug;
 
 /// Phases of the PBFT algorithm, in `Normal` mode
@@ -42,13 +45,13 @@ pub enum PbftMode {
 }
 
 /// Information about the PBFT algorithm's state
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
 pub struct PbftState {
     /// This node's ID
     pub id: PeerId,
 
-    /// This node's KeyPair
-    pub kp: KeyPair,
+    /// Signs the messages of this node
+    pub signer: Arc<dyn ConsensusSigner>,
 
     /// The node's current sequence number(next block number)
     pub seq_num: u64,
@@ -71,6 +74,9 @@ pub struct PbftState {
     /// The maximum number of faulty nodes in the network
     pub f: u64,
 
+    /// The configured number of faulty nodes, overrides the one derived from the validator count
+    pub faults: Option<u64>,
+
     /// Timer used to make sure the primary publishes blocks in a timely manner. If not, then this
     /// node will initiate a view change.
     pub idle_timeout: Timeout,
@@ -130,29 +136,43 @@ impl fmt::Display for PbftState {
 }
 
 impl PbftState {
+    /// Creates the state of a node signing its messages with the validator key `sk`.
     pub fn new(
         sk: SecretKey,
         head_block_num: u64,
         last_block_timestamp: u64,
         config: &PbftConfig,
-    ) -> Self {
-        let kp = KeyPair::from_secret_key(SECP256K1, &sk);
-        let id = pk2id(&kp.public_key());
-        // Maximum number of faulty nodes in this network. Panic if there are not enough nodes.
-        let f = ((config.members.len() - 1) / 3) as u64;
-        if f == 0 {
-            panic!("This network does not contain enough nodes to be fault tolerant");
-        }
+    ) -> Result<Self, PbftError> {
+        Self::with_signer(
+            Arc::new(LocalSigner::new(sk)),
+            head_block_num,
+            last_block_timestamp,
+            config,
+        )
+    }
+
+    /// Creates the state of a node signing its messages with `signer`, the node's ID is derived
+    /// from the signer's public key.
+    pub fn with_signer(
+        signer: Arc<dyn ConsensusSigner>,
+        head_block_num: u64,
+        last_block_timestamp: u64,
+        config: &PbftConfig,
+    ) -> Result<Self, PbftError> {
+        let id = signer.id();
+        // Maximum number of faulty nodes in this network
+        let f = fault_tolerance(config.members.len(), config.faults)?;
 
-        PbftState {
+        Ok(PbftState {
             id,
-            kp,
+            signer,
             seq_num: head_block_num + 1,
             view: 0,
             chain_head: B256::default(),
             phase: PbftPhase::PrePreparing,
             mode: PbftMode::Normal,
             f,
+            faults: config.faults,
             validators: Validators::new(config.members.clone()),
             idle_timeout: Timeout::new(config.idle_timeout),
             commit_timeout: Timeout::new(config.commit_timeout),
@@ -166,7 +186,7 @@ impl PbftState {
             becoming_validator: false,
             last_send_seal_timestamp: 0,
             has_send_seal: 0,
-        }
+        })
     }
     /// Obtain the ID for the primary node in the network
     pub fn get_primary_id(&self) -> PeerId {
@@ -221,16 +241,69 @@ impl PbftState {
         }
     }
 
+    /// Returns `true` if the primary of the next block is rotated, see
+    /// [PbftConfig::forced_view_change_interval].
     pub fn at_forced_view_change(&self) -> bool {
-        self.seq_num % self.forced_view_change_interval == 0
+        self.forced_view_change_interval != 0
+            && self.seq_num % self.forced_view_change_interval == 0
     }
 
     pub fn update_members(&mut self, members: &Vec<PeerId>) {
         self.validators.update(members);
-        let f = (self.validators.len() - 1) / 3;
-        if f == 0 {
-            panic!("This network no longer contains enough nodes to be fault tolerant");
+        self.f = fault_tolerance(self.validators.len(), self.faults).unwrap_or_else(|err| {
+            panic!("This network no longer contains enough nodes to be fault tolerant: {err}")
+        });
+    }
+}
+
+/// Returns the maximum number of faulty nodes `members` validators tolerate.
+///
+/// `faults` overrides the default of `(members - 1) / 3`. Fails unless at least one fault is
+/// tolerated and `members >= 3f + 1`, so that a quorum of `2f + 1` can always be reached.
+pub fn fault_tolerance(members: usize, faults: Option<u64>) -> Result<u64, PbftError> {
+    let members = members as u64;
+    let f = faults.unwrap_or(members.saturating_sub(1) / 3);
+    if f == 0 {
+        return Err(PbftError::InternalError(format!(
+            "{members} validators are not enough to be fault tolerant"
+        )))
+    }
+    if members < 3 * f + 1 {
+        return Err(PbftError::InternalError(format!(
+            "{members} validators can not tolerate {f} faulty nodes, at least {} are required",
+            3 * f + 1
+        )))
+    }
+    Ok(f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quorum_thresholds() {
+        // (validators, configured faults, expected f)
+        let cases = [
+            (4, None, 1),
+            (5, None, 1),
+            (6, None, 1),
+            (7, None, 2),
+            (10, None, 3),
+            (7, Some(1), 1),
+            (7, Some(2), 2),
+            (10, Some(2), 2),
+        ];
+        for (n, faults, f) in cases {
+            assert_eq!(fault_tolerance(n, faults).unwrap(), f, "n={n} faults={faults:?}");
         }
-        self.f = f as u64;
+        // a quorum of 2f + 1 out of 3f + 1
+        assert_eq!(2 * fault_tolerance(4, None).unwrap() + 1, 3);
+        assert_eq!(2 * fault_tolerance(7, Some(1)).unwrap() + 1, 3);
+
+        // not enough validators
+        assert!(fault_tolerance(3, None).is_err());
+        assert!(fault_tolerance(6, Some(2)).is_err());
+        assert!(fault_tolerance(4, Some(0)).is_err());
     }
 }
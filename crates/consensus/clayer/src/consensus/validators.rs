@@ -1,7 +1,54 @@
+use reth_primitives::{keccak256, Log, B256};
 use reth_rpc_types::PeerId;
 use serde_derive::{Deserialize, Serialize};
 use std::fmt;
 
+/// Signature of the event of the validator contract adding a validator
+pub const VALIDATOR_ADDED_EVENT: &str = "ValidatorAdded(bytes32,bytes32)";
+
+/// Signature of the event of the validator contract removing a validator
+pub const VALIDATOR_REMOVED_EVENT: &str = "ValidatorRemoved(bytes32,bytes32)";
+
+/// A change of the validator set emitted by the validator contract.
+///
+/// Both events carry the node id of the validator as two indexed `bytes32` halves, like the
+/// election contract returns them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidatorSetChange {
+    /// The validator joins the set
+    Added(PeerId),
+    /// The validator leaves the set
+    Removed(PeerId),
+}
+
+impl ValidatorSetChange {
+    /// Decodes the change from a log of the validator contract, `None` for any other event.
+    pub fn from_log(log: &Log) -> Option<Self> {
+        let [signature, high, low] = log.topics.as_slice() else { return None };
+        let mut id = [0u8; 64];
+        id[..32].copy_from_slice(high.as_slice());
+        id[32..].copy_from_slice(low.as_slice());
+        let id = PeerId::from(id);
+
+        if *signature == keccak256(VALIDATOR_ADDED_EVENT) {
+            Some(Self::Added(id))
+        } else if *signature == keccak256(VALIDATOR_REMOVED_EVENT) {
+            Some(Self::Removed(id))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the topics of the log emitting this change.
+    pub fn topics(&self) -> Vec<B256> {
+        let (signature, id) = match self {
+            Self::Added(id) => (VALIDATOR_ADDED_EVENT, id),
+            Self::Removed(id) => (VALIDATOR_REMOVED_EVENT, id),
+        };
+        vec![keccak256(signature), B256::from_slice(&id[..32]), B256::from_slice(&id[32..])]
+    }
+}
+
 /// A set of validators
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Validators {
@@ -46,6 +93,19 @@ impl Validators {
         matching == self.validators.len() && matching == validators.len()
     }
 
+    /// Returns the validators after applying `changes` in order
+    pub fn apply(&self, changes: impl IntoIterator<Item = ValidatorSetChange>) -> Vec<PeerId> {
+        let mut validators = self.validators.clone();
+        for change in changes {
+            match change {
+                ValidatorSetChange::Added(id) if !validators.contains(&id) => validators.push(id),
+                ValidatorSetChange::Added(_) => {}
+                ValidatorSetChange::Removed(id) => validators.retain(|validator| *validator != id),
+            }
+        }
+        validators
+    }
+
     pub fn contains(&self, id: &PeerId) -> bool {
         self.validators.contains(id)
     }
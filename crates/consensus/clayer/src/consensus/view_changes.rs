@@ -0,0 +1,52 @@
+//! History of the view changes started by this node.
+
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Why a node started a view change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ViewChangeReason {
+    /// No block was proposed before the idle timeout expired
+    IdleTimeout,
+    /// The block of the current sequence number was not committed before the commit timeout
+    /// expired
+    CommitTimeout,
+    /// No NewView message was received before the view change timeout expired
+    ViewChangeTimeout,
+    /// The primary sent conflicting or disallowed messages
+    FaultyPrimary,
+    /// f + 1 other nodes already started a view change
+    PeerViewChanges,
+    /// This node stepped down as primary
+    StepDown,
+    /// The primary stopped answering the heartbeat
+    UnresponsivePrimary,
+    /// The view lasted longer than its maximum lifetime
+    MaxViewLifetime,
+}
+
+/// A view change started by this node, as returned by `clayer_viewChanges`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewChangeRecord {
+    /// The view the node was on
+    pub old_view: u64,
+    /// The view the node changed to
+    pub new_view: u64,
+    /// Why the view change was started
+    pub reason: ViewChangeReason,
+    /// Seconds since the unix epoch when the view change was started
+    pub timestamp: u64,
+    /// Number of the block the network failed to agree on
+    pub block_number: u64,
+}
+
+impl ViewChangeRecord {
+    /// Creates the record of a view change started now.
+    pub fn new(old_view: u64, new_view: u64, reason: ViewChangeReason, block_number: u64) -> Self {
+        let timestamp =
+            SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+        Self { old_view, new_view, reason, timestamp, block_number }
+    }
+}
@@ -1,24 +1,43 @@
-use crate::error::{PrettyReqwestError, RpcError};
+use crate::{
+    consensus::execution_payload_from_payload,
+    error::{PrettyReqwestError, RpcError},
+    timing::retry_bounded,
+};
 use alloy_primitives::{B256, U256};
+use alloy_rlp::Encodable;
 
 use reqwest::StatusCode;
 
 use reth_rpc_types::{
     engine::{
         ExecutionPayloadInputV2, ForkchoiceState, ForkchoiceUpdated, PayloadAttributes, PayloadId,
-        PayloadStatus,
+        PayloadStatus, PayloadStatusEnum,
     },
-    ExecutionPayloadV2,
+    ExecutionPayloadV1, ExecutionPayloadV2, Withdrawal,
+};
+use reth_transaction_pool::TransactionPool;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
 };
-use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use self::http_blocking::HttpJsonRpcSync;
+use self::{
+    forkchoice_history::{ForkchoiceHistory, ForkchoiceRecorder},
+    http_blocking::HttpJsonRpcSync,
+    json_structures::BlockTag,
+    state_root::StateRootVerifier,
+};
 
 pub mod auth;
+pub mod forkchoice_history;
 pub mod http;
 pub mod http_blocking;
+pub mod identity;
+pub mod in_process;
 pub mod json_structures;
+pub mod state_root;
 
 // pub const LATEST_TAG: &str = "latest";
 
@@ -60,6 +79,9 @@ pub const ENGINE_GET_PAYLOAD_BODIES_BY_RANGE_V1: &str = "engine_getPayloadBodies
 pub const ENGINE_EXCHANGE_CAPABILITIES: &str = "engine_exchangeCapabilities";
 pub const ENGINE_EXCHANGE_CAPABILITIES_TIMEOUT: Duration = Duration::from_secs(1);
 
+pub const ENGINE_GET_CLIENT_VERSION_V1: &str = "engine_getClientVersionV1";
+pub const ENGINE_GET_CLIENT_VERSION_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// This error is returned during a `chainId` call by Geth.
 pub const EIP155_ERROR_STR: &str = "chain not synced beyond EIP-155 replay-protection fork block";
 
@@ -80,6 +102,16 @@ pub static CL_CAPABILITIES: &[&str] = &[
 pub struct AuthHttpConfig {
     pub port: u16,
     pub auth: Vec<u8>,
+    /// How long a minted JWT token is reused, see [auth::Auth::with_refresh_interval]
+    pub token_refresh_interval: Duration,
+    /// Talk HTTP/2 to the engine API, see [http_blocking::HttpJsonRpcSync::with_http2]
+    pub http2: bool,
+    /// Requests sent to the engine API at the same time, see
+    /// [http::HttpJsonRpc::with_max_concurrent_requests]
+    pub max_concurrent_requests: usize,
+    /// Calls the engine API of the execution layer of this process instead of the port, see
+    /// [http_blocking::HttpJsonRpcSync::with_in_process_engine]
+    pub in_process: Option<in_process::InProcessEngine>,
 }
 
 #[derive(Debug)]
@@ -148,6 +180,9 @@ impl From<auth::Error> for ClRpcError {
 /// Representation of an exection block with enough detail to determine the terminal PoW block.
 ///
 /// See `get_pow_block_hash_at_total_difficulty`.
+///
+/// Like all engine API responses, fields unknown to this client are ignored so that newer
+/// execution layers stay compatible.
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExecutionBlock {
@@ -164,13 +199,127 @@ pub struct ExecutionBlock {
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExecutionPayloadWrapperV2 {
+    #[serde(deserialize_with = "deserialize_execution_payload_v2")]
     pub execution_payload: ExecutionPayloadV2,
     /// The expected value to be received by the feeRecipient in wei
     pub block_value: U256,
 }
 
+/// Deserializes an [ExecutionPayloadV2], ignoring unknown fields.
+///
+/// [ExecutionPayloadV2] denies unknown fields, which would reject payloads of execution layers
+/// that already return fields of later forks.
+fn deserialize_execution_payload_v2<'de, D>(deserializer: D) -> Result<ExecutionPayloadV2, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct LenientExecutionPayloadV2 {
+        #[serde(flatten)]
+        payload_inner: ExecutionPayloadV1,
+        withdrawals: Vec<Withdrawal>,
+    }
+
+    let LenientExecutionPayloadV2 { payload_inner, withdrawals } =
+        LenientExecutionPayloadV2::deserialize(deserializer)?;
+    Ok(ExecutionPayloadV2 { payload_inner, withdrawals })
+}
+
+/// The engine API calls the consensus layer drives an execution layer with.
+///
+/// Implemented by [HttpJsonRpcSync]; abstracted so the execution layer can be mocked.
+pub trait ExecutionApi: Send + Sync {
+    /// Returns the block with the given tag, if the execution layer knows it
+    fn get_block_by_number(&self, tag: BlockTag) -> Result<Option<ExecutionBlock>, ClRpcError>;
+
+    /// Returns the block with the given hash, if the execution layer knows it
+    fn get_block_by_hash(&self, block_hash: B256) -> Result<Option<ExecutionBlock>, ClRpcError>;
+
+    /// Sends `engine_forkchoiceUpdatedV2`
+    fn forkchoice_updated_v2(
+        &self,
+        forkchoice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+    ) -> Result<ForkchoiceUpdated, ClRpcError>;
+
+    /// Sends `engine_newPayloadV2`
+    fn new_payload_v2(&self, payload: ExecutionPayloadInputV2)
+        -> Result<PayloadStatus, ClRpcError>;
+
+    /// Sends `engine_getPayloadV2`
+    fn get_payload_v2(
+        &self,
+        payload_id: PayloadId,
+    ) -> Result<ExecutionPayloadWrapperV2, ClRpcError>;
+
+    /// Returns the validator set stored in the voting contract at the given block
+    fn query_validators(
+        &self,
+        contract_address: String,
+        block_number: u64,
+    ) -> Result<Vec<Vec<u8>>, ClRpcError>;
+
+    /// Sends `engine_exchangeCapabilities`, `None` if the execution layer does not support it
+    fn exchange_capabilities(&self) -> Result<Option<HashSet<String>>, ClRpcError> {
+        Ok(None)
+    }
+
+    /// Sends `engine_getClientVersionV1`, `None` if the execution layer does not support it
+    fn client_version(&self) -> Result<Option<String>, ClRpcError> {
+        Ok(None)
+    }
+}
+
+impl ExecutionApi for HttpJsonRpcSync {
+    fn get_block_by_number(&self, tag: BlockTag) -> Result<Option<ExecutionBlock>, ClRpcError> {
+        HttpJsonRpcSync::get_block_by_number(self, tag)
+    }
+
+    fn get_block_by_hash(&self, block_hash: B256) -> Result<Option<ExecutionBlock>, ClRpcError> {
+        HttpJsonRpcSync::get_block_by_hash(self, block_hash)
+    }
+
+    fn forkchoice_updated_v2(
+        &self,
+        forkchoice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+    ) -> Result<ForkchoiceUpdated, ClRpcError> {
+        HttpJsonRpcSync::forkchoice_updated_v2(self, forkchoice_state, payload_attributes)
+    }
+
+    fn new_payload_v2(
+        &self,
+        payload: ExecutionPayloadInputV2,
+    ) -> Result<PayloadStatus, ClRpcError> {
+        HttpJsonRpcSync::new_payload_v2(self, payload)
+    }
+
+    fn get_payload_v2(
+        &self,
+        payload_id: PayloadId,
+    ) -> Result<ExecutionPayloadWrapperV2, ClRpcError> {
+        HttpJsonRpcSync::get_payload_v2(self, payload_id)
+    }
+
+    fn query_validators(
+        &self,
+        contract_address: String,
+        block_number: u64,
+    ) -> Result<Vec<Vec<u8>>, ClRpcError> {
+        HttpJsonRpcSync::query_validators(self, contract_address, block_number)
+    }
+
+    fn exchange_capabilities(&self) -> Result<Option<HashSet<String>>, ClRpcError> {
+        HttpJsonRpcSync::exchange_capabilities(self)
+    }
+
+    fn client_version(&self) -> Result<Option<String>, ClRpcError> {
+        HttpJsonRpcSync::client_version(self)
+    }
+}
+
 pub fn forkchoice_updated(
-    api: &Arc<HttpJsonRpcSync>,
+    api: &Arc<dyn ExecutionApi>,
     last_block: B256,
 ) -> Result<ForkchoiceUpdated, ClRpcError> {
     let forkchoice_state = ForkchoiceState {
@@ -182,8 +331,29 @@ pub fn forkchoice_updated(
     api.forkchoice_updated_v2(forkchoice_state, None)
 }
 
+/// Checks that the execution layer answers engine API calls by sending a `forkchoice_updated` to
+/// `head`, the block consensus starts from.
+///
+/// An execution layer that is still syncing passes, only an unreachable execution layer, a
+/// rejected JWT secret or an invalid head fail the check.
+pub fn startup_self_test(api: &dyn ExecutionApi, head: B256) -> Result<(), ClRpcError> {
+    let forkchoice_state = ForkchoiceState {
+        head_block_hash: head,
+        finalized_block_hash: head,
+        safe_block_hash: head,
+    };
+    let updated = api.forkchoice_updated_v2(forkchoice_state, None)?;
+    if updated.payload_status.status.is_invalid() {
+        return Err(ClRpcError::BadResponse(format!(
+            "forkchoice_updated to {} returned {:?}",
+            head, updated.payload_status.status
+        )));
+    }
+    Ok(())
+}
+
 pub fn forkchoice_updated_with_attributes(
-    api: &Arc<HttpJsonRpcSync>,
+    api: &Arc<dyn ExecutionApi>,
     last_block: B256,
     index: u64,
     accounts: Vec<alloy_primitives::Address>,
@@ -236,7 +406,7 @@ pub fn forkchoice_updated_with_attributes(
 }
 
 pub fn new_payload(
-    api: &Arc<HttpJsonRpcSync>,
+    api: &Arc<dyn ExecutionApi>,
     execution_payload: ExecutionPayloadWrapperV2,
 ) -> Result<PayloadStatus, ClRpcError> {
     let input = ExecutionPayloadInputV2 {
@@ -246,6 +416,15 @@ pub fn new_payload(
     api.new_payload_v2(input)
 }
 
+/// Statuses the execution layer returned while importing a committed block and making it the head
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitResult {
+    /// Status returned by `engine_newPayloadV2`
+    pub payload_status: PayloadStatus,
+    /// Status returned by `engine_forkchoiceUpdatedV2` for the imported block
+    pub forkchoice_status: PayloadStatus,
+}
+
 #[derive(Debug)]
 pub enum ApiServiceError {
     ApiError(String),
@@ -254,6 +433,15 @@ pub enum ApiServiceError {
     UnknownPeer(String),
     NoChainHead,
     BlockNotReady,
+    /// The built payload has no transactions and empty blocks are not proposed
+    EmptyPayload,
+    /// The state root of the payload does not match the one of executing it locally
+    InvalidStateRoot(String),
+    /// Making the block canonical would unwind more blocks of the EL than allowed, carries the
+    /// depth of the reorg
+    ReorgTooDeep(u64),
+    /// The built payload is larger than the maximum block size, carries its size in bytes
+    PayloadTooLarge(usize),
 }
 
 impl std::fmt::Display for ApiServiceError {
@@ -262,25 +450,313 @@ impl std::fmt::Display for ApiServiceError {
     }
 }
 
+/// Where [`ApiService`] is in the `initialize` -> `summarize` -> `finalize` -> `commit` flow of
+/// the block it is currently building.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockBuildingState {
+    /// No block is being built
+    Idle,
+    /// `initialize_block` set the parent of the next block
+    Initialized,
+    /// `summarize_block` obtained a payload id from the EL
+    Summarized,
+    /// `finalize_block` fetched the built payload
+    Finalized,
+}
+
+impl BlockBuildingState {
+    /// Returns an `InvalidState` error naming the expected states if `self` is not one of them
+    fn expect(self, expected: &[BlockBuildingState], call: &str) -> Result<(), ApiServiceError> {
+        if expected.contains(&self) {
+            return Ok(());
+        }
+        let expected = expected.iter().map(|s| format!("{:?}", s)).collect::<Vec<_>>().join(" or ");
+        Err(ApiServiceError::InvalidState(format!(
+            "expected {}, got call {} in state {:?}",
+            expected, call, self
+        )))
+    }
+}
+
+/// A block proposal that was finalized or checked, but not committed yet
+#[derive(Debug, Clone)]
+struct ProposingPayload {
+    payload_id: PayloadId,
+    payload: ExecutionPayloadWrapperV2,
+    proposed_at: Instant,
+}
+
+/// A payload id returned by `summarize_block`, with the attributes it was requested with so the
+/// payload can be rebuilt
+#[derive(Debug, Clone)]
+struct BuildingPayload {
+    payload_id: PayloadId,
+    index: u64,
+    accounts: Vec<alloy_primitives::Address>,
+    requested_at: Instant,
+}
+
+/// Read-only view of a block proposal that was not committed yet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingProposal {
+    /// Hash of the proposed block
+    pub block_hash: B256,
+    /// Number of the proposed block
+    pub block_number: u64,
+    /// Id of the payload the block was built from
+    pub payload_id: PayloadId,
+    /// Milliseconds since the proposal was finalized or checked
+    pub age_ms: u64,
+    /// The expected value to be received by the feeRecipient in wei
+    pub block_value: U256,
+}
+
+/// The proposals of an [ApiService], shared so they can be inspected from outside of the
+/// consensus thread.
+#[derive(Debug, Clone, Default)]
+pub struct PendingProposals {
+    inner: Arc<parking_lot::RwLock<HashMap<B256, ProposingPayload>>>,
+}
+
+impl PendingProposals {
+    fn insert(&self, block_id: B256, payload_id: PayloadId, payload: ExecutionPayloadWrapperV2) {
+        let proposal = ProposingPayload { payload_id, payload, proposed_at: Instant::now() };
+        self.inner.write().insert(block_id, proposal);
+    }
+
+    fn payload(&self, block_id: &B256) -> Option<ExecutionPayloadWrapperV2> {
+        self.inner.read().get(block_id).map(|proposal| proposal.payload.clone())
+    }
+
+    fn remove(&self, block_id: &B256) {
+        self.inner.write().remove(block_id);
+    }
+
+    /// Drops the proposals at or below the committed `number`, they can't be committed anymore
+    fn prune(&self, number: u64) {
+        self.inner.write().retain(|_, proposal| {
+            proposal.payload.execution_payload.payload_inner.block_number > number
+        });
+    }
+
+    /// Returns all pending proposals, oldest first
+    pub fn snapshot(&self) -> Vec<PendingProposal> {
+        let mut proposals = self
+            .inner
+            .read()
+            .iter()
+            .map(|(block_hash, proposal)| PendingProposal {
+                block_hash: *block_hash,
+                block_number: proposal.payload.execution_payload.payload_inner.block_number,
+                payload_id: proposal.payload_id,
+                age_ms: proposal.proposed_at.elapsed().as_millis() as u64,
+                block_value: proposal.payload.block_value,
+            })
+            .collect::<Vec<_>>();
+        proposals.sort_by(|a, b| b.age_ms.cmp(&a.age_ms));
+        proposals
+    }
+}
+
+/// Returns the size in bytes a payload adds to a proposal: the length of its RLP encoding in the
+/// `BlockNew` message.
+pub fn payload_size(payload: &ExecutionPayloadWrapperV2) -> usize {
+    execution_payload_from_payload(payload).length()
+}
+
+/// Tells [ApiService] whether there are transactions to build a block from
+pub trait PendingWork: Send + Sync {
+    /// Returns whether transactions are ready to be included in the next block
+    fn has_pending_work(&self) -> bool;
+}
+
+impl<P: TransactionPool> PendingWork for P {
+    fn has_pending_work(&self) -> bool {
+        self.pool_size().pending > 0
+    }
+}
+
+/// How often `summarize_block` asks the EL again for a payload id when a valid forkchoice update
+/// returned none
+const MISSING_PAYLOAD_ID_RETRIES: u32 = 2;
+
 pub struct ApiService {
-    api: Arc<HttpJsonRpcSync>,
+    api: Arc<dyn ExecutionApi>,
     latest_committed_id: Option<B256>,
-    /// key latest_committed_id, value:payload_id
-    next_payload_id_pairs: HashMap<B256, PayloadId>,
-    /// key proposing block_id, value:ExecutionPayloadWrapperV2
-    proposing_payload_pairs: HashMap<B256, (PayloadId, ExecutionPayloadWrapperV2)>,
+    /// key latest_committed_id, value: the payload being built on top of it
+    next_payload_id_pairs: HashMap<B256, BuildingPayload>,
+    /// proposed blocks waiting to be committed, keyed by block id
+    proposing_payloads: PendingProposals,
+    /// Progress of the block this node is building
+    building_state: BlockBuildingState,
+    /// Whether `finalize_block` cancels payloads without transactions
+    cancel_empty_payloads: bool,
+    /// How often [ApiService::retry_not_ready] retries a call
+    not_ready_retries: u32,
+    /// Backoff before the first retry, doubled for every further retry
+    not_ready_retry_base: Duration,
+    /// Upper bound of the backoff between two retries
+    not_ready_retry_max: Duration,
+    /// Age after which a payload id is considered discarded by the EL, zero never expires ids
+    payload_id_max_age: Duration,
+    /// Verifies the state root of every payload before it can be committed, unset trusts the EL
+    state_root_verifier: Option<Arc<dyn StateRootVerifier>>,
+    /// Whether `commit_block` compares the head of the EL with the committed block
+    check_head_after_commit: bool,
+    /// Number of commits after which the head of the EL was not the committed block
+    head_divergences: u64,
+    /// Whether a commit waits until the EL reports the committed block as finalized
+    require_el_finalized: bool,
+    /// Maximum number of blocks of the EL a commit may unwind, unbounded if unset
+    max_reorg_depth: Option<u64>,
+    /// Maximum size in bytes of a proposed payload, unbounded if unset
+    max_block_bytes: Option<usize>,
+    /// Whether there are transactions to build a block from, payloads are always built if unset
+    pending_work: Option<Arc<dyn PendingWork>>,
+    /// The head the EL was last sent a plain forkchoice update for while there was nothing to
+    /// build
+    idle_head: Option<B256>,
 }
 
 impl ApiService {
-    pub fn new(api: Arc<HttpJsonRpcSync>) -> Self {
+    pub fn new(api: Arc<dyn ExecutionApi>) -> Self {
         Self {
             api,
             latest_committed_id: None,
             next_payload_id_pairs: HashMap::new(),
-            proposing_payload_pairs: HashMap::new(),
+            proposing_payloads: PendingProposals::default(),
+            building_state: BlockBuildingState::Idle,
+            cancel_empty_payloads: false,
+            not_ready_retries: 0,
+            not_ready_retry_base: Duration::from_millis(100),
+            not_ready_retry_max: Duration::from_secs(1),
+            payload_id_max_age: Duration::ZERO,
+            state_root_verifier: None,
+            check_head_after_commit: false,
+            head_divergences: 0,
+            require_el_finalized: false,
+            max_reorg_depth: None,
+            max_block_bytes: None,
+            pending_work: None,
+            idle_head: None,
         }
     }
 
+    /// Keeps the proposals in the given shared [PendingProposals]
+    pub fn with_pending_proposals(mut self, proposals: PendingProposals) -> Self {
+        self.proposing_payloads = proposals;
+        self
+    }
+
+    /// Records every forkchoice update sent to the EL in the given shared [ForkchoiceHistory]
+    pub fn with_forkchoice_history(mut self, history: ForkchoiceHistory) -> Self {
+        self.api = Arc::new(ForkchoiceRecorder::new(self.api, history));
+        self
+    }
+
+    /// Makes `finalize_block` fail with [ApiServiceError::EmptyPayload] instead of proposing a
+    /// payload without transactions
+    pub fn with_cancel_empty_payloads(mut self, cancel_empty_payloads: bool) -> Self {
+        self.cancel_empty_payloads = cancel_empty_payloads;
+        self
+    }
+
+    /// Makes [ApiService::retry_not_ready] retry up to `retries` times, backing off `base` before
+    /// the first retry and doubling the backoff up to `max` for every further retry
+    pub fn with_not_ready_retry(mut self, retries: u32, base: Duration, max: Duration) -> Self {
+        self.not_ready_retries = retries;
+        self.not_ready_retry_base = base;
+        self.not_ready_retry_max = max;
+        self
+    }
+
+    /// Makes `finalize_block` request a new payload id if the one returned by `summarize_block` is
+    /// older than `max_age`, since the EL discards payloads that were not fetched in time. Zero
+    /// always uses the cached id.
+    pub fn with_payload_id_max_age(mut self, max_age: Duration) -> Self {
+        self.payload_id_max_age = max_age;
+        self
+    }
+
+    /// Makes `finalize_block` and `check_blocks` reject payloads whose state root differs from
+    /// the one `verifier` computes, so they are never committed
+    pub fn with_state_root_verifier(mut self, verifier: Arc<dyn StateRootVerifier>) -> Self {
+        self.state_root_verifier = Some(verifier);
+        self
+    }
+
+    /// Makes `commit_block` fetch the head of the EL after every commit and log an error if it is
+    /// not the committed block. A debug check for commit bugs, it costs an extra call per block
+    pub fn with_head_check_after_commit(mut self, enabled: bool) -> Self {
+        self.check_head_after_commit = enabled;
+        self
+    }
+
+    /// Makes a commit wait until the EL reports the committed block as its finalized block, polled
+    /// with the backoff of [ApiService::with_not_ready_retry]. The block is only considered
+    /// committed once the EL confirmed it
+    pub fn with_el_finalized_required(mut self, required: bool) -> Self {
+        self.require_el_finalized = required;
+        self
+    }
+
+    /// Makes a commit fail with [ApiServiceError::ReorgTooDeep] instead of making the block
+    /// canonical if that would unwind more than `max_depth` blocks of the EL
+    pub fn with_max_reorg_depth(mut self, max_depth: u64) -> Self {
+        self.max_reorg_depth = Some(max_depth);
+        self
+    }
+
+    /// Makes `finalize_block` fail with [ApiServiceError::PayloadTooLarge] for payloads larger
+    /// than `max_bytes`, see [payload_size]
+    pub fn with_max_block_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_block_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Makes `summarize_block` fail with [ApiServiceError::EmptyPayload] without asking the EL to
+    /// build a payload while `pending_work` has no transactions and empty payloads are cancelled.
+    /// The EL only gets a plain forkchoice update to keep its head current
+    pub fn with_pending_work(mut self, pending_work: Arc<dyn PendingWork>) -> Self {
+        self.pending_work = Some(pending_work);
+        self
+    }
+
+    /// Returns how often the head of the EL was not the committed block after a commit, only
+    /// checked if enabled with [ApiService::with_head_check_after_commit]
+    pub fn head_divergences(&self) -> u64 {
+        self.head_divergences
+    }
+
+    /// Runs `call` and retries it with exponential backoff while it fails with
+    /// [ApiServiceError::BlockNotReady], e.g. because the EL is still syncing or building the
+    /// payload. Gives up after the configured number of retries and returns the last error.
+    ///
+    /// The calling thread sleeps during the backoff.
+    pub fn retry_not_ready<T>(
+        &mut self,
+        mut call: impl FnMut(&mut Self) -> Result<T, ApiServiceError>,
+    ) -> Result<T, ApiServiceError> {
+        retry_bounded(
+            self.not_ready_retry_base,
+            self.not_ready_retry_max,
+            self.not_ready_retries,
+            |err| matches!(err, ApiServiceError::BlockNotReady),
+            || call(self),
+        )
+    }
+
+    /// Returns the progress of the block this node is building
+    pub fn building_state(&self) -> BlockBuildingState {
+        self.building_state
+    }
+
+    /// Returns the proposals that were finalized or checked but not committed yet, oldest first
+    pub fn pending_proposals(&self) -> Vec<PendingProposal> {
+        self.proposing_payloads.snapshot()
+    }
+
     /// Initialize a new block built on the block with the given previous id and
     /// begin adding batches to it. If no previous id is specified, the current
     /// head will be used.
@@ -290,7 +766,7 @@ impl ApiService {
         let block_id = if let Some(block_id) = previous_id {
             block_id
         } else {
-            let last_block_hash = match self.api.get_block_by_number("latest".to_string()) {
+            let last_block_hash = match self.api.get_block_by_number(BlockTag::Latest) {
                 Ok(x) => {
                     if let Some(execution_block) = x {
                         execution_block.block_hash
@@ -326,6 +802,7 @@ impl ApiService {
             return Err(ApiServiceError::BlockNotReady);
         }
         self.latest_committed_id = Some(block_id);
+        self.building_state = BlockBuildingState::Initialized;
         return Ok(());
     }
 
@@ -337,6 +814,15 @@ impl ApiService {
         accounts: Vec<alloy_primitives::Address>,
     ) -> Result<(), ApiServiceError> {
         tracing::info!(target:"consensus::cl","ApiService::summarize_block");
+        // a failed finalize or an unused payload may be summarized again on the next tick
+        self.building_state.expect(
+            &[
+                BlockBuildingState::Initialized,
+                BlockBuildingState::Summarized,
+                BlockBuildingState::Finalized,
+            ],
+            "summarize_block",
+        )?;
         let previous_id = match self.latest_committed_id {
             Some(id) => id,
             None => {
@@ -345,46 +831,94 @@ impl ApiService {
             }
         };
 
-        let forkchoice_updated = match forkchoice_updated_with_attributes(
-            &self.api,
-            previous_id,
-            index,
-            accounts,
-        ) {
+        // an empty payload would be cancelled anyway, don't make the EL build one
+        if self.cancel_empty_payloads &&
+            self.pending_work.as_ref().is_some_and(|work| !work.has_pending_work())
+        {
+            if self.idle_head != Some(previous_id) {
+                self.update_idle_head(previous_id)?;
+            }
+            tracing::trace!(target:"consensus::cl", head = %previous_id, "ApiService::summarize_block no pending transactions, not building a payload");
+            return Err(ApiServiceError::EmptyPayload);
+        }
+        self.idle_head = None;
+
+        // the EL may accept the attributes without starting a payload yet, ask again a few times
+        // before the block is reported as not ready
+        let mut retries = 0;
+        let payload_id = loop {
+            let forkchoice_updated = match forkchoice_updated_with_attributes(
+                &self.api,
+                previous_id,
+                index,
+                accounts.clone(),
+            ) {
+                Ok(x) => x,
+                Err(e) => {
+                    tracing::error!(target:"consensus::cl","ApiService::summarize_block::forkchoice_updated_with_attributes return(error: {:?})", e);
+                    return Err(ApiServiceError::ApiError(format!(
+                        "forkchoice_updated_with_attributes: {:?}",
+                        e
+                    )));
+                }
+            };
+
+            if !forkchoice_updated.payload_status.status.is_valid() {
+                tracing::error!(target:"consensus::cl","ApiService::summarize_block::forkchoice_updated_with_attributes return(not valid)");
+                return Err(ApiServiceError::BlockNotReady);
+            }
+            match forkchoice_updated.payload_id {
+                Some(payload_id) => break payload_id,
+                None if retries < MISSING_PAYLOAD_ID_RETRIES => {
+                    retries += 1;
+                    tracing::warn!(target:"consensus::cl", retries, "ApiService::summarize_block::forkchoice_updated_with_attributes payload_id is None, retrying");
+                    std::thread::sleep(self.not_ready_retry_base);
+                }
+                None => {
+                    tracing::error!(target:"consensus::cl", retries, "ApiService::summarize_block::forkchoice_updated_with_attributes payload_id is None");
+                    return Err(ApiServiceError::BlockNotReady);
+                }
+            }
+        };
+
+        let building =
+            BuildingPayload { payload_id, index, accounts, requested_at: Instant::now() };
+        self.next_payload_id_pairs.insert(previous_id, building);
+        self.building_state = BlockBuildingState::Summarized;
+        Ok(())
+    }
+
+    /// Sends the EL a forkchoice update to `head` without attributes
+    fn update_idle_head(&mut self, head: B256) -> Result<(), ApiServiceError> {
+        let forkchoice_updated = match forkchoice_updated(&self.api, head) {
             Ok(x) => x,
             Err(e) => {
-                tracing::error!(target:"consensus::cl","ApiService::summarize_block::forkchoice_updated_with_attributes return(error: {:?})", e);
-                return Err(ApiServiceError::ApiError(format!(
-                    "forkchoice_updated_with_attributes: {:?}",
-                    e
-                )));
+                tracing::error!(target:"consensus::cl","ApiService::summarize_block::forkchoice_updated return(error: {:?})", e);
+                return Err(ApiServiceError::ApiError(format!("forkchoice_updated: {:?}", e)));
             }
         };
-
         if !forkchoice_updated.payload_status.status.is_valid() {
-            tracing::error!(target:"consensus::cl","ApiService::summarize_block::forkchoice_updated_with_attributes return(not valid)");
+            tracing::error!(target:"consensus::cl","ApiService::summarize_block::forkchoice_updated return(not valid)");
             return Err(ApiServiceError::BlockNotReady);
-        } else {
-            if let Some(payload_id) = &forkchoice_updated.payload_id {
-                self.next_payload_id_pairs.insert(previous_id, payload_id.clone());
-                return Ok(());
-            } else {
-                tracing::error!(target:"consensus::cl","ApiService::summarize_block::forkchoice_updated_with_attributes payload_id is None");
-                return Err(ApiServiceError::BlockNotReady);
-            }
         }
+        self.idle_head = Some(head);
+        Ok(())
     }
 
     /// Insert the given consensus data into the block and sign it. If this call is successful, the
     /// consensus engine will receive the block afterwards.
+    ///
+    /// If empty payloads are cancelled and the payload has no transactions, the block stays
+    /// summarized so it can be finalized again once the EL included transactions.
     pub fn finalize_block(
         &mut self,
     ) -> Result<(PayloadId, ExecutionPayloadWrapperV2), ApiServiceError> {
         tracing::info!(target:"consensus::cl","ApiService::finalize_block");
-        let (previous_id, payload_id) = match self.latest_committed_id {
+        self.building_state.expect(&[BlockBuildingState::Summarized], "finalize_block")?;
+        let (previous_id, mut building) = match self.latest_committed_id {
             Some(id) => {
-                if let Some(payload_id) = self.next_payload_id_pairs.get(&id) {
-                    (id, payload_id.clone())
+                if let Some(building) = self.next_payload_id_pairs.get(&id) {
+                    (id, building.clone())
                 } else {
                     tracing::error!(target:"consensus::cl","ApiService::finalize_block payload_id is None");
                     return Err(ApiServiceError::BlockNotReady);
@@ -396,6 +930,16 @@ impl ApiService {
             }
         };
 
+        // the EL may have discarded a payload that was not fetched in time, build it again
+        if !self.payload_id_max_age.is_zero() &&
+            building.requested_at.elapsed() > self.payload_id_max_age
+        {
+            tracing::debug!(target:"consensus::cl", payload_id = ?building.payload_id, age = ?building.requested_at.elapsed(), "ApiService::finalize_block payload id is stale, rebuilding");
+            self.summarize_block(building.index, building.accounts)?;
+            building = self.next_payload_id_pairs[&previous_id].clone();
+        }
+        let payload_id = building.payload_id;
+
         let playload = match self.api.get_payload_v2(payload_id) {
             Ok(p) => p,
             Err(e) => {
@@ -406,6 +950,21 @@ impl ApiService {
 
         tracing::info!(target:"consensus::cl","ApiService::finalize_block payload withdrawals: {:?}", playload.execution_payload.withdrawals);
 
+        if self.cancel_empty_payloads &&
+            playload.execution_payload.payload_inner.transactions.is_empty()
+        {
+            tracing::debug!(target:"consensus::cl", ?payload_id, "ApiService::finalize_block payload is empty, cancelling proposal");
+            return Err(ApiServiceError::EmptyPayload);
+        }
+
+        if let Some(max_bytes) = self.max_block_bytes {
+            let size = payload_size(&playload);
+            if size > max_bytes {
+                tracing::warn!(target:"consensus::cl", ?payload_id, size, max_bytes, "ApiService::finalize_block payload exceeds the maximum block size, not proposing it");
+                return Err(ApiServiceError::PayloadTooLarge(size));
+            }
+        }
+
         let block_id = playload.execution_payload.payload_inner.block_hash;
         let last_block_id = playload.execution_payload.payload_inner.parent_hash;
 
@@ -413,13 +972,16 @@ impl ApiService {
         if last_block_id != previous_id {
             panic!("TODO: check parent_hash consistent");
         }
-        self.proposing_payload_pairs.insert(block_id, (payload_id, playload.clone()));
+        self.verify_state_root(&playload)?;
+        self.proposing_payloads.insert(block_id, payload_id, playload.clone());
+        self.building_state = BlockBuildingState::Finalized;
 
         return Ok((payload_id, playload.clone()));
     }
 
     /// Stop adding batches to the current block and abandon it.
     pub fn cancel_block(&mut self) -> Result<(), ApiServiceError> {
+        self.building_state = BlockBuildingState::Idle;
         Ok(())
     }
 
@@ -451,7 +1013,32 @@ impl ApiService {
             tracing::error!(target:"consensus::cl","ApiService::check_blocks::forkchoice_updated return(not valid)");
             return Err(ApiServiceError::BlockNotReady);
         } else {
-            self.proposing_payload_pairs.insert(block_id, (payload_id, playload.clone()));
+            self.verify_state_root(&playload)?;
+            self.proposing_payloads.insert(block_id, payload_id, playload.clone());
+        }
+        Ok(())
+    }
+
+    /// Checks the state root of `payload` with the [StateRootVerifier], if one is set.
+    fn verify_state_root(
+        &self,
+        payload: &ExecutionPayloadWrapperV2,
+    ) -> Result<(), ApiServiceError> {
+        let Some(verifier) = &self.state_root_verifier else { return Ok(()) };
+        let inner = &payload.execution_payload.payload_inner;
+        let computed = verifier.state_root(&payload.execution_payload).map_err(|err| {
+            tracing::error!(target:"consensus::cl", block_hash = ?inner.block_hash, %err, "ApiService::verify_state_root failed to execute payload");
+            ApiServiceError::InvalidStateRoot(format!(
+                "failed to execute block {}: {}",
+                inner.block_hash, err
+            ))
+        })?;
+        if computed != inner.state_root {
+            tracing::error!(target:"consensus::cl", block_hash = ?inner.block_hash, expected = ?inner.state_root, ?computed, "ApiService::verify_state_root state root mismatch");
+            return Err(ApiServiceError::InvalidStateRoot(format!(
+                "block {} has state root {}, executing it locally results in {}",
+                inner.block_hash, inner.state_root, computed
+            )))
         }
         Ok(())
     }
@@ -462,37 +1049,177 @@ impl ApiService {
         block_id: B256,
     ) -> Result<ExecutionPayloadWrapperV2, ApiServiceError> {
         tracing::info!(target:"consensus::cl","ApiService::commit_block");
-        let (_, execution_payload) = match self.proposing_payload_pairs.get(&block_id) {
-            Some(payload) => payload.clone(),
+        let execution_payload = match self.proposing_payloads.payload(&block_id) {
+            Some(payload) => payload,
             None => {
-                return Err(ApiServiceError::BlockNotReady);
+                return Err(ApiServiceError::InvalidState(format!(
+                    "expected finalize_block or check_blocks for block {}, got call commit_block",
+                    block_id
+                )));
+            }
+        };
+
+        self.import_and_canonicalize(execution_payload.clone(), block_id)?;
+        self.proposing_payloads
+            .prune(execution_payload.execution_payload.payload_inner.block_number);
+        if self.check_head_after_commit {
+            self.check_committed_head(block_id);
+        }
+        // the chain head moved, the next block has to be initialized on top of it
+        self.building_state = BlockBuildingState::Idle;
+        Ok(execution_payload)
+    }
+
+    /// Logs an error if the latest block of the EL is not the committed `block_id`, which
+    /// `latest_committed_id` assumes after a commit.
+    fn check_committed_head(&mut self, block_id: B256) {
+        match self.api.get_block_by_number(BlockTag::Latest) {
+            Ok(Some(head)) if head.block_hash == block_id => {}
+            Ok(head) => {
+                self.head_divergences += 1;
+                tracing::error!(target:"consensus::cl", committed = %block_id, el_head = ?head.map(|head| head.block_hash), "ApiService::commit_block the head of the EL is not the committed block");
+            }
+            Err(e) => {
+                tracing::warn!(target:"consensus::cl", committed = %block_id, "ApiService::commit_block failed to fetch the head of the EL: {:?}", e);
+            }
+        }
+    }
+
+    /// Fails with [ApiServiceError::BlockNotReady] until the finalized block of the EL is
+    /// `block_id`.
+    fn check_el_finalized(&mut self, block_id: B256) -> Result<(), ApiServiceError> {
+        match self.api.get_block_by_number(BlockTag::Finalized) {
+            Ok(Some(finalized)) if finalized.block_hash == block_id => Ok(()),
+            Ok(finalized) => {
+                tracing::debug!(target:"consensus::cl", committed = %block_id, el_finalized = ?finalized.map(|block| block.block_hash), "ApiService::import_and_canonicalize waiting for the EL to finalize the block");
+                Err(ApiServiceError::BlockNotReady)
+            }
+            Err(e) => {
+                tracing::error!(target:"consensus::cl","ApiService::import_and_canonicalize::get_block_by_number return(error: {:?})", e);
+                Err(ApiServiceError::ApiError(format!("get_block_by_number: {:?}", e)))
+            }
+        }
+    }
+
+    /// Imports `payload` with `engine_newPayloadV2` and then makes `head` the canonical head with
+    /// `engine_forkchoiceUpdatedV2`.
+    ///
+    /// A payload the EL only `ACCEPTED` without validating it yet is submitted again with the
+    /// backoff of [ApiService::with_not_ready_retry] until the EL reports it as valid.
+    ///
+    /// Fails with [ApiServiceError::BlockNotReady] if either call does not report the block as
+    /// valid, the forkchoice update is never skipped for a valid payload.
+    pub fn import_and_canonicalize(
+        &mut self,
+        payload: ExecutionPayloadWrapperV2,
+        head: B256,
+    ) -> Result<CommitResult, ApiServiceError> {
+        let payload_status = self.retry_not_ready(|service| service.submit_payload(&payload))?;
+        if !payload_status.status.is_valid() {
+            tracing::error!(target:"consensus::cl","ApiService::import_and_canonicalize::new_payload return(not valid)");
+            return Err(ApiServiceError::BlockNotReady);
+        }
+        if payload_status.latest_valid_hash.is_none() {
+            tracing::error!(target:"consensus::cl","ApiService::import_and_canonicalize::new_payload latest_valid_hash is None");
+            return Err(ApiServiceError::BlockNotReady);
+        }
+
+        if let Some(max_depth) = self.max_reorg_depth {
+            let depth = self.reorg_depth(&payload.execution_payload.payload_inner, max_depth)?;
+            if depth > max_depth {
+                tracing::error!(target:"consensus::cl", block = %head, depth, max_depth, "ApiService::import_and_canonicalize refusing to reorg the EL deeper than allowed");
+                return Err(ApiServiceError::ReorgTooDeep(depth));
+            }
+        }
+        let forkchoice_updated = match forkchoice_updated(&self.api, head) {
+            Ok(x) => x,
+            Err(e) => {
+                tracing::error!(target:"consensus::cl","ApiService::import_and_canonicalize::forkchoice_updated return(error: {:?})", e);
+                return Err(ApiServiceError::ApiError(format!("forkchoice_updated: {:?}", e)));
             }
         };
+        if !forkchoice_updated.payload_status.status.is_valid() {
+            tracing::error!(target:"consensus::cl","ApiService::import_and_canonicalize::forkchoice_updated return(not valid)");
+            return Err(ApiServiceError::BlockNotReady);
+        }
+        if self.require_el_finalized {
+            self.retry_not_ready(|service| service.check_el_finalized(head))?;
+        }
+        self.latest_committed_id = Some(head);
+
+        Ok(CommitResult { payload_status, forkchoice_status: forkchoice_updated.payload_status })
+    }
 
-        let payload_status = match new_payload(&self.api, execution_payload.clone()) {
+    /// Imports `payload` with `engine_newPayloadV2`, fails with [ApiServiceError::BlockNotReady]
+    /// while the EL accepts it without validating it.
+    fn submit_payload(
+        &mut self,
+        payload: &ExecutionPayloadWrapperV2,
+    ) -> Result<PayloadStatus, ApiServiceError> {
+        let payload_status = match new_payload(&self.api, payload.clone()) {
             Ok(x) => x,
             Err(e) => {
-                tracing::error!(target:"consensus::cl","ApiService::commit_block::new_payload return(error: {:?})", e);
+                tracing::error!(target:"consensus::cl","ApiService::import_and_canonicalize::new_payload return(error: {:?})", e);
                 return Err(ApiServiceError::ApiError(format!("new_payload: {:?}", e)));
             }
         };
+        if matches!(payload_status.status, PayloadStatusEnum::Accepted) {
+            tracing::debug!(target:"consensus::cl", block = %payload.execution_payload.payload_inner.block_hash, "ApiService::import_and_canonicalize waiting for the EL to validate the accepted payload");
+            return Err(ApiServiceError::BlockNotReady);
+        }
+        Ok(payload_status)
+    }
 
-        if payload_status.status.is_valid() {
-            if let Some(_) = &payload_status.latest_valid_hash {
-                return Ok(execution_payload);
-            } else {
-                tracing::error!(target:"consensus::cl","ApiService::commit_block::new_payload latest_valid_hash is None");
-                return Err(ApiServiceError::BlockNotReady);
+    /// Returns how many blocks of the canonical chain of the EL are unwound when `payload` becomes
+    /// its head, 0 if the payload extends it.
+    ///
+    /// Walks back from the head of the EL to the common ancestor, but at most `max_depth` + 1
+    /// blocks.
+    fn reorg_depth(
+        &self,
+        payload: &ExecutionPayloadV1,
+        max_depth: u64,
+    ) -> Result<u64, ApiServiceError> {
+        let mut old = match self.api.get_block_by_number(BlockTag::Latest) {
+            Ok(Some(block)) => block,
+            Ok(None) => {
+                return Err(ApiServiceError::UnknownBlock("get block return none".to_string()))
             }
-        } else {
-            tracing::error!(target:"consensus::cl","ApiService::commit_block::new_payload return(not valid)");
-            return Err(ApiServiceError::BlockNotReady);
+            Err(e) => {
+                return Err(ApiServiceError::ApiError(format!("get block by number error: {:?}", e)))
+            }
+        };
+        if old.block_hash == payload.parent_hash {
+            return Ok(0);
+        }
+
+        let mut new = self.block_by_hash(payload.parent_hash)?;
+        let mut depth = 0;
+        while new.block_hash != old.block_hash && depth <= max_depth {
+            if new.block_number >= old.block_number {
+                new = self.block_by_hash(new.parent_hash)?;
+            }
+            if old.block_number > new.block_number {
+                old = self.block_by_hash(old.parent_hash)?;
+                depth += 1;
+            }
+        }
+        Ok(depth)
+    }
+
+    /// Returns the block of the EL with the given hash
+    fn block_by_hash(&self, block_hash: B256) -> Result<ExecutionBlock, ApiServiceError> {
+        match self.api.get_block_by_hash(block_hash) {
+            Ok(Some(block)) => Ok(block),
+            Ok(None) => Err(ApiServiceError::UnknownBlock(format!("unknown block {}", block_hash))),
+            Err(e) => Err(ApiServiceError::ApiError(format!("get block by hash error: {:?}", e))),
         }
     }
 
     /// Mark this block as invalid from the perspective of consensus
     pub fn fail_block(&mut self, block_id: B256) -> Result<(), ApiServiceError> {
         tracing::info!(target:"consensus::cl","ApiService::fail_block {}",block_id);
+        self.proposing_payloads.remove(&block_id);
         Ok(())
     }
 
@@ -520,6 +1247,42 @@ impl ApiService {
         return Ok(());
     }
 
+    /// Compares the head of the EL with the last committed block and, if they differ, sends a
+    /// forkchoice update to make the committed block the head again.
+    ///
+    /// Returns the head of the EL if it diverged. Nothing is checked before the first block was
+    /// initialized or committed.
+    pub fn reconcile_head(&mut self) -> Result<Option<B256>, ApiServiceError> {
+        let Some(committed_id) = self.latest_committed_id else { return Ok(None) };
+        let el_head = match self.api.get_block_by_number(BlockTag::Latest) {
+            Ok(Some(block)) => block.block_hash,
+            Ok(None) => {
+                return Err(ApiServiceError::UnknownBlock("get block return none".to_string()))
+            }
+            Err(e) => {
+                return Err(ApiServiceError::ApiError(format!("get block by number error: {:?}", e)))
+            }
+        };
+        if el_head == committed_id {
+            return Ok(None);
+        }
+
+        let forkchoice_updated = forkchoice_updated(&self.api, committed_id)
+            .map_err(|e| ApiServiceError::ApiError(format!("forkchoice_updated: {:?}", e)))?;
+        if !forkchoice_updated.payload_status.status.is_valid() {
+            tracing::warn!(target:"consensus::cl", status = ?forkchoice_updated.payload_status.status, "ApiService::reconcile_head::forkchoice_updated return(not valid)");
+        }
+        Ok(Some(el_head))
+    }
+
+    /// Returns the number of the latest block of the EL, `None` if the EL has no blocks
+    pub fn latest_block_number(&self) -> Result<Option<u64>, ApiServiceError> {
+        match self.api.get_block_by_number(BlockTag::Latest) {
+            Ok(block) => Ok(block.map(|block| block.block_number)),
+            Err(e) => Err(ApiServiceError::ApiError(format!("get_block_by_number: {:?}", e))),
+        }
+    }
+
     pub fn query_validators(
         &mut self,
         contract_address: String,
@@ -534,3 +1297,748 @@ impl ApiService {
         Ok(validator_datas)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Bytes;
+    use reth_primitives::MAINNET;
+    use reth_provider::{
+        test_utils::{MockEthProvider, TestExecutorFactory},
+        BundleStateWithReceipts,
+    };
+    use serde_json::json;
+    use state_root::LocalStateRootVerifier;
+
+    fn service() -> ApiService {
+        ApiService::new(Arc::new(HttpJsonRpcSync::default()))
+    }
+
+    fn assert_invalid_state(res: Result<(), ApiServiceError>, call: &str) {
+        match res {
+            Err(ApiServiceError::InvalidState(msg)) => {
+                assert!(msg.contains(&format!("got call {}", call)), "{}", msg)
+            }
+            other => panic!("expected InvalidState, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn summarize_before_initialize() {
+        let mut service = service();
+        assert_invalid_state(service.summarize_block(1, vec![]), "summarize_block");
+        assert_eq!(service.building_state(), BlockBuildingState::Idle);
+    }
+
+    #[test]
+    fn finalize_before_initialize() {
+        let mut service = service();
+        assert_invalid_state(service.finalize_block().map(|_| ()), "finalize_block");
+    }
+
+    #[test]
+    fn finalize_before_summarize() {
+        let mut service = service();
+        service.latest_committed_id = Some(B256::with_last_byte(1));
+        service.building_state = BlockBuildingState::Initialized;
+        assert_invalid_state(service.finalize_block().map(|_| ()), "finalize_block");
+        assert_eq!(service.building_state(), BlockBuildingState::Initialized);
+    }
+
+    #[test]
+    fn finalize_twice() {
+        let mut service = service();
+        service.building_state = BlockBuildingState::Finalized;
+        assert_invalid_state(service.finalize_block().map(|_| ()), "finalize_block");
+    }
+
+    #[test]
+    fn commit_unknown_block() {
+        let mut service = service();
+        assert_invalid_state(
+            service.commit_block(B256::with_last_byte(1)).map(|_| ()),
+            "commit_block",
+        );
+    }
+
+    fn payload_id() -> PayloadId {
+        PayloadId::new([7; 8])
+    }
+
+    /// An execution layer that builds a single block on top of its head
+    struct MockEl {
+        head: B256,
+        block: B256,
+        /// Names of the engine API calls, in order
+        calls: parking_lot::Mutex<Vec<&'static str>>,
+        /// Number of forkchoice updates answered with `SYNCING` before the EL is ready
+        not_ready: parking_lot::Mutex<u32>,
+        /// Number of forkchoice updates with attributes answered without a payload id
+        missing_payload_ids: parking_lot::Mutex<u32>,
+        /// Head block of every forkchoice update, in order
+        forkchoice_heads: parking_lot::Mutex<Vec<B256>>,
+        /// Number of queries for the finalized block answered with the old head, before the
+        /// built block is reported as finalized
+        unfinalized: parking_lot::Mutex<u32>,
+        /// State root of the built block
+        state_root: B256,
+        /// Canonical chain of the EL, oldest first. Its last block is the latest block instead of
+        /// `head` if set
+        canonical: parking_lot::Mutex<Vec<ExecutionBlock>>,
+        /// Transactions of the built block
+        transactions: Vec<Bytes>,
+        /// Number of new payloads answered with `ACCEPTED` before the EL validated the payload
+        accepted: parking_lot::Mutex<u32>,
+        /// Number of forkchoice updates with attributes
+        payload_builds: parking_lot::Mutex<u32>,
+    }
+
+    impl MockEl {
+        fn new(head: B256, block: B256) -> Self {
+            Self {
+                head,
+                block,
+                calls: Default::default(),
+                not_ready: Default::default(),
+                missing_payload_ids: Default::default(),
+                forkchoice_heads: Default::default(),
+                unfinalized: Default::default(),
+                state_root: B256::ZERO,
+                canonical: Default::default(),
+                transactions: vec![],
+                accepted: Default::default(),
+                payload_builds: Default::default(),
+            }
+        }
+    }
+
+    impl ExecutionApi for MockEl {
+        fn get_block_by_number(&self, tag: BlockTag) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            if let (BlockTag::Latest, Some(latest)) = (tag, self.canonical.lock().last()) {
+                return Ok(Some(*latest))
+            }
+            let mut unfinalized = self.unfinalized.lock();
+            let block_hash = match tag {
+                BlockTag::Finalized if *unfinalized == 0 => self.block,
+                BlockTag::Finalized => {
+                    *unfinalized -= 1;
+                    self.head
+                }
+                _ => self.head,
+            };
+            Ok(Some(ExecutionBlock {
+                block_hash,
+                block_number: 1,
+                parent_hash: B256::ZERO,
+                total_difficulty: U256::ZERO,
+                timestamp: 0,
+            }))
+        }
+
+        fn get_block_by_hash(
+            &self,
+            block_hash: B256,
+        ) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            Ok(self.canonical.lock().iter().find(|block| block.block_hash == block_hash).copied())
+        }
+
+        fn forkchoice_updated_v2(
+            &self,
+            forkchoice_state: ForkchoiceState,
+            payload_attributes: Option<PayloadAttributes>,
+        ) -> Result<ForkchoiceUpdated, ClRpcError> {
+            self.calls.lock().push("forkchoice_updated_v2");
+            self.forkchoice_heads.lock().push(forkchoice_state.head_block_hash);
+            if payload_attributes.is_some() {
+                *self.payload_builds.lock() += 1;
+            }
+            let mut not_ready = self.not_ready.lock();
+            if *not_ready > 0 {
+                *not_ready -= 1;
+                return Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing))
+            }
+            let updated = ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid);
+            let mut missing_payload_ids = self.missing_payload_ids.lock();
+            if payload_attributes.is_some() && *missing_payload_ids > 0 {
+                *missing_payload_ids -= 1;
+                return Ok(updated)
+            }
+            Ok(if payload_attributes.is_some() {
+                updated.with_payload_id(payload_id())
+            } else {
+                updated
+            })
+        }
+
+        fn new_payload_v2(
+            &self,
+            _payload: ExecutionPayloadInputV2,
+        ) -> Result<PayloadStatus, ClRpcError> {
+            self.calls.lock().push("new_payload_v2");
+            let mut accepted = self.accepted.lock();
+            if *accepted > 0 {
+                *accepted -= 1;
+                return Ok(PayloadStatus::from_status(PayloadStatusEnum::Accepted))
+            }
+            Ok(PayloadStatus::new(PayloadStatusEnum::Valid, Some(self.block)))
+        }
+
+        fn get_payload_v2(
+            &self,
+            _payload_id: PayloadId,
+        ) -> Result<ExecutionPayloadWrapperV2, ClRpcError> {
+            let payload_inner = ExecutionPayloadV1 {
+                parent_hash: self.head,
+                fee_recipient: Default::default(),
+                state_root: self.state_root,
+                receipts_root: B256::ZERO,
+                logs_bloom: Default::default(),
+                prev_randao: B256::ZERO,
+                block_number: 2,
+                gas_limit: 30_000_000,
+                gas_used: 21_000,
+                timestamp: 1,
+                extra_data: Default::default(),
+                base_fee_per_gas: U256::from(7),
+                block_hash: self.block,
+                transactions: self.transactions.clone(),
+            };
+            Ok(ExecutionPayloadWrapperV2 {
+                execution_payload: ExecutionPayloadV2 { payload_inner, withdrawals: vec![] },
+                block_value: U256::from(1_000),
+            })
+        }
+
+        fn query_validators(
+            &self,
+            _contract_address: String,
+            _block_number: u64,
+        ) -> Result<Vec<Vec<u8>>, ClRpcError> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn startup_self_test_fails_fast_without_execution_layer() {
+        // nothing listens on the port once the listener is dropped
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let api = crate::create_sync_api_with_addr(addr, &[0; 32]);
+
+        let start = Instant::now();
+        let res = startup_self_test(&api, B256::with_last_byte(1));
+        assert!(matches!(res, Err(ClRpcError::HttpClient(_))), "{:?}", res);
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let el = MockEl::new(B256::with_last_byte(1), B256::with_last_byte(2));
+        startup_self_test(&el, B256::with_last_byte(1)).unwrap();
+        assert_eq!(*el.calls.lock(), vec!["forkchoice_updated_v2"]);
+    }
+
+    #[test]
+    fn pending_proposals_lists_finalized_block() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let proposals = PendingProposals::default();
+        let mut service = ApiService::new(Arc::new(MockEl::new(head, block)))
+            .with_pending_proposals(proposals.clone());
+        assert!(service.pending_proposals().is_empty());
+
+        service.initialize_block(None).unwrap();
+        service.summarize_block(1, vec![]).unwrap();
+        service.finalize_block().unwrap();
+
+        let pending = service.pending_proposals();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].block_hash, block);
+        assert_eq!(pending[0].block_number, 2);
+        assert_eq!(pending[0].payload_id, payload_id());
+        assert_eq!(pending[0].block_value, U256::from(1_000));
+        // the shared handle sees the same proposals
+        assert_eq!(proposals.snapshot(), pending);
+
+        // the proposal can be committed from the shared store
+        service.commit_block(block).unwrap();
+        assert_eq!(service.building_state(), BlockBuildingState::Idle);
+        // and is no longer pending once committed
+        assert!(service.pending_proposals().is_empty());
+        assert!(proposals.snapshot().is_empty());
+    }
+
+    #[test]
+    fn pending_proposals_drop_committed_and_failed_blocks() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let el = MockEl::new(head, block);
+        let proposal = |number: u64, block_hash: B256| {
+            let mut payload = el.get_payload_v2(payload_id()).unwrap();
+            payload.execution_payload.payload_inner.block_number = number;
+            payload.execution_payload.payload_inner.block_hash = block_hash;
+            payload
+        };
+        let (competing, next) = (B256::with_last_byte(3), B256::with_last_byte(4));
+        let mut service = ApiService::new(Arc::new(MockEl::new(head, block)));
+        for (number, block_hash) in [(2, block), (2, competing), (3, next)] {
+            service.check_blocks(payload_id(), proposal(number, block_hash), false).unwrap();
+        }
+        assert_eq!(service.pending_proposals().len(), 3);
+
+        // committing a block drops the proposals of its height
+        service.commit_block(block).unwrap();
+        let pending = service.pending_proposals();
+        assert_eq!(pending.iter().map(|p| p.block_hash).collect::<Vec<_>>(), vec![next]);
+
+        service.fail_block(next).unwrap();
+        assert!(service.pending_proposals().is_empty());
+    }
+
+    #[test]
+    fn commit_imports_then_canonicalizes() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let el = Arc::new(MockEl::new(head, block));
+        let mut service = ApiService::new(el.clone());
+        service.initialize_block(None).unwrap();
+        service.summarize_block(1, vec![]).unwrap();
+        service.finalize_block().unwrap();
+        el.calls.lock().clear();
+
+        service.commit_block(block).unwrap();
+        assert_eq!(*el.calls.lock(), vec!["new_payload_v2", "forkchoice_updated_v2"]);
+        assert_eq!(service.latest_committed_id, Some(block));
+    }
+
+    #[test]
+    fn commit_waits_for_accepted_payload_to_be_validated() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let base = Duration::from_millis(1);
+        let propose = |service: &mut ApiService| {
+            service.initialize_block(None).unwrap();
+            service.summarize_block(1, vec![]).unwrap();
+            service.finalize_block().unwrap();
+        };
+
+        // the EL validates the payload once it is submitted the second time
+        let el = Arc::new(MockEl::new(head, block));
+        *el.accepted.lock() = 1;
+        let mut service = ApiService::new(el.clone()).with_not_ready_retry(3, base, base);
+        propose(&mut service);
+        el.calls.lock().clear();
+        service.commit_block(block).unwrap();
+        assert_eq!(
+            *el.calls.lock(),
+            vec!["new_payload_v2", "new_payload_v2", "forkchoice_updated_v2"]
+        );
+        assert_eq!(service.latest_committed_id, Some(block));
+
+        // the block is not committed while the EL does not validate it
+        let el = Arc::new(MockEl::new(head, block));
+        *el.accepted.lock() = 10;
+        let mut service = ApiService::new(el.clone()).with_not_ready_retry(3, base, base);
+        propose(&mut service);
+        el.calls.lock().clear();
+        assert!(matches!(service.commit_block(block), Err(ApiServiceError::BlockNotReady)));
+        assert_eq!(*el.calls.lock(), vec!["new_payload_v2"; 4]);
+        assert_eq!(service.latest_committed_id, None);
+    }
+
+    #[test]
+    fn deserialize_responses_with_unknown_fields() {
+        let mock = MockEl::new(B256::with_last_byte(1), B256::with_last_byte(2));
+        let wrapper = mock.get_payload_v2(payload_id()).unwrap();
+        let mut value = serde_json::to_value(&wrapper).unwrap();
+        value["shouldOverrideBuilder"] = json!(false);
+        value["executionPayload"]["blobGasUsed"] = json!("0x0");
+        let decoded: ExecutionPayloadWrapperV2 = serde_json::from_value(value).unwrap();
+        assert_eq!(decoded, wrapper);
+
+        let block = mock.get_block_by_number(BlockTag::Latest).unwrap().unwrap();
+        let mut value = serde_json::to_value(block).unwrap();
+        value["withdrawalsRoot"] = json!(B256::ZERO);
+        let decoded: ExecutionBlock = serde_json::from_value(value).unwrap();
+        assert_eq!(decoded, block);
+    }
+
+    #[test]
+    fn empty_payload_handling() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+
+        // proceeds by default
+        let mut service = ApiService::new(Arc::new(MockEl::new(head, block)));
+        service.initialize_block(None).unwrap();
+        service.summarize_block(1, vec![]).unwrap();
+        let (_, payload) = service.finalize_block().unwrap();
+        assert!(payload.execution_payload.payload_inner.transactions.is_empty());
+        assert_eq!(service.building_state(), BlockBuildingState::Finalized);
+
+        let mut service =
+            ApiService::new(Arc::new(MockEl::new(head, block))).with_cancel_empty_payloads(true);
+        service.initialize_block(None).unwrap();
+        service.summarize_block(1, vec![]).unwrap();
+        assert!(matches!(service.finalize_block(), Err(ApiServiceError::EmptyPayload)));
+        assert!(service.pending_proposals().is_empty());
+        // the block can be summarized and finalized again on the next tick
+        assert_eq!(service.building_state(), BlockBuildingState::Summarized);
+        service.summarize_block(1, vec![]).unwrap();
+    }
+
+    /// Transaction pool that is empty unless told otherwise
+    #[derive(Default)]
+    struct MockPool(std::sync::atomic::AtomicBool);
+
+    impl PendingWork for MockPool {
+        fn has_pending_work(&self) -> bool {
+            self.0.load(std::sync::atomic::Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn stops_building_payloads_while_idle() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let el = Arc::new(MockEl::new(head, block));
+        let pool = Arc::new(MockPool::default());
+        let mut service = ApiService::new(el.clone())
+            .with_cancel_empty_payloads(true)
+            .with_pending_work(pool.clone());
+        service.initialize_block(None).unwrap();
+        el.calls.lock().clear();
+
+        // only the head is kept current while the pool is empty
+        for _ in 0..3 {
+            assert!(matches!(
+                service.summarize_block(1, vec![]),
+                Err(ApiServiceError::EmptyPayload)
+            ));
+        }
+        assert_eq!(*el.payload_builds.lock(), 0);
+        assert_eq!(*el.calls.lock(), vec!["forkchoice_updated_v2"]);
+        assert_eq!(*el.forkchoice_heads.lock().last().unwrap(), head);
+
+        pool.0.store(true, std::sync::atomic::Ordering::Relaxed);
+        service.summarize_block(1, vec![]).unwrap();
+        assert_eq!(*el.payload_builds.lock(), 1);
+        assert_eq!(service.building_state(), BlockBuildingState::Summarized);
+
+        // payloads are always built unless empty payloads are cancelled
+        let el = Arc::new(MockEl::new(head, block));
+        let mut service =
+            ApiService::new(el.clone()).with_pending_work(Arc::new(MockPool::default()));
+        service.initialize_block(None).unwrap();
+        service.summarize_block(1, vec![]).unwrap();
+        assert_eq!(*el.payload_builds.lock(), 1);
+    }
+
+    #[test]
+    fn payload_size_is_encoded_length() {
+        let payload_inner = ExecutionPayloadV1 {
+            parent_hash: B256::ZERO,
+            fee_recipient: Default::default(),
+            state_root: B256::ZERO,
+            receipts_root: B256::ZERO,
+            logs_bloom: Default::default(),
+            prev_randao: B256::ZERO,
+            block_number: 0,
+            gas_limit: 0,
+            gas_used: 0,
+            timestamp: 0,
+            extra_data: Default::default(),
+            base_fee_per_gas: U256::ZERO,
+            block_hash: B256::ZERO,
+            transactions: vec![Bytes::from(vec![0u8; 100])],
+        };
+        let mut payload = ExecutionPayloadWrapperV2 {
+            execution_payload: ExecutionPayloadV2 { payload_inner, withdrawals: vec![] },
+            block_value: U256::ZERO,
+        };
+        // 4 hashes, the fee recipient and the bloom take 412 bytes, the 6 zero numbers and the
+        // empty extra data 7, the block hash 33, the transaction list 104, the empty withdrawals
+        // 1, and the list header 3
+        assert_eq!(payload_size(&payload), 560);
+
+        // a zero withdrawal encodes to 25 bytes
+        payload.execution_payload.withdrawals = vec![Withdrawal::default(); 2];
+        assert_eq!(payload_size(&payload), 610);
+    }
+
+    #[test]
+    fn refuses_payload_larger_than_max_block_bytes() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let el = || {
+            let mut el = MockEl::new(head, block);
+            el.transactions = vec![Bytes::from(vec![0u8; 100]); 3];
+            Arc::new(el)
+        };
+        let size = payload_size(&el().get_payload_v2(PayloadId::new([0; 8])).unwrap());
+        assert!(size > 300);
+
+        let mut service = ApiService::new(el()).with_max_block_bytes(size - 1);
+        service.initialize_block(None).unwrap();
+        service.summarize_block(1, vec![]).unwrap();
+        assert!(
+            matches!(service.finalize_block(), Err(ApiServiceError::PayloadTooLarge(s)) if s == size)
+        );
+        assert!(service.pending_proposals().is_empty());
+
+        let mut service = ApiService::new(el()).with_max_block_bytes(size);
+        service.initialize_block(None).unwrap();
+        service.summarize_block(1, vec![]).unwrap();
+        service.finalize_block().unwrap();
+        assert_eq!(service.building_state(), BlockBuildingState::Finalized);
+    }
+
+    #[test]
+    fn rebuilds_stale_payload_id() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let el = Arc::new(MockEl::new(head, block));
+        let mut service =
+            ApiService::new(el.clone()).with_payload_id_max_age(Duration::from_millis(20));
+        service.initialize_block(None).unwrap();
+
+        // a fresh payload id is used as is
+        service.summarize_block(1, vec![]).unwrap();
+        el.calls.lock().clear();
+        service.finalize_block().unwrap();
+        assert_eq!(*el.calls.lock(), vec!["get_payload_v2"]);
+
+        // a stale payload id is requested again before fetching the payload
+        service.summarize_block(1, vec![]).unwrap();
+        std::thread::sleep(Duration::from_millis(30));
+        el.calls.lock().clear();
+        let (id, payload) = service.finalize_block().unwrap();
+        assert_eq!(*el.calls.lock(), vec!["forkchoice_updated_v2", "get_payload_v2"]);
+        assert_eq!(*el.forkchoice_heads.lock().last().unwrap(), head);
+        assert_eq!(id, payload_id());
+        assert_eq!(payload.execution_payload.payload_inner.block_hash, block);
+        assert_eq!(service.building_state(), BlockBuildingState::Finalized);
+    }
+
+    #[test]
+    fn reports_el_head_diverging_after_commit() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let commit = |service: &mut ApiService| {
+            service.initialize_block(None).unwrap();
+            service.summarize_block(1, vec![]).unwrap();
+            service.finalize_block().unwrap();
+            service.commit_block(block).unwrap();
+        };
+
+        // the mock EL keeps reporting its old head after the commit
+        let el = Arc::new(MockEl::new(head, block));
+        let mut service = ApiService::new(el.clone()).with_head_check_after_commit(true);
+        commit(&mut service);
+        assert_eq!(service.head_divergences(), 1);
+        assert_eq!(service.latest_committed_id, Some(block));
+
+        // not checked by default
+        let mut service = ApiService::new(el);
+        commit(&mut service);
+        assert_eq!(service.head_divergences(), 0);
+    }
+
+    #[test]
+    fn commit_waits_for_el_finalization() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let base = Duration::from_millis(1);
+        let propose = |service: &mut ApiService| {
+            service.initialize_block(None).unwrap();
+            service.summarize_block(1, vec![]).unwrap();
+            service.finalize_block().unwrap();
+        };
+
+        // the EL finalizes the block on the third query
+        let el = Arc::new(MockEl::new(head, block));
+        *el.unfinalized.lock() = 2;
+        let mut service = ApiService::new(el.clone())
+            .with_el_finalized_required(true)
+            .with_not_ready_retry(3, base, base);
+        propose(&mut service);
+        service.commit_block(block).unwrap();
+        assert_eq!(*el.unfinalized.lock(), 0);
+        assert_eq!(service.latest_committed_id, Some(block));
+
+        // the block is not committed while the EL does not finalize it
+        let el = Arc::new(MockEl::new(head, block));
+        *el.unfinalized.lock() = 10;
+        let mut service = ApiService::new(el.clone())
+            .with_el_finalized_required(true)
+            .with_not_ready_retry(3, base, base);
+        propose(&mut service);
+        assert!(matches!(service.commit_block(block), Err(ApiServiceError::BlockNotReady)));
+        assert_eq!(*el.unfinalized.lock(), 6);
+        assert_ne!(service.latest_committed_id, Some(block));
+
+        // not required by default
+        let el = Arc::new(MockEl::new(head, block));
+        *el.unfinalized.lock() = 10;
+        let mut service = ApiService::new(el.clone());
+        propose(&mut service);
+        service.commit_block(block).unwrap();
+        assert_eq!(*el.unfinalized.lock(), 10);
+    }
+
+    #[test]
+    fn refuses_reorg_deeper_than_max() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let el = Arc::new(MockEl::new(head, block));
+        let mut service = ApiService::new(el.clone()).with_max_reorg_depth(2);
+        service.initialize_block(None).unwrap();
+        service.summarize_block(1, vec![]).unwrap();
+        service.finalize_block().unwrap();
+
+        // meanwhile the EL followed another fork up to block 4, the built block on top of block 1
+        // would unwind three blocks
+        let hash =
+            |number: u8| if number == 1 { head } else { B256::with_last_byte(0x10 + number) };
+        *el.canonical.lock() = (1..=4)
+            .map(|number| ExecutionBlock {
+                block_hash: hash(number),
+                block_number: number as u64,
+                parent_hash: hash(number - 1),
+                total_difficulty: U256::ZERO,
+                timestamp: 0,
+            })
+            .collect();
+        let res = service.commit_block(block);
+        assert!(matches!(res, Err(ApiServiceError::ReorgTooDeep(3))), "{:?}", res.err());
+        assert!(!el.forkchoice_heads.lock().contains(&block));
+        assert_ne!(service.latest_committed_id, Some(block));
+
+        // a reorg within the limit is committed
+        service.max_reorg_depth = Some(3);
+        service.commit_block(block).unwrap();
+        assert_eq!(el.forkchoice_heads.lock().last(), Some(&block));
+    }
+
+    #[test]
+    fn retries_missing_payload_id() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let el = Arc::new(MockEl::new(head, block));
+        let mut service = ApiService::new(el.clone()).with_not_ready_retry(
+            0,
+            Duration::from_millis(1),
+            Duration::from_millis(1),
+        );
+        service.initialize_block(None).unwrap();
+
+        // the first forkchoice update starts no payload, the second one does
+        *el.missing_payload_ids.lock() = 1;
+        el.calls.lock().clear();
+        service.summarize_block(1, vec![]).unwrap();
+        assert_eq!(*el.calls.lock(), vec!["forkchoice_updated_v2", "forkchoice_updated_v2"]);
+        assert_eq!(service.building_state(), BlockBuildingState::Summarized);
+        let (id, _) = service.finalize_block().unwrap();
+        assert_eq!(id, payload_id());
+
+        // gives up once the retries are used up
+        let mut service = ApiService::new(el.clone());
+        service.initialize_block(None).unwrap();
+        *el.missing_payload_ids.lock() = MISSING_PAYLOAD_ID_RETRIES + 1;
+        el.calls.lock().clear();
+        assert!(matches!(service.summarize_block(1, vec![]), Err(ApiServiceError::BlockNotReady)));
+        assert_eq!(el.calls.lock().len(), MISSING_PAYLOAD_ID_RETRIES as usize + 1);
+        assert_eq!(service.building_state(), BlockBuildingState::Initialized);
+    }
+
+    #[test]
+    fn retries_block_not_ready_with_bounded_backoff() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let el = Arc::new(MockEl::new(head, block));
+        let (base, max) = (Duration::from_millis(10), Duration::from_millis(20));
+        let mut service = ApiService::new(el.clone()).with_not_ready_retry(3, base, max);
+
+        // ready after two retries, backing off 10ms and 20ms
+        *el.not_ready.lock() = 2;
+        let start = Instant::now();
+        service.retry_not_ready(|service| service.initialize_block(None)).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(30));
+        assert_eq!(el.calls.lock().len(), 3);
+        assert_eq!(service.building_state(), BlockBuildingState::Initialized);
+
+        // gives up once the retries are used up, the backoff is capped at 20ms
+        el.calls.lock().clear();
+        *el.not_ready.lock() = 10;
+        let start = Instant::now();
+        let res = service.retry_not_ready(|service| service.initialize_block(None));
+        assert!(matches!(res, Err(ApiServiceError::BlockNotReady)));
+        assert_eq!(el.calls.lock().len(), 4);
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(50) && elapsed < Duration::from_secs(1));
+
+        // other errors are not retried
+        service.cancel_block().unwrap();
+        el.calls.lock().clear();
+        assert_invalid_state(
+            service.retry_not_ready(|service| service.summarize_block(1, vec![])),
+            "summarize_block",
+        );
+        assert!(el.calls.lock().is_empty());
+    }
+
+    #[test]
+    fn reconcile_diverged_head() {
+        let (el_head, committed) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let el = Arc::new(MockEl::new(el_head, committed));
+        let mut service = ApiService::new(el.clone());
+        // nothing committed yet
+        assert_eq!(service.reconcile_head().unwrap(), None);
+        assert!(el.calls.lock().is_empty());
+
+        service.latest_committed_id = Some(el_head);
+        assert_eq!(service.reconcile_head().unwrap(), None);
+        assert!(el.calls.lock().is_empty());
+
+        // the EL reports a head the CL never committed
+        service.latest_committed_id = Some(committed);
+        assert_eq!(service.reconcile_head().unwrap(), Some(el_head));
+        assert_eq!(*el.forkchoice_heads.lock(), vec![committed]);
+        assert_eq!(service.latest_committed_id, Some(committed));
+    }
+
+    #[test]
+    fn cancel_resets_state() {
+        let mut service = service();
+        service.building_state = BlockBuildingState::Summarized;
+        service.cancel_block().unwrap();
+        assert_invalid_state(service.summarize_block(1, vec![]), "summarize_block");
+    }
+
+    #[test]
+    fn rejects_payload_with_mismatching_state_root() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        // the mock provider computes a zero state root for every executed block
+        let verifier = || {
+            let executor_factory = TestExecutorFactory::new(MAINNET.clone());
+            executor_factory.extend(vec![BundleStateWithReceipts::default()]);
+            Arc::new(LocalStateRootVerifier::new(MockEthProvider::default(), executor_factory))
+        };
+
+        let el = MockEl { state_root: B256::with_last_byte(9), ..MockEl::new(head, block) };
+        let mut service = ApiService::new(Arc::new(el)).with_state_root_verifier(verifier());
+        service.initialize_block(None).unwrap();
+        service.summarize_block(1, vec![]).unwrap();
+        let res = service.finalize_block();
+        assert!(matches!(res, Err(ApiServiceError::InvalidStateRoot(_))), "{:?}", res.err());
+        assert!(service.pending_proposals().is_empty());
+        assert_invalid_state(service.commit_block(block).map(|_| ()), "commit_block");
+
+        // a matching state root is proposed and committed
+        let mut service = ApiService::new(Arc::new(MockEl::new(head, block)))
+            .with_state_root_verifier(verifier());
+        service.initialize_block(None).unwrap();
+        service.summarize_block(1, vec![]).unwrap();
+        service.finalize_block().unwrap();
+        service.commit_block(block).unwrap();
+    }
+
+    #[test]
+    fn keeps_last_forkchoice_updates() {
+        let history = ForkchoiceHistory::new(3);
+        let el = MockEl::new(B256::with_last_byte(1), B256::with_last_byte(2));
+        let mut service = ApiService::new(Arc::new(el)).with_forkchoice_history(history.clone());
+        for block in 1..=5 {
+            service.sync_block(B256::with_last_byte(block)).unwrap();
+        }
+
+        let records = history.snapshot();
+        let heads = records.iter().map(|record| record.head_block_hash).collect::<Vec<_>>();
+        assert_eq!(heads, [3, 4, 5].map(B256::with_last_byte));
+        assert!(records.iter().all(|record| record.status.as_deref() == Some("VALID")));
+        assert!(records.windows(2).all(|pair| pair[0].timestamp <= pair[1].timestamp));
+    }
+}
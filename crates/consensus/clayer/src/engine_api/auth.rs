@@ -1,13 +1,25 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use jsonwebtoken::{encode, get_current_timestamp, Algorithm, EncodingKey, Header};
+use parking_lot::Mutex;
 use rand::Rng;
+use reth_metrics::{metrics::Counter, Metrics};
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
 /// Default algorithm used for JWT token signing.
 const DEFAULT_ALGORITHM: Algorithm = Algorithm::HS256;
 
+/// Execution layers reject tokens whose `iat` is further than this from their clock.
+pub const TOKEN_ACCEPTANCE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Default interval after which a new token is minted.
+pub const DEFAULT_TOKEN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Upper bound of the refresh interval, leaves headroom within [TOKEN_ACCEPTANCE_WINDOW] for
+/// clock drift between the consensus and the execution layer.
+pub const MAX_TOKEN_REFRESH_INTERVAL: Duration = Duration::from_secs(50);
+
 /// JWT secret length in bytes.
 pub const JWT_SECRET_LENGTH: usize = 32;
 
@@ -68,16 +80,43 @@ pub fn strip_prefix(s: &str) -> &str {
     }
 }
 
+/// Engine API authentication metrics
+#[derive(Metrics)]
+#[metrics(scope = "consensus.clayer.engine_api")]
+struct AuthMetrics {
+    /// Number of JWT tokens minted for engine API requests
+    tokens_minted: Counter,
+}
+
 /// Contains the JWT secret and claims parameters.
 pub struct Auth {
     key: EncodingKey,
     id: Option<String>,
     clv: Option<String>,
+    /// How long a minted token is reused
+    refresh_interval: Duration,
+    /// The last minted token and its `iat`
+    token: Mutex<Option<(String, u64)>>,
+    metrics: AuthMetrics,
 }
 
 impl Auth {
     pub fn new(secret: JwtKey, id: Option<String>, clv: Option<String>) -> Self {
-        Self { key: EncodingKey::from_secret(secret.as_bytes()), id, clv }
+        Self {
+            key: EncodingKey::from_secret(secret.as_bytes()),
+            id,
+            clv,
+            refresh_interval: DEFAULT_TOKEN_REFRESH_INTERVAL,
+            token: Mutex::new(None),
+            metrics: AuthMetrics::default(),
+        }
+    }
+
+    /// Sets how long a minted token is reused for further requests, capped at
+    /// [MAX_TOKEN_REFRESH_INTERVAL]. A zero interval mints a token for every request.
+    pub fn with_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval.min(MAX_TOKEN_REFRESH_INTERVAL);
+        self
     }
 
     /// Create a new `Auth` struct given the path to the file containing the hex
@@ -102,10 +141,26 @@ impl Auth {
             })
     }
 
-    /// Generate a JWT token with `claims.iat` set to current time.
+    /// Returns a JWT token that was minted less than the refresh interval ago, minting a new one
+    /// with `claims.iat` set to current time if needed.
     pub fn generate_token(&self) -> Result<String, Error> {
-        let claims = self.generate_claims_at_timestamp();
-        self.generate_token_with_claims(&claims)
+        self.token_at(get_current_timestamp())
+    }
+
+    /// Returns the token to use at `now`, in seconds since UNIX_EPOCH.
+    fn token_at(&self, now: u64) -> Result<String, Error> {
+        let mut token = self.token.lock();
+        if let Some((cached, iat)) = token.as_ref() {
+            // a clock that went backwards could make the token look issued in the future
+            if *iat <= now && now - iat < self.refresh_interval.as_secs() {
+                return Ok(cached.clone());
+            }
+        }
+        let claims = self.generate_claims_at_timestamp(now);
+        let minted = self.generate_token_with_claims(&claims)?;
+        self.metrics.tokens_minted.increment(1);
+        *token = Some((minted.clone(), now));
+        Ok(minted)
     }
 
     /// Generate a JWT token with the given claims.
@@ -114,9 +169,9 @@ impl Auth {
         Ok(encode(&header, claims, &self.key)?)
     }
 
-    /// Generate a `Claims` struct with `iat` set to current time
-    fn generate_claims_at_timestamp(&self) -> Claims {
-        Claims { iat: get_current_timestamp(), id: self.id.clone(), clv: self.clv.clone() }
+    /// Generate a `Claims` struct with the given `iat`
+    fn generate_claims_at_timestamp(&self, iat: u64) -> Claims {
+        Claims { iat, id: self.id.clone(), clv: self.clv.clone() }
     }
 
     /// Validate a JWT token given the secret key and return the originally signed `TokenData`.
@@ -147,3 +202,47 @@ pub struct Claims {
     /// Optional client version for the CL node.
     clv: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refreshes_tokens_before_expiry() {
+        let secret = JwtKey::random();
+        // an execution layer accepting tokens issued within the acceptance window
+        let verify = |token: &str, now: u64| {
+            let claims = Auth::validate_token(token, &secret).unwrap().claims;
+            now.abs_diff(claims.iat) <= TOKEN_ACCEPTANCE_WINDOW.as_secs()
+        };
+        let start = 1_700_000_000;
+
+        let auth =
+            Auth::new(secret.clone(), None, None).with_refresh_interval(Duration::from_secs(30));
+        let first = auth.token_at(start).unwrap();
+        assert_eq!(auth.token_at(start + 29).unwrap(), first);
+        assert_ne!(auth.token_at(start + 30).unwrap(), first);
+        for now in start..start + 300 {
+            assert!(verify(&auth.token_at(now).unwrap(), now));
+        }
+
+        // the interval is capped below the acceptance window
+        let auth =
+            Auth::new(secret.clone(), None, None).with_refresh_interval(Duration::from_secs(600));
+        for now in start..start + 300 {
+            assert!(verify(&auth.token_at(now).unwrap(), now));
+        }
+
+        // a zero interval mints a token for every request
+        let auth = Auth::new(secret.clone(), None, None).with_refresh_interval(Duration::ZERO);
+        auth.token_at(start).unwrap();
+        let claims = Auth::validate_token(&auth.token_at(start + 1).unwrap(), &secret).unwrap();
+        assert_eq!(claims.claims.iat, start + 1);
+
+        // a token is never reused after the clock went backwards
+        let auth = Auth::new(secret.clone(), None, None);
+        auth.token_at(start).unwrap();
+        let claims = Auth::validate_token(&auth.token_at(start - 10).unwrap(), &secret).unwrap();
+        assert_eq!(claims.claims.iat, start - 10);
+    }
+}
@@ -0,0 +1,159 @@
+//! History of the forkchoice updates sent to the execution layer.
+//!
+//! The last forkchoice updates are kept in a bounded ring buffer, so the head the consensus layer
+//! drove the execution layer to can be traced back after an incident without enabling logs.
+
+use super::{
+    json_structures::BlockTag, ClRpcError, ExecutionApi, ExecutionBlock, ExecutionPayloadWrapperV2,
+};
+use alloy_primitives::B256;
+use reth_rpc_types::engine::{
+    ExecutionPayloadInputV2, ForkchoiceState, ForkchoiceUpdated, PayloadAttributes, PayloadId,
+    PayloadStatus,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+};
+
+/// Number of forkchoice updates kept by default
+pub const DEFAULT_FORKCHOICE_HISTORY_SIZE: usize = 64;
+
+/// A forkchoice update sent to the execution layer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkchoiceRecord {
+    /// Unix timestamp in milliseconds the update was sent at
+    pub timestamp: i64,
+    /// The head block sent to the execution layer
+    pub head_block_hash: B256,
+    /// The safe block sent to the execution layer
+    pub safe_block_hash: B256,
+    /// The finalized block sent to the execution layer
+    pub finalized_block_hash: B256,
+    /// Whether payload attributes were sent to start building a block
+    pub with_attributes: bool,
+    /// The payload status the execution layer answered with, unset if the call failed
+    pub status: Option<String>,
+    /// Why the call failed, unset if the execution layer answered
+    pub error: Option<String>,
+}
+
+/// The most recent forkchoice updates, oldest first, shared so they can be inspected from outside
+/// of the consensus thread.
+#[derive(Debug, Clone)]
+pub struct ForkchoiceHistory {
+    inner: Arc<parking_lot::Mutex<VecDeque<ForkchoiceRecord>>>,
+    capacity: usize,
+}
+
+impl Default for ForkchoiceHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_FORKCHOICE_HISTORY_SIZE)
+    }
+}
+
+impl ForkchoiceHistory {
+    /// Creates a history keeping the last `capacity` forkchoice updates, zero keeps none.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(parking_lot::Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Records a forkchoice update, dropping the oldest one once the history is full.
+    pub fn record(&self, record: ForkchoiceRecord) {
+        if self.capacity == 0 {
+            return
+        }
+        let mut records = self.inner.lock();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Returns the recorded forkchoice updates, oldest first.
+    pub fn snapshot(&self) -> Vec<ForkchoiceRecord> {
+        self.inner.lock().iter().cloned().collect()
+    }
+}
+
+/// Records every forkchoice update sent through the wrapped [ExecutionApi] in a
+/// [ForkchoiceHistory].
+pub(crate) struct ForkchoiceRecorder {
+    api: Arc<dyn ExecutionApi>,
+    history: ForkchoiceHistory,
+}
+
+impl ForkchoiceRecorder {
+    pub(crate) fn new(api: Arc<dyn ExecutionApi>, history: ForkchoiceHistory) -> Self {
+        Self { api, history }
+    }
+}
+
+impl ExecutionApi for ForkchoiceRecorder {
+    fn get_block_by_number(&self, tag: BlockTag) -> Result<Option<ExecutionBlock>, ClRpcError> {
+        self.api.get_block_by_number(tag)
+    }
+
+    fn get_block_by_hash(&self, block_hash: B256) -> Result<Option<ExecutionBlock>, ClRpcError> {
+        self.api.get_block_by_hash(block_hash)
+    }
+
+    fn forkchoice_updated_v2(
+        &self,
+        forkchoice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+    ) -> Result<ForkchoiceUpdated, ClRpcError> {
+        let timestamp = chrono::prelude::Local::now().timestamp_millis();
+        let with_attributes = payload_attributes.is_some();
+        let result = self.api.forkchoice_updated_v2(forkchoice_state, payload_attributes);
+        let (status, error) = match &result {
+            Ok(updated) => (Some(updated.payload_status.status.as_str().to_string()), None),
+            Err(err) => (None, Some(format!("{err:?}"))),
+        };
+        self.history.record(ForkchoiceRecord {
+            timestamp,
+            head_block_hash: forkchoice_state.head_block_hash,
+            safe_block_hash: forkchoice_state.safe_block_hash,
+            finalized_block_hash: forkchoice_state.finalized_block_hash,
+            with_attributes,
+            status,
+            error,
+        });
+        result
+    }
+
+    fn new_payload_v2(
+        &self,
+        payload: ExecutionPayloadInputV2,
+    ) -> Result<PayloadStatus, ClRpcError> {
+        self.api.new_payload_v2(payload)
+    }
+
+    fn get_payload_v2(
+        &self,
+        payload_id: PayloadId,
+    ) -> Result<ExecutionPayloadWrapperV2, ClRpcError> {
+        self.api.get_payload_v2(payload_id)
+    }
+
+    fn query_validators(
+        &self,
+        contract_address: String,
+        block_number: u64,
+    ) -> Result<Vec<Vec<u8>>, ClRpcError> {
+        self.api.query_validators(contract_address, block_number)
+    }
+
+    fn exchange_capabilities(&self) -> Result<Option<HashSet<String>>, ClRpcError> {
+        self.api.exchange_capabilities()
+    }
+
+    fn client_version(&self) -> Result<Option<String>, ClRpcError> {
+        self.api.client_version()
+    }
+}
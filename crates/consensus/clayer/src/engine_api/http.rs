@@ -1,5 +1,5 @@
 use super::auth::Auth;
-use super::json_structures::{JsonRequestBody, JsonResponseBody};
+use super::json_structures::{correlate_batch, BlockTag, JsonRequestBody, JsonResponseBody};
 use super::*;
 use reqwest::header::CONTENT_TYPE;
 pub use reqwest::Client;
@@ -10,14 +10,21 @@ use reth_rpc_types::engine::{
 use serde::de::DeserializeOwned;
 use serde_json::json;
 use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
 use url::Url;
 
+/// Default number of requests sent to the execution layer at the same time
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 32;
+
 pub struct HttpJsonRpc {
     pub client: Client,
     pub url: Url,
     pub execution_timeout_multiplier: u32,
     auth: Option<Auth>,
+    /// Permits of the requests in flight, shared by all requests of the client
+    in_flight: Arc<Semaphore>,
 }
 
 impl Default for HttpJsonRpc {
@@ -33,6 +40,7 @@ impl HttpJsonRpc {
             url,
             execution_timeout_multiplier: execution_timeout_multiplier.unwrap_or(1),
             auth: None,
+            in_flight: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
         })
     }
 
@@ -46,15 +54,36 @@ impl HttpJsonRpc {
             url,
             execution_timeout_multiplier: execution_timeout_multiplier.unwrap_or(1),
             auth: Some(auth),
+            in_flight: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
         })
     }
 
+    /// Sends at most `max` requests to the execution layer at the same time, at least one.
+    ///
+    /// Further requests wait in line until a request in flight completed, a batch counts as a
+    /// single request.
+    pub fn with_max_concurrent_requests(mut self, max: usize) -> Self {
+        self.in_flight = Arc::new(Semaphore::new(max.max(1)));
+        self
+    }
+
+    /// Talks HTTP/2 to the execution layer if `enabled`, see [HttpJsonRpcSync::with_http2].
+    ///
+    /// [HttpJsonRpcSync::with_http2]: super::http_blocking::HttpJsonRpcSync::with_http2
+    pub fn with_http2(mut self, enabled: bool) -> Result<Self, ClRpcError> {
+        if enabled {
+            self.client = Client::builder().http2_prior_knowledge().build()?;
+        }
+        Ok(self)
+    }
+
     pub async fn rpc_request<D: DeserializeOwned>(
         &self,
         method: &str,
         params: serde_json::Value,
         timeout: Duration,
     ) -> Result<D, ClRpcError> {
+        let _permit = self.acquire_permit().await?;
         let body =
             JsonRequestBody { jsonrpc: JSONRPC_VERSION, method, params, id: json!(STATIC_ID) };
 
@@ -74,16 +103,54 @@ impl HttpJsonRpc {
 
         // println!("===={:?}", body);
 
-        match (body.result, body.error) {
-            (result, None) => serde_json::from_value(result).map_err(Into::into),
-            (_, Some(error)) => {
-                if error.message.contains(EIP155_ERROR_STR) {
-                    Err(ClRpcError::Eip155Failure)
-                } else {
-                    Err(ClRpcError::ServerMessage { code: error.code, message: error.message })
-                }
-            }
-        }
+        serde_json::from_value(body.into_result()?).map_err(Into::into)
+    }
+
+    /// Sends the `(method, params)` `requests` as a single JSON-RPC batch.
+    ///
+    /// Returns the result of every request in the order of `requests`, responses are matched to
+    /// their request by id, see [correlate_batch]. Fails if the batch could not be sent or the
+    /// response contains ids that were not requested.
+    pub async fn rpc_batch_request(
+        &self,
+        requests: &[(&str, serde_json::Value)],
+        timeout: Duration,
+    ) -> Result<Vec<Result<serde_json::Value, ClRpcError>>, ClRpcError> {
+        let _permit = self.acquire_permit().await?;
+        let body: Vec<_> = requests
+            .iter()
+            .enumerate()
+            .map(|(index, (method, params))| JsonRequestBody {
+                jsonrpc: JSONRPC_VERSION,
+                method,
+                params: params.clone(),
+                id: json!(index + 1),
+            })
+            .collect();
+
+        let mut request = self
+            .client
+            .post(self.url.clone())
+            .timeout(timeout)
+            .header(CONTENT_TYPE, "application/json")
+            .json(&body);
+
+        // Generate and add a jwt token to the header if auth is defined.
+        if let Some(auth) = &self.auth {
+            request = request.bearer_auth(auth.generate_token()?);
+        };
+
+        let responses: Vec<JsonResponseBody> =
+            request.send().await?.error_for_status()?.json().await?;
+        correlate_batch(requests.len(), responses)
+    }
+
+    /// Waits until fewer than the maximum number of requests are in flight.
+    async fn acquire_permit(&self) -> Result<SemaphorePermit<'_>, ClRpcError> {
+        self.in_flight
+            .acquire()
+            .await
+            .map_err(|_| ClRpcError::RequestFailed("request limiter closed".to_string()))
     }
 }
 
@@ -125,11 +192,15 @@ impl HttpJsonRpc {
         .await
     }
 
-    pub async fn get_block_by_number<'a>(
+    /// Returns the block selected by `tag`, `eth_getBlockByHash` is used for [BlockTag::Hash]
+    pub async fn get_block_by_number(
         &self,
-        query: String,
+        tag: BlockTag,
     ) -> Result<Option<ExecutionBlock>, ClRpcError> {
-        let params = json!([query, RETURN_FULL_TRANSACTION_OBJECTS]);
+        if let BlockTag::Hash(block_hash) = tag {
+            return self.get_block_by_hash(block_hash).await;
+        }
+        let params = json!([tag, RETURN_FULL_TRANSACTION_OBJECTS]);
 
         self.rpc_request(
             ETH_GET_BLOCK_BY_NUMBER,
@@ -330,3 +401,68 @@ impl HttpJsonRpc {
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::join_all;
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Request, Response, Server,
+    };
+    use std::{
+        convert::Infallible,
+        net::{Ipv4Addr, SocketAddr},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    #[derive(Default)]
+    struct InFlight {
+        current: AtomicUsize,
+        max: AtomicUsize,
+    }
+
+    /// Spawns a server that answers every request after a delay and records how many requests it
+    /// handled at the same time.
+    fn spawn_slow_server(in_flight: Arc<InFlight>) -> SocketAddr {
+        let make_service = make_service_fn(move |_| {
+            let in_flight = in_flight.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |_: Request<Body>| {
+                    let in_flight = in_flight.clone();
+                    async move {
+                        let current = in_flight.current.fetch_add(1, Ordering::SeqCst) + 1;
+                        in_flight.max.fetch_max(current, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        in_flight.current.fetch_sub(1, Ordering::SeqCst);
+                        let body = json!({ "jsonrpc": "2.0", "id": STATIC_ID, "result": true });
+                        Ok::<_, Infallible>(Response::new(Body::from(body.to_string())))
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&SocketAddr::from((Ipv4Addr::LOCALHOST, 0))).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    #[test]
+    fn bounds_requests_in_flight() {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let in_flight = Arc::new(InFlight::default());
+            let addr = spawn_slow_server(in_flight.clone());
+            let url = Url::parse(&format!("http://{addr}/")).unwrap();
+            let api = HttpJsonRpc::new(url, None).unwrap().with_max_concurrent_requests(2);
+
+            let requests = (0..8)
+                .map(|_| api.rpc_request::<bool>("test_slow", json!([]), Duration::from_secs(5)));
+            let results = join_all(requests).await;
+
+            assert!(results.into_iter().all(|result| result.unwrap()));
+            assert_eq!(in_flight.max.load(Ordering::SeqCst), 2);
+            assert_eq!(in_flight.current.load(Ordering::SeqCst), 0);
+        });
+    }
+}
@@ -1,5 +1,6 @@
 use super::auth::Auth;
-use super::json_structures::{JsonRequestBody, JsonResponseBody};
+use super::in_process::InProcessEngine;
+use super::json_structures::{BlockTag, JsonRequestBody, JsonResponseBody};
 use super::*;
 pub use reqwest::blocking::Client as ClientBlocking;
 use reqwest::header::CONTENT_TYPE;
@@ -18,6 +19,8 @@ pub struct HttpJsonRpcSync {
     pub url: Url,
     pub execution_timeout_multiplier: u32,
     auth: Option<Auth>,
+    /// Sends the requests to the execution layer of this process instead of `url`
+    in_process: Option<InProcessEngine>,
 }
 
 impl Default for HttpJsonRpcSync {
@@ -33,6 +36,7 @@ impl HttpJsonRpcSync {
             url,
             execution_timeout_multiplier: execution_timeout_multiplier.unwrap_or(1),
             auth: None,
+            in_process: None,
         })
     }
 
@@ -46,15 +50,39 @@ impl HttpJsonRpcSync {
             url,
             execution_timeout_multiplier: execution_timeout_multiplier.unwrap_or(1),
             auth: Some(auth),
+            in_process: None,
         })
     }
 
+    /// Talks HTTP/2 to the execution layer if `enabled`, multiplexing all requests over a single
+    /// connection.
+    ///
+    /// HTTP/2 is used with prior knowledge, the execution layer must accept HTTP/2 without an
+    /// upgrade from HTTP/1.1.
+    pub fn with_http2(mut self, enabled: bool) -> Result<Self, ClRpcError> {
+        if enabled {
+            self.client = ClientBlocking::builder().http2_prior_knowledge().build()?;
+        }
+        Ok(self)
+    }
+
+    /// Calls the engine API of the execution layer of this process through `engine` instead of
+    /// sending the requests to the url. The url, the JWT and HTTP/2 are unused.
+    pub fn with_in_process_engine(mut self, engine: InProcessEngine) -> Self {
+        self.in_process = Some(engine);
+        self
+    }
+
     pub fn rpc_request<D: DeserializeOwned>(
         &self,
         method: &str,
         params: serde_json::Value,
         timeout: Duration,
     ) -> Result<D, ClRpcError> {
+        if let Some(engine) = &self.in_process {
+            return engine.rpc_request(method, params, timeout)
+        }
+
         let body =
             JsonRequestBody { jsonrpc: JSONRPC_VERSION, method, params, id: json!(STATIC_ID) };
 
@@ -74,21 +102,15 @@ impl HttpJsonRpcSync {
 
         // println!("===={:?}", body);
 
-        match (body.result, body.error) {
-            (result, None) => serde_json::from_value(result).map_err(Into::into),
-            (_, Some(error)) => {
-                if error.message.contains(EIP155_ERROR_STR) {
-                    Err(ClRpcError::Eip155Failure)
-                } else {
-                    Err(ClRpcError::ServerMessage { code: error.code, message: error.message })
-                }
-            }
-        }
+        serde_json::from_value(body.into_result()?).map_err(Into::into)
     }
 }
 
 impl std::fmt::Display for HttpJsonRpcSync {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.in_process.is_some() {
+            return write!(f, "in-process")
+        }
         write!(f, "{}, auth={}", self.url, self.auth.is_some())
     }
 }
@@ -194,11 +216,12 @@ impl HttpJsonRpcSync {
         )
     }
 
-    pub fn get_block_by_number<'a>(
-        &self,
-        query: String,
-    ) -> Result<Option<ExecutionBlock>, ClRpcError> {
-        let params = json!([query, RETURN_FULL_TRANSACTION_OBJECTS]);
+    /// Returns the block selected by `tag`, `eth_getBlockByHash` is used for [BlockTag::Hash]
+    pub fn get_block_by_number(&self, tag: BlockTag) -> Result<Option<ExecutionBlock>, ClRpcError> {
+        if let BlockTag::Hash(block_hash) = tag {
+            return self.get_block_by_hash(block_hash);
+        }
+        let params = json!([tag, RETURN_FULL_TRANSACTION_OBJECTS]);
 
         self.rpc_request(
             ETH_GET_BLOCK_BY_NUMBER,
@@ -220,7 +243,9 @@ impl HttpJsonRpcSync {
         )
     }
 
-    pub fn exchange_capabilities(&self) -> Result<(), ClRpcError> {
+    /// Returns the engine API methods the execution layer supports, `None` if it does not
+    /// support `engine_exchangeCapabilities`.
+    pub fn exchange_capabilities(&self) -> Result<Option<HashSet<String>>, ClRpcError> {
         let params = json!([CL_CAPABILITIES]);
 
         let response: Result<HashSet<String>, _> = self.rpc_request(
@@ -233,14 +258,39 @@ impl HttpJsonRpcSync {
             // TODO (mark): rip this out once we are post capella on mainnet
             Err(error) => match error {
                 ClRpcError::ServerMessage { code, message: _ } if code == METHOD_NOT_FOUND_CODE => {
-                    Ok(())
+                    Ok(None)
                 }
                 _ => Err(error),
             },
-            Ok(capabilities) => {
-                println!("Capabilities: {:?}", capabilities);
-                Ok(())
+            Ok(capabilities) => Ok(Some(capabilities)),
+        }
+    }
+
+    /// Returns the client version of the execution layer as `name/version/commit`, `None` if it
+    /// does not support `engine_getClientVersionV1`.
+    pub fn client_version(&self) -> Result<Option<String>, ClRpcError> {
+        let params = json!([{
+            "code": "CL",
+            "name": "clayer",
+            "version": env!("CARGO_PKG_VERSION"),
+            "commit": "0x00000000",
+        }]);
+
+        let response: Result<Vec<serde_json::Value>, _> = self.rpc_request(
+            ENGINE_GET_CLIENT_VERSION_V1,
+            params,
+            ENGINE_GET_CLIENT_VERSION_TIMEOUT * self.execution_timeout_multiplier,
+        );
+
+        match response {
+            Err(ClRpcError::ServerMessage { code, .. }) if code == METHOD_NOT_FOUND_CODE => {
+                Ok(None)
             }
+            Err(error) => Err(error),
+            Ok(versions) => Ok(versions.first().map(|version| {
+                let field = |name: &str| version[name].as_str().unwrap_or_default().to_string();
+                format!("{}/{}/{}", field("name"), field("version"), field("commit"))
+            })),
         }
     }
 
@@ -377,3 +427,57 @@ impl HttpJsonRpcSync {
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Request, Response, Server,
+    };
+    use std::{
+        convert::Infallible,
+        net::{Ipv4Addr, SocketAddr},
+    };
+
+    /// Spawns a server speaking HTTP/1.1 and HTTP/2 that answers every request with the HTTP
+    /// version it was sent with.
+    fn spawn_version_server() -> SocketAddr {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let runtime =
+                tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            runtime.block_on(async move {
+                let make_service = make_service_fn(|_| async {
+                    Ok::<_, Infallible>(service_fn(|request: Request<Body>| async move {
+                        let version = format!("{:?}", request.version());
+                        let body = json!({ "jsonrpc": "2.0", "id": STATIC_ID, "result": version });
+                        Ok::<_, Infallible>(Response::new(Body::from(body.to_string())))
+                    }))
+                });
+                let server =
+                    Server::bind(&SocketAddr::from((Ipv4Addr::LOCALHOST, 0))).serve(make_service);
+                tx.send(server.local_addr()).unwrap();
+                server.await.unwrap();
+            });
+        });
+        rx.recv().unwrap()
+    }
+
+    fn http_version(api: &HttpJsonRpcSync) -> String {
+        api.rpc_request("test_httpVersion", json!([]), Duration::from_secs(5)).unwrap()
+    }
+
+    #[test]
+    fn uses_http2_when_enabled() {
+        let addr = spawn_version_server();
+        let url = Url::parse(&format!("http://{addr}/")).unwrap();
+
+        let api = HttpJsonRpcSync::new(url.clone(), None).unwrap();
+        assert_eq!(http_version(&api), "HTTP/1.1");
+
+        let api = HttpJsonRpcSync::new(url, None).unwrap().with_http2(true).unwrap();
+        assert_eq!(http_version(&api), "HTTP/2.0");
+        assert_eq!(http_version(&api), "HTTP/2.0");
+    }
+}
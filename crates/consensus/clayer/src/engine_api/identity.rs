@@ -0,0 +1,168 @@
+//! Tracks the execution layer across restarts.
+//!
+//! The capabilities and client version the execution layer advertises are stored in a small
+//! state file. On the next startup they are compared with the ones of the running execution
+//! layer, so an unexpected upgrade or downgrade shows up in the logs.
+
+use super::{ClRpcError, ExecutionApi};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tracing::*;
+
+/// What an execution layer advertises about itself over the engine API
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElIdentity {
+    /// The engine API methods returned by `engine_exchangeCapabilities`
+    pub capabilities: BTreeSet<String>,
+    /// The client version returned by `engine_getClientVersionV1`, unset if not supported
+    pub client_version: Option<String>,
+}
+
+impl ElIdentity {
+    /// Queries the identity of the execution layer behind `api`.
+    ///
+    /// Calls the execution layer does not support leave the corresponding field empty.
+    pub fn fetch(api: &dyn ExecutionApi) -> Result<Self, ClRpcError> {
+        Ok(Self {
+            capabilities: api.exchange_capabilities()?.unwrap_or_default().into_iter().collect(),
+            client_version: api.client_version()?,
+        })
+    }
+
+    /// Returns how `self` differs from the `previous` identity, `None` if it is the same.
+    pub fn changes_since(&self, previous: &ElIdentity) -> Option<ElIdentityChange> {
+        if self == previous {
+            return None
+        }
+        Some(ElIdentityChange {
+            added: self.capabilities.difference(&previous.capabilities).cloned().collect(),
+            removed: previous.capabilities.difference(&self.capabilities).cloned().collect(),
+            previous_version: previous.client_version.clone(),
+            version: self.client_version.clone(),
+        })
+    }
+}
+
+/// Difference between the stored and the current [ElIdentity]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElIdentityChange {
+    /// Capabilities the execution layer gained
+    pub added: Vec<String>,
+    /// Capabilities the execution layer lost
+    pub removed: Vec<String>,
+    /// The stored client version
+    pub previous_version: Option<String>,
+    /// The current client version
+    pub version: Option<String>,
+}
+
+/// The state file holding the last known [ElIdentity]
+#[derive(Debug, Clone)]
+pub struct ElIdentityFile {
+    path: PathBuf,
+}
+
+impl ElIdentityFile {
+    /// Creates a handle to the state file at `path`, the file is created on the first
+    /// [ElIdentityFile::record].
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the path of the state file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the stored identity, `None` if none was recorded yet.
+    pub fn load(&self) -> io::Result<Option<ElIdentity>> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Stores `identity` and returns how it differs from the previously stored one. A change is
+    /// logged as a warning.
+    pub fn record(&self, identity: &ElIdentity) -> io::Result<Option<ElIdentityChange>> {
+        let change = self.load()?.and_then(|previous| identity.changes_since(&previous));
+        if let Some(change) = &change {
+            warn!(target: "consensus::cl", added = ?change.added, removed = ?change.removed, previous_version = ?change.previous_version, version = ?change.version, "Execution layer changed since the last run");
+        }
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_vec_pretty(identity)?)?;
+        Ok(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tracing::Level;
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<parking_lot::Mutex<Vec<u8>>>);
+
+    impl io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn identity(capabilities: &[&str], client_version: &str) -> ElIdentity {
+        ElIdentity {
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            client_version: Some(client_version.to_string()),
+        }
+    }
+
+    #[test]
+    fn warns_about_changed_execution_layer() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = ElIdentityFile::new(dir.path().join("clayer").join("el-identity.json"));
+
+        let first = identity(&["engine_newPayloadV1", "engine_newPayloadV2"], "reth/v0.1.0");
+        assert_eq!(file.record(&first).unwrap(), None);
+        assert_eq!(file.load().unwrap(), Some(first.clone()));
+        // restarting against the same execution layer
+        assert_eq!(file.record(&first).unwrap(), None);
+
+        // restarting against a downgraded execution layer
+        let second = identity(&["engine_newPayloadV1"], "reth/v0.0.9");
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = reth_tracing::tracing_subscriber::fmt()
+            .with_max_level(Level::WARN)
+            .with_writer(move || writer.clone())
+            .finish();
+        let change =
+            tracing::subscriber::with_default(subscriber, || file.record(&second).unwrap());
+
+        assert_eq!(
+            change,
+            Some(ElIdentityChange {
+                added: vec![],
+                removed: vec!["engine_newPayloadV2".to_string()],
+                previous_version: Some("reth/v0.1.0".to_string()),
+                version: Some("reth/v0.0.9".to_string()),
+            })
+        );
+        let logs = String::from_utf8(logs.0.lock().clone()).unwrap();
+        assert!(logs.contains("Execution layer changed since the last run"), "{logs}");
+        assert_eq!(file.load().unwrap(), Some(second));
+    }
+}
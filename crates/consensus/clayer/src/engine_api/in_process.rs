@@ -0,0 +1,241 @@
+//! Engine API transport to the execution layer running in the same process.
+
+use super::{
+    json_structures::{JsonRequestBody, JsonResponseBody},
+    *,
+};
+use jsonrpsee::Methods;
+use parking_lot::{Condvar, Mutex};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use tokio::runtime::Handle;
+
+/// Handle to the engine API of the execution layer of this node, called directly instead of over
+/// HTTP.
+///
+/// The handle is created before the execution layer starts its RPC servers and is installed with
+/// the methods of its auth server once they are built. Requests sent before wait for the methods
+/// up to their timeout. Requests and responses are encoded as on the auth server, so the
+/// execution layer behaves the same as over HTTP, without the network and the JWT.
+#[derive(Clone, Default)]
+pub struct InProcessEngine {
+    inner: Arc<(Mutex<Option<(Methods, Handle)>>, Condvar)>,
+}
+
+impl InProcessEngine {
+    /// Creates a handle without methods, see [InProcessEngine::install].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Installs the methods of the auth server, answered on the runtime of the caller.
+    ///
+    /// # Panics
+    ///
+    /// If called outside of the tokio runtime.
+    pub fn install(&self, methods: impl Into<Methods>) {
+        let (methods_slot, installed) = &*self.inner;
+        *methods_slot.lock() = Some((methods.into(), Handle::current()));
+        installed.notify_all();
+    }
+
+    /// Returns whether the methods are installed.
+    pub fn is_installed(&self) -> bool {
+        self.inner.0.lock().is_some()
+    }
+
+    /// Waits up to `timeout` for the methods to be installed.
+    fn methods(&self, timeout: Duration) -> Result<(Methods, Handle), ClRpcError> {
+        let (methods_slot, installed) = &*self.inner;
+        let deadline = Instant::now() + timeout;
+        let mut methods = methods_slot.lock();
+        while methods.is_none() {
+            if installed.wait_until(&mut methods, deadline).timed_out() {
+                return Err(ClRpcError::RequestFailed(
+                    "in-process engine API is not installed".to_string(),
+                ))
+            }
+        }
+        Ok(methods.clone().expect("installed"))
+    }
+
+    /// Calls `method` on the execution layer, blocking the calling thread until it answered.
+    ///
+    /// Must not be called from within the tokio runtime.
+    pub fn rpc_request<D: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Duration,
+    ) -> Result<D, ClRpcError> {
+        let (methods, runtime) = self.methods(timeout)?;
+        let body =
+            JsonRequestBody { jsonrpc: JSONRPC_VERSION, method, params, id: json!(STATIC_ID) };
+        let request = serde_json::to_string(&body)?;
+
+        let (response, _) = runtime
+            .block_on(tokio::time::timeout(timeout, methods.raw_json_request(&request, 1)))
+            .map_err(|_| ClRpcError::RequestFailed(format!("{method} timed out")))?
+            .map_err(|err| ClRpcError::RequestFailed(format!("{method}: {err}")))?;
+        let body: JsonResponseBody = serde_json::from_str(&response)?;
+
+        serde_json::from_value(body.into_result()?).map_err(Into::into)
+    }
+}
+
+impl std::fmt::Debug for InProcessEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InProcessEngine").field("installed", &self.is_installed()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_api::http_blocking::HttpJsonRpcSync;
+    use jsonrpsee::{server::ServerBuilder, types::ErrorObjectOwned, RpcModule};
+    use reth_rpc_types::engine::PayloadStatusEnum;
+    use url::Url;
+
+    /// Spawns a runtime on a thread of its own and returns its handle.
+    fn spawn_runtime() -> Handle {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let runtime =
+                tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            tx.send(runtime.handle().clone()).unwrap();
+            runtime.block_on(std::future::pending::<()>());
+        });
+        rx.recv().unwrap()
+    }
+
+    /// An execution layer serving the engine API that builds a single block on top of its head,
+    /// recording the names of the calls in `calls`
+    fn mock_el(head: B256, block: B256, calls: Arc<Mutex<Vec<String>>>) -> RpcModule<()> {
+        let mut module = RpcModule::new(());
+        let record = move |method: &str| calls.lock().push(method.to_string());
+
+        let record_block = record.clone();
+        module
+            .register_method("eth_getBlockByNumber", move |_, _| {
+                record_block("eth_getBlockByNumber");
+                let block = ExecutionBlock {
+                    block_hash: head,
+                    block_number: 1,
+                    parent_hash: B256::ZERO,
+                    total_difficulty: U256::ZERO,
+                    timestamp: 0,
+                };
+                Ok::<_, ErrorObjectOwned>(json!(block))
+            })
+            .unwrap();
+
+        let record_fcu = record.clone();
+        module
+            .register_method("engine_forkchoiceUpdatedV2", move |params, _| {
+                record_fcu("engine_forkchoiceUpdatedV2");
+                let (_, attributes): (ForkchoiceState, Option<PayloadAttributes>) =
+                    params.parse()?;
+                let updated = ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid);
+                let updated = match attributes {
+                    Some(_) => updated.with_payload_id(PayloadId::new([7; 8])),
+                    None => updated,
+                };
+                Ok::<_, ErrorObjectOwned>(json!(updated))
+            })
+            .unwrap();
+
+        let record_get = record.clone();
+        module
+            .register_method("engine_getPayloadV2", move |_, _| {
+                record_get("engine_getPayloadV2");
+                let payload_inner = ExecutionPayloadV1 {
+                    parent_hash: head,
+                    fee_recipient: Default::default(),
+                    state_root: B256::ZERO,
+                    receipts_root: B256::ZERO,
+                    logs_bloom: Default::default(),
+                    prev_randao: B256::ZERO,
+                    block_number: 2,
+                    gas_limit: 30_000_000,
+                    gas_used: 21_000,
+                    timestamp: 1,
+                    extra_data: Default::default(),
+                    base_fee_per_gas: U256::from(7),
+                    block_hash: block,
+                    transactions: vec![],
+                };
+                let payload = ExecutionPayloadWrapperV2 {
+                    execution_payload: ExecutionPayloadV2 { payload_inner, withdrawals: vec![] },
+                    block_value: U256::from(1_000),
+                };
+                Ok::<_, ErrorObjectOwned>(json!(payload))
+            })
+            .unwrap();
+
+        module
+            .register_method("engine_newPayloadV2", move |_, _| {
+                record("engine_newPayloadV2");
+                let status = PayloadStatus::new(PayloadStatusEnum::Valid, Some(block));
+                Ok::<_, ErrorObjectOwned>(json!(status))
+            })
+            .unwrap();
+
+        module
+    }
+
+    /// Builds and commits a block, returns the calls the EL received
+    fn commit(api: Arc<dyn ExecutionApi>, calls: &Mutex<Vec<String>>, block: B256) -> Vec<String> {
+        let mut service = ApiService::new(api);
+        service.initialize_block(None).unwrap();
+        service.summarize_block(1, vec![]).unwrap();
+        service.finalize_block().unwrap();
+        service.commit_block(block).unwrap();
+        assert_eq!(service.latest_committed_id, Some(block));
+        assert_eq!(service.building_state(), BlockBuildingState::Idle);
+        std::mem::take(&mut *calls.lock())
+    }
+
+    #[test]
+    fn commits_in_process_like_over_http() {
+        let (head, block) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let runtime = spawn_runtime();
+        let calls = Arc::new(Mutex::new(vec![]));
+        let module = mock_el(head, block, calls.clone());
+
+        let (addr, _server) = runtime.block_on(async {
+            let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+            (server.local_addr().unwrap(), server.start(module.clone()))
+        });
+        let url = Url::parse(&format!("http://{addr}/")).unwrap();
+        let http_calls = commit(Arc::new(HttpJsonRpcSync::new(url, None).unwrap()), &calls, block);
+
+        let engine = InProcessEngine::new();
+        runtime.block_on(async { engine.install(module) });
+        let api = HttpJsonRpcSync::default().with_in_process_engine(engine);
+        let in_process_calls = commit(Arc::new(api), &calls, block);
+
+        assert!(http_calls.contains(&"engine_newPayloadV2".to_string()), "{http_calls:?}");
+        assert_eq!(in_process_calls, http_calls);
+    }
+
+    #[test]
+    fn waits_for_installed_methods() {
+        let engine = InProcessEngine::new();
+        let res = engine.rpc_request::<bool>(ETH_SYNCING, json!([]), Duration::from_millis(10));
+        assert!(matches!(res, Err(ClRpcError::RequestFailed(_))), "{res:?}");
+
+        let runtime = spawn_runtime();
+        let mut module = RpcModule::new(());
+        module.register_method(ETH_SYNCING, |_, _| false).unwrap();
+        let installer = engine.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            runtime.block_on(async { installer.install(module) });
+        });
+
+        let syncing: bool =
+            engine.rpc_request(ETH_SYNCING, json!([]), Duration::from_secs(5)).unwrap();
+        assert!(!syncing);
+    }
+}
@@ -1,4 +1,6 @@
-use serde::{Deserialize, Serialize};
+use super::{ClRpcError, EIP155_ERROR_STR};
+use alloy_primitives::B256;
+use serde::{Deserialize, Serialize, Serializer};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -25,3 +27,147 @@ pub struct JsonResponseBody {
     pub result: serde_json::Value,
     pub id: serde_json::Value,
 }
+
+impl JsonResponseBody {
+    /// Returns the result of the response, or the error reported by the server.
+    pub fn into_result(self) -> Result<serde_json::Value, ClRpcError> {
+        match (self.result, self.error) {
+            (result, None) => Ok(result),
+            (_, Some(error)) => {
+                if error.message.contains(EIP155_ERROR_STR) {
+                    Err(ClRpcError::Eip155Failure)
+                } else {
+                    Err(ClRpcError::ServerMessage { code: error.code, message: error.message })
+                }
+            }
+        }
+    }
+}
+
+/// Matches the responses to a batch of `len` requests with the ids `1..=len` to their requests.
+///
+/// Servers may answer a batch in any order, so responses are matched by id, not by position. A
+/// request without a response gets an error of its own, a response with an id that was not
+/// requested or answered twice fails the whole batch.
+pub fn correlate_batch(
+    len: usize,
+    responses: Vec<JsonResponseBody>,
+) -> Result<Vec<Result<serde_json::Value, ClRpcError>>, ClRpcError> {
+    let mut results: Vec<Option<Result<serde_json::Value, ClRpcError>>> =
+        (0..len).map(|_| None).collect();
+    for response in responses {
+        let index = response
+            .id
+            .as_u64()
+            .and_then(|id| (id as usize).checked_sub(1))
+            .filter(|index| *index < len)
+            .ok_or_else(|| {
+                ClRpcError::BadResponse(format!("batch response with unknown id {}", response.id))
+            })?;
+        if results[index].is_some() {
+            return Err(ClRpcError::BadResponse(format!(
+                "batch response with duplicate id {}",
+                response.id
+            )))
+        }
+        results[index] = Some(response.into_result());
+    }
+    Ok(results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| {
+            result.unwrap_or_else(|| {
+                Err(ClRpcError::BadResponse(format!("no response to batch request {}", index + 1)))
+            })
+        })
+        .collect())
+}
+
+/// Selects the block of an `eth_getBlockByNumber` request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockTag {
+    /// The most recent block
+    Latest,
+    /// The most recent finalized block
+    Finalized,
+    /// The most recent safe block
+    Safe,
+    /// The genesis block
+    Earliest,
+    /// The block with the given number
+    Number(u64),
+    /// The block with the given hash
+    Hash(B256),
+}
+
+impl Serialize for BlockTag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            BlockTag::Latest => serializer.serialize_str("latest"),
+            BlockTag::Finalized => serializer.serialize_str("finalized"),
+            BlockTag::Safe => serializer.serialize_str("safe"),
+            BlockTag::Earliest => serializer.serialize_str("earliest"),
+            BlockTag::Number(number) => serializer.serialize_str(&format!("0x{:x}", number)),
+            BlockTag::Hash(hash) => hash.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn response(id: serde_json::Value, result: serde_json::Value) -> JsonResponseBody {
+        JsonResponseBody { jsonrpc: "2.0".to_string(), error: None, result, id }
+    }
+
+    #[test]
+    fn correlates_batch_by_id() {
+        let error = JsonResponseBody {
+            jsonrpc: "2.0".to_string(),
+            error: Some(JsonError { code: -32601, message: "method not found".to_string() }),
+            result: serde_json::Value::Null,
+            id: json!(2),
+        };
+        // answered out of order, request 3 is missing
+        let responses =
+            vec![response(json!(4), json!("four")), error, response(json!(1), json!("one"))];
+
+        let results = correlate_batch(4, responses).unwrap();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap(), &json!("one"));
+        assert!(matches!(
+            &results[1],
+            Err(ClRpcError::ServerMessage { code: -32601, message }) if message == "method not found"
+        ));
+        assert!(matches!(&results[2], Err(ClRpcError::BadResponse(_))));
+        assert_eq!(results[3].as_ref().unwrap(), &json!("four"));
+    }
+
+    #[test]
+    fn rejects_unmatched_batch_ids() {
+        for id in [json!(0), json!(3), json!("1"), serde_json::Value::Null] {
+            let responses = vec![response(id, json!(true))];
+            assert!(matches!(correlate_batch(2, responses), Err(ClRpcError::BadResponse(_))));
+        }
+
+        let responses = vec![response(json!(1), json!(true)), response(json!(1), json!(false))];
+        assert!(matches!(correlate_batch(2, responses), Err(ClRpcError::BadResponse(_))));
+    }
+
+    #[test]
+    fn serialize_block_tag() {
+        assert_eq!(serde_json::to_value(BlockTag::Latest).unwrap(), json!("latest"));
+        assert_eq!(serde_json::to_value(BlockTag::Finalized).unwrap(), json!("finalized"));
+        assert_eq!(serde_json::to_value(BlockTag::Safe).unwrap(), json!("safe"));
+        assert_eq!(serde_json::to_value(BlockTag::Earliest).unwrap(), json!("earliest"));
+        assert_eq!(serde_json::to_value(BlockTag::Number(0)).unwrap(), json!("0x0"));
+        assert_eq!(serde_json::to_value(BlockTag::Number(1024)).unwrap(), json!("0x400"));
+        let hash = B256::with_last_byte(0xab);
+        assert_eq!(
+            serde_json::to_value(BlockTag::Hash(hash)).unwrap(),
+            json!("0x00000000000000000000000000000000000000000000000000000000000000ab")
+        );
+    }
+}
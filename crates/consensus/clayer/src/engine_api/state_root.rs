@@ -0,0 +1,70 @@
+//! Local verification of the state root of a payload.
+//!
+//! On a permissioned chain a validator may not want to rely on the execution layer alone. A
+//! [StateRootVerifier] executes a payload on top of its parent with the node's own state and
+//! returns the resulting state root, payloads with a different state root are never voted on.
+
+use reth_primitives::{B256, U256};
+use reth_provider::{ExecutorFactory, HeaderProvider, StateProviderFactory};
+use reth_rpc_types::ExecutionPayloadV2;
+use reth_rpc_types_compat::engine::payload::try_payload_v2_to_block;
+use std::fmt;
+
+/// Recomputes the state root of a payload.
+pub trait StateRootVerifier: fmt::Debug + Send + Sync {
+    /// Executes `payload` on top of its parent and returns the resulting state root.
+    fn state_root(&self, payload: &ExecutionPayloadV2) -> Result<B256, String>;
+}
+
+/// Executes payloads with the local state of the node.
+pub struct LocalStateRootVerifier<Provider, EF> {
+    provider: Provider,
+    executor_factory: EF,
+}
+
+impl<Provider, EF> LocalStateRootVerifier<Provider, EF> {
+    /// Creates a verifier reading the parent state from `provider` and executing with the
+    /// executors of `executor_factory`.
+    pub fn new(provider: Provider, executor_factory: EF) -> Self {
+        Self { provider, executor_factory }
+    }
+}
+
+impl<Provider, EF> fmt::Debug for LocalStateRootVerifier<Provider, EF> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalStateRootVerifier").finish_non_exhaustive()
+    }
+}
+
+impl<Provider, EF> StateRootVerifier for LocalStateRootVerifier<Provider, EF>
+where
+    Provider: StateProviderFactory + HeaderProvider + Send + Sync,
+    EF: ExecutorFactory,
+{
+    fn state_root(&self, payload: &ExecutionPayloadV2) -> Result<B256, String> {
+        let block = try_payload_v2_to_block(payload.clone())
+            .map_err(|err| format!("invalid payload: {err}"))?;
+        let parent_td = self
+            .provider
+            .header_td(&block.parent_hash)
+            .map_err(|err| format!("failed to read the parent total difficulty: {err}"))?
+            .unwrap_or(U256::ZERO);
+        let total_difficulty = parent_td + block.difficulty;
+        let block = block
+            .with_recovered_senders()
+            .ok_or_else(|| "failed to recover the transaction senders".to_string())?;
+
+        let state = self
+            .provider
+            .state_by_block_hash(block.parent_hash)
+            .map_err(|err| format!("parent state unavailable: {err}"))?;
+        let bundle = {
+            let mut executor = self.executor_factory.with_state(&state);
+            executor
+                .execute_and_verify_receipt(&block, total_difficulty)
+                .map_err(|err| format!("execution failed: {err}"))?;
+            executor.take_output_state()
+        };
+        state.state_root(&bundle).map_err(|err| format!("failed to compute the state root: {err}"))
+    }
+}
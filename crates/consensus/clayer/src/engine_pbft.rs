@@ -1,12 +1,20 @@
 use alloy_rlp::Decodable;
-use reth_eth_wire::{ClayerConsensusMessage, ClayerConsensusMessageHeader};
+use reth_eth_wire::{
+    ClayerBlock, ClayerConsensusMessage, ClayerConsensusMessageHeader, PbftMessageType,
+};
+use reth_metrics::{metrics::Counter, Metrics};
 use reth_primitives::B256;
 use reth_provider::{BlockReaderIdExt, ConsensusNumberReader, ConsensusNumberWriter};
 use reth_rpc_types::PeerId;
-use tracing::info;
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
 
 use crate::{
-    consensus::{ParsedMessage, PbftError, PbftState},
+    consensus::{ParsedMessage, PbftError, PbftLog, PbftState},
     ClayerConsensusEngine,
 };
 
@@ -14,12 +22,162 @@ use crate::{
 pub enum ConsensusEvent {
     PeerConnected(PeerId),
     PeerDisconnected(PeerId),
+    PeerDead(PeerId),
     PeerMessage(PeerId, ClayerConsensusMessage),
     BlockValid(B256),
     BlockInvalid(B256),
     BlockCommit((B256, u64, bool)),
 }
 
+/// Batches `Commit` messages that arrive within a short window, so they are handed to the engine
+/// together instead of one by one.
+///
+/// A batch is released as soon as it completes a quorum of `2f + 1` commits for a block, so
+/// reaching quorum is never delayed. A zero window disables batching.
+#[derive(Debug)]
+pub struct CommitAggregator {
+    window: Duration,
+    pending: Vec<(PeerId, ParsedMessage)>,
+    opened_at: Option<Instant>,
+}
+
+impl CommitAggregator {
+    pub fn new(window: Duration) -> Self {
+        Self { window, pending: Vec::new(), opened_at: None }
+    }
+
+    /// Adds a commit to the current batch and returns the batch if it has to be processed right
+    /// away, because it completes a quorum together with the commits already in `log`.
+    pub fn push(
+        &mut self,
+        peer_id: PeerId,
+        msg: ParsedMessage,
+        log: &PbftLog,
+        f: u64,
+    ) -> Option<Vec<(PeerId, ParsedMessage)>> {
+        if self.window.is_zero() {
+            return Some(vec![(peer_id, msg)]);
+        }
+        let (seq_num, view, block_id) = (msg.info().seq_num, msg.info().view, msg.get_block_id());
+        self.pending.push((peer_id, msg));
+        self.opened_at.get_or_insert_with(Instant::now);
+
+        let is_match = |msg: &ParsedMessage| {
+            msg.info().seq_num == seq_num &&
+                msg.info().view == view &&
+                msg.get_block_id() == block_id
+        };
+        let signers: HashSet<PeerId> = log
+            .get_messages_of_type_seq_view_block(PbftMessageType::Commit, seq_num, view, block_id)
+            .into_iter()
+            .chain(self.pending.iter().map(|(_, msg)| msg).filter(|msg| is_match(msg)))
+            .map(|msg| msg.info().signer_id)
+            .collect();
+        (signers.len() as u64 > 2 * f).then(|| self.take())
+    }
+
+    /// Returns the batch once the window since its first commit has elapsed.
+    pub fn poll_expired(&mut self) -> Option<Vec<(PeerId, ParsedMessage)>> {
+        let opened_at = self.opened_at?;
+        (opened_at.elapsed() >= self.window).then(|| self.take())
+    }
+
+    fn take(&mut self) -> Vec<(PeerId, ParsedMessage)> {
+        self.opened_at = None;
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// Maximum number of [EquivocationEvidence]s kept, the oldest evidence is dropped first.
+const MAX_EQUIVOCATION_EVIDENCE: usize = 64;
+
+#[derive(Metrics)]
+#[metrics(scope = "consensus.clayer")]
+struct EquivocationMetrics {
+    /// Number of conflicting votes detected
+    equivocations: Counter,
+}
+
+/// Two conflicting votes of the same validator for the same view and sequence number
+#[derive(Debug, Clone)]
+pub struct EquivocationEvidence {
+    /// The vote received first
+    pub first: ParsedMessage,
+    /// The vote for a different block
+    pub second: ParsedMessage,
+}
+
+/// Detects validators that sign `Prepare`s or `Commit`s for different blocks at the same view and
+/// sequence number.
+///
+/// The first vote of every validator is remembered, a later vote for another block is reported
+/// once as [EquivocationEvidence] with both signed messages.
+#[derive(Debug, Default)]
+pub struct EquivocationDetector {
+    votes: HashMap<(PeerId, u8, u64, u64), ParsedMessage>,
+    evidence: VecDeque<EquivocationEvidence>,
+    metrics: EquivocationMetrics,
+}
+
+impl EquivocationDetector {
+    /// Remembers the vote and returns the evidence if it conflicts with an earlier vote of the
+    /// same signer.
+    pub fn check(&mut self, msg: &ParsedMessage) -> Option<&EquivocationEvidence> {
+        let info = msg.info();
+        if info.ptype != PbftMessageType::Prepare as u8 &&
+            info.ptype != PbftMessageType::Commit as u8
+        {
+            return None;
+        }
+        let first = match self.votes.entry((info.signer_id, info.ptype, info.view, info.seq_num)) {
+            Entry::Vacant(entry) => {
+                entry.insert(msg.clone());
+                return None;
+            }
+            Entry::Occupied(entry) => entry.into_mut(),
+        };
+        if first.get_block_id() == msg.get_block_id() {
+            return None;
+        }
+        // report every equivocating vote only once
+        let reported = self.evidence.iter().any(|evidence| {
+            evidence.first.info() == first.info() &&
+                evidence.first.get_block_id() == first.get_block_id()
+        });
+        if reported {
+            return None;
+        }
+
+        self.metrics.equivocations.increment(1);
+        if self.evidence.len() == MAX_EQUIVOCATION_EVIDENCE {
+            self.evidence.pop_front();
+        }
+        self.evidence.push_back(EquivocationEvidence { first: first.clone(), second: msg.clone() });
+        self.evidence.back()
+    }
+
+    /// Forgets the votes for sequence numbers below `seq_num`, the evidence is kept.
+    pub fn prune(&mut self, seq_num: u64) {
+        self.votes.retain(|(_, _, _, seq), _| *seq >= seq_num);
+    }
+
+    /// Returns the evidence collected so far, oldest first
+    pub fn evidence(&self) -> impl Iterator<Item = &EquivocationEvidence> {
+        self.evidence.iter()
+    }
+}
+
+/// Orders competing blocks proposed for the same height: highest weight first, then by the
+/// tie-break rule, lowest block hash first.
+///
+/// Two validators can propose a block for the same height before a view change resolves, e.g. the
+/// primary of the old view and the one of the new view. Nodes receive these blocks in different
+/// orders, so they evaluate them in this order instead and the first block a node accepts is the
+/// lowest hash among the acceptable ones of the highest weight on every node.
+pub fn order_competing_blocks(blocks: &mut [ClayerBlock], weight: impl Fn(&ClayerBlock) -> u64) {
+    blocks.sort_by_key(|block| (Reverse(weight(block)), block.block_id()));
+}
+
 pub fn parse_consensus_message(
     bytes: &reth_primitives::Bytes,
 ) -> Result<ClayerConsensusMessage, PbftError> {
@@ -42,8 +200,30 @@ fn parse_consensus_message_header(
     })
 }
 
+/// Parses a peer message and checks that it was signed by the signer named in its header
+fn parse_peer_message(
+    message: ClayerConsensusMessage,
+    state: &PbftState,
+) -> Result<ParsedMessage, PbftError> {
+    let header: ClayerConsensusMessageHeader =
+        parse_consensus_message_header(&message.header_bytes)?;
+    let verified_signer_id = header.signer_id.clone();
+    let parsed_message = ParsedMessage::from_peer_message(message, state.id.as_slice())?;
+    let pbft_signer_id = parsed_message.info().signer_id;
+    if pbft_signer_id != verified_signer_id {
+        return Err(PbftError::InvalidMessage(format!(
+            "Mismatch between PbftMessage's signer ID ({:?}) and PeerMessage's signer ID ({:?}) \
+             of peer message: {:?}",
+            pbft_signer_id, verified_signer_id, parsed_message
+        )));
+    }
+    Ok(parsed_message)
+}
+
 pub fn handle_consensus_event<Client, CDB>(
     consensus: &mut ClayerConsensusEngine<Client, CDB>,
+    commits: &mut CommitAggregator,
+    equivocations: &mut EquivocationDetector,
     incoming_event: ConsensusEvent,
     state: &mut PbftState,
 ) -> Result<bool, PbftError>
@@ -55,22 +235,42 @@ where
         ConsensusEvent::BlockValid(block_id) => consensus.on_block_valid(block_id, state)?,
         ConsensusEvent::BlockInvalid(block_id) => consensus.on_block_invalid(block_id)?,
         ConsensusEvent::BlockCommit((block_id, timestamp, committing)) => {
-            consensus.on_block_commit(block_id, timestamp, committing, state)?
+            consensus.on_block_commit(block_id, timestamp, committing, state)?;
+            equivocations.prune(state.seq_num);
         }
         ConsensusEvent::PeerMessage(peer_id, message) => {
-            let header: ClayerConsensusMessageHeader =
-                parse_consensus_message_header(&message.header_bytes)?;
-            let verified_signer_id = header.signer_id.clone();
-            let parsed_message = ParsedMessage::from_peer_message(message, state.id.as_slice())?;
-            let pbft_signer_id = parsed_message.info().signer_id;
-            if pbft_signer_id != verified_signer_id {
+            // the peer may only have relayed the message, it is not blamed
+            if consensus.check_duplicate_identity(&message, state) {
+                return Err(PbftError::InvalidMessage(format!(
+                    "Message signed by the key of this node received from {:?}",
+                    peer_id
+                )));
+            }
+            let parsed_message = parse_peer_message(message, state).map_err(|err| {
+                consensus.agent().record_rejected(peer_id);
+                err
+            })?;
+            if let Some(evidence) = equivocations.check(&parsed_message) {
+                warn!(target: "consensus::cl", signer = ?evidence.first.info().signer_id, first = %evidence.first.info(), first_block = ?evidence.first.get_block_id(), second_block = ?evidence.second.get_block_id(), "Validator equivocated");
+                consensus.agent().record_rejected(peer_id);
                 return Err(PbftError::InvalidMessage(format!(
-                    "Mismatch between PbftMessage's signer ID ({:?}) and PeerMessage's signer ID \
-                     ({:?}) of peer message: {:?}",
-                    pbft_signer_id, verified_signer_id, parsed_message
+                    "Conflicting vote from {:?}: {}",
+                    evidence.first.info().signer_id,
+                    evidence.second.info()
                 )));
             }
-            consensus.on_peer_message(peer_id, parsed_message, state)?
+            if parsed_message.info().ptype == PbftMessageType::Commit as u8 {
+                if let Some(batch) =
+                    commits.push(peer_id, parsed_message, &consensus.msg_log, state.f)
+                {
+                    handle_commits(consensus, batch, state)?
+                }
+            } else {
+                consensus.on_peer_message(peer_id, parsed_message, state).map_err(|err| {
+                    consensus.agent().record_rejected(peer_id);
+                    err
+                })?
+            }
         }
         ConsensusEvent::PeerConnected(peer_id) => {
             info!(target: "consensus::cl","Received PeerConnected message with peer ID: {:?}", peer_id);
@@ -79,7 +279,157 @@ where
         ConsensusEvent::PeerDisconnected(peer_id) => {
             info!(target: "consensus::cl","Received PeerDisconnected message with peer ID: {:?}", peer_id);
         }
+        ConsensusEvent::PeerDead(peer_id) => {
+            warn!(target: "consensus::cl","Received PeerDead message with peer ID: {:?}", peer_id);
+            consensus.on_peer_dead(peer_id, state)?
+        }
     }
 
     Ok(true)
 }
+
+/// Hands the batched commits to the engine once the aggregation window has elapsed
+pub fn flush_expired_commits<Client, CDB>(
+    consensus: &mut ClayerConsensusEngine<Client, CDB>,
+    commits: &mut CommitAggregator,
+    state: &mut PbftState,
+) -> Result<(), PbftError>
+where
+    CDB: ConsensusNumberReader + ConsensusNumberWriter + 'static,
+    Client: BlockReaderIdExt + 'static,
+{
+    match commits.poll_expired() {
+        Some(batch) => handle_commits(consensus, batch, state),
+        None => Ok(()),
+    }
+}
+
+/// Handles all commits of a batch, returning the first error. Every rejected commit is recorded
+/// for the peer it was received from.
+fn handle_commits<Client, CDB>(
+    consensus: &mut ClayerConsensusEngine<Client, CDB>,
+    batch: Vec<(PeerId, ParsedMessage)>,
+    state: &mut PbftState,
+) -> Result<(), PbftError>
+where
+    CDB: ConsensusNumberReader + ConsensusNumberWriter + 'static,
+    Client: BlockReaderIdExt + 'static,
+{
+    let mut result = Ok(());
+    for (peer_id, msg) in batch {
+        if let Err(err) = consensus.on_peer_message(peer_id, msg, state) {
+            consensus.agent().record_rejected(peer_id);
+            if result.is_ok() {
+                result = Err(err);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::PbftMessageWrapper;
+    use reth_eth_wire::{PbftMessage, PbftMessageInfo};
+
+    fn commit(block_id: B256, signer: PeerId) -> (PeerId, ParsedMessage) {
+        let info = PbftMessageInfo {
+            ptype: PbftMessageType::Commit as u8,
+            view: 0,
+            seq_num: 5,
+            signer_id: signer,
+        };
+        (signer, ParsedMessage::from_pbft_message(PbftMessage { info, block_id }).unwrap())
+    }
+
+    #[test]
+    fn quorum_releases_batch_immediately() {
+        let window = Duration::from_millis(50);
+        let mut commits = CommitAggregator::new(window);
+        let mut log = PbftLog::default();
+        let block = B256::with_last_byte(1);
+
+        // the node's own commit is already logged
+        log.add_message(commit(block, PeerId::random()).1);
+
+        // f = 1, a quorum needs 3 commits
+        let (peer, msg) = commit(block, PeerId::random());
+        assert!(commits.push(peer, msg, &log, 1).is_none());
+        let (peer, msg) = commit(block, PeerId::random());
+        let batch = commits.push(peer, msg, &log, 1).expect("quorum reached");
+        assert_eq!(batch.len(), 2);
+        assert!(commits.poll_expired().is_none());
+
+        // sub-quorum commits stay batched until the window elapsed
+        let (peer, msg) = commit(B256::with_last_byte(2), PeerId::random());
+        assert!(commits.push(peer, msg, &log, 1).is_none());
+        let (peer, msg) = commit(B256::with_last_byte(2), peer);
+        assert!(commits.push(peer, msg, &log, 1).is_none());
+        assert!(commits.poll_expired().is_none());
+        std::thread::sleep(window);
+        assert_eq!(commits.poll_expired().map(|batch| batch.len()), Some(2));
+        assert!(commits.poll_expired().is_none());
+    }
+
+    #[test]
+    fn detects_conflicting_votes() {
+        let mut detector = EquivocationDetector::default();
+        let signer = PeerId::random();
+        let (_, first) = commit(B256::with_last_byte(1), signer);
+        let (_, second) = commit(B256::with_last_byte(2), signer);
+
+        assert!(detector.check(&first).is_none());
+        // a repeated vote is no equivocation
+        assert!(detector.check(&first).is_none());
+        // votes of other signers and other phases are independent
+        assert!(detector.check(&commit(B256::with_last_byte(2), PeerId::random()).1).is_none());
+        let mut prepare = second.clone();
+        if let PbftMessageWrapper::Message(msg) = &mut prepare.message {
+            msg.info.ptype = PbftMessageType::Prepare as u8;
+        }
+        assert!(detector.check(&prepare).is_none());
+
+        let evidence = detector.check(&second).expect("equivocation detected");
+        assert_eq!(evidence.first.get_block_id(), B256::with_last_byte(1));
+        assert_eq!(evidence.second.get_block_id(), B256::with_last_byte(2));
+        // reported once
+        assert!(detector.check(&second).is_none());
+        assert_eq!(detector.evidence().count(), 1);
+
+        // evidence survives pruning the votes
+        detector.prune(6);
+        assert!(detector.check(&second).is_none());
+        assert_eq!(detector.evidence().count(), 1);
+    }
+
+    #[test]
+    fn zero_window_disables_batching() {
+        let mut commits = CommitAggregator::new(Duration::ZERO);
+        let (peer, msg) = commit(B256::with_last_byte(1), PeerId::random());
+        let batch = commits.push(peer, msg, &PbftLog::default(), 1).unwrap();
+        assert_eq!(batch.len(), 1);
+    }
+
+    #[test]
+    fn competing_blocks_select_same_winner() {
+        let proposal = |signer_id, block_hash| {
+            let mut block = ClayerBlock::default();
+            block.info.signer_id = signer_id;
+            block.block.block_number = 5;
+            block.block.block_hash = block_hash;
+            block
+        };
+        let old_primary = proposal(PeerId::random(), B256::with_last_byte(0xb0));
+        let new_primary = proposal(PeerId::random(), B256::with_last_byte(0x0a));
+
+        // both nodes received the proposals in a different order
+        let mut first_node = vec![old_primary.clone(), new_primary.clone()];
+        let mut second_node = vec![new_primary.clone(), old_primary];
+        order_competing_blocks(&mut first_node, |_| 0);
+        order_competing_blocks(&mut second_node, |_| 0);
+
+        assert_eq!(first_node, second_node);
+        assert_eq!(first_node[0].block_id(), new_primary.block_id());
+    }
+}
@@ -0,0 +1,100 @@
+//! Epochs of the committed chain.
+//!
+//! An epoch ends with every committed block whose number is a multiple of the epoch length. The
+//! consensus engine changes the validator set at these boundaries, other components can subscribe
+//! to them, e.g. to take checkpoints.
+
+use reth_primitives::{BlockNumber, B256};
+use tokio::sync::broadcast;
+
+/// Number of epoch boundaries buffered for a subscriber that is not keeping up.
+const EPOCH_CHANNEL_CAPACITY: usize = 16;
+
+/// The last committed block of an epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochBoundary {
+    /// Number of the epoch that ended, the first epoch ends with block `epoch_length`
+    pub epoch: u64,
+    /// Number of the last block of the epoch
+    pub block_number: BlockNumber,
+    /// Hash of the last block of the epoch
+    pub block_hash: B256,
+}
+
+/// Emits an [EpochBoundary] every `epoch_length` committed blocks.
+#[derive(Debug, Clone)]
+pub struct EpochTracker {
+    epoch_length: u64,
+    sender: broadcast::Sender<EpochBoundary>,
+}
+
+impl EpochTracker {
+    /// Creates a tracker for epochs of `epoch_length` blocks, at least one block.
+    pub fn new(epoch_length: u64) -> Self {
+        let (sender, _) = broadcast::channel(EPOCH_CHANNEL_CAPACITY);
+        Self { epoch_length: epoch_length.max(1), sender }
+    }
+
+    /// Sets the number of blocks of an epoch, at least one block.
+    pub fn set_epoch_length(&mut self, epoch_length: u64) {
+        self.epoch_length = epoch_length.max(1);
+    }
+
+    /// Returns the number of blocks of an epoch.
+    pub fn epoch_length(&self) -> u64 {
+        self.epoch_length
+    }
+
+    /// Returns `true` if `block_number` is the last block of an epoch.
+    pub fn is_boundary(&self, block_number: BlockNumber) -> bool {
+        block_number != 0 && block_number % self.epoch_length == 0
+    }
+
+    /// Returns a receiver of the epoch boundaries committed from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<EpochBoundary> {
+        self.sender.subscribe()
+    }
+
+    /// Notifies the subscribers if the committed block ends an epoch.
+    pub fn on_commit(&self, block_number: BlockNumber, block_hash: B256) -> Option<EpochBoundary> {
+        if !self.is_boundary(block_number) {
+            return None
+        }
+        let boundary =
+            EpochBoundary { epoch: block_number / self.epoch_length, block_number, block_hash };
+        // no subscribers is fine
+        let _ = self.sender.send(boundary);
+        Some(boundary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fires_at_multiples_of_epoch_length() {
+        let tracker = EpochTracker::new(4);
+        let mut boundaries = tracker.subscribe();
+
+        for number in 0..=12 {
+            tracker.on_commit(number, B256::with_last_byte(number as u8));
+        }
+
+        let mut fired = Vec::new();
+        while let Ok(boundary) = boundaries.try_recv() {
+            fired.push(boundary);
+        }
+        assert_eq!(
+            fired,
+            [4, 8, 12]
+                .into_iter()
+                .map(|number| EpochBoundary {
+                    epoch: number / 4,
+                    block_number: number,
+                    block_hash: B256::with_last_byte(number as u8),
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+}
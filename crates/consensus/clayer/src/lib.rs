@@ -1,24 +1,56 @@
+mod backpressure;
 mod consensus;
 mod engine_api;
 mod engine_pbft;
+mod epoch;
 mod error;
+mod readiness;
+mod replay;
+mod rpc;
 mod task;
 mod timing;
+mod verify;
 use crate::engine_api::{
     auth::{Auth, JwtKey},
     http::HttpJsonRpc,
 };
-pub use consensus::{ClayerConsensusEngine, ClayerConsensusMessagingAgent};
+pub use backpressure::{PipelineProgress, DEFAULT_MAX_PIPELINE_LAG, DEFAULT_MAX_QUEUED_TICKS};
+pub use consensus::{
+    read_message_log, BlockMetadata, ClayerConsensusEngine, ClayerConsensusMessagingAgent,
+    Compression, ConsensusCommand, ConsensusHandle, ConsensusMessageLog, ConsensusSigner,
+    LocalSigner, MessageDirection, MessageLogRecord, MessageReplaySummary, MessageReplayer,
+    PbftConfig, RemoteSigner, ValidatorSetChange, ViewChangeReason, ViewChangeRecord,
+    DEFAULT_REMOTE_SIGNER_TIMEOUT, DEFAULT_SEEN_CACHE_SIZE, DEFAULT_SEEN_CACHE_TTL,
+};
 use engine_api::http_blocking::HttpJsonRpcSync;
-pub use engine_api::AuthHttpConfig;
+pub use engine_api::{
+    auth::DEFAULT_TOKEN_REFRESH_INTERVAL,
+    forkchoice_history::{ForkchoiceHistory, ForkchoiceRecord, DEFAULT_FORKCHOICE_HISTORY_SIZE},
+    http::DEFAULT_MAX_CONCURRENT_REQUESTS,
+    identity::{ElIdentity, ElIdentityChange, ElIdentityFile},
+    in_process::InProcessEngine,
+    state_root::{LocalStateRootVerifier, StateRootVerifier},
+    AuthHttpConfig, ExecutionApi, PendingProposal, PendingProposals, PendingWork,
+};
+pub use epoch::{EpochBoundary, EpochTracker};
+pub use readiness::{ConsensusReadiness, ReadinessState};
+pub use replay::{ChainReplayer, ReplayError, ReplayProgress};
+pub use reth_eth_wire::CLAYER_PROTOCOL_VERSION;
+pub use rpc::{ClayerAdminApiServer, ClayerApiServer, ClayerRpc, ConsensusHealth};
+pub use task::ClTask;
+pub use timing::MAX_TICKER_JITTER;
+pub use verify::{ChainIssue, ChainIssueKind, ChainVerifier, VerifyError, VerifyReport};
 
 use reth_network::NetworkHandle;
 use reth_primitives::{ChainSpec, SealedHeader};
 use reth_provider::{BlockReaderIdExt, ConsensusNumberReader, ConsensusNumberWriter};
 
 use secp256k1::SecretKey;
-use std::sync::Arc;
-use task::ClTask;
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
 
 use url::Url;
 
@@ -29,8 +61,11 @@ pub fn create_api(config: &AuthHttpConfig) -> HttpJsonRpc {
 
     let jwt_key = JwtKey::from_slice(&config.auth).unwrap();
 
-    let auth = Auth::new(jwt_key, None, None);
-    let api = match HttpJsonRpc::new_with_auth(execution_url, auth, execution_timeout_multiplier) {
+    let auth = Auth::new(jwt_key, None, None).with_refresh_interval(config.token_refresh_interval);
+    let api = match HttpJsonRpc::new_with_auth(execution_url, auth, execution_timeout_multiplier)
+        .and_then(|api| api.with_http2(config.http2))
+        .map(|api| api.with_max_concurrent_requests(config.max_concurrent_requests))
+    {
         Ok(api) => api,
         Err(e) => {
             panic!("Failed to create execution api. Error: {:?}", e);
@@ -40,15 +75,41 @@ pub fn create_api(config: &AuthHttpConfig) -> HttpJsonRpc {
 }
 
 pub fn create_sync_api(config: &AuthHttpConfig) -> HttpJsonRpcSync {
-    let str = format!("http://127.0.0.1:{}/", config.port);
+    let api = sync_api(
+        SocketAddr::from((Ipv4Addr::LOCALHOST, config.port)),
+        &config.auth,
+        config.token_refresh_interval,
+        config.http2,
+    );
+    match config.in_process.clone() {
+        Some(engine) => api.with_in_process_engine(engine),
+        None => api,
+    }
+}
+
+/// Creates a blocking engine API client for the execution layer listening on `addr`,
+/// authenticated with the given JWT secret.
+pub fn create_sync_api_with_addr(addr: SocketAddr, jwt_secret: &[u8]) -> HttpJsonRpcSync {
+    sync_api(addr, jwt_secret, DEFAULT_TOKEN_REFRESH_INTERVAL, false)
+}
+
+fn sync_api(
+    addr: SocketAddr,
+    jwt_secret: &[u8],
+    token_refresh_interval: Duration,
+    http2: bool,
+) -> HttpJsonRpcSync {
+    let str = format!("http://{}/", addr);
     let execution_url = Url::parse(&str).unwrap();
     let execution_timeout_multiplier = Option::from(3);
 
-    let jwt_key = JwtKey::from_slice(&config.auth).unwrap();
+    let jwt_key = JwtKey::from_slice(jwt_secret).unwrap();
 
-    let auth = Auth::new(jwt_key, None, None);
+    let auth = Auth::new(jwt_key, None, None).with_refresh_interval(token_refresh_interval);
     let api =
-        match HttpJsonRpcSync::new_with_auth(execution_url, auth, execution_timeout_multiplier) {
+        match HttpJsonRpcSync::new_with_auth(execution_url, auth, execution_timeout_multiplier)
+            .and_then(|api| api.with_http2(http2))
+        {
             Ok(api) => api,
             Err(e) => {
                 panic!("Failed to create execution api. Error: {:?}", e);
@@ -58,7 +119,9 @@ pub fn create_sync_api(config: &AuthHttpConfig) -> HttpJsonRpcSync {
 }
 
 pub struct ConsensusBuilder<Client, CDB> {
-    secret: SecretKey,
+    signer: Arc<dyn ConsensusSigner>,
+    state_root_verifier: Option<Arc<dyn StateRootVerifier>>,
+    pending_work: Option<Arc<dyn PendingWork>>,
     chain_spec: Arc<ChainSpec>,
     client: Client,
     network: NetworkHandle,
@@ -66,6 +129,7 @@ pub struct ConsensusBuilder<Client, CDB> {
     storages: CDB,
     latest_header: SealedHeader,
     auth_config: AuthHttpConfig,
+    pbft_config: PbftConfig,
 }
 
 impl<Client, CDB> ConsensusBuilder<Client, CDB>
@@ -89,7 +153,9 @@ where
             .unwrap_or_else(|| chain_spec.sealed_genesis_header());
 
         Self {
-            secret,
+            signer: Arc::new(LocalSigner::new(secret)),
+            state_root_verifier: None,
+            pending_work: None,
             chain_spec,
             client,
             network,
@@ -97,8 +163,37 @@ where
             storages,
             latest_header,
             auth_config,
+            pbft_config: PbftConfig::default(),
         }
     }
+
+    /// Sets the PBFT configuration, the members are always loaded from the validator contract.
+    pub fn with_pbft_config(mut self, pbft_config: PbftConfig) -> Self {
+        self.pbft_config = pbft_config;
+        self
+    }
+
+    /// Signs the consensus messages with `signer` instead of the secret key, e.g. to keep the
+    /// validator key in an HSM. The signer must hold the key of this node.
+    pub fn with_signer(mut self, signer: Arc<dyn ConsensusSigner>) -> Self {
+        self.signer = signer;
+        self
+    }
+
+    /// Executes every payload locally with `verifier` before it is committed and rejects it if
+    /// the state root differs.
+    pub fn with_state_root_verifier(mut self, verifier: Arc<dyn StateRootVerifier>) -> Self {
+        self.state_root_verifier = Some(verifier);
+        self
+    }
+
+    /// Skips building payloads while `pending_work` has no transactions, if empty blocks are not
+    /// proposed. The transaction pool of the node is a [PendingWork].
+    pub fn with_pending_work(mut self, pending_work: Arc<dyn PendingWork>) -> Self {
+        self.pending_work = Some(pending_work);
+        self
+    }
+
     /// Consumes the type and returns all components
     #[track_caller]
     pub fn build(self) -> ClTask<Client, CDB>
@@ -107,7 +202,9 @@ where
         Client: BlockReaderIdExt + Clone + 'static,
     {
         let Self {
-            secret,
+            signer,
+            state_root_verifier,
+            pending_work,
             chain_spec,
             client,
             network,
@@ -115,9 +212,12 @@ where
             storages,
             latest_header,
             auth_config,
+            pbft_config,
         } = self;
         let task = ClTask::new(
-            secret,
+            signer,
+            state_root_verifier,
+            pending_work,
             Arc::clone(&chain_spec),
             client,
             auth_config,
@@ -125,6 +225,7 @@ where
             consensus_agent,
             storages,
             latest_header,
+            pbft_config,
         );
         task
     }
@@ -0,0 +1,59 @@
+//! Readiness of the consensus engine.
+//!
+//! The node is only considered started once consensus can make progress: the execution layer
+//! answered an engine API call and enough validators are connected to reach quorum.
+
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// What the consensus engine has achieved since startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadinessState {
+    /// The execution layer answered the startup self-test
+    pub engine_api: bool,
+    /// Enough validators are connected to reach quorum
+    pub peered: bool,
+}
+
+impl ReadinessState {
+    /// Returns whether consensus is ready to make progress.
+    pub fn is_ready(&self) -> bool {
+        self.engine_api && self.peered
+    }
+}
+
+/// Readiness of the consensus engine, updated by the engine and awaited by the node.
+#[derive(Debug, Clone)]
+pub struct ConsensusReadiness {
+    state: Arc<watch::Sender<ReadinessState>>,
+}
+
+impl Default for ConsensusReadiness {
+    fn default() -> Self {
+        Self { state: Arc::new(watch::channel(ReadinessState::default()).0) }
+    }
+}
+
+impl ConsensusReadiness {
+    /// Returns what the consensus engine has achieved so far.
+    pub fn state(&self) -> ReadinessState {
+        *self.state.borrow()
+    }
+
+    /// Records the first successful engine API round trip.
+    pub fn set_engine_api_ready(&self) {
+        self.state.send_if_modified(|state| !std::mem::replace(&mut state.engine_api, true));
+    }
+
+    /// Records that enough validators are connected to reach quorum.
+    pub fn set_peered(&self) {
+        self.state.send_if_modified(|state| !std::mem::replace(&mut state.peered, true));
+    }
+
+    /// Resolves once consensus is ready.
+    pub async fn ready(&self) {
+        let mut state = self.state.subscribe();
+        // the sender lives as long as `self`
+        let _ = state.wait_for(ReadinessState::is_ready).await;
+    }
+}
@@ -0,0 +1,324 @@
+//! Replays the committed chain against an execution layer.
+//!
+//! A block is committed once its [PbftSeal](reth_eth_wire::PbftSeal) is stored in the consensus
+//! tables, so the committed chain is the canonical chain of the local database up to the first
+//! block without a seal.
+
+use crate::engine_api::{ClRpcError, ExecutionApi};
+use reth_interfaces::provider::ProviderError;
+use reth_primitives::{BlockNumber, B256};
+use reth_provider::{BlockReader, ConsensusNumberReader};
+use reth_rpc_types::engine::{ForkchoiceState, PayloadStatus, PayloadStatusEnum};
+use reth_rpc_types_compat::engine::payload::convert_block_to_payload_input_v2;
+use std::sync::Arc;
+
+/// A committed block the execution layer accepted as its new head
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayProgress {
+    /// Number of the replayed block
+    pub number: BlockNumber,
+    /// Hash of the replayed block
+    pub hash: B256,
+    /// The last block number the replay was asked to reach
+    pub target: BlockNumber,
+}
+
+/// Errors that stop a replay
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayError {
+    /// Reading the local database failed
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    /// An engine API call failed
+    #[error("engine api call failed: {0:?}")]
+    Api(ClRpcError),
+    /// A block of the committed range is missing from the local database
+    #[error("block {0} is missing from the local database")]
+    MissingBlock(BlockNumber),
+    /// The execution layer did not accept a committed block
+    #[error("execution layer diverged at block {number} ({hash}): {status}")]
+    Divergence {
+        /// Number of the rejected block
+        number: BlockNumber,
+        /// Hash of the rejected block
+        hash: B256,
+        /// Status returned by the execution layer
+        status: PayloadStatusEnum,
+    },
+}
+
+/// Drives an execution layer to the tip of the committed chain via `engine_newPayloadV2` and
+/// `engine_forkchoiceUpdatedV2`.
+pub struct ChainReplayer<Client, CDB> {
+    client: Client,
+    storages: CDB,
+    api: Arc<dyn ExecutionApi>,
+}
+
+impl<Client, CDB> ChainReplayer<Client, CDB>
+where
+    Client: BlockReader,
+    CDB: ConsensusNumberReader,
+{
+    /// Creates a new replayer reading blocks from `client` and seals from `storages`
+    pub fn new(client: Client, storages: CDB, api: Arc<dyn ExecutionApi>) -> Self {
+        Self { client, storages, api }
+    }
+
+    /// Replays the committed blocks `from..=to` in order.
+    ///
+    /// Stops early at the first block that has no seal. `on_progress` is called for every block
+    /// the execution layer accepted. Returns the last replayed block, or `None` if nothing was
+    /// replayed.
+    pub fn replay(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+        mut on_progress: impl FnMut(ReplayProgress),
+    ) -> Result<Option<ReplayProgress>, ReplayError> {
+        let mut tip = None;
+        for number in from..=to {
+            let block =
+                self.client.block(number.into())?.ok_or(ReplayError::MissingBlock(number))?;
+            let block = block.seal_slow();
+            let hash = block.hash;
+
+            if self.storages.consensus_content(hash)?.is_none() {
+                tracing::info!(target: "consensus::cl", number, ?hash, "Reached the end of the committed chain");
+                break
+            }
+
+            let status = self
+                .api
+                .new_payload_v2(convert_block_to_payload_input_v2(block))
+                .map_err(ReplayError::Api)?;
+            Self::ensure_valid(number, hash, status)?;
+
+            let forkchoice_state = ForkchoiceState {
+                head_block_hash: hash,
+                finalized_block_hash: hash,
+                safe_block_hash: hash,
+            };
+            let forkchoice_updated =
+                self.api.forkchoice_updated_v2(forkchoice_state, None).map_err(ReplayError::Api)?;
+            Self::ensure_valid(number, hash, forkchoice_updated.payload_status)?;
+
+            let progress = ReplayProgress { number, hash, target: to };
+            on_progress(progress);
+            tip = Some(progress);
+        }
+        Ok(tip)
+    }
+
+    /// Returns a divergence error unless the execution layer reported `hash` as valid
+    fn ensure_valid(
+        number: BlockNumber,
+        hash: B256,
+        status: PayloadStatus,
+    ) -> Result<(), ReplayError> {
+        let diverged = match status.latest_valid_hash {
+            Some(latest_valid_hash) => latest_valid_hash != hash,
+            None => false,
+        };
+        if !status.status.is_valid() || diverged {
+            tracing::error!(target: "consensus::cl", number, ?hash, status = %status, "Execution layer diverged from the committed chain");
+            return Err(ReplayError::Divergence { number, hash, status: status.status })
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_api::{json_structures::BlockTag, ExecutionBlock, ExecutionPayloadWrapperV2};
+    use parking_lot::Mutex;
+    use reth_db::models::consensus::ConsensusBytes;
+    use reth_interfaces::provider::ProviderResult;
+    use reth_primitives::{Block, Header, U256};
+    use reth_provider::test_utils::MockEthProvider;
+    use reth_rpc_types::engine::{
+        ExecutionPayloadInputV2, ForkchoiceUpdated, PayloadAttributes, PayloadId,
+    };
+    use std::collections::HashSet;
+
+    /// Seals stored for the committed blocks
+    #[derive(Default)]
+    struct MockSeals(HashSet<B256>);
+
+    impl ConsensusNumberReader for MockSeals {
+        fn last_consensus_number(&self) -> ProviderResult<BlockNumber> {
+            Ok(0)
+        }
+
+        fn consensus_number(&self, _hash: B256) -> ProviderResult<Option<BlockNumber>> {
+            Ok(None)
+        }
+
+        fn consensus_content(&self, hash: B256) -> ProviderResult<Option<ConsensusBytes>> {
+            Ok(self.0.contains(&hash).then(|| ConsensusBytes { content: vec![] }))
+        }
+
+        fn committed_payload(
+            &self,
+            _number: BlockNumber,
+        ) -> ProviderResult<Option<ConsensusBytes>> {
+            Ok(None)
+        }
+
+        fn view_changes(
+            &self,
+            _views: std::ops::RangeInclusive<u64>,
+        ) -> ProviderResult<Vec<ConsensusBytes>> {
+            Ok(vec![])
+        }
+
+        fn randao_mix(&self, _number: u64) -> ProviderResult<Option<B256>> {
+            Ok(None)
+        }
+
+        fn first_randao_mix_number(&self) -> ProviderResult<Option<u64>> {
+            Ok(None)
+        }
+    }
+
+    /// An execution layer that only accepts payloads extending its head
+    struct MockEl {
+        head: Mutex<B256>,
+        known: Mutex<HashSet<B256>>,
+        reject: Option<BlockNumber>,
+    }
+
+    impl MockEl {
+        fn new(genesis: B256, reject: Option<BlockNumber>) -> Self {
+            Self { head: Mutex::new(genesis), known: Mutex::new(HashSet::new()), reject }
+        }
+    }
+
+    impl ExecutionApi for MockEl {
+        fn get_block_by_number(
+            &self,
+            _tag: BlockTag,
+        ) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            Ok(None)
+        }
+
+        fn get_block_by_hash(
+            &self,
+            _block_hash: B256,
+        ) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            Ok(None)
+        }
+
+        fn forkchoice_updated_v2(
+            &self,
+            forkchoice_state: ForkchoiceState,
+            _payload_attributes: Option<PayloadAttributes>,
+        ) -> Result<ForkchoiceUpdated, ClRpcError> {
+            let head = forkchoice_state.head_block_hash;
+            if !self.known.lock().contains(&head) {
+                return Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing))
+            }
+            *self.head.lock() = head;
+            Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid)
+                .with_latest_valid_hash(head))
+        }
+
+        fn new_payload_v2(
+            &self,
+            payload: ExecutionPayloadInputV2,
+        ) -> Result<PayloadStatus, ClRpcError> {
+            let payload = payload.execution_payload;
+            if Some(payload.block_number) == self.reject {
+                return Ok(PayloadStatus::from_status(PayloadStatusEnum::Invalid {
+                    validation_error: "bad state root".to_string(),
+                }))
+            }
+            if payload.parent_hash != *self.head.lock() {
+                return Ok(PayloadStatus::from_status(PayloadStatusEnum::Syncing))
+            }
+            self.known.lock().insert(payload.block_hash);
+            Ok(PayloadStatus::new(PayloadStatusEnum::Valid, Some(payload.block_hash)))
+        }
+
+        fn get_payload_v2(
+            &self,
+            _payload_id: PayloadId,
+        ) -> Result<ExecutionPayloadWrapperV2, ClRpcError> {
+            Err(ClRpcError::RequestFailed("not building payloads".to_string()))
+        }
+
+        fn query_validators(
+            &self,
+            _contract_address: String,
+            _block_number: u64,
+        ) -> Result<Vec<Vec<u8>>, ClRpcError> {
+            Ok(vec![])
+        }
+    }
+
+    /// Returns a provider holding `len` blocks on top of a genesis block, and all block hashes
+    fn chain(len: u64) -> (MockEthProvider, Vec<B256>) {
+        let provider = MockEthProvider::default();
+        let mut hashes = Vec::new();
+        let mut parent_hash = B256::ZERO;
+        for number in 0..=len {
+            let header = Header {
+                number,
+                parent_hash,
+                timestamp: number,
+                gas_limit: 30_000_000,
+                base_fee_per_gas: Some(7),
+                difficulty: U256::ZERO,
+                ..Default::default()
+            };
+            let block = Block { header, body: vec![], ommers: vec![], withdrawals: Some(vec![]) };
+            let hash = block.header.hash_slow();
+            provider.add_block(hash, block);
+            hashes.push(hash);
+            parent_hash = hash;
+        }
+        (provider, hashes)
+    }
+
+    #[test]
+    fn replay_reaches_committed_tip() {
+        let (provider, hashes) = chain(5);
+        // blocks 1..=3 are committed, 4 and 5 are not
+        let seals = MockSeals(hashes[1..=3].iter().copied().collect());
+        let el = Arc::new(MockEl::new(hashes[0], None));
+        let replayer = ChainReplayer::new(provider, seals, el.clone());
+
+        let mut replayed = Vec::new();
+        let tip = replayer.replay(1, 5, |progress| replayed.push(progress.number)).unwrap();
+
+        assert_eq!(replayed, vec![1, 2, 3]);
+        assert_eq!(tip, Some(ReplayProgress { number: 3, hash: hashes[3], target: 5 }));
+        assert_eq!(*el.head.lock(), hashes[3]);
+    }
+
+    #[test]
+    fn replay_reports_divergence() {
+        let (provider, hashes) = chain(3);
+        let seals = MockSeals(hashes[1..].iter().copied().collect());
+        let el = Arc::new(MockEl::new(hashes[0], Some(2)));
+        let replayer = ChainReplayer::new(provider, seals, el.clone());
+
+        let mut replayed = Vec::new();
+        let err = replayer.replay(1, 3, |progress| replayed.push(progress.number)).unwrap_err();
+
+        assert!(matches!(err, ReplayError::Divergence { number: 2, .. }), "{err:?}");
+        assert_eq!(replayed, vec![1]);
+        assert_eq!(*el.head.lock(), hashes[1]);
+    }
+
+    #[test]
+    fn replay_missing_block() {
+        let (provider, hashes) = chain(1);
+        let seals = MockSeals(hashes[1..].iter().copied().collect());
+        let replayer = ChainReplayer::new(provider, seals, Arc::new(MockEl::new(hashes[0], None)));
+
+        let err = replayer.replay(1, 2, |_| {}).unwrap_err();
+        assert!(matches!(err, ReplayError::MissingBlock(2)), "{err:?}");
+    }
+}
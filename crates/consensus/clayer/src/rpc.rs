@@ -0,0 +1,160 @@
+//! The `clayer` RPC namespace for inspecting and controlling the consensus layer.
+//!
+//! The namespace is split in two: [ClayerApi] only reads the state of the consensus layer and is
+//! served on the transports that list the `clayer` module, [ClayerAdminApi] changes it and is only
+//! served on IPC and the JWT authenticated auth server.
+
+use crate::{
+    consensus::{ConsensusHandle, ViewChangeRecord},
+    engine_api::{
+        forkchoice_history::{ForkchoiceHistory, ForkchoiceRecord},
+        PendingProposal, PendingProposals,
+    },
+};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    types::{error::INTERNAL_ERROR_CODE, ErrorObject},
+};
+use reth_primitives::B256;
+use serde::{Deserialize, Serialize};
+
+/// Status of the consensus engine as reported by `clayer_health`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusHealth {
+    /// Whether consensus was paused with `clayer_pause`
+    pub paused: bool,
+}
+
+/// Consensus layer debug API.
+#[rpc(server, namespace = "clayer")]
+pub trait ClayerApi {
+    /// Returns the block proposals that were not committed yet, oldest first.
+    #[method(name = "pendingProposals")]
+    fn pending_proposals(&self) -> RpcResult<Vec<PendingProposal>>;
+
+    /// Returns the last forkchoice updates sent to the execution layer, oldest first. The number
+    /// of updates kept is set with `--clayer.forkchoice-history-size`.
+    #[method(name = "forkchoiceHistory")]
+    fn forkchoice_history(&self) -> RpcResult<Vec<ForkchoiceRecord>>;
+
+    /// Returns the status of the consensus engine.
+    #[method(name = "health")]
+    fn health(&self) -> RpcResult<ConsensusHealth>;
+
+    /// Returns the view changes this node started to the views from `from_view` to `to_view`,
+    /// both inclusive, ordered by view.
+    #[method(name = "viewChanges")]
+    async fn view_changes(&self, from_view: u64, to_view: u64) -> RpcResult<Vec<ViewChangeRecord>>;
+
+    /// Returns the randao mix after the block at `number` was committed, `null` if the node did
+    /// not record one for it. Mixes are only recorded with `--clayer.randao`.
+    #[method(name = "randaoMix")]
+    async fn randao_mix(&self, number: u64) -> RpcResult<Option<B256>>;
+}
+
+/// Consensus layer admin API, changing the state of the consensus engine.
+#[rpc(server, namespace = "clayer")]
+pub trait ClayerAdminApi {
+    /// Starts a view change if this node is the primary, so it can be taken down for maintenance.
+    /// No blocks are proposed until another node became primary.
+    ///
+    /// Returns whether a view change was started.
+    #[method(name = "stepDown")]
+    async fn step_down(&self) -> RpcResult<bool>;
+
+    /// Commits the given validated block of the current height without quorum, to recover a
+    /// network that is stuck. Fails unless the node runs with `--clayer.allow-force-commit`.
+    #[method(name = "forceCommit")]
+    async fn force_commit(&self, block_hash: B256) -> RpcResult<()>;
+
+    /// Stops proposing and committing blocks until `clayer_resume` is called. Consensus messages
+    /// are still processed, so the node stays in sync with the network.
+    ///
+    /// Returns `false` if consensus is already paused.
+    #[method(name = "pause")]
+    async fn pause(&self) -> RpcResult<bool>;
+
+    /// Resumes consensus after `clayer_pause`.
+    ///
+    /// Returns `false` if consensus is not paused.
+    #[method(name = "resume")]
+    async fn resume(&self) -> RpcResult<bool>;
+
+    /// Resumes proposing after repeated failed proposals put the node in safe mode.
+    ///
+    /// Returns `false` if the node is not in safe mode.
+    #[method(name = "exitSafeMode")]
+    async fn exit_safe_mode(&self) -> RpcResult<bool>;
+}
+
+/// Implementation of the `clayer` namespace, reading the state shared by the consensus thread.
+#[derive(Debug, Clone)]
+pub struct ClayerRpc {
+    proposals: PendingProposals,
+    forkchoice_history: ForkchoiceHistory,
+    consensus: ConsensusHandle,
+}
+
+impl ClayerRpc {
+    /// Creates a new instance serving the given proposals and forkchoice updates and controlling
+    /// the consensus engine through `consensus`.
+    pub fn new(
+        proposals: PendingProposals,
+        forkchoice_history: ForkchoiceHistory,
+        consensus: ConsensusHandle,
+    ) -> Self {
+        Self { proposals, forkchoice_history, consensus }
+    }
+}
+
+#[async_trait]
+impl ClayerApiServer for ClayerRpc {
+    fn pending_proposals(&self) -> RpcResult<Vec<PendingProposal>> {
+        Ok(self.proposals.snapshot())
+    }
+
+    fn forkchoice_history(&self) -> RpcResult<Vec<ForkchoiceRecord>> {
+        Ok(self.forkchoice_history.snapshot())
+    }
+
+    fn health(&self) -> RpcResult<ConsensusHealth> {
+        Ok(ConsensusHealth { paused: self.consensus.is_paused() })
+    }
+
+    async fn view_changes(&self, from_view: u64, to_view: u64) -> RpcResult<Vec<ViewChangeRecord>> {
+        self.consensus.view_changes(from_view..=to_view).await.map_err(internal_error)
+    }
+
+    async fn randao_mix(&self, number: u64) -> RpcResult<Option<B256>> {
+        self.consensus.randao_mix(number).await.map_err(internal_error)
+    }
+}
+
+#[async_trait]
+impl ClayerAdminApiServer for ClayerRpc {
+    async fn step_down(&self) -> RpcResult<bool> {
+        self.consensus.step_down().await.map_err(internal_error)
+    }
+
+    async fn force_commit(&self, block_hash: B256) -> RpcResult<()> {
+        self.consensus.force_commit(block_hash).await.map_err(internal_error)
+    }
+
+    async fn pause(&self) -> RpcResult<bool> {
+        self.consensus.pause().await.map_err(internal_error)
+    }
+
+    async fn resume(&self) -> RpcResult<bool> {
+        self.consensus.resume().await.map_err(internal_error)
+    }
+
+    async fn exit_safe_mode(&self) -> RpcResult<bool> {
+        self.consensus.exit_safe_mode().await.map_err(internal_error)
+    }
+}
+
+fn internal_error(err: impl ToString) -> jsonrpsee::core::Error {
+    ErrorObject::owned(INTERNAL_ERROR_CODE, err.to_string(), None::<()>).into()
+}
@@ -1,35 +1,48 @@
 use crate::consensus::{
     assemble_peer_id, clayer_block_from_header, clayer_block_from_seal,
-    ClayerConsensusMessagingAgent, PbftConfig, PbftError, PbftMode, PbftState,
+    ClayerConsensusMessagingAgent, ConsensusCommand, ConsensusHandle, ConsensusSigner, PbftConfig,
+    PbftError, PbftMode, PbftState, ViewChangeReason,
 };
 
-use crate::engine_api::ApiService;
-use crate::engine_pbft::{handle_consensus_event, parse_consensus_message, ConsensusEvent};
+use crate::backpressure::{PipelineProgress, TickQueue};
+use crate::readiness::ConsensusReadiness;
+use crate::epoch::{EpochBoundary, EpochTracker};
+use crate::engine_api::{
+    forkchoice_history::ForkchoiceHistory,
+    identity::{ElIdentity, ElIdentityFile},
+    json_structures::BlockTag,
+    startup_self_test,
+    state_root::StateRootVerifier,
+    ApiService, ExecutionApi, PendingProposals, PendingWork,
+};
+use crate::engine_pbft::{
+    flush_expired_commits, handle_consensus_event, parse_consensus_message, CommitAggregator,
+    ConsensusEvent, EquivocationDetector,
+};
 use crate::{
     consensus::{ClayerConsensusEngine, ELECT_VOTING_ADDRESS},
     timing,
 };
 use crate::{create_sync_api, AuthHttpConfig};
-use futures_util::{future::BoxFuture, FutureExt};
+use futures_util::{future::BoxFuture, FutureExt, StreamExt};
 use reth_interfaces::clayer::{ClayerConsensusEvent, ClayerConsensusMessageAgentTrait};
 use reth_network::NetworkHandle;
-use reth_primitives::{ChainSpec, SealedHeader};
+use reth_primitives::{ChainSpec, ForkCondition, Hardfork, SealedHeader};
 use reth_provider::{
     BlockReaderIdExt, CanonChainTracker, ConsensusNumberReader, ConsensusNumberWriter,
     StateProviderFactory,
 };
 use reth_stages::PipelineEvent;
-use secp256k1::SecretKey;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread::sleep;
 use std::{
-    collections::VecDeque,
     future::Future,
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
+use tokio::sync::broadcast;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::*;
 
@@ -42,7 +55,7 @@ pub struct ClTask<Client, CDB> {
     insert_task: Option<BoxFuture<'static, Option<UnboundedReceiverStream<PipelineEvent>>>>,
     /// backlog of sets of transactions ready to be mined
     // queued: VecDeque<Vec<Arc<ValidPoolTransaction<<Pool as TransactionPool>::Transaction>>>>,
-    queued: VecDeque<u64>,
+    queued: TickQueue,
     /// The pipeline events to listen on
     pipe_line_events: Option<UnboundedReceiverStream<PipelineEvent>>,
     ///
@@ -55,9 +68,34 @@ pub struct ClTask<Client, CDB> {
     storages: Arc<CDB>,
     pbft_running_state: Arc<AtomicBool>,
     startup_latest_header: SealedHeader,
-    consensus_engine_task_handle: Option<std::thread::JoinHandle<()>>,
+    consensus_engine_task_handle: Option<std::thread::JoinHandle<Result<(), PbftError>>>,
     auth_config: AuthHttpConfig,
-    secret: SecretKey,
+    /// Signs the consensus messages of this node
+    signer: Arc<dyn ConsensusSigner>,
+    /// Verifies the state root of every payload before it is committed, unset trusts the EL
+    state_root_verifier: Option<Arc<dyn StateRootVerifier>>,
+    /// Whether there are transactions to build a block from, unset always builds payloads
+    pending_work: Option<Arc<dyn PendingWork>>,
+    /// Block proposals of the consensus engine, shared with the `clayer` RPC namespace
+    pending_proposals: PendingProposals,
+    /// Forkchoice updates sent to the execution layer, shared with the `clayer` RPC namespace
+    forkchoice_history: ForkchoiceHistory,
+    /// Progress of the pipeline, shared with the consensus engine for backpressure
+    pipeline_progress: PipelineProgress,
+    /// Readiness of the consensus engine, awaited by the node before it reports being started
+    readiness: ConsensusReadiness,
+    /// Configuration of the consensus engine, members are loaded on startup
+    pbft_config: PbftConfig,
+    /// Handle to send commands to the consensus engine
+    handle: ConsensusHandle,
+    /// Emits the epoch boundaries of the committed chain, shared with the consensus engine
+    epochs: EpochTracker,
+    /// Commands sent through the handle, moved to the consensus engine thread once it is started
+    commands: Option<tokio::sync::mpsc::UnboundedReceiver<ConsensusCommand>>,
+    /// Set to stop the consensus engine thread
+    shutdown: Arc<AtomicBool>,
+    /// Execution layer the engine talks to, unset connects to the authenticated endpoint
+    execution_api: Option<Arc<dyn ExecutionApi>>,
 }
 
 impl<Client, CDB> ClTask<Client, CDB>
@@ -67,7 +105,9 @@ where
 {
     /// Creates a new instance of the task
     pub(crate) fn new(
-        secret: SecretKey,
+        signer: Arc<dyn ConsensusSigner>,
+        state_root_verifier: Option<Arc<dyn StateRootVerifier>>,
+        pending_work: Option<Arc<dyn PendingWork>>,
         chain_spec: Arc<ChainSpec>,
         client: Client,
         auth_config: AuthHttpConfig,
@@ -75,13 +115,18 @@ where
         consensus_agent: ClayerConsensusMessagingAgent,
         storages: CDB,
         startup_latest_header: SealedHeader,
+        pbft_config: PbftConfig,
     ) -> Self {
+        let (handle, commands) = ConsensusHandle::new();
+        let epochs = EpochTracker::new(pbft_config.epoch_length);
         Self {
-            secret,
+            signer,
+            state_root_verifier,
+            pending_work,
             chain_spec,
             client,
             insert_task: None,
-            queued: Default::default(),
+            queued: TickQueue::new(pbft_config.max_queued_ticks),
             pipe_line_events: None,
             auth_config,
             block_publishing_ticker: timing::AsyncTicker::new(Duration::from_secs(30)),
@@ -91,9 +136,53 @@ where
             pbft_running_state: Arc::new(AtomicBool::new(false)),
             startup_latest_header,
             consensus_engine_task_handle: None,
+            pending_proposals: PendingProposals::default(),
+            forkchoice_history: ForkchoiceHistory::new(pbft_config.forkchoice_history_size),
+            pipeline_progress: PipelineProgress::default(),
+            readiness: ConsensusReadiness::default(),
+            pbft_config,
+            handle,
+            epochs,
+            commands: Some(commands),
+            shutdown: Arc::new(AtomicBool::new(false)),
+            execution_api: None,
         }
     }
 
+    /// Talks to `api` instead of the authenticated endpoint of the execution layer.
+    #[cfg(test)]
+    pub(crate) fn with_execution_api(mut self, api: Arc<dyn ExecutionApi>) -> Self {
+        self.execution_api = Some(api);
+        self
+    }
+
+    /// Returns a handle to send commands to the consensus engine.
+    pub fn handle(&self) -> ConsensusHandle {
+        self.handle.clone()
+    }
+
+    /// Returns a handle to the block proposals that were not committed yet.
+    pub fn pending_proposals(&self) -> PendingProposals {
+        self.pending_proposals.clone()
+    }
+
+    /// Returns a handle to the most recent forkchoice updates sent to the execution layer.
+    pub fn forkchoice_history(&self) -> ForkchoiceHistory {
+        self.forkchoice_history.clone()
+    }
+
+    /// Returns the readiness of the consensus engine, ready once the execution layer passed the
+    /// startup self-test and enough validators are connected to reach quorum.
+    pub fn readiness(&self) -> ConsensusReadiness {
+        self.readiness.clone()
+    }
+
+    /// Returns a receiver of the epoch boundaries, emitted every
+    /// [PbftConfig::epoch_length] committed blocks.
+    pub fn epoch_events(&self) -> broadcast::Receiver<EpochBoundary> {
+        self.epochs.subscribe()
+    }
+
     /// Sets the pipeline events to listen on.
     pub fn set_pipeline_events(&mut self, events: UnboundedReceiverStream<PipelineEvent>) {
         self.pipe_line_events = Some(events);
@@ -105,38 +194,107 @@ where
 
         let cdb = self.storages.clone();
         let client = self.client.clone();
-        let secret = self.secret.clone();
+        let signer = self.signer.clone();
+        let state_root_verifier = self.state_root_verifier.clone();
+        let pending_work = self.pending_work.clone();
 
         let startup_latest_header = self.startup_latest_header.clone();
+        let pending_proposals = self.pending_proposals.clone();
+        let forkchoice_history = self.forkchoice_history.clone();
+        let pipeline_progress = self.pipeline_progress.clone();
+        let readiness = self.readiness.clone();
+        let epochs = self.epochs.clone();
+        let mut pbft_config = self.pbft_config.clone();
+        // the gas limit of the parent is doubled on the block activating London
+        pbft_config.london_block = match self.chain_spec.fork(Hardfork::London) {
+            ForkCondition::Block(block) => Some(block),
+            _ => None,
+        };
+        let mut commands = self.commands.take().expect("consensus engine is started once");
+        let shutdown = self.shutdown.clone();
+        let execution_api = self.execution_api.clone();
         let thread_join_handle = std::thread::spawn(move || {
-            let api = create_sync_api(&auth_config);
+            let api = execution_api.unwrap_or_else(|| Arc::new(create_sync_api(&auth_config)));
+            match startup_self_test(api.as_ref(), startup_latest_header.hash) {
+                Ok(()) => {
+                    info!(target: "consensus::cl", head = ?startup_latest_header.hash, "Execution layer passed the startup self-test");
+                    readiness.set_engine_api_ready();
+                }
+                Err(err) if pbft_config.abort_on_failed_self_test => {
+                    panic!("Execution layer failed the startup self-test: {:?}", err)
+                }
+                Err(err) => {
+                    error!(target: "consensus::cl", ?err, "Execution layer failed the startup self-test")
+                }
+            }
+            match ElIdentity::fetch(api.as_ref()) {
+                Ok(identity) => {
+                    if let Some(path) = &pbft_config.el_identity_path {
+                        if let Err(err) = ElIdentityFile::new(path).record(&identity) {
+                            warn!(target: "consensus::cl", ?path, %err, "Failed to record the execution layer identity")
+                        }
+                    }
+                    // lets the other validators detect a fleet with different payload versions
+                    consensus_agent.set_engine_capabilities(identity.capabilities);
+                }
+                Err(err) => {
+                    warn!(target: "consensus::cl", ?err, "Failed to query the execution layer identity")
+                }
+            }
             let execution_block =
-                api.get_block_by_number("latest".to_string()).expect("get latest block error");
+                api.get_block_by_number(BlockTag::Latest).expect("get latest block error");
             info!(target: "consensus::cl","latest block: {:?}", execution_block);
             let validator_datas = api
                 .query_validators(ELECT_VOTING_ADDRESS.to_string(), startup_latest_header.number)
                 .expect("query validators failed");
             let peers = assemble_peer_id(validator_datas).expect("parse peer id failed");
 
-            let mut pbft_config = PbftConfig::default();
             pbft_config.members.clone_from(&peers);
-            let mut pbft_state = PbftState::new(
-                secret,
+            let mut pbft_state = PbftState::with_signer(
+                signer,
                 startup_latest_header.number,
                 startup_latest_header.timestamp,
                 &pbft_config,
-            );
+            )
+            .expect("invalid PBFT fault tolerance");
             let state = &mut pbft_state;
-            let mut consensus_engine = ClayerConsensusEngine::new(
-                consensus_agent.clone(),
-                ApiService::new(Arc::new(api)),
-                cdb,
-                client,
-            );
+            let mut service = ApiService::new(api)
+                .with_pending_proposals(pending_proposals)
+                .with_forkchoice_history(forkchoice_history)
+                .with_cancel_empty_payloads(pbft_config.cancel_empty_payloads)
+                .with_payload_id_max_age(pbft_config.payload_id_max_age)
+                .with_head_check_after_commit(pbft_config.check_head_after_commit)
+                .with_el_finalized_required(pbft_config.require_el_finalized)
+                .with_not_ready_retry(
+                    pbft_config.not_ready_retries,
+                    pbft_config.exponential_retry_base,
+                    pbft_config.exponential_retry_max,
+                );
+            if let Some(verifier) = state_root_verifier {
+                service = service.with_state_root_verifier(verifier);
+            }
+            if let Some(pending_work) = pending_work {
+                service = service.with_pending_work(pending_work);
+            }
+            if let Some(max_reorg_depth) = pbft_config.max_reorg_depth {
+                service = service.with_max_reorg_depth(max_reorg_depth);
+            }
+            if let Some(max_block_bytes) = pbft_config.max_block_bytes {
+                service = service.with_max_block_bytes(max_block_bytes);
+            }
+            let mut consensus_engine =
+                ClayerConsensusEngine::new(consensus_agent.clone(), service, cdb, client)
+                    .with_pipeline_progress(pipeline_progress)
+                    .with_epoch_tracker(epochs);
+
+            let mut commits = CommitAggregator::new(pbft_config.commit_aggregation_window);
+            let mut equivocations = EquivocationDetector::default();
 
             // let receiver = consensus_agent.receiver();
             let mut block_publishing_ticker =
-                timing::SyncTicker::new(pbft_config.block_publishing_delay);
+                timing::SyncTicker::new(pbft_config.block_publishing_delay)
+                    .with_jitter(pbft_config.block_publishing_jitter);
+            let mut head_check_ticker = timing::SyncTicker::new(pbft_config.head_check_interval);
 
             let seal = match consensus_engine.load_seal(startup_latest_header.hash) {
                 Ok(seal) => seal,
@@ -165,6 +323,16 @@ where
             consensus_engine.start_idle_timeout(state);
 
             loop {
+                if shutdown.load(Ordering::Relaxed) {
+                    log_any_error(consensus_engine.shutdown());
+                    info!(target: "consensus::cl", "Consensus engine stopped");
+                    break;
+                }
+
+                while let Ok(command) = commands.try_recv() {
+                    consensus_engine.on_command(command, state);
+                }
+
                 if let Some(event) = consensus_agent.pop_event() {
                     let incoming_event = match event {
                         ClayerConsensusEvent::PeerNetWork(peer_id, connect) => {
@@ -175,10 +343,14 @@ where
                             };
                             e
                         }
+                        ClayerConsensusEvent::PeerDead(peer_id) => {
+                            Some(ConsensusEvent::PeerDead(peer_id))
+                        }
                         ClayerConsensusEvent::PeerMessage(peer_id, bytes) => {
                             let e = match parse_consensus_message(&bytes) {
                                 Ok(msg) => Some(ConsensusEvent::PeerMessage(peer_id, msg)),
                                 Err(e) => {
+                                    consensus_agent.record_rejected(peer_id);
                                     log_any_error(Err(e));
                                     None
                                 }
@@ -196,7 +368,13 @@ where
                         }
                     };
                     if let Some(incoming_event) = incoming_event {
-                        match handle_consensus_event(&mut consensus_engine, incoming_event, state) {
+                        match handle_consensus_event(
+                            &mut consensus_engine,
+                            &mut commits,
+                            &mut equivocations,
+                            incoming_event,
+                            state,
+                        ) {
                             Ok(again) => {
                                 if !again {
                                     break;
@@ -209,6 +387,25 @@ where
                     log_any_error(consensus_engine.sync_seal(state));
                     sleep(pbft_config.update_recv_timeout);
                 }
+                log_any_error(flush_expired_commits(&mut consensus_engine, &mut commits, state));
+
+                if !readiness.state().peered && consensus_engine.has_connected_quorum(state) {
+                    info!(target: "consensus::cl", "Connected to enough validators to reach quorum");
+                    readiness.set_peered();
+                }
+
+                if !pbft_config.head_check_interval.is_zero() {
+                    let mut head_check = Ok(());
+                    head_check_ticker.tick(|| head_check = consensus_engine.reconcile_head());
+                    match head_check {
+                        Err(err @ PbftError::HeadDiverged(_)) => {
+                            error!(target: "consensus::cl", %err, "Stopping the consensus engine");
+                            log_any_error(consensus_engine.shutdown());
+                            return Err(err)
+                        }
+                        head_check => log_any_error(head_check),
+                    }
+                }
 
                 if state.is_validator() {
                     // If the block publishing delay has passed, attempt to publish a block
@@ -218,13 +415,31 @@ where
                     // If the idle timeout has expired, initiate a view change
                     if consensus_engine.check_idle_timeout_expired(state) {
                         warn!(target:"consensus::cl", "Idle timeout expired; proposing view change");
-                        log_any_error(consensus_engine.start_view_change(state, state.view + 1));
+                        log_any_error(consensus_engine.start_view_change(
+                            state,
+                            state.view + 1,
+                            ViewChangeReason::IdleTimeout,
+                        ));
                     }
 
                     // If the commit timeout has expired, initiate a view change
                     if consensus_engine.check_commit_timeout_expired(state) {
                         warn!(target:"consensus::cl", "Commit timeout expired; proposing view change");
-                        log_any_error(consensus_engine.start_view_change(state, state.view + 1));
+                        log_any_error(consensus_engine.start_view_change(
+                            state,
+                            state.view + 1,
+                            ViewChangeReason::CommitTimeout,
+                        ));
+                    }
+
+                    // If the view lasted too long, rotate the primary
+                    if consensus_engine.check_view_lifetime_expired(state) {
+                        warn!(target:"consensus::cl", "Maximum view lifetime expired; proposing view change");
+                        log_any_error(consensus_engine.start_view_change(
+                            state,
+                            state.view + 1,
+                            ViewChangeReason::MaxViewLifetime,
+                        ));
                     }
 
                     // Check the view change timeout if the node is view changing so we can start a new
@@ -235,11 +450,16 @@ where
                                 "View change timeout expired; proposing view change for view {}",
                                 v + 1
                             );
-                            log_any_error(consensus_engine.start_view_change(state, v + 1));
+                            log_any_error(consensus_engine.start_view_change(
+                                state,
+                                v + 1,
+                                ViewChangeReason::ViewChangeTimeout,
+                            ));
                         }
                     }
                 }
             }
+            Ok(())
         });
         self.consensus_engine_task_handle = Some(thread_join_handle);
     }
@@ -254,9 +474,27 @@ where
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
+
+        // a panic of the consensus engine takes down the task, e.g. a failed startup self-test, and
+        // so does a fatal error, either shuts down the node
+        if this.consensus_engine_task_handle.as_ref().is_some_and(|handle| handle.is_finished()) {
+            let handle = this.consensus_engine_task_handle.take().expect("checked above");
+            match handle.join() {
+                Ok(Ok(())) => return Poll::Ready(()),
+                Ok(Err(err)) => panic!("Consensus engine stopped: {err}"),
+                Err(panic) => std::panic::resume_unwind(panic),
+            }
+        }
+
         loop {
+            if let Some(events) = this.pipe_line_events.as_mut() {
+                while let Poll::Ready(Some(event)) = events.poll_next_unpin(cx) {
+                    this.pipeline_progress.on_pipeline_event(&event);
+                }
+            }
+
             if let Poll::Ready(x) = this.block_publishing_ticker.poll(cx) {
-                this.queued.push_back(x);
+                this.queued.push(x);
 
                 if !this.pbft_running_state.load(Ordering::Relaxed) {
                     this.pbft_running_state.store(true, Ordering::Relaxed);
@@ -265,7 +503,7 @@ where
             }
 
             if this.insert_task.is_none() {
-                if this.queued.is_empty() {
+                if this.queued.pop_front().is_none() {
                     // nothing to insert
                     break;
                 }
@@ -310,6 +548,46 @@ where
     }
 }
 
+impl<Client, CDB> ClTask<Client, CDB> {
+    /// Stops the consensus engine thread and waits until it saved its in-memory state.
+    ///
+    /// The engine finishes the event it is handling first, which may include calls to the
+    /// execution layer. If it does not stop within [PbftConfig::shutdown_timeout], the thread is
+    /// abandoned with its unsaved state so the process can exit.
+    ///
+    /// This blocks the calling thread, call it from a blocking task when running on a runtime.
+    pub fn shutdown(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.consensus_engine_task_handle.take() {
+            let timeout = self.pbft_config.shutdown_timeout;
+            match timing::join_timeout(handle, timeout) {
+                Some(Ok(result)) => log_any_error(result),
+                Some(Err(_)) => {
+                    error!(target: "consensus::cl", "Consensus engine panicked before shutdown")
+                }
+                None => {
+                    warn!(target: "consensus::cl", ?timeout, "Consensus engine did not stop within the shutdown timeout, abandoning its flush")
+                }
+            }
+        }
+    }
+}
+
+impl<Client, CDB> Drop for ClTask<Client, CDB> {
+    fn drop(&mut self) {
+        // waiting on a runtime worker stalls the runtime while the engine is stuck in a call to
+        // the execution layer, the engine then stops on its own once the call returns
+        if tokio::runtime::Handle::try_current().is_ok() {
+            self.shutdown.store(true, Ordering::Relaxed);
+            if self.consensus_engine_task_handle.take().is_some() {
+                debug!(target: "consensus::cl", "Consensus task dropped on a runtime, not waiting for the engine to stop");
+            }
+        } else {
+            self.shutdown();
+        }
+    }
+}
+
 fn log_any_error(res: Result<(), PbftError>) {
     if let Err(e) = res {
         // Treat errors that result from other nodes' messages as warnings
@@ -321,3 +599,209 @@ fn log_any_error(res: Result<(), PbftError>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        consensus::LocalSigner,
+        engine_api::{ClRpcError, ExecutionBlock, ExecutionPayloadWrapperV2},
+    };
+    use reth_db::models::consensus::ConsensusBytes;
+    use reth_ecies::util::pk2id;
+    use reth_interfaces::provider::ProviderResult;
+    use reth_network::{NetworkConfigBuilder, NetworkManager};
+    use reth_primitives::{PeerId, B256, MAINNET};
+    use reth_provider::test_utils::{MockEthProvider, NoopProvider};
+    use reth_rpc_types::engine::{
+        ExecutionPayloadInputV2, ForkchoiceState, ForkchoiceUpdated, PayloadAttributes, PayloadId,
+        PayloadStatus, PayloadStatusEnum,
+    };
+    use secp256k1::{KeyPair, SecretKey, SECP256K1};
+    use std::{ops::RangeInclusive, time::Instant};
+
+    /// Execution layer that doesn't answer the startup self-test until `release` is set
+    struct StuckEl {
+        members: Vec<PeerId>,
+        release: Arc<AtomicBool>,
+    }
+
+    impl ExecutionApi for StuckEl {
+        fn get_block_by_number(
+            &self,
+            _tag: BlockTag,
+        ) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            Ok(None)
+        }
+
+        fn get_block_by_hash(
+            &self,
+            _block_hash: B256,
+        ) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            Ok(None)
+        }
+
+        fn forkchoice_updated_v2(
+            &self,
+            _forkchoice_state: ForkchoiceState,
+            _payload_attributes: Option<PayloadAttributes>,
+        ) -> Result<ForkchoiceUpdated, ClRpcError> {
+            while !self.release.load(Ordering::Relaxed) {
+                sleep(Duration::from_millis(10));
+            }
+            Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid))
+        }
+
+        fn new_payload_v2(
+            &self,
+            _payload: ExecutionPayloadInputV2,
+        ) -> Result<PayloadStatus, ClRpcError> {
+            Err(ClRpcError::RequestFailed("not supported".to_string()))
+        }
+
+        fn get_payload_v2(
+            &self,
+            _payload_id: PayloadId,
+        ) -> Result<ExecutionPayloadWrapperV2, ClRpcError> {
+            Err(ClRpcError::RequestFailed("not supported".to_string()))
+        }
+
+        fn query_validators(
+            &self,
+            _contract_address: String,
+            _block_number: u64,
+        ) -> Result<Vec<Vec<u8>>, ClRpcError> {
+            // the contract returns every id in two halves
+            Ok(self.members.iter().flat_map(|id| [id[..32].to_vec(), id[32..].to_vec()]).collect())
+        }
+    }
+
+    /// Consensus storage without any seals, sets `released` once the engine dropped it
+    struct ReleasedStorage {
+        released: Arc<AtomicBool>,
+    }
+
+    impl Drop for ReleasedStorage {
+        fn drop(&mut self) {
+            self.released.store(true, Ordering::Relaxed);
+        }
+    }
+
+    impl ConsensusNumberReader for ReleasedStorage {
+        fn last_consensus_number(&self) -> ProviderResult<u64> {
+            Ok(0)
+        }
+
+        fn consensus_number(&self, _hash: B256) -> ProviderResult<Option<u64>> {
+            Ok(None)
+        }
+
+        fn consensus_content(&self, _hash: B256) -> ProviderResult<Option<ConsensusBytes>> {
+            Ok(None)
+        }
+
+        fn committed_payload(&self, _number: u64) -> ProviderResult<Option<ConsensusBytes>> {
+            Ok(None)
+        }
+
+        fn view_changes(&self, _views: RangeInclusive<u64>) -> ProviderResult<Vec<ConsensusBytes>> {
+            Ok(vec![])
+        }
+
+        fn randao_mix(&self, _number: u64) -> ProviderResult<Option<B256>> {
+            Ok(None)
+        }
+
+        fn first_randao_mix_number(&self) -> ProviderResult<Option<u64>> {
+            Ok(None)
+        }
+    }
+
+    impl ConsensusNumberWriter for ReleasedStorage {
+        fn save_consensus_number(&self, _hash: B256, _num: u64) -> ProviderResult<bool> {
+            Ok(true)
+        }
+
+        fn save_consensus_content(&self, _hash: B256, _ct: ConsensusBytes) -> ProviderResult<bool> {
+            Ok(true)
+        }
+
+        fn save_committed_payload(
+            &self,
+            _number: u64,
+            _payload: ConsensusBytes,
+        ) -> ProviderResult<bool> {
+            Ok(true)
+        }
+
+        fn prune_committed_payloads(&self, _number: u64) -> ProviderResult<usize> {
+            Ok(0)
+        }
+
+        fn save_view_change(&self, _view: u64, _record: ConsensusBytes) -> ProviderResult<bool> {
+            Ok(true)
+        }
+
+        fn save_randao_mix(&self, _number: u64, _mix: B256) -> ProviderResult<bool> {
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn drop_on_runtime_does_not_wait_for_stuck_execution_layer() {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let secret = SecretKey::new(&mut rand::thread_rng());
+        let id = pk2id(&KeyPair::from_secret_key(SECP256K1, &secret).public_key());
+        let network_config = NetworkConfigBuilder::new(secret)
+            .listener_port(0)
+            .disable_discovery()
+            .build(NoopProvider::default());
+        let network = runtime.block_on(NetworkManager::new(network_config)).unwrap();
+
+        let release = Arc::new(AtomicBool::new(false));
+        let released = Arc::new(AtomicBool::new(false));
+        let el = StuckEl {
+            members: vec![id, PeerId::random(), PeerId::random(), PeerId::random()],
+            release: release.clone(),
+        };
+        let pbft_config =
+            PbftConfig { shutdown_timeout: Duration::from_secs(60), ..Default::default() };
+        let mut task = ClTask::new(
+            Arc::new(LocalSigner::new(secret)),
+            None,
+            None,
+            MAINNET.clone(),
+            MockEthProvider::default(),
+            AuthHttpConfig {
+                port: 0,
+                auth: vec![0; 32],
+                token_refresh_interval: Duration::from_secs(60),
+                http2: false,
+                max_concurrent_requests: 1,
+                in_process: None,
+            },
+            network.handle().clone(),
+            ClayerConsensusMessagingAgent::new(),
+            ReleasedStorage { released: released.clone() },
+            MAINNET.sealed_genesis_header(),
+            pbft_config,
+        )
+        .with_execution_api(Arc::new(el));
+        task.start_clayer_consensus_engine();
+
+        // the engine is stuck in the startup self-test, dropping the task on the runtime must not
+        // wait for it
+        let start = Instant::now();
+        runtime.block_on(async move { drop(task) });
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert!(!released.load(Ordering::Relaxed));
+
+        // once the call returns, the engine shuts down on its own and releases the storage
+        release.store(true, Ordering::Relaxed);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !released.load(Ordering::Relaxed) {
+            assert!(Instant::now() < deadline, "consensus engine did not stop");
+            sleep(Duration::from_millis(10));
+        }
+    }
+}
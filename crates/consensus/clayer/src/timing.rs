@@ -1,27 +1,69 @@
+use rand::Rng;
 use serde_derive::{Deserialize, Serialize};
 use std::{
     task::{Context, Poll},
     thread::sleep,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+/// Largest jitter of a [SyncTicker] in percent of its period, so ticks keep their order
+pub const MAX_TICKER_JITTER: u8 = 50;
+
 pub struct SyncTicker {
     last: std::time::Instant,
     timeout: std::time::Duration,
+    /// Shift of every tick by up to this percentage of the period in either direction
+    jitter: u8,
+    /// When the current tick is due, including its jitter
+    due: std::time::Instant,
 }
 
 impl SyncTicker {
     pub fn new(period: Duration) -> Self {
-        SyncTicker { last: std::time::Instant::now(), timeout: period }
+        let now = Instant::now();
+        SyncTicker { last: now, timeout: period, jitter: 0, due: now + period }
+    }
+
+    /// Shifts every tick randomly by up to `percent` of the period, at most
+    /// [MAX_TICKER_JITTER], so nodes ticking at the same period don't act at the same time.
+    ///
+    /// With jitter the ticks stay on the schedule of the period, i.e. the `n`th tick is due `n`
+    /// periods after the first one plus its own jitter, which doesn't add up over time.
+    pub fn with_jitter(mut self, percent: u8) -> Self {
+        self.jitter = percent.min(MAX_TICKER_JITTER);
+        self.due = self.jittered(self.last + self.timeout);
+        self
     }
 
     // Do some work if the timeout has expired
-    pub fn tick<T: FnMut()>(&mut self, mut callback: T) {
-        let elapsed = std::time::Instant::now() - self.last;
-        if elapsed >= self.timeout {
-            callback();
-            self.last = std::time::Instant::now();
+    pub fn tick<T: FnMut()>(&mut self, callback: T) {
+        self.tick_at(Instant::now(), callback)
+    }
+
+    fn tick_at<T: FnMut()>(&mut self, now: Instant, mut callback: T) {
+        if now < self.due {
+            return
+        }
+        callback();
+        if self.jitter == 0 {
+            self.last = Instant::now().max(now);
+            self.due = self.last + self.timeout;
+            return
         }
+        // the next tick is scheduled from the unjittered time of this one, unless the ticker fell
+        // more than a period behind
+        self.last += self.timeout;
+        if self.last + self.timeout < now {
+            self.last = now;
+        }
+        self.due = self.jittered(self.last + self.timeout);
+    }
+
+    /// Returns `at` shifted by a random jitter.
+    fn jittered(&self, at: Instant) -> Instant {
+        let max_shift = self.timeout * self.jitter as u32 / 100;
+        let shift = rand::thread_rng().gen_range(Duration::ZERO..=max_shift * 2);
+        (at + shift).checked_sub(max_shift).unwrap_or(at)
     }
 }
 
@@ -101,6 +143,31 @@ impl Timeout {
     }
 }
 
+/// Measures how long the current view lasts, to rotate a primary that held its role for too long
+#[derive(Debug, Clone)]
+pub struct ViewLifetime {
+    max: Duration,
+    view: u64,
+    started: Instant,
+}
+
+impl ViewLifetime {
+    pub fn new(max: Duration) -> Self {
+        ViewLifetime { max, view: 0, started: Instant::now() }
+    }
+
+    /// Returns whether `view` lasted longer than the maximum lifetime at `now`, the lifetime
+    /// restarts whenever the view changes.
+    pub fn check_expired_at(&mut self, view: u64, now: Instant) -> bool {
+        if view != self.view {
+            self.view = view;
+            self.started = now;
+            return false
+        }
+        now.saturating_duration_since(self.started) > self.max
+    }
+}
+
 /// With exponential backoff, repeatedly try the callback until the result is `Ok`
 pub fn retry_until_ok<T, E, F: FnMut() -> Result<T, E>>(
     base: Duration,
@@ -127,3 +194,103 @@ pub fn retry_until_ok<T, E, F: FnMut() -> Result<T, E>>(
         }
     }
 }
+
+/// With exponential backoff, retry the callback as long as it fails with an error `retryable`
+/// accepts, at most `max_retries` times. Returns the first result that is not retried.
+pub fn retry_bounded<T, E, F: FnMut() -> Result<T, E>>(
+    base: Duration,
+    max: Duration,
+    max_retries: u32,
+    retryable: impl Fn(&E) -> bool,
+    mut callback: F,
+) -> Result<T, E> {
+    let mut delay = base;
+    let mut retries = 0;
+    loop {
+        match callback() {
+            Err(err) if retries < max_retries && retryable(&err) => {
+                sleep(delay);
+                retries += 1;
+                delay = delay.saturating_mul(2).min(max);
+            }
+            res => return res,
+        }
+    }
+}
+
+/// Waits up to `timeout` for the thread of `handle` to finish and joins it.
+///
+/// Returns `None` if the thread is still running after the timeout, it is then left running
+/// detached.
+pub fn join_timeout<T>(
+    handle: std::thread::JoinHandle<T>,
+    timeout: Duration,
+) -> Option<std::thread::Result<T>> {
+    let deadline = Instant::now() + timeout;
+    while !handle.is_finished() {
+        if Instant::now() >= deadline {
+            return None
+        }
+        sleep(Duration::from_millis(10));
+    }
+    Some(handle.join())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn jittered_ticks_stay_on_schedule() {
+        let period = Duration::from_millis(100);
+        let mut ticker = SyncTicker::new(period).with_jitter(20);
+        let start = ticker.last;
+
+        // simulate polling the ticker every millisecond
+        let mut ticks = Vec::new();
+        for ms in 0..=100 * 100 {
+            let now = start + Duration::from_millis(ms);
+            ticker.tick_at(now, || ticks.push(now));
+        }
+
+        assert!(ticks.len() >= 99, "{} ticks", ticks.len());
+        for (n, tick) in ticks.iter().enumerate() {
+            let scheduled = start + period * (n as u32 + 1);
+            // within 20% of the scheduled time, polling adds up to 1ms
+            assert!(*tick >= scheduled - period / 5, "tick {n} too early");
+            assert!(
+                *tick <= scheduled + period / 5 + Duration::from_millis(1),
+                "tick {n} too late"
+            );
+        }
+        // the ticks are spread out
+        assert!(ticks.windows(2).any(|pair| pair[1] - pair[0] != period));
+    }
+
+    #[test]
+    fn abandons_thread_hanging_past_timeout() {
+        // a consensus thread that hangs on its final flush
+        let release = Arc::new(AtomicBool::new(false));
+        let hanging = release.clone();
+        let handle = std::thread::spawn(move || {
+            while !hanging.load(Ordering::Relaxed) {
+                sleep(Duration::from_millis(5));
+            }
+        });
+
+        let timeout = Duration::from_millis(100);
+        let start = Instant::now();
+        assert!(join_timeout(handle, timeout).is_none());
+        let elapsed = start.elapsed();
+        assert!(elapsed >= timeout && elapsed < timeout * 5, "{elapsed:?}");
+        release.store(true, Ordering::Relaxed);
+
+        // a thread that exits in time is joined
+        let handle = std::thread::spawn(|| 7);
+        assert_eq!(join_timeout(handle, timeout).unwrap().unwrap(), 7);
+    }
+}
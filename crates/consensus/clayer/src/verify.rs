@@ -0,0 +1,320 @@
+//! Audits the committed chain against an execution layer.
+//!
+//! Like the [ChainReplayer](crate::ChainReplayer), the committed chain is the canonical chain of
+//! the local database up to the first block without a seal. Every committed block must be known
+//! to the execution layer with the same number and parent.
+
+use crate::engine_api::{ClRpcError, ExecutionApi};
+use reth_interfaces::provider::ProviderError;
+use reth_primitives::{BlockNumber, B256};
+use reth_provider::{BlockReader, ConsensusNumberReader};
+use std::{fmt, sync::Arc};
+
+/// A committed block the execution layer does not agree with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainIssue {
+    /// Number of the committed block
+    pub number: BlockNumber,
+    /// Hash of the committed block
+    pub hash: B256,
+    /// What the execution layer reported for the block
+    pub kind: ChainIssueKind,
+}
+
+/// How the execution layer disagrees with a committed block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainIssueKind {
+    /// The execution layer does not know the block
+    Missing,
+    /// The execution layer knows the block at another position of the chain
+    Mismatch {
+        /// Block number reported by the execution layer
+        number: BlockNumber,
+        /// Parent hash reported by the execution layer
+        parent_hash: B256,
+    },
+}
+
+impl fmt::Display for ChainIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ChainIssueKind::Missing => {
+                write!(
+                    f,
+                    "block {} ({}) is missing from the execution layer",
+                    self.number, self.hash
+                )
+            }
+            ChainIssueKind::Mismatch { number, parent_hash } => write!(
+                f,
+                "block {} ({}) is block {number} with parent {parent_hash} in the execution layer",
+                self.number, self.hash
+            ),
+        }
+    }
+}
+
+/// Result of a completed audit
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Number of committed blocks checked
+    pub checked: u64,
+    /// The committed blocks the execution layer does not agree with
+    pub issues: Vec<ChainIssue>,
+}
+
+impl VerifyReport {
+    /// Returns `true` if the execution layer agrees with every checked block.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Errors that stop an audit
+#[derive(Debug, thiserror::Error)]
+pub enum VerifyError {
+    /// Reading the local database failed
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    /// An engine API call failed
+    #[error("engine api call failed: {0:?}")]
+    Api(ClRpcError),
+    /// A block of the audited range is missing from the local database
+    #[error("block {0} is missing from the local database")]
+    MissingBlock(BlockNumber),
+}
+
+/// Checks that every committed block is known to an execution layer via `eth_getBlockByHash`.
+pub struct ChainVerifier<Client, CDB> {
+    client: Client,
+    storages: CDB,
+    api: Arc<dyn ExecutionApi>,
+}
+
+impl<Client, CDB> ChainVerifier<Client, CDB>
+where
+    Client: BlockReader,
+    CDB: ConsensusNumberReader,
+{
+    /// Creates a new verifier reading blocks from `client` and seals from `storages`
+    pub fn new(client: Client, storages: CDB, api: Arc<dyn ExecutionApi>) -> Self {
+        Self { client, storages, api }
+    }
+
+    /// Audits the committed blocks `from..=to`.
+    ///
+    /// Stops early at the first block that has no seal. Blocks the execution layer does not agree
+    /// with are reported, the audit continues with the next block.
+    pub fn verify(&self, from: BlockNumber, to: BlockNumber) -> Result<VerifyReport, VerifyError> {
+        let mut report = VerifyReport::default();
+        for number in from..=to {
+            let block =
+                self.client.block(number.into())?.ok_or(VerifyError::MissingBlock(number))?;
+            let block = block.seal_slow();
+            let hash = block.hash;
+
+            if self.storages.consensus_content(hash)?.is_none() {
+                tracing::info!(target: "consensus::cl", number, ?hash, "Reached the end of the committed chain");
+                break
+            }
+            report.checked += 1;
+
+            let kind = match self.api.get_block_by_hash(hash).map_err(VerifyError::Api)? {
+                None => ChainIssueKind::Missing,
+                Some(el_block)
+                    if el_block.block_number != number ||
+                        el_block.parent_hash != block.parent_hash =>
+                {
+                    ChainIssueKind::Mismatch {
+                        number: el_block.block_number,
+                        parent_hash: el_block.parent_hash,
+                    }
+                }
+                Some(_) => continue,
+            };
+            let issue = ChainIssue { number, hash, kind };
+            tracing::warn!(target: "consensus::cl", %issue, "Execution layer disagrees with a committed block");
+            report.issues.push(issue);
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_api::{json_structures::BlockTag, ExecutionBlock, ExecutionPayloadWrapperV2};
+    use reth_db::models::consensus::ConsensusBytes;
+    use reth_interfaces::provider::ProviderResult;
+    use reth_primitives::{Block, Header, SealedHeader, U256};
+    use reth_provider::test_utils::MockEthProvider;
+    use reth_rpc_types::engine::{
+        ExecutionPayloadInputV2, ForkchoiceState, ForkchoiceUpdated, PayloadAttributes, PayloadId,
+        PayloadStatus,
+    };
+    use std::collections::{HashMap, HashSet};
+
+    /// Seals stored for the committed blocks
+    #[derive(Default)]
+    struct MockSeals(HashSet<B256>);
+
+    impl ConsensusNumberReader for MockSeals {
+        fn last_consensus_number(&self) -> ProviderResult<BlockNumber> {
+            Ok(0)
+        }
+
+        fn consensus_number(&self, _hash: B256) -> ProviderResult<Option<BlockNumber>> {
+            Ok(None)
+        }
+
+        fn consensus_content(&self, hash: B256) -> ProviderResult<Option<ConsensusBytes>> {
+            Ok(self.0.contains(&hash).then(|| ConsensusBytes { content: vec![] }))
+        }
+
+        fn committed_payload(
+            &self,
+            _number: BlockNumber,
+        ) -> ProviderResult<Option<ConsensusBytes>> {
+            Ok(None)
+        }
+
+        fn view_changes(
+            &self,
+            _views: std::ops::RangeInclusive<u64>,
+        ) -> ProviderResult<Vec<ConsensusBytes>> {
+            Ok(vec![])
+        }
+
+        fn randao_mix(&self, _number: u64) -> ProviderResult<Option<B256>> {
+            Ok(None)
+        }
+
+        fn first_randao_mix_number(&self) -> ProviderResult<Option<u64>> {
+            Ok(None)
+        }
+    }
+
+    /// An execution layer that knows a fixed set of blocks
+    #[derive(Default)]
+    struct MockEl(HashMap<B256, ExecutionBlock>);
+
+    impl MockEl {
+        fn insert(&mut self, header: &SealedHeader) {
+            self.0.insert(
+                header.hash,
+                ExecutionBlock {
+                    block_hash: header.hash,
+                    block_number: header.number,
+                    parent_hash: header.parent_hash,
+                    total_difficulty: U256::ZERO,
+                    timestamp: header.timestamp,
+                },
+            );
+        }
+    }
+
+    impl ExecutionApi for MockEl {
+        fn get_block_by_number(
+            &self,
+            _tag: BlockTag,
+        ) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            Ok(None)
+        }
+
+        fn get_block_by_hash(
+            &self,
+            block_hash: B256,
+        ) -> Result<Option<ExecutionBlock>, ClRpcError> {
+            Ok(self.0.get(&block_hash).copied())
+        }
+
+        fn forkchoice_updated_v2(
+            &self,
+            _forkchoice_state: ForkchoiceState,
+            _payload_attributes: Option<PayloadAttributes>,
+        ) -> Result<ForkchoiceUpdated, ClRpcError> {
+            Err(ClRpcError::RequestFailed("not following a chain".to_string()))
+        }
+
+        fn new_payload_v2(
+            &self,
+            _payload: ExecutionPayloadInputV2,
+        ) -> Result<PayloadStatus, ClRpcError> {
+            Err(ClRpcError::RequestFailed("not importing payloads".to_string()))
+        }
+
+        fn get_payload_v2(
+            &self,
+            _payload_id: PayloadId,
+        ) -> Result<ExecutionPayloadWrapperV2, ClRpcError> {
+            Err(ClRpcError::RequestFailed("not building payloads".to_string()))
+        }
+
+        fn query_validators(
+            &self,
+            _contract_address: String,
+            _block_number: u64,
+        ) -> Result<Vec<Vec<u8>>, ClRpcError> {
+            Ok(vec![])
+        }
+    }
+
+    /// Returns a provider holding `len` blocks on top of a genesis block, and all headers
+    fn chain(len: u64) -> (MockEthProvider, Vec<SealedHeader>) {
+        let provider = MockEthProvider::default();
+        let mut headers = Vec::new();
+        let mut parent_hash = B256::ZERO;
+        for number in 0..=len {
+            let header = Header { number, parent_hash, timestamp: number, ..Default::default() };
+            let sealed = header.clone().seal_slow();
+            let block = Block { header, body: vec![], ommers: vec![], withdrawals: None };
+            provider.add_block(sealed.hash, block);
+            parent_hash = sealed.hash;
+            headers.push(sealed);
+        }
+        (provider, headers)
+    }
+
+    #[test]
+    fn flags_missing_block() {
+        let (provider, headers) = chain(5);
+        // blocks 1..=4 are committed, 5 is not
+        let seals = MockSeals(headers[1..=4].iter().map(|header| header.hash).collect());
+        let mut el = MockEl::default();
+        for header in headers.iter().filter(|header| header.number != 3) {
+            el.insert(header);
+        }
+        let verifier = ChainVerifier::new(provider, seals, Arc::new(el));
+
+        let report = verifier.verify(1, 5).unwrap();
+        assert_eq!(report.checked, 4);
+        assert_eq!(
+            report.issues,
+            vec![ChainIssue { number: 3, hash: headers[3].hash, kind: ChainIssueKind::Missing }]
+        );
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn flags_mismatched_block() {
+        let (provider, headers) = chain(2);
+        let seals = MockSeals(headers[1..].iter().map(|header| header.hash).collect());
+        let mut el = MockEl::default();
+        headers.iter().for_each(|header| el.insert(header));
+        // the EL reports block 2 with another parent
+        el.0.get_mut(&headers[2].hash).unwrap().parent_hash = B256::with_last_byte(9);
+        let verifier = ChainVerifier::new(provider, seals, Arc::new(el));
+
+        let report = verifier.verify(1, 2).unwrap();
+        assert_eq!(
+            report.issues,
+            vec![ChainIssue {
+                number: 2,
+                hash: headers[2].hash,
+                kind: ChainIssueKind::Mismatch { number: 2, parent_hash: B256::with_last_byte(9) },
+            }]
+        );
+
+        assert!(matches!(verifier.verify(1, 3), Err(VerifyError::MissingBlock(3))));
+    }
+}
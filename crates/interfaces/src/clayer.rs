@@ -9,6 +9,8 @@ pub enum ClayerConsensusEvent {
     PeerNetWork(PeerId, bool),
     /// Consensus message
     PeerMessage(PeerId, reth_primitives::Bytes),
+    /// Connected peer stopped answering the heartbeat
+    PeerDead(PeerId),
     /// Consensus OnBlockValid
     BlockValid(B256),
     /// Consensus OnBlockInvalid
@@ -36,6 +38,9 @@ pub trait ClayerConsensusMessageAgentTrait: Send + Sync + Clone {
 
     /// broadcast consensus
     fn broadcast_consensus(&self, peers: Vec<PeerId>, data: reth_primitives::Bytes);
+    /// Called by the network for every message taken from the pending consensus listener, in
+    /// order, with the number of peers it was sent to
+    fn on_consensus_dispatched(&self, _peers: usize) {}
     /// get all peers
     fn get_peers(&self) -> Vec<PeerId>;
 }
@@ -1074,7 +1074,7 @@ impl SyncTargetBlock {
 
 /// The builder for [ReverseHeadersDownloader] with
 /// some default settings
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReverseHeadersDownloaderBuilder {
     /// The batch size per one request
     request_limit: u64,
@@ -31,6 +31,42 @@ pub struct ClayerConsensusMessage {
     pub header_signature: ClayerSignature,
     /// message body
     pub message_bytes: Bytes,
+    /// remaining number of hops the message may be relayed, not covered by the header signature
+    pub ttl: u8,
+    /// network the message is intended for, by default the chain id, not covered by the header
+    /// signature
+    pub topic: u64,
+}
+
+/// Latest version of the consensus message envelope.
+///
+/// Version 2 added `ttl` and `topic` to [ClayerConsensusMessage], version 1 messages are encoded
+/// as [ClayerConsensusMessageV1]. Nodes decode both, but only send version 2 messages once all
+/// validators are upgraded.
+pub const CLAYER_PROTOCOL_VERSION: u8 = 2;
+
+/// Consensus layer message of protocol version 1, without the relay fields of
+/// [ClayerConsensusMessage]
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClayerConsensusMessageV1 {
+    /// header bytes
+    pub header_bytes: Bytes,
+    /// consensus signature
+    pub header_signature: ClayerSignature,
+    /// message body
+    pub message_bytes: Bytes,
+}
+
+impl From<ClayerConsensusMessage> for ClayerConsensusMessageV1 {
+    fn from(message: ClayerConsensusMessage) -> Self {
+        Self {
+            header_bytes: message.header_bytes,
+            header_signature: message.header_signature,
+            message_bytes: message.message_bytes,
+        }
+    }
 }
 
 /// Represents all common information used in a PBFT message
@@ -201,6 +237,7 @@ impl From<u8> for PbftMessageType {
 #[derive_arbitrary(rlp)]
 #[derive(Clone, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[rlp(trailing)]
 pub struct ClayerBlock {
     /// info
     pub info: PbftMessageInfo,
@@ -210,6 +247,8 @@ pub struct ClayerBlock {
     pub seal_bytes: Bytes,
     /// payload id
     pub payload_id: B64,
+    /// metadata signed by the proposer, omitted from the encoding if unset
+    pub metadata: Option<Bytes>,
 }
 
 impl ClayerBlock {
@@ -220,7 +259,7 @@ impl ClayerBlock {
         seal_bytes: Bytes,
         payload_id: B64,
     ) -> Self {
-        ClayerBlock { info, block, seal_bytes, payload_id }
+        ClayerBlock { info, block, seal_bytes, payload_id, metadata: None }
     }
 
     /// Get the block id, call hash_slow for performance
@@ -93,15 +93,19 @@ where
     }
 
     fn propagate_consensus(&mut self, peers: Vec<PeerId>, data: reth_primitives::Bytes) {
+        let mut dispatched = 0;
         for (_, (peer_id, _peer)) in self.peers.iter_mut().enumerate() {
             if peers.is_empty() {
                 self.network.send_consensus(*peer_id, data.clone());
+                dispatched += 1;
             } else {
                 if peers.contains(peer_id) {
                     self.network.send_consensus(*peer_id, data.clone());
+                    dispatched += 1;
                 }
             }
         }
+        self.clayer.on_consensus_dispatched(dispatched);
     }
 }
 
@@ -53,6 +53,12 @@ impl PeersHandle {
         self.send(PeerCommand::Remove(peer_id));
     }
 
+    /// Replaces the set of consensus validators, which are reconnected with the
+    /// [`PeersConfig::validator_reconnect_interval`].
+    pub fn set_validators(&self, validators: HashSet<PeerId>) {
+        self.send(PeerCommand::SetValidators(validators));
+    }
+
     /// Send a reputation change for the given peer.
     pub fn reputation_change(&self, peer_id: PeerId, kind: ReputationChangeKind) {
         self.send(PeerCommand::ReputationChange(peer_id, kind));
@@ -114,6 +120,14 @@ pub struct PeersManager {
     last_tick: Instant,
     /// Maximum number of backoff attempts before we give up on a peer and dropping.
     max_backoff_count: u32,
+    /// Peers of the consensus validator set and the consensus bootstrap peers, they are never
+    /// given up on.
+    validators: HashSet<PeerId>,
+    /// Bootstrap peers of the consensus overlay, kept in `validators` when the validator set is
+    /// replaced.
+    consensus_bootstrap: HashSet<PeerId>,
+    /// How long validators are backed off before they are dialed again.
+    validator_reconnect_interval: Duration,
 }
 
 impl PeersManager {
@@ -130,14 +144,19 @@ impl PeersManager {
             connect_trusted_nodes_only,
             basic_nodes,
             max_backoff_count,
+            validator_reconnect_interval,
+            consensus_bootstrap_nodes,
         } = config;
         let (manager_tx, handle_rx) = mpsc::unbounded_channel();
         let now = Instant::now();
 
         // We use half of the interval to decrease the max duration to `150%` in worst case
-        let unban_interval = ban_duration.min(backoff_durations.low) / 2;
+        let unban_interval =
+            ban_duration.min(backoff_durations.low).min(validator_reconnect_interval) / 2;
 
-        let mut peers = HashMap::with_capacity(trusted_nodes.len() + basic_nodes.len());
+        let mut peers = HashMap::with_capacity(
+            trusted_nodes.len() + basic_nodes.len() + consensus_bootstrap_nodes.len(),
+        );
 
         for NodeRecord { address, tcp_port, udp_port: _, id } in trusted_nodes {
             peers.entry(id).or_insert_with(|| Peer::trusted(SocketAddr::from((address, tcp_port))));
@@ -147,6 +166,12 @@ impl PeersManager {
             peers.entry(id).or_insert_with(|| Peer::new(SocketAddr::from((address, tcp_port))));
         }
 
+        let mut consensus_bootstrap = HashSet::with_capacity(consensus_bootstrap_nodes.len());
+        for NodeRecord { address, tcp_port, udp_port: _, id } in consensus_bootstrap_nodes {
+            peers.entry(id).or_insert_with(|| Peer::new(SocketAddr::from((address, tcp_port))));
+            consensus_bootstrap.insert(id);
+        }
+
         Self {
             peers,
             manager_tx,
@@ -166,6 +191,9 @@ impl PeersManager {
             connect_trusted_nodes_only,
             last_tick: Instant::now(),
             max_backoff_count,
+            validators: consensus_bootstrap.clone(),
+            consensus_bootstrap,
+            validator_reconnect_interval,
         }
     }
 
@@ -406,7 +434,9 @@ impl PeersManager {
             Entry::Occupied(mut entry) => {
                 self.connection_info.decr_state(entry.get().state);
 
-                if entry.get().remove_after_disconnect && !entry.get().is_trusted() {
+                let is_validator = self.validators.contains(&peer_id);
+                if entry.get().remove_after_disconnect && !entry.get().is_trusted() && !is_validator
+                {
                     // this peer should be removed from the set
                     entry.remove();
                     self.queued_actions.push_back(PeerAction::PeerRemoved(peer_id));
@@ -416,7 +446,10 @@ impl PeersManager {
                     // session to that peer
                     entry.get_mut().severe_backoff_counter = 0;
                     entry.get_mut().state = PeerConnectionState::Idle;
-                    return
+                    // validators are needed for quorum, reconnect right away
+                    if !is_validator {
+                        return
+                    }
                 }
             }
             Entry::Vacant(_) => return,
@@ -481,8 +514,14 @@ impl PeersManager {
             let mut backoff_until = None;
             let mut remove_peer = false;
 
+            let is_validator = self.validators.contains(peer_id);
             if let Some(peer) = self.peers.get_mut(peer_id) {
-                if let Some(kind) = err.should_backoff() {
+                if is_validator {
+                    // validators are needed for quorum, they are retried at a fixed short interval
+                    // regardless of the error
+                    backoff_until =
+                        Some(std::time::Instant::now() + self.validator_reconnect_interval);
+                } else if let Some(kind) = err.should_backoff() {
                     // Increment peer.backoff_counter
                     if kind.is_severe() {
                         peer.severe_backoff_counter += 1;
@@ -504,9 +543,12 @@ impl PeersManager {
                 self.connection_info.decr_state(peer.state);
                 peer.state = PeerConnectionState::Idle;
 
-                if peer.severe_backoff_counter > self.max_backoff_count && !peer.is_trusted() {
+                if peer.severe_backoff_counter > self.max_backoff_count &&
+                    !peer.is_trusted() &&
+                    !is_validator
+                {
                     // mark peer for removal if it has been backoff too many times and is _not_
-                    // trusted
+                    // trusted or a validator
                     remove_peer = true;
                 }
             }
@@ -644,8 +686,8 @@ impl PeersManager {
 
     /// Returns the idle peer with the highest reputation.
     ///
-    /// Peers that are `trusted`, see [PeerKind], are prioritized as long as they're not currently
-    /// marked as banned or backed off.
+    /// Peers that are `trusted`, see [PeerKind], and consensus validators are prioritized as long
+    /// as they're not currently marked as banned or backed off.
     ///
     /// If `connect_trusted_nodes_only` is enabled, see [PeersConfig], then this will only consider
     /// `trusted` peers.
@@ -659,16 +701,20 @@ impl PeersManager {
                 (!self.connect_trusted_nodes_only || peer.is_trusted())
         });
 
+        let validators = &self.validators;
+        let is_prioritized =
+            |peer_id: &PeerId, peer: &Peer| peer.is_trusted() || validators.contains(peer_id);
+
         // keep track of the best peer, if there's one
         let mut best_peer = unconnected.next()?;
 
-        if best_peer.1.is_trusted() {
+        if is_prioritized(best_peer.0, best_peer.1) {
             return Some((*best_peer.0, best_peer.1))
         }
 
         for maybe_better in unconnected {
-            // if the peer is trusted, return it immediately
-            if maybe_better.1.is_trusted() {
+            // if the peer is trusted or a validator, return it immediately
+            if is_prioritized(maybe_better.0, maybe_better.1) {
                 return Some((*maybe_better.0, maybe_better.1))
             }
 
@@ -745,6 +791,10 @@ impl PeersManager {
                     PeerCommand::GetPeers(tx) => {
                         let _ = tx.send(self.iter_peers().collect());
                     }
+                    PeerCommand::SetValidators(validators) => {
+                        self.validators = validators;
+                        self.validators.extend(self.consensus_bootstrap.iter().copied());
+                    }
                 }
             }
 
@@ -763,15 +813,22 @@ impl PeersManager {
 
                 // clear the backoff list of expired backoffs, and mark the relevant peers as
                 // ready to be dialed
+                let mut released_validator = false;
                 self.backed_off_peers.retain(|peer_id, until| {
                     if now > *until {
                         if let Some(peer) = self.peers.get_mut(peer_id) {
                             peer.backed_off = false;
                         }
+                        released_validator |= self.validators.contains(peer_id);
                         return false
                     }
                     true
-                })
+                });
+
+                // validators are dialed as soon as they are released instead of on the next refill
+                if released_validator {
+                    self.fill_outbound_slots();
+                }
             }
 
             while self.refill_slots_interval.poll_tick(cx).is_ready() {
@@ -1040,6 +1097,8 @@ pub(crate) enum PeerCommand {
     GetPeer(PeerId, oneshot::Sender<Option<Peer>>),
     /// Get node information on all peers
     GetPeers(oneshot::Sender<Vec<NodeRecord>>),
+    /// Replace the set of consensus validators
+    SetValidators(HashSet<PeerId>),
 }
 
 /// Actions the peer manager can trigger.
@@ -1133,6 +1192,18 @@ pub struct PeersConfig {
     ///
     /// The backoff duration increases with number of backoff attempts.
     pub backoff_durations: PeerBackoffDurations,
+    /// How long to backoff consensus validators before reconnecting, see
+    /// [`PeersHandle::set_validators`].
+    ///
+    /// Validators are needed for quorum, so unlike other peers they are retried at this fixed
+    /// interval and never dropped from the set.
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub validator_reconnect_interval: Duration,
+    /// Bootstrap nodes of the consensus overlay, dialed at launch.
+    ///
+    /// They are retained and reconnected like consensus validators but, unlike trusted nodes, are
+    /// regular peers of the eth network.
+    pub consensus_bootstrap_nodes: HashSet<NodeRecord>,
 }
 
 impl Default for PeersConfig {
@@ -1149,6 +1220,8 @@ impl Default for PeersConfig {
             connect_trusted_nodes_only: false,
             basic_nodes: Default::default(),
             max_backoff_count: 5,
+            validator_reconnect_interval: Duration::from_secs(1),
+            consensus_bootstrap_nodes: Default::default(),
         }
     }
 }
@@ -1248,6 +1321,18 @@ impl PeersConfig {
         self
     }
 
+    /// Configures how long to backoff consensus validators before reconnecting.
+    pub fn with_validator_reconnect_interval(mut self, interval: Duration) -> Self {
+        self.validator_reconnect_interval = interval;
+        self
+    }
+
+    /// Bootstrap nodes of the consensus overlay, see [`PeersConfig::consensus_bootstrap_nodes`].
+    pub fn with_consensus_bootstrap_nodes(mut self, nodes: HashSet<NodeRecord>) -> Self {
+        self.consensus_bootstrap_nodes = nodes;
+        self
+    }
+
     /// Read from file nodes available at launch. Ignored if None.
     pub fn with_basic_nodes_from_file(
         self,
@@ -1353,7 +1438,7 @@ mod test {
         DisconnectReason,
     };
     use reth_net_common::ban_list::BanList;
-    use reth_network_api::ReputationChangeKind;
+    use reth_network_api::{PeerKind, ReputationChangeKind};
     use reth_primitives::{PeerId, B512};
     use std::{
         collections::HashSet,
@@ -1744,6 +1829,58 @@ mod test {
         assert!(peers.peers.get(&peer).is_none());
     }
 
+    #[tokio::test]
+    async fn test_reconnect_validator() {
+        let peer = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let config = PeersConfig::default()
+            .with_validator_reconnect_interval(Duration::from_millis(100))
+            .with_max_backoff_count(2);
+        let max_backoff_count = config.max_backoff_count;
+        let mut peers = PeersManager::new(config);
+        peers.handle().set_validators(HashSet::from([peer]));
+        poll_fn(|cx| {
+            assert!(peers.poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        peers.add_peer(peer, socket_addr, None);
+
+        match event!(peers) {
+            PeerAction::PeerAdded(peer_id) => {
+                assert_eq!(peer_id, peer);
+            }
+            _ => unreachable!(),
+        }
+
+        // a validator is redialed after the validator interval, well before the next refill of
+        // the outbound slots, and never dropped from the set
+        for attempt in 0..=max_backoff_count + 2 {
+            let action = tokio::time::timeout(
+                Duration::from_secs(1),
+                PeerActionFuture { peers: &mut peers },
+            )
+            .await
+            .unwrap_or_else(|_| panic!("validator was not redialed, attempt {attempt}"));
+            match action {
+                PeerAction::Connect { peer_id, .. } => {
+                    assert_eq!(peer_id, peer);
+                }
+                _ => unreachable!(),
+            }
+
+            peers.on_pending_session_dropped(
+                &socket_addr,
+                &peer,
+                &PendingSessionHandshakeError::Eth(
+                    io::Error::new(io::ErrorKind::ConnectionRefused, "peer unreachable").into(),
+                ),
+            );
+            assert!(peers.backed_off_peers.contains_key(&peer));
+        }
+        assert!(peers.peers.contains_key(&peer));
+    }
+
     #[tokio::test]
     async fn test_ban_on_pending_drop() {
         let peer = PeerId::random();
@@ -2127,6 +2264,89 @@ mod test {
         }
     }
 
+    #[tokio::test]
+    async fn test_connect_consensus_bootstrap_nodes() {
+        let trusted_peer = PeerId::random();
+        let bootstrap_peer = PeerId::random();
+        let bootstrap_sock = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 3)), 8008);
+        let config = PeersConfig::default()
+            .with_trusted_nodes(HashSet::from([NodeRecord {
+                address: IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)),
+                tcp_port: 8008,
+                udp_port: 8008,
+                id: trusted_peer,
+            }]))
+            .with_consensus_bootstrap_nodes(HashSet::from([NodeRecord {
+                address: IpAddr::V4(Ipv4Addr::new(127, 0, 1, 3)),
+                tcp_port: 8008,
+                udp_port: 8008,
+                id: bootstrap_peer,
+            }]))
+            .with_validator_reconnect_interval(Duration::from_millis(100))
+            .with_max_backoff_count(0);
+        let mut peers = PeersManager::new(config);
+
+        let basic_peer = PeerId::random();
+        let basic_sock = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8009);
+        peers.add_peer(basic_peer, basic_sock, None);
+
+        match event!(peers) {
+            PeerAction::PeerAdded(peer_id) => {
+                assert_eq!(peer_id, basic_peer);
+            }
+            _ => unreachable!(),
+        }
+        // the bootstrap peer is dialed at launch along with the trusted peer, before other peers
+        let mut dialed = HashSet::new();
+        for _ in 0..2 {
+            match event!(peers) {
+                PeerAction::Connect { peer_id, .. } => dialed.insert(peer_id),
+                _ => unreachable!(),
+            };
+        }
+        assert_eq!(dialed, HashSet::from([trusted_peer, bootstrap_peer]));
+        match event!(peers) {
+            PeerAction::Connect { peer_id, .. } => {
+                assert_eq!(peer_id, basic_peer);
+            }
+            _ => unreachable!(),
+        }
+
+        // it is not a trusted peer
+        assert_eq!(peers.peers_by_kind(PeerKind::Trusted).collect::<Vec<_>>(), vec![trusted_peer]);
+        assert_eq!(peers.peers.get(&bootstrap_peer).unwrap().kind, PeerKind::Basic);
+
+        // but is kept and redialed like a validator, also once the validator set is replaced
+        peers.handle().set_validators(HashSet::new());
+        poll_fn(|cx| {
+            assert!(peers.poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        for _ in 0..2 {
+            peers.on_pending_session_dropped(
+                &bootstrap_sock,
+                &bootstrap_peer,
+                &PendingSessionHandshakeError::Eth(
+                    io::Error::new(io::ErrorKind::ConnectionRefused, "peer unreachable").into(),
+                ),
+            );
+            assert!(peers.peers.contains_key(&bootstrap_peer));
+            let action = tokio::time::timeout(
+                Duration::from_secs(1),
+                PeerActionFuture { peers: &mut peers },
+            )
+            .await
+            .expect("bootstrap peer was not redialed");
+            match action {
+                PeerAction::Connect { peer_id, .. } => {
+                    assert_eq!(peer_id, bootstrap_peer);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
     #[tokio::test]
     async fn test_connect_trusted_nodes_only() {
         let trusted_peer = PeerId::random();
@@ -0,0 +1,82 @@
+//! Gas target of the payloads built by this node.
+//!
+//! Like the [TxOrdering](crate::TxOrdering), the target only applies if this node builds the
+//! payload.
+
+/// Stops adding transactions to a payload once its gas used reaches a share of the block gas
+/// limit.
+///
+/// The transaction that crosses the target is still included, so the gas used of a full payload
+/// lies between the target and the target plus the gas of a single transaction, and never exceeds
+/// the gas limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasTarget {
+    /// Share of the gas limit in percent, between 1 and 100
+    percent: u8,
+}
+
+impl GasTarget {
+    /// Creates a target of `percent` of the block gas limit, capped at 100.
+    pub fn new(percent: u8) -> Self {
+        Self { percent: percent.min(100) }
+    }
+
+    /// Returns the share of the gas limit in percent.
+    pub fn percent(&self) -> u8 {
+        self.percent
+    }
+
+    /// Returns the gas a payload with the given `gas_limit` should use.
+    pub fn target_gas(&self, gas_limit: u64) -> u64 {
+        (gas_limit as u128 * self.percent as u128 / 100) as u64
+    }
+
+    /// Returns `true` if a payload using `gas_used` reached the target.
+    pub fn is_reached(&self, gas_used: u64, gas_limit: u64) -> bool {
+        gas_used >= self.target_gas(gas_limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fills a block like the payload builder, returns the gas used.
+    fn fill(target: Option<GasTarget>, gas_limit: u64, tx_gas: u64, txs: usize) -> u64 {
+        let mut gas_used = 0;
+        for _ in 0..txs {
+            if target.map_or(false, |target| target.is_reached(gas_used, gas_limit)) {
+                break
+            }
+            if gas_used + tx_gas > gas_limit {
+                continue
+            }
+            gas_used += tx_gas;
+        }
+        gas_used
+    }
+
+    #[test]
+    fn gas_used_within_target_band() {
+        let (gas_limit, tx_gas) = (30_000_000, 21_000);
+        for percent in [1, 25, 50, 99, 100] {
+            let target = GasTarget::new(percent);
+            let gas_used = fill(Some(target), gas_limit, tx_gas, 10_000);
+            assert!(gas_used >= target.target_gas(gas_limit).min(gas_limit / tx_gas * tx_gas));
+            assert!(gas_used < target.target_gas(gas_limit) + tx_gas);
+            assert!(gas_used <= gas_limit);
+        }
+
+        // not enough transactions to reach the target
+        assert_eq!(fill(Some(GasTarget::new(50)), gas_limit, tx_gas, 10), 10 * tx_gas);
+        // without a target the block is filled up
+        assert_eq!(fill(None, gas_limit, tx_gas, 10_000), gas_limit / tx_gas * tx_gas);
+    }
+
+    #[test]
+    fn target_is_capped() {
+        assert_eq!(GasTarget::new(150).percent(), 100);
+        assert_eq!(GasTarget::new(100).target_gas(u64::MAX), u64::MAX);
+        assert_eq!(GasTarget::new(50).target_gas(30_000_000), 15_000_000);
+    }
+}
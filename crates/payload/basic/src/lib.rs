@@ -59,6 +59,12 @@ use tracing::{debug, trace, warn};
 
 mod metrics;
 
+mod gas_target;
+pub use gas_target::GasTarget;
+
+mod ordering;
+pub use ordering::{FeePriorityOrdering, FifoOrdering, TxOrdering, TxOrderingPolicy};
+
 #[cfg(feature = "optimism")]
 mod optimism;
 #[cfg(feature = "optimism")]
@@ -67,7 +73,30 @@ pub use optimism::OptimismPayloadBuilder;
 /// Ethereum payload builder
 #[derive(Debug, Clone, Copy, Default)]
 #[non_exhaustive]
-pub struct EthereumPayloadBuilder;
+pub struct EthereumPayloadBuilder {
+    /// The order of the transactions in built payloads.
+    ordering: TxOrdering,
+    /// Stops filling built payloads once they reach the target.
+    gas_target: Option<GasTarget>,
+}
+
+impl EthereumPayloadBuilder {
+    /// Sets the order of the transactions in built payloads.
+    ///
+    /// Defaults to [TxOrdering::Pool], the order of the transaction pool.
+    pub fn with_ordering(mut self, ordering: TxOrdering) -> Self {
+        self.ordering = ordering;
+        self
+    }
+
+    /// Sets the gas built payloads are filled up to.
+    ///
+    /// Defaults to `None`, payloads are filled up to the gas limit.
+    pub fn with_gas_target(mut self, gas_target: Option<GasTarget>) -> Self {
+        self.gas_target = gas_target;
+        self
+    }
+}
 
 /// The [`PayloadJobGenerator`] that creates [`BasicPayloadJob`]s.
 #[derive(Debug)]
@@ -107,7 +136,7 @@ impl<Client, Pool, Tasks> BasicPayloadJobGenerator<Client, Pool, Tasks> {
             executor,
             config,
             chain_spec,
-            EthereumPayloadBuilder,
+            EthereumPayloadBuilder::default(),
         )
     }
 }
@@ -782,7 +811,7 @@ where
         &self,
         args: BuildArguments<Pool, Client>,
     ) -> Result<BuildOutcome, PayloadBuilderError> {
-        default_payload_builder(args)
+        ordered_payload_builder(args, self.ordering, self.gas_target)
     }
 }
 
@@ -795,6 +824,22 @@ where
 pub fn default_payload_builder<Pool, Client>(
     args: BuildArguments<Pool, Client>,
 ) -> Result<BuildOutcome, PayloadBuilderError>
+where
+    Client: StateProviderFactory,
+    Pool: TransactionPool,
+{
+    ordered_payload_builder(args, TxOrdering::Pool, None)
+}
+
+/// Constructs an Ethereum transaction payload like [default_payload_builder], but includes the
+/// best transactions from the pool in the given [TxOrdering].
+///
+/// If a [GasTarget] is given, no more transactions are added once the payload reached it.
+pub fn ordered_payload_builder<Pool, Client>(
+    args: BuildArguments<Pool, Client>,
+    ordering: TxOrdering,
+    gas_target: Option<GasTarget>,
+) -> Result<BuildOutcome, PayloadBuilderError>
 where
     Client: StateProviderFactory,
     Pool: TransactionPool,
@@ -822,7 +867,7 @@ where
     let base_fee = initialized_block_env.basefee.to::<u64>();
 
     let mut executed_txs = Vec::new();
-    let mut best_txs = pool.best_transactions_with_base_fee(base_fee);
+    let mut best_txs = ordering.apply(pool.best_transactions_with_base_fee(base_fee), base_fee);
 
     let mut total_fees = U256::ZERO;
 
@@ -840,6 +885,13 @@ where
 
     let mut receipts = Vec::new();
     while let Some(pool_tx) = best_txs.next() {
+        if gas_target
+            .map_or(false, |target| target.is_reached(cumulative_gas_used, block_gas_limit))
+        {
+            trace!(target: "payload_builder", cumulative_gas_used, block_gas_limit, "reached gas target");
+            break
+        }
+
         // ensure we still have capacity for this transaction
         if cumulative_gas_used + pool_tx.gas_limit() > block_gas_limit {
             // we can't fit this transaction into the block, so we need to mark it as invalid
@@ -0,0 +1,284 @@
+//! Ordering of the transactions in payloads built by this node.
+//!
+//! A policy only applies if this node builds the payload. If blocks are built by an external
+//! execution layer, the ordering is delegated to it.
+
+use reth_primitives::Address;
+use reth_transaction_pool::{BestTransactions, PoolTransaction, ValidPoolTransaction};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    fmt,
+    str::FromStr,
+    sync::Arc,
+};
+
+/// Decides the order of the transactions in a payload.
+///
+/// The transactions are handed to the policy in the order the pool yields them, which is nonce
+/// order per sender. A policy may interleave senders freely, but must keep the transactions of each
+/// sender in nonce order.
+pub trait TxOrderingPolicy: fmt::Debug + Send + Sync {
+    /// Returns the `transactions` in the order they should be included in a block with the given
+    /// `base_fee`.
+    fn order<T: PoolTransaction>(
+        &self,
+        transactions: Vec<Arc<ValidPoolTransaction<T>>>,
+        base_fee: u64,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>>;
+}
+
+/// Includes transactions in the order they were added to the pool.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct FifoOrdering;
+
+impl TxOrderingPolicy for FifoOrdering {
+    fn order<T: PoolTransaction>(
+        &self,
+        transactions: Vec<Arc<ValidPoolTransaction<T>>>,
+        _base_fee: u64,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        merge_senders(transactions, |tx| tx.timestamp)
+    }
+}
+
+/// Includes transactions with the highest tip first, transactions paying the same tip are included
+/// in the order they were added to the pool.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct FeePriorityOrdering;
+
+impl TxOrderingPolicy for FeePriorityOrdering {
+    fn order<T: PoolTransaction>(
+        &self,
+        transactions: Vec<Arc<ValidPoolTransaction<T>>>,
+        base_fee: u64,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        merge_senders(transactions, |tx| {
+            (
+                Reverse(tx.transaction.effective_tip_per_gas(base_fee).unwrap_or_default()),
+                tx.timestamp,
+            )
+        })
+    }
+}
+
+/// The configurable [TxOrderingPolicy]s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TxOrdering {
+    /// Keep the order of the transaction pool.
+    #[default]
+    Pool,
+    /// See [FifoOrdering].
+    Fifo,
+    /// See [FeePriorityOrdering].
+    FeePriority,
+}
+
+impl TxOrdering {
+    /// Applies the ordering to the `best` transactions of the pool.
+    ///
+    /// Unless the pool order is kept, all transactions are taken from `best` up front, so the
+    /// returned iterator does not receive pool updates.
+    pub fn apply<'a, T: PoolTransaction + 'a>(
+        &self,
+        mut best: Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T>>> + 'a>,
+        base_fee: u64,
+    ) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T>>> + 'a> {
+        if *self == TxOrdering::Pool {
+            return best
+        }
+        best.no_updates();
+        Box::new(OrderedTransactions::new(self.order(best.collect(), base_fee)))
+    }
+}
+
+impl TxOrderingPolicy for TxOrdering {
+    fn order<T: PoolTransaction>(
+        &self,
+        transactions: Vec<Arc<ValidPoolTransaction<T>>>,
+        base_fee: u64,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        match self {
+            TxOrdering::Pool => transactions,
+            TxOrdering::Fifo => FifoOrdering.order(transactions, base_fee),
+            TxOrdering::FeePriority => FeePriorityOrdering.order(transactions, base_fee),
+        }
+    }
+}
+
+impl fmt::Display for TxOrdering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxOrdering::Pool => f.write_str("pool"),
+            TxOrdering::Fifo => f.write_str("fifo"),
+            TxOrdering::FeePriority => f.write_str("fee-priority"),
+        }
+    }
+}
+
+impl FromStr for TxOrdering {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pool" => Ok(TxOrdering::Pool),
+            "fifo" => Ok(TxOrdering::Fifo),
+            "fee-priority" => Ok(TxOrdering::FeePriority),
+            _ => Err(format!(
+                "unknown transaction ordering {s}, expected pool, fifo or fee-priority"
+            )),
+        }
+    }
+}
+
+/// Merges the transactions of all senders, always taking the next transaction of the sender whose
+/// next transaction has the lowest key. Ties are resolved by the original order.
+fn merge_senders<T, K>(
+    transactions: Vec<Arc<ValidPoolTransaction<T>>>,
+    key: impl Fn(&ValidPoolTransaction<T>) -> K,
+) -> Vec<Arc<ValidPoolTransaction<T>>>
+where
+    T: PoolTransaction,
+    K: Ord,
+{
+    let len = transactions.len();
+    let mut senders = HashMap::new();
+    let mut queues: Vec<VecDeque<(usize, Arc<ValidPoolTransaction<T>>)>> = Vec::new();
+    for (index, tx) in transactions.into_iter().enumerate() {
+        let queue = *senders.entry(tx.sender()).or_insert_with(|| {
+            queues.push(VecDeque::new());
+            queues.len() - 1
+        });
+        queues[queue].push_back((index, tx));
+    }
+
+    let mut heads = BinaryHeap::new();
+    for (queue, txs) in queues.iter().enumerate() {
+        if let Some((index, tx)) = txs.front() {
+            heads.push(Reverse((key(tx), *index, queue)));
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(len);
+    while let Some(Reverse((_, _, queue))) = heads.pop() {
+        let (_, tx) = queues[queue].pop_front().expect("queue has a head");
+        ordered.push(tx);
+        if let Some((index, next)) = queues[queue].front() {
+            heads.push(Reverse((key(next), *index, queue)));
+        }
+    }
+    ordered
+}
+
+/// [BestTransactions] over transactions that were already ordered by a [TxOrderingPolicy].
+struct OrderedTransactions<T: PoolTransaction> {
+    transactions: VecDeque<Arc<ValidPoolTransaction<T>>>,
+    /// Senders whose remaining transactions are skipped
+    invalid: HashSet<Address>,
+    skip_blobs: bool,
+}
+
+impl<T: PoolTransaction> OrderedTransactions<T> {
+    fn new(transactions: Vec<Arc<ValidPoolTransaction<T>>>) -> Self {
+        Self { transactions: transactions.into(), invalid: HashSet::new(), skip_blobs: false }
+    }
+}
+
+impl<T: PoolTransaction> Iterator for OrderedTransactions<T> {
+    type Item = Arc<ValidPoolTransaction<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(tx) = self.transactions.pop_front() {
+            if self.invalid.contains(&tx.sender()) {
+                continue
+            }
+            if self.skip_blobs && tx.is_eip4844() {
+                // later transactions of the sender depend on the skipped one
+                self.invalid.insert(tx.sender());
+                continue
+            }
+            return Some(tx)
+        }
+        None
+    }
+}
+
+impl<T: PoolTransaction> BestTransactions for OrderedTransactions<T> {
+    fn mark_invalid(&mut self, transaction: &Self::Item) {
+        // the transactions of a sender are in nonce order, so all later ones depend on it
+        self.invalid.insert(transaction.sender());
+    }
+
+    fn no_updates(&mut self) {}
+
+    fn skip_blobs(&mut self) {
+        self.set_skip_blobs(true)
+    }
+
+    fn set_skip_blobs(&mut self, skip_blobs: bool) {
+        self.skip_blobs = skip_blobs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_transaction_pool::test_utils::{MockTransaction, MockTransactionFactory};
+
+    #[test]
+    fn fifo_and_fee_priority_differ() {
+        let mut factory = MockTransactionFactory::default();
+        // sender a submits two cheap transactions, then sender b a more expensive one
+        let a0 = MockTransaction::eip1559().with_priority_fee(1).with_max_fee(100);
+        let a1 = a0.next();
+        let b0 = MockTransaction::eip1559().with_priority_fee(10).with_max_fee(100);
+        let transactions: Vec<_> =
+            [a0, a1, b0].into_iter().map(|tx| factory.validated_arc(tx)).collect();
+        let hashes = |txs: Vec<Arc<ValidPoolTransaction<MockTransaction>>>| {
+            txs.iter().map(|tx| *tx.hash()).collect::<Vec<_>>()
+        };
+        let (a0, a1, b0) =
+            (*transactions[0].hash(), *transactions[1].hash(), *transactions[2].hash());
+
+        let fifo = hashes(TxOrdering::Fifo.order(transactions.clone(), 0));
+        assert_eq!(fifo, vec![a0, a1, b0]);
+
+        let fee_priority = hashes(TxOrdering::FeePriority.order(transactions.clone(), 0));
+        assert_eq!(fee_priority, vec![b0, a0, a1]);
+
+        assert_eq!(hashes(TxOrdering::Pool.order(transactions, 0)), vec![a0, a1, b0]);
+    }
+
+    #[test]
+    fn keeps_nonce_order_of_sender() {
+        let mut factory = MockTransactionFactory::default();
+        // the second transaction of the sender pays more, but depends on the first
+        let a0 = MockTransaction::eip1559().with_priority_fee(1).with_max_fee(100);
+        let a1 = a0.next().with_priority_fee(50);
+        let b0 = MockTransaction::eip1559().with_priority_fee(10).with_max_fee(100);
+        let transactions: Vec<_> =
+            [a0, a1, b0].into_iter().map(|tx| factory.validated_arc(tx)).collect();
+
+        let ordered = TxOrdering::FeePriority.order(transactions.clone(), 0);
+        let nonces: Vec<_> = ordered.iter().map(|tx| (tx.sender(), tx.nonce())).collect();
+        let (a, b) = (transactions[0].sender(), transactions[2].sender());
+        assert_eq!(nonces, vec![(b, 0), (a, 0), (a, 1)]);
+
+        // invalidating a transaction skips the rest of its sender
+        let mut best = OrderedTransactions::new(ordered);
+        assert_eq!(best.next().unwrap().sender(), b);
+        let first = best.next().unwrap();
+        best.mark_invalid(&first);
+        assert!(best.next().is_none());
+    }
+
+    #[test]
+    fn parse_tx_ordering() {
+        for ordering in [TxOrdering::Pool, TxOrdering::Fifo, TxOrdering::FeePriority] {
+            assert_eq!(ordering.to_string().parse::<TxOrdering>().unwrap(), ordering);
+        }
+        assert!("lifo".parse::<TxOrdering>().is_err());
+    }
+}
@@ -278,6 +278,15 @@ impl SealedBlock {
         }
     }
 
+    /// Seal sealed block with transaction senders recovered without ensuring that the signatures
+    /// have a low `s` value, see [TransactionSigned::recover_signer_unchecked].
+    pub fn try_seal_with_senders_unchecked(self) -> Result<SealedBlockWithSenders, Self> {
+        match TransactionSigned::recover_signers_unchecked(&self.body, self.body.len()) {
+            Some(senders) => Ok(SealedBlockWithSenders { block: self, senders }),
+            None => Err(self),
+        }
+    }
+
     /// Unseal the block
     pub fn unseal(self) -> Block {
         Block {
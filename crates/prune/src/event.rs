@@ -10,4 +10,10 @@ pub enum PrunerEvent {
         elapsed: Duration,
         stats: BTreeMap<PruneSegment, (PruneProgress, usize)>,
     },
+    /// Emitted by [PrunerStallMonitor][crate::PrunerStallMonitor] when the pruner has more data
+    /// to prune, but made no progress within the stall window.
+    Stalled {
+        /// Time since the pruner last made progress.
+        since_progress: Duration,
+    },
 }
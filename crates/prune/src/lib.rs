@@ -14,6 +14,7 @@ mod error;
 mod event;
 mod metrics;
 mod pruner;
+mod stall;
 pub mod segments;
 
 use crate::metrics::Metrics;
@@ -21,3 +22,4 @@ pub use builder::PrunerBuilder;
 pub use error::PrunerError;
 pub use event::PrunerEvent;
 pub use pruner::{Pruner, PrunerResult, PrunerWithResult};
+pub use stall::{PrunerStallMonitor, DEFAULT_STALL_WINDOW};
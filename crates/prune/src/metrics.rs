@@ -1,4 +1,8 @@
-use reth_metrics::{metrics, metrics::Histogram, Metrics};
+use reth_metrics::{
+    metrics,
+    metrics::{Counter, Gauge, Histogram},
+    Metrics,
+};
 use reth_primitives::PruneSegment;
 use std::collections::HashMap;
 
@@ -30,3 +34,12 @@ pub(crate) struct PrunerSegmentMetrics {
     /// Pruning duration for this segment
     pub(crate) duration_seconds: Histogram,
 }
+
+#[derive(Metrics)]
+#[metrics(scope = "pruner")]
+pub(crate) struct PrunerStallMetrics {
+    /// Whether the pruner made no progress within the stall window
+    pub(crate) stalled: Gauge,
+    /// Number of times the stall window elapsed without pruning progress
+    pub(crate) stalls: Counter,
+}
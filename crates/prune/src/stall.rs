@@ -0,0 +1,171 @@
+//! Detection of a pruner that stopped making progress.
+
+use crate::{metrics::PrunerStallMetrics, PrunerEvent};
+use reth_primitives::PruneProgress;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{Instant, Sleep};
+use tokio_stream::Stream;
+
+/// Default period without pruning progress after which the pruner is considered stalled.
+pub const DEFAULT_STALL_WINDOW: Duration = Duration::from_secs(600);
+
+/// A Stream of [PrunerEvent]s that additionally emits [PrunerEvent::Stalled] when the pruner
+/// reported outstanding data but made no progress within the configured window.
+///
+/// A pruner run made progress if it deleted at least one entry or finished all segments. The
+/// monitor only arms once a run reported [PruneProgress::HasMoreData], so an idle node with
+/// nothing left to prune is never reported as stalled.
+#[derive(Debug)]
+pub struct PrunerStallMonitor<S> {
+    events: S,
+    window: Duration,
+    deadline: Pin<Box<Sleep>>,
+    last_progress: Instant,
+    /// Whether the last pruner run left data to prune.
+    has_more_data: bool,
+    stalled: bool,
+    metrics: PrunerStallMetrics,
+}
+
+impl<S> PrunerStallMonitor<S> {
+    /// Creates a new [PrunerStallMonitor] wrapping the given pruner event stream.
+    pub fn new(events: S, window: Duration) -> Self {
+        Self {
+            events,
+            window,
+            deadline: Box::pin(tokio::time::sleep(window)),
+            last_progress: Instant::now(),
+            has_more_data: false,
+            stalled: false,
+            metrics: PrunerStallMetrics::default(),
+        }
+    }
+
+    /// Returns `true` if no pruning progress was made within the window.
+    pub fn is_stalled(&self) -> bool {
+        self.stalled
+    }
+
+    fn on_event(&mut self, event: &PrunerEvent) {
+        let PrunerEvent::Finished { stats, .. } = event else { return };
+
+        let pruned = stats.values().any(|(_, pruned)| *pruned > 0);
+        let finished =
+            stats.values().all(|(progress, _)| matches!(progress, PruneProgress::Finished));
+        self.has_more_data = !finished;
+
+        if pruned || finished {
+            self.last_progress = Instant::now();
+            self.deadline.as_mut().reset(self.last_progress + self.window);
+            if self.stalled {
+                self.stalled = false;
+                self.metrics.stalled.set(0.0);
+            }
+        }
+    }
+}
+
+impl<S> Stream for PrunerStallMonitor<S>
+where
+    S: Stream<Item = PrunerEvent> + Unpin,
+{
+    type Item = PrunerEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        match Pin::new(&mut this.events).poll_next(cx) {
+            Poll::Ready(Some(event)) => {
+                this.on_event(&event);
+                return Poll::Ready(Some(event))
+            }
+            Poll::Ready(None) => return Poll::Ready(None),
+            Poll::Pending => {}
+        }
+
+        while this.deadline.as_mut().poll(cx).is_ready() {
+            let since_progress = this.last_progress.elapsed();
+            // Keep reporting every window for as long as the pruner stays stalled.
+            this.deadline.as_mut().reset(Instant::now() + this.window);
+
+            if this.has_more_data {
+                if !this.stalled {
+                    this.stalled = true;
+                    this.metrics.stalled.set(1.0);
+                }
+                this.metrics.stalls.increment(1);
+                return Poll::Ready(Some(PrunerEvent::Stalled { since_progress }))
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::PruneSegment;
+    use std::collections::BTreeMap;
+    use tokio_stream::StreamExt;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    fn finished(progress: PruneProgress, pruned: usize) -> PrunerEvent {
+        PrunerEvent::Finished {
+            tip_block_number: 1,
+            elapsed: Duration::ZERO,
+            stats: BTreeMap::from([(PruneSegment::Receipts, (progress, pruned))]),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reports_stall_without_progress() {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut monitor = PrunerStallMonitor::new(
+            tokio_stream::wrappers::UnboundedReceiverStream::new(rx),
+            WINDOW,
+        );
+
+        // The pruner made progress, but has more data to prune
+        tx.send(finished(PruneProgress::HasMoreData, 10)).unwrap();
+        assert_eq!(monitor.next().await, Some(finished(PruneProgress::HasMoreData, 10)));
+
+        // Subsequent runs don't delete anything
+        tokio::time::advance(WINDOW / 2).await;
+        tx.send(finished(PruneProgress::HasMoreData, 0)).unwrap();
+        assert_eq!(monitor.next().await, Some(finished(PruneProgress::HasMoreData, 0)));
+        assert!(!monitor.is_stalled());
+
+        let event = monitor.next().await;
+        assert_eq!(event, Some(PrunerEvent::Stalled { since_progress: WINDOW }));
+        assert!(monitor.is_stalled());
+
+        // Progress clears the stall
+        tx.send(finished(PruneProgress::Finished, 5)).unwrap();
+        assert_eq!(monitor.next().await, Some(finished(PruneProgress::Finished, 5)));
+        assert!(!monitor.is_stalled());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_pruner_is_not_stalled() {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut monitor = PrunerStallMonitor::new(
+            tokio_stream::wrappers::UnboundedReceiverStream::new(rx),
+            WINDOW,
+        );
+
+        tx.send(finished(PruneProgress::Finished, 0)).unwrap();
+        assert_eq!(monitor.next().await, Some(finished(PruneProgress::Finished, 0)));
+
+        tokio::time::advance(WINDOW * 3).await;
+        drop(tx);
+        assert_eq!(monitor.next().await, None);
+        assert!(!monitor.is_stalled());
+    }
+}
@@ -185,7 +185,10 @@ use crate::{
     RpcModuleSelection::Selection,
 };
 // re-export for convenience
-pub use crate::eth::{EthConfig, EthHandlers};
+pub use crate::{
+    eth::{EthConfig, EthHandlers},
+    method_filter::RpcMethodFilter,
+};
 
 /// Auth server utilities.
 pub mod auth;
@@ -199,6 +202,9 @@ pub mod error;
 /// Eth utils
 mod eth;
 
+/// Filtering of individual methods.
+mod method_filter;
+
 /// Common RPC constants.
 pub mod constants;
 
@@ -780,6 +786,10 @@ pub enum RethRpcModule {
     /// This is separate from [RethRpcModule::Eth] because it is a non standardized call that
     /// should be opt-in.
     EthCallBundle,
+    /// `clayer_` module
+    ///
+    /// Served by the consensus layer, its methods are merged into the transports by the node.
+    Clayer,
 }
 
 // === impl RethRpcModule ===
@@ -819,6 +829,7 @@ impl FromStr for RethRpcModule {
             "reth" => RethRpcModule::Reth,
             "ots" => RethRpcModule::Ots,
             "eth-call-bundle" | "eth_callBundle" => RethRpcModule::EthCallBundle,
+            "clayer" => RethRpcModule::Clayer,
             _ => return Err(ParseError::VariantNotFound),
         })
     }
@@ -1163,6 +1174,8 @@ where
                                 .into_rpc()
                                 .into()
                         }
+                        // the node merges the methods of the consensus layer
+                        RethRpcModule::Clayer => RpcModule::new(()).into(),
                     })
                     .clone()
             })
@@ -1368,6 +1381,8 @@ pub struct RpcServerConfig {
     ipc_endpoint: Option<Endpoint>,
     /// JWT secret for authentication
     jwt_secret: Option<JwtSecret>,
+    /// Filter of the methods served by all transports
+    method_filter: RpcMethodFilter,
 }
 
 impl fmt::Debug for RpcServerConfig {
@@ -1381,6 +1396,7 @@ impl fmt::Debug for RpcServerConfig {
             .field("ipc_server_config", &self.ipc_server_config)
             .field("ipc_endpoint", &self.ipc_endpoint.as_ref().map(|endpoint| endpoint.path()))
             .field("jwt_secret", &self.jwt_secret)
+            .field("method_filter", &self.method_filter)
             .finish()
     }
 }
@@ -1498,6 +1514,14 @@ impl RpcServerConfig {
         self
     }
 
+    /// Configures which individual methods are served by all transports.
+    ///
+    /// Filtered methods are removed from the modules on [RpcServerConfig::start].
+    pub fn with_method_filter(mut self, filter: RpcMethodFilter) -> Self {
+        self.method_filter = filter;
+        self
+    }
+
     /// Returns true if any server is configured.
     ///
     /// If no server is configured, no server will be be launched on [RpcServerConfig::start].
@@ -1523,7 +1547,11 @@ impl RpcServerConfig {
     }
 
     /// Convenience function to do [RpcServerConfig::build] and [RpcServer::start] in one step
-    pub async fn start(self, modules: TransportRpcModules) -> Result<RpcServerHandle, RpcError> {
+    pub async fn start(
+        self,
+        mut modules: TransportRpcModules,
+    ) -> Result<RpcServerHandle, RpcError> {
+        modules.filter_methods(&self.method_filter);
         self.build(&modules).await?.start(modules).await
     }
 
@@ -1841,6 +1869,13 @@ impl TransportRpcModules {
         Ok(())
     }
 
+    /// Removes the methods from all configured transports that `filter` does not serve.
+    pub fn filter_methods(&mut self, filter: &RpcMethodFilter) {
+        for module in [&mut self.http, &mut self.ws, &mut self.ipc].into_iter().flatten() {
+            filter.apply(module);
+        }
+    }
+
     /// Convenience function for starting a server
     pub async fn start_server(self, builder: RpcServerConfig) -> Result<RpcServerHandle, RpcError> {
         builder.start(self).await
@@ -2296,6 +2331,7 @@ mod tests {
                 "rpc" => RethRpcModule::Rpc,
                 "ots" => RethRpcModule::Ots,
                 "reth" => RethRpcModule::Reth,
+                "clayer" => RethRpcModule::Clayer,
             );
     }
 
@@ -0,0 +1,70 @@
+use jsonrpsee::RpcModule;
+use std::collections::HashSet;
+
+/// Filters the individual methods served by the regular RPC servers, on top of the selected
+/// [RethRpcModule](crate::RethRpcModule)s.
+///
+/// Filtered methods are not installed, so calling them fails with `method not found`.
+///
+/// # Example
+///
+/// ```
+/// use reth_rpc_builder::RpcMethodFilter;
+/// let filter = RpcMethodFilter::default().deny(["admin_addPeer"]);
+/// assert!(!filter.is_allowed("admin_addPeer"));
+/// assert!(filter.is_allowed("admin_nodeInfo"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RpcMethodFilter {
+    /// Only these methods are served if set
+    allowed: Option<HashSet<String>>,
+    /// These methods are never served
+    denied: HashSet<String>,
+}
+
+// === impl RpcMethodFilter ===
+
+impl RpcMethodFilter {
+    /// Serves only the given methods, in addition to the methods allowed before.
+    pub fn allow<I, S>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed.get_or_insert_with(HashSet::new).extend(methods.into_iter().map(Into::into));
+        self
+    }
+
+    /// Never serves the given methods, even if they are allowed.
+    pub fn deny<I, S>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.denied.extend(methods.into_iter().map(Into::into));
+        self
+    }
+
+    /// Returns true if the filter lets all methods pass.
+    pub fn is_empty(&self) -> bool {
+        self.allowed.is_none() && self.denied.is_empty()
+    }
+
+    /// Returns true if `method` is served.
+    pub fn is_allowed(&self, method: &str) -> bool {
+        !self.denied.contains(method) &&
+            self.allowed.as_ref().map_or(true, |allowed| allowed.contains(method))
+    }
+
+    /// Removes all methods from `module` that are not served.
+    pub(crate) fn apply<Context>(&self, module: &mut RpcModule<Context>) {
+        if self.is_empty() {
+            return
+        }
+        let filtered =
+            module.method_names().filter(|method| !self.is_allowed(method)).collect::<Vec<_>>();
+        for method in filtered {
+            module.remove_method(method);
+        }
+    }
+}
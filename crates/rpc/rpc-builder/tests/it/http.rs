@@ -1,5 +1,5 @@
 //! Standalone http tests
-use crate::utils::{launch_http, launch_http_ws, launch_ws};
+use crate::utils::{launch_http, launch_http_ws, launch_ws, test_address, test_rpc_builder};
 use jsonrpsee::{
     core::{
         client::{ClientT, SubscriptionClientT},
@@ -17,7 +17,7 @@ use reth_rpc_api::{
     DebugApiClient, EthFilterApiClient, NetApiClient, OtterscanClient, TraceApiClient,
     Web3ApiClient,
 };
-use reth_rpc_builder::RethRpcModule;
+use reth_rpc_builder::{RethRpcModule, RpcMethodFilter, RpcServerConfig, TransportRpcModuleConfig};
 use reth_rpc_types::{
     trace::filter::TraceFilter, CallRequest, Filter, Index, Log, PendingTransactionFilterKind,
     TransactionRequest,
@@ -482,6 +482,29 @@ async fn test_call_web3_functions_http_and_ws() {
     test_basic_web3_calls(&client).await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_denied_method_http() {
+    reth_tracing::init_test_tracing();
+
+    let modules =
+        test_rpc_builder().build(TransportRpcModuleConfig::set_http(vec![RethRpcModule::Web3]));
+    let handle = modules
+        .start_server(
+            RpcServerConfig::http(Default::default())
+                .with_http_address(test_address())
+                .with_method_filter(RpcMethodFilter::default().deny(["web3_clientVersion"])),
+        )
+        .await
+        .unwrap();
+    let client = handle.http_client().unwrap();
+
+    match Web3ApiClient::client_version(&client).await.unwrap_err() {
+        Error::Call(error_obj) => assert_eq!(error_obj.code(), ErrorCode::MethodNotFound.code()),
+        err => panic!("expected method not found, got {err:?}"),
+    }
+    Web3ApiClient::sha3(&client, Bytes::default()).await.unwrap();
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_call_otterscan_functions_http() {
     reth_tracing::init_test_tracing();
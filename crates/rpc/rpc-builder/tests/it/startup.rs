@@ -4,9 +4,16 @@ use crate::utils::{
 };
 use reth_rpc_builder::{
     error::{RpcError, ServerKind, WsHttpSamePortError},
-    RethRpcModule, RpcServerConfig, TransportRpcModuleConfig,
+    RethRpcModule, RpcServerConfig, ServerBuilder, TransportRpcModuleConfig,
+};
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
 };
-use std::io;
 
 fn is_addr_in_use_kind(err: &RpcError, kind: ServerKind) -> bool {
     match err {
@@ -118,3 +125,43 @@ async fn test_launch_same_port_different_cors() {
         RpcError::WsHttpSamePortError(WsHttpSamePortError::ConflictingCorsDomains { .. })
     ));
 }
+
+/// Sends a `web3_clientVersion` request over `stream` and returns the status line of the response.
+async fn request_status(stream: &mut TcpStream) -> String {
+    let body = r#"{"jsonrpc":"2.0","method":"web3_clientVersion","params":[],"id":1}"#;
+    let request = format!(
+        "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = vec![0; 1024];
+    let len = stream.read(&mut response).await.unwrap();
+    String::from_utf8_lossy(&response[..len]).lines().next().unwrap_or_default().to_string()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_http_max_connections() {
+    let builder = test_rpc_builder();
+    let server = builder.build(TransportRpcModuleConfig::set_http(vec![RethRpcModule::Web3]));
+    let handle = server
+        .start_server(
+            RpcServerConfig::http(ServerBuilder::new().max_connections(2))
+                .with_http_address(test_address()),
+        )
+        .await
+        .unwrap();
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, handle.http_local_addr().unwrap().port()));
+
+    // keep-alive connections up to the limit are served
+    let mut open = Vec::new();
+    for _ in 0..2 {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        assert_eq!(request_status(&mut stream).await, "HTTP/1.1 200 OK");
+        open.push(stream);
+    }
+
+    // the next connection is rejected while the others are still open
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let status = request_status(&mut stream).await;
+    assert!(status.starts_with("HTTP/1.1 429"), "{status}");
+}
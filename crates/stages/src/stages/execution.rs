@@ -3,6 +3,7 @@ use crate::{
     MetricEvent, MetricEventsSender, Stage, StageError, UnwindInput, UnwindOutput,
 };
 use num_traits::Zero;
+use rayon::prelude::*;
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
     database::Database,
@@ -15,12 +16,13 @@ use reth_primitives::{
     stage::{
         CheckpointBlockRange, EntitiesCheckpoint, ExecutionCheckpoint, StageCheckpoint, StageId,
     },
-    BlockNumber, Header, PruneModes, U256,
+    BlockNumber, ChainSpec, Hardfork, Header, PruneModes, U256,
 };
 use reth_provider::{
-    BlockReader, DatabaseProviderRW, ExecutorFactory, HeaderProvider, LatestStateProviderRef,
-    OriginalValuesKnown, ProviderError, TransactionVariant,
+    BlockReader, BundleStateWithReceipts, DatabaseProviderRW, ExecutorFactory, HeaderProvider,
+    LatestStateProviderRef, OriginalValuesKnown, ProviderError, TransactionVariant,
 };
+use reth_revm::processor::verify_receipt;
 use std::{
     ops::RangeInclusive,
     time::{Duration, Instant},
@@ -70,6 +72,9 @@ pub struct ExecutionStage<EF: ExecutorFactory> {
     external_clean_threshold: u64,
     /// Pruning configuration.
     prune_modes: PruneModes,
+    /// Whether receipts are verified in parallel after the batch is executed instead of after
+    /// each block.
+    parallel_receipt_verification: bool,
 }
 
 impl<EF: ExecutorFactory> ExecutionStage<EF> {
@@ -86,6 +91,7 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
             executor_factory,
             thresholds,
             prune_modes,
+            parallel_receipt_verification: false,
         }
     }
 
@@ -101,6 +107,20 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
         )
     }
 
+    /// Verify the receipts of a batch in parallel once all of its blocks are executed.
+    ///
+    /// Blocks are still executed in order since every block reads the state written by its
+    /// parent, only computing the receipts root and logs bloom of a block is moved off the
+    /// execution path and spread across the rayon pool. Blocks whose receipts are pruned are
+    /// verified right after execution as before.
+    pub fn with_parallel_receipt_verification(
+        mut self,
+        parallel_receipt_verification: bool,
+    ) -> Self {
+        self.parallel_receipt_verification = parallel_receipt_verification;
+        self
+    }
+
     /// Set the metric events sender.
     pub fn with_metrics_tx(mut self, metrics_tx: MetricEventsSender) -> Self {
         self.metrics_tx = Some(metrics_tx);
@@ -120,6 +140,11 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
         let start_block = input.next_block();
         let max_block = input.target();
         let prune_modes = self.adjust_prune_modes(provider, start_block, max_block)?;
+        // Receipts can only be verified after the batch if the executor keeps all of them
+        let defer_receipts = self.parallel_receipt_verification &&
+            prune_modes.receipts.is_none() &&
+            prune_modes.receipts_log_filter.is_empty();
+        let chain_spec = self.executor_factory.chain_spec();
 
         // Build executor
         let mut executor =
@@ -138,6 +163,8 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
         // Execute block range
 
         let mut cumulative_gas = 0;
+        // Headers of the executed blocks whose receipts are verified after the batch
+        let mut unverified = Vec::new();
 
         for block_number in start_block..=max_block {
             let time = Instant::now();
@@ -159,10 +186,20 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
 
             let time = Instant::now();
             // Execute the block
-            executor.execute_and_verify_receipt(&block, td).map_err(|error| StageError::Block {
+            if defer_receipts {
+                executor.execute(&block, td)
+            } else {
+                executor.execute_and_verify_receipt(&block, td)
+            }
+            .map_err(|error| StageError::Block {
                 block: Box::new(block.header.clone().seal_slow()),
                 error: BlockErrorKind::Execution(error),
             })?;
+            if defer_receipts &&
+                chain_spec.fork(Hardfork::Byzantium).active_at_block(block.header.number)
+            {
+                unverified.push(block.header.clone());
+            }
 
             execution_duration += time.elapsed();
 
@@ -190,6 +227,10 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
         let state = executor.take_output_state();
         let write_preparation_duration = time.elapsed();
 
+        let time = Instant::now();
+        verify_receipts(chain_spec, &state, &unverified)?;
+        let receipt_verification_duration = time.elapsed();
+
         let time = Instant::now();
         // write output
         state.write_to_db(provider.tx_ref(), OriginalValuesKnown::Yes)?;
@@ -199,6 +240,7 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
             block_fetch = ?fetch_block_duration,
             execution = ?execution_duration,
             write_preperation = ?write_preparation_duration,
+            receipt_verification = ?receipt_verification_duration,
             write = ?db_write_duration,
             "Execution time"
         );
@@ -243,6 +285,42 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
     }
 }
 
+/// Verifies the receipts root and logs bloom of the given headers against the receipts of the
+/// executed batch in parallel.
+///
+/// Reports the lowest block that doesn't match, like serial execution would have.
+fn verify_receipts(
+    chain_spec: &ChainSpec,
+    state: &BundleStateWithReceipts,
+    headers: &[Header],
+) -> Result<(), StageError> {
+    #[cfg(not(feature = "optimism"))]
+    let _ = chain_spec;
+
+    let failed = headers.par_iter().find_map_first(|header| {
+        let receipts = state.receipts_by_block(header.number);
+        verify_receipt(
+            header.receipts_root,
+            header.logs_bloom,
+            receipts.iter().map(|receipt| receipt.as_ref().expect("receipts are not pruned")),
+            #[cfg(feature = "optimism")]
+            chain_spec,
+            #[cfg(feature = "optimism")]
+            header.timestamp,
+        )
+        .err()
+        .map(|error| (header, error))
+    });
+
+    match failed {
+        Some((header, error)) => Err(StageError::Block {
+            block: Box::new(header.clone().seal_slow()),
+            error: BlockErrorKind::Execution(error),
+        }),
+        None => Ok(()),
+    }
+}
+
 fn execution_checkpoint<DB: Database>(
     provider: &DatabaseProviderRW<DB>,
     start_block: BlockNumber,
@@ -492,15 +570,19 @@ mod tests {
     use alloy_rlp::Decodable;
     use assert_matches::assert_matches;
     use reth_db::{models::AccountBeforeTx, test_utils::create_test_rw_db};
-    use reth_interfaces::executor::BlockValidationError;
+    use reth_interfaces::executor::{BlockExecutionError, BlockValidationError};
     use reth_primitives::{
-        address, hex_literal::hex, keccak256, stage::StageUnitCheckpoint, Account, Bytecode,
-        ChainSpecBuilder, PruneModes, SealedBlock, StorageEntry, B256, MAINNET, U256,
+        address, hex_literal::hex, keccak256, proofs, sign_message, stage::StageUnitCheckpoint,
+        Account, Bytecode, Bytes, ChainSpecBuilder, PruneModes, Receipt, SealedBlock, StorageEntry,
+        Transaction, TransactionKind, TransactionSigned, TxLegacy, TxType, B256, MAINNET, U256,
     };
     use reth_provider::{AccountReader, BlockWriter, ProviderFactory, ReceiptProvider};
     use reth_revm::EvmProcessorFactory;
     use std::sync::Arc;
 
+    const GENESIS_RLP: &[u8] = &hex!("f901faf901f5a00000000000000000000000000000000000000000000000000000000000000000a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa045571b40ae66ca7480791bbb2887286e4e4c4b1b298b191c889d6959023a32eda056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421b901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000808502540be400808000a00000000000000000000000000000000000000000000000000000000000000000880000000000000000c0c0");
+    const BLOCK_RLP: &[u8] = &hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0");
+
     fn stage() -> ExecutionStage<EvmProcessorFactory> {
         let executor_factory = EvmProcessorFactory::new(Arc::new(
             ChainSpecBuilder::mainnet().berlin_activated().build(),
@@ -548,9 +630,9 @@ mod tests {
         let factory = ProviderFactory::new(state_db.as_ref(), MAINNET.clone());
         let provider = factory.provider_rw().unwrap();
 
-        let mut genesis_rlp = hex!("f901faf901f5a00000000000000000000000000000000000000000000000000000000000000000a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa045571b40ae66ca7480791bbb2887286e4e4c4b1b298b191c889d6959023a32eda056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421b901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000808502540be400808000a00000000000000000000000000000000000000000000000000000000000000000880000000000000000c0c0").as_slice();
+        let mut genesis_rlp = GENESIS_RLP;
         let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
-        let mut block_rlp = hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0").as_slice();
+        let mut block_rlp = BLOCK_RLP;
         let block = SealedBlock::decode(&mut block_rlp).unwrap();
         provider
             .insert_block(
@@ -592,9 +674,9 @@ mod tests {
         let factory = ProviderFactory::new(state_db.as_ref(), MAINNET.clone());
         let provider = factory.provider_rw().unwrap();
 
-        let mut genesis_rlp = hex!("f901faf901f5a00000000000000000000000000000000000000000000000000000000000000000a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa045571b40ae66ca7480791bbb2887286e4e4c4b1b298b191c889d6959023a32eda056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421b901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000808502540be400808000a00000000000000000000000000000000000000000000000000000000000000000880000000000000000c0c0").as_slice();
+        let mut genesis_rlp = GENESIS_RLP;
         let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
-        let mut block_rlp = hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0").as_slice();
+        let mut block_rlp = BLOCK_RLP;
         let block = SealedBlock::decode(&mut block_rlp).unwrap();
         provider.insert_block(genesis.try_seal_with_senders().unwrap(), None).unwrap();
         provider.insert_block(block.clone().try_seal_with_senders().unwrap(), None).unwrap();
@@ -628,9 +710,9 @@ mod tests {
         let factory = ProviderFactory::new(state_db.as_ref(), MAINNET.clone());
         let provider = factory.provider_rw().unwrap();
 
-        let mut genesis_rlp = hex!("f901faf901f5a00000000000000000000000000000000000000000000000000000000000000000a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa045571b40ae66ca7480791bbb2887286e4e4c4b1b298b191c889d6959023a32eda056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421b901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000808502540be400808000a00000000000000000000000000000000000000000000000000000000000000000880000000000000000c0c0").as_slice();
+        let mut genesis_rlp = GENESIS_RLP;
         let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
-        let mut block_rlp = hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0").as_slice();
+        let mut block_rlp = BLOCK_RLP;
         let block = SealedBlock::decode(&mut block_rlp).unwrap();
         provider.insert_block(genesis.try_seal_with_senders().unwrap(), None).unwrap();
         provider.insert_block(block.clone().try_seal_with_senders().unwrap(), None).unwrap();
@@ -658,9 +740,9 @@ mod tests {
         let factory = ProviderFactory::new(state_db.as_ref(), MAINNET.clone());
         let provider = factory.provider_rw().unwrap();
         let input = ExecInput { target: Some(1), checkpoint: None };
-        let mut genesis_rlp = hex!("f901faf901f5a00000000000000000000000000000000000000000000000000000000000000000a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa045571b40ae66ca7480791bbb2887286e4e4c4b1b298b191c889d6959023a32eda056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421b901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000808502540be400808000a00000000000000000000000000000000000000000000000000000000000000000880000000000000000c0c0").as_slice();
+        let mut genesis_rlp = GENESIS_RLP;
         let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
-        let mut block_rlp = hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0").as_slice();
+        let mut block_rlp = BLOCK_RLP;
         let block = SealedBlock::decode(&mut block_rlp).unwrap();
         provider.insert_block(genesis.try_seal_with_senders().unwrap(), None).unwrap();
         provider.insert_block(block.clone().try_seal_with_senders().unwrap(), None).unwrap();
@@ -764,9 +846,9 @@ mod tests {
         let factory = ProviderFactory::new(state_db.as_ref(), MAINNET.clone());
         let provider = factory.provider_rw().unwrap();
         let input = ExecInput { target: Some(1), checkpoint: None };
-        let mut genesis_rlp = hex!("f901faf901f5a00000000000000000000000000000000000000000000000000000000000000000a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa045571b40ae66ca7480791bbb2887286e4e4c4b1b298b191c889d6959023a32eda056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421b901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000808502540be400808000a00000000000000000000000000000000000000000000000000000000000000000880000000000000000c0c0").as_slice();
+        let mut genesis_rlp = GENESIS_RLP;
         let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
-        let mut block_rlp = hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0").as_slice();
+        let mut block_rlp = BLOCK_RLP;
         let block = SealedBlock::decode(&mut block_rlp).unwrap();
         provider.insert_block(genesis.try_seal_with_senders().unwrap(), None).unwrap();
         provider.insert_block(block.clone().try_seal_with_senders().unwrap(), None).unwrap();
@@ -962,4 +1044,171 @@ mod tests {
             ]
         );
     }
+
+    /// Inserts the sanity block followed by `transfers` blocks that each send a transfer from its
+    /// sender, executes them on a fresh database and returns the output with the resulting state
+    /// root.
+    ///
+    /// The receipts root of the block `corrupt_receipts_root` is replaced to make it invalid.
+    fn execute_sanity_range(
+        parallel_receipt_verification: bool,
+        transfers: u64,
+        corrupt_receipts_root: Option<BlockNumber>,
+    ) -> Result<(ExecOutput, B256), StageError> {
+        let state_db = create_test_rw_db();
+        let factory = ProviderFactory::new(state_db.as_ref(), MAINNET.clone());
+        let provider = factory.provider_rw().unwrap();
+        let mut genesis_rlp = GENESIS_RLP;
+        let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
+        let mut block_rlp = BLOCK_RLP;
+        let mut blocks = vec![SealedBlock::decode(&mut block_rlp).unwrap()];
+
+        // secret key of the sender of the sanity block
+        let secret =
+            B256::from(hex!("45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8"));
+        for nonce in 1..=transfers {
+            let parent = &blocks.last().unwrap().header;
+            let transaction = Transaction::Legacy(TxLegacy {
+                chain_id: None,
+                nonce,
+                gas_price: 10,
+                gas_limit: 21_000,
+                to: TransactionKind::Call(address!("3000000000000000000000000000000000000000")),
+                value: U256::from(1).into(),
+                input: Bytes::default(),
+            });
+            let signature = sign_message(secret, transaction.signature_hash()).unwrap();
+            let transaction =
+                TransactionSigned::from_transaction_and_signature(transaction, signature);
+            let receipt = Receipt {
+                tx_type: TxType::Legacy,
+                success: true,
+                cumulative_gas_used: 21_000,
+                logs: Vec::new(),
+                #[cfg(feature = "optimism")]
+                deposit_nonce: None,
+                #[cfg(feature = "optimism")]
+                deposit_receipt_version: None,
+            };
+            let mut header = Header {
+                parent_hash: parent.hash(),
+                beneficiary: parent.beneficiary,
+                transactions_root: proofs::calculate_transaction_root(&[&transaction]),
+                receipts_root: proofs::calculate_receipt_root(
+                    &[receipt.with_bloom()],
+                    #[cfg(feature = "optimism")]
+                    &MAINNET,
+                    #[cfg(feature = "optimism")]
+                    0,
+                ),
+                difficulty: parent.difficulty,
+                number: parent.number + 1,
+                gas_limit: parent.gas_limit,
+                gas_used: 21_000,
+                timestamp: parent.timestamp + 1,
+                ..Default::default()
+            };
+            if corrupt_receipts_root == Some(header.number) {
+                header.receipts_root = B256::ZERO;
+            }
+            blocks.push(SealedBlock {
+                header: header.seal_slow(),
+                body: vec![transaction],
+                ommers: Vec::new(),
+                withdrawals: None,
+            });
+        }
+
+        provider.insert_block(genesis.try_seal_with_senders().unwrap(), None).unwrap();
+        for block in blocks {
+            provider.insert_block(block.try_seal_with_senders().unwrap(), None).unwrap();
+        }
+        provider.commit().unwrap();
+
+        // insert pre state
+        let provider = factory.provider_rw().unwrap();
+        let code = hex!("5a465a905090036002900360015500");
+        let code_hash = keccak256(code);
+        provider
+            .tx_ref()
+            .put::<tables::PlainAccountState>(
+                address!("1000000000000000000000000000000000000000"),
+                Account { nonce: 0, balance: U256::ZERO, bytecode_hash: Some(code_hash) },
+            )
+            .unwrap();
+        provider
+            .tx_ref()
+            .put::<tables::PlainAccountState>(
+                address!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b"),
+                Account {
+                    nonce: 0,
+                    balance: U256::from(0x3635c9adc5dea00000u128),
+                    bytecode_hash: None,
+                },
+            )
+            .unwrap();
+        provider
+            .tx_ref()
+            .put::<tables::Bytecodes>(code_hash, Bytecode::new_raw(code.to_vec().into()))
+            .unwrap();
+        provider.commit().unwrap();
+
+        let provider = factory.provider_rw().unwrap();
+        let mut execution_stage =
+            stage().with_parallel_receipt_verification(parallel_receipt_verification);
+        let output = execution_stage
+            .execute(&provider, ExecInput { target: Some(1 + transfers), checkpoint: None })?;
+        provider.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let accounts = provider
+            .tx_ref()
+            .cursor_read::<tables::PlainAccountState>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let mut storage_cursor =
+            provider.tx_ref().cursor_dup_read::<tables::PlainStorageState>().unwrap();
+        let state_root =
+            reth_trie::test_utils::state_root(accounts.into_iter().map(|(address, account)| {
+                let storage = storage_cursor
+                    .walk_dup(Some(address), None)
+                    .unwrap()
+                    .map(|entry| {
+                        let (_, StorageEntry { key, value }) = entry.unwrap();
+                        (key, value)
+                    })
+                    .collect::<Vec<_>>();
+                (address, (account, storage))
+            }));
+        Ok((output, state_root))
+    }
+
+    #[test]
+    fn parallel_receipt_verification_matches_serial_execution() {
+        let (serial, serial_root) = execute_sanity_range(false, 3, None).unwrap();
+        assert_eq!(serial.checkpoint.block_number, 4);
+        assert!(serial.done);
+
+        let (parallel, parallel_root) = execute_sanity_range(true, 3, None).unwrap();
+        assert_eq!(parallel, serial);
+        assert_eq!(parallel_root, serial_root);
+    }
+
+    #[test]
+    fn parallel_receipt_verification_reports_invalid_block() {
+        for parallel_receipt_verification in [false, true] {
+            assert_matches!(
+                execute_sanity_range(parallel_receipt_verification, 3, Some(3)),
+                Err(StageError::Block {
+                    block,
+                    error: BlockErrorKind::Execution(BlockExecutionError::Validation(
+                        BlockValidationError::ReceiptRootDiff(_)
+                    )),
+                }) if block.number == 3
+            );
+        }
+    }
 }
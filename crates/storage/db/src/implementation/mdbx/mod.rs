@@ -23,6 +23,11 @@ pub mod tx;
 const GIGABYTE: usize = 1024 * 1024 * 1024;
 const TERABYTE: usize = GIGABYTE * 1024;
 
+/// Default maximum database size of 4 terabytes
+pub const DEFAULT_MAX_SIZE: usize = 4 * TERABYTE;
+/// Default step the database grows by, 4 gigabytes
+pub const DEFAULT_GROWTH_STEP: usize = 4 * GIGABYTE;
+
 /// MDBX allows up to 32767 readers (`MDBX_READERS_LIMIT`), but we limit it to slightly below that
 const DEFAULT_MAX_READERS: u64 = 32_000;
 
@@ -35,6 +40,21 @@ pub enum DatabaseEnvKind {
     RW,
 }
 
+/// Size limits of the memory map of a MDBX environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatabaseGeometry {
+    /// Maximum size of the database in bytes, writes fail once it is reached
+    pub max_size: usize,
+    /// Step in bytes the database file grows by
+    pub growth_step: usize,
+}
+
+impl Default for DatabaseGeometry {
+    fn default() -> Self {
+        Self { max_size: DEFAULT_MAX_SIZE, growth_step: DEFAULT_GROWTH_STEP }
+    }
+}
+
 /// Wrapper for the libmdbx environment: [Environment]
 #[derive(Debug)]
 pub struct DatabaseEnv {
@@ -146,6 +166,18 @@ impl DatabaseEnv {
         path: &Path,
         kind: DatabaseEnvKind,
         log_level: Option<LogLevel>,
+    ) -> Result<DatabaseEnv, DatabaseError> {
+        Self::open_with_geometry(path, kind, log_level, DatabaseGeometry::default())
+    }
+
+    /// Opens the database at the specified path with the given `EnvKind` and size limits.
+    ///
+    /// It does not create the tables, for that call [`DatabaseEnv::create_tables`].
+    pub fn open_with_geometry(
+        path: &Path,
+        kind: DatabaseEnvKind,
+        log_level: Option<LogLevel>,
+        geometry: DatabaseGeometry,
     ) -> Result<DatabaseEnv, DatabaseError> {
         let mut inner_env = Environment::builder();
 
@@ -160,10 +192,8 @@ impl DatabaseEnv {
 
         inner_env.set_max_dbs(Tables::ALL.len());
         inner_env.set_geometry(Geometry {
-            // Maximum database size of 4 terabytes
-            size: Some(0..(4 * TERABYTE)),
-            // We grow the database in increments of 4 gigabytes
-            growth_step: Some(4 * GIGABYTE as isize),
+            size: Some(0..geometry.max_size),
+            growth_step: Some(geometry.growth_step as isize),
             // The database never shrinks
             shrink_threshold: None,
             page_size: Some(PageSize::Set(default_page_size())),
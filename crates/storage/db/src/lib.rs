@@ -87,7 +87,7 @@ pub use tables::*;
 pub use utils::is_database_empty;
 
 #[cfg(feature = "mdbx")]
-pub use mdbx::{DatabaseEnv, DatabaseEnvKind};
+pub use mdbx::{DatabaseEnv, DatabaseEnvKind, DatabaseGeometry};
 
 use eyre::WrapErr;
 use reth_interfaces::db::LogLevel;
@@ -96,6 +96,15 @@ use std::path::Path;
 /// Opens up an existing database or creates a new one at the specified path. Creates tables if
 /// necessary. Read/Write mode.
 pub fn init_db<P: AsRef<Path>>(path: P, log_level: Option<LogLevel>) -> eyre::Result<DatabaseEnv> {
+    init_db_with_geometry(path, log_level, DatabaseGeometry::default())
+}
+
+/// Like [init_db], with the given size limits of the database.
+pub fn init_db_with_geometry<P: AsRef<Path>>(
+    path: P,
+    log_level: Option<LogLevel>,
+    geometry: DatabaseGeometry,
+) -> eyre::Result<DatabaseEnv> {
     use crate::version::{check_db_version_file, create_db_version_file, DatabaseVersionError};
 
     let rpath = path.as_ref();
@@ -112,7 +121,7 @@ pub fn init_db<P: AsRef<Path>>(path: P, log_level: Option<LogLevel>) -> eyre::Re
     }
     #[cfg(feature = "mdbx")]
     {
-        let db = DatabaseEnv::open(rpath, DatabaseEnvKind::RW, log_level)?;
+        let db = DatabaseEnv::open_with_geometry(rpath, DatabaseEnvKind::RW, log_level, geometry)?;
         db.create_tables()?;
         Ok(db)
     }
@@ -257,8 +266,9 @@ pub mod test_utils {
 #[cfg(test)]
 mod tests {
     use crate::{
-        init_db,
+        init_db, init_db_with_geometry,
         version::{db_version_file_path, DatabaseVersionError},
+        DatabaseGeometry,
     };
     use assert_matches::assert_matches;
     use reth_primitives::fs;
@@ -302,4 +312,16 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    fn db_geometry() {
+        let path = tempdir().unwrap();
+        let geometry =
+            DatabaseGeometry { max_size: 8 * 1024 * 1024 * 1024, growth_step: 64 * 1024 * 1024 };
+
+        let db = init_db_with_geometry(&path, None, geometry).unwrap();
+        let info = db.info().unwrap();
+        assert_eq!(info.geometry().max(), geometry.max_size as u64);
+        assert_eq!(info.geometry().grow(), geometry.growth_step as u64);
+    }
 }
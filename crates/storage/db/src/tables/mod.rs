@@ -53,7 +53,7 @@ pub enum TableType {
 }
 
 /// Number of tables that should be present inside database.
-pub const NUM_TABLES: usize = 28;
+pub const NUM_TABLES: usize = 31;
 
 /// The general purpose of this is to use with a combination of Tables enum,
 /// by implementing a `TableViewer` trait you can operate on db tables in an abstract way.
@@ -219,7 +219,10 @@ tables!([
             SyncStageProgress,
             PruneCheckpoints,
             ConsensusNumber,
-            ConsensusContent
+            ConsensusContent,
+            CommittedPayloads,
+            ViewChanges,
+            RandaoMixes
         ]
     ),
     (
@@ -465,6 +468,21 @@ table!(
     ( ConsensusContent ) BlockHash | ConsensusBytes
 );
 
+table!(
+    /// Stores the execution payloads committed by consensus, by block number.
+    ( CommittedPayloads ) BlockNumber | ConsensusBytes
+);
+
+table!(
+    /// Stores the record of each view change, by the view that was changed to.
+    ( ViewChanges ) u64 | ConsensusBytes
+);
+
+table!(
+    /// Stores the randao mix of each committed block, a hash chain of the committed block hashes.
+    ( RandaoMixes ) BlockNumber | B256
+);
+
 /// Alias Types
 
 /// List with transaction numbers.
@@ -501,6 +519,9 @@ mod tests {
         (TableType::Table, PruneCheckpoints::NAME),
         (TableType::Table, ConsensusNumber::NAME),
         (TableType::Table, ConsensusContent::NAME),
+        (TableType::Table, CommittedPayloads::NAME),
+        (TableType::Table, ViewChanges::NAME),
+        (TableType::Table, RandaoMixes::NAME),
         (TableType::DupSort, PlainStorageState::NAME),
         (TableType::DupSort, AccountChangeSet::NAME),
         (TableType::DupSort, StorageChangeSet::NAME),
@@ -369,6 +369,16 @@ impl GeometryInfo {
     pub fn min(&self) -> u64 {
         self.0.lower
     }
+
+    /// Upper limit of the database size.
+    pub fn max(&self) -> u64 {
+        self.0.upper
+    }
+
+    /// Step the database file grows by.
+    pub fn grow(&self) -> u64 {
+        self.0.grow
+    }
 }
 
 /// Environment information.
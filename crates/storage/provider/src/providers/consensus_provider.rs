@@ -1,7 +1,68 @@
-use crate::{ConsensusNumberReader, ConsensusNumberWriter, ProviderFactory};
-use reth_db::{database::Database, models::consensus::ConsensusBytes};
-use reth_interfaces::provider::ProviderResult;
+use crate::{ConsensusNumberReader, ConsensusNumberWriter, DatabaseProviderRW, ProviderFactory};
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    mdbx::Error as MdbxError,
+    models::consensus::ConsensusBytes,
+    table::Table,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseError,
+};
+use reth_interfaces::provider::{ProviderError, ProviderResult};
 use reth_primitives::{BlockNumber, B256};
+use std::{ops::RangeInclusive, time::Duration};
+use tracing::warn;
+
+/// Default number of retries of a write that failed with a transient database error.
+pub const DEFAULT_DB_WRITE_RETRIES: u32 = 3;
+
+/// Backoff before the first retry of a failed write, doubled for every further retry.
+const DB_WRITE_RETRY_BACKOFF: Duration = Duration::from_millis(10);
+
+/// Returns whether `err` is a transient write conflict that may succeed if the write is retried,
+/// e.g. because another transaction holds the write lock. All other errors are permanent.
+pub fn is_transient_write_error(err: &ProviderError) -> bool {
+    let code = match err {
+        ProviderError::Database(DatabaseError::InitTx(code) | DatabaseError::Commit(code)) => *code,
+        ProviderError::Database(DatabaseError::Write(err)) => err.code,
+        _ => return false,
+    };
+    [MdbxError::Busy, MdbxError::ReadersFull]
+        .iter()
+        .any(|transient| transient.to_err_code() == code)
+}
+
+/// The content of all consensus tables, e.g. to migrate a validator to another node.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConsensusState {
+    /// Entries of [tables::ConsensusNumber]
+    pub numbers: Vec<(B256, BlockNumber)>,
+    /// Entries of [tables::ConsensusContent]
+    pub contents: Vec<(B256, ConsensusBytes)>,
+    /// Entries of [tables::CommittedPayloads]
+    pub committed_payloads: Vec<(BlockNumber, ConsensusBytes)>,
+    /// Entries of [tables::ViewChanges]
+    pub view_changes: Vec<(u64, ConsensusBytes)>,
+    /// Entries of [tables::RandaoMixes]
+    pub randao_mixes: Vec<(BlockNumber, B256)>,
+}
+
+impl ConsensusState {
+    /// Returns whether none of the consensus tables has an entry.
+    pub fn is_empty(&self) -> bool {
+        self.numbers.is_empty() &&
+            self.contents.is_empty() &&
+            self.committed_payloads.is_empty() &&
+            self.view_changes.is_empty() &&
+            self.randao_mixes.is_empty()
+    }
+}
+
+/// Returns all entries of table `T`, ordered by key.
+fn walk_table<T: Table>(tx: &impl DbTx) -> ProviderResult<Vec<(T::Key, T::Value)>> {
+    Ok(tx.cursor_read::<T>()?.walk(None)?.collect::<Result<Vec<_>, _>>()?)
+}
 
 /// The main type for interacting with the blockchain.
 ///
@@ -12,6 +73,8 @@ use reth_primitives::{BlockNumber, B256};
 pub struct ConsensusProvider<DB> {
     /// Provider type used to access the database.
     database: ProviderFactory<DB>,
+    /// How often a write that failed with a transient error is retried
+    write_retries: u32,
 }
 
 impl<DB> ConsensusProvider<DB>
@@ -21,7 +84,73 @@ where
     /// Create a new provider using only the database and the tree, fetching the latest header from
     /// the database to initialize the provider.
     pub fn new(database: ProviderFactory<DB>) -> ProviderResult<Self> {
-        Ok(Self { database })
+        Ok(Self { database, write_retries: DEFAULT_DB_WRITE_RETRIES })
+    }
+
+    /// Retries a write up to `retries` times if it failed with a transient error, see
+    /// [is_transient_write_error]. The calling thread backs off between the retries.
+    pub fn with_write_retries(mut self, retries: u32) -> Self {
+        self.write_retries = retries;
+        self
+    }
+
+    /// Reads the content of all consensus tables in one transaction.
+    pub fn export_state(&self) -> ProviderResult<ConsensusState> {
+        let provider = self.database.provider()?;
+        let tx = provider.tx_ref();
+        Ok(ConsensusState {
+            numbers: walk_table::<tables::ConsensusNumber>(tx)?,
+            contents: walk_table::<tables::ConsensusContent>(tx)?,
+            committed_payloads: walk_table::<tables::CommittedPayloads>(tx)?,
+            view_changes: walk_table::<tables::ViewChanges>(tx)?,
+            randao_mixes: walk_table::<tables::RandaoMixes>(tx)?,
+        })
+    }
+
+    /// Writes `state` to the consensus tables in one transaction, replacing the entries with the
+    /// same keys.
+    pub fn import_state(&self, state: &ConsensusState) -> ProviderResult<()> {
+        self.write_with_retry(|provider| {
+            let tx = provider.tx_ref();
+            for (hash, number) in &state.numbers {
+                tx.put::<tables::ConsensusNumber>(*hash, *number)?;
+            }
+            for (hash, content) in &state.contents {
+                tx.put::<tables::ConsensusContent>(*hash, content.clone())?;
+            }
+            for (number, payload) in &state.committed_payloads {
+                tx.put::<tables::CommittedPayloads>(*number, payload.clone())?;
+            }
+            for (view, record) in &state.view_changes {
+                tx.put::<tables::ViewChanges>(*view, record.clone())?;
+            }
+            for (number, mix) in &state.randao_mixes {
+                tx.put::<tables::RandaoMixes>(*number, *mix)?;
+            }
+            provider.commit()?;
+            Ok(())
+        })
+    }
+
+    /// Runs `write` in a new transaction, which it must commit. Retries in a new transaction with
+    /// exponential backoff while it fails with a transient error, up to the configured retries.
+    fn write_with_retry<T>(
+        &self,
+        mut write: impl FnMut(DatabaseProviderRW<DB>) -> ProviderResult<T>,
+    ) -> ProviderResult<T> {
+        let mut backoff = DB_WRITE_RETRY_BACKOFF;
+        let mut attempt = 0;
+        loop {
+            match self.database.provider_rw().and_then(&mut write) {
+                Err(err) if attempt < self.write_retries && is_transient_write_error(&err) => {
+                    attempt += 1;
+                    warn!(target: "providers::consensus", %err, attempt, "Retrying transient database write failure");
+                    std::thread::sleep(backoff);
+                    backoff *= 2;
+                }
+                res => return res,
+            }
+        }
     }
 }
 
@@ -43,6 +172,22 @@ where
     fn consensus_content(&self, hash: B256) -> ProviderResult<Option<ConsensusBytes>> {
         self.database.provider()?.consensus_content(hash)
     }
+
+    fn committed_payload(&self, number: BlockNumber) -> ProviderResult<Option<ConsensusBytes>> {
+        self.database.provider()?.committed_payload(number)
+    }
+
+    fn view_changes(&self, views: RangeInclusive<u64>) -> ProviderResult<Vec<ConsensusBytes>> {
+        self.database.provider()?.view_changes(views)
+    }
+
+    fn randao_mix(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        self.database.provider()?.randao_mix(number)
+    }
+
+    fn first_randao_mix_number(&self) -> ProviderResult<Option<BlockNumber>> {
+        self.database.provider()?.first_randao_mix_number()
+    }
 }
 
 impl<DB> ConsensusNumberWriter for ConsensusProvider<DB>
@@ -50,14 +195,125 @@ where
     DB: Database,
 {
     fn save_consensus_number(&self, hash: B256, num: BlockNumber) -> ProviderResult<bool> {
-        let provider = self.database.provider_rw()?;
-        provider.save_consensus_number(hash, num)?;
-        provider.commit()
+        self.write_with_retry(|provider| {
+            provider.save_consensus_number(hash, num)?;
+            provider.commit()
+        })
     }
 
     fn save_consensus_content(&self, hash: B256, ct: ConsensusBytes) -> ProviderResult<bool> {
-        let provider = self.database.provider_rw()?;
-        provider.save_consensus_content(hash, ct)?;
-        provider.commit()
+        self.write_with_retry(|provider| {
+            provider.save_consensus_content(hash, ct.clone())?;
+            provider.commit()
+        })
+    }
+
+    fn save_committed_payload(
+        &self,
+        number: BlockNumber,
+        payload: ConsensusBytes,
+    ) -> ProviderResult<bool> {
+        self.write_with_retry(|provider| {
+            provider.save_committed_payload(number, payload.clone())?;
+            provider.commit()
+        })
+    }
+
+    fn prune_committed_payloads(&self, number: BlockNumber) -> ProviderResult<usize> {
+        self.write_with_retry(|provider| {
+            let pruned = provider.prune_committed_payloads(number)?;
+            provider.commit()?;
+            Ok(pruned)
+        })
+    }
+
+    fn save_view_change(&self, view: u64, record: ConsensusBytes) -> ProviderResult<bool> {
+        self.write_with_retry(|provider| {
+            provider.save_view_change(view, record.clone())?;
+            provider.commit()
+        })
+    }
+
+    fn save_randao_mix(&self, number: BlockNumber, mix: B256) -> ProviderResult<bool> {
+        self.write_with_retry(|provider| {
+            provider.save_randao_mix(number, mix)?;
+            provider.commit()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::create_test_provider_factory;
+
+    fn busy() -> ProviderError {
+        DatabaseError::Commit(MdbxError::Busy.to_err_code()).into()
+    }
+
+    #[test]
+    fn retries_transient_write_errors() {
+        let provider = ConsensusProvider::new(create_test_provider_factory()).unwrap();
+        let hash = B256::with_last_byte(1);
+
+        // the write conflicts twice before it succeeds
+        let mut attempts = 0;
+        let saved = provider.write_with_retry(|provider| {
+            attempts += 1;
+            if attempts <= 2 {
+                return Err(busy())
+            }
+            provider.save_consensus_number(hash, 7)?;
+            provider.commit()
+        });
+        assert_eq!(saved, Ok(true));
+        assert_eq!(attempts, 3);
+        assert_eq!(provider.consensus_number(hash), Ok(Some(7)));
+
+        // permanent errors fail immediately
+        attempts = 0;
+        let res = provider.write_with_retry(|_| -> ProviderResult<bool> {
+            attempts += 1;
+            Err(DatabaseError::Commit(MdbxError::MapFull.to_err_code()).into())
+        });
+        assert!(res.is_err());
+        assert_eq!(attempts, 1);
+
+        // transient errors are retried only up to the configured retries
+        let provider = provider.with_write_retries(1);
+        attempts = 0;
+        let res = provider.write_with_retry(|_| -> ProviderResult<bool> {
+            attempts += 1;
+            Err(busy())
+        });
+        assert_eq!(res, Err(busy()));
+        assert_eq!(attempts, 2);
+    }
+
+    #[test]
+    fn state_round_trip() {
+        let source = ConsensusProvider::new(create_test_provider_factory()).unwrap();
+        let content = |byte| ConsensusBytes { content: vec![byte; 4] };
+        source.save_consensus_number(B256::with_last_byte(1), 1).unwrap();
+        source.save_consensus_number(B256::with_last_byte(2), 2).unwrap();
+        source.save_consensus_content(B256::with_last_byte(2), content(2)).unwrap();
+        source.save_committed_payload(2, content(3)).unwrap();
+        source.save_view_change(5, content(4)).unwrap();
+        source.save_randao_mix(2, B256::with_last_byte(5)).unwrap();
+
+        let state = source.export_state().unwrap();
+        assert_eq!(state.numbers.len(), 2);
+        assert!(!state.is_empty());
+
+        let target = ConsensusProvider::new(create_test_provider_factory()).unwrap();
+        assert!(target.export_state().unwrap().is_empty());
+        target.import_state(&state).unwrap();
+
+        assert_eq!(target.export_state().unwrap(), state);
+        assert_eq!(target.consensus_number(B256::with_last_byte(2)), Ok(Some(2)));
+        assert_eq!(target.consensus_content(B256::with_last_byte(2)), Ok(Some(content(2))));
+        assert_eq!(target.committed_payload(2), Ok(Some(content(3))));
+        assert_eq!(target.view_changes(0..=10), Ok(vec![content(4)]));
+        assert_eq!(target.randao_mix(2), Ok(Some(B256::with_last_byte(5))));
     }
 }
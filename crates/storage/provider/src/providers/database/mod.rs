@@ -513,6 +513,22 @@ impl<DB: Database> ConsensusNumberReader for ProviderFactory<DB> {
     fn consensus_content(&self, hash: B256) -> ProviderResult<Option<ConsensusBytes>> {
         self.provider()?.consensus_content(hash)
     }
+
+    fn committed_payload(&self, number: BlockNumber) -> ProviderResult<Option<ConsensusBytes>> {
+        self.provider()?.committed_payload(number)
+    }
+
+    fn view_changes(&self, views: RangeInclusive<u64>) -> ProviderResult<Vec<ConsensusBytes>> {
+        self.provider()?.view_changes(views)
+    }
+
+    fn randao_mix(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        self.provider()?.randao_mix(number)
+    }
+
+    fn first_randao_mix_number(&self) -> ProviderResult<Option<BlockNumber>> {
+        self.provider()?.first_randao_mix_number()
+    }
 }
 
 #[cfg(test)]
@@ -2531,6 +2531,27 @@ impl<TX: DbTx> ConsensusNumberReader for DatabaseProvider<TX> {
         let content = self.tx.get::<tables::ConsensusContent>(hash)?;
         Ok(content)
     }
+
+    fn committed_payload(&self, number: BlockNumber) -> ProviderResult<Option<ConsensusBytes>> {
+        Ok(self.tx.get::<tables::CommittedPayloads>(number)?)
+    }
+
+    fn view_changes(&self, views: RangeInclusive<u64>) -> ProviderResult<Vec<ConsensusBytes>> {
+        Ok(self
+            .tx
+            .cursor_read::<tables::ViewChanges>()?
+            .walk_range(views)?
+            .map(|entry| entry.map(|(_, record)| record))
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    fn randao_mix(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        Ok(self.tx.get::<tables::RandaoMixes>(number)?)
+    }
+
+    fn first_randao_mix_number(&self) -> ProviderResult<Option<BlockNumber>> {
+        Ok(self.tx.cursor_read::<tables::RandaoMixes>()?.first()?.map(|(number, _)| number))
+    }
 }
 
 impl<TX: DbTxMut> ConsensusNumberWriter for DatabaseProvider<TX> {
@@ -2545,6 +2566,36 @@ impl<TX: DbTxMut> ConsensusNumberWriter for DatabaseProvider<TX> {
         self.tx.put::<tables::ConsensusContent>(hash, ct)?;
         Ok(true)
     }
+
+    fn save_committed_payload(
+        &self,
+        number: BlockNumber,
+        payload: ConsensusBytes,
+    ) -> ProviderResult<bool> {
+        self.tx.put::<tables::CommittedPayloads>(number, payload)?;
+        Ok(true)
+    }
+
+    fn prune_committed_payloads(&self, number: BlockNumber) -> ProviderResult<usize> {
+        let mut cursor = self.tx.cursor_write::<tables::CommittedPayloads>()?;
+        let mut walker = cursor.walk_range(..number)?;
+        let mut pruned = 0;
+        while walker.next().transpose()?.is_some() {
+            walker.delete_current()?;
+            pruned += 1;
+        }
+        Ok(pruned)
+    }
+
+    fn save_view_change(&self, view: u64, record: ConsensusBytes) -> ProviderResult<bool> {
+        self.tx.put::<tables::ViewChanges>(view, record)?;
+        Ok(true)
+    }
+
+    fn save_randao_mix(&self, number: BlockNumber, mix: B256) -> ProviderResult<bool> {
+        self.tx.put::<tables::RandaoMixes>(number, mix)?;
+        Ok(true)
+    }
 }
 
 fn range_size_hint(range: &impl RangeBounds<TxNumber>) -> Option<usize> {
@@ -37,7 +37,9 @@ pub use state::{
 mod bundle_state_provider;
 mod chain_info;
 mod consensus_provider;
-pub use consensus_provider::ConsensusProvider;
+pub use consensus_provider::{
+    is_transient_write_error, ConsensusProvider, ConsensusState, DEFAULT_DB_WRITE_RETRIES,
+};
 mod database;
 mod snapshot;
 pub use snapshot::{SnapshotJarProvider, SnapshotProvider};
@@ -601,6 +603,14 @@ where
         self.tree.buffer_block(block)
     }
 
+    fn insert_block_without_senders(
+        &self,
+        block: SealedBlock,
+        validation_kind: BlockValidationKind,
+    ) -> Result<InsertPayloadOk, InsertBlockError> {
+        self.tree.insert_block_without_senders(block, validation_kind)
+    }
+
     fn insert_block(
         &self,
         block: SealedBlockWithSenders,
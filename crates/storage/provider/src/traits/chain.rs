@@ -1,6 +1,7 @@
 //! Canonical chain state notification trait and types.
 use crate::{chain::BlockReceipts, Chain};
 use auto_impl::auto_impl;
+use reth_metrics::{metrics::Counter, Metrics};
 use reth_primitives::SealedBlockWithSenders;
 use std::{
     pin::Pin,
@@ -8,7 +9,10 @@ use std::{
     task::{ready, Context, Poll},
 };
 use tokio::sync::broadcast;
-use tokio_stream::{wrappers::BroadcastStream, Stream};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    Stream,
+};
 use tracing::debug;
 
 /// Type alias for a receiver that receives [CanonStateNotification]
@@ -27,18 +31,47 @@ pub trait CanonStateSubscriptions: Send + Sync {
 
     /// Convenience method to get a stream of [`CanonStateNotification`].
     fn canonical_state_stream(&self) -> CanonStateNotificationStream {
-        CanonStateNotificationStream {
-            st: BroadcastStream::new(self.subscribe_to_canonical_state()),
-        }
+        CanonStateNotificationStream::new(self.subscribe_to_canonical_state())
     }
 }
 
+/// Metrics of all [CanonStateNotificationStream]s
+#[derive(Metrics)]
+#[metrics(scope = "canon_state_notifications")]
+struct CanonStateNotificationMetrics {
+    /// Number of notifications dropped because a subscriber lagged behind
+    lagged: Counter,
+}
+
 /// A Stream of [CanonStateNotification].
+///
+/// The notifications are buffered in a bounded channel. If the subscriber falls behind by more
+/// than the capacity of the channel, the oldest notifications are dropped and the stream continues
+/// with the oldest notification still buffered.
 #[derive(Debug)]
 #[pin_project::pin_project]
 pub struct CanonStateNotificationStream {
     #[pin]
     st: BroadcastStream<CanonStateNotification>,
+    /// Number of notifications this stream missed because it lagged behind
+    lagged: u64,
+    metrics: CanonStateNotificationMetrics,
+}
+
+impl CanonStateNotificationStream {
+    /// Creates a stream of the notifications received by `notifications`.
+    pub fn new(notifications: CanonStateNotifications) -> Self {
+        Self {
+            st: BroadcastStream::new(notifications),
+            lagged: 0,
+            metrics: CanonStateNotificationMetrics::default(),
+        }
+    }
+
+    /// Returns the number of notifications this stream missed because it lagged behind.
+    pub fn lagged(&self) -> u64 {
+        self.lagged
+    }
 }
 
 impl Stream for CanonStateNotificationStream {
@@ -48,8 +81,11 @@ impl Stream for CanonStateNotificationStream {
         loop {
             return match ready!(self.as_mut().project().st.poll_next(cx)) {
                 Some(Ok(notification)) => Poll::Ready(Some(notification)),
-                Some(Err(err)) => {
-                    debug!(%err, "canonical state notification stream lagging behind");
+                Some(Err(BroadcastStreamRecvError::Lagged(skipped))) => {
+                    debug!(skipped, "canonical state notification stream lagging behind");
+                    let this = self.as_mut().project();
+                    *this.lagged += skipped;
+                    this.metrics.lagged.increment(skipped);
                     continue
                 }
                 None => Poll::Ready(None),
@@ -134,3 +170,41 @@ impl CanonStateNotification {
         receipts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Header, SealedBlock};
+    use tokio_stream::StreamExt;
+
+    fn commit(number: u64) -> CanonStateNotification {
+        let block = SealedBlock {
+            header: Header { number, ..Default::default() }.seal_slow(),
+            ..Default::default()
+        };
+        let block = SealedBlockWithSenders { block, senders: vec![] };
+        let chain = Chain::new(vec![block], Default::default());
+        CanonStateNotification::Commit { new: Arc::new(chain) }
+    }
+
+    #[tokio::test]
+    async fn slow_subscriber_drops_oldest() {
+        let (sender, receiver) = broadcast::channel(2);
+        let mut stream = CanonStateNotificationStream::new(receiver);
+
+        // the subscriber does not receive while five notifications are sent
+        for number in 1..=5 {
+            sender.send(commit(number)).unwrap();
+        }
+        assert_eq!(sender.len(), 2);
+
+        // the oldest notifications were dropped, the newest are still delivered
+        assert_eq!(stream.next().await.unwrap().tip().number, 4);
+        assert_eq!(stream.lagged(), 3);
+        assert_eq!(stream.next().await.unwrap().tip().number, 5);
+
+        sender.send(commit(6)).unwrap();
+        assert_eq!(stream.next().await.unwrap().tip().number, 6);
+        assert_eq!(stream.lagged(), 3);
+    }
+}
@@ -1,6 +1,7 @@
 use reth_db::models::consensus::ConsensusBytes;
 use reth_interfaces::provider::ProviderResult;
 use reth_primitives::{BlockNumber, B256};
+use std::ops::RangeInclusive;
 
 /// Client trait for getting important block numbers (such as the latest block number), converting
 /// block hashes to numbers, and fetching a block hash from its block number.
@@ -16,6 +17,21 @@ pub trait ConsensusNumberReader: Send + Sync {
 
     /// Gets the `BlockNumber` for the given hash. Returns `None` if no block with this hash exists.
     fn consensus_content(&self, hash: B256) -> ProviderResult<Option<ConsensusBytes>>;
+
+    /// Gets the execution payload committed at the given block number. Returns `None` if it was
+    /// not stored or already pruned.
+    fn committed_payload(&self, number: BlockNumber) -> ProviderResult<Option<ConsensusBytes>>;
+
+    /// Gets the recorded view changes to the views in `views`, ordered by view.
+    fn view_changes(&self, views: RangeInclusive<u64>) -> ProviderResult<Vec<ConsensusBytes>>;
+
+    /// Gets the randao mix after the block at the given block number was committed. Returns
+    /// `None` if it was not recorded.
+    fn randao_mix(&self, number: BlockNumber) -> ProviderResult<Option<B256>>;
+
+    /// Gets the number of the first block a randao mix was recorded for. Returns `None` if no mix
+    /// was recorded.
+    fn first_randao_mix_number(&self) -> ProviderResult<Option<BlockNumber>>;
 }
 
 /// Client trait for getting important block numbers (such as the latest block number), converting
@@ -29,4 +45,21 @@ pub trait ConsensusNumberWriter: Send + Sync {
 
     /// Gets the `BlockNumber` for the given hash. Returns `None` if no block with this hash exists.
     fn save_consensus_content(&self, hash: B256, ct: ConsensusBytes) -> ProviderResult<bool>;
+
+    /// Stores the execution payload committed at the given block number.
+    fn save_committed_payload(
+        &self,
+        number: BlockNumber,
+        payload: ConsensusBytes,
+    ) -> ProviderResult<bool>;
+
+    /// Removes the committed execution payloads of all blocks below `number`. Returns the number
+    /// of removed payloads.
+    fn prune_committed_payloads(&self, number: BlockNumber) -> ProviderResult<usize>;
+
+    /// Stores the record of the view change to `view`.
+    fn save_view_change(&self, view: u64, record: ConsensusBytes) -> ProviderResult<bool>;
+
+    /// Stores the randao mix after the block at the given block number was committed.
+    fn save_randao_mix(&self, number: BlockNumber, mix: B256) -> ProviderResult<bool>;
 }
@@ -6,7 +6,8 @@
 //! - [`file()`]
 //! - [`journald()`]
 //!
-//! As well as a simple way to initialize a subscriber: [`init`].
+//! As well as a simple way to initialize a subscriber: [`init`], and a [`TargetLevelFilter`] to
+//! change the level of a single target at runtime.
 
 #![doc(
     html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/reth/main/assets/reth-docs.png",
@@ -21,9 +22,12 @@ use rolling_file::{RollingConditionBasic, RollingFileAppender};
 use std::path::Path;
 use tracing::Subscriber;
 use tracing_subscriber::{
-    filter::Directive, prelude::*, registry::LookupSpan, EnvFilter, Layer, Registry,
+    filter::Directive, layer::Filter, prelude::*, registry::LookupSpan, EnvFilter, Layer, Registry,
 };
 
+mod target_level;
+pub use target_level::{TargetLevel, TargetLevelFilter};
+
 // Re-export tracing crates
 pub use tracing;
 pub use tracing_subscriber;
@@ -43,6 +47,21 @@ pub fn init(layers: Vec<BoxedLayer<Registry>>) {
 /// Colors can be disabled with `RUST_LOG_STYLE=never`, and event targets can be displayed with
 /// `RUST_LOG_TARGET=1`.
 pub fn stdout<S>(default_directive: impl Into<Directive>, color: &str) -> BoxedLayer<S>
+where
+    S: Subscriber,
+    for<'a> S: LookupSpan<'a>,
+{
+    stdout_with_target_level(default_directive, color, None)
+}
+
+/// Builds a new tracing layer that writes to stdout, like [`stdout()`].
+///
+/// The events of the target of `target_level` are filtered by its level instead.
+pub fn stdout_with_target_level<S>(
+    default_directive: impl Into<Directive>,
+    color: &str,
+    target_level: Option<TargetLevel>,
+) -> BoxedLayer<S>
 where
     S: Subscriber,
     for<'a> S: LookupSpan<'a>,
@@ -54,6 +73,7 @@ where
 
     let filter =
         EnvFilter::builder().with_default_directive(default_directive.into()).from_env_lossy();
+    let filter = TargetLevelFilter::new(filter, target_level);
 
     tracing_subscriber::fmt::layer()
         .with_ansi(with_ansi)
@@ -69,8 +89,8 @@ where
 /// The boxed layer and a guard is returned. When the guard is dropped the buffer for the log
 /// file is immediately flushed to disk. Any events after the guard is dropped may be missed.
 #[must_use = "tracing guard must be kept alive to flush events to disk"]
-pub fn file<S>(
-    filter: EnvFilter,
+pub fn file<S, F>(
+    filter: F,
     dir: impl AsRef<Path>,
     file_name: impl AsRef<Path>,
     max_size_bytes: u64,
@@ -79,6 +99,7 @@ pub fn file<S>(
 where
     S: Subscriber,
     for<'a> S: LookupSpan<'a>,
+    F: Filter<S> + Send + Sync + 'static,
 {
     // Create log dir if it doesn't exist (RFA doesn't do this for us)
     let log_dir = dir.as_ref();
@@ -115,10 +136,11 @@ pub type FileWorkerGuard = tracing_appender::non_blocking::WorkerGuard;
 /// The events are filtered by `filter`.
 ///
 /// If the layer cannot connect to journald for any reason this function will return an error.
-pub fn journald<S>(filter: EnvFilter) -> std::io::Result<BoxedLayer<S>>
+pub fn journald<S, F>(filter: F) -> std::io::Result<BoxedLayer<S>>
 where
     S: Subscriber,
     for<'a> S: LookupSpan<'a>,
+    F: Filter<S> + Send + Sync + 'static,
 {
     Ok(tracing_journald::layer()?.with_filter(filter).boxed())
 }
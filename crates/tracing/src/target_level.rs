@@ -0,0 +1,200 @@
+//! Log levels of a single target that can be changed at runtime.
+
+use std::sync::{Arc, PoisonError, RwLock};
+use tracing::{
+    level_filters::LevelFilter,
+    span::{Attributes, Id, Record},
+    subscriber::Interest,
+    Metadata,
+};
+use tracing_subscriber::layer::{Context, Filter};
+
+/// The levels from the least to the most verbose.
+const LEVELS: [LevelFilter; 6] = [
+    LevelFilter::OFF,
+    LevelFilter::ERROR,
+    LevelFilter::WARN,
+    LevelFilter::INFO,
+    LevelFilter::DEBUG,
+    LevelFilter::TRACE,
+];
+
+/// The level of a log target and its child targets, shared by all layers filtering with it.
+///
+/// Changing the level applies to all layers immediately, e.g. to raise the verbosity of a single
+/// component of a running node.
+#[derive(Debug, Clone)]
+pub struct TargetLevel {
+    target: String,
+    level: Arc<RwLock<LevelFilter>>,
+}
+
+impl TargetLevel {
+    /// Creates the `level` of `target`.
+    pub fn new(target: impl Into<String>, level: LevelFilter) -> Self {
+        Self { target: target.into(), level: Arc::new(RwLock::new(level)) }
+    }
+
+    /// Returns the target.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the current level.
+    pub fn level(&self) -> LevelFilter {
+        *self.level.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Sets the level.
+    pub fn set_level(&self, level: LevelFilter) {
+        *self.level.write().unwrap_or_else(PoisonError::into_inner) = level;
+    }
+
+    /// Makes the target one level more verbose, up to `TRACE`, and returns the new level.
+    pub fn raise(&self) -> LevelFilter {
+        self.step(|index| (index + 1).min(LEVELS.len() - 1))
+    }
+
+    /// Makes the target one level less verbose, down to `OFF`, and returns the new level.
+    pub fn lower(&self) -> LevelFilter {
+        self.step(|index| index.saturating_sub(1))
+    }
+
+    fn step(&self, next: impl FnOnce(usize) -> usize) -> LevelFilter {
+        let mut level = self.level.write().unwrap_or_else(PoisonError::into_inner);
+        let index = LEVELS.iter().position(|l| l == &*level).unwrap_or_default();
+        *level = LEVELS[next(index)];
+        *level
+    }
+
+    /// Returns `true` if `target` is the target or one of its children.
+    fn applies_to(&self, target: &str) -> bool {
+        target
+            .strip_prefix(self.target.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+    }
+}
+
+/// A per-layer [Filter] that filters the events of a [TargetLevel] by its level and all other
+/// events with the inner filter.
+#[derive(Debug)]
+pub struct TargetLevelFilter<F> {
+    inner: F,
+    level: Option<TargetLevel>,
+}
+
+impl<F> TargetLevelFilter<F> {
+    /// Creates a filter overriding `inner` for the target of `level`, if set.
+    pub fn new(inner: F, level: Option<TargetLevel>) -> Self {
+        Self { inner, level }
+    }
+
+    fn level_of(&self, metadata: &Metadata<'_>) -> Option<LevelFilter> {
+        self.level
+            .as_ref()
+            .filter(|level| level.applies_to(metadata.target()))
+            .map(TargetLevel::level)
+    }
+}
+
+impl<S, F: Filter<S>> Filter<S> for TargetLevelFilter<F> {
+    fn enabled(&self, metadata: &Metadata<'_>, cx: &Context<'_, S>) -> bool {
+        match self.level_of(metadata) {
+            Some(level) => metadata.level() <= &level,
+            None => self.inner.enabled(metadata, cx),
+        }
+    }
+
+    fn callsite_enabled(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if self.level_of(metadata).is_some() {
+            // the level can change at any time, so it is checked for every event
+            return Interest::sometimes()
+        }
+        self.inner.callsite_enabled(metadata)
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        // the level of the target can be raised above the one of the inner filter
+        if self.level.is_some() {
+            return None
+        }
+        self.inner.max_level_hint()
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, cx: Context<'_, S>) {
+        self.inner.on_new_span(attrs, id, cx)
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, cx: Context<'_, S>) {
+        self.inner.on_record(id, values, cx)
+    }
+
+    fn on_enter(&self, id: &Id, cx: Context<'_, S>) {
+        self.inner.on_enter(id, cx)
+    }
+
+    fn on_exit(&self, id: &Id, cx: Context<'_, S>) {
+        self.inner.on_exit(id, cx)
+    }
+
+    fn on_close(&self, id: Id, cx: Context<'_, S>) {
+        self.inner.on_close(id, cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io, sync::Mutex};
+    use tracing::{debug, info, trace, warn};
+    use tracing_subscriber::{prelude::*, EnvFilter, Registry};
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn filters_target_by_its_own_level() {
+        let level = TargetLevel::new("consensus::cl", LevelFilter::DEBUG);
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = Registry::default().with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(move || writer.clone())
+                .with_filter(TargetLevelFilter::new(EnvFilter::new("info"), Some(level.clone()))),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            debug!(target: "consensus::cl", "consensus debug");
+            trace!(target: "consensus::cl", "consensus trace");
+            debug!(target: "net", "net debug");
+            info!(target: "net", "net info");
+
+            assert_eq!(level.lower(), LevelFilter::INFO);
+            assert_eq!(level.lower(), LevelFilter::WARN);
+            info!(target: "consensus::cl", "consensus info");
+            warn!(target: "consensus::cl::engine", "consensus warn");
+            debug!(target: "consensus::clayer", "other debug");
+        });
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("consensus debug"), "{logs}");
+        assert!(!logs.contains("consensus trace"), "{logs}");
+        assert!(!logs.contains("net debug"), "{logs}");
+        assert!(logs.contains("net info"), "{logs}");
+        assert!(!logs.contains("consensus info"), "{logs}");
+        assert!(logs.contains("consensus warn"), "{logs}");
+        assert!(!logs.contains("other debug"), "{logs}");
+    }
+}
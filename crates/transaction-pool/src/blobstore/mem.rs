@@ -1,19 +1,137 @@
-use crate::blobstore::{BlobStore, BlobStoreError, BlobStoreSize, BlobTransactionSidecar};
+use crate::blobstore::{
+    BlobStore, BlobStoreError, BlobStoreSize, BlobTransactionSidecar, DiskFileBlobStore,
+};
 use parking_lot::RwLock;
 use reth_primitives::B256;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+use tracing::trace;
 
 /// An in-memory blob store.
+///
+/// By default the store is unbounded. If limits are configured via
+/// [InMemoryBlobStore::with_config], the oldest blobs are evicted once a limit is exceeded and
+/// moved to the overflow store, if one is set via [InMemoryBlobStore::with_overflow].
 #[derive(Clone, Debug, Default)]
 pub struct InMemoryBlobStore {
     inner: Arc<InMemoryBlobStoreInner>,
 }
 
+impl InMemoryBlobStore {
+    /// Creates a new in-memory blob store with the given limits.
+    pub fn with_config(config: InMemoryBlobStoreConfig) -> Self {
+        Self { inner: Arc::new(InMemoryBlobStoreInner { config, ..Default::default() }) }
+    }
+
+    /// Creates a new in-memory blob store with the given limits that moves evicted blobs to the
+    /// given disk store instead of dropping them.
+    pub fn with_overflow(config: InMemoryBlobStoreConfig, overflow: DiskFileBlobStore) -> Self {
+        Self {
+            inner: Arc::new(InMemoryBlobStoreInner {
+                config,
+                overflow: Some(overflow),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Evicts the oldest blobs until the store is within its configured limits.
+    fn evict(&self, store: &mut MemStore) -> Result<(), BlobStoreError> {
+        let mut evicted = Vec::new();
+        let mut total_sub = 0;
+        while self
+            .inner
+            .config
+            .is_exceeded(self.inner.size_tracker.data_size() - total_sub, store.len())
+        {
+            let Some(tx) = store.order.pop_front() else { break };
+            if let Some(blob) = store.blobs.remove(&tx) {
+                total_sub += blob.size();
+                evicted.push((tx, blob));
+            }
+        }
+        self.inner.size_tracker.sub_size(total_sub);
+        self.inner.size_tracker.update_len(store.len());
+
+        if evicted.is_empty() {
+            return Ok(())
+        }
+        trace!(target: "txpool::blob", evicted = evicted.len(), overflow = self.inner.overflow.is_some(), "Evicted blobs from in-memory blob store");
+        match &self.inner.overflow {
+            Some(overflow) => overflow.insert_all(evicted),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Limits of an [InMemoryBlobStore].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InMemoryBlobStoreConfig {
+    /// Maximum total size of the blob data held in memory, in bytes.
+    pub max_size: Option<usize>,
+    /// Maximum number of blobs held in memory.
+    pub max_entries: Option<usize>,
+}
+
+impl InMemoryBlobStoreConfig {
+    /// Sets the maximum total size of the blob data held in memory, in bytes.
+    pub const fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Sets the maximum number of blobs held in memory.
+    pub const fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Returns true if a store with the given data size and number of blobs exceeds the limits.
+    #[inline]
+    fn is_exceeded(&self, size: usize, len: usize) -> bool {
+        self.max_size.map_or(false, |max| size > max) ||
+            self.max_entries.map_or(false, |max| len > max)
+    }
+}
+
 #[derive(Debug, Default)]
 struct InMemoryBlobStoreInner {
     /// Storage for all blob data.
-    store: RwLock<HashMap<B256, BlobTransactionSidecar>>,
+    store: RwLock<MemStore>,
     size_tracker: BlobStoreSize,
+    /// Limits of the in-memory storage.
+    config: InMemoryBlobStoreConfig,
+    /// Where evicted blobs are moved to.
+    overflow: Option<DiskFileBlobStore>,
+}
+
+/// Blob data held in memory.
+#[derive(Debug, Default)]
+struct MemStore {
+    blobs: HashMap<B256, BlobTransactionSidecar>,
+    /// Insertion order of the blobs, oldest first.
+    ///
+    /// Deleted blobs are only removed lazily, so this can contain hashes that are no longer in
+    /// `blobs`.
+    order: VecDeque<B256>,
+}
+
+impl MemStore {
+    #[inline]
+    fn len(&self) -> usize {
+        self.blobs.len()
+    }
+
+    /// Drops the hashes of deleted blobs from the insertion order once they dominate it.
+    #[inline]
+    fn compact_order(&mut self) {
+        if self.order.len() > 2 * self.blobs.len() {
+            let blobs = &self.blobs;
+            self.order.retain(|tx| blobs.contains_key(tx));
+        }
+    }
 }
 
 impl BlobStore for InMemoryBlobStore {
@@ -21,7 +139,7 @@ impl BlobStore for InMemoryBlobStore {
         let mut store = self.inner.store.write();
         self.inner.size_tracker.add_size(insert_size(&mut store, tx, data));
         self.inner.size_tracker.update_len(store.len());
-        Ok(())
+        self.evict(&mut store)
     }
 
     fn insert_all(&self, txs: Vec<(B256, BlobTransactionSidecar)>) -> Result<(), BlobStoreError> {
@@ -36,15 +154,21 @@ impl BlobStore for InMemoryBlobStore {
         }
         self.inner.size_tracker.add_size(total_add);
         self.inner.size_tracker.update_len(store.len());
-        Ok(())
+        self.evict(&mut store)
     }
 
     fn delete(&self, tx: B256) -> Result<(), BlobStoreError> {
         let mut store = self.inner.store.write();
-        let sub = remove_size(&mut store, &tx);
-        self.inner.size_tracker.sub_size(sub);
+        let removed = store.blobs.remove(&tx);
+        store.compact_order();
+        self.inner
+            .size_tracker
+            .sub_size(removed.as_ref().map(|rem| rem.size()).unwrap_or_default());
         self.inner.size_tracker.update_len(store.len());
-        Ok(())
+        match (&self.inner.overflow, removed) {
+            (Some(overflow), None) => overflow.delete_all(vec![tx]),
+            _ => Ok(()),
+        }
     }
 
     fn delete_all(&self, txs: Vec<B256>) -> Result<(), BlobStoreError> {
@@ -53,23 +177,41 @@ impl BlobStore for InMemoryBlobStore {
         }
         let mut store = self.inner.store.write();
         let mut total_sub = 0;
+        let mut missing = Vec::new();
         for tx in txs {
-            total_sub += remove_size(&mut store, &tx);
+            match store.blobs.remove(&tx) {
+                Some(rem) => total_sub += rem.size(),
+                None => missing.push(tx),
+            }
         }
+        store.compact_order();
         self.inner.size_tracker.sub_size(total_sub);
         self.inner.size_tracker.update_len(store.len());
-        Ok(())
+        match &self.inner.overflow {
+            Some(overflow) => overflow.delete_all(missing),
+            None => Ok(()),
+        }
     }
 
     // Retrieves the decoded blob data for the given transaction hash.
     fn get(&self, tx: B256) -> Result<Option<BlobTransactionSidecar>, BlobStoreError> {
-        let store = self.inner.store.read();
-        Ok(store.get(&tx).cloned())
+        if let Some(blob) = self.inner.store.read().blobs.get(&tx) {
+            return Ok(Some(blob.clone()))
+        }
+        match &self.inner.overflow {
+            Some(overflow) => overflow.get(tx),
+            None => Ok(None),
+        }
     }
 
     fn contains(&self, tx: B256) -> Result<bool, BlobStoreError> {
-        let store = self.inner.store.read();
-        Ok(store.contains_key(&tx))
+        if self.inner.store.read().blobs.contains_key(&tx) {
+            return Ok(true)
+        }
+        match &self.inner.overflow {
+            Some(overflow) => overflow.contains(tx),
+            None => Ok(false),
+        }
     }
 
     fn get_all(
@@ -77,10 +219,20 @@ impl BlobStore for InMemoryBlobStore {
         txs: Vec<B256>,
     ) -> Result<Vec<(B256, BlobTransactionSidecar)>, BlobStoreError> {
         let mut items = Vec::with_capacity(txs.len());
-        let store = self.inner.store.read();
-        for tx in txs {
-            if let Some(item) = store.get(&tx) {
-                items.push((tx, item.clone()));
+        let mut missing = Vec::new();
+        {
+            let store = self.inner.store.read();
+            for tx in txs {
+                if let Some(item) = store.blobs.get(&tx) {
+                    items.push((tx, item.clone()));
+                } else {
+                    missing.push(tx);
+                }
+            }
+        }
+        if let Some(overflow) = &self.inner.overflow {
+            if !missing.is_empty() {
+                items.extend(overflow.get_all(missing)?);
             }
         }
 
@@ -91,8 +243,12 @@ impl BlobStore for InMemoryBlobStore {
         let mut items = Vec::with_capacity(txs.len());
         let store = self.inner.store.read();
         for tx in txs {
-            if let Some(item) = store.get(&tx) {
+            if let Some(item) = store.blobs.get(&tx) {
                 items.push(item.clone());
+            } else if let Some(item) =
+                self.inner.overflow.as_ref().map(|overflow| overflow.get(tx)).transpose()?.flatten()
+            {
+                items.push(item);
             } else {
                 return Err(BlobStoreError::MissingSidecar(tx))
             }
@@ -102,30 +258,119 @@ impl BlobStore for InMemoryBlobStore {
     }
 
     fn data_size_hint(&self) -> Option<usize> {
-        Some(self.inner.size_tracker.data_size())
+        let overflow = self
+            .inner
+            .overflow
+            .as_ref()
+            .and_then(|overflow| overflow.data_size_hint())
+            .unwrap_or_default();
+        Some(self.inner.size_tracker.data_size() + overflow)
     }
 
     fn blobs_len(&self) -> usize {
-        self.inner.size_tracker.blobs_len()
+        let overflow =
+            self.inner.overflow.as_ref().map(|overflow| overflow.blobs_len()).unwrap_or_default();
+        self.inner.size_tracker.blobs_len() + overflow
     }
 }
 
-/// Removes the given blob from the store and returns the size of the blob that was removed.
-#[inline]
-fn remove_size(store: &mut HashMap<B256, BlobTransactionSidecar>, tx: &B256) -> usize {
-    store.remove(tx).map(|rem| rem.size()).unwrap_or_default()
-}
-
 /// Inserts the given blob into the store and returns the size of the blob that was added
 ///
 /// We don't need to handle the size updates for replacements because transactions are unique.
 #[inline]
-fn insert_size(
-    store: &mut HashMap<B256, BlobTransactionSidecar>,
-    tx: B256,
-    blob: BlobTransactionSidecar,
-) -> usize {
+fn insert_size(store: &mut MemStore, tx: B256, blob: BlobTransactionSidecar) -> usize {
     let add = blob.size();
-    store.insert(tx, blob).map(|rem| rem.size());
+    if store.blobs.insert(tx, blob).is_none() {
+        store.order.push_back(tx);
+    }
     add
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{
+        prelude::*,
+        strategy::{Strategy, ValueTree},
+        test_runner::TestRunner,
+    };
+
+    fn rng_blobs(num: usize) -> Vec<(B256, BlobTransactionSidecar)> {
+        let mut runner = TestRunner::new(Default::default());
+        prop::collection::vec(any::<(B256, BlobTransactionSidecar)>(), num)
+            .new_tree(&mut runner)
+            .unwrap()
+            .current()
+    }
+
+    #[test]
+    fn mem_evicts_oldest_past_max_entries() {
+        let store =
+            InMemoryBlobStore::with_config(InMemoryBlobStoreConfig::default().with_max_entries(3));
+
+        let blobs = rng_blobs(5);
+        for (tx, blob) in blobs.clone() {
+            store.insert(tx, blob).unwrap();
+        }
+
+        assert_eq!(store.blobs_len(), 3);
+        assert!(!store.contains(blobs[0].0).unwrap());
+        assert!(!store.contains(blobs[1].0).unwrap());
+        for (tx, blob) in &blobs[2..] {
+            assert_eq!(store.get(*tx).unwrap().as_ref(), Some(blob));
+        }
+        let size = blobs[2..].iter().map(|(_, blob)| blob.size()).sum::<usize>();
+        assert_eq!(store.data_size_hint(), Some(size));
+    }
+
+    #[test]
+    fn mem_evicts_oldest_past_max_size() {
+        let blobs = rng_blobs(4);
+        let max_size = blobs[2..].iter().map(|(_, blob)| blob.size()).sum::<usize>();
+        let store = InMemoryBlobStore::with_config(
+            InMemoryBlobStoreConfig::default().with_max_size(max_size),
+        );
+
+        store.insert_all(blobs.clone()).unwrap();
+
+        assert!(store.data_size_hint().unwrap() <= max_size);
+        assert!(!store.contains(blobs[0].0).unwrap());
+        assert!(store.contains(blobs[3].0).unwrap());
+    }
+
+    #[test]
+    fn mem_overflows_to_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let disk = DiskFileBlobStore::open(dir.path(), Default::default()).unwrap();
+        let store = InMemoryBlobStore::with_overflow(
+            InMemoryBlobStoreConfig::default().with_max_entries(2),
+            disk.clone(),
+        );
+
+        let blobs = rng_blobs(4);
+        let all_hashes = blobs.iter().map(|(tx, _)| *tx).collect::<Vec<_>>();
+        store.insert_all(blobs.clone()).unwrap();
+
+        // the two oldest blobs were moved to disk, nothing was lost
+        assert_eq!(store.inner.store.read().len(), 2);
+        assert!(disk.contains(blobs[0].0).unwrap());
+        assert!(disk.contains(blobs[1].0).unwrap());
+        assert_eq!(store.blobs_len(), 4);
+        let exact = store.get_exact(all_hashes.clone()).unwrap();
+        assert_eq!(exact, blobs.iter().map(|(_, blob)| blob.clone()).collect::<Vec<_>>());
+
+        store.delete_all(all_hashes).unwrap();
+        assert_eq!(store.inner.store.read().len(), 0);
+        assert!(!dir.path().join(format!("{:x}", blobs[0].0)).exists());
+    }
+
+    #[test]
+    fn mem_unbounded_by_default() {
+        let store = InMemoryBlobStore::default();
+        let blobs = rng_blobs(5);
+        store.insert_all(blobs.clone()).unwrap();
+        store.delete(blobs[0].0).unwrap();
+        assert_eq!(store.blobs_len(), 4);
+        assert_eq!(store.inner.store.read().order.len(), 5);
+    }
+}
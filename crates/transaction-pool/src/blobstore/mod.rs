@@ -1,7 +1,7 @@
 //! Storage for blob data of EIP4844 transactions.
 
 pub use disk::{DiskFileBlobStore, DiskFileBlobStoreConfig, OpenDiskFileBlobStore};
-pub use mem::InMemoryBlobStore;
+pub use mem::{InMemoryBlobStore, InMemoryBlobStoreConfig};
 pub use noop::NoopBlobStore;
 use reth_primitives::{BlobTransactionSidecar, B256};
 use std::{fmt, sync::atomic::AtomicUsize};
@@ -2,30 +2,35 @@
 
 use crate::{
     blobstore::{BlobStoreCanonTracker, BlobStoreUpdates},
+    error::PoolError,
     metrics::MaintainPoolMetrics,
-    traits::{CanonicalStateUpdate, ChangedAccount, TransactionPoolExt},
-    BlockInfo, TransactionPool,
+    traits::{AllPoolTransactions, CanonicalStateUpdate, ChangedAccount, TransactionPoolExt},
+    BlockInfo, TransactionOrigin, TransactionPool,
 };
+use alloy_rlp::{Decodable, RlpDecodable, RlpEncodable};
 use futures_util::{
     future::{BoxFuture, Fuse, FusedFuture},
     FutureExt, Stream, StreamExt,
 };
 use reth_primitives::{
+    fs::{self, FsPathError},
     Address, BlockHash, BlockNumber, BlockNumberOrTag, FromRecoveredPooledTransaction,
-    FromRecoveredTransaction, PooledTransactionsElementEcRecovered,
+    FromRecoveredTransaction, IntoRecoveredTransaction, PooledTransactionsElementEcRecovered,
+    TransactionSigned,
 };
 use reth_provider::{
     BlockReaderIdExt, BundleStateWithReceipts, CanonStateNotification, ChainSpecProvider,
     ProviderError, StateProviderFactory,
 };
-use reth_tasks::TaskSpawner;
+use reth_tasks::{shutdown::GracefulShutdown, TaskSpawner};
 use std::{
     borrow::Borrow,
     collections::HashSet,
     hash::{Hash, Hasher},
+    path::{Path, PathBuf},
 };
 use tokio::sync::oneshot;
-use tracing::{debug, trace};
+use tracing::{debug, info, trace, warn};
 
 /// Additional settings for maintaining the transaction pool
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -535,9 +540,117 @@ fn changed_accounts_iter(
         .map(|(address, acc)| ChangedAccount { address, nonce: acc.nonce, balance: acc.balance })
 }
 
+/// Errors of persisting the transactions of the pool across restarts
+#[derive(Debug, thiserror::Error)]
+pub enum TransactionsBackupError {
+    /// Failed to read or write the file of the persisted transactions.
+    #[error(transparent)]
+    FsPath(#[from] FsPathError),
+    /// Failed to decode the persisted transactions.
+    #[error("failed to decode persisted transactions: {0}")]
+    Decode(#[from] alloy_rlp::Error),
+    /// The pool failed to add the persisted transactions.
+    #[error(transparent)]
+    Pool(#[from] PoolError),
+}
+
+/// The transactions of the pool as persisted on disk, split by origin so local transactions keep
+/// their exemptions after a restart
+#[derive(Debug, Default, RlpEncodable, RlpDecodable)]
+struct PersistedTransactions {
+    local: Vec<TransactionSigned>,
+    external: Vec<TransactionSigned>,
+}
+
+/// Waits for the graceful shutdown of the node and then writes all pending and queued
+/// transactions of the pool to `path`, see [save_transactions].
+pub async fn persist_transactions_task<P>(shutdown: GracefulShutdown, pool: P, path: PathBuf)
+where
+    P: TransactionPool,
+{
+    let graceful_guard = shutdown.await;
+    match save_transactions(&pool, &path) {
+        Ok(count) => info!(target: "txpool", count, ?path, "Persisted transactions"),
+        Err(err) => warn!(target: "txpool", %err, ?path, "Failed to persist transactions"),
+    }
+    drop(graceful_guard)
+}
+
+/// Writes all pending and queued transactions of the pool to `path` and returns how many were
+/// written.
+///
+/// Blob transactions are skipped since their sidecars are not persisted.
+pub fn save_transactions<P>(pool: &P, path: &Path) -> Result<usize, TransactionsBackupError>
+where
+    P: TransactionPool,
+{
+    let AllPoolTransactions { pending, queued } = pool.all_transactions();
+    let mut persisted = PersistedTransactions::default();
+    for tx in pending.into_iter().chain(queued).filter(|tx| !tx.transaction.is_eip4844()) {
+        let signed = tx.to_recovered_transaction().into_signed();
+        if tx.origin.is_local() {
+            persisted.local.push(signed);
+        } else {
+            persisted.external.push(signed);
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, alloy_rlp::encode(&persisted))?;
+    Ok(persisted.local.len() + persisted.external.len())
+}
+
+/// Re-injects the transactions written by [save_transactions] to `path` into the pool and removes
+/// the file.
+///
+/// The transactions are validated like new ones, transactions that were mined or became invalid
+/// in the meantime are dropped. Returns how many transactions were added, nothing is added if
+/// there is no file.
+pub async fn load_persisted_transactions<P>(
+    pool: &P,
+    path: &Path,
+) -> Result<usize, TransactionsBackupError>
+where
+    P: TransactionPool,
+{
+    if !path.exists() {
+        return Ok(0)
+    }
+    let data = fs::read(path)?;
+    let persisted = PersistedTransactions::decode(&mut data.as_slice())?;
+    // the transactions must not be loaded again after the next restart
+    std::fs::remove_file(path).map_err(|err| FsPathError::remove_file(err, path))?;
+
+    let mut added = 0;
+    for (origin, transactions) in [
+        (TransactionOrigin::Local, persisted.local),
+        (TransactionOrigin::External, persisted.external),
+    ] {
+        let transactions = transactions
+            .into_iter()
+            .filter_map(TransactionSigned::into_ecrecovered)
+            .map(<P::Transaction as FromRecoveredTransaction>::from_recovered_transaction)
+            .collect();
+        let results = pool.add_transactions(origin, transactions).await?;
+        added += results.into_iter().filter(Result::is_ok).count();
+    }
+    Ok(added)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        blobstore::InMemoryBlobStore, validate::EthTransactionValidatorBuilder,
+        CoinbaseTipOrdering, EthPooledTransaction, Pool, PoolTransaction,
+    };
+    use reth_primitives::{
+        sign_message, Transaction, TransactionKind, TxEip1559, B256, MAINNET, U256,
+    };
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_tasks::TaskManager;
 
     #[test]
     fn changed_acc_entry() {
@@ -546,4 +659,61 @@ mod tests {
         copy.nonce = 10;
         assert!(changed_acc.eq(&ChangedAccountEntry(copy)));
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn restores_persisted_transactions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("txpool.rlp");
+        let provider = MockEthProvider::default();
+        let new_pool = || {
+            let blob_store = InMemoryBlobStore::default();
+            let validator = EthTransactionValidatorBuilder::new(MAINNET.clone())
+                .build(provider.clone(), blob_store.clone());
+            Pool::new(validator, CoinbaseTipOrdering::default(), blob_store, Default::default())
+        };
+        let secret = B256::random();
+        let sign = |nonce| {
+            let tx = Transaction::Eip1559(TxEip1559 {
+                chain_id: 1,
+                nonce,
+                gas_limit: 21_000,
+                max_fee_per_gas: 10_000_000_000,
+                max_priority_fee_per_gas: 1_000_000_000,
+                to: TransactionKind::Call(Address::random()),
+                ..Default::default()
+            });
+            let signature = sign_message(secret, tx.signature_hash()).unwrap();
+            let tx = TransactionSigned::from_transaction_and_signature(tx, signature);
+            EthPooledTransaction::from_recovered_transaction(tx.into_ecrecovered().unwrap())
+        };
+
+        // a pending local and a queued external transaction, after a nonce gap
+        let pool = new_pool();
+        let pending = sign(0);
+        let queued = sign(2);
+        provider.add_account(pending.sender(), ExtendedAccount::new(0, U256::MAX));
+        pool.add_transaction(TransactionOrigin::Local, pending.clone()).await.unwrap();
+        pool.add_external_transaction(queued.clone()).await.unwrap();
+
+        let manager = TaskManager::new(tokio::runtime::Handle::current());
+        manager
+            .executor()
+            .spawn_critical_with_graceful_shutdown_signal("txpool persistence", |shutdown| {
+                persist_transactions_task(shutdown, pool.clone(), path.clone())
+            });
+        tokio::task::spawn_blocking(move || manager.graceful_shutdown()).await.unwrap();
+        assert!(path.exists());
+
+        // the next run starts with an empty pool and loads the persisted transactions
+        let pool = new_pool();
+        assert_eq!(load_persisted_transactions(&pool, &path).await.unwrap(), 2);
+        let restored = pool.all_transactions();
+        assert_eq!(restored.pending.len(), 1);
+        assert_eq!(restored.pending[0].hash(), pending.hash());
+        assert!(restored.pending[0].origin.is_local());
+        assert_eq!(restored.queued.len(), 1);
+        assert_eq!(restored.queued[0].hash(), queued.hash());
+        assert!(!path.exists());
+        assert_eq!(load_persisted_transactions(&pool, &path).await.unwrap(), 0);
+    }
 }
@@ -7,6 +7,7 @@ use crate::{
     validate::{ValidTransaction, ValidationTask, MAX_INIT_CODE_SIZE, TX_MAX_SIZE},
     EthBlobTransactionSidecar, EthPoolTransaction, LocalTransactionConfig, PoolTransaction,
     TransactionValidationOutcome, TransactionValidationTaskExecutor, TransactionValidator,
+    DEFAULT_ADDITIONAL_VALIDATION_TASKS,
 };
 use reth_primitives::{
     constants::{
@@ -479,7 +480,7 @@ pub struct EthTransactionValidatorBuilder {
     minimum_priority_fee: Option<u128>,
     /// Determines how many additional tasks to spawn
     ///
-    /// Default is 1
+    /// Default is [DEFAULT_ADDITIONAL_VALIDATION_TASKS]
     additional_tasks: usize,
     /// Toggle to determine if a local transaction should be propagated
     propagate_local_transactions: bool,
@@ -500,7 +501,7 @@ impl EthTransactionValidatorBuilder {
             chain_spec,
             block_gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
             minimum_priority_fee: None,
-            additional_tasks: 1,
+            additional_tasks: DEFAULT_ADDITIONAL_VALIDATION_TASKS,
             // default to true, can potentially take this as a param in the future
             propagate_local_transactions: true,
             kzg_settings: Arc::clone(&MAINNET_KZG_TRUSTED_SETUP),
@@ -859,4 +860,72 @@ mod tests {
         };
         assert_eq!(err.to_string(), "transaction type not supported");
     }
+
+    #[cfg(not(feature = "optimism"))]
+    #[tokio::test]
+    async fn spawns_configured_validation_tasks() {
+        use crate::blobstore::InMemoryBlobStore;
+        use reth_primitives::MAINNET;
+        use reth_provider::test_utils::MockEthProvider;
+        use reth_tasks::TaskSpawner;
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+        use tokio::task::JoinHandle;
+
+        /// Counts the spawned tasks without running them
+        #[derive(Debug, Clone, Default)]
+        struct CountingSpawner {
+            blocking: Arc<AtomicUsize>,
+            critical_blocking: Arc<AtomicUsize>,
+        }
+
+        impl TaskSpawner for CountingSpawner {
+            fn spawn(&self, _fut: futures_util::future::BoxFuture<'static, ()>) -> JoinHandle<()> {
+                unreachable!("validation tasks are blocking")
+            }
+
+            fn spawn_critical(
+                &self,
+                _name: &'static str,
+                _fut: futures_util::future::BoxFuture<'static, ()>,
+            ) -> JoinHandle<()> {
+                unreachable!("validation tasks are blocking")
+            }
+
+            fn spawn_blocking(
+                &self,
+                _fut: futures_util::future::BoxFuture<'static, ()>,
+            ) -> JoinHandle<()> {
+                self.blocking.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async {})
+            }
+
+            fn spawn_critical_blocking(
+                &self,
+                _name: &'static str,
+                _fut: futures_util::future::BoxFuture<'static, ()>,
+            ) -> JoinHandle<()> {
+                self.critical_blocking.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async {})
+            }
+        }
+
+        for additional_tasks in [0, DEFAULT_ADDITIONAL_VALIDATION_TASKS, 8] {
+            let spawner = CountingSpawner::default();
+            let _validator: TransactionValidationTaskExecutor<
+                EthTransactionValidator<_, crate::EthPooledTransaction>,
+            > = EthTransactionValidatorBuilder::new(MAINNET.clone())
+                .with_additional_tasks(additional_tasks)
+                .build_with_tasks(
+                    MockEthProvider::default(),
+                    spawner.clone(),
+                    InMemoryBlobStore::default(),
+                );
+
+            assert_eq!(spawner.blocking.load(Ordering::SeqCst), additional_tasks);
+            assert_eq!(spawner.critical_blocking.load(Ordering::SeqCst), 1);
+        }
+    }
 }